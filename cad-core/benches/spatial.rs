@@ -0,0 +1,112 @@
+//! Выбор у курсора на чертеже из 100 000 сущностей: полный перебор с разбиением
+//! кривых против кандидатов из пространственного индекса.
+//!
+//! cargo bench -p cad-core --bench spatial --profile dev
+
+use cad_core::*;
+use std::time::Instant;
+
+const COUNT: usize = 100_000;
+const QUERIES: usize = 1_000;
+const REACH: f32 = 2.0;
+
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+fn drawing(rng: &mut Lcg) -> Document {
+    let mut doc = Document::new();
+    let size = (COUNT as f32).sqrt() * 20.0;
+    for i in 0..COUNT {
+        let c = Pt2::new(rng.next() * size, rng.next() * size);
+        let kind = match i % 4 {
+            0 => EntityKind::LineSeg {
+                a: c,
+                b: Pt2::new(c.x + rng.next() * 20.0, c.y + rng.next() * 20.0),
+            },
+            1 => EntityKind::Arc {
+                center: c,
+                radius: 1.0 + rng.next() * 5.0,
+                start_angle: rng.next() * 6.0,
+                end_angle: 6.0 + rng.next() * 3.0,
+            },
+            2 => EntityKind::Circle {
+                center: c,
+                radius: 1.0 + rng.next() * 5.0,
+            },
+            _ => EntityKind::NurbsCurve2D {
+                degree: 2,
+                knots: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+                ctrl_pts: vec![
+                    c,
+                    Pt2::new(c.x + 5.0, c.y + 10.0 * rng.next()),
+                    Pt2::new(c.x + 10.0, c.y),
+                ],
+                weights: None,
+            },
+        };
+        doc.add_entity(Entity::new("0", kind));
+    }
+    doc
+}
+
+/// Ближайшая к `p` сущность по ломаным — как при выборе мышью.
+fn pick<'a>(cands: impl Iterator<Item = &'a Entity>, p: Pt2) -> Option<u64> {
+    let mut best: Option<(u64, f32)> = None;
+    for e in cands {
+        for w in e.kind.sample(64).windows(2) {
+            let (ax, ay, bx, by) = (w[0].x, w[0].y, w[1].x, w[1].y);
+            let (vx, vy) = (bx - ax, by - ay);
+            let len2 = (vx * vx + vy * vy).max(1e-12);
+            let t = (((p.x - ax) * vx + (p.y - ay) * vy) / len2).clamp(0.0, 1.0);
+            let d = (ax + vx * t - p.x).hypot(ay + vy * t - p.y);
+            if d <= REACH && best.is_none_or(|b| d < b.1) {
+                best = Some((e.id, d));
+            }
+        }
+    }
+    best.map(|b| b.0)
+}
+
+fn main() {
+    let mut rng = Lcg(42);
+    let mut doc = drawing(&mut rng);
+    let size = (COUNT as f32).sqrt() * 20.0;
+    let points: Vec<Pt2> = (0..QUERIES)
+        .map(|_| Pt2::new(rng.next() * size, rng.next() * size))
+        .collect();
+
+    let t = Instant::now();
+    doc.refresh_index();
+    println!("index build, {COUNT} entities: {:?}", t.elapsed());
+
+    let t = Instant::now();
+    let mut indexed = Vec::with_capacity(QUERIES);
+    for &p in &points {
+        indexed.push(pick(doc.entities_near(p, REACH).into_iter(), p));
+    }
+    let per = t.elapsed() / QUERIES as u32;
+    println!("indexed pick: {per:?} per query");
+
+    // полный перебор медленный — меряем на части запросов
+    let n = 20;
+    let t = Instant::now();
+    for (i, &p) in points.iter().take(n).enumerate() {
+        assert_eq!(pick(doc.entities().iter(), p), indexed[i]);
+    }
+    println!("full scan pick: {:?} per query", t.elapsed() / n as u32);
+
+    let t = Instant::now();
+    let ids: Vec<u64> = doc.entities().iter().step_by(100).map(|e| e.id).collect();
+    move_entities(&mut doc, &ids, 1.0, 1.0);
+    doc.refresh_index();
+    println!("move {} entities + refresh: {:?}", ids.len(), t.elapsed());
+}
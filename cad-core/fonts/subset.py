@@ -0,0 +1,195 @@
+#!/usr/bin/env python3
+"""Урезанный DejaVu Sans для встраивания в PDF (cad-core/src/pdf.rs).
+
+    python3 subset.py DejaVuSans.ttf DejaVuSans-CAD.ttf
+
+DejaVuSans.ttf — из дистрибутива https://dejavu-fonts.github.io (в репозитории
+не хранится).
+
+Остаются латиница, греческий, кириллица, знаки пунктуации, математики и
+черчения (⌀, №, °, ±). Из таблиц — только нужные для набора и встраивания;
+cmap пересобирается (формат 4), номера глифов перенумеровываются подряд.
+"""
+
+import struct
+import sys
+
+RANGES = [
+    (0x0020, 0x017F),  # латиница
+    (0x0370, 0x03FF),  # греческий
+    (0x0400, 0x04FF),  # кириллица
+    (0x2000, 0x206F),  # пунктуация
+    (0x2070, 0x209F),  # индексы
+    (0x20A0, 0x20CF),  # валюты
+    (0x2100, 0x218F),  # буквоподобные, числовые формы
+    (0x2190, 0x21FF),  # стрелки
+    (0x2200, 0x22FF),  # математика
+    (0x2300, 0x2300),  # ⌀
+    (0x25A0, 0x25FF),  # геометрические фигуры
+]
+KEEP = [b"OS/2", b"cvt ", b"fpgm", b"head", b"hhea", b"maxp", b"name", b"prep"]
+
+
+def u16(d, o):
+    return struct.unpack_from(">H", d, o)[0]
+
+
+def u32(d, o):
+    return struct.unpack_from(">I", d, o)[0]
+
+
+def tables(data):
+    out = {}
+    for i in range(u16(data, 4)):
+        tag, _, off, ln = struct.unpack_from(">4sIII", data, 12 + 16 * i)
+        out[tag] = data[off : off + ln]
+    return out
+
+
+def cmap_format4(cmap):
+    for i in range(u16(cmap, 2)):
+        plat, enc, off = struct.unpack_from(">HHI", cmap, 4 + 8 * i)
+        if (plat, enc) == (3, 1) and u16(cmap, off) == 4:
+            t = cmap[off:]
+            break
+    segs = u16(t, 6) // 2
+    ends, starts, deltas, rngs = 14, 16 + 2 * segs, 16 + 4 * segs, 16 + 6 * segs
+    m = {}
+    for s in range(segs):
+        end, start = u16(t, ends + 2 * s), u16(t, starts + 2 * s)
+        delta, rng = u16(t, deltas + 2 * s), u16(t, rngs + 2 * s)
+        for c in range(start, min(end, 0xFFFE) + 1):
+            if rng == 0:
+                g = (c + delta) & 0xFFFF
+            else:
+                g = u16(t, rngs + 2 * s + rng + 2 * (c - start))
+                g = (g + delta) & 0xFFFF if g else 0
+            if g:
+                m[c] = g
+    return m
+
+
+def components(outline):
+    if len(outline) < 10 or struct.unpack_from(">h", outline, 0)[0] >= 0:
+        return []
+    out, o = [], 10
+    while True:
+        flags, g = struct.unpack_from(">HH", outline, o)
+        out.append((o + 2, g))
+        o += 4 + (4 if flags & 1 else 2)
+        o += 2 if flags & 8 else 4 if flags & 0x40 else 8 if flags & 0x80 else 0
+        if not flags & 0x20:
+            return out
+
+
+def build_cmap(chars):
+    codes = sorted(chars)
+    segs = []
+    for c in codes:
+        if segs and segs[-1][1] == c - 1 and chars[c] - c == chars[segs[-1][0]] - segs[-1][0]:
+            segs[-1][1] = c
+        else:
+            segs.append([c, c])
+    segs.append([0xFFFF, 0xFFFF])
+    n = len(segs)
+    ends = [e for _, e in segs]
+    starts = [s for s, _ in segs]
+    deltas = [(chars[s] - s) & 0xFFFF for s, _ in segs[:-1]] + [1]
+    search = 2 ** (n.bit_length() - 1)
+    body = struct.pack(">HHHH", 2 * n, 2 * search, search.bit_length() - 1, 2 * n - 2 * search)
+    body += struct.pack(f">{n}H", *ends) + b"\0\0" + struct.pack(f">{n}H", *starts)
+    body += struct.pack(f">{n}H", *deltas) + struct.pack(f">{n}H", *([0] * n))
+    sub = struct.pack(">HHH", 4, 6 + len(body), 0) + body
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + sub
+
+
+def checksum(d):
+    d = d + b"\0" * (-len(d) % 4)
+    return sum(struct.unpack(f">{len(d) // 4}I", d)) & 0xFFFFFFFF
+
+
+def write_sfnt(tabs):
+    tags = sorted(tabs)
+    n = len(tags)
+    search = 2 ** (n.bit_length() - 1)
+    head = struct.pack(">IHHHH", 0x00010000, n, 16 * search, search.bit_length() - 1, 16 * n - 16 * search)
+    off = 12 + 16 * n
+    dirs, body = b"", b""
+    for t in tags:
+        d = tabs[t]
+        dirs += struct.pack(">4sIII", t, checksum(d), off + len(body), len(d))
+        body += d + b"\0" * (-len(d) % 4)
+    font = bytearray(head + dirs + body)
+    at = 12 + 16 * tags.index(b"head")
+    hoff = u32(font, at + 8)
+    struct.pack_into(">I", font, hoff + 8, (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF)
+    return bytes(font)
+
+
+def main(src, dst):
+    data = open(src, "rb").read()
+    t = tables(data)
+    full = cmap_format4(t[b"cmap"])
+    long_loca = u16(t[b"head"], 50) == 1
+    nh = u16(t[b"hhea"], 34)
+
+    def outline(g):
+        if long_loca:
+            s, e = u32(t[b"loca"], 4 * g), u32(t[b"loca"], 4 * g + 4)
+        else:
+            s, e = 2 * u16(t[b"loca"], 2 * g), 2 * u16(t[b"loca"], 2 * g + 2)
+        return t[b"glyf"][s:e]
+
+    def metrics(g):
+        adv = u16(t[b"hmtx"], 4 * min(g, nh - 1))
+        lsb = u16(t[b"hmtx"], 4 * g + 2) if g < nh else u16(t[b"hmtx"], 4 * nh + 2 * (g - nh))
+        return adv, lsb
+
+    chars = {c: g for c, g in full.items() if any(a <= c <= b for a, b in RANGES)}
+    order = [0] + sorted(set(chars.values()))
+    index = {g: i for i, g in enumerate(order)}
+    i = 0
+    while i < len(order):
+        for _, c in components(outline(order[i])):
+            if c not in index:
+                index[c] = len(order)
+                order.append(c)
+        i += 1
+
+    glyf, loca, hmtx = bytearray(), b"", b""
+    for g in order:
+        loca += struct.pack(">I", len(glyf))
+        o = bytearray(outline(g))
+        for at, c in components(bytes(o)):
+            struct.pack_into(">H", o, at, index[c])
+        glyf += o + b"\0" * (-len(o) % 4)
+        hmtx += struct.pack(">HH", *metrics(g))
+    loca += struct.pack(">I", len(glyf))
+
+    n = len(order)
+    out = {tag: t[tag] for tag in KEEP if tag in t}
+    head = bytearray(t[b"head"])
+    struct.pack_into(">I", head, 8, 0)
+    struct.pack_into(">H", head, 50, 1)
+    hhea = bytearray(t[b"hhea"])
+    struct.pack_into(">H", hhea, 34, n)
+    maxp = bytearray(t[b"maxp"])
+    struct.pack_into(">H", maxp, 4, n)
+    post = bytearray(t[b"post"][:32])
+    struct.pack_into(">I", post, 0, 0x00030000)
+    out.update({
+        b"head": bytes(head),
+        b"hhea": bytes(hhea),
+        b"maxp": bytes(maxp),
+        b"post": bytes(post),
+        b"glyf": bytes(glyf),
+        b"loca": loca,
+        b"hmtx": hmtx,
+        b"cmap": build_cmap({c: index[g] for c, g in chars.items()}),
+    })
+    open(dst, "wb").write(write_sfnt(out))
+    print(f"{len(chars)} символов, {n} глифов, {len(write_sfnt(out))} байт")
+
+
+if __name__ == "__main__":
+    main(*sys.argv[1:3])
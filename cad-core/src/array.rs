@@ -0,0 +1,129 @@
+//! Массивы: копии набора сущностей по сетке, по окружности и вдоль кривой.
+//! Исходный набор остаётся первым элементом массива; функции возвращают id копий.
+
+use crate::hatch::curve_points;
+use crate::{copy_entities, is_closed_curve, Affine2, Document, Pt2};
+use anyhow::{anyhow, Result};
+use std::f32::consts::TAU;
+
+/// Прямоугольный массив `rows`×`cols` с шагом `spacing` (x — между столбцами,
+/// y — между рядами), сетка повёрнута на `angle` радиан.
+pub fn array_rect(
+    doc: &mut Document,
+    ids: &[u64],
+    rows: usize,
+    cols: usize,
+    spacing: Pt2,
+    angle: f32,
+) -> Vec<u64> {
+    let (s, c) = angle.sin_cos();
+    let mut out = Vec::new();
+    for r in 0..rows {
+        for k in 0..cols {
+            if r == 0 && k == 0 {
+                continue;
+            }
+            let (x, y) = (k as f32 * spacing.x, r as f32 * spacing.y);
+            let tr = Affine2::translation(x * c - y * s, x * s + y * c);
+            out.extend(copy_entities(doc, ids, &tr));
+        }
+    }
+    out
+}
+
+/// Круговой массив из `count` элементов вокруг `center` на угол `fill` (полный оборот —
+/// элементы не повторяются на стыке). Без `rotate_items` копии только сдвигаются: точка
+/// `base` набора обходит окружность.
+pub fn array_polar(
+    doc: &mut Document,
+    ids: &[u64],
+    center: Pt2,
+    base: Pt2,
+    count: usize,
+    fill: f32,
+    rotate_items: bool,
+) -> Vec<u64> {
+    if count < 2 {
+        return vec![];
+    }
+    let step = if fill.abs() >= TAU - 1e-4 {
+        fill / count as f32
+    } else {
+        fill / (count - 1) as f32
+    };
+    let mut out = Vec::new();
+    for i in 1..count {
+        let rot = Affine2::rotation(center, step * i as f32);
+        let tr = if rotate_items {
+            rot
+        } else {
+            let p = rot.apply(base);
+            Affine2::translation(p.x - base.x, p.y - base.y)
+        };
+        out.extend(copy_entities(doc, ids, &tr));
+    }
+    out
+}
+
+/// Массив вдоль кривой `path`: `count` элементов равномерно по её длине (на замкнутой
+/// кривой без повтора на стыке). Набор повторяет форму кривой начиная с исходного
+/// положения: копия `i` сдвинута на разность точек кривой `i` и начала. С `align`
+/// копии ещё поворачиваются вокруг сдвинутой точки `base` по касательной относительно
+/// направления в начале кривой.
+pub fn array_path(
+    doc: &mut Document,
+    ids: &[u64],
+    path: u64,
+    base: Pt2,
+    count: usize,
+    align: bool,
+) -> Result<Vec<u64>> {
+    let kind = &doc
+        .entity(path)
+        .ok_or_else(|| anyhow!("Entity {path} not found"))?
+        .kind;
+    let pts = curve_points(kind);
+    let lens: Vec<f32> = pts
+        .windows(2)
+        .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+        .collect();
+    let total: f32 = lens.iter().sum();
+    if total <= 0.0 {
+        return Err(anyhow!("Entity {path} is not a curve"));
+    }
+    if count < 2 {
+        return Ok(vec![]);
+    }
+    let closed = is_closed_curve(kind);
+    let step = total / if closed { count } else { count - 1 } as f32;
+
+    // точка и направление на длине s
+    let at = |s: f32| {
+        let mut rest = s.clamp(0.0, total);
+        let mut last = (pts[0], 0.0);
+        for (i, &l) in lens.iter().enumerate().filter(|(_, &l)| l > 0.0) {
+            let (a, b) = (pts[i], pts[i + 1]);
+            let f = (rest / l).min(1.0);
+            last = (
+                Pt2::new(a.x + (b.x - a.x) * f, a.y + (b.y - a.y) * f),
+                (b.y - a.y).atan2(b.x - a.x),
+            );
+            if rest <= l {
+                break;
+            }
+            rest -= l;
+        }
+        last
+    };
+    let (p0, a0) = at(0.0);
+    let mut out = Vec::new();
+    for i in 1..count {
+        let (p, a) = at(step * i as f32);
+        let mut tr = Affine2::translation(p.x - p0.x, p.y - p0.y);
+        if align {
+            tr = tr.then(&Affine2::rotation(tr.apply(base), a - a0));
+        }
+        out.extend(copy_entities(doc, ids, &tr));
+    }
+    Ok(out)
+}
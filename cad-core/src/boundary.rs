@@ -0,0 +1,65 @@
+//! Замкнутые контуры из разрозненных кривых: импортированный DXF чаще даёт профиль
+//! отдельными LINE и ARC, а не замкнутой полилинией. Строится плоский граф с привязкой
+//! концов по допуску, из него — все минимальные грани с вложенностью.
+
+use crate::hatch::{curve_points, point_in_polygon, PlanarGraph};
+use crate::{Document, EntityKind, Region};
+use std::collections::HashSet;
+
+/// Допуск привязки концов по умолчанию, мм.
+pub const LOOP_TOL: f32 = 0.01;
+
+/// Все минимальные замкнутые грани, ограниченные кривыми `kinds`. Отверстия грани —
+/// внешние границы не связанной с ней геометрии, лежащей прямо в ней; сами острова
+/// дают свои грани. Концы ближе `tol` считаются совпадающими. Граф строится по
+/// ломаным, как у штриховки; рёбра на дугах сохраняют свои окружности.
+pub fn find_loops(kinds: &[EntityKind], tol: f32) -> Vec<Region> {
+    let polylines: Vec<_> = kinds.iter().map(curve_points).collect();
+    let cycles = PlanarGraph::build(&polylines, tol.max(0.0) as f64).cycles();
+
+    let faces: Vec<usize> = (0..cycles.len())
+        .filter(|&i| cycles[i].area > 0.0)
+        .collect();
+    let mut holes = vec![Vec::new(); faces.len()];
+    for c in cycles.iter().filter(|c| c.area < 0.0) {
+        // хозяин острова — наименьшая грань чужой компоненты вокруг него
+        let owner = faces
+            .iter()
+            .enumerate()
+            .filter(|(_, &f)| {
+                cycles[f].comp != c.comp && point_in_polygon(c.pts[0], &cycles[f].pts)
+            })
+            .min_by(|a, b| cycles[*a.1].area.total_cmp(&cycles[*b.1].area));
+        if let Some((k, _)) = owner {
+            holes[k].push(c.pts.clone());
+        }
+    }
+    faces
+        .iter()
+        .zip(holes)
+        .filter_map(|(&f, h)| Region::traced(cycles[f].pts.clone(), h, kinds, tol))
+        .collect()
+}
+
+/// [`find_loops`] по выбранным сущностям документа; вставки блоков раскрываются,
+/// невидимые слои пропускаются.
+pub fn find_loops_in(doc: &Document, ids: &[u64], tol: f32) -> Vec<Region> {
+    let ids: HashSet<u64> = ids.iter().copied().collect();
+    let mut kinds = Vec::new();
+    for e in doc.entities.iter().filter(|e| ids.contains(&e.id)) {
+        if !doc.is_layer_visible(&e.layer) {
+            continue;
+        }
+        if let EntityKind::Insert { .. } = e.kind {
+            kinds.extend(
+                doc.insert_geometry(e)
+                    .into_iter()
+                    .filter(|s| doc.is_layer_visible(&s.layer))
+                    .map(|s| s.kind),
+            );
+        } else {
+            kinds.push(e.kind.clone());
+        }
+    }
+    find_loops(&kinds, tol)
+}
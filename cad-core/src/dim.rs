@@ -0,0 +1,598 @@
+//! Размеры: ассоциативные точки, размерные стили и геометрия размерного блока.
+
+use crate::{ellipse_point, Document, Entity, EntityKind, HatchFill, HatchLoop, Pt2};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::f32::consts::{PI, TAU};
+
+// --------------------------- привязки ---------------------------
+
+/// Характерная точка сущности, к которой привязан размер.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DimSnap {
+    Start,
+    End,
+    Mid,
+    Center,
+    /// Вершина полилинии
+    Vertex(usize),
+    /// Точка окружности/дуги под углом (рад) или эллипса по параметру
+    OnCurve(f32),
+}
+
+/// Ссылка на точку сущности по id.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct DimLink {
+    pub entity: u64,
+    pub snap: DimSnap,
+}
+
+/// Определяющая точка размера. Если есть `link`, `pos` пересчитывается
+/// из геометрии (`update_dimensions`), иначе точка свободная.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct DimPoint {
+    pub pos: Pt2,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<DimLink>,
+}
+
+impl DimPoint {
+    pub fn free(pos: Pt2) -> Self {
+        Self { pos, link: None }
+    }
+
+    /// Точка `snap` сущности `entity`, привязанная к ней.
+    pub fn on(doc: &Document, entity: u64, snap: DimSnap) -> Result<Self> {
+        let e = doc
+            .entity(entity)
+            .ok_or_else(|| anyhow!("Entity {entity} not found"))?;
+        let pos = snap_point(&e.kind, snap)
+            .ok_or_else(|| anyhow!("Entity {entity} has no {snap:?} point"))?;
+        Ok(Self {
+            pos,
+            link: Some(DimLink { entity, snap }),
+        })
+    }
+
+    /// Ближайшая к `p` характерная точка сущности (конец, середина, центр, вершина),
+    /// не дальше `tol`.
+    pub fn nearest(doc: &Document, entity: u64, p: Pt2, tol: f32) -> Option<Self> {
+        let e = doc.entity(entity)?;
+        let mut snaps = vec![DimSnap::Start, DimSnap::End, DimSnap::Mid, DimSnap::Center];
+        if let EntityKind::Polyline { pts, .. } = &e.kind {
+            snaps.extend((0..pts.len()).map(DimSnap::Vertex));
+        }
+        snaps
+            .into_iter()
+            .filter_map(|s| Some((s, snap_point(&e.kind, s)?)))
+            .map(|(s, q)| (s, dist(p, q)))
+            .filter(|(_, d)| *d <= tol)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(snap, _)| Self {
+                pos: snap_point(&e.kind, snap).unwrap_or(p),
+                link: Some(DimLink { entity, snap }),
+            })
+    }
+}
+
+/// Положение характерной точки на сущности; `None`, если у сущности такой нет.
+pub fn snap_point(kind: &EntityKind, snap: DimSnap) -> Option<Pt2> {
+    let on_circle = |c: Pt2, r: f32, a: f32| Pt2::new(c.x + r * a.cos(), c.y + r * a.sin());
+    match (kind, snap) {
+        (EntityKind::LineSeg { a, .. }, DimSnap::Start) => Some(*a),
+        (EntityKind::LineSeg { b, .. }, DimSnap::End) => Some(*b),
+        (EntityKind::LineSeg { a, b }, DimSnap::Mid) => Some(lerp(*a, *b, 0.5)),
+        (
+            EntityKind::Arc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            },
+            s,
+        ) => match s {
+            DimSnap::Start => Some(on_circle(*center, *radius, *start_angle)),
+            DimSnap::End => Some(on_circle(*center, *radius, *end_angle)),
+            DimSnap::Mid => Some(on_circle(*center, *radius, (start_angle + end_angle) * 0.5)),
+            DimSnap::Center => Some(*center),
+            DimSnap::OnCurve(a) => Some(on_circle(*center, *radius, a)),
+            DimSnap::Vertex(_) => None,
+        },
+        (EntityKind::Circle { center, .. }, DimSnap::Center) => Some(*center),
+        (EntityKind::Circle { center, radius }, DimSnap::OnCurve(a)) => {
+            Some(on_circle(*center, *radius, a))
+        }
+        (
+            EntityKind::Ellipse {
+                center,
+                major,
+                ratio,
+                start_param,
+                end_param,
+            },
+            s,
+        ) => {
+            let at = |t: f32| ellipse_point(*center, *major, *ratio, t);
+            match s {
+                DimSnap::Center => Some(*center),
+                DimSnap::Start => Some(at(*start_param)),
+                DimSnap::End => Some(at(*end_param)),
+                DimSnap::OnCurve(t) => Some(at(t)),
+                DimSnap::Mid | DimSnap::Vertex(_) => None,
+            }
+        }
+        (EntityKind::Polyline { pts, .. }, DimSnap::Start) => pts.first().copied(),
+        (EntityKind::Polyline { pts, .. }, DimSnap::End) => pts.last().copied(),
+        (EntityKind::Polyline { pts, .. }, DimSnap::Vertex(i)) => pts.get(i).copied(),
+        (EntityKind::NurbsCurve2D { ctrl_pts, .. }, DimSnap::Start) => ctrl_pts.first().copied(),
+        (EntityKind::NurbsCurve2D { ctrl_pts, .. }, DimSnap::End) => ctrl_pts.last().copied(),
+        (EntityKind::Text { pos, .. } | EntityKind::Insert { pos, .. }, DimSnap::Start) => {
+            Some(*pos)
+        }
+        _ => None,
+    }
+}
+
+/// Пересчитать привязанные точки размеров по текущей геометрии. Если все точки размера
+/// сдвинулись одинаково, размерная линия едет вместе с ними. Привязки к удалённым
+/// сущностям снимаются, точки остаются на месте.
+pub fn update_dimensions(doc: &mut Document) {
+    relink_dimensions(doc, None, &[]);
+}
+
+/// `update_dimensions` только для размеров из `changed` или привязанных к ним
+/// (`None` — все). У размеров из `keep_line` размерная линия остаётся на месте (их
+/// сдвинули отдельно от объектов). Меняются лишь размеры, у которых что-то сдвинулось.
+pub(crate) fn relink_dimensions(doc: &mut Document, changed: Option<&[u64]>, keep_line: &[u64]) {
+    let changed: Option<HashSet<u64>> = changed.map(|c| c.iter().copied().collect());
+    let touches = |id: u64, pts: &[DimPoint]| match &changed {
+        None => true,
+        Some(c) => {
+            c.contains(&id)
+                || pts
+                    .iter()
+                    .any(|p| p.link.is_some_and(|l| c.contains(&l.entity)))
+        }
+    };
+    let updates: Vec<(u64, Vec<DimPoint>, Pt2)> = doc
+        .entities
+        .iter()
+        .filter_map(|e| match &e.kind {
+            EntityKind::Dimension { pts, line_pos, .. } if touches(e.id, pts) => {
+                let keep = keep_line.contains(&e.id);
+                let (new_pts, new_line) = relinked(doc, pts, *line_pos, keep);
+                (new_pts != *pts || new_line != *line_pos).then_some((e.id, new_pts, new_line))
+            }
+            _ => None,
+        })
+        .collect();
+    for (id, new_pts, new_line) in updates {
+        if let Some(EntityKind::Dimension { pts, line_pos, .. }) =
+            doc.entity_mut(id).map(|e| &mut e.kind)
+        {
+            *pts = new_pts;
+            *line_pos = new_line;
+        }
+    }
+}
+
+/// Точки размера по текущей геометрии и сдвинутое положение размерной линии.
+fn relinked(doc: &Document, pts: &[DimPoint], line_pos: Pt2, keep: bool) -> (Vec<DimPoint>, Pt2) {
+    let mut pts = pts.to_vec();
+    let mut shift: Option<Pt2> = None;
+    let mut rigid = true;
+    for p in &mut pts {
+        let moved = p
+            .link
+            .and_then(|l| snap_point(&doc.entity(l.entity)?.kind, l.snap));
+        match moved {
+            Some(q) => {
+                let d = sub(q, p.pos);
+                match shift {
+                    None => shift = Some(d),
+                    Some(s) => rigid &= dist(s, d) <= 1e-5 * (1.0 + s.x.abs() + s.y.abs()),
+                }
+                p.pos = q;
+            }
+            None => {
+                rigid &= p.link.is_none();
+                p.link = None;
+            }
+        }
+    }
+    match (rigid, keep, shift) {
+        (true, false, Some(s)) => (pts, add(line_pos, s)),
+        _ => (pts, line_pos),
+    }
+}
+
+// --------------------------- виды и стили ---------------------------
+
+/// Вид размера и смысл его определяющих точек.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DimKind {
+    /// Проекция отрезка [p1, p2] на направление `angle` (рад): 0 — горизонтальный,
+    /// π/2 — вертикальный
+    Linear { angle: f32 },
+    /// Параллельный отрезку [p1, p2]
+    Aligned,
+    /// Угол по трём точкам: [вершина, точка на первой стороне, на второй]
+    Angular,
+    /// [центр, точка на окружности]
+    Radius,
+    /// [центр, точка на окружности]
+    Diameter,
+    /// [начало отсчёта, точка]; `x` — координата X, иначе Y
+    Ordinate { x: bool },
+}
+
+impl DimKind {
+    /// Сколько определяющих точек нужно.
+    pub fn point_count(&self) -> usize {
+        match self {
+            DimKind::Angular => 3,
+            _ => 2,
+        }
+    }
+}
+
+/// Стрелка на концах размерной линии (DIMBLK).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum DimArrow {
+    #[default]
+    ClosedFilled,
+    Open,
+    /// Засечка под 45°, как в строительных чертежах
+    Tick,
+    Dot,
+}
+
+/// Размерный стиль (DIMSTYLE); размеры в единицах чертежа.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DimStyle {
+    pub name: String,
+    pub arrow: DimArrow,
+    /// DIMASZ
+    pub arrow_size: f32,
+    /// DIMTXT
+    pub text_height: f32,
+    /// DIMGAP — зазор между текстом и размерной линией
+    pub text_gap: f32,
+    /// DIMEXO — отступ выносной линии от объекта
+    pub ext_offset: f32,
+    /// DIMEXE — выход выносной линии за размерную
+    pub ext_extend: f32,
+    /// DIMDEC — знаков после запятой
+    pub precision: u8,
+    /// DIMPOST — суффикс единиц, например " мм"
+    #[serde(default)]
+    pub suffix: String,
+}
+
+impl DimStyle {
+    pub const STANDARD: &'static str = "Standard";
+}
+
+impl Default for DimStyle {
+    /// Как ISO-25.
+    fn default() -> Self {
+        Self {
+            name: Self::STANDARD.into(),
+            arrow: DimArrow::ClosedFilled,
+            arrow_size: 2.5,
+            text_height: 2.5,
+            text_gap: 0.625,
+            ext_offset: 0.625,
+            ext_extend: 1.25,
+            precision: 2,
+            suffix: String::new(),
+        }
+    }
+}
+
+// --------------------------- измерение и текст ---------------------------
+
+/// Измеренное значение: длина в единицах чертежа, угол — в радианах.
+pub fn dim_measure(kind: &DimKind, pts: &[Pt2]) -> Option<f32> {
+    if pts.len() < kind.point_count() {
+        return None;
+    }
+    Some(match kind {
+        DimKind::Linear { angle } => dot(sub(pts[1], pts[0]), dir(*angle)).abs(),
+        DimKind::Aligned | DimKind::Radius => dist(pts[0], pts[1]),
+        DimKind::Diameter => 2.0 * dist(pts[0], pts[1]),
+        DimKind::Angular => {
+            let (u, v) = (sub(pts[1], pts[0]), sub(pts[2], pts[0]));
+            (u.x * v.y - u.y * v.x).atan2(dot(u, v)).abs()
+        }
+        DimKind::Ordinate { x: true } => (pts[1].x - pts[0].x).abs(),
+        DimKind::Ordinate { x: false } => (pts[1].y - pts[0].y).abs(),
+    })
+}
+
+/// Текст размера: пустое `text_override` — измеренное значение, "<>" в нём
+/// заменяется измеренным.
+pub fn dim_text(kind: &DimKind, value: f32, style: &DimStyle, text_override: &str) -> String {
+    let prec = style.precision as usize;
+    let measured = match kind {
+        DimKind::Angular => format!("{:.*}°", prec, value.to_degrees()),
+        DimKind::Radius => format!("R{:.*}{}", prec, value, style.suffix),
+        DimKind::Diameter => format!("⌀{:.*}{}", prec, value, style.suffix),
+        _ => format!("{:.*}{}", prec, value, style.suffix),
+    };
+    if text_override.is_empty() {
+        measured
+    } else {
+        text_override.replace("<>", &measured)
+    }
+}
+
+// --------------------------- геометрия ---------------------------
+
+/// Примитивы размерного блока в мировых координатах: выносные и размерные линии,
+/// стрелки (сплошные штриховки) и текст.
+pub fn dim_geometry(
+    kind: &DimKind,
+    pts: &[Pt2],
+    line_pos: Pt2,
+    text_override: &str,
+    style: &DimStyle,
+) -> Vec<EntityKind> {
+    let Some(value) = dim_measure(kind, pts) else {
+        return vec![];
+    };
+    let text = dim_text(kind, value, style, text_override);
+    let mut out = Vec::new();
+    let line = |out: &mut Vec<EntityKind>, a: Pt2, b: Pt2| {
+        if dist(a, b) > 1e-9 {
+            out.push(EntityKind::LineSeg { a, b });
+        }
+    };
+
+    match kind {
+        DimKind::Linear { .. } | DimKind::Aligned => {
+            let d = match kind {
+                DimKind::Linear { angle } => dir(*angle),
+                _ => match norm(sub(pts[1], pts[0])) {
+                    Some(d) => d,
+                    None => return vec![],
+                },
+            };
+            let n = perp(d);
+            // точки на размерной линии и выносные линии к ним
+            let q: Vec<Pt2> = pts[..2]
+                .iter()
+                .map(|&p| add(p, scale(n, dot(sub(line_pos, p), n))))
+                .collect();
+            for (&p, &qi) in pts.iter().zip(&q) {
+                if let Some(u) = norm(sub(qi, p)) {
+                    line(
+                        &mut out,
+                        add(p, scale(u, style.ext_offset)),
+                        add(qi, scale(u, style.ext_extend)),
+                    );
+                }
+            }
+            line(&mut out, q[0], q[1]);
+            if let Some(u) = norm(sub(q[1], q[0])) {
+                out.extend(arrow(q[1], u, style));
+                out.extend(arrow(q[0], scale(u, -1.0), style));
+            }
+            // текст над линией — со стороны от объекта
+            let side = if dot(sub(line_pos, pts[0]), n) < 0.0 {
+                scale(n, -1.0)
+            } else {
+                n
+            };
+            let mid = lerp(q[0], q[1], 0.5);
+            out.push(text_at(offset_text(mid, side, &text, style), &text, style));
+        }
+        DimKind::Angular => {
+            let v = pts[0];
+            let r = dist(line_pos, v);
+            let (a1, a2) = (angle_of(sub(pts[1], v)), angle_of(sub(pts[2], v)));
+            // меньший из двух углов между сторонами
+            let sweep = (a2 - a1).rem_euclid(TAU);
+            let (s, e) = if sweep <= PI {
+                (a1, a1 + sweep)
+            } else {
+                (a2, a2 + TAU - sweep)
+            };
+            if r <= 1e-9 {
+                return vec![];
+            }
+            for &p in &pts[1..] {
+                let u = match norm(sub(p, v)) {
+                    Some(u) => u,
+                    None => continue,
+                };
+                let lp = dist(p, v);
+                if lp + style.ext_offset < r {
+                    line(
+                        &mut out,
+                        add(p, scale(u, style.ext_offset)),
+                        add(v, scale(u, r + style.ext_extend)),
+                    );
+                }
+            }
+            out.push(EntityKind::Arc {
+                center: v,
+                radius: r,
+                start_angle: s,
+                end_angle: e,
+            });
+            let at = |a: f32| add(v, scale(dir(a), r));
+            // стрелки по касательной к дуге
+            out.extend(arrow(at(e), dir(e + PI / 2.0), style));
+            out.extend(arrow(at(s), dir(s - PI / 2.0), style));
+            let m = (s + e) * 0.5;
+            out.push(text_at(
+                offset_text(at(m), dir(m), &text, style),
+                &text,
+                style,
+            ));
+        }
+        DimKind::Radius | DimKind::Diameter => {
+            let (c, p) = (pts[0], pts[1]);
+            let r = dist(c, p);
+            let Some(u) = norm(sub(p, c)) else {
+                return vec![];
+            };
+            let start = if matches!(kind, DimKind::Diameter) {
+                sub(c, scale(u, r))
+            } else {
+                c
+            };
+            line(&mut out, start, p);
+            out.extend(arrow(p, u, style));
+            if matches!(kind, DimKind::Diameter) {
+                out.extend(arrow(start, scale(u, -1.0), style));
+            }
+            // текст снаружи — полка от точки на окружности
+            let lr = dot(sub(line_pos, c), u);
+            if lr > r {
+                line(&mut out, p, add(c, scale(u, lr)));
+            }
+            out.push(text_at(line_pos, &text, style));
+        }
+        DimKind::Ordinate { x } => {
+            let p = pts[1];
+            let (along, end) = if *x {
+                (Pt2::new(0.0, 1.0), Pt2::new(p.x, line_pos.y))
+            } else {
+                (Pt2::new(1.0, 0.0), Pt2::new(line_pos.x, p.y))
+            };
+            let sgn = if dot(sub(end, p), along) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            let u = scale(along, sgn);
+            line(&mut out, add(p, scale(u, style.ext_offset)), end);
+            out.push(text_at(offset_text(end, u, &text, style), &text, style));
+        }
+    }
+    out
+}
+
+/// Размер развёрнутый в сущности с его слоем и свойствами.
+pub fn dimension_entities(doc: &Document, ent: &Entity) -> Vec<Entity> {
+    let EntityKind::Dimension {
+        kind,
+        pts,
+        line_pos,
+        text,
+        style,
+    } = &ent.kind
+    else {
+        return vec![];
+    };
+    let style = doc.dim_style(style);
+    let pts: Vec<Pt2> = pts.iter().map(|p| p.pos).collect();
+    dim_geometry(kind, &pts, *line_pos, text, &style)
+        .into_iter()
+        .map(|k| Entity {
+            color: ent.color,
+            linetype: ent.linetype.clone(),
+            lineweight: ent.lineweight,
+            ..Entity::new(ent.layer.clone(), k)
+        })
+        .collect()
+}
+
+/// Стрелка с остриём в `tip`, `u` — направление, куда она смотрит.
+fn arrow(tip: Pt2, u: Pt2, style: &DimStyle) -> Vec<EntityKind> {
+    let s = style.arrow_size;
+    let (back, n) = (sub(tip, scale(u, s)), perp(u));
+    match style.arrow {
+        DimArrow::ClosedFilled => vec![EntityKind::Hatch {
+            loops: vec![HatchLoop::polyline(
+                vec![
+                    tip,
+                    add(back, scale(n, s / 6.0)),
+                    sub(back, scale(n, s / 6.0)),
+                ],
+                vec![],
+            )],
+            fill: HatchFill::Solid,
+        }],
+        DimArrow::Open => vec![
+            EntityKind::LineSeg {
+                a: add(back, scale(n, s / 6.0)),
+                b: tip,
+            },
+            EntityKind::LineSeg {
+                a: tip,
+                b: sub(back, scale(n, s / 6.0)),
+            },
+        ],
+        DimArrow::Tick => {
+            let t = scale(add(u, n), s * 0.5 / 2f32.sqrt());
+            vec![EntityKind::LineSeg {
+                a: sub(tip, t),
+                b: add(tip, t),
+            }]
+        }
+        DimArrow::Dot => vec![EntityKind::Hatch {
+            loops: vec![HatchLoop {
+                edges: vec![EntityKind::Circle {
+                    center: tip,
+                    radius: s / 4.0,
+                }],
+            }],
+            fill: HatchFill::Solid,
+        }],
+    }
+}
+
+fn text_at(pos: Pt2, text: &str, style: &DimStyle) -> EntityKind {
+    EntityKind::Text {
+        pos,
+        content: text.to_owned(),
+        height: style.text_height,
+    }
+}
+
+/// Центр горизонтального текста, отодвинутого от точки `p` в сторону `side` на зазор.
+fn offset_text(p: Pt2, side: Pt2, text: &str, style: &DimStyle) -> Pt2 {
+    // ширина текста — примерно 0.6 высоты на символ
+    let half_w = 0.3 * style.text_height * text.chars().count() as f32;
+    let half_h = 0.5 * style.text_height;
+    let reach = style.text_gap + (half_w * side.x).abs() + (half_h * side.y).abs();
+    add(p, scale(side, reach))
+}
+
+// --------------------------- векторы ---------------------------
+
+fn add(a: Pt2, b: Pt2) -> Pt2 {
+    Pt2::new(a.x + b.x, a.y + b.y)
+}
+fn sub(a: Pt2, b: Pt2) -> Pt2 {
+    Pt2::new(a.x - b.x, a.y - b.y)
+}
+fn scale(a: Pt2, k: f32) -> Pt2 {
+    Pt2::new(a.x * k, a.y * k)
+}
+fn dot(a: Pt2, b: Pt2) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+fn dist(a: Pt2, b: Pt2) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+fn lerp(a: Pt2, b: Pt2, t: f32) -> Pt2 {
+    Pt2::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+fn dir(a: f32) -> Pt2 {
+    Pt2::new(a.cos(), a.sin())
+}
+fn perp(a: Pt2) -> Pt2 {
+    Pt2::new(-a.y, a.x)
+}
+fn angle_of(a: Pt2) -> f32 {
+    a.y.atan2(a.x)
+}
+fn norm(a: Pt2) -> Option<Pt2> {
+    let l = a.x.hypot(a.y);
+    (l > 1e-9).then(|| scale(a, 1.0 / l))
+}
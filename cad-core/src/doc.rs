@@ -1,4 +1,10 @@
-use crate::{Entity, EntityKind, Layer, Pt2, Style};
+use crate::history::Journal;
+use crate::spatial::SpatialIndex;
+use crate::{
+    bulge_arc, dimension_entities, ellipse_point, table_entities, Affine2, Bbox, BlockDef, Color,
+    DimStyle, Entity, EntityKind, EntityStore, HatchFill, HatchLine, Layer, LineWeight,
+    LinetypeDef, Pt2, Style, LINETYPE_BYBLOCK,
+};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fmt::Write as _;
@@ -7,6 +13,19 @@ use std::fmt::Write as _;
 use cgmath::Point2;
 use cryxtal_geometry::prelude::*; // BSplineCurve, KnotVec, трейты
 
+/// Предел вложенности блоков при развёртке вставок.
+const MAX_BLOCK_DEPTH: usize = 16;
+
+/// Свойства сущности с раскрытыми ПоСлою/ПоБлоку.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityStyle<'a> {
+    /// Всегда явный цвет (ACI или RGB)
+    pub color: Color,
+    /// Шаблон типа линии; пусто — сплошная
+    pub pattern: &'a [f32],
+    pub weight_mm: f32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct Grid {
     pub step: f32,
@@ -30,30 +49,42 @@ impl Default for Camera2D {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Document {
     pub layers: Vec<Layer>,
-    pub entities: Vec<Entity>,
+    /// Меняется только через методы документа — их видят история и индекс
+    pub(crate) entities: EntityStore,
+    /// Меняется через [`Document::add_block`] — от блоков зависят рамки вставок
+    #[serde(default)]
+    pub(crate) blocks: Vec<BlockDef>,
+    #[serde(default)]
+    pub linetypes: Vec<LinetypeDef>,
     pub style: Style,
     pub grid: Grid,
     pub camera: Camera2D,
+    #[serde(default)]
+    pub dim_styles: Vec<DimStyle>,
     #[serde(skip)]
-    next_id: u64,
+    pub(crate) index: SpatialIndex,
+    /// Журнал открытой транзакции истории
+    #[serde(skip)]
+    pub(crate) journal: Option<Journal>,
 }
 
 impl Default for Document {
     fn default() -> Self {
+        let entities = EntityStore::new();
         Self {
-            layers: vec![Layer {
-                name: "0".into(),
-                visible: true,
-                locked: false,
-            }],
-            entities: vec![],
+            layers: vec![Layer::new("0")],
+            index: SpatialIndex::new(entities.generation()),
+            entities,
+            blocks: vec![],
+            linetypes: vec![],
             style: Style::default(),
             grid: Grid {
                 step: 10.0,
                 show: true,
             },
             camera: Camera2D::default(),
-            next_id: 1,
+            dim_styles: vec![],
+            journal: None,
         }
     }
 }
@@ -63,21 +94,302 @@ impl Document {
         Self::default()
     }
 
-    pub fn add_entity(&mut self, mut e: Entity) -> u64 {
-        e.id = self.next_id;
-        self.next_id += 1;
-        self.entities.push(e);
-        self.next_id - 1
+    pub fn add_entity(&mut self, e: Entity) -> u64 {
+        let before = self.entities.generation();
+        let id = self.entities.push(e);
+        self.index.invalidate(id);
+        self.index.follow(before);
+        if let Some(j) = &mut self.journal {
+            j.added(id);
+        }
+        id
     }
     pub fn remove_entity(&mut self, id: u64) -> bool {
-        if let Some(i) = self.entities.iter().position(|e| e.id == id) {
-            self.entities.remove(i);
-            true
+        let next = self.entities.next_after(id);
+        let before = self.entities.generation();
+        let Some(e) = self.entities.remove(id) else {
+            return false;
+        };
+        self.index.forget(id);
+        self.index.follow(before);
+        if let Some(j) = &mut self.journal {
+            j.removed(e, next);
+        }
+        true
+    }
+
+    pub fn entity(&self, id: u64) -> Option<&Entity> {
+        self.entities.get(id)
+    }
+
+    /// Сущности в порядке отрисовки (только чтение; правка — через [`Document::entity_mut`]).
+    pub fn entities(&self) -> &EntityStore {
+        &self.entities
+    }
+
+    /// Сущность для изменения; её рамка в индексе пересчитается.
+    pub fn entity_mut(&mut self, id: u64) -> Option<&mut Entity> {
+        let before = self.entities.generation();
+        let e = self.entities.get_mut(id)?;
+        self.index.invalidate(id);
+        self.index.follow(before);
+        if let Some(j) = &mut self.journal {
+            j.modifying(|| e.clone(), id);
+        }
+        Some(e)
+    }
+
+    /// Пересчитать рамки изменённых сущностей в пространственном индексе.
+    /// Дёшево, если ничего не менялось.
+    pub fn refresh_index(&mut self) {
+        SpatialIndex::refresh(self);
+    }
+
+    /// Сущности, чьи рамки пересекают `b`, в порядке документа. Это кандидаты:
+    /// точную проверку делает вызывающий. Без актуального индекса — все сущности.
+    pub fn entities_in_rect(&self, b: &Bbox) -> Vec<&Entity> {
+        match self.index.query(b, self.entities.generation()) {
+            Some(ids) => {
+                let mut ids: Vec<u64> = ids.into_iter().collect();
+                ids.sort_by_key(|&id| self.entities.order(id));
+                ids.iter().filter_map(|&id| self.entities.get(id)).collect()
+            }
+            None => self.entities.iter().collect(),
+        }
+    }
+
+    /// Кандидаты в пределах `reach` от точки.
+    pub fn entities_near(&self, p: Pt2, reach: f32) -> Vec<&Entity> {
+        self.entities_in_rect(&Bbox::around(p, reach))
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    /// Слой для изменения (видимость, цвет и т.п.).
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        let i = self.layers.iter().position(|l| l.name == name)?;
+        if let Some(j) = &mut self.journal {
+            j.layers(&self.layers);
+        }
+        Some(&mut self.layers[i])
+    }
+
+    /// Добавить слой (одноимённый заменяется).
+    pub fn add_layer(&mut self, layer: Layer) {
+        if let Some(j) = &mut self.journal {
+            j.layers(&self.layers);
+        }
+        match self.layers.iter_mut().find(|l| l.name == layer.name) {
+            Some(old) => *old = layer,
+            None => self.layers.push(layer),
+        }
+    }
+
+    /// Слой существует и не выключен (неизвестные слои считаются видимыми).
+    pub fn is_layer_visible(&self, name: &str) -> bool {
+        self.layer(name).is_none_or(|l| l.visible)
+    }
+
+    /// Тип линии по имени, без учёта регистра (как в DXF).
+    pub fn linetype(&self, name: &str) -> Option<&LinetypeDef> {
+        self.linetypes
+            .iter()
+            .find(|l| l.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Размерный стиль по имени без учёта регистра; неизвестный или пустой — стандартный.
+    pub fn dim_style(&self, name: &str) -> DimStyle {
+        self.dim_styles
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Добавить размерный стиль (одноимённый заменяется).
+    pub fn add_dim_style(&mut self, style: DimStyle) {
+        match self
+            .dim_styles
+            .iter_mut()
+            .find(|s| s.name.eq_ignore_ascii_case(&style.name))
+        {
+            Some(old) => *old = style,
+            None => self.dim_styles.push(style),
+        }
+    }
+
+    /// Итоговые цвет, тип и вес линии сущности. ПоБлоку вне вставки ведёт себя как
+    /// в AutoCAD: белый/чёрный цвет, сплошная линия, вес по умолчанию.
+    pub fn entity_style(&self, e: &Entity) -> EntityStyle<'_> {
+        let layer = self.layer(&e.layer);
+        let color = match e.color {
+            Color::ByLayer => layer.map_or(Color::WHITE, |l| l.color),
+            Color::ByBlock => Color::WHITE,
+            c => c,
+        };
+        // слой мог прийти с ПоСлою/ПоБлоку из чужого файла
+        let color = match color {
+            Color::ByLayer | Color::ByBlock => Color::WHITE,
+            c => c,
+        };
+        let linetype = if e.linetype.is_empty() {
+            layer.map_or("", |l| l.linetype.as_str())
+        } else if e.linetype.eq_ignore_ascii_case(LINETYPE_BYBLOCK) {
+            ""
         } else {
-            false
+            e.linetype.as_str()
+        };
+        let pattern = self.linetype(linetype).map_or(&[][..], |l| &l.pattern[..]);
+        let weight_mm = match e.lineweight {
+            LineWeight::ByLayer => layer.and_then(|l| l.lineweight.mm()),
+            w => w.mm(),
+        }
+        .unwrap_or(LineWeight::DEFAULT_MM);
+        EntityStyle {
+            color,
+            pattern,
+            weight_mm,
         }
     }
 
+    /// Определение блока по имени.
+    pub fn block(&self, name: &str) -> Option<&BlockDef> {
+        self.blocks.iter().find(|b| b.name == name)
+    }
+
+    /// Определения блоков (только чтение; правка — через [`Document::add_block`]).
+    pub fn blocks(&self) -> &[BlockDef] {
+        &self.blocks
+    }
+
+    /// Добавить определение блока (одноимённое заменяется).
+    pub fn add_block(&mut self, block: BlockDef) {
+        if let Some(j) = &mut self.journal {
+            j.blocks(&self.blocks);
+        }
+        // блок может входить во вложенные вставки других блоков
+        self.invalidate_block_refs();
+        match self.blocks.iter_mut().find(|b| b.name == block.name) {
+            Some(old) => *old = block,
+            None => self.blocks.push(block),
+        }
+    }
+
+    /// Рамки всех вставок, размеров и таблиц — после смены определений блоков.
+    pub(crate) fn invalidate_block_refs(&mut self) {
+        for e in self.entities.iter() {
+            if e.kind.is_block_ref() {
+                self.index.invalidate(e.id);
+            }
+        }
+    }
+
+    /// Содержимое вставки в мировых координатах — один уровень, как EXPLODE:
+    /// вложенные вставки остаются вставками, атрибуты становятся текстом.
+    /// Сущности блока на слое "0" получают слой вставки. Id у результата нулевые.
+    /// Размер раскладывается на линии, стрелки и текст.
+    pub fn explode_insert(&self, ent: &Entity) -> Vec<Entity> {
+        match ent.kind {
+            EntityKind::Dimension { .. } => return dimension_entities(self, ent),
+            EntityKind::Table { .. } => return table_entities(ent),
+            _ => {}
+        }
+        let EntityKind::Insert {
+            block,
+            pos,
+            scale,
+            rotation,
+            attribs,
+        } = &ent.kind
+        else {
+            return vec![];
+        };
+        let Some(def) = self.block(block) else {
+            return vec![];
+        };
+        let tr = Affine2::insert(*pos, *scale, *rotation, def.base);
+
+        let layer_of = |l: &str| {
+            if l == "0" || l.is_empty() {
+                ent.layer.clone()
+            } else {
+                l.to_string()
+            }
+        };
+        let mut out: Vec<Entity> = def
+            .entities
+            .iter()
+            .map(|e| Entity {
+                id: 0,
+                layer: layer_of(&e.layer),
+                kind: e.kind.transformed(&tr),
+                // ПоБлоку — свойства самой вставки
+                color: match e.color {
+                    Color::ByBlock => ent.color,
+                    c => c,
+                },
+                linetype: if e.linetype.eq_ignore_ascii_case(LINETYPE_BYBLOCK) {
+                    ent.linetype.clone()
+                } else {
+                    e.linetype.clone()
+                },
+                lineweight: match e.lineweight {
+                    LineWeight::ByBlock => ent.lineweight,
+                    w => w,
+                },
+            })
+            .collect();
+        out.extend(attribs.iter().map(|a| Entity {
+            color: ent.color,
+            linetype: ent.linetype.clone(),
+            lineweight: ent.lineweight,
+            ..Entity::new(
+                ent.layer.clone(),
+                EntityKind::Text {
+                    pos: a.pos,
+                    content: a.value.clone(),
+                    height: a.height,
+                },
+            )
+        }));
+        out
+    }
+
+    /// Вставка, развёрнутая до примитивов (для отрисовки и выбора).
+    /// Глубина вложенности ограничена — на случай циклических ссылок блоков.
+    pub fn insert_geometry(&self, ent: &Entity) -> Vec<Entity> {
+        fn walk(doc: &Document, ent: &Entity, depth: usize, out: &mut Vec<Entity>) {
+            for e in doc.explode_insert(ent) {
+                if e.kind.is_block_ref() {
+                    if depth < MAX_BLOCK_DEPTH {
+                        walk(doc, &e, depth + 1, out);
+                    }
+                } else {
+                    out.push(e);
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(self, ent, 0, &mut out);
+        out
+    }
+
+    /// Заменить вставку `id` её содержимым. Возвращает id новых сущностей
+    /// (пусто, если это не вставка или блок не найден).
+    pub fn explode(&mut self, id: u64) -> Vec<u64> {
+        let Some(e) = self.entities.get(id) else {
+            return vec![];
+        };
+        let parts = self.explode_insert(e);
+        if parts.is_empty() {
+            return vec![];
+        }
+        self.remove_entity(id);
+        parts.into_iter().map(|e| self.add_entity(e)).collect()
+    }
+
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(self).unwrap()
     }
@@ -95,92 +407,323 @@ impl Document {
         );
 
         for e in &self.entities {
-            match &e.kind {
-                EntityKind::LineSeg { a, b } => {
-                    let _ = writeln!(
-                        out,
-                        "<line x1='{:.3}' y1='{:.3}' x2='{:.3}' y2='{:.3}' />",
-                        a.x, a.y, b.x, b.y
-                    );
+            self.write_svg_entity(&mut out, e, 0, false);
+        }
+
+        let _ = writeln!(out, "</g></svg>");
+        out
+    }
+
+    /// Лист бумаги `size_mm` (единицы чертежа — мм, начало — левый нижний угол):
+    /// ось Y вверх, веса линий — в мм, текст не зеркалится.
+    pub fn export_svg_page(&self, size_mm: (f32, f32)) -> String {
+        let (w, h) = size_mm;
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg xmlns='http://www.w3.org/2000/svg' width='{w}mm' height='{h}mm' viewBox='0 0 {w} {h}'>"
+        );
+        let _ = writeln!(
+            out,
+            "<g transform='matrix(1 0 0 -1 0 {h})' stroke='black' fill='none' stroke-width='{}'>",
+            LineWeight::DEFAULT_MM
+        );
+        for e in &self.entities {
+            self.write_svg_entity(&mut out, e, 0, true);
+        }
+        let _ = writeln!(out, "</g></svg>");
+        out
+    }
+
+    /// `page` — вывод листа: ось Y перевёрнута группой, базовый вес — в мм.
+    fn write_svg_entity(&self, out: &mut String, e: &Entity, depth: usize, page: bool) {
+        if !self.is_layer_visible(&e.layer) {
+            return;
+        }
+        if e.kind.is_block_ref() {
+            if depth < MAX_BLOCK_DEPTH {
+                for sub in self.explode_insert(e) {
+                    self.write_svg_entity(out, &sub, depth + 1, page);
                 }
-                EntityKind::Arc {
-                    center,
-                    radius,
-                    start_angle,
-                    end_angle,
-                } => {
-                    let (sa, ea) = (*start_angle as f64, *end_angle as f64);
-                    let (sx, sy) = (
-                        center.x as f64 + (*radius as f64) * sa.cos(),
-                        center.y as f64 + (*radius as f64) * sa.sin(),
-                    );
-                    let (ex, ey) = (
-                        center.x as f64 + (*radius as f64) * ea.cos(),
-                        center.y as f64 + (*radius as f64) * ea.sin(),
-                    );
-                    let large = if (ea - sa).abs() > std::f64::consts::PI {
+            }
+            return;
+        }
+
+        // свойства, отличные от общих для <g>, — во вложенную группу
+        let st = self.entity_style(e);
+        let color = svg_color(st.color);
+        let mut attrs = String::new();
+        if color != "black" {
+            let _ = write!(attrs, " stroke='{color}'");
+        }
+        if (st.weight_mm - LineWeight::DEFAULT_MM).abs() > 1e-6 {
+            let base = if page {
+                LineWeight::DEFAULT_MM
+            } else {
+                self.style.stroke_px
+            };
+            let w = base * st.weight_mm / LineWeight::DEFAULT_MM;
+            let _ = write!(attrs, " stroke-width='{w:.3}'");
+        }
+        if !st.pattern.is_empty() {
+            let mut dashes: Vec<String> = st.pattern.iter().map(|v| v.abs().to_string()).collect();
+            if st.pattern[0] < 0.0 {
+                dashes.insert(0, "0".into());
+            }
+            let _ = write!(
+                attrs,
+                " stroke-dasharray='{}' stroke-linecap='round'",
+                dashes.join(" ")
+            );
+        }
+        if !attrs.is_empty() {
+            let _ = writeln!(out, "<g{attrs}>");
+        }
+        self.write_svg_primitive(out, e, &color, page);
+        if !attrs.is_empty() {
+            let _ = writeln!(out, "</g>");
+        }
+    }
+
+    fn write_svg_primitive(&self, out: &mut String, e: &Entity, color: &str, page: bool) {
+        match &e.kind {
+            EntityKind::LineSeg { a, b } => {
+                let _ = writeln!(
+                    out,
+                    "<line x1='{:.3}' y1='{:.3}' x2='{:.3}' y2='{:.3}' />",
+                    a.x, a.y, b.x, b.y
+                );
+            }
+            EntityKind::Arc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                let (sa, ea) = (*start_angle as f64, *end_angle as f64);
+                let (sx, sy) = (
+                    center.x as f64 + (*radius as f64) * sa.cos(),
+                    center.y as f64 + (*radius as f64) * sa.sin(),
+                );
+                let (ex, ey) = (
+                    center.x as f64 + (*radius as f64) * ea.cos(),
+                    center.y as f64 + (*radius as f64) * ea.sin(),
+                );
+                let large = if (ea - sa).abs() > std::f64::consts::PI {
+                    1
+                } else {
+                    0
+                };
+                let sweep = if ea > sa { 1 } else { 0 };
+                let _ = writeln!(
+                    out,
+                    "<path d='M {sx} {sy} A {r} {r} 0 {large} {sweep} {ex} {ey}' />",
+                    sx = sx,
+                    sy = sy,
+                    r = radius,
+                    large = large,
+                    sweep = sweep,
+                    ex = ex,
+                    ey = ey
+                );
+            }
+            EntityKind::Circle { center, radius } => {
+                let _ = writeln!(
+                    out,
+                    "<circle cx='{:.3}' cy='{:.3}' r='{:.3}' />",
+                    center.x, center.y, radius
+                );
+            }
+            EntityKind::Ellipse {
+                center,
+                major,
+                ratio,
+                start_param,
+                end_param,
+            } => {
+                let rx = major.x.hypot(major.y);
+                let ry = rx * ratio;
+                let rot = major.y.atan2(major.x).to_degrees();
+                let (sp, ep) = (*start_param, *end_param);
+                let at = |t: f32| ellipse_point(*center, *major, *ratio, t);
+                let p0 = at(sp);
+                let mut d = format!("M {} {} ", p0.x, p0.y);
+                if (ep - sp).abs() >= std::f32::consts::TAU - 1e-4 {
+                    // полный эллипс — двумя половинами, одной дугой SVG не нарисовать
+                    let p1 = at(sp + std::f32::consts::PI);
+                    let _ = write!(d, "A {rx} {ry} {rot} 0 1 {} {} ", p1.x, p1.y);
+                    let _ = write!(d, "A {rx} {ry} {rot} 0 1 {} {} Z", p0.x, p0.y);
+                } else {
+                    let p1 = at(ep);
+                    let large = if (ep - sp).abs() > std::f32::consts::PI {
                         1
                     } else {
                         0
                     };
-                    let sweep = if ea > sa { 1 } else { 0 };
-                    let _ = writeln!(
-                        out,
-                        "<path d='M {sx} {sy} A {r} {r} 0 {large} {sweep} {ex} {ey}' />",
-                        sx = sx,
-                        sy = sy,
-                        r = radius,
-                        large = large,
-                        sweep = sweep,
-                        ex = ex,
-                        ey = ey
-                    );
+                    let sweep = if ep > sp { 1 } else { 0 };
+                    let _ = write!(d, "A {rx} {ry} {rot} {large} {sweep} {} {}", p1.x, p1.y);
                 }
-                EntityKind::Polyline { pts, closed } => {
-                    if let Some(p0) = pts.first() {
-                        let mut d = String::new();
-                        let _ = write!(d, "M {} {} ", p0.x, p0.y);
-                        for p in &pts[1..] {
-                            let _ = write!(d, "L {} {} ", p.x, p.y);
-                        }
-                        if *closed {
-                            let _ = write!(d, "Z");
+                let _ = writeln!(out, "<path d='{d}' />");
+            }
+            EntityKind::Polyline {
+                pts,
+                closed,
+                bulges,
+            } => {
+                if let Some(p0) = pts.first() {
+                    let mut d = String::new();
+                    let _ = write!(d, "M {} {} ", p0.x, p0.y);
+                    let n = pts.len();
+                    let count = if *closed && n > 2 { n } else { n - 1 };
+                    for i in 0..count {
+                        let (a, b) = (pts[i], pts[(i + 1) % n]);
+                        let bulge = bulges.get(i).copied().unwrap_or(0.0);
+                        match bulge_arc(a, b, bulge) {
+                            Some((_, r, a0, a1)) => {
+                                let large = if (a1 - a0).abs() > std::f32::consts::PI {
+                                    1
+                                } else {
+                                    0
+                                };
+                                let sweep = if a1 > a0 { 1 } else { 0 };
+                                let _ = write!(d, "A {r} {r} 0 {large} {sweep} {} {} ", b.x, b.y);
+                            }
+                            None => {
+                                let _ = write!(d, "L {} {} ", b.x, b.y);
+                            }
                         }
-                        let _ = writeln!(out, "<path d='{d}' />");
                     }
+                    if *closed {
+                        let _ = write!(d, "Z");
+                    }
+                    let _ = writeln!(out, "<path d='{d}' />");
                 }
-                EntityKind::NurbsCurve2D { .. } => {
-                    let poly = sample_nurbs2d_as_polyline(&e.kind, 64);
-                    if !poly.is_empty() {
-                        let mut d = String::new();
-                        let p0 = &poly[0];
-                        let _ = write!(d, "M {} {} ", p0.x, p0.y);
-                        for p in &poly[1..] {
-                            let _ = write!(d, "L {} {} ", p.x, p.y);
-                        }
-                        let _ = writeln!(out, "<path d='{d}' />");
+            }
+            EntityKind::NurbsCurve2D { .. } => {
+                let poly = sample_nurbs2d_as_polyline(&e.kind, 64);
+                if !poly.is_empty() {
+                    let mut d = String::new();
+                    let p0 = &poly[0];
+                    let _ = write!(d, "M {} {} ", p0.x, p0.y);
+                    for p in &poly[1..] {
+                        let _ = write!(d, "L {} {} ", p.x, p.y);
                     }
+                    let _ = writeln!(out, "<path d='{d}' />");
                 }
-                EntityKind::Text {
-                    pos,
-                    content,
+            }
+            EntityKind::Text {
+                pos,
+                content,
+                height,
+            } if page => {
+                // под перевёрнутой осью Y текст зеркалится обратно
+                let _ = writeln!(
+                    out,
+                    "<text transform='matrix(1 0 0 -1 {:.3} {:.3})' font-size='{:.3}' fill='{}' stroke='none'>{}</text>",
+                    pos.x,
+                    pos.y,
+                    height,
+                    color,
+                    xml_escape(content)
+                );
+            }
+            EntityKind::Text {
+                pos,
+                content,
+                height,
+            } => {
+                let _ = writeln!(
+                    out,
+                    "<text x='{:.3}' y='{:.3}' font-size='{:.3}' fill='{}'>{}</text>",
+                    pos.x,
+                    pos.y,
                     height,
-                } => {
-                    let _ = writeln!(
-                        out,
-                        "<text x='{:.3}' y='{:.3}' font-size='{:.3}' fill='black'>{}</text>",
-                        pos.x,
-                        pos.y,
-                        height,
-                        xml_escape(content)
-                    );
+                    color,
+                    xml_escape(content)
+                );
+            }
+            EntityKind::Hatch { loops, fill } => {
+                let mut d = String::new();
+                for poly in loops.iter().map(|l| l.polygon()).filter(|p| p.len() >= 3) {
+                    let _ = write!(d, "M {} {} ", poly[0].x, poly[0].y);
+                    for p in &poly[1..] {
+                        let _ = write!(d, "L {} {} ", p.x, p.y);
+                    }
+                    let _ = write!(d, "Z ");
                 }
+                match fill {
+                    HatchFill::Solid => {
+                        let _ = writeln!(
+                            out,
+                            "<path d='{d}' fill='{color}' fill-rule='evenodd' stroke='none' />"
+                        );
+                    }
+                    // каждое семейство линий — своим <pattern>, id уникален по позиции в выводе
+                    HatchFill::Pattern { lines, .. } => {
+                        let tag = out.len();
+                        for (i, fam) in lines.iter().enumerate() {
+                            let id = format!("hatch{tag}_{i}");
+                            if write_svg_hatch_pattern(out, &id, fam) {
+                                let _ = writeln!(
+                                    out,
+                                    "<path d='{d}' fill='url(#{id})' fill-rule='evenodd' stroke='none' />"
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            // развёрнуты в write_svg_entity
+            EntityKind::Insert { .. } | EntityKind::Dimension { .. } | EntityKind::Table { .. } => {
             }
         }
+    }
+}
 
-        let _ = writeln!(out, "</g></svg>");
-        out
+/// `<pattern>` для семейства линий штриховки: плитка «период штрихов × шаг линий»
+/// в осях семейства, сдвиг соседних линий вдоль — через skewX.
+fn write_svg_hatch_pattern(out: &mut String, id: &str, fam: &HatchLine) -> bool {
+    let (s, c) = fam.angle.sin_cos();
+    let along = fam.offset.x * c + fam.offset.y * s;
+    let spacing = -fam.offset.x * s + fam.offset.y * c;
+    if spacing.abs() < 1e-6 {
+        return false;
     }
+    let period: f32 = fam.dashes.iter().map(|v| v.abs()).sum();
+    let (w, h) = (
+        if period > 1e-6 { period } else { spacing.abs() },
+        spacing.abs(),
+    );
+    let skew = if period > 1e-6 {
+        (along.rem_euclid(period) / spacing).atan().to_degrees()
+    } else {
+        0.0
+    };
+
+    let y = h / 2.0;
+    let mut d = String::new();
+    if period > 1e-6 {
+        let mut x = 0.0;
+        for v in &fam.dashes {
+            if *v >= 0.0 {
+                let _ = write!(d, "M {x} {y} h {v} ");
+            }
+            x += v.abs();
+        }
+    } else {
+        let _ = write!(d, "M 0 {y} h {w} ");
+    }
+    let _ = writeln!(
+        out,
+        "<defs><pattern id='{id}' patternUnits='userSpaceOnUse' width='{w}' height='{h}' \
+         patternTransform='translate({} {}) rotate({}) skewX({skew}) translate(0 {})'>\
+         <path d='{d}' stroke-linecap='round' /></pattern></defs>",
+        fam.origin.x,
+        fam.origin.y,
+        fam.angle.to_degrees(),
+        -y
+    );
+    true
 }
 
 /// Семплируем как B-spline (веса игнорируем, чтобы обойти расхождения API).
@@ -218,6 +761,17 @@ fn sample_nurbs2d_as_polyline(kind: &EntityKind, samples: usize) -> Vec<Pt2> {
     }
 }
 
+/// ACI 7 на белом листе — чёрный.
+fn svg_color(c: Color) -> String {
+    match c {
+        Color::Aci(7) | Color::ByLayer | Color::ByBlock => "black".into(),
+        c => {
+            let [r, g, b] = c.rgb().unwrap_or([0, 0, 0]);
+            format!("#{r:02x}{g:02x}{b:02x}")
+        }
+    }
+}
+
 fn xml_escape(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     for ch in s.chars() {
@@ -1,12 +1,26 @@
-use crate::{Document, Entity, EntityKind, Pt2};
+use crate::{
+    dim_geometry, dim_measure, nearest_aci, polyline_segments, table_entities, table_geometry,
+    Attrib, BlockDef, CellAlign, CellMerge, Color, DimArrow, DimKind, DimPoint, DimStyle, Document,
+    Entity, EntityKind, HatchFill, HatchLine, HatchLoop, Layer, LineWeight, LinetypeDef, Pt2,
+    Table, LINETYPE_BYBLOCK, LINETYPE_CONTINUOUS,
+};
 use anyhow::Result;
 
 // dxf 0.6 API
 use dxf::entities::{
-    Entity as DEntity, EntityType, Line as DLine, LwPolyline as DLwPolyline, Spline as DSpline,
-    Text as DText,
+    AngularThreePointDimension, Arc as DArc, Attribute as DAttribute,
+    AttributeDefinition as DAttDef, Circle as DCircle, DiameterDimension, DimensionBase,
+    Ellipse as DEllipse, Entity as DEntity, EntityType, Insert as DInsert, Line as DLine,
+    LwPolyline as DLwPolyline, ModelPoint as DModelPoint, OrdinateDimension, RadialDimension,
+    RotatedDimension, Spline as DSpline, Text as DText,
+};
+use dxf::enums::{AcadVersion, DimensionType};
+use dxf::tables::{DimStyle as DDimStyle, Layer as DLayer, LineType as DLineType};
+use dxf::{
+    Block as DBlock, Color as DColor, Drawing, LineWeight as DLineWeight, LwPolylineVertex,
+    Point as DPoint, Vector as DVector,
 };
-use dxf::{Drawing, LwPolylineVertex, Point as DPoint};
+use std::f64::consts::{PI, TAU};
 
 #[inline]
 fn p2(x: f64, y: f64) -> Pt2 {
@@ -25,172 +39,1744 @@ fn dpoint(p: Pt2) -> DPoint {
     }
 }
 
-/// Импорт DXF → наш Document (LINE, LWPOLYLINE, SPLINE, TEXT, MTEXT).
+/// Дуга в плоскости XY: (центр, начальный и конечный угол в радианах, end > start).
+/// При нормали −Z (зеркальная OCS) X отражается и направление обхода меняется.
+fn arc_from_ocs(
+    center: &DPoint,
+    normal: &DVector,
+    start_deg: f64,
+    end_deg: f64,
+) -> (Pt2, f64, f64) {
+    let (mut a0, mut a1) = (start_deg.to_radians(), end_deg.to_radians());
+    let mut c = p2(center.x, center.y);
+    if normal.z < 0.0 {
+        c.x = -c.x;
+        (a0, a1) = (PI - a1, PI - a0);
+    }
+    while a1 <= a0 {
+        a1 += TAU;
+    }
+    (c, a0, a1)
+}
+
+/// Служебные блоки пространств модели/листа — их содержимое не блок, а сам чертёж.
+fn is_layout_block(name: &str) -> bool {
+    let n = name.to_ascii_lowercase();
+    n.starts_with("*model_space") || n.starts_with("*paper_space")
+}
+
+/// Размерные блоки (*D1, *D2, …) — их геометрию строит сам размер.
+fn is_dim_block(name: &str) -> bool {
+    name.len() > 2 && name[..2].eq_ignore_ascii_case("*D") && name[2..].parse::<u32>().is_ok()
+}
+
+/// Блоки таблиц (*T1, *T2, …) — их геометрию строит сама таблица.
+fn is_table_block(name: &str) -> bool {
+    name.len() > 2 && name[..2].eq_ignore_ascii_case("*T") && name[2..].parse::<u32>().is_ok()
+}
+
+/// Импорт DXF → наш Document (LINE, ARC, CIRCLE, ELLIPSE, LWPOLYLINE, SPLINE, TEXT, MTEXT,
+/// INSERT с ATTRIB, HATCH, ACAD_TABLE, DIMENSION без ассоциативности, DIMSTYLE и определения
+/// блоков с ATTDEF).
 pub fn import_dxf(path: &str) -> Result<Document> {
     let drawing = Drawing::load_file(path)?;
     let mut doc = Document::new();
+    // штриховки и таблицы — первыми, чтобы рисовались под остальным
+    let mut raw = read_raw_entities(path)?;
 
-    for e in drawing.entities() {
-        let layer = e.common.layer.clone();
-        match &e.specific {
-            EntityType::Line(line) => {
-                let a = p2(line.p1.x, line.p1.y);
-                let b = p2(line.p2.x, line.p2.y);
-                doc.add_entity(Entity {
-                    id: 0,
-                    layer,
-                    kind: EntityKind::LineSeg { a, b },
+    for lt in drawing.line_types() {
+        if is_builtin_linetype(&lt.name) {
+            continue;
+        }
+        doc.linetypes.push(LinetypeDef {
+            name: lt.name.clone(),
+            description: lt.description.clone(),
+            pattern: lt
+                .dash_dot_space_lengths
+                .iter()
+                .map(|&v| v as f32)
+                .collect(),
+        });
+    }
+    for l in drawing.layers() {
+        let layer = Layer {
+            visible: l.is_layer_on,
+            color: import_color(&l.color, 0),
+            linetype: l.line_type_name.clone(),
+            lineweight: LineWeight::from_dxf(l.line_weight.raw_value()),
+            ..Layer::new(l.name.clone())
+        };
+        match doc.layers.iter_mut().find(|x| x.name == layer.name) {
+            Some(x) => *x = layer,
+            None => doc.layers.push(layer),
+        }
+    }
+
+    for ds in drawing.dim_styles() {
+        doc.add_dim_style(import_dim_style(ds));
+    }
+
+    for b in drawing.blocks() {
+        if is_layout_block(&b.name) || is_dim_block(&b.name) || is_table_block(&b.name) {
+            continue;
+        }
+        let mut def = BlockDef {
+            name: b.name.clone(),
+            base: p2(b.base_point.x, b.base_point.y),
+            entities: vec![],
+            attdefs: vec![],
+        };
+        for (_, h) in raw.extract_if(.., |(owner, _)| owner.as_deref() == Some(&b.name)) {
+            def.entities.push(Entity {
+                id: def.entities.len() as u64 + 1,
+                ..h
+            });
+        }
+        for e in &b.entities {
+            if let EntityType::AttributeDefinition(ad) = &e.specific {
+                def.attdefs.push(Attrib {
+                    tag: ad.text_tag.clone(),
+                    value: ad.value.clone(),
+                    pos: p2(ad.location.x, ad.location.y),
+                    height: text_height(ad.text_height),
+                });
+            } else if let Some(kind) = import_entity(e) {
+                def.entities.push(Entity {
+                    id: def.entities.len() as u64 + 1,
+                    ..import_common(e, kind)
                 });
             }
-            EntityType::LwPolyline(pl) => {
-                let pts: Vec<Pt2> = pl.vertices.iter().map(|v| p2(v.x, v.y)).collect();
-                let closed = pl.is_closed();
-                if pts.len() >= 2 {
-                    doc.add_entity(Entity {
-                        id: 0,
-                        layer,
-                        kind: EntityKind::Polyline { pts, closed },
-                    });
-                }
+        }
+        doc.add_block(def);
+    }
+
+    for (owner, h) in raw {
+        if owner.is_none() {
+            doc.add_entity(h);
+        }
+    }
+    for e in drawing.entities() {
+        if let Some(kind) = import_entity(e) {
+            doc.add_entity(import_common(e, kind));
+        }
+    }
+
+    Ok(doc)
+}
+
+/// Слой, цвет, тип и вес линии DXF-сущности.
+fn import_common(e: &DEntity, kind: EntityKind) -> Entity {
+    let c = &e.common;
+    Entity {
+        color: import_color(&c.color, c.color_24_bit),
+        linetype: import_linetype(&c.line_type_name),
+        // 0 — значение dxf-крейта при отсутствии кода 370, т.е. ПоСлою
+        lineweight: match c.lineweight_enum_value {
+            0 => LineWeight::ByLayer,
+            w => LineWeight::from_dxf(w),
+        },
+        ..Entity::new(c.layer.clone(), kind)
+    }
+}
+
+/// BYLAYER — пустое имя, BYBLOCK — `LINETYPE_BYBLOCK`.
+fn import_linetype(name: &str) -> String {
+    if name.eq_ignore_ascii_case("BYLAYER") {
+        String::new()
+    } else if name.eq_ignore_ascii_case("BYBLOCK") {
+        LINETYPE_BYBLOCK.into()
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Код 62 (+ 420 с R2004): истинный цвет важнее индекса.
+fn import_color(c: &DColor, true_color: i32) -> Color {
+    if true_color > 0 && !c.is_by_layer() && !c.is_by_block() {
+        let [_, r, g, b] = true_color.to_be_bytes();
+        return Color::Rgb([r, g, b]);
+    }
+    if c.is_by_layer() {
+        Color::ByLayer
+    } else if c.is_by_block() {
+        Color::ByBlock
+    } else {
+        c.index().map(Color::Aci).unwrap_or(Color::WHITE)
+    }
+}
+
+fn export_color(c: Color) -> (DColor, i32) {
+    match c {
+        Color::ByLayer => (DColor::by_layer(), 0),
+        Color::ByBlock => (DColor::by_block(), 0),
+        Color::Aci(i) => (DColor::from_index(i), 0),
+        Color::Rgb([r, g, b]) => (
+            DColor::from_index(nearest_aci([r, g, b])),
+            i32::from_be_bytes([0, r, g, b]),
+        ),
+    }
+}
+
+/// `LineWeight::from_raw_value` в dxf 0.6 закрыт; собираем через его serde-представление.
+fn dxf_line_weight(w: LineWeight) -> DLineWeight {
+    serde_json::from_value(serde_json::json!({ "raw_value": w.to_dxf() })).unwrap_or_default()
+}
+
+/// BYLAYER/BYBLOCK/CONTINUOUS есть в любом чертеже, шаблонов у них нет.
+fn is_builtin_linetype(name: &str) -> bool {
+    ["BYLAYER", "BYBLOCK", LINETYPE_CONTINUOUS]
+        .iter()
+        .any(|n| name.eq_ignore_ascii_case(n))
+}
+
+fn text_height(h: f64) -> f32 {
+    if h > 0.0 {
+        h as f32
+    } else {
+        2.5
+    }
+}
+
+/// Одна DXF-сущность → наша; неподдерживаемые и вырожденные — `None`.
+fn import_entity(e: &DEntity) -> Option<EntityKind> {
+    match &e.specific {
+        EntityType::Line(line) => {
+            let a = p2(line.p1.x, line.p1.y);
+            let b = p2(line.p2.x, line.p2.y);
+            Some(EntityKind::LineSeg { a, b })
+        }
+        EntityType::Arc(arc) => {
+            let (center, a0, a1) =
+                arc_from_ocs(&arc.center, &arc.normal, arc.start_angle, arc.end_angle);
+            Some(EntityKind::Arc {
+                center,
+                radius: arc.radius as f32,
+                start_angle: a0 as f32,
+                end_angle: a1 as f32,
+            })
+        }
+        EntityType::Circle(c) => {
+            let (center, ..) = arc_from_ocs(&c.center, &c.normal, 0.0, 360.0);
+            Some(EntityKind::Circle {
+                center,
+                radius: c.radius as f32,
+            })
+        }
+        EntityType::Ellipse(el) => {
+            // центр и ось в WCS; при нормали −Z параметр идёт по часовой
+            let (mut t0, mut t1) = (el.start_parameter, el.end_parameter);
+            if el.normal.z < 0.0 {
+                (t0, t1) = (-t1, -t0);
             }
-            EntityType::Spline(sp) => {
-                let degree = sp.degree_of_curve as usize;
-                let knots: Vec<f64> = sp.knot_values.clone();
-                let ctrl_pts: Vec<Pt2> = sp.control_points.iter().map(|p| p2(p.x, p.y)).collect();
-                let weights = if sp.weight_values.is_empty() {
-                    None
-                } else {
-                    Some(sp.weight_values.clone())
-                };
+            while t1 <= t0 {
+                t1 += TAU;
+            }
+            Some(EntityKind::Ellipse {
+                center: p2(el.center.x, el.center.y),
+                major: p2(el.major_axis.x, el.major_axis.y),
+                ratio: el.minor_axis_ratio as f32,
+                start_param: t0 as f32,
+                end_param: t1 as f32,
+            })
+        }
+        EntityType::LwPolyline(pl) => {
+            let pts: Vec<Pt2> = pl.vertices.iter().map(|v| p2(v.x, v.y)).collect();
+            let closed = pl.is_closed();
+            let mut bulges: Vec<f32> = pl.vertices.iter().map(|v| v.bulge as f32).collect();
+            if bulges.iter().all(|b| *b == 0.0) {
+                bulges.clear();
+            }
+            (pts.len() >= 2).then_some(EntityKind::Polyline {
+                pts,
+                closed,
+                bulges,
+            })
+        }
+        EntityType::Spline(sp) => {
+            let degree = sp.degree_of_curve as usize;
+            let knots: Vec<f64> = sp.knot_values.clone();
+            let ctrl_pts: Vec<Pt2> = sp.control_points.iter().map(|p| p2(p.x, p.y)).collect();
+            let weights = if sp.weight_values.is_empty() {
+                None
+            } else {
+                Some(sp.weight_values.clone())
+            };
 
-                if ctrl_pts.len() >= degree + 1 && !knots.is_empty() {
-                    doc.add_entity(Entity {
-                        id: 0,
-                        layer,
-                        kind: EntityKind::NurbsCurve2D {
-                            degree,
-                            knots,
-                            ctrl_pts,
-                            weights,
-                        },
-                    });
-                }
+            (ctrl_pts.len() >= degree + 1 && !knots.is_empty()).then_some(
+                EntityKind::NurbsCurve2D {
+                    degree,
+                    knots,
+                    ctrl_pts,
+                    weights,
+                },
+            )
+        }
+        EntityType::Text(t) => Some(EntityKind::Text {
+            pos: p2(t.location.x, t.location.y),
+            content: t.value.clone(),
+            height: text_height(t.text_height),
+        }),
+        // dxf 0.6 пишет пустой MTEXT после каждого ATTRIB/ATTDEF — пропускаем
+        EntityType::MText(mt) if mt.text.is_empty() => None,
+        EntityType::MText(mt) => Some(EntityKind::Text {
+            pos: p2(mt.insertion_point.x, mt.insertion_point.y),
+            content: mt.text.clone(),
+            // ключевое поле: initial_text_height
+            height: text_height(mt.initial_text_height),
+        }),
+        EntityType::RotatedDimension(_)
+        | EntityType::RadialDimension(_)
+        | EntityType::DiameterDimension(_)
+        | EntityType::AngularThreePointDimension(_)
+        | EntityType::OrdinateDimension(_) => import_dimension(&e.specific),
+        EntityType::Insert(ins) => Some(EntityKind::Insert {
+            block: ins.name.clone(),
+            pos: p2(ins.location.x, ins.location.y),
+            scale: p2(ins.x_scale_factor, ins.y_scale_factor),
+            rotation: ins.rotation.to_radians() as f32,
+            attribs: ins
+                .attributes()
+                .map(|a| Attrib {
+                    tag: a.attribute_tag.clone(),
+                    value: a.value.clone(),
+                    pos: p2(a.location.x, a.location.y),
+                    height: text_height(a.text_height),
+                })
+                .collect(),
+        }),
+        _ => None,
+    }
+}
+
+/// Экспорт Document → DXF (LINE, ARC, CIRCLE, ELLIPSE, LWPOLYLINE, SPLINE, TEXT,
+/// INSERT с ATTRIB, HATCH, DIMENSION с блоками *D, ACAD_TABLE с блоками *T, DIMSTYLE
+/// и блоки с ATTDEF).
+pub fn export_dxf(doc: &Document, path: &str) -> Result<()> {
+    export_dxf_with(doc, path, &DxfOptions::default())
+}
+
+/// Лист `size_mm` в пространстве листа: сущности чертежа с флагом paper space,
+/// границы листа — по размеру, при открытии активен лист.
+pub fn export_dxf_paper(doc: &Document, size_mm: (f32, f32), path: &str) -> Result<()> {
+    let opts = DxfOptions {
+        paper: Some(size_mm),
+        ..Default::default()
+    };
+    export_dxf_with(doc, path, &opts)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DxfOptions {
+    /// Размер листа: сущности пишутся в пространство листа.
+    pub paper: Option<(f32, f32)>,
+    /// Таблицы — отрезками и текстами вместо ACAD_TABLE.
+    pub explode_tables: bool,
+}
+
+pub fn export_dxf_with(doc: &Document, path: &str, opts: &DxfOptions) -> Result<()> {
+    let paper = opts.paper;
+    // развёрнутая таблица — её отрезки и тексты, остальное как есть
+    let expand = |ent: &Entity| -> Vec<Entity> {
+        match &ent.kind {
+            EntityKind::Table { .. } if opts.explode_tables => table_entities(ent),
+            _ => vec![ent.clone()],
+        }
+    };
+    let mut drawing = Drawing::new();
+    // по умолчанию R12 — в нём нет LWPOLYLINE/SPLINE/ELLIPSE, и они молча теряются
+    // R2004 — ради истинных цветов (код 420)
+    drawing.header.version = AcadVersion::R2004;
+
+    for lt in &doc.linetypes {
+        if is_builtin_linetype(&lt.name) {
+            continue;
+        }
+        drawing.add_line_type(DLineType {
+            name: lt.name.clone(),
+            description: lt.description.clone(),
+            element_count: lt.pattern.len() as i32,
+            total_pattern_length: lt.pattern_length() as f64,
+            dash_dot_space_lengths: lt.pattern.iter().map(|&v| v as f64).collect(),
+            ..Default::default()
+        });
+    }
+    for l in &doc.layers {
+        // у слоя в dxf 0.6 нет истинного цвета — ближайший ACI
+        let color = match l.color {
+            Color::Rgb(rgb) => DColor::from_index(nearest_aci(rgb)),
+            c => export_color(c).0,
+        };
+        drawing.add_layer(DLayer {
+            name: l.name.clone(),
+            color,
+            line_type_name: l.linetype.clone(),
+            line_weight: dxf_line_weight(l.lineweight),
+            is_layer_on: l.visible,
+            ..Default::default()
+        });
+    }
+
+    for st in &doc.dim_styles {
+        export_dim_style(&mut drawing, st);
+    }
+
+    let mut bodies = Bodies::default();
+    for def in &doc.blocks {
+        let mut entities: Vec<DEntity> = def
+            .entities
+            .iter()
+            .flat_map(expand)
+            .map(|e| export_entity(&mut drawing, doc, &mut bodies, &e))
+            .collect();
+        entities.extend(def.attdefs.iter().map(|a| {
+            let ad = DAttDef {
+                text_tag: a.tag.clone(),
+                value: a.value.clone(),
+                location: dpoint(a.pos),
+                text_height: a.height.max(0.1) as f64,
+                ..Default::default()
+            };
+            DEntity::new(EntityType::AttributeDefinition(ad))
+        }));
+        drawing.add_block(DBlock {
+            name: def.name.clone(),
+            base_point: dpoint(def.base),
+            entities,
+            ..Default::default()
+        });
+    }
+
+    if let Some((w, h)) = paper {
+        let (lo, hi) = (DPoint::origin(), DPoint::new(w as f64, h as f64, 0.0));
+        let hd = &mut drawing.header;
+        hd.previous_release_tile_compatability = false;
+        hd.paperspace_minimum_drawing_limits = lo.clone();
+        hd.paperspace_maximum_drawing_limits = hi.clone();
+        hd.paperspace_minimum_drawing_extents = lo;
+        hd.paperspace_maximum_drawing_extents = hi;
+    }
+    for ent in doc.entities.iter().flat_map(expand) {
+        let mut de = export_entity(&mut drawing, doc, &mut bodies, &ent);
+        de.common.is_in_paper_space = paper.is_some();
+        drawing.add_entity(de);
+    }
+
+    if bodies.hatches.is_empty() && bodies.tables.is_empty() {
+        drawing.save_file(path)?;
+    } else {
+        let mut buf = Vec::new();
+        drawing.save(&mut buf)?;
+        let text = splice_hatches(&String::from_utf8(buf)?, &bodies.hatches);
+        std::fs::write(path, splice_tables(&text, &bodies.tables))?;
+    }
+    Ok(())
+}
+
+/// Наша сущность → DXF. `drawing` нужен для хэндлов атрибутов вставки и блоков размеров
+/// и таблиц, в `bodies` копятся тела HATCH и ACAD_TABLE.
+fn export_entity(
+    drawing: &mut Drawing,
+    doc: &Document,
+    bodies: &mut Bodies,
+    ent: &Entity,
+) -> DEntity {
+    let specific = match &ent.kind {
+        EntityKind::LineSeg { a, b } => EntityType::Line(DLine::new(dpoint(*a), dpoint(*b))),
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => {
+            let mut pl = DLwPolyline::default();
+            pl.set_is_closed(*closed);
+            pl.vertices = pts
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let mut v = LwPolylineVertex::default();
+                    v.id = i as i32;
+                    v.x = p.x as f64;
+                    v.y = p.y as f64;
+                    v.bulge = bulges.get(i).copied().unwrap_or(0.0) as f64;
+                    v
+                })
+                .collect();
+            EntityType::LwPolyline(pl)
+        }
+        EntityKind::NurbsCurve2D {
+            degree,
+            knots,
+            ctrl_pts,
+            weights,
+        } => {
+            let mut sp = DSpline::default();
+            sp.degree_of_curve = *degree as i32;
+            sp.knot_values = knots.clone();
+            sp.control_points = ctrl_pts.iter().map(|p| dpoint(*p)).collect();
+            if let Some(w) = weights {
+                sp.weight_values = w.clone();
+                sp.set_is_rational(true);
+            } else {
+                sp.weight_values.clear();
+                sp.set_is_rational(false);
             }
-            EntityType::Text(t) => {
-                let pos = p2(t.location.x, t.location.y);
-                let height = if t.text_height > 0.0 {
-                    t.text_height as f32
-                } else {
-                    2.5
-                };
-                let content = t.value.clone();
-                doc.add_entity(Entity {
-                    id: 0,
-                    layer,
-                    kind: EntityKind::Text {
-                        pos,
-                        content,
-                        height,
-                    },
-                });
+            EntityType::Spline(sp)
+        }
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            // DXF-дуга всегда против часовой от start к end
+            let (a0, a1) = if end_angle >= start_angle {
+                (*start_angle as f64, *end_angle as f64)
+            } else {
+                (*end_angle as f64, *start_angle as f64)
+            };
+            if a1 - a0 >= TAU - 1e-6 {
+                EntityType::Circle(DCircle::new(dpoint(*center), *radius as f64))
+            } else {
+                EntityType::Arc(DArc::new(
+                    dpoint(*center),
+                    *radius as f64,
+                    a0.to_degrees().rem_euclid(360.0),
+                    a1.to_degrees().rem_euclid(360.0),
+                ))
             }
-            EntityType::MText(mt) => {
-                let pos = p2(mt.insertion_point.x, mt.insertion_point.y);
-                // ключевое поле: initial_text_height
-                let height = if mt.initial_text_height > 0.0 {
-                    mt.initial_text_height as f32
-                } else {
-                    2.5
+        }
+        EntityKind::Circle { center, radius } => {
+            EntityType::Circle(DCircle::new(dpoint(*center), *radius as f64))
+        }
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            start_param,
+            end_param,
+        } => {
+            let (t0, t1) = if end_param >= start_param {
+                (*start_param as f64, *end_param as f64)
+            } else {
+                (*end_param as f64, *start_param as f64)
+            };
+            let (start_parameter, end_parameter) = if t1 - t0 >= TAU - 1e-6 {
+                (0.0, TAU)
+            } else {
+                let s = t0.rem_euclid(TAU);
+                (s, s + (t1 - t0))
+            };
+            EntityType::Ellipse(DEllipse {
+                center: dpoint(*center),
+                major_axis: DVector::new(major.x as f64, major.y as f64, 0.0),
+                minor_axis_ratio: *ratio as f64,
+                start_parameter,
+                end_parameter,
+                ..Default::default()
+            })
+        }
+        EntityKind::Text {
+            pos,
+            content,
+            height,
+        } => {
+            let mut t = DText::default();
+            t.location = dpoint(*pos);
+            t.value = content.clone();
+            t.text_height = (*height).max(0.1) as f64;
+            EntityType::Text(t)
+        }
+        EntityKind::Insert {
+            block,
+            pos,
+            scale,
+            rotation,
+            attribs,
+        } => {
+            let mut ins = DInsert {
+                name: block.clone(),
+                location: dpoint(*pos),
+                x_scale_factor: scale.x as f64,
+                y_scale_factor: scale.y as f64,
+                rotation: (*rotation as f64).to_degrees(),
+                ..Default::default()
+            };
+            for a in attribs {
+                let att = DAttribute {
+                    attribute_tag: a.tag.clone(),
+                    value: a.value.clone(),
+                    location: dpoint(a.pos),
+                    text_height: a.height.max(0.1) as f64,
+                    ..Default::default()
                 };
-                let content = mt.text.clone();
-                doc.add_entity(Entity {
-                    id: 0,
-                    layer,
-                    kind: EntityKind::Text {
-                        pos,
-                        content,
-                        height,
-                    },
-                });
+                ins.add_attribute(drawing, att);
             }
-            _ => {}
+            EntityType::Insert(ins)
+        }
+        // заглушка с общими свойствами и номером тела в X; тело HATCH подставит splice_hatches
+        EntityKind::Hatch { loops, fill } => {
+            bodies.hatches.push(hatch_pairs(loops, fill));
+            EntityType::ModelPoint(DModelPoint {
+                location: DPoint::new((bodies.hatches.len() - 1) as f64, 0.0, 0.0),
+                ..Default::default()
+            })
+        }
+        EntityKind::Dimension { .. } => export_dimension(drawing, doc, bodies, ent),
+        EntityKind::Table { pos, table } => export_table(drawing, doc, bodies, *pos, table),
+    };
+    let mut de = DEntity::new(specific);
+    de.common.layer = ent.layer.clone();
+    (de.common.color, de.common.color_24_bit) = export_color(ent.color);
+    de.common.line_type_name = if ent.linetype.is_empty() {
+        "BYLAYER".into()
+    } else if ent.linetype.eq_ignore_ascii_case(LINETYPE_BYBLOCK) {
+        "BYBLOCK".into()
+    } else {
+        ent.linetype.clone()
+    };
+    de.common.lineweight_enum_value = ent.lineweight.to_dxf();
+    de
+}
+
+// --------------------------- DIMENSION ---------------------------
+
+/// Размер → DIMENSION с анонимным блоком *Dn: геометрия блока на слое 0 со свойствами
+/// ПоБлоку, определяющие точки — по соглашениям DXF для каждого вида.
+fn export_dimension(
+    drawing: &mut Drawing,
+    doc: &Document,
+    bodies: &mut Bodies,
+    ent: &Entity,
+) -> EntityType {
+    let EntityKind::Dimension {
+        kind,
+        pts,
+        line_pos,
+        text,
+        style,
+    } = &ent.kind
+    else {
+        unreachable!("export_dimension for {:?}", ent.kind)
+    };
+    let pts: Vec<Pt2> = pts.iter().map(|p| p.pos).collect();
+    let geometry = dim_geometry(kind, &pts, *line_pos, text, &doc.dim_style(style));
+    let text_mid = geometry
+        .iter()
+        .find_map(|k| match k {
+            EntityKind::Text { pos, .. } => Some(*pos),
+            _ => None,
+        })
+        .unwrap_or(*line_pos);
+
+    let block_name = format!(
+        "*D{}",
+        drawing.blocks().filter(|b| is_dim_block(&b.name)).count() + 1
+    );
+    let entities = geometry
+        .into_iter()
+        .map(|k| Entity {
+            color: Color::ByBlock,
+            linetype: LINETYPE_BYBLOCK.into(),
+            lineweight: LineWeight::ByBlock,
+            ..Entity::new("0", k)
+        })
+        .map(|e| export_entity(drawing, doc, bodies, &e))
+        .collect();
+    let mut block = DBlock {
+        name: block_name.clone(),
+        entities,
+        ..Default::default()
+    };
+    block.set_is_anonymous(true);
+    drawing.add_block(block);
+
+    let mut base = DimensionBase {
+        block_name,
+        definition_point_1: dpoint(*line_pos),
+        text_mid_point: dpoint(text_mid),
+        actual_measurement: dim_measure(kind, &pts).unwrap_or(0.0) as f64,
+        text: if text.is_empty() {
+            "<>".into()
+        } else {
+            text.clone()
+        },
+        dimension_style_name: if style.is_empty() {
+            "STANDARD".into()
+        } else {
+            style.clone()
+        },
+        ..Default::default()
+    };
+    match kind {
+        DimKind::Linear { .. } | DimKind::Aligned => {
+            let rotation_angle = match kind {
+                DimKind::Linear { angle } => (*angle as f64).to_degrees(),
+                _ => 0.0,
+            };
+            base.dimension_type = match kind {
+                DimKind::Linear { .. } => DimensionType::RotatedHorizontalOrVertical,
+                _ => DimensionType::Aligned,
+            };
+            EntityType::RotatedDimension(RotatedDimension {
+                dimension_base: base,
+                definition_point_2: dpoint(pts[0]),
+                definition_point_3: dpoint(pts[1]),
+                rotation_angle,
+                ..Default::default()
+            })
+        }
+        DimKind::Angular => {
+            base.dimension_type = DimensionType::AngularThreePoint;
+            EntityType::AngularThreePointDimension(AngularThreePointDimension {
+                dimension_base: base,
+                definition_point_2: dpoint(pts[1]),
+                definition_point_3: dpoint(pts[2]),
+                definition_point_4: dpoint(pts[0]),
+                ..Default::default()
+            })
+        }
+        DimKind::Radius => {
+            base.dimension_type = DimensionType::Radius;
+            base.definition_point_1 = dpoint(pts[0]);
+            EntityType::RadialDimension(RadialDimension {
+                dimension_base: base,
+                definition_point_2: dpoint(pts[1]),
+                ..Default::default()
+            })
+        }
+        DimKind::Diameter => {
+            // 10 — точка напротив 15 через центр
+            base.dimension_type = DimensionType::Diameter;
+            base.definition_point_1 = dpoint(Pt2::new(
+                2.0 * pts[0].x - pts[1].x,
+                2.0 * pts[0].y - pts[1].y,
+            ));
+            EntityType::DiameterDimension(DiameterDimension {
+                dimension_base: base,
+                definition_point_2: dpoint(pts[1]),
+                ..Default::default()
+            })
+        }
+        DimKind::Ordinate { x } => {
+            base.dimension_type = DimensionType::Ordinate;
+            base.is_ordinate_x_type = *x;
+            base.definition_point_1 = dpoint(pts[0]);
+            EntityType::OrdinateDimension(OrdinateDimension {
+                dimension_base: base,
+                definition_point_2: dpoint(pts[1]),
+                definition_point_3: dpoint(*line_pos),
+            })
         }
     }
+}
 
-    Ok(doc)
+/// DIMENSION → размер со свободными точками (привязки к объектам в DXF не храним).
+fn import_dimension(t: &EntityType) -> Option<EntityKind> {
+    let pt = |p: &DPoint| p2(p.x, p.y);
+    let base = match t {
+        EntityType::RotatedDimension(d) => &d.dimension_base,
+        EntityType::RadialDimension(d) => &d.dimension_base,
+        EntityType::DiameterDimension(d) => &d.dimension_base,
+        EntityType::AngularThreePointDimension(d) => &d.dimension_base,
+        EntityType::OrdinateDimension(d) => &d.dimension_base,
+        _ => return None,
+    };
+    let (kind, pts, line_pos) = match t {
+        EntityType::RotatedDimension(d) => {
+            let kind = match base.dimension_type {
+                DimensionType::Aligned => DimKind::Aligned,
+                _ => DimKind::Linear {
+                    angle: d.rotation_angle.to_radians() as f32,
+                },
+            };
+            let pts = vec![pt(&d.definition_point_2), pt(&d.definition_point_3)];
+            (kind, pts, pt(&base.definition_point_1))
+        }
+        EntityType::RadialDimension(d) => (
+            DimKind::Radius,
+            vec![pt(&base.definition_point_1), pt(&d.definition_point_2)],
+            pt(&base.text_mid_point),
+        ),
+        EntityType::DiameterDimension(d) => {
+            let (a, b) = (pt(&base.definition_point_1), pt(&d.definition_point_2));
+            let c = Pt2::new((a.x + b.x) * 0.5, (a.y + b.y) * 0.5);
+            (DimKind::Diameter, vec![c, b], pt(&base.text_mid_point))
+        }
+        EntityType::AngularThreePointDimension(d) => (
+            DimKind::Angular,
+            vec![
+                pt(&d.definition_point_4),
+                pt(&d.definition_point_2),
+                pt(&d.definition_point_3),
+            ],
+            pt(&base.definition_point_1),
+        ),
+        EntityType::OrdinateDimension(d) => (
+            DimKind::Ordinate {
+                x: base.is_ordinate_x_type,
+            },
+            vec![pt(&base.definition_point_1), pt(&d.definition_point_2)],
+            pt(&d.definition_point_3),
+        ),
+        _ => return None,
+    };
+    Some(EntityKind::Dimension {
+        kind,
+        pts: pts.into_iter().map(DimPoint::free).collect(),
+        line_pos,
+        text: if base.text == "<>" {
+            String::new()
+        } else {
+            base.text.clone()
+        },
+        style: base.dimension_style_name.clone(),
+    })
 }
 
-/// Экспорт Document → DXF (LINE, LWPOLYLINE, SPLINE, TEXT).
-pub fn export_dxf(doc: &Document, path: &str) -> Result<()> {
-    let mut drawing = Drawing::new();
+/// Стиль → DIMSTYLE (одноимённый, например STANDARD, перезаписывается). Из стрелок
+/// в dxf 0.6 выражается только засечка (DIMTSZ); остальные видны по блоку размера.
+fn export_dim_style(drawing: &mut Drawing, st: &DimStyle) {
+    let tick = if st.arrow == DimArrow::Tick {
+        st.arrow_size as f64
+    } else {
+        0.0
+    };
+    let fill = |d: &mut DDimStyle| {
+        d.dimensioning_suffix = if st.suffix.is_empty() {
+            String::new()
+        } else {
+            format!("<>{}", st.suffix)
+        };
+        d.dimensioning_arrow_size = st.arrow_size as f64;
+        d.dimensioning_tick_size = tick;
+        d.dimensioning_text_height = st.text_height as f64;
+        d.dimension_line_gap = st.text_gap as f64;
+        d.dimension_extension_line_offset = st.ext_offset as f64;
+        d.dimension_extension_line_extension = st.ext_extend as f64;
+        d.dimension_unit_tolerance_decimal_places = st.precision as i16;
+    };
+    if let Some(d) = drawing
+        .dim_styles_mut()
+        .find(|d| d.name.eq_ignore_ascii_case(&st.name))
+    {
+        fill(d);
+        return;
+    }
+    let mut d = DDimStyle {
+        name: st.name.clone(),
+        ..Default::default()
+    };
+    fill(&mut d);
+    drawing.add_dim_style(d);
+}
 
-    for ent in &doc.entities {
-        match &ent.kind {
-            EntityKind::LineSeg { a, b } => {
-                let dl = DLine::new(dpoint(*a), dpoint(*b));
-                let mut de = DEntity::new(EntityType::Line(dl));
-                de.common.layer = ent.layer.clone();
-                drawing.add_entity(de);
-            }
-            EntityKind::Polyline { pts, closed } => {
-                let mut pl = DLwPolyline::default();
-                pl.set_is_closed(*closed);
-                pl.vertices = pts
+fn import_dim_style(d: &DDimStyle) -> DimStyle {
+    DimStyle {
+        name: d.name.clone(),
+        arrow: if d.dimensioning_tick_size > 0.0 {
+            DimArrow::Tick
+        } else {
+            DimArrow::ClosedFilled
+        },
+        arrow_size: if d.dimensioning_tick_size > 0.0 {
+            d.dimensioning_tick_size as f32
+        } else {
+            d.dimensioning_arrow_size as f32
+        },
+        text_height: text_height(d.dimensioning_text_height),
+        text_gap: d.dimension_line_gap as f32,
+        ext_offset: d.dimension_extension_line_offset as f32,
+        ext_extend: d.dimension_extension_line_extension as f32,
+        precision: d.dimension_unit_tolerance_decimal_places.clamp(0, 8) as u8,
+        // DIMPOST: "<>" — место значения, суффикс — после него
+        suffix: match d.dimensioning_suffix.split_once("<>") {
+            Some((_, after)) => after.to_owned(),
+            None => d.dimensioning_suffix.clone(),
+        },
+    }
+}
+
+// --------------------------- HATCH ---------------------------
+// В dxf 0.6 нет HATCH: при чтении сущность молча пропускается, записать её нечем.
+// Читаем её сами по парам «код — значение», а при записи ставим на её место
+// точку-заглушку с общими свойствами и подменяем тело в готовом тексте.
+
+type Pairs = Vec<(i32, String)>;
+
+/// Тела сущностей, которых нет в dxf 0.6: HATCH — по номеру заглушки,
+/// ACAD_TABLE — по номеру блока *Tn.
+#[derive(Default)]
+struct Bodies {
+    hatches: Vec<Pairs>,
+    tables: Vec<Pairs>,
+}
+
+#[derive(Default)]
+struct PairWriter(Pairs);
+
+impl PairWriter {
+    fn put(&mut self, code: i32, v: impl std::fmt::Display) {
+        self.0.push((code, v.to_string()));
+    }
+}
+
+/// Тело HATCH после общих полей сущности (с подкласса AcDbHatch).
+fn hatch_pairs(loops: &[HatchLoop], fill: &HatchFill) -> Pairs {
+    let mut w = PairWriter::default();
+    w.put(100, "AcDbHatch");
+    for (code, v) in [
+        (10, 0.0),
+        (20, 0.0),
+        (30, 0.0),
+        (210, 0.0),
+        (220, 0.0),
+        (230, 1.0),
+    ] {
+        w.put(code, v);
+    }
+    w.put(
+        2,
+        match fill {
+            HatchFill::Solid => "SOLID",
+            HatchFill::Pattern { name, .. } => name.as_str(),
+        },
+    );
+    w.put(70, matches!(fill, HatchFill::Solid) as i32);
+    w.put(71, 0);
+    w.put(91, loops.len());
+    for (i, l) in loops.iter().enumerate() {
+        // 1 — внешний контур, 2 — контур-полилиния
+        let external = (i == 0) as i32;
+        match l.edges.as_slice() {
+            [EntityKind::Polyline {
+                pts,
+                closed: true,
+                bulges,
+            }] => {
+                let has_bulge = bulges.iter().any(|b| *b != 0.0);
+                w.put(92, external | 2);
+                w.put(72, has_bulge as i32);
+                w.put(73, 1);
+                w.put(93, pts.len());
+                for (k, p) in pts.iter().enumerate() {
+                    w.put(10, p.x);
+                    w.put(20, p.y);
+                    if has_bulge {
+                        w.put(42, bulges.get(k).copied().unwrap_or(0.0));
+                    }
+                }
+            }
+            edges => {
+                let edges: Vec<EntityKind> = edges
                     .iter()
-                    .enumerate()
-                    .map(|(i, p)| {
-                        let mut v = LwPolylineVertex::default();
-                        v.id = i as i32;
-                        v.x = p.x as f64;
-                        v.y = p.y as f64;
-                        v
+                    .flat_map(|e| match e {
+                        EntityKind::Polyline {
+                            pts,
+                            closed,
+                            bulges,
+                        } => polyline_segments(pts, bulges, *closed),
+                        e => vec![e.clone()],
+                    })
+                    .filter(|e| {
+                        matches!(
+                            e,
+                            EntityKind::LineSeg { .. }
+                                | EntityKind::Arc { .. }
+                                | EntityKind::Circle { .. }
+                                | EntityKind::Ellipse { .. }
+                                | EntityKind::NurbsCurve2D { .. }
+                        )
                     })
                     .collect();
-                let mut de = DEntity::new(EntityType::LwPolyline(pl));
-                de.common.layer = ent.layer.clone();
-                drawing.add_entity(de);
+                w.put(92, external);
+                w.put(93, edges.len());
+                for e in &edges {
+                    put_hatch_edge(&mut w, e);
+                }
+            }
+        }
+        w.put(97, 0);
+    }
+    w.put(75, 0);
+    match fill {
+        HatchFill::Solid => w.put(76, 1),
+        HatchFill::Pattern {
+            name,
+            angle,
+            scale,
+            lines,
+        } => {
+            // 1 — стандартный образец, 2 — пользовательский
+            let predefined = HatchFill::pattern(name, 0.0, 1.0).is_some();
+            w.put(76, if predefined { 1 } else { 2 });
+            w.put(52, angle.to_degrees());
+            w.put(41, scale);
+            w.put(77, 0);
+            w.put(78, lines.len());
+            // в DXF семейства уже повёрнуты и отмасштабированы — как у нас
+            for l in lines {
+                w.put(53, l.angle.to_degrees());
+                w.put(43, l.origin.x);
+                w.put(44, l.origin.y);
+                w.put(45, l.offset.x);
+                w.put(46, l.offset.y);
+                w.put(79, l.dashes.len());
+                for d in &l.dashes {
+                    w.put(49, d);
+                }
+            }
+        }
+    }
+    w.put(98, 0);
+    w.0
+}
+
+/// Углы дуги/эллипса для ребра: по часовой DXF хранит их с обратным знаком.
+fn edge_angles(a0: f32, a1: f32) -> (f64, f64, bool) {
+    let (a0, a1) = ((a0 as f64).to_degrees(), (a1 as f64).to_degrees());
+    let ccw = a1 >= a0;
+    let (s, e) = if ccw { (a0, a1) } else { (-a0, -a1) };
+    let s0 = s.rem_euclid(360.0);
+    (s0, s0 + (e - s), ccw)
+}
+
+fn put_hatch_edge(w: &mut PairWriter, e: &EntityKind) {
+    match e {
+        EntityKind::LineSeg { a, b } => {
+            w.put(72, 1);
+            w.put(10, a.x);
+            w.put(20, a.y);
+            w.put(11, b.x);
+            w.put(21, b.y);
+        }
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let (s, e, ccw) = edge_angles(*start_angle, *end_angle);
+            w.put(72, 2);
+            w.put(10, center.x);
+            w.put(20, center.y);
+            w.put(40, radius);
+            w.put(50, s);
+            w.put(51, e);
+            w.put(73, ccw as i32);
+        }
+        EntityKind::Circle { center, radius } => {
+            w.put(72, 2);
+            w.put(10, center.x);
+            w.put(20, center.y);
+            w.put(40, radius);
+            w.put(50, 0);
+            w.put(51, 360);
+            w.put(73, 1);
+        }
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            start_param,
+            end_param,
+        } => {
+            let (s, e, ccw) = edge_angles(*start_param, *end_param);
+            w.put(72, 3);
+            w.put(10, center.x);
+            w.put(20, center.y);
+            w.put(11, major.x);
+            w.put(21, major.y);
+            w.put(40, ratio);
+            w.put(50, s);
+            w.put(51, e);
+            w.put(73, ccw as i32);
+        }
+        EntityKind::NurbsCurve2D {
+            degree,
+            knots,
+            ctrl_pts,
+            weights,
+        } => {
+            w.put(72, 4);
+            w.put(94, degree);
+            w.put(73, weights.is_some() as i32);
+            w.put(74, 0);
+            w.put(95, knots.len());
+            w.put(96, ctrl_pts.len());
+            for k in knots {
+                w.put(40, k);
+            }
+            for (i, p) in ctrl_pts.iter().enumerate() {
+                w.put(10, p.x);
+                w.put(20, p.y);
+                if let Some(ws) = weights {
+                    w.put(42, ws.get(i).copied().unwrap_or(1.0));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_pair(out: &mut String, code: &str, value: &str) {
+    out.push_str(code);
+    out.push_str("\r\n");
+    out.push_str(value);
+    out.push_str("\r\n");
+}
+
+/// Заменить точки-заглушки на HATCH с телами `bodies` (номер тела — X точки).
+fn splice_hatches(text: &str, bodies: &[Pairs]) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = String::with_capacity(text.len() + bodies.len() * 512);
+    let push = push_pair;
+    let mut i = 0;
+    while i + 1 < lines.len() {
+        let (code, value) = (lines[i], lines[i + 1]);
+        i += 2;
+        if code.trim() == "0" && value.trim() == "POINT" {
+            // общие поля сущности — как есть, поля точки — долой
+            let start = i;
+            while i + 1 < lines.len()
+                && !(lines[i].trim() == "100" && lines[i + 1].trim() == "AcDbPoint")
+            {
+                i += 2;
+            }
+            let (common, fields) = (start..i, i);
+            i += 2;
+            let mut index = None;
+            while i + 1 < lines.len() && lines[i].trim() != "0" {
+                if lines[i].trim() == "10" {
+                    index = lines[i + 1].trim().parse::<f64>().ok();
+                }
+                i += 2;
+            }
+            let body = index
+                .filter(|x| *x >= 0.0 && x.fract() == 0.0)
+                .and_then(|x| bodies.get(x as usize));
+            push(&mut out, code, if body.is_some() { "HATCH" } else { value });
+            for k in common.step_by(2) {
+                push(&mut out, lines[k], lines[k + 1]);
+            }
+            match body {
+                Some(body) => {
+                    for (c, v) in body {
+                        push(&mut out, &format!("{c:>3}"), v);
+                    }
+                }
+                // чужая точка — вернуть как была
+                None => {
+                    for k in (fields..i).step_by(2) {
+                        push(&mut out, lines[k], lines[k + 1]);
+                    }
+                }
+            }
+            continue;
+        }
+        push(&mut out, code, value);
+    }
+    out
+}
+
+/// Все HATCH и ACAD_TABLE файла: (имя блока или `None` для чертежа, сущность).
+fn read_raw_entities(path: &str) -> Result<Vec<(Option<String>, Entity)>> {
+    let bytes = std::fs::read(path)?;
+    if bytes.starts_with(b"AutoCAD Binary DXF") {
+        return Ok(vec![]);
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = text.lines().collect();
+    let pairs: Pairs = lines
+        .chunks_exact(2)
+        .filter_map(|c| Some((c[0].trim().parse().ok()?, c[1].trim().to_owned())))
+        .collect();
+
+    let mut out = Vec::new();
+    let (mut section, mut block) = (String::new(), None::<String>);
+    let mut i = 0;
+    while i < pairs.len() {
+        if pairs[i].0 != 0 {
+            i += 1;
+            continue;
+        }
+        let end = pairs[i + 1..]
+            .iter()
+            .position(|p| p.0 == 0)
+            .map_or(pairs.len(), |k| i + 1 + k);
+        let body = &pairs[i + 1..end];
+        let name = || body.iter().find(|p| p.0 == 2).map(|p| p.1.clone());
+        match pairs[i].1.as_str() {
+            "SECTION" => section = name().unwrap_or_default(),
+            "BLOCK" => block = name(),
+            "ENDBLK" => block = None,
+            kind @ ("HATCH" | "ACAD_TABLE") => {
+                let owner = match section.as_str() {
+                    "ENTITIES" => Some(None),
+                    "BLOCKS" => block
+                        .as_ref()
+                        .filter(|b| !is_layout_block(b))
+                        .map(|b| Some(decode_dxf_text(b))),
+                    _ => None,
+                };
+                let parsed = match kind {
+                    "HATCH" => parse_hatch(body),
+                    _ => parse_table(body),
+                };
+                if let (Some(owner), Some(e)) = (owner, parsed) {
+                    out.push((owner, e));
+                }
+            }
+            _ => {}
+        }
+        i = end;
+    }
+    Ok(out)
+}
+
+/// Последовательное чтение пар с пропуском незнакомых кодов.
+struct PairReader<'a> {
+    pairs: &'a [(i32, String)],
+    i: usize,
+}
+
+impl<'a> PairReader<'a> {
+    fn peek(&self, k: usize) -> Option<i32> {
+        self.pairs.get(self.i + k).map(|p| p.0)
+    }
+
+    /// Значение ближайшей пары с кодом `code`.
+    fn find(&mut self, code: i32) -> Option<&'a str> {
+        while let Some((c, v)) = self.pairs.get(self.i) {
+            self.i += 1;
+            if *c == code {
+                return Some(v);
+            }
+        }
+        None
+    }
+
+    fn f(&mut self, code: i32) -> Option<f64> {
+        self.find(code)?.parse().ok()
+    }
+
+    fn n(&mut self, code: i32) -> Option<usize> {
+        self.find(code)?
+            .parse::<i64>()
+            .ok()
+            .map(|v| v.max(0) as usize)
+    }
+
+    /// Пара с кодом `code`, только если она следующая.
+    fn opt_f(&mut self, code: i32) -> Option<f64> {
+        (self.peek(0) == Some(code)).then(|| self.f(code))?
+    }
+}
+
+/// Общие свойства сущности из сырых пар.
+struct RawCommon {
+    layer: String,
+    linetype: String,
+    color: i32,
+    true_color: i32,
+    weight: Option<i16>,
+}
+
+impl RawCommon {
+    /// Читать до подкласса `subclass` включительно.
+    fn read(r: &mut PairReader, subclass: &str) -> Self {
+        let mut c = RawCommon {
+            layer: "0".into(),
+            linetype: "BYLAYER".into(),
+            color: 256,
+            true_color: 0,
+            weight: None,
+        };
+        while let Some((code, v)) = r.pairs.get(r.i) {
+            r.i += 1;
+            match code {
+                8 => c.layer = decode_dxf_text(v),
+                6 => c.linetype = decode_dxf_text(v),
+                62 => c.color = v.parse().unwrap_or(256),
+                420 => c.true_color = v.parse().unwrap_or(0),
+                370 => c.weight = v.parse().ok(),
+                100 if v == subclass => break,
+                _ => {}
+            }
+        }
+        c
+    }
+
+    fn entity(self, kind: EntityKind) -> Entity {
+        Entity {
+            color: color_from_raw(self.color, self.true_color),
+            linetype: import_linetype(&self.linetype),
+            lineweight: self
+                .weight
+                .map_or(LineWeight::ByLayer, LineWeight::from_dxf),
+            ..Entity::new(self.layer, kind)
+        }
+    }
+}
+
+fn parse_hatch(body: &[(i32, String)]) -> Option<Entity> {
+    let mut r = PairReader { pairs: body, i: 0 };
+    let common = RawCommon::read(&mut r, "AcDbHatch");
+
+    let name = r.find(2)?.to_owned();
+    let solid = r.n(70)? != 0;
+    let mut loops = Vec::new();
+    for _ in 0..r.n(91)? {
+        let flags = r.n(92)?;
+        if flags & 2 != 0 {
+            let has_bulge = r.n(72)? != 0;
+            r.n(73)?;
+            let (mut pts, mut bulges) = (Vec::new(), Vec::new());
+            for _ in 0..r.n(93)? {
+                pts.push(p2(r.f(10)?, r.f(20)?));
+                bulges.push(if has_bulge {
+                    r.opt_f(42).unwrap_or(0.0) as f32
+                } else {
+                    0.0
+                });
+            }
+            if bulges.iter().all(|b| *b == 0.0) {
+                bulges.clear();
+            }
+            loops.push(HatchLoop::polyline(pts, bulges));
+        } else {
+            let mut edges = Vec::new();
+            for _ in 0..r.n(93)? {
+                edges.push(parse_hatch_edge(&mut r)?);
+            }
+            loops.push(HatchLoop { edges });
+        }
+        // ссылки на исходные объекты ассоциативной штриховки
+        for _ in 0..r.n(97)? {
+            r.find(330)?;
+        }
+    }
+    loops.retain(|l| !l.edges.is_empty());
+    if loops.is_empty() {
+        return None;
+    }
+
+    let fill = if solid {
+        HatchFill::Solid
+    } else {
+        r.n(76)?;
+        let angle = r.f(52).unwrap_or(0.0).to_radians() as f32;
+        let scale = r.f(41).unwrap_or(1.0) as f32;
+        let mut lines = Vec::new();
+        if r.peek(0) == Some(77) {
+            r.i += 1;
+        }
+        if r.peek(0) == Some(78) {
+            for _ in 0..r.n(78)? {
+                let a = r.f(53)?.to_radians() as f32;
+                let origin = p2(r.f(43)?, r.f(44)?);
+                let offset = p2(r.f(45)?, r.f(46)?);
+                let mut dashes = Vec::new();
+                for _ in 0..r.n(79)? {
+                    dashes.push(r.f(49)? as f32);
+                }
+                lines.push(HatchLine {
+                    angle: a,
+                    origin,
+                    offset,
+                    dashes,
+                });
+            }
+        }
+        if lines.is_empty() {
+            // без описания линий — из своей библиотеки по имени
+            HatchFill::pattern(&name, angle, scale)?
+        } else {
+            HatchFill::Pattern {
+                name,
+                angle,
+                scale,
+                lines,
+            }
+        }
+    };
+
+    Some(common.entity(EntityKind::Hatch { loops, fill }))
+}
+
+fn parse_hatch_edge(r: &mut PairReader) -> Option<EntityKind> {
+    // углы дуг по часовой записаны с обратным знаком
+    let angles = |s: f64, e: f64, ccw: bool| -> (f32, f32) {
+        let (mut a0, mut a1) = if ccw { (s, e) } else { (-s, -e) };
+        a0 = a0.to_radians();
+        a1 = a1.to_radians();
+        if ccw {
+            while a1 <= a0 {
+                a1 += TAU;
+            }
+        } else {
+            while a1 >= a0 {
+                a1 -= TAU;
+            }
+        }
+        (a0 as f32, a1 as f32)
+    };
+    let kind = match r.n(72)? {
+        1 => EntityKind::LineSeg {
+            a: p2(r.f(10)?, r.f(20)?),
+            b: p2(r.f(11)?, r.f(21)?),
+        },
+        2 => {
+            let center = p2(r.f(10)?, r.f(20)?);
+            let radius = r.f(40)? as f32;
+            let (s, e) = (r.f(50)?, r.f(51)?);
+            let ccw = r.n(73)? != 0;
+            if (e - s).abs() >= 360.0 - 1e-9 {
+                EntityKind::Circle { center, radius }
+            } else {
+                let (start_angle, end_angle) = angles(s, e, ccw);
+                EntityKind::Arc {
+                    center,
+                    radius,
+                    start_angle,
+                    end_angle,
+                }
+            }
+        }
+        3 => {
+            let center = p2(r.f(10)?, r.f(20)?);
+            let major = p2(r.f(11)?, r.f(21)?);
+            let ratio = r.f(40)? as f32;
+            let (s, e) = (r.f(50)?, r.f(51)?);
+            let (start_param, end_param) = angles(s, e, r.n(73)? != 0);
+            EntityKind::Ellipse {
+                center,
+                major,
+                ratio,
+                start_param,
+                end_param,
+            }
+        }
+        4 => {
+            let degree = r.n(94)?;
+            let rational = r.n(73)? != 0;
+            r.n(74)?;
+            let (nk, nc) = (r.n(95)?, r.n(96)?);
+            let knots = (0..nk).map(|_| r.f(40)).collect::<Option<Vec<f64>>>()?;
+            let (mut ctrl_pts, mut weights) = (Vec::new(), Vec::new());
+            for _ in 0..nc {
+                ctrl_pts.push(p2(r.f(10)?, r.f(20)?));
+                if rational {
+                    weights.push(r.opt_f(42).unwrap_or(1.0));
+                }
+            }
+            // с R2010 — опорные точки и касательные; отличаем от счётчика 97 после рёбер
+            if r.peek(0) == Some(97) && matches!(r.peek(1), Some(11 | 12 | 72 | 97)) {
+                r.i += 1;
+                while matches!(r.peek(0), Some(11 | 21 | 12 | 22 | 13 | 23)) {
+                    r.i += 1;
+                }
             }
             EntityKind::NurbsCurve2D {
                 degree,
                 knots,
                 ctrl_pts,
-                weights,
-            } => {
-                let mut sp = DSpline::default();
-                sp.degree_of_curve = *degree as i32;
-                sp.knot_values = knots.clone();
-                sp.control_points = ctrl_pts.iter().map(|p| dpoint(*p)).collect();
-                if let Some(w) = weights {
-                    sp.weight_values = w.clone();
-                    sp.set_is_rational(true);
-                } else {
-                    sp.weight_values.clear();
-                    sp.set_is_rational(false);
+                weights: rational.then_some(weights),
+            }
+        }
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Коды 62/420 в сыром виде: 256 — ПоСлою, 0 — ПоБлоку.
+fn color_from_raw(index: i32, true_color: i32) -> Color {
+    match index {
+        0 => Color::ByBlock,
+        i if (1..=255).contains(&i.abs()) => {
+            if true_color > 0 {
+                let [_, r, g, b] = true_color.to_be_bytes();
+                Color::Rgb([r, g, b])
+            } else {
+                Color::Aci(i.unsigned_abs() as u8)
+            }
+        }
+        _ => Color::ByLayer,
+    }
+}
+
+/// Экранирование \U+XXXX, которым dxf-крейт пишет не-ASCII.
+fn decode_dxf_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(k) = rest.find("\\U+") {
+        out.push_str(&rest[..k]);
+        let hex = rest.get(k + 3..k + 7).unwrap_or("");
+        match u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+            Some(ch) if hex.len() == 4 => {
+                out.push(ch);
+                rest = &rest[k + 7..];
+            }
+            _ => {
+                out.push_str("\\U+");
+                rest = &rest[k + 3..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// --------------------------- ACAD_TABLE ---------------------------
+// ACAD_TABLE в dxf 0.6 тоже нет. Пишем её как INSERT анонимного блока *Tn с сеткой
+// и текстами (его покажет и программа без поддержки таблиц), а в готовом тексте
+// переименовываем вставку и дописываем сетку и ячейки подкласса AcDbTable.
+
+/// Таблица → вставка блока *Tn с её отрезками и текстами на слое 0 со свойствами ПоБлоку.
+fn export_table(
+    drawing: &mut Drawing,
+    doc: &Document,
+    bodies: &mut Bodies,
+    pos: Pt2,
+    table: &Table,
+) -> EntityType {
+    bodies.tables.push(table_pairs(table));
+    let name = format!("*T{}", bodies.tables.len());
+    let entities = table_geometry(table, Pt2::new(0.0, 0.0))
+        .into_iter()
+        .map(|k| Entity {
+            color: Color::ByBlock,
+            linetype: LINETYPE_BYBLOCK.into(),
+            lineweight: LineWeight::ByBlock,
+            ..Entity::new("0", k)
+        })
+        .map(|e| export_entity(drawing, doc, bodies, &e))
+        .collect();
+    let mut block = DBlock {
+        name: name.clone(),
+        entities,
+        ..Default::default()
+    };
+    block.set_is_anonymous(true);
+    drawing.add_block(block);
+    EntityType::Insert(DInsert {
+        name,
+        location: dpoint(pos),
+        ..Default::default()
+    })
+}
+
+/// Тело ACAD_TABLE после полей вставки: сетка по раскладке и ячейки по строкам.
+/// Хэндл записи блока (343) вставит splice_tables сразу после флага 280.
+fn table_pairs(table: &Table) -> Pairs {
+    let lay = table.layout();
+    let (nr, nc) = (lay.heights.len(), lay.widths.len());
+    let mut w = PairWriter::default();
+    w.put(100, "AcDbTable");
+    w.put(280, 0);
+    for (code, v) in [(11, 1.0), (21, 0.0), (31, 0.0)] {
+        w.put(code, v);
+    }
+    w.put(90, 0);
+    w.put(91, nr);
+    w.put(92, nc);
+    for code in 93..=96 {
+        w.put(code, 0);
+    }
+    for h in &lay.heights {
+        w.put(141, h);
+    }
+    for width in &lay.widths {
+        w.put(142, width);
+    }
+    let (_, owner) = table.merge_grid();
+    for (r, own) in owner.iter().enumerate() {
+        for (c, merged) in own.iter().enumerate() {
+            let cell = lay.cells.iter().find(|x| (x.row, x.col) == (r, c));
+            let (rows, cols) = cell.map_or((0, 0), |x| (x.rows, x.cols));
+            // 4, 5, 6 — посередине слева, по центру, справа
+            let attach = match cell.map_or(CellAlign::Center, |x| x.align) {
+                CellAlign::Left => 4,
+                CellAlign::Center => 5,
+                CellAlign::Right => 6,
+            };
+            w.put(171, 1);
+            w.put(172, 0);
+            w.put(173, merged.is_some() as i32);
+            w.put(174, 0);
+            w.put(175, cols);
+            w.put(176, rows);
+            w.put(91, 0);
+            w.put(178, 0);
+            w.put(145, 0.0);
+            w.put(170, attach);
+            w.put(140, table.text_height);
+            let text = if cell.is_some() { table.text(r, c) } else { "" };
+            w.put(1, encode_dxf_text(&text.replace('\n', "\\P")));
+        }
+    }
+    w.0
+}
+
+/// Вставки блоков *Tn → ACAD_TABLE с телом `tables[n - 1]`.
+fn splice_tables(text: &str, tables: &[Pairs]) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let pair = |i: usize| (lines[i].trim(), lines[i + 1]);
+    // конец сущности, начатой парой `i`
+    let end_of = |i: usize| {
+        let mut k = i + 2;
+        while k + 1 < lines.len() && lines[k].trim() != "0" {
+            k += 2;
+        }
+        k
+    };
+
+    // хэндлы записей блоков по именам
+    let mut records = std::collections::HashMap::new();
+    let mut i = 0;
+    while i + 1 < lines.len() {
+        if pair(i) == ("0", "BLOCK_RECORD") {
+            let end = end_of(i);
+            let field = |code: &str| {
+                (i + 2..end)
+                    .step_by(2)
+                    .find(|&k| lines[k].trim() == code)
+                    .map(|k| lines[k + 1].trim())
+            };
+            if let (Some(handle), Some(name)) = (field("5"), field("2")) {
+                records.insert(name, handle);
+            }
+            i = end;
+        } else {
+            i += 2;
+        }
+    }
+
+    let mut out = String::with_capacity(text.len() + tables.len() * 2048);
+    let mut i = 0;
+    while i + 1 < lines.len() {
+        let (code, value) = pair(i);
+        if (code, value.trim()) != ("0", "INSERT") {
+            push_pair(&mut out, lines[i], value);
+            i += 2;
+            continue;
+        }
+        let end = end_of(i);
+        let name = (i + 2..end)
+            .step_by(2)
+            .find(|&k| lines[k].trim() == "2")
+            .map(|k| lines[k + 1].trim());
+        let body = name
+            .filter(|n| is_table_block(n))
+            .and_then(|n| n[2..].parse::<usize>().ok())
+            .and_then(|n| tables.get(n.checked_sub(1)?));
+        push_pair(
+            &mut out,
+            lines[i],
+            if body.is_some() { "ACAD_TABLE" } else { value },
+        );
+        for k in (i + 2..end).step_by(2) {
+            push_pair(&mut out, lines[k], lines[k + 1]);
+        }
+        if let Some(body) = body {
+            for (n, (c, v)) in body.iter().enumerate() {
+                push_pair(&mut out, &format!("{c:>3}"), v);
+                if n == 1 {
+                    if let Some(handle) = name.and_then(|n| records.get(n)) {
+                        push_pair(&mut out, "343", handle);
+                    }
                 }
+            }
+        }
+        i = end;
+    }
+    out
+}
 
-                let mut de = DEntity::new(EntityType::Spline(sp));
-                de.common.layer = ent.layer.clone();
-                drawing.add_entity(de);
+fn parse_table(body: &[(i32, String)]) -> Option<Entity> {
+    let mut r = PairReader { pairs: body, i: 0 };
+    let common = RawCommon::read(&mut r, "AcDbBlockReference");
+    let pos = p2(r.f(10)?, r.f(20)?);
+    while r.find(100)? != "AcDbTable" {}
+    let (nr, nc) = (r.n(91)?, r.n(92)?);
+    let heights = (0..nr).map(|_| r.f(141)).collect::<Option<Vec<f64>>>()?;
+    let widths = (0..nc).map(|_| r.f(142)).collect::<Option<Vec<f64>>>()?;
+
+    let mut table = Table {
+        rows: vec![vec![String::new(); nc]; nr],
+        columns: widths.iter().map(|w| *w as f32).collect(),
+        row_height: heights.iter().copied().fold(f64::MAX, f64::min) as f32,
+        ..Default::default()
+    };
+    // выравнивание ячеек по строкам: шапку и столбцы выведем из него
+    let mut aligns = vec![vec![None; nc]; nr];
+    let mut text_height = None;
+    for (row, row_aligns) in aligns.iter_mut().enumerate() {
+        for (col, align) in row_aligns.iter_mut().enumerate() {
+            r.find(171)?;
+            let (mut rows, mut cols) = (1, 1);
+            while let Some((c, v)) = r.pairs.get(r.i).filter(|p| p.0 != 171) {
+                r.i += 1;
+                match c {
+                    175 => cols = v.parse().unwrap_or(1),
+                    176 => rows = v.parse().unwrap_or(1),
+                    170 => {
+                        *align = match v.as_str() {
+                            "1" | "4" | "7" => Some(CellAlign::Left),
+                            "3" | "6" | "9" => Some(CellAlign::Right),
+                            _ => Some(CellAlign::Center),
+                        }
+                    }
+                    140 => text_height = text_height.or(v.parse::<f32>().ok()),
+                    1 => table.rows[row][col] = decode_dxf_text(v).replace("\\P", "\n"),
+                    _ => {}
+                }
             }
-            EntityKind::Arc { .. } => {
-                // DXF Arc можно добавить позже
+            if rows * cols > 1 {
+                table.merges.push(CellMerge {
+                    row,
+                    col,
+                    rows,
+                    cols,
+                });
             }
-            EntityKind::Text {
-                pos,
-                content,
-                height,
-            } => {
-                let mut t = DText::default();
-                t.location = dpoint(*pos);
-                t.value = content.clone();
-                t.text_height = (*height).max(0.1) as f64;
-                let mut de = DEntity::new(EntityType::Text(t));
-                de.common.layer = ent.layer.clone();
-                drawing.add_entity(de);
+        }
+    }
+    // ячейки под объединением
+    for m in &table.merges {
+        for (rr, row) in aligns.iter_mut().enumerate().skip(m.row).take(m.rows) {
+            for (cc, a) in row.iter_mut().enumerate().skip(m.col).take(m.cols) {
+                if (rr, cc) != (m.row, m.col) {
+                    *a = None;
+                }
             }
         }
     }
+    let centered =
+        |row: &Vec<Option<CellAlign>>| row.iter().flatten().all(|a| *a == CellAlign::Center);
+    // шапка — ведущие строки по центру, если ниже есть другое выравнивание
+    let lead = aligns.iter().take_while(|row| centered(row)).count();
+    if lead < nr {
+        table.header_rows = lead;
+    }
+    table.align = (0..nc)
+        .map(|c| {
+            aligns[table.header_rows..]
+                .iter()
+                .rev()
+                .find_map(|row| row[c])
+                .unwrap_or_default()
+        })
+        .collect();
+    table.text_height = text_height.unwrap_or(table.text_height);
+    Some(common.entity(EntityKind::Table { pos, table }))
+}
 
-    drawing.save_file(path)?;
-    Ok(())
+/// Не-ASCII — экранами \U+XXXX, как пишет dxf-крейт.
+fn encode_dxf_text(s: &str) -> String {
+    s.chars()
+        .map(|ch| match ch {
+            ' '..='~' => ch.to_string(),
+            _ => format!("\\U+{:04X}", ch as u32),
+        })
+        .collect()
 }
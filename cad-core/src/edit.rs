@@ -0,0 +1,890 @@
+//! Правка кривых в «родных» параметрах сущностей: кусок кривой, обрезка по пересечениям,
+//! продление конца до границы, сшивка кусков в полилинию.
+//!
+//! Параметр: у отрезка 0..1, у дуги и окружности — угол (рад), у эллипса — его параметр,
+//! у полилинии — номер сегмента плюс доля (у дугового — доля угла), у NURBS — узловой.
+
+use crate::{bulge_arc, ellipse_point, intersect, EntityKind, Pt2, TruckCurve2};
+use cryxtal_geometry::prelude::*;
+use std::f64::consts::TAU;
+
+/// Совпадение концов при сшивке.
+pub const JOIN_TOL: f32 = 1e-4;
+
+// --------------------------- параметры ---------------------------
+
+/// Область параметра кривой; `None` для не-кривых.
+pub fn curve_range(kind: &EntityKind) -> Option<(f64, f64)> {
+    match kind {
+        EntityKind::LineSeg { .. } => Some((0.0, 1.0)),
+        EntityKind::Arc {
+            start_angle,
+            end_angle,
+            ..
+        } => Some(arc_range(*start_angle, *end_angle)),
+        EntityKind::Circle { .. } => Some((0.0, TAU)),
+        EntityKind::Ellipse {
+            start_param,
+            end_param,
+            ..
+        } => Some(arc_range(*start_param, *end_param)),
+        EntityKind::Polyline { pts, closed, .. } => {
+            let n = segment_count(pts.len(), *closed);
+            (n > 0).then_some((0.0, n as f64))
+        }
+        EntityKind::NurbsCurve2D { .. } => kind.to_truck().map(|c| c.range_tuple()),
+        _ => None,
+    }
+}
+
+/// Замкнутая кривая: окружность, полный эллипс, замкнутая полилиния, NURBS с совпадающими
+/// концами.
+pub fn is_closed_curve(kind: &EntityKind) -> bool {
+    match kind {
+        EntityKind::Circle { .. } => true,
+        EntityKind::Ellipse {
+            start_param,
+            end_param,
+            ..
+        } => (end_param - start_param).abs() as f64 >= TAU - 1e-4,
+        EntityKind::Polyline { pts, closed, .. } => *closed && pts.len() > 2,
+        EntityKind::NurbsCurve2D { .. } => match curve_range(kind) {
+            Some((t0, t1)) => match (curve_at(kind, t0), curve_at(kind, t1)) {
+                (Some(a), Some(b)) => dist(a, b) <= JOIN_TOL,
+                _ => false,
+            },
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Точка кривой при параметре `t`.
+pub fn curve_at(kind: &EntityKind, t: f64) -> Option<Pt2> {
+    match kind {
+        EntityKind::LineSeg { a, b } => Some(lerp(*a, *b, t)),
+        EntityKind::Arc { center, radius, .. } | EntityKind::Circle { center, radius } => {
+            Some(on_circle(*center, *radius, t))
+        }
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            ..
+        } => Some(ellipse_point(*center, *major, *ratio, t as f32)),
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => {
+            let n = segment_count(pts.len(), *closed);
+            if n == 0 {
+                return pts.first().copied();
+            }
+            let i = (t.floor().max(0.0) as usize).min(n - 1);
+            Some(segment_at(pts, bulges, i, t - i as f64))
+        }
+        EntityKind::NurbsCurve2D { .. } => kind.to_truck().map(|c| Pt2::from(c.subs(t))),
+        _ => None,
+    }
+}
+
+/// Параметр ближайшей к `p` точки кривой.
+pub fn curve_param(kind: &EntityKind, p: Pt2) -> Option<f64> {
+    match kind {
+        EntityKind::LineSeg { a, b } => {
+            let (d, w) = (sub(*b, *a), sub(p, *a));
+            let l2 = dot(d, d);
+            Some(if l2 > 0.0 {
+                (dot(w, d) / l2).clamp(0.0, 1.0) as f64
+            } else {
+                0.0
+            })
+        }
+        EntityKind::Arc { center, .. } | EntityKind::Circle { center, .. } => {
+            let range = curve_range(kind)?;
+            let a = ((p.y - center.y) as f64).atan2((p.x - center.x) as f64);
+            Some(angle_in(a, range))
+        }
+        EntityKind::Ellipse { center, major, .. } => {
+            let range = curve_range(kind)?;
+            // в осях эллипса точка на нём — (cos t, sin t)
+            let minor = perp(*major);
+            let d = sub(p, *center);
+            let (u, v) = (dot(d, *major), dot(d, minor));
+            let EntityKind::Ellipse { ratio, .. } = kind else {
+                unreachable!()
+            };
+            // начало — лучшее из проекции и грубой выборки, дальше Ньютоном к ближайшей
+            // точке (у вытянутого эллипса локальных минимумов расстояния несколько)
+            let a = (dot(*major, *major) as f64).sqrt();
+            let b = a * *ratio as f64;
+            let (x, y) = (u as f64 / a.max(1e-30), v as f64 / a.max(1e-30));
+            let d2 = |t: f64| (a * t.cos() - x).powi(2) + (b * t.sin() - y).powi(2);
+            let mut t = (0..32)
+                .map(|i| i as f64 * TAU / 32.0)
+                .chain([(v / ratio.max(1e-9)).atan2(u) as f64])
+                .min_by(|s, t| d2(*s).total_cmp(&d2(*t)))
+                .unwrap_or(0.0);
+            for _ in 0..16 {
+                let (sn, cs) = t.sin_cos();
+                let g = (b * b - a * a) * sn * cs + a * x * sn - b * y * cs;
+                let dg = (b * b - a * a) * (cs * cs - sn * sn) + a * x * cs + b * y * sn;
+                if dg <= 0.0 {
+                    break;
+                }
+                let step = (g / dg).clamp(-0.5, 0.5);
+                t -= step;
+                if step.abs() < 1e-12 {
+                    break;
+                }
+            }
+            Some(angle_in(t, range))
+        }
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => {
+            let n = segment_count(pts.len(), *closed);
+            (0..n)
+                .map(|i| {
+                    let f = segment_param(pts, bulges, i, p);
+                    (i as f64 + f, dist(segment_at(pts, bulges, i, f), p))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(t, _)| t)
+        }
+        EntityKind::NurbsCurve2D { .. } => {
+            let c = kind.to_truck()?;
+            let q = Point2::from(p);
+            let hint = c.sample_params().into_iter().min_by(|a, b| {
+                (c.subs(*a) - q)
+                    .magnitude2()
+                    .total_cmp(&(c.subs(*b) - q).magnitude2())
+            })?;
+            let (t0, t1) = c.range_tuple();
+            let t = match &c {
+                TruckCurve2::BSpline(b) => b.search_nearest_parameter(q, Some(hint), 32),
+                TruckCurve2::Nurbs(n) => n.search_nearest_parameter(q, Some(hint), 32),
+            }
+            .unwrap_or(hint);
+            Some(t.clamp(t0, t1))
+        }
+        _ => None,
+    }
+}
+
+/// Параметры точек пересечения кривой с `cutters`, по возрастанию без повторов.
+pub fn cut_params(kind: &EntityKind, cutters: &[EntityKind]) -> Vec<f64> {
+    let nurbs = matches!(kind, EntityKind::NurbsCurve2D { .. });
+    let mut ts: Vec<f64> = cutters
+        .iter()
+        .flat_map(|c| intersect(kind, c))
+        .filter_map(|x| {
+            if nurbs {
+                Some(x.t_a)
+            } else {
+                curve_param(kind, x.point)
+            }
+        })
+        .collect();
+    ts.sort_by(f64::total_cmp);
+    ts.dedup_by(|a, b| (*a - *b).abs() <= 1e-7);
+    ts
+}
+
+// --------------------------- куски ---------------------------
+
+/// Кусок кривой между параметрами. У замкнутых `t1 <= t0` — проход через шов
+/// (окружность даёт дугу, полный эллипс — эллиптическую дугу, полилиния — открытую).
+pub fn sub_curve(kind: &EntityKind, t0: f64, t1: f64) -> Option<EntityKind> {
+    let closed = is_closed_curve(kind);
+    let (r0, r1) = curve_range(kind)?;
+    let t1 = if closed && t1 <= t0 {
+        t1 + (r1 - r0)
+    } else {
+        t1
+    };
+    if t1 - t0 <= 1e-9 {
+        return None;
+    }
+    match kind {
+        EntityKind::LineSeg { a, b } => Some(EntityKind::LineSeg {
+            a: lerp(*a, *b, t0),
+            b: lerp(*a, *b, t1),
+        }),
+        EntityKind::Arc { center, radius, .. } | EntityKind::Circle { center, radius } => {
+            Some(EntityKind::Arc {
+                center: *center,
+                radius: *radius,
+                start_angle: t0 as f32,
+                end_angle: t1 as f32,
+            })
+        }
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            ..
+        } => Some(EntityKind::Ellipse {
+            center: *center,
+            major: *major,
+            ratio: *ratio,
+            start_param: t0 as f32,
+            end_param: t1 as f32,
+        }),
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => {
+            let n = segment_count(pts.len(), *closed);
+            let at = |t: f64| {
+                let i = (t.floor().max(0.0) as usize).min(2 * n - 1);
+                segment_at(pts, bulges, i % n, t - i as f64)
+            };
+            let mut out = vec![at(t0)];
+            let mut out_bulges = Vec::new();
+            let mut t = t0;
+            while t < t1 - 1e-9 {
+                let i = t.floor();
+                let end = (i + 1.0).min(t1);
+                let b = bulges.get(i as usize % n).copied().unwrap_or(0.0) as f64;
+                out_bulges.push((b.atan() * (end - t)).tan() as f32);
+                out.push(if end < i + 1.0 {
+                    at(end)
+                } else {
+                    pts[(i as usize + 1) % pts.len()]
+                });
+                t = end;
+            }
+            if out_bulges.iter().all(|b| *b == 0.0) {
+                out_bulges.clear();
+            }
+            Some(EntityKind::Polyline {
+                pts: out,
+                closed: false,
+                bulges: out_bulges,
+            })
+        }
+        EntityKind::NurbsCurve2D { .. } => {
+            let (h, rational) = homogeneous(kind)?;
+            let piece = if t1 > r1 + 1e-12 {
+                // замкнутый NURBS через шов: [t0, r1] + [r0, t1 - период]
+                let a = bspline_piece(&h, t0, r1);
+                let mut b = bspline_piece(&h, r0, t1 - (r1 - r0));
+                b.knot_translate(r1 - r0);
+                a.try_concat(&b).ok()?
+            } else {
+                bspline_piece(&h, t0, t1)
+            };
+            Some(from_homogeneous(&piece, rational))
+        }
+        _ => None,
+    }
+}
+
+/// Обрезка кривой по пересечениям с `cutters`: удаляется участок между соседними точками
+/// пересечения, содержащий `pick`. Возвращает оставшиеся куски (0–2) или `None`, если
+/// обрезать нечего.
+pub fn trim_curve(kind: &EntityKind, cutters: &[EntityKind], pick: Pt2) -> Option<Vec<EntityKind>> {
+    let (r0, r1) = curve_range(kind)?;
+    let tp = curve_param(kind, pick)?;
+    let eps = 1e-7 * (r1 - r0).max(1.0);
+    let cuts: Vec<f64> = cut_params(kind, cutters)
+        .into_iter()
+        .filter(|t| *t > r0 + eps && *t < r1 - eps || is_closed_curve(kind))
+        .collect();
+
+    if is_closed_curve(kind) {
+        // по кругу: участок между соседними точками, нужен хотя бы один кусок
+        if cuts.len() < 2 {
+            return None;
+        }
+        let lo = cuts
+            .iter()
+            .rev()
+            .find(|t| **t < tp)
+            .or(cuts.last())
+            .copied()?;
+        let hi = cuts.iter().find(|t| **t > tp).or(cuts.first()).copied()?;
+        return Some(sub_curve(kind, hi, lo).into_iter().collect());
+    }
+
+    let lo = cuts.iter().rev().find(|t| **t < tp).copied();
+    let hi = cuts.iter().find(|t| **t > tp).copied();
+    if lo.is_none() && hi.is_none() {
+        return None;
+    }
+    let mut out = Vec::new();
+    if let Some(lo) = lo {
+        out.extend(sub_curve(kind, r0, lo));
+    }
+    if let Some(hi) = hi {
+        out.extend(sub_curve(kind, hi, r1));
+    }
+    Some(out)
+}
+
+/// Разбить кривую в точке, ближайшей к `p`: два куска у открытой, один открытый
+/// с началом в этой точке у замкнутой полилинии/NURBS. `None` на концах, для окружности
+/// и полного эллипса (дуга в 360° не бывает).
+pub fn split_curve(kind: &EntityKind, p: Pt2) -> Option<Vec<EntityKind>> {
+    let (r0, r1) = curve_range(kind)?;
+    let t = curve_param(kind, p)?;
+    if is_closed_curve(kind) {
+        return match kind {
+            EntityKind::Polyline { .. } | EntityKind::NurbsCurve2D { .. } => {
+                let t = if t >= r1 - 1e-9 { r0 } else { t };
+                let open = if t <= r0 + 1e-9 {
+                    sub_curve(kind, r0, r1)
+                } else {
+                    sub_curve(kind, t, t)
+                };
+                open.map(|k| vec![k])
+            }
+            _ => None,
+        };
+    }
+    let eps = 1e-7 * (r1 - r0).max(1.0);
+    if t <= r0 + eps || t >= r1 - eps {
+        return None;
+    }
+    Some(vec![sub_curve(kind, r0, t)?, sub_curve(kind, t, r1)?])
+}
+
+// --------------------------- продление ---------------------------
+
+/// Продлить ближайший к `pick` конец открытой кривой до первого пересечения с `bounds`
+/// (отрезок и прямой сегмент полилинии — по прямой, дуги — по своей окружности,
+/// эллиптическая дуга — по эллипсу, NURBS — продолжением крайнего полинома).
+/// `None`, если продлевать некуда.
+pub fn extend_curve(kind: &EntityKind, bounds: &[EntityKind], pick: Pt2) -> Option<EntityKind> {
+    if is_closed_curve(kind) {
+        return None;
+    }
+    let reach = reach(kind, bounds);
+    let (r0, r1) = curve_range(kind)?;
+    let at_end = dist(pick, curve_at(kind, r1)?) <= dist(pick, curve_at(kind, r0)?);
+    match kind {
+        EntityKind::LineSeg { a, b } => {
+            let (from, to) = if at_end { (*a, *b) } else { (*b, *a) };
+            let q = extend_ray(from, to, bounds, reach)?;
+            Some(if at_end {
+                EntityKind::LineSeg { a: *a, b: q }
+            } else {
+                EntityKind::LineSeg { a: q, b: *b }
+            })
+        }
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let (a0, a1) = arc_range(*start_angle, *end_angle);
+            let a = extend_arc(*center, *radius, (a0, a1), at_end, bounds)?;
+            let (s, e) = if at_end { (a0, a) } else { (a, a1) };
+            Some(EntityKind::Arc {
+                center: *center,
+                radius: *radius,
+                start_angle: s as f32,
+                end_angle: e as f32,
+            })
+        }
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            ..
+        } => {
+            let rest = if at_end {
+                (r1, r0 + TAU)
+            } else {
+                (r1 - TAU, r0)
+            };
+            let probe = EntityKind::Ellipse {
+                center: *center,
+                major: *major,
+                ratio: *ratio,
+                start_param: rest.0 as f32,
+                end_param: rest.1 as f32,
+            };
+            let t = nearest_cut(&probe, bounds, at_end)?;
+            let (s, e) = if at_end { (r0, t) } else { (t, r1) };
+            Some(EntityKind::Ellipse {
+                center: *center,
+                major: *major,
+                ratio: *ratio,
+                start_param: s as f32,
+                end_param: e as f32,
+            })
+        }
+        EntityKind::Polyline { pts, bulges, .. } => {
+            let n = pts.len();
+            let (mut pts, mut bulges) = (pts.clone(), bulges.clone());
+            bulges.resize(n - 1, 0.0);
+            let (i0, i1, seg) = if at_end {
+                (n - 2, n - 1, n - 2)
+            } else {
+                (1, 0, 0)
+            };
+            match bulge_arc(pts[seg], pts[seg + 1], bulges[seg]) {
+                None => pts[i1] = extend_ray(pts[i0], pts[i1], bounds, reach)?,
+                Some((c, r, s, e)) => {
+                    // дуговой сегмент может идти по часовой — продлеваем в его направлении
+                    let ccw = e >= s;
+                    let (lo, hi) = arc_range(s, e);
+                    let grow_hi = at_end == ccw;
+                    let a = extend_arc(c, r, (lo, hi), grow_hi, bounds)?;
+                    let sweep = if at_end { a - s as f64 } else { e as f64 - a };
+                    pts[i1] = on_circle(c, r, a);
+                    bulges[seg] = (sweep / 4.0).tan() as f32;
+                }
+            }
+            if bulges.iter().all(|b| *b == 0.0) {
+                bulges.clear();
+            }
+            Some(EntityKind::Polyline {
+                pts,
+                closed: false,
+                bulges,
+            })
+        }
+        EntityKind::NurbsCurve2D { .. } => {
+            let (h, rational) = homogeneous(kind)?;
+            let h = if at_end { h } else { reversed(&h) };
+            // пробное продление на `reach`, затем ровно до пересечения
+            let (a, b, speed) = last_span(&h)?;
+            let s_probe = 1.0 + reach as f64 / (speed * (b - a)).max(1e-12);
+            let probe = from_homogeneous(&extrapolated(&h, s_probe)?, rational);
+            let u = bounds
+                .iter()
+                .flat_map(|c| intersect(&probe, c))
+                .map(|x| x.t_a)
+                .filter(|u| *u > b + 1e-9 * (b - a))
+                .min_by(f64::total_cmp)?;
+            let ext = extrapolated(&h, (u - a) / (b - a))?;
+            let ext = if at_end { ext } else { reversed(&ext) };
+            Some(from_homogeneous(&ext, rational))
+        }
+        _ => None,
+    }
+}
+
+/// Длина, которой заведомо хватает до любой границы: диагональ общих габаритов.
+fn reach(kind: &EntityKind, bounds: &[EntityKind]) -> f32 {
+    let (mut lo, mut hi) = (Pt2::new(f32::MAX, f32::MAX), Pt2::new(f32::MIN, f32::MIN));
+    for c in std::iter::once(kind)
+        .chain(bounds)
+        .filter_map(|k| k.to_truck())
+    {
+        for t in c.sample_params() {
+            let p = Pt2::from(c.subs(t));
+            lo = Pt2::new(lo.x.min(p.x), lo.y.min(p.y));
+            hi = Pt2::new(hi.x.max(p.x), hi.y.max(p.y));
+        }
+    }
+    2.0 * dist(lo, hi).max(1.0)
+}
+
+/// Продлить отрезок `from → to` за `to` до ближайшей границы.
+fn extend_ray(from: Pt2, to: Pt2, bounds: &[EntityKind], reach: f32) -> Option<Pt2> {
+    let len = dist(from, to);
+    if len <= 0.0 {
+        return None;
+    }
+    let d = Pt2::new((to.x - from.x) / len, (to.y - from.y) / len);
+    let ray = EntityKind::LineSeg {
+        a: to,
+        b: Pt2::new(to.x + d.x * reach, to.y + d.y * reach),
+    };
+    let t = nearest_cut(&ray, bounds, true)?;
+    curve_at(&ray, t)
+}
+
+/// Новый угол конца дуги `range` (по возрастанию), продлённой через верхний (`grow_hi`)
+/// или нижний конец до ближайшей границы.
+fn extend_arc(
+    center: Pt2,
+    radius: f32,
+    (a0, a1): (f64, f64),
+    grow_hi: bool,
+    bounds: &[EntityKind],
+) -> Option<f64> {
+    let rest = if grow_hi {
+        (a1, a0 + TAU)
+    } else {
+        (a1 - TAU, a0)
+    };
+    let probe = EntityKind::Arc {
+        center,
+        radius,
+        start_angle: rest.0 as f32,
+        end_angle: rest.1 as f32,
+    };
+    nearest_cut(&probe, bounds, grow_hi)
+}
+
+/// Ближайший к началу (`from_start`) или к концу параметр пересечения пробной кривой
+/// с границами, не совпадающий с самим этим концом.
+fn nearest_cut(probe: &EntityKind, bounds: &[EntityKind], from_start: bool) -> Option<f64> {
+    let (r0, r1) = curve_range(probe)?;
+    let eps = 1e-6 * (r1 - r0).max(1.0);
+    let ts = cut_params(probe, bounds);
+    if from_start {
+        ts.into_iter().find(|t| *t > r0 + eps)
+    } else {
+        ts.into_iter().rev().find(|t| *t < r1 - eps)
+    }
+}
+
+// --------------------------- сшивка ---------------------------
+
+/// Сегмент пути: начало, конец, выпуклость (как в полилинии).
+pub(crate) type PathSeg = (Pt2, Pt2, f32);
+
+pub(crate) fn to_path(kind: &EntityKind) -> Option<Vec<PathSeg>> {
+    match kind {
+        EntityKind::LineSeg { a, b } => Some(vec![(*a, *b, 0.0)]),
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let (a0, a1) = arc_range(*start_angle, *end_angle);
+            Some(vec![(
+                on_circle(*center, *radius, a0),
+                on_circle(*center, *radius, a1),
+                ((a1 - a0) / 4.0).tan() as f32,
+            )])
+        }
+        EntityKind::Polyline {
+            pts,
+            closed: false,
+            bulges,
+        } if pts.len() >= 2 => Some(
+            pts.windows(2)
+                .enumerate()
+                .map(|(i, w)| (w[0], w[1], bulges.get(i).copied().unwrap_or(0.0)))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+pub(crate) fn reverse_path(path: &mut [PathSeg]) {
+    path.reverse();
+    for s in path.iter_mut() {
+        *s = (s.1, s.0, -s.2);
+    }
+}
+
+/// Сшить касающиеся концами отрезки, дуги и открытые полилинии в цепочки. Для каждой
+/// цепочки — индексы исходных кусков и результат: соосные прямые и дуги одной окружности
+/// сливаются, одиночный сегмент остаётся отрезком/дугой, иначе — полилиния (замкнутая,
+/// если цепочка сомкнулась). Куски другого вида не участвуют.
+pub fn join_curves(pieces: &[EntityKind]) -> Vec<(Vec<usize>, EntityKind)> {
+    let mut paths: Vec<Option<Vec<PathSeg>>> = pieces.iter().map(to_path).collect();
+    let mut out = Vec::new();
+    for first in 0..paths.len() {
+        let Some(mut chain) = paths[first].take() else {
+            continue;
+        };
+        let mut used = vec![first];
+        loop {
+            let head = chain[0].0;
+            let tail = chain[chain.len() - 1].1;
+            let next = paths.iter().enumerate().find_map(|(i, p)| {
+                let p = p.as_ref()?;
+                let (s, e) = (p[0].0, p[p.len() - 1].1);
+                [
+                    (dist(tail, s), true, false),
+                    (dist(tail, e), true, true),
+                    (dist(head, e), false, false),
+                    (dist(head, s), false, true),
+                ]
+                .into_iter()
+                .find(|(d, ..)| *d <= JOIN_TOL)
+                .map(|(_, at_tail, rev)| (i, at_tail, rev))
+            });
+            let Some((i, at_tail, rev)) = next else {
+                break;
+            };
+            let mut p = paths[i].take().unwrap();
+            if rev {
+                reverse_path(&mut p);
+            }
+            if at_tail {
+                chain.extend(p);
+            } else {
+                p.extend(chain);
+                chain = p;
+            }
+            used.push(i);
+        }
+        out.push((used, path_entity(merge_path(chain))));
+    }
+    out
+}
+
+/// Слить соседние соосные прямые и дуги одной окружности.
+fn merge_path(path: Vec<PathSeg>) -> Vec<PathSeg> {
+    let mut out: Vec<PathSeg> = Vec::with_capacity(path.len());
+    for seg in path {
+        if let Some(last) = out.last_mut() {
+            if let Some(m) = merge_segs(*last, seg) {
+                *last = m;
+                continue;
+            }
+        }
+        out.push(seg);
+    }
+    // у замкнутого контура шов тоже может прийтись на середину прямой или дуги
+    if out.len() > 2 && dist(out[0].0, out[out.len() - 1].1) <= JOIN_TOL {
+        if let Some(m) = merge_segs(out[out.len() - 1], out[0]) {
+            out[0] = m;
+            out.pop();
+        }
+    }
+    out
+}
+
+fn merge_segs(a: PathSeg, b: PathSeg) -> Option<PathSeg> {
+    match (bulge_arc(a.0, a.1, a.2), bulge_arc(b.0, b.1, b.2)) {
+        (None, None) => {
+            let (u, v) = (sub(a.1, a.0), sub(b.1, b.0));
+            let cross = (u.x * v.y - u.y * v.x) as f64;
+            let scale = (dist(a.0, a.1) * dist(b.0, b.1)) as f64;
+            (cross.abs() <= 1e-6 * scale && dot(u, v) > 0.0).then_some((a.0, b.1, 0.0))
+        }
+        (Some((c1, r1, s1, e1)), Some((c2, r2, s2, e2))) => {
+            let same = dist(c1, c2) <= JOIN_TOL && (r1 - r2).abs() <= JOIN_TOL;
+            let (w1, w2) = ((e1 - s1) as f64, (e2 - s2) as f64);
+            let sweep = w1 + w2;
+            (same && w1 * w2 > 0.0 && sweep.abs() < TAU - 1e-6).then_some((
+                a.0,
+                b.1,
+                (sweep / 4.0).tan() as f32,
+            ))
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn path_entity(path: Vec<PathSeg>) -> EntityKind {
+    let closed = path.len() > 1 && dist(path[0].0, path[path.len() - 1].1) <= JOIN_TOL;
+    if let [(a, b, bulge)] = path[..] {
+        return match bulge_arc(a, b, bulge) {
+            None => EntityKind::LineSeg { a, b },
+            Some((center, radius, s, e)) => {
+                let (s, e) = if e >= s { (s, e) } else { (e, s) };
+                EntityKind::Arc {
+                    center,
+                    radius,
+                    start_angle: s,
+                    end_angle: e,
+                }
+            }
+        };
+    }
+    let mut pts: Vec<Pt2> = path.iter().map(|s| s.0).collect();
+    if !closed {
+        pts.push(path[path.len() - 1].1);
+    }
+    let mut bulges: Vec<f32> = path.iter().map(|s| s.2).collect();
+    if bulges.iter().all(|b| *b == 0.0) {
+        bulges.clear();
+    }
+    EntityKind::Polyline {
+        pts,
+        closed,
+        bulges,
+    }
+}
+
+// --------------------------- NURBS ---------------------------
+
+/// NURBS-сущность в однородных координатах (x·w, y·w, w) и признак рациональности.
+pub(crate) fn homogeneous(kind: &EntityKind) -> Option<(BSplineCurve<Vector3>, bool)> {
+    let EntityKind::NurbsCurve2D {
+        knots,
+        ctrl_pts,
+        weights,
+        ..
+    } = kind
+    else {
+        return None;
+    };
+    let w = |i: usize| {
+        weights
+            .as_ref()
+            .and_then(|w| w.get(i).copied())
+            .unwrap_or(1.0)
+    };
+    let ctrl = ctrl_pts
+        .iter()
+        .enumerate()
+        .map(|(i, p)| Vector3::new(p.x as f64 * w(i), p.y as f64 * w(i), w(i)))
+        .collect();
+    let kv = KnotVec::try_from(knots.clone()).ok()?;
+    Some((BSplineCurve::try_new(kv, ctrl).ok()?, weights.is_some()))
+}
+
+pub(crate) fn from_homogeneous(c: &BSplineCurve<Vector3>, rational: bool) -> EntityKind {
+    let ctrl_pts = c
+        .control_points()
+        .iter()
+        .map(|v| Pt2::new((v.x / v.z) as f32, (v.y / v.z) as f32))
+        .collect();
+    EntityKind::NurbsCurve2D {
+        degree: c.degree(),
+        knots: c.knot_vec().to_vec(),
+        ctrl_pts,
+        weights: rational.then(|| c.control_points().iter().map(|v| v.z).collect()),
+    }
+}
+
+fn bspline_piece(c: &BSplineCurve<Vector3>, t0: f64, t1: f64) -> BSplineCurve<Vector3> {
+    let (r0, r1) = c.range_tuple();
+    let mut left = c.clone();
+    let mut piece = if t0 > r0 + 1e-12 { left.cut(t0) } else { left };
+    if t1 < r1 - 1e-12 {
+        piece.cut(t1);
+    }
+    piece
+}
+
+/// Тот же сплайн с обращённым направлением.
+pub(crate) fn reversed(c: &BSplineCurve<Vector3>) -> BSplineCurve<Vector3> {
+    let mut r = c.clone();
+    r.invert();
+    r
+}
+
+/// Последний пролёт [a, b] и скорость |C'| в его конце.
+fn last_span(c: &BSplineCurve<Vector3>) -> Option<(f64, f64, f64)> {
+    let knots: &[f64] = c.knot_vec();
+    let b = *knots.last()?;
+    let a = *knots.iter().rev().find(|k| **k < b - 1e-12)?;
+    let nurbs = NurbsCurve::new(c.clone());
+    Some((a, b, nurbs.der(b).magnitude()))
+}
+
+/// Продолжить последний пролёт [a, b] его же полиномом до a + s·(b − a), s ≥ 1:
+/// пролёт приводится к форме Безье вставкой узла a, затем де Кастельжо при s.
+fn extrapolated(c: &BSplineCurve<Vector3>, s: f64) -> Option<BSplineCurve<Vector3>> {
+    let p = c.degree();
+    let (a, b, _) = last_span(c)?;
+    let mut c = c.clone();
+    let mult = c
+        .knot_vec()
+        .iter()
+        .filter(|k| (**k - a).abs() <= 1e-12)
+        .count();
+    for _ in mult..p {
+        c.add_knot(a);
+    }
+    let mut knots: Vec<f64> = c.knot_vec().to_vec();
+    let mut ctrl: Vec<Vector3> = c.control_points().clone();
+    let n = ctrl.len();
+    let mut q: Vec<Vector3> = ctrl[n - p - 1..].to_vec();
+    let mut left = vec![q[0]];
+    for _ in 0..p {
+        q = q.windows(2).map(|w| w[0] * (1.0 - s) + w[1] * s).collect();
+        left.push(q[0]);
+    }
+    ctrl[n - p - 1..].copy_from_slice(&left);
+    let k = knots.len();
+    for x in &mut knots[k - p - 1..] {
+        *x = a + s * (b - a);
+    }
+    BSplineCurve::try_new(KnotVec::try_from(knots).ok()?, ctrl).ok()
+}
+
+// --------------------------- мелочи ---------------------------
+
+fn segment_count(n: usize, closed: bool) -> usize {
+    if closed && n > 2 {
+        n
+    } else {
+        n.saturating_sub(1)
+    }
+}
+
+/// Точка сегмента `i` полилинии при доле `f` (у дугового — доле угла).
+fn segment_at(pts: &[Pt2], bulges: &[f32], i: usize, f: f64) -> Pt2 {
+    let (a, b) = (pts[i], pts[(i + 1) % pts.len()]);
+    match bulge_arc(a, b, bulges.get(i).copied().unwrap_or(0.0)) {
+        Some((c, r, s, e)) => on_circle(c, r, s as f64 + (e - s) as f64 * f),
+        None => lerp(a, b, f),
+    }
+}
+
+/// Доля ближайшей к `p` точки сегмента `i`.
+fn segment_param(pts: &[Pt2], bulges: &[f32], i: usize, p: Pt2) -> f64 {
+    let (a, b) = (pts[i], pts[(i + 1) % pts.len()]);
+    match bulge_arc(a, b, bulges.get(i).copied().unwrap_or(0.0)) {
+        Some((c, _, s, e)) => {
+            let (lo, hi) = arc_range(s, e);
+            let ang = angle_in(((p.y - c.y) as f64).atan2((p.x - c.x) as f64), (lo, hi));
+            let f = (ang - lo) / (hi - lo);
+            if e >= s {
+                f
+            } else {
+                1.0 - f
+            }
+        }
+        None => curve_param(&EntityKind::LineSeg { a, b }, p).unwrap_or(0.0),
+    }
+}
+
+/// Диапазон углов дуги по возрастанию (как пишется в DXF).
+fn arc_range(s: f32, e: f32) -> (f64, f64) {
+    if e >= s {
+        (s as f64, e as f64)
+    } else {
+        (e as f64, s as f64)
+    }
+}
+
+/// Угол `a`, приведённый к `[lo, lo + 2π)`; вне `[lo, hi]` — ближайший конец.
+fn angle_in(a: f64, (lo, hi): (f64, f64)) -> f64 {
+    let a = lo + (a - lo).rem_euclid(TAU);
+    if a <= hi + 1e-9 {
+        return a.min(hi);
+    }
+    // за концом: ближе к hi или (через шов) к lo
+    if a - hi <= lo + TAU - a {
+        hi
+    } else {
+        lo
+    }
+}
+
+fn on_circle(c: Pt2, r: f32, a: f64) -> Pt2 {
+    Pt2::new(c.x + r * a.cos() as f32, c.y + r * a.sin() as f32)
+}
+
+fn lerp(a: Pt2, b: Pt2, t: f64) -> Pt2 {
+    let t = t as f32;
+    Pt2::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+fn sub(a: Pt2, b: Pt2) -> Pt2 {
+    Pt2::new(a.x - b.x, a.y - b.y)
+}
+
+fn dot(a: Pt2, b: Pt2) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+fn perp(a: Pt2) -> Pt2 {
+    Pt2::new(-a.y, a.x)
+}
+
+fn dist(a: Pt2, b: Pt2) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
@@ -0,0 +1,463 @@
+//! Скругление и фаска: между двумя кривыми (отрезки, дуги, концевые сегменты открытых
+//! полилиний) и во всех вершинах одной полилинии.
+//!
+//! Угол двух кривых — пересечение их продолженных концевых сегментов, ближайшее к концам.
+//! У каждой кривой остаётся дальняя от угла часть, обрезанная или продлённая до сопряжения.
+
+use crate::edit::{path_entity, reverse_path, to_path, PathSeg};
+use crate::{bulge_arc, EntityKind, Pt2};
+use std::f32::consts::TAU;
+
+/// Сопряжение угла.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CornerJoint {
+    /// Касательная дуга радиуса; ноль — просто свести кривые в угол
+    Fillet(f32),
+    /// Прямой срез на расстояниях от угла вдоль первой и второй кривой
+    Chamfer(f32, f32),
+}
+
+/// Сопряжённые кривые: обе обрезаны (или продлены) до вставки между ними.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CornerCut {
+    pub a: EntityKind,
+    pub b: EntityKind,
+    /// Дуга скругления или отрезок фаски; `None` при нулевом радиусе/расстояниях
+    pub joint: Option<EntityKind>,
+}
+
+/// Сопрячь две кривые. `None`, если кривые не отрезки/дуги/открытые полилинии, не
+/// пересекаются (параллельны), касаются или сопряжение не помещается на их концах.
+pub fn corner_curves(a: &EntityKind, b: &EntityKind, joint: CornerJoint) -> Option<CornerCut> {
+    let (mut pa, mut pb) = (to_path(a)?, to_path(b)?);
+    let (x, a_at_end, b_at_end) = corner_point(&pa, &pb)?;
+    // угол — в конце первой кривой и в начале второй
+    if !a_at_end {
+        reverse_path(&mut pa);
+    }
+    if b_at_end {
+        reverse_path(&mut pb);
+    }
+    let last = pa.len() - 1;
+    let seg_in = with_end(pa[last], x)?;
+    let seg_out = with_start(pb[0], x)?;
+    let (seg_in, mid, seg_out) = cut_vertex(seg_in, seg_out, joint)?;
+    pa[last] = seg_in;
+    pb[0] = seg_out;
+    // направление кривых — прежнее
+    if !a_at_end {
+        reverse_path(&mut pa);
+    }
+    if b_at_end {
+        reverse_path(&mut pb);
+    }
+    Some(CornerCut {
+        a: rebuild(a, pa)?,
+        b: rebuild(b, pb)?,
+        joint: mid.map(|s| path_entity(vec![s])),
+    })
+}
+
+/// Сопрячь все вершины полилинии, где это возможно (вершины на прямой и те, где сопряжение
+/// не помещается, остаются). `None`, если не сопряжена ни одна.
+pub fn corner_polyline(kind: &EntityKind, joint: CornerJoint) -> Option<EntityKind> {
+    let EntityKind::Polyline {
+        pts,
+        closed,
+        bulges,
+    } = kind
+    else {
+        return None;
+    };
+    let n = pts.len();
+    let count = if *closed && n > 2 {
+        n
+    } else {
+        n.saturating_sub(1)
+    };
+    if count < 2 {
+        return None;
+    }
+    let mut segs: Vec<PathSeg> = (0..count)
+        .map(|i| {
+            (
+                pts[i],
+                pts[(i + 1) % n],
+                bulges.get(i).copied().unwrap_or(0.0),
+            )
+        })
+        .collect();
+    // вставка перед сегментом k — в вершине между k−1 и k
+    let mut joints: Vec<Option<PathSeg>> = vec![None; count];
+    let first = if *closed && n > 2 { 0 } else { 1 };
+    for k in first..count {
+        let i = (k + count - 1) % count;
+        if let Some((s_in, Some(mid), s_out)) = cut_vertex(segs[i], segs[k], joint) {
+            segs[i] = s_in;
+            segs[k] = s_out;
+            joints[k] = Some(mid);
+        }
+    }
+    if joints.iter().all(Option::is_none) {
+        return None;
+    }
+    let path: Vec<PathSeg> = joints
+        .into_iter()
+        .zip(segs)
+        .flat_map(|(j, s)| j.into_iter().chain([s]))
+        .filter(|s| !degenerate(s))
+        .collect();
+    let mut pts: Vec<Pt2> = path.iter().map(|s| s.0).collect();
+    if !closed {
+        pts.push(path.last()?.1);
+    }
+    let mut bulges: Vec<f32> = path.iter().map(|s| s.2).collect();
+    if bulges.iter().all(|b| *b == 0.0) {
+        bulges.clear();
+    }
+    Some(EntityKind::Polyline {
+        pts,
+        closed: *closed,
+        bulges,
+    })
+}
+
+/// Угол двух путей: точка и то, у какого конца (начала/конца) каждого пути он лежит.
+fn corner_point(pa: &[PathSeg], pb: &[PathSeg]) -> Option<(Pt2, bool, bool)> {
+    let mut best: Option<(f32, Pt2, bool, bool)> = None;
+    for a_end in [false, true] {
+        for b_end in [false, true] {
+            let (sa, ea) = if a_end {
+                (pa[pa.len() - 1], pa[pa.len() - 1].1)
+            } else {
+                (pa[0], pa[0].0)
+            };
+            let (sb, eb) = if b_end {
+                (pb[pb.len() - 1], pb[pb.len() - 1].1)
+            } else {
+                (pb[0], pb[0].0)
+            };
+            let (Some(ga), Some(gb)) = (geo(&sa), geo(&sb)) else {
+                continue;
+            };
+            for x in meet(&ga, &gb) {
+                let score = dist(x, ea) + dist(x, eb);
+                if best.is_none_or(|b| score < b.0) {
+                    best = Some((score, x, a_end, b_end));
+                }
+            }
+        }
+    }
+    best.map(|(_, x, a, b)| (x, a, b))
+}
+
+/// Сопряжение в вершине: `seg_in` кончается в ней, `seg_out` из неё начинается.
+/// Возвращает укороченные сегменты и вставку между ними.
+fn cut_vertex(
+    seg_in: PathSeg,
+    seg_out: PathSeg,
+    joint: CornerJoint,
+) -> Option<(PathSeg, Option<PathSeg>, PathSeg)> {
+    // без излома сопрягать нечего
+    let (t_in, t_out) = (tangent(&seg_in, seg_in.1)?, tangent(&seg_out, seg_out.0)?);
+    if cross(t_in, t_out).abs() < 1e-6 && dot(t_in, t_out) > 0.0 {
+        return None;
+    }
+    match joint {
+        CornerJoint::Fillet(r) if r > 0.0 => fillet_vertex(seg_in, seg_out, r),
+        CornerJoint::Chamfer(d1, d2) if d1 > 0.0 || d2 > 0.0 => {
+            let p = along_back(&seg_in, d1.max(0.0))?;
+            let q = along_back(&reverse(&seg_out), d2.max(0.0))?;
+            Some((
+                with_end(seg_in, p)?,
+                Some((p, q, 0.0)),
+                with_start(seg_out, q)?,
+            ))
+        }
+        _ => Some((seg_in, None, seg_out)),
+    }
+}
+
+/// Касательная дуга радиуса `r`: центр — на пересечении эквидистант обоих сегментов,
+/// точки касания — на самих сегментах, обход без излома; из подходящих — самая короткая.
+fn fillet_vertex(
+    seg_in: PathSeg,
+    seg_out: PathSeg,
+    r: f32,
+) -> Option<(PathSeg, Option<PathSeg>, PathSeg)> {
+    let (g1, g2) = (geo(&seg_in)?, geo(&seg_out)?);
+    let mut best: Option<(f32, Pt2, Pt2)> = None;
+    for s1 in [r, -r] {
+        for s2 in [r, -r] {
+            let (Some(o1), Some(o2)) = (offset(&g1, s1), offset(&g2, s2)) else {
+                continue;
+            };
+            for c in meet(&o1, &o2) {
+                let (p, q) = (foot(&g1, c), foot(&g2, c));
+                if !on_seg(&seg_in, p) || !on_seg(&seg_out, q) {
+                    continue;
+                }
+                let Some(sweep) = fillet_sweep(&seg_in, &seg_out, c, p, q) else {
+                    continue;
+                };
+                if best.is_none_or(|b| sweep.abs() < b.0.abs()) {
+                    best = Some((sweep, p, q));
+                }
+            }
+        }
+    }
+    let (sweep, p, q) = best?;
+    Some((
+        with_end(seg_in, p)?,
+        Some((p, q, (sweep / 4.0).tan())),
+        with_start(seg_out, q)?,
+    ))
+}
+
+/// Угол дуги с центром `c` от `p` до `q`, продолжающей `seg_in` без излома; `None`, если
+/// в `q` она входит в `seg_out` с изломом.
+fn fillet_sweep(seg_in: &PathSeg, seg_out: &PathSeg, c: Pt2, p: Pt2, q: Pt2) -> Option<f32> {
+    let ccw = cross(tangent(seg_in, p)?, sub(c, p)) > 0.0;
+    let (a0, a1) = (angle(sub(p, c)), angle(sub(q, c)));
+    let sweep = if ccw {
+        (a1 - a0).rem_euclid(TAU)
+    } else {
+        -(a0 - a1).rem_euclid(TAU)
+    };
+    let rel = sub(q, c);
+    let t = unit(if ccw {
+        Pt2::new(-rel.y, rel.x)
+    } else {
+        Pt2::new(rel.y, -rel.x)
+    })?;
+    (sweep.abs() > 1e-6 && dot(t, tangent(seg_out, q)?) > 0.999).then_some(sweep)
+}
+
+/// Пересобрать сущность с изменённым путём: полилиния остаётся полилинией.
+fn rebuild(orig: &EntityKind, path: Vec<PathSeg>) -> Option<EntityKind> {
+    let path: Vec<PathSeg> = path.into_iter().filter(|s| !degenerate(s)).collect();
+    if path.is_empty() {
+        return None;
+    }
+    if !matches!(orig, EntityKind::Polyline { .. }) {
+        return Some(path_entity(path));
+    }
+    let mut pts: Vec<Pt2> = path.iter().map(|s| s.0).collect();
+    pts.push(path[path.len() - 1].1);
+    let mut bulges: Vec<f32> = path.iter().map(|s| s.2).collect();
+    if bulges.iter().all(|b| *b == 0.0) {
+        bulges.clear();
+    }
+    Some(EntityKind::Polyline {
+        pts,
+        closed: false,
+        bulges,
+    })
+}
+
+// --------------------------- сегменты ---------------------------
+
+/// Опорная линия сегмента: прямая (точка, единичное направление) или окружность.
+enum Geo {
+    Line(Pt2, Pt2),
+    Circle(Pt2, f32),
+}
+
+fn geo(s: &PathSeg) -> Option<Geo> {
+    match bulge_arc(s.0, s.1, s.2) {
+        Some((c, r, ..)) => Some(Geo::Circle(c, r)),
+        None => Some(Geo::Line(s.0, unit(sub(s.1, s.0))?)),
+    }
+}
+
+/// Эквидистанта опорной линии: прямая — влево на `d`, окружность — радиус плюс `d`.
+fn offset(g: &Geo, d: f32) -> Option<Geo> {
+    match *g {
+        Geo::Line(p, u) => Some(Geo::Line(add(p, scale(Pt2::new(-u.y, u.x), d)), u)),
+        Geo::Circle(c, r) => (r + d > 1e-6).then_some(Geo::Circle(c, r + d)),
+    }
+}
+
+fn foot(g: &Geo, q: Pt2) -> Pt2 {
+    match *g {
+        Geo::Line(p, u) => add(p, scale(u, dot(sub(q, p), u))),
+        Geo::Circle(c, r) => add(c, scale(unit(sub(q, c)).unwrap_or(Pt2::new(1.0, 0.0)), r)),
+    }
+}
+
+/// Точки пересечения опорных линий (касание — одна точка).
+fn meet(g1: &Geo, g2: &Geo) -> Vec<Pt2> {
+    match (g1, g2) {
+        (Geo::Line(p1, u1), Geo::Line(p2, u2)) => {
+            let den = cross(*u1, *u2);
+            if den.abs() < 1e-7 {
+                return vec![];
+            }
+            vec![add(*p1, scale(*u1, cross(sub(*p2, *p1), *u2) / den))]
+        }
+        (Geo::Line(p, u), Geo::Circle(c, r)) | (Geo::Circle(c, r), Geo::Line(p, u)) => {
+            let w = sub(*p, *c);
+            let b = dot(w, *u) as f64;
+            let disc = b * b - (dot(w, w) - r * r) as f64;
+            if disc < -1e-6 * (*r as f64).powi(2) {
+                return vec![];
+            }
+            let s = disc.max(0.0).sqrt();
+            let mut out = vec![add(*p, scale(*u, (-b - s) as f32))];
+            if s > 0.0 {
+                out.push(add(*p, scale(*u, (-b + s) as f32)));
+            }
+            out
+        }
+        (Geo::Circle(c1, r1), Geo::Circle(c2, r2)) => {
+            let d = dist(*c1, *c2) as f64;
+            if d < 1e-9 {
+                return vec![];
+            }
+            let (r1, r2) = (*r1 as f64, *r2 as f64);
+            let a = (r1 * r1 - r2 * r2 + d * d) / (2.0 * d);
+            let h2 = r1 * r1 - a * a;
+            if h2 < -1e-6 * r1 * r1 {
+                return vec![];
+            }
+            let h = h2.max(0.0).sqrt();
+            let u = scale(sub(*c2, *c1), (1.0 / d) as f32);
+            let m = add(*c1, scale(u, a as f32));
+            let n = scale(Pt2::new(-u.y, u.x), h as f32);
+            if h > 0.0 {
+                vec![add(m, n), sub(m, n)]
+            } else {
+                vec![m]
+            }
+        }
+    }
+}
+
+/// Лежит ли точка опорной линии на самом сегменте.
+fn on_seg(s: &PathSeg, q: Pt2) -> bool {
+    const EPS: f32 = 1e-4;
+    let f = match bulge_arc(s.0, s.1, s.2) {
+        None => {
+            let d = sub(s.1, s.0);
+            dot(sub(q, s.0), d) / dot(d, d).max(1e-30)
+        }
+        Some((c, _, a0, a1)) => {
+            let sweep = a1 - a0;
+            let mut delta = if sweep > 0.0 {
+                (angle(sub(q, c)) - a0).rem_euclid(TAU)
+            } else {
+                (a0 - angle(sub(q, c))).rem_euclid(TAU)
+            };
+            if delta > TAU - EPS {
+                delta -= TAU;
+            }
+            delta / sweep.abs()
+        }
+    };
+    (-EPS..=1.0 + EPS).contains(&f)
+}
+
+/// Касательная по ходу сегмента в его точке `q`.
+fn tangent(s: &PathSeg, q: Pt2) -> Option<Pt2> {
+    match bulge_arc(s.0, s.1, s.2) {
+        None => unit(sub(s.1, s.0)),
+        Some((c, _, a0, a1)) => {
+            let rel = sub(q, c);
+            unit(if a1 > a0 {
+                Pt2::new(-rel.y, rel.x)
+            } else {
+                Pt2::new(rel.y, -rel.x)
+            })
+        }
+    }
+}
+
+/// Сегмент с концом, перенесённым в точку `q` его опорной линии (обрезка или продление).
+/// `None`, если прямой сегмент при этом развернулся бы.
+fn with_end(s: PathSeg, q: Pt2) -> Option<PathSeg> {
+    let (a, b, bulge) = s;
+    if dist(a, q) <= 1e-6 * dist(a, b).max(1.0) {
+        return Some((a, a, 0.0));
+    }
+    match bulge_arc(a, b, bulge) {
+        None => (dot(sub(q, a), sub(b, a)) > 0.0).then_some((a, q, 0.0)),
+        Some((c, _, a0, a1)) => {
+            let aq = angle(sub(q, c));
+            let sweep = if a1 > a0 {
+                (aq - a0).rem_euclid(TAU)
+            } else {
+                -(a0 - aq).rem_euclid(TAU)
+            };
+            Some((a, q, (sweep / 4.0).tan()))
+        }
+    }
+}
+
+fn with_start(s: PathSeg, q: Pt2) -> Option<PathSeg> {
+    with_end(reverse(&s), q).map(|s| reverse(&s))
+}
+
+/// Точка на длине `d` от конца сегмента назад; `None`, если сегмент короче.
+fn along_back(s: &PathSeg, d: f32) -> Option<Pt2> {
+    let (a, b, bulge) = *s;
+    match bulge_arc(a, b, bulge) {
+        None => {
+            let l = dist(a, b);
+            if d > l * (1.0 + 1e-5) || l == 0.0 {
+                return None;
+            }
+            Some(add(b, scale(sub(a, b), d / l)))
+        }
+        Some((c, r, a0, a1)) => {
+            let sweep = a1 - a0;
+            if d > sweep.abs() * r * (1.0 + 1e-5) {
+                return None;
+            }
+            let t = a1 - sweep.signum() * d / r;
+            Some(Pt2::new(c.x + r * t.cos(), c.y + r * t.sin()))
+        }
+    }
+}
+
+fn reverse(s: &PathSeg) -> PathSeg {
+    (s.1, s.0, -s.2)
+}
+
+fn degenerate(s: &PathSeg) -> bool {
+    dist(s.0, s.1) <= 1e-6
+}
+
+// --------------------------- мелочи ---------------------------
+
+fn add(a: Pt2, b: Pt2) -> Pt2 {
+    Pt2::new(a.x + b.x, a.y + b.y)
+}
+
+fn sub(a: Pt2, b: Pt2) -> Pt2 {
+    Pt2::new(a.x - b.x, a.y - b.y)
+}
+
+fn scale(a: Pt2, k: f32) -> Pt2 {
+    Pt2::new(a.x * k, a.y * k)
+}
+
+fn dot(a: Pt2, b: Pt2) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Pt2, b: Pt2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn dist(a: Pt2, b: Pt2) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+fn angle(a: Pt2) -> f32 {
+    a.y.atan2(a.x)
+}
+
+fn unit(a: Pt2) -> Option<Pt2> {
+    let l = a.x.hypot(a.y);
+    (l > 0.0).then(|| scale(a, 1.0 / l))
+}
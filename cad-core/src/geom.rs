@@ -1,3 +1,6 @@
+use crate::{
+    Color, DimKind, DimPoint, HatchFill, HatchLoop, LineWeight, Table, LINETYPE_CONTINUOUS,
+};
 use cryxtal_geometry::prelude::*;
 use serde::{Deserialize, Serialize}; // Point2, Vector2, Vector3, BSplineCurve, NurbsCurve, KnotVec, ParametricCurve, BoundedCurve
 
@@ -29,9 +32,29 @@ pub enum EntityKind {
         end_angle: f32,
     },
 
+    Circle {
+        center: Pt2,
+        radius: f32,
+    },
+
+    /// Эллипс или эллиптическая дуга (как ELLIPSE в DXF).
+    /// `major` — вектор большой полуоси от центра, `ratio` — малая/большая,
+    /// параметры в радианах; полный эллипс — `0..2π`.
+    Ellipse {
+        center: Pt2,
+        major: Pt2,
+        ratio: f32,
+        start_param: f32,
+        end_param: f32,
+    },
+
+    /// `bulges[i]` — выпуклость сегмента i → i+1 (у замкнутой последний идёт в 0),
+    /// как в DXF: tg(угол дуги / 4), > 0 — против часовой. Пустой вектор — все прямые.
     Polyline {
         pts: Vec<Pt2>,
         closed: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        bulges: Vec<f32>,
     },
 
     /// Если `weights == None` — обычный B-сплайн.
@@ -48,6 +71,63 @@ pub enum EntityKind {
         content: String,
         height: f32,
     },
+
+    /// Вставка блока: точка вставки, масштабы по X/Y, поворот в радианах.
+    /// Атрибуты хранят значения и мировые позиции (как ATTRIB в DXF).
+    Insert {
+        block: String,
+        pos: Pt2,
+        scale: Pt2,
+        rotation: f32,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        attribs: Vec<Attrib>,
+    },
+
+    /// Штриховка: первый контур внешний, остальные — острова (заливка по чёт-нечет).
+    Hatch {
+        loops: Vec<HatchLoop>,
+        fill: HatchFill,
+    },
+
+    /// Размер: определяющие точки (см. `DimKind`), положение размерной линии/текста,
+    /// текст ("" — измеренное значение, "<>" заменяется им) и имя размерного стиля.
+    /// Рисуется как блок, сгенерированный `dim_geometry`.
+    Dimension {
+        kind: DimKind,
+        pts: Vec<DimPoint>,
+        line_pos: Pt2,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        text: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        style: String,
+    },
+
+    /// Таблица с левым верхним углом `pos`. Рисуется как блок, сгенерированный
+    /// `table_geometry`.
+    Table {
+        pos: Pt2,
+        table: Table,
+    },
+}
+
+/// Атрибут блока (ATTDEF/ATTRIB). В определении блока `pos` — в координатах блока,
+/// `value` — значение по умолчанию; во вставке — мировая позиция и введённое значение.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Attrib {
+    pub tag: String,
+    pub value: String,
+    pub pos: Pt2,
+    pub height: f32,
+}
+
+/// Определение блока: геометрия в собственных координатах относительно `base`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockDef {
+    pub name: String,
+    pub base: Pt2,
+    pub entities: Vec<Entity>,
+    #[serde(default)]
+    pub attdefs: Vec<Attrib>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -55,6 +135,27 @@ pub struct Entity {
     pub id: u64,
     pub layer: String,
     pub kind: EntityKind,
+    #[serde(default, skip_serializing_if = "Color::is_by_layer")]
+    pub color: Color,
+    /// Имя типа линии; пусто — ПоСлою, `LINETYPE_BYBLOCK` — ПоБлоку.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub linetype: String,
+    #[serde(default, skip_serializing_if = "LineWeight::is_by_layer")]
+    pub lineweight: LineWeight,
+}
+
+impl Entity {
+    /// Сущность со свойствами ПоСлою.
+    pub fn new(layer: impl Into<String>, kind: EntityKind) -> Self {
+        Self {
+            id: 0,
+            layer: layer.into(),
+            kind,
+            color: Color::ByLayer,
+            linetype: String::new(),
+            lineweight: LineWeight::ByLayer,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -62,6 +163,35 @@ pub struct Layer {
     pub name: String,
     pub visible: bool,
     pub locked: bool,
+    #[serde(default = "layer_color")]
+    pub color: Color,
+    #[serde(default = "layer_linetype")]
+    pub linetype: String,
+    #[serde(default = "layer_lineweight")]
+    pub lineweight: LineWeight,
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            visible: true,
+            locked: false,
+            color: layer_color(),
+            linetype: layer_linetype(),
+            lineweight: layer_lineweight(),
+        }
+    }
+}
+
+fn layer_color() -> Color {
+    Color::WHITE
+}
+fn layer_linetype() -> String {
+    LINETYPE_CONTINUOUS.into()
+}
+fn layer_lineweight() -> LineWeight {
+    LineWeight::Default
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -74,6 +204,370 @@ impl Default for Style {
     }
 }
 
+// --------------------------- аффинные преобразования ---------------------------
+
+/// Аффинное преобразование плоскости: p' = m·p + t (`m` по строкам).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2 {
+    pub m: [[f32; 2]; 2],
+    pub t: Pt2,
+}
+
+impl Affine2 {
+    pub const IDENTITY: Self = Self {
+        m: [[1.0, 0.0], [0.0, 1.0]],
+        t: Pt2 { x: 0.0, y: 0.0 },
+    };
+
+    pub fn translation(dx: f32, dy: f32) -> Self {
+        Self {
+            t: Pt2::new(dx, dy),
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Поворот на `angle` радиан вокруг `center`.
+    pub fn rotation(center: Pt2, angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::translation(center.x, center.y)
+            .then_after(&Self {
+                m: [[c, -s], [s, c]],
+                t: Pt2::new(0.0, 0.0),
+            })
+            .then_after(&Self::translation(-center.x, -center.y))
+    }
+
+    /// Масштаб по осям относительно `center`.
+    pub fn scaling(center: Pt2, sx: f32, sy: f32) -> Self {
+        Self {
+            m: [[sx, 0.0], [0.0, sy]],
+            t: Pt2::new(center.x * (1.0 - sx), center.y * (1.0 - sy)),
+        }
+    }
+
+    /// Зеркало относительно прямой через `a` и `b` (совпадающие точки — тождество).
+    pub fn mirror(a: Pt2, b: Pt2) -> Self {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        if dx == 0.0 && dy == 0.0 {
+            return Self::IDENTITY;
+        }
+        let (s, c) = (2.0 * dy.atan2(dx)).sin_cos();
+        let m = Self {
+            m: [[c, s], [s, -c]],
+            t: Pt2::new(0.0, 0.0),
+        };
+        let ma = m.apply(a);
+        Self {
+            t: Pt2::new(a.x - ma.x, a.y - ma.y),
+            ..m
+        }
+    }
+
+    /// Преобразование вставки блока: база блока → точка вставки, масштаб, поворот.
+    pub fn insert(pos: Pt2, scale: Pt2, rotation: f32, base: Pt2) -> Self {
+        Self::rotation(Pt2::new(0.0, 0.0), rotation)
+            .then_after(&Self::scaling(Pt2::new(0.0, 0.0), scale.x, scale.y))
+            .then_after(&Self::translation(-base.x, -base.y))
+            .then(&Self::translation(pos.x, pos.y))
+    }
+
+    /// Композиция: сначала `self`, потом `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        next.then_after(self)
+    }
+
+    /// Композиция: сначала `first`, потом `self`.
+    pub fn then_after(&self, first: &Self) -> Self {
+        let (a, b) = (&self.m, &first.m);
+        let mut m = [[0.0; 2]; 2];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = a[i][0] * b[0][j] + a[i][1] * b[1][j];
+            }
+        }
+        Self {
+            m,
+            t: self.apply(first.t),
+        }
+    }
+
+    pub fn apply(&self, p: Pt2) -> Pt2 {
+        let v = self.apply_vec(p);
+        Pt2::new(v.x + self.t.x, v.y + self.t.y)
+    }
+
+    /// Только линейная часть (для векторов).
+    pub fn apply_vec(&self, v: Pt2) -> Pt2 {
+        Pt2::new(
+            self.m[0][0] * v.x + self.m[0][1] * v.y,
+            self.m[1][0] * v.x + self.m[1][1] * v.y,
+        )
+    }
+
+    pub fn det(&self) -> f32 {
+        self.m[0][0] * self.m[1][1] - self.m[0][1] * self.m[1][0]
+    }
+
+    /// Средний масштаб (√|det|) — для высоты текста и т.п.
+    pub fn mean_scale(&self) -> f32 {
+        self.det().abs().sqrt()
+    }
+
+    /// Поворот + равномерный масштаб (возможно, с зеркалом): окружности остаются окружностями.
+    pub fn is_similarity(&self) -> bool {
+        let [[a, b], [c, d]] = self.m;
+        let tol = 1e-5 * (a.abs() + b.abs() + c.abs() + d.abs()).max(1e-12);
+        if self.det() >= 0.0 {
+            (a - d).abs() <= tol && (b + c).abs() <= tol
+        } else {
+            (a + d).abs() <= tol && (b - c).abs() <= tol
+        }
+    }
+
+    /// Угол поворота образа оси X.
+    pub fn angle(&self) -> f32 {
+        self.m[1][0].atan2(self.m[0][0])
+    }
+}
+
+impl EntityKind {
+    /// Вставка или размер — сущность, которая рисуется как блок (`Document::explode_insert`).
+    pub fn is_block_ref(&self) -> bool {
+        matches!(
+            self,
+            EntityKind::Insert { .. } | EntityKind::Dimension { .. } | EntityKind::Table { .. }
+        )
+    }
+
+    /// Образ сущности при аффинном преобразовании. Дуги и окружности при неравномерном
+    /// масштабе становятся эллипсами; дуговые сегменты полилиний — ломаной.
+    pub fn transformed(&self, tr: &Affine2) -> EntityKind {
+        let mirror = tr.det() < 0.0;
+        match self {
+            EntityKind::LineSeg { a, b } => EntityKind::LineSeg {
+                a: tr.apply(*a),
+                b: tr.apply(*b),
+            },
+            EntityKind::Arc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => conic_transformed(
+                tr,
+                *center,
+                Pt2::new(*radius, 0.0),
+                1.0,
+                (*start_angle, *end_angle),
+                true,
+                false,
+            ),
+            EntityKind::Circle { center, radius } => conic_transformed(
+                tr,
+                *center,
+                Pt2::new(*radius, 0.0),
+                1.0,
+                (0.0, std::f32::consts::TAU),
+                true,
+                true,
+            ),
+            EntityKind::Ellipse {
+                center,
+                major,
+                ratio,
+                start_param,
+                end_param,
+            } => conic_transformed(
+                tr,
+                *center,
+                *major,
+                *ratio,
+                (*start_param, *end_param),
+                false,
+                (end_param - start_param).abs() >= std::f32::consts::TAU - 1e-4,
+            ),
+            EntityKind::Polyline {
+                pts,
+                closed,
+                bulges,
+            } => {
+                if bulges.iter().all(|b| *b == 0.0) || tr.is_similarity() {
+                    let sign = if mirror { -1.0 } else { 1.0 };
+                    EntityKind::Polyline {
+                        pts: pts.iter().map(|p| tr.apply(*p)).collect(),
+                        closed: *closed,
+                        bulges: bulges.iter().map(|b| b * sign).collect(),
+                    }
+                } else {
+                    let mut flat = flatten_polyline(pts, bulges, *closed, 0.05);
+                    if *closed {
+                        flat.pop();
+                    }
+                    EntityKind::Polyline {
+                        pts: flat.into_iter().map(|p| tr.apply(p)).collect(),
+                        closed: *closed,
+                        bulges: vec![],
+                    }
+                }
+            }
+            EntityKind::NurbsCurve2D {
+                degree,
+                knots,
+                ctrl_pts,
+                weights,
+            } => EntityKind::NurbsCurve2D {
+                degree: *degree,
+                knots: knots.clone(),
+                ctrl_pts: ctrl_pts.iter().map(|p| tr.apply(*p)).collect(),
+                weights: weights.clone(),
+            },
+            EntityKind::Text {
+                pos,
+                content,
+                height,
+            } => EntityKind::Text {
+                pos: tr.apply(*pos),
+                content: content.clone(),
+                height: height * tr.mean_scale(),
+            },
+            EntityKind::Insert {
+                block,
+                pos,
+                scale,
+                rotation,
+                attribs,
+            } => {
+                // R(φ)·F·R(θ) = R(φ−θ)·F: при зеркале поворот вставки меняет знак
+                let k = tr.mean_scale();
+                let (rotation, sy) = if mirror {
+                    (tr.angle() - rotation, -scale.y * k)
+                } else {
+                    (tr.angle() + rotation, scale.y * k)
+                };
+                EntityKind::Insert {
+                    block: block.clone(),
+                    pos: tr.apply(*pos),
+                    scale: Pt2::new(scale.x * k, sy),
+                    rotation,
+                    attribs: attribs
+                        .iter()
+                        .map(|a| Attrib {
+                            pos: tr.apply(a.pos),
+                            height: a.height * k,
+                            ..a.clone()
+                        })
+                        .collect(),
+                }
+            }
+            EntityKind::Hatch { loops, fill } => EntityKind::Hatch {
+                loops: loops.iter().map(|l| l.transformed(tr)).collect(),
+                fill: fill.transformed(tr),
+            },
+            EntityKind::Dimension {
+                kind,
+                pts,
+                line_pos,
+                text,
+                style,
+            } => EntityKind::Dimension {
+                kind: match kind {
+                    DimKind::Linear { angle } if mirror => DimKind::Linear {
+                        angle: tr.angle() - angle,
+                    },
+                    DimKind::Linear { angle } => DimKind::Linear {
+                        angle: tr.angle() + angle,
+                    },
+                    k => *k,
+                },
+                pts: pts
+                    .iter()
+                    .map(|p| DimPoint {
+                        pos: tr.apply(p.pos),
+                        link: p.link,
+                    })
+                    .collect(),
+                line_pos: tr.apply(*line_pos),
+                text: text.clone(),
+                style: style.clone(),
+            },
+            // таблица не поворачивается: угол переносится, размеры — по среднему масштабу
+            EntityKind::Table { pos, table } => {
+                let k = tr.mean_scale();
+                EntityKind::Table {
+                    pos: tr.apply(*pos),
+                    table: Table {
+                        columns: table.columns.iter().map(|w| w * k).collect(),
+                        text_height: table.text_height * k,
+                        row_height: table.row_height * k,
+                        ..table.clone()
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Образ эллипса c + u·cos t + v·sin t (v = ratio·⊥u), t ∈ range: ищем главные оси
+/// образа и пересчитываем параметр. `circular` — исходная сущность была дугой/окружностью
+/// и, если образ остался окружностью, вернём Arc/Circle.
+fn conic_transformed(
+    tr: &Affine2,
+    center: Pt2,
+    major: Pt2,
+    ratio: f32,
+    range: (f32, f32),
+    circular: bool,
+    full: bool,
+) -> EntityKind {
+    let c = tr.apply(center);
+    let u = tr.apply_vec(major);
+    let v = tr.apply_vec(Pt2::new(-major.y * ratio, major.x * ratio));
+    let dot = |a: Pt2, b: Pt2| a.x * b.x + a.y * b.y;
+
+    // P(t) = c + M·cos(t−τ) + N·sin(t−τ), где M ⟂ N и |M| ≥ |N|
+    let tau = 0.5 * (2.0 * dot(u, v)).atan2(dot(u, u) - dot(v, v));
+    let (st, ct) = tau.sin_cos();
+    let m = Pt2::new(u.x * ct + v.x * st, u.y * ct + v.y * st);
+    let n = Pt2::new(-u.x * st + v.x * ct, -u.y * st + v.y * ct);
+    let (lm, ln) = (m.x.hypot(m.y), n.x.hypot(n.y));
+    let new_ratio = if lm > 0.0 { ln / lm } else { 1.0 };
+    // N направлен по часовой от M — параметр идёт в обратную сторону
+    let sign = if m.x * n.y - m.y * n.x < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+    let (t0, t1) = (sign * (range.0 - tau), sign * (range.1 - tau));
+
+    if circular && (new_ratio - 1.0).abs() < 1e-4 {
+        if full {
+            return EntityKind::Circle {
+                center: c,
+                radius: lm,
+            };
+        }
+        let phi = m.y.atan2(m.x);
+        return EntityKind::Arc {
+            center: c,
+            radius: lm,
+            start_angle: phi + t0,
+            end_angle: phi + t1,
+        };
+    }
+    let (start_param, end_param) = if full {
+        (0.0, std::f32::consts::TAU)
+    } else {
+        (t0, t1)
+    };
+    EntityKind::Ellipse {
+        center: c,
+        major: m,
+        ratio: new_ratio,
+        start_param,
+        end_param,
+    }
+}
+
 // --------------------------- конвертеры в truck ---------------------------
 
 impl From<Pt2> for Point2 {
@@ -88,6 +582,7 @@ impl From<Point2> for Pt2 {
 }
 
 /// Представление кривой truck'ом
+#[derive(Clone, Debug)]
 pub enum TruckCurve2 {
     BSpline(BSplineCurve<Point2>),
     // ВАЖНО: NURBS в 2D — это NurbsCurve<Vector3> (гомогенные координаты)
@@ -104,7 +599,38 @@ impl EntityKind {
                 Some(TruckCurve2::BSpline(BSplineCurve::new(knots, ctrl)))
             }
 
-            EntityKind::Polyline { pts, closed } => {
+            EntityKind::Polyline {
+                pts,
+                closed,
+                bulges,
+            } if bulges.iter().any(|b| *b != 0.0) => {
+                let mut pieces = Vec::new();
+                for seg in polyline_segments(pts, bulges, *closed) {
+                    match seg {
+                        EntityKind::LineSeg { a, b } => {
+                            let (p0, p2) = (Point2::from(a), Point2::from(b));
+                            pieces.push((p0, p0.midpoint(p2), p2, 1.0));
+                        }
+                        EntityKind::Arc {
+                            center,
+                            radius,
+                            start_angle,
+                            end_angle,
+                        } => {
+                            let c = (center.x as f64, center.y as f64);
+                            let angs = split_angles(start_angle as f64, end_angle as f64);
+                            for w in angs.windows(2) {
+                                pieces.push(arc_segment_ctrl(c, radius as f64, w[0], w[1]));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                let nurbs = nurbs_from_quadratic_pieces(&pieces)?;
+                Some(TruckCurve2::Nurbs(nurbs))
+            }
+
+            EntityKind::Polyline { pts, closed, .. } => {
                 if pts.len() < 2 {
                     return None;
                 }
@@ -143,6 +669,29 @@ impl EntityKind {
                 Some(TruckCurve2::Nurbs(nurbs))
             }
 
+            EntityKind::Circle { center, radius } => {
+                let c = (center.x as f64, center.y as f64);
+                let nurbs = nurbs_arc_deg2(c, *radius as f64, 0.0, std::f64::consts::TAU)?;
+                Some(TruckCurve2::Nurbs(nurbs))
+            }
+
+            EntityKind::Ellipse {
+                center,
+                major,
+                ratio,
+                start_param,
+                end_param,
+            } => {
+                let nurbs = nurbs_ellipse_deg2(
+                    *center,
+                    *major,
+                    *ratio,
+                    *start_param as f64,
+                    *end_param as f64,
+                )?;
+                Some(TruckCurve2::Nurbs(nurbs))
+            }
+
             EntityKind::NurbsCurve2D {
                 degree: _deg,
                 knots,
@@ -162,7 +711,11 @@ impl EntityKind {
                 }
             }
 
-            EntityKind::Text { .. } => None,
+            EntityKind::Text { .. }
+            | EntityKind::Insert { .. }
+            | EntityKind::Hatch { .. }
+            | EntityKind::Dimension { .. }
+            | EntityKind::Table { .. } => None,
         }
     }
 
@@ -172,7 +725,11 @@ impl EntityKind {
             Some(TruckCurve2::BSpline(c)) => sample_curve(&c, steps),
             Some(TruckCurve2::Nurbs(c)) => sample_curve(&c, steps),
             None => match self {
-                EntityKind::Text { .. } => vec![],
+                EntityKind::Text { .. }
+                | EntityKind::Insert { .. }
+                | EntityKind::Hatch { .. }
+                | EntityKind::Dimension { .. }
+                | EntityKind::Table { .. } => vec![],
                 EntityKind::Polyline { pts, .. } => pts.clone(),
                 EntityKind::LineSeg { a, b } => vec![*a, *b],
                 EntityKind::Arc {
@@ -187,6 +744,26 @@ impl EntityKind {
                     let e = *end_angle as f64;
                     sample_arc_fallback(c, r, s, e, steps)
                 }
+                EntityKind::Circle { center, radius } => {
+                    let c = (center.x as f64, center.y as f64);
+                    sample_arc_fallback(c, *radius as f64, 0.0, std::f64::consts::TAU, steps)
+                }
+                EntityKind::Ellipse {
+                    center,
+                    major,
+                    ratio,
+                    start_param,
+                    end_param,
+                } => {
+                    let steps = steps.max(2);
+                    (0..=steps)
+                        .map(|i| {
+                            let t = i as f32 / steps as f32;
+                            let a = start_param + (end_param - start_param) * t;
+                            ellipse_point(*center, *major, *ratio, a)
+                        })
+                        .collect()
+                }
                 EntityKind::NurbsCurve2D { .. } => vec![],
             },
         }
@@ -198,37 +775,39 @@ impl EntityKind {
 /// Точный квадратичный рациональный NURBS для дуги [a0..a1] центра c и радиуса r.
 /// Разбиваем на куски ≤ 90°. Возвращаем None, если сборка не удалась.
 fn nurbs_arc_deg2(c: (f64, f64), r: f64, a0: f64, a1: f64) -> Option<NurbsCurve<Vector3>> {
-    let mut angs = split_angles(a0, a1);
-    if angs.len() < 2 {
-        angs = vec![a0, a1];
-    }
-    let segs = angs.len() - 1;
+    let angs = split_angles(a0, a1);
+    let pieces: Vec<_> = angs
+        .windows(2)
+        .map(|w| arc_segment_ctrl(c, r, w[0], w[1]))
+        .collect();
+    nurbs_from_quadratic_pieces(&pieces)
+}
+
+/// Сшить квадратичные куски (P0, P1, P2, w) в одну кривую degree=2.
+/// Конец каждого куска должен совпадать с началом следующего.
+fn nurbs_from_quadratic_pieces(
+    pieces: &[(Point2, Point2, Point2, f64)],
+) -> Option<NurbsCurve<Vector3>> {
+    let (p0, ..) = pieces.first()?;
+    let segs = pieces.len();
 
     let mut ctrl: Vec<Point2> = Vec::with_capacity(2 * segs + 1);
     let mut wts: Vec<f64> = Vec::with_capacity(2 * segs + 1);
-
-    // первый сегмент
-    let (p0, p1, p2, w) = arc_segment_ctrl(c, r, angs[0], angs[1]);
-    ctrl.push(p0);
-    ctrl.push(p1);
-    ctrl.push(p2);
-    wts.push(1.0);
-    wts.push(w);
+    ctrl.push(*p0);
     wts.push(1.0);
-
-    // остальные сегменты «пришиваем»
-    for k in 1..segs {
-        let (.., q1, q2, wq) = arc_segment_ctrl(c, r, angs[k], angs[k + 1]);
-        ctrl.push(q1);
-        ctrl.push(q2);
-        wts.push(wq);
+    for &(_, p1, p2, w) in pieces {
+        ctrl.push(p1);
+        ctrl.push(p2);
+        wts.push(w);
         wts.push(1.0);
     }
 
-    // degree=2, knots: [0,0,0, s1, ..., 1,1,1]
+    // degree=2, knots: [0,0,0, s1,s1, ..., 1,1,1] — внутренние узлы двойные,
+    // иначе на 2·segs+1 контрольных точек узлов не хватает
     let mut kv = vec![0.0, 0.0, 0.0];
     for i in 1..segs {
-        kv.push(i as f64 / segs as f64);
+        let s = i as f64 / segs as f64;
+        kv.extend_from_slice(&[s, s]);
     }
     kv.extend_from_slice(&[1.0, 1.0, 1.0]);
 
@@ -236,8 +815,115 @@ fn nurbs_arc_deg2(c: (f64, f64), r: f64, a0: f64, a1: f64) -> Option<NurbsCurve<
     NurbsCurve::<Vector3>::try_from_bspline_and_weights(bsp, wts).ok()
 }
 
+// --------------------------- полилиния с выпуклостями ---------------------------
+
+/// Дуга сегмента a → b с выпуклостью `bulge`: (центр, радиус, начальный и конечный угол).
+/// Угол дуги = 4·atan(bulge); конечный угол меньше начального — обход по часовой.
+/// `None` для прямого сегмента.
+pub fn bulge_arc(a: Pt2, b: Pt2, bulge: f32) -> Option<(Pt2, f32, f32, f32)> {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    if bulge == 0.0 || (dx == 0.0 && dy == 0.0) {
+        return None;
+    }
+    // центр — от середины хорды по левой нормали
+    let k = (1.0 - bulge * bulge) / (4.0 * bulge);
+    let center = Pt2::new((a.x + b.x) * 0.5 - dy * k, (a.y + b.y) * 0.5 + dx * k);
+    let radius = (a.x - center.x).hypot(a.y - center.y);
+    let a0 = (a.y - center.y).atan2(a.x - center.x);
+    Some((center, radius, a0, a0 + 4.0 * bulge.atan()))
+}
+
+/// Разложить полилинию на сегменты: `LineSeg` для прямых и `Arc` для дуговых.
+pub fn polyline_segments(pts: &[Pt2], bulges: &[f32], closed: bool) -> Vec<EntityKind> {
+    let n = pts.len();
+    let count = if closed && n > 2 {
+        n
+    } else {
+        n.saturating_sub(1)
+    };
+    (0..count)
+        .map(|i| {
+            let (a, b) = (pts[i], pts[(i + 1) % n]);
+            let bulge = bulges.get(i).copied().unwrap_or(0.0);
+            match bulge_arc(a, b, bulge) {
+                Some((center, radius, start_angle, end_angle)) => EntityKind::Arc {
+                    center,
+                    radius,
+                    start_angle,
+                    end_angle,
+                },
+                None => EntityKind::LineSeg { a, b },
+            }
+        })
+        .collect()
+}
+
+/// Полилиния как ломаная: дуговые сегменты дробятся так, чтобы шаг был не больше
+/// `max_step` радиан. Для замкнутой первая точка повторяется в конце.
+pub fn flatten_polyline(pts: &[Pt2], bulges: &[f32], closed: bool, max_step: f32) -> Vec<Pt2> {
+    let n = pts.len();
+    let count = if closed && n > 2 {
+        n
+    } else {
+        n.saturating_sub(1)
+    };
+    let mut out = Vec::with_capacity(n + 1);
+    out.extend(pts.first().copied());
+    for i in 0..count {
+        let (a, b) = (pts[i], pts[(i + 1) % n]);
+        let bulge = bulges.get(i).copied().unwrap_or(0.0);
+        if let Some((c, r, a0, a1)) = bulge_arc(a, b, bulge) {
+            let steps = ((a1 - a0).abs() / max_step.max(1e-3)).ceil() as usize;
+            for k in 1..steps {
+                let t = a0 + (a1 - a0) * (k as f32 / steps as f32);
+                out.push(Pt2::new(c.x + r * t.cos(), c.y + r * t.sin()));
+            }
+        }
+        out.push(b);
+    }
+    out
+}
+
+/// Эллиптическая дуга: дуга единичной окружности, отображённая аффинно
+/// (c + major·x + minor·y) — веса при этом не меняются.
+fn nurbs_ellipse_deg2(
+    center: Pt2,
+    major: Pt2,
+    ratio: f32,
+    a0: f64,
+    a1: f64,
+) -> Option<NurbsCurve<Vector3>> {
+    let unit = nurbs_arc_deg2((0.0, 0.0), 1.0, a0, a1)?;
+    let (c, m) = (
+        Point2::from(center),
+        Vector2::new(major.x as f64, major.y as f64),
+    );
+    let n = Vector2::new(-m.y, m.x) * ratio as f64;
+    let ctrl: Vec<Point2> = unit
+        .control_points()
+        .iter()
+        .map(|h| {
+            let (x, y) = (h.x / h.z, h.y / h.z);
+            c + m * x + n * y
+        })
+        .collect();
+    let wts: Vec<f64> = unit.control_points().iter().map(|h| h.z).collect();
+    let bsp = BSplineCurve::new(unit.knot_vec().clone(), ctrl);
+    NurbsCurve::<Vector3>::try_from_bspline_and_weights(bsp, wts).ok()
+}
+
+/// Точка эллипса по параметру `t` (радианы), нормаль +Z.
+pub fn ellipse_point(center: Pt2, major: Pt2, ratio: f32, t: f32) -> Pt2 {
+    let (s, c) = t.sin_cos();
+    Pt2::new(
+        center.x + major.x * c - ratio * major.y * s,
+        center.y + major.y * c + ratio * major.x * s,
+    )
+}
+
 /// Один дуговой сегмент (≤ 90°): (P0, P1, P2, w), w = cos(Δ/2).
-/// ВАЖНО: P1 = C + ((P0-C)+(P2-C)) / (2w), иначе дуга «сползёт» при смещённом центре.
+/// ВАЖНО: P1 = C + ((P0-C)+(P2-C)) / (2w²) — на биссектрисе на расстоянии r/w от центра;
+/// считаем от C, иначе дуга «сползёт» при смещённом центре.
 fn arc_segment_ctrl(c: (f64, f64), r: f64, a0: f64, a1: f64) -> (Point2, Point2, Point2, f64) {
     let (cx, cy) = c;
     let (s0, c0) = a0.sin_cos();
@@ -248,24 +934,20 @@ fn arc_segment_ctrl(c: (f64, f64), r: f64, a0: f64, a1: f64) -> (Point2, Point2,
     let dm = 0.5 * (a1 - a0);
     let w = dm.cos(); // вес средней
     let p1 = Point2::new(
-        cx + ((p0.x - cx) + (p2.x - cx)) / (2.0 * w),
-        cy + ((p0.y - cy) + (p2.y - cy)) / (2.0 * w),
+        cx + ((p0.x - cx) + (p2.x - cx)) / (2.0 * w * w),
+        cy + ((p0.y - cy) + (p2.y - cy)) / (2.0 * w * w),
     );
     (p0, p1, p2, w)
 }
 
-/// Разбить [a0..a1] на куски по ≤ 90° (сохраняем направление)
+/// Разбить [a0..a1] на равные куски по ≤ 90° (сохраняем направление).
+/// Допуск нужен для углов из f32: π/2 чуть больше π/2 не должно давать лишний кусок.
 fn split_angles(a0: f64, a1: f64) -> Vec<f64> {
-    let mut start = a0;
-    let mut v = vec![start];
     let step = std::f64::consts::FRAC_PI_2;
-    let dir = if a1 >= a0 { 1.0 } else { -1.0 };
-    while (a1 - start) * dir > step {
-        start += dir * step;
-        v.push(start);
-    }
-    v.push(a1);
-    v
+    let n = ((a1 - a0).abs() / step - 1e-6).ceil().max(1.0) as usize;
+    (0..=n)
+        .map(|i| a0 + (a1 - a0) * (i as f64 / n as f64))
+        .collect()
 }
 
 // --------------------------- сэмплинг ---------------------------
@@ -0,0 +1,698 @@
+use crate::{flatten_polyline, Affine2, Document, EntityKind, Pt2};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::PI;
+
+// --------------------------- контуры ---------------------------
+
+/// Замкнутый контур штриховки: цепочка рёбер «конец к началу»
+/// (LineSeg, Arc, Circle, Ellipse, NurbsCurve2D, Polyline).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HatchLoop {
+    pub edges: Vec<EntityKind>,
+}
+
+impl HatchLoop {
+    /// Контур из замкнутой полилинии (с выпуклостями) — одним ребром.
+    pub fn polyline(pts: Vec<Pt2>, bulges: Vec<f32>) -> Self {
+        Self {
+            edges: vec![EntityKind::Polyline {
+                pts,
+                closed: true,
+                bulges,
+            }],
+        }
+    }
+
+    /// Ломаная контура без повтора первой точки в конце.
+    pub fn polygon(&self) -> Vec<Pt2> {
+        let mut out: Vec<Pt2> = Vec::new();
+        for e in &self.edges {
+            for p in curve_points(e) {
+                if out.last().is_none_or(|q| !same_pt(*q, p)) {
+                    out.push(p);
+                }
+            }
+        }
+        if out.len() > 1 && same_pt(out[0], out[out.len() - 1]) {
+            out.pop();
+        }
+        out
+    }
+
+    pub fn transformed(&self, tr: &Affine2) -> Self {
+        Self {
+            edges: self.edges.iter().map(|e| e.transformed(tr)).collect(),
+        }
+    }
+}
+
+fn same_pt(a: Pt2, b: Pt2) -> bool {
+    (a.x - b.x).abs() <= 1e-6 * (1.0 + a.x.abs()) && (a.y - b.y).abs() <= 1e-6 * (1.0 + a.y.abs())
+}
+
+/// Шаг по углу при разбиении дуг контура.
+const ARC_STEP: f32 = PI / 48.0;
+
+fn arc_steps(sweep: f32) -> usize {
+    ((sweep.abs() / ARC_STEP).ceil() as usize).max(2)
+}
+
+/// Кривая → ломаная для контуров и поиска границ. Текст, вставки и штриховки — пусто.
+pub(crate) fn curve_points(kind: &EntityKind) -> Vec<Pt2> {
+    match kind {
+        EntityKind::LineSeg { a, b } => vec![*a, *b],
+        EntityKind::Arc {
+            start_angle,
+            end_angle,
+            ..
+        } => kind.sample(arc_steps(end_angle - start_angle)),
+        EntityKind::Circle { .. } => kind.sample(arc_steps(2.0 * PI)),
+        EntityKind::Ellipse {
+            start_param,
+            end_param,
+            ..
+        } => kind.sample(arc_steps(end_param - start_param)),
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => flatten_polyline(pts, bulges, *closed, ARC_STEP),
+        EntityKind::NurbsCurve2D { .. } => kind.sample(128),
+        EntityKind::Text { .. }
+        | EntityKind::Insert { .. }
+        | EntityKind::Hatch { .. }
+        | EntityKind::Dimension { .. }
+        | EntityKind::Table { .. } => vec![],
+    }
+}
+
+// --------------------------- заполнение ---------------------------
+
+/// Семейство параллельных линий образца в координатах чертежа: линии направления `angle`
+/// (рад) проходят через `origin + k·offset`, штрихи — как у типа линии (штрих > 0,
+/// пробел < 0, точка = 0), отсчёт от базовой точки каждой линии.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HatchLine {
+    pub angle: f32,
+    pub origin: Pt2,
+    pub offset: Pt2,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dashes: Vec<f32>,
+}
+
+impl HatchLine {
+    /// Строка .pat: угол в градусах, базовая точка, смещение (dx вдоль линии, dy поперёк).
+    pub fn from_pat(angle_deg: f32, x: f32, y: f32, dx: f32, dy: f32, dashes: &[f32]) -> Self {
+        let a = angle_deg.to_radians();
+        let (s, c) = a.sin_cos();
+        Self {
+            angle: a,
+            origin: Pt2::new(x, y),
+            offset: Pt2::new(dx * c - dy * s, dx * s + dy * c),
+            dashes: dashes.to_vec(),
+        }
+    }
+
+    pub fn transformed(&self, tr: &Affine2) -> Self {
+        let d = tr.apply_vec(Pt2::new(self.angle.cos(), self.angle.sin()));
+        let k = tr.mean_scale();
+        Self {
+            angle: d.y.atan2(d.x),
+            origin: tr.apply(self.origin),
+            offset: tr.apply_vec(self.offset),
+            dashes: self.dashes.iter().map(|v| v * k).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HatchFill {
+    Solid,
+    /// `lines` уже повёрнуты на `angle` и отмасштабированы на `scale`;
+    /// имя, угол и масштаб хранятся для DXF и правки.
+    Pattern {
+        name: String,
+        angle: f32,
+        scale: f32,
+        lines: Vec<HatchLine>,
+    },
+}
+
+impl HatchFill {
+    /// Образец из встроенной библиотеки (`pattern_names`), угол в радианах.
+    pub fn pattern(name: &str, angle: f32, scale: f32) -> Option<Self> {
+        let lines = library_pattern(name)?;
+        Some(Self::custom(name.to_ascii_uppercase(), lines, angle, scale))
+    }
+
+    /// Пользовательские семейства линий в единицах образца.
+    pub fn custom(name: impl Into<String>, lines: Vec<HatchLine>, angle: f32, scale: f32) -> Self {
+        let tr = Affine2::scaling(Pt2::new(0.0, 0.0), scale, scale)
+            .then(&Affine2::rotation(Pt2::new(0.0, 0.0), angle));
+        HatchFill::Pattern {
+            name: name.into(),
+            angle,
+            scale,
+            lines: lines.iter().map(|l| l.transformed(&tr)).collect(),
+        }
+    }
+
+    pub fn transformed(&self, tr: &Affine2) -> Self {
+        match self {
+            HatchFill::Solid => HatchFill::Solid,
+            HatchFill::Pattern {
+                name,
+                angle,
+                scale,
+                lines,
+            } => HatchFill::Pattern {
+                name: name.clone(),
+                angle: angle + tr.angle(),
+                scale: scale * tr.mean_scale(),
+                lines: lines.iter().map(|l| l.transformed(tr)).collect(),
+            },
+        }
+    }
+}
+
+/// Имена встроенных образцов; "SOLID" — сплошная заливка.
+pub fn pattern_names() -> &'static [&'static str] {
+    &["SOLID", "ANSI31", "ANSI37", "AR-CONC", "INSUL"]
+}
+
+/// Определения образцов в мм, как в acadiso.pat.
+fn library_pattern(name: &str) -> Option<Vec<HatchLine>> {
+    let l = HatchLine::from_pat;
+    let lines = match name.to_ascii_uppercase().as_str() {
+        // металл, общий разрез
+        "ANSI31" => vec![l(45.0, 0.0, 0.0, 0.0, 3.175, &[])],
+        "ANSI37" => vec![
+            l(45.0, 0.0, 0.0, 0.0, 3.175, &[]),
+            l(135.0, 0.0, 0.0, 0.0, 3.175, &[]),
+        ],
+        // бетон: «камни» и точки
+        "AR-CONC" => {
+            type Row = (f32, f32, f32, f32, f32, &'static [f32]);
+            const ROWS: &[Row] = &[
+                (50.0, 0.0, 0.0, 4.12975, -5.897895, &[0.75, -8.25]),
+                (355.0, 0.0, 0.0, -2.037812, 7.372368, &[0.6, -6.6]),
+                (
+                    100.45145,
+                    0.597717,
+                    -0.052293,
+                    5.730587,
+                    6.939767,
+                    &[0.637402, -7.011421],
+                ),
+                (46.184283, 0.0, 2.0, 6.207147, -4.166214, &[0.93, -10.23]),
+                (
+                    96.63556,
+                    0.889367,
+                    1.862067,
+                    8.900991,
+                    8.070463,
+                    &[0.927499, -10.202492],
+                ),
+                (351.18415, 0.0, 2.0, 7.533705, 2.111974, &[0.9358, -10.2938]),
+                (21.0, 1.0, 1.5, 4.11242, -5.26403, &[0.6, -6.6]),
+                (326.0, 1.0, 1.5, -1.120242, 6.60347, &[0.5, -5.5]),
+                (71.0, 0.5, 2.0, 6.119404, 4.647387, &[0.64, -7.04]),
+                (
+                    37.5,
+                    0.0,
+                    0.0,
+                    2.10363,
+                    -5.16634,
+                    &[0.0, -3.2, 0.0, -4.4, 0.0, -2.4],
+                ),
+                (
+                    7.5,
+                    0.0,
+                    0.0,
+                    4.17586,
+                    -4.29593,
+                    &[0.0, -3.7, 0.0, -5.8, 0.0, -2.6],
+                ),
+                (
+                    -32.5,
+                    -2.31923,
+                    -1.74618,
+                    4.34011,
+                    4.09698,
+                    &[0.0, -2.7, 0.0, -4.1, 0.0, -2.9],
+                ),
+                (
+                    -42.5,
+                    -2.5,
+                    -1.5,
+                    5.3,
+                    -1.2,
+                    &[0.0, -3.3, 0.0, -4.3, 0.0, -3.1],
+                ),
+            ];
+            // в acad.pat — дюймы
+            const INCH: f32 = 25.4;
+            ROWS.iter()
+                .map(|&(a, x, y, dx, dy, dashes)| {
+                    let dashes: Vec<f32> = dashes.iter().map(|v| v * INCH).collect();
+                    l(a, x * INCH, y * INCH, dx * INCH, dy * INCH, &dashes)
+                })
+                .collect()
+        }
+        // теплоизоляция
+        "INSUL" => vec![
+            l(0.0, 0.0, 0.0, 0.0, 9.525, &[]),
+            l(0.0, 0.0, 3.175, 0.0, 9.525, &[3.175, -3.175]),
+            l(0.0, 0.0, 6.35, 0.0, 9.525, &[3.175, -3.175]),
+        ],
+        _ => return None,
+    };
+    Some(lines)
+}
+
+// --------------------------- геометрия заливки ---------------------------
+
+/// Предел числа линий одного семейства — защита от слишком мелкого масштаба.
+const MAX_PATTERN_LINES: i64 = 20_000;
+
+/// Отрезки образца внутри контуров (правило чёт-нечет). Точки образца — отрезки нулевой длины.
+pub fn pattern_segments(polygons: &[Vec<Pt2>], lines: &[HatchLine]) -> Vec<[Pt2; 2]> {
+    let mut out = Vec::new();
+    let edges: Vec<(Pt2, Pt2)> = polygons
+        .iter()
+        .filter(|p| p.len() >= 3)
+        .flat_map(|p| (0..p.len()).map(move |i| (p[i], p[(i + 1) % p.len()])))
+        .collect();
+    if edges.is_empty() {
+        return out;
+    }
+
+    for fam in lines {
+        let d = (fam.angle.cos(), fam.angle.sin());
+        let n = (-d.1, d.0);
+        let dot = |v: (f32, f32), w: (f32, f32)| v.0 * w.0 + v.1 * w.1;
+        let spacing = dot((fam.offset.x, fam.offset.y), n);
+        if spacing.abs() < 1e-6 {
+            continue;
+        }
+        let o = (fam.origin.x, fam.origin.y);
+        let (mut lo, mut hi) = (f32::INFINITY, f32::NEG_INFINITY);
+        for (a, _) in &edges {
+            let h = dot((a.x - o.0, a.y - o.1), n);
+            lo = lo.min(h);
+            hi = hi.max(h);
+        }
+        let (k0, k1) = {
+            let (a, b) = ((lo / spacing).floor() as i64, (hi / spacing).ceil() as i64);
+            (a.min(b), a.max(b))
+        };
+        if k1 - k0 > MAX_PATTERN_LINES {
+            continue;
+        }
+
+        let period: f32 = fam.dashes.iter().map(|v| v.abs()).sum();
+        for k in k0..=k1 {
+            let base = (o.0 + fam.offset.x * k as f32, o.1 + fam.offset.y * k as f32);
+            let mut ts: Vec<f32> = Vec::new();
+            for (p, q) in &edges {
+                let hp = dot((p.x - base.0, p.y - base.1), n);
+                let hq = dot((q.x - base.0, q.y - base.1), n);
+                // полуоткрытое правило — вершина на линии считается один раз
+                if (hp > 0.0) != (hq > 0.0) {
+                    let tp = dot((p.x - base.0, p.y - base.1), d);
+                    let tq = dot((q.x - base.0, q.y - base.1), d);
+                    ts.push(tp + (tq - tp) * hp / (hp - hq));
+                }
+            }
+            ts.sort_by(f32::total_cmp);
+            let at = |t: f32| Pt2::new(base.0 + d.0 * t, base.1 + d.1 * t);
+            for w in ts.chunks_exact(2) {
+                let (t0, t1) = (w[0], w[1]);
+                if fam.dashes.is_empty() || period <= 1e-6 {
+                    out.push([at(t0), at(t1)]);
+                    continue;
+                }
+                let mut pos = (t0 / period).floor() * period;
+                'dash: loop {
+                    for &len in &fam.dashes {
+                        if pos > t1 {
+                            break 'dash;
+                        }
+                        if len >= 0.0 {
+                            let (a, b) = (pos.max(t0), (pos + len).min(t1));
+                            if a < b || (len == 0.0 && pos >= t0) {
+                                out.push([at(a), at(b)]);
+                            }
+                        }
+                        pos += len.abs();
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Треугольники сплошной заливки. Контуры разбираются по вложенности: чётная глубина —
+/// заливаемая область, нечётная — её остров.
+pub fn fill_triangles(polygons: &[Vec<Pt2>]) -> Vec<[Pt2; 3]> {
+    let polys: Vec<&Vec<Pt2>> = polygons.iter().filter(|p| p.len() >= 3).collect();
+    let contains = |outer: &Vec<Pt2>, inner: &Vec<Pt2>| point_in_polygon(inner[0], outer);
+    let depth: Vec<usize> = polys
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            polys
+                .iter()
+                .enumerate()
+                .filter(|(j, q)| *j != i && contains(q, p))
+                .count()
+        })
+        .collect();
+
+    let mut out = Vec::new();
+    for (i, outer) in polys.iter().enumerate() {
+        if depth[i] % 2 == 1 {
+            continue;
+        }
+        let mut coords: Vec<f64> = Vec::new();
+        let mut holes: Vec<usize> = Vec::new();
+        let mut pts: Vec<Pt2> = Vec::new();
+        let mut push = |poly: &Vec<Pt2>, coords: &mut Vec<f64>| {
+            for p in poly {
+                coords.extend([p.x as f64, p.y as f64]);
+                pts.push(*p);
+            }
+        };
+        push(outer, &mut coords);
+        for (j, hole) in polys.iter().enumerate() {
+            if depth[j] == depth[i] + 1 && contains(outer, hole) {
+                holes.push(coords.len() / 2);
+                push(hole, &mut coords);
+            }
+        }
+        if let Ok(idx) = earcutr::earcut(&coords, &holes, 2) {
+            out.extend(
+                idx.chunks_exact(3)
+                    .map(|t| [pts[t[0]], pts[t[1]], pts[t[2]]]),
+            );
+        }
+    }
+    out
+}
+
+pub fn point_in_polygon(p: Pt2, poly: &[Pt2]) -> bool {
+    let mut inside = false;
+    let n = poly.len();
+    for i in 0..n {
+        let (a, b) = (poly[i], poly[(i + n - 1) % n]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (b.x - a.x) * (p.y - a.y) / (b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn signed_area(poly: &[Pt2]) -> f64 {
+    let n = poly.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (poly[i], poly[(i + 1) % n]);
+            a.x as f64 * b.y as f64 - b.x as f64 * a.y as f64
+        })
+        .sum::<f64>()
+        * 0.5
+}
+
+// --------------------------- поиск контура по точке ---------------------------
+
+/// Контуры штриховки вокруг точки `p` по видимой геометрии документа (как BHATCH):
+/// внешний — наименьшая замкнутая область, содержащая точку; острова — внешние контуры
+/// не связанной с ним геометрии, лежащей прямо в этой области. `None`, если точка не
+/// окружена. Кривые аппроксимируются ломаными.
+pub fn detect_boundary(doc: &Document, p: Pt2) -> Option<Vec<HatchLoop>> {
+    let mut polylines: Vec<Vec<Pt2>> = Vec::new();
+    for e in &doc.entities {
+        if !doc.is_layer_visible(&e.layer) {
+            continue;
+        }
+        if let EntityKind::Insert { .. } = e.kind {
+            for sub in doc.insert_geometry(e) {
+                if doc.is_layer_visible(&sub.layer) {
+                    polylines.push(curve_points(&sub.kind));
+                }
+            }
+        } else {
+            polylines.push(curve_points(&e.kind));
+        }
+    }
+    let graph = PlanarGraph::build(&polylines, 0.0);
+    let cycles = graph.cycles();
+
+    let (outer_i, outer) = cycles
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.area > 0.0 && point_in_polygon(p, &c.pts))
+        .min_by(|a, b| a.1.area.total_cmp(&b.1.area))?;
+
+    let mut loops = vec![HatchLoop::polyline(outer.pts.clone(), vec![])];
+    for c in cycles
+        .iter()
+        .filter(|c| c.area < 0.0 && c.comp != outer.comp)
+    {
+        // хозяин острова — наименьшая область чужой компоненты вокруг него
+        let owner = cycles
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.area > 0.0 && o.comp != c.comp && point_in_polygon(c.pts[0], &o.pts))
+            .min_by(|a, b| a.1.area.total_cmp(&b.1.area))
+            .map(|(i, _)| i);
+        if owner == Some(outer_i) {
+            loops.push(HatchLoop::polyline(c.pts.clone(), vec![]));
+        }
+    }
+    Some(loops)
+}
+
+pub(crate) struct Cycle {
+    pub(crate) pts: Vec<Pt2>,
+    /// > 0 — обход против часовой (ограниченная грань), < 0 — внешняя граница компоненты
+    pub(crate) area: f64,
+    pub(crate) comp: usize,
+}
+
+/// Плоский граф из ломаных: отрезки разбиты в точках пересечения, висячие рёбра удалены.
+pub(crate) struct PlanarGraph {
+    verts: Vec<Pt2>,
+    adj: Vec<Vec<usize>>,
+}
+
+impl PlanarGraph {
+    /// `tol` — допуск привязки: концы ближе него сливаются, зазор до чужого отрезка
+    /// меньше него замыкается. 0 — только погрешность вычислений.
+    pub(crate) fn build(polylines: &[Vec<Pt2>], tol: f64) -> Self {
+        let segs: Vec<(Pt2, Pt2)> = polylines
+            .iter()
+            .flat_map(|pl| pl.windows(2).map(|w| (w[0], w[1])))
+            .filter(|(a, b)| a != b)
+            .collect();
+
+        let (mut lo, mut hi) = (
+            Pt2::new(f32::INFINITY, f32::INFINITY),
+            Pt2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+        );
+        for (a, b) in &segs {
+            for q in [a, b] {
+                lo = Pt2::new(lo.x.min(q.x), lo.y.min(q.y));
+                hi = Pt2::new(hi.x.max(q.x), hi.y.max(q.y));
+            }
+        }
+        let auto = (((hi.x - lo.x) as f64).hypot((hi.y - lo.y) as f64) * 1e-6).max(1e-9);
+        let eps = auto.max(tol);
+
+        // параметры разбиения каждого отрезка
+        let mut cuts: Vec<Vec<f64>> = vec![vec![0.0, 1.0]; segs.len()];
+        let bbox = |s: &(Pt2, Pt2)| {
+            (
+                s.0.x.min(s.1.x) as f64 - eps,
+                s.0.y.min(s.1.y) as f64 - eps,
+                s.0.x.max(s.1.x) as f64 + eps,
+                s.0.y.max(s.1.y) as f64 + eps,
+            )
+        };
+        let boxes: Vec<_> = segs.iter().map(bbox).collect();
+        for i in 0..segs.len() {
+            for j in i + 1..segs.len() {
+                let (a, b) = (boxes[i], boxes[j]);
+                if a.2 < b.0 || b.2 < a.0 || a.3 < b.1 || b.3 < a.1 {
+                    continue;
+                }
+                for (ti, tj) in seg_intersections(segs[i], segs[j], eps) {
+                    cuts[i].push(ti);
+                    cuts[j].push(tj);
+                }
+            }
+        }
+
+        // вершины с привязкой по сетке eps
+        let mut verts: Vec<Pt2> = Vec::new();
+        let mut grid: HashMap<(i64, i64), usize> = HashMap::new();
+        let cell = (auto * 4.0).max(tol);
+        let mut vertex = |p: (f64, f64)| -> usize {
+            let key = ((p.0 / cell).round() as i64, (p.1 / cell).round() as i64);
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    if let Some(&v) = grid.get(&(key.0 + dx, key.1 + dy)) {
+                        let q = verts[v];
+                        if (q.x as f64 - p.0).hypot(q.y as f64 - p.1) <= cell {
+                            return v;
+                        }
+                    }
+                }
+            }
+            verts.push(Pt2::new(p.0 as f32, p.1 as f32));
+            grid.insert(key, verts.len() - 1);
+            verts.len() - 1
+        };
+
+        let mut edges: HashSet<(usize, usize)> = HashSet::new();
+        for (s, ts) in segs.iter().zip(cuts.iter_mut()) {
+            ts.sort_by(f64::total_cmp);
+            let at = |t: f64| {
+                (
+                    s.0.x as f64 + (s.1.x as f64 - s.0.x as f64) * t,
+                    s.0.y as f64 + (s.1.y as f64 - s.0.y as f64) * t,
+                )
+            };
+            let ids: Vec<usize> = ts.iter().map(|&t| vertex(at(t))).collect();
+            for w in ids.windows(2) {
+                if w[0] != w[1] {
+                    edges.insert((w[0].min(w[1]), w[0].max(w[1])));
+                }
+            }
+        }
+
+        let mut adj: Vec<Vec<usize>> = vec![Vec::new(); verts.len()];
+        for &(a, b) in &edges {
+            adj[a].push(b);
+            adj[b].push(a);
+        }
+        // висячие рёбра не ограничивают областей
+        let mut stack: Vec<usize> = (0..verts.len()).filter(|&v| adj[v].len() == 1).collect();
+        while let Some(v) = stack.pop() {
+            if let [u] = adj[v][..] {
+                adj[v].clear();
+                adj[u].retain(|&w| w != v);
+                if adj[u].len() == 1 {
+                    stack.push(u);
+                }
+            }
+        }
+        for v in 0..verts.len() {
+            let c = verts[v];
+            let ang = |w: &usize| (verts[*w].y - c.y).atan2(verts[*w].x - c.x);
+            adj[v].sort_by(|a, b| ang(a).total_cmp(&ang(b)));
+        }
+        Self { verts, adj }
+    }
+
+    fn components(&self) -> Vec<usize> {
+        let mut comp = vec![usize::MAX; self.verts.len()];
+        let mut next = 0;
+        for s in 0..self.verts.len() {
+            if comp[s] != usize::MAX {
+                continue;
+            }
+            let mut stack = vec![s];
+            comp[s] = next;
+            while let Some(v) = stack.pop() {
+                for &w in &self.adj[v] {
+                    if comp[w] == usize::MAX {
+                        comp[w] = next;
+                        stack.push(w);
+                    }
+                }
+            }
+            next += 1;
+        }
+        comp
+    }
+
+    /// Обход граней: грань слева от направленного ребра, в вершине берём ближайшее
+    /// ребро по часовой от обратного.
+    pub(crate) fn cycles(&self) -> Vec<Cycle> {
+        let comp = self.components();
+        let mut used: HashSet<(usize, usize)> = HashSet::new();
+        let mut out = Vec::new();
+        for (u0, around0) in self.adj.iter().enumerate() {
+            for &v0 in around0 {
+                if used.contains(&(u0, v0)) {
+                    continue;
+                }
+                let mut pts = Vec::new();
+                let (mut u, mut v) = (u0, v0);
+                while used.insert((u, v)) {
+                    pts.push(self.verts[u]);
+                    let around = &self.adj[v];
+                    let i = around.iter().position(|&w| w == u).unwrap_or(0);
+                    let next = around[(i + around.len() - 1) % around.len()];
+                    (u, v) = (v, next);
+                }
+                if pts.len() >= 3 {
+                    let area = signed_area(&pts);
+                    out.push(Cycle {
+                        pts,
+                        area,
+                        comp: comp[u0],
+                    });
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Параметры точек пересечения отрезков (на первом и втором). Для наложенных коллинеарных —
+/// концы одного, лежащие на другом.
+fn seg_intersections(s: (Pt2, Pt2), r: (Pt2, Pt2), eps: f64) -> Vec<(f64, f64)> {
+    let (p, q) = ((s.0.x as f64, s.0.y as f64), (r.0.x as f64, r.0.y as f64));
+    let d1 = (s.1.x as f64 - p.0, s.1.y as f64 - p.1);
+    let d2 = (r.1.x as f64 - q.0, r.1.y as f64 - q.1);
+    let cross = |a: (f64, f64), b: (f64, f64)| a.0 * b.1 - a.1 * b.0;
+    let (l1, l2) = (d1.0.hypot(d1.1), d2.0.hypot(d2.1));
+    if l1 == 0.0 || l2 == 0.0 {
+        return vec![];
+    }
+    let w = (q.0 - p.0, q.1 - p.1);
+    let den = cross(d1, d2);
+    if den.abs() > 1e-12 * l1 * l2 {
+        let t = cross(w, d2) / den;
+        let u = cross(w, d1) / den;
+        let (et, eu) = (eps / l1, eps / l2);
+        if (-et..=1.0 + et).contains(&t) && (-eu..=1.0 + eu).contains(&u) {
+            return vec![(t.clamp(0.0, 1.0), u.clamp(0.0, 1.0))];
+        }
+        return vec![];
+    }
+    // параллельны: коллинеарны, если расстояние между прямыми мало
+    if cross(w, d1).abs() / l1 > eps {
+        return vec![];
+    }
+    let proj1 = |x: (f64, f64)| ((x.0 - p.0) * d1.0 + (x.1 - p.1) * d1.1) / (l1 * l1);
+    let proj2 = |x: (f64, f64)| ((x.0 - q.0) * d2.0 + (x.1 - q.1) * d2.1) / (l2 * l2);
+    let mut out = Vec::new();
+    for (x, on_r) in [(q, 0.0), ((r.1.x as f64, r.1.y as f64), 1.0)] {
+        let t = proj1(x);
+        if (0.0..=1.0).contains(&t) {
+            out.push((t, on_r));
+        }
+    }
+    for (x, on_s) in [(p, 0.0), ((s.1.x as f64, s.1.y as f64), 1.0)] {
+        let u = proj2(x);
+        if (0.0..=1.0).contains(&u) {
+            out.push((on_s, u));
+        }
+    }
+    out
+}
@@ -0,0 +1,434 @@
+//! Отмена и повтор правок документа. Пока открыта транзакция, документ сам пишет
+//! журнал: добавленные и удалённые сущности, прежние версии изменённых, прежние
+//! таблицы слоёв и блоков. Отмена и повтор меняют сохранённое с текущим местами,
+//! так что каждая запись хранит одно состояние.
+
+use crate::{BlockDef, Document, Entity, EntityKind, Layer};
+use std::collections::{HashSet, VecDeque};
+use std::mem::{size_of, swap};
+
+/// Предел памяти истории по умолчанию, байт (оценка).
+pub const HISTORY_BYTES: usize = 64 << 20;
+/// Предел числа шагов по умолчанию.
+pub const HISTORY_STEPS: usize = 500;
+
+/// Запись журнала. Применение записи переводит документ между состояниями до и
+/// после правки в обе стороны.
+#[derive(Debug, Clone)]
+pub(crate) enum Change {
+    /// Сущность `id` есть в документе ровно в одном из состояний. `stash` хранит её,
+    /// пока её нет; `next` — за какой сущностью она стоит (нет — в конце).
+    Presence {
+        id: u64,
+        next: Option<u64>,
+        stash: Option<Entity>,
+    },
+    /// Другая версия сущности с тем же id.
+    Modified(Entity),
+    Layers(Vec<Layer>),
+    Blocks(Vec<BlockDef>),
+    /// Документ целиком (открытие файла).
+    Document(Box<Document>),
+}
+
+impl Change {
+    fn apply(&mut self, doc: &mut Document) {
+        match self {
+            Change::Presence { id, next, stash } => match stash.take() {
+                Some(e) => {
+                    doc.index.invalidate(e.id);
+                    restore(doc, vec![(e, *next)]);
+                }
+                None => {
+                    let before = doc.entities.generation();
+                    *stash = doc.entities.remove(*id);
+                    if stash.is_some() {
+                        doc.index.forget(*id);
+                        doc.index.follow(before);
+                    }
+                }
+            },
+            Change::Modified(e) => {
+                let before = doc.entities.generation();
+                if let Some(cur) = doc.entities.get_mut(e.id) {
+                    swap(cur, e);
+                    doc.index.invalidate(cur.id);
+                    doc.index.follow(before);
+                }
+            }
+            Change::Layers(l) => swap(&mut doc.layers, l),
+            Change::Blocks(b) => {
+                swap(&mut doc.blocks, b);
+                doc.invalidate_block_refs();
+            }
+            Change::Document(d) => {
+                swap(doc, d);
+                doc.journal = None;
+            }
+        }
+    }
+
+    fn bytes(&self) -> usize {
+        size_of::<Self>()
+            + match self {
+                Change::Presence { stash, .. } => stash.as_ref().map_or(0, entity_bytes),
+                Change::Modified(e) => entity_bytes(e),
+                Change::Layers(l) => l.len() * size_of::<Layer>(),
+                Change::Blocks(b) => b
+                    .iter()
+                    .map(|b| {
+                        size_of::<BlockDef>() + b.entities.iter().map(entity_bytes).sum::<usize>()
+                    })
+                    .sum(),
+                Change::Document(d) => d.entities.iter().map(entity_bytes).sum(),
+            }
+    }
+}
+
+/// Грубая оценка памяти сущности.
+fn entity_bytes(e: &Entity) -> usize {
+    size_of::<Entity>() + e.layer.len() + e.linetype.len() + kind_bytes(&e.kind)
+}
+
+fn kind_bytes(k: &EntityKind) -> usize {
+    match k {
+        EntityKind::Polyline { pts, bulges, .. } => pts.len() * 8 + bulges.len() * 4,
+        EntityKind::NurbsCurve2D {
+            knots,
+            ctrl_pts,
+            weights,
+            ..
+        } => knots.len() * 4 + ctrl_pts.len() * 8 + weights.as_ref().map_or(0, |w| w.len() * 4),
+        EntityKind::Text { content, .. } => content.len(),
+        EntityKind::Insert { block, attribs, .. } => block.len() + attribs.len() * 64,
+        EntityKind::Hatch { loops, .. } => loops
+            .iter()
+            .flat_map(|l| &l.edges)
+            .map(|k| size_of::<EntityKind>() + kind_bytes(k))
+            .sum(),
+        EntityKind::Dimension { pts, text, .. } => pts.len() * 32 + text.len(),
+        _ => 0,
+    }
+}
+
+/// Журнал открытой транзакции, живёт в документе.
+#[derive(Clone, Default)]
+pub(crate) struct Journal {
+    changes: Vec<Change>,
+    /// Сущности, чьё прежнее состояние уже записано
+    seen: HashSet<u64>,
+    layers: bool,
+    blocks: bool,
+}
+
+impl std::fmt::Debug for Journal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Journal")
+            .field("changes", &self.changes.len())
+            .finish()
+    }
+}
+
+/// Как и пространственный индекс, в сравнении документов не участвует.
+impl PartialEq for Journal {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Journal {
+    pub(crate) fn added(&mut self, id: u64) {
+        self.seen.insert(id);
+        self.changes.push(Change::Presence {
+            id,
+            next: None,
+            stash: None,
+        });
+    }
+
+    pub(crate) fn removed(&mut self, e: Entity, next: Option<u64>) {
+        self.changes.push(Change::Presence {
+            id: e.id,
+            next,
+            stash: Some(e),
+        });
+    }
+
+    /// Сущность сейчас изменят; `before` — её текущая версия.
+    pub(crate) fn modifying(&mut self, before: impl FnOnce() -> Entity, id: u64) {
+        if self.seen.insert(id) {
+            self.changes.push(Change::Modified(before()));
+        }
+    }
+
+    pub(crate) fn layers(&mut self, before: &[Layer]) {
+        if !std::mem::replace(&mut self.layers, true) {
+            self.changes.push(Change::Layers(before.to_vec()));
+        }
+    }
+
+    pub(crate) fn blocks(&mut self, before: &[BlockDef]) {
+        if !std::mem::replace(&mut self.blocks, true) {
+            self.changes.push(Change::Blocks(before.to_vec()));
+        }
+    }
+}
+
+/// Шаг истории — одна транзакция.
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    label: String,
+    changes: Vec<Change>,
+    bytes: usize,
+}
+
+impl Transaction {
+    /// Название действия для списка истории.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Число записанных изменений.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn undo(&mut self, doc: &mut Document) {
+        apply_all(self.changes.iter_mut().rev(), doc);
+    }
+
+    fn redo(&mut self, doc: &mut Document) {
+        apply_all(self.changes.iter_mut(), doc);
+    }
+}
+
+/// Применить записи по порядку. Идущие подряд возвраты сущностей ставятся на
+/// место одной пачкой, иначе отмена удаления `k` сущностей стоила бы O(k·n).
+fn apply_all<'a>(changes: impl Iterator<Item = &'a mut Change>, doc: &mut Document) {
+    let mut batch = Vec::new();
+    for c in changes {
+        match c {
+            Change::Presence { next, stash, .. } if stash.is_some() => {
+                let e = stash.take().unwrap();
+                doc.index.invalidate(e.id);
+                batch.push((e, *next));
+            }
+            c => {
+                if !batch.is_empty() {
+                    restore(doc, std::mem::take(&mut batch));
+                }
+                c.apply(doc);
+            }
+        }
+    }
+    if !batch.is_empty() {
+        restore(doc, batch);
+    }
+}
+
+/// Вернуть сущности в документ (в индексе они уже помечены изменёнными).
+fn restore(doc: &mut Document, batch: Vec<(Entity, Option<u64>)>) {
+    let before = doc.entities.generation();
+    doc.entities.restore(batch);
+    doc.index.follow(before);
+}
+
+/// История правок документа: транзакции с отменой и повтором.
+///
+/// Правка оборачивается в [`History::begin`]/[`History::commit`] (или [`History::run`]);
+/// повторный `begin` при открытой транзакции ничего не делает, так что
+/// перетаскивание из многих шагов становится одним действием. Сущности меняются
+/// только через методы документа (`add_entity`, `remove_entity`, `entity_mut`) и
+/// всегда попадают в журнал; слои и блоки — через `add_layer`, `layer_mut`,
+/// `add_block`, запись прямо в `layers` и `blocks` не видна.
+#[derive(Debug, Clone)]
+pub struct History {
+    undo: VecDeque<Transaction>,
+    redo: Vec<Transaction>,
+    open: Option<String>,
+    bytes: usize,
+    max_bytes: usize,
+    max_steps: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::with_limits(HISTORY_STEPS, HISTORY_BYTES)
+    }
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// История не больше `max_steps` шагов и примерно `max_bytes` памяти; старые
+    /// шаги вытесняются (последний остаётся всегда).
+    pub fn with_limits(max_steps: usize, max_bytes: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            open: None,
+            bytes: 0,
+            max_bytes,
+            max_steps: max_steps.max(1),
+        }
+    }
+
+    /// Открыть транзакцию `label`. Если уже открыта — продолжить её.
+    pub fn begin(&mut self, doc: &mut Document, label: impl Into<String>) {
+        if self.open.is_none() {
+            self.open = Some(label.into());
+            doc.journal = Some(Journal::default());
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Закрыть транзакцию. Возвращает `true`, если она что-то изменила и попала в
+    /// историю; тогда повторять больше нечего.
+    pub fn commit(&mut self, doc: &mut Document) -> bool {
+        let Some(label) = self.open.take() else {
+            return false;
+        };
+        let mut changes = doc.journal.take().map(|j| j.changes).unwrap_or_default();
+        // тронутые, но не изменённые
+        changes.retain(|c| match c {
+            Change::Modified(e) => doc.entities.get(e.id) != Some(e),
+            Change::Layers(l) => *l != doc.layers,
+            Change::Blocks(b) => *b != doc.blocks,
+            _ => true,
+        });
+        self.push(Transaction {
+            label,
+            changes,
+            bytes: 0,
+        })
+    }
+
+    /// Закрыть транзакцию, откатив её изменения.
+    pub fn rollback(&mut self, doc: &mut Document) {
+        if self.open.take().is_some() {
+            if let Some(j) = doc.journal.take() {
+                Transaction {
+                    label: String::new(),
+                    changes: j.changes,
+                    bytes: 0,
+                }
+                .undo(doc);
+            }
+        }
+    }
+
+    /// Выполнить `f` одной транзакцией (внутри уже открытой — её частью).
+    pub fn run<R>(
+        &mut self,
+        doc: &mut Document,
+        label: impl Into<String>,
+        f: impl FnOnce(&mut Document) -> R,
+    ) -> R {
+        let nested = self.is_open();
+        self.begin(doc, label);
+        let r = f(doc);
+        if !nested {
+            self.commit(doc);
+        }
+        r
+    }
+
+    /// Заменить документ целиком (открытие файла) с возможностью отмены.
+    pub fn replace(&mut self, doc: &mut Document, new: Document, label: impl Into<String>) {
+        self.commit(doc);
+        let old = std::mem::replace(doc, new);
+        self.push(Transaction {
+            label: label.into(),
+            changes: vec![Change::Document(Box::new(old))],
+            bytes: 0,
+        });
+    }
+
+    fn push(&mut self, mut t: Transaction) -> bool {
+        if t.is_empty() {
+            return false;
+        }
+        t.bytes = t.changes.iter().map(Change::bytes).sum();
+        self.bytes += t.bytes;
+        self.undo.push_back(t);
+        self.redo.clear();
+        self.evict();
+        true
+    }
+
+    /// Вытеснить старые шаги сверх пределов (последний остаётся всегда).
+    fn evict(&mut self) {
+        while self.undo.len() > 1
+            && (self.undo.len() > self.max_steps || self.bytes > self.max_bytes)
+        {
+            let old = self.undo.pop_front().unwrap();
+            self.bytes -= old.bytes;
+        }
+    }
+
+    /// Отменить последний шаг (открытая транзакция сначала закрывается).
+    /// Возвращает название отменённого.
+    pub fn undo(&mut self, doc: &mut Document) -> Option<String> {
+        self.commit(doc);
+        let mut t = self.undo.pop_back()?;
+        self.bytes -= t.bytes;
+        t.undo(doc);
+        let label = t.label.clone();
+        self.redo.push(t);
+        Some(label)
+    }
+
+    /// Повторить последний отменённый шаг. Возвращает его название.
+    pub fn redo(&mut self, doc: &mut Document) -> Option<String> {
+        if self.is_open() {
+            return None;
+        }
+        let mut t = self.redo.pop()?;
+        t.redo(doc);
+        let label = t.label.clone();
+        t.bytes = t.changes.iter().map(Change::bytes).sum();
+        self.bytes += t.bytes;
+        self.undo.push_back(t);
+        self.evict();
+        Some(label)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.is_open()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty() && !self.is_open()
+    }
+
+    /// Сделанные шаги, от старых к новым.
+    pub fn undo_list(&self) -> impl DoubleEndedIterator<Item = &Transaction> {
+        self.undo.iter()
+    }
+
+    /// Отменённые шаги, от ближайшего к дальнему.
+    pub fn redo_list(&self) -> impl DoubleEndedIterator<Item = &Transaction> {
+        self.redo.iter().rev()
+    }
+
+    /// Оценка занятой памяти (сделанные шаги), байт.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn clear(&mut self, doc: &mut Document) {
+        self.commit(doc);
+        self.undo.clear();
+        self.redo.clear();
+        self.bytes = 0;
+    }
+}
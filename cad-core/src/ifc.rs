@@ -1,70 +1,1240 @@
 // cad-core/src/ifc.rs
+//! Импорт/экспорт IFC ↔ Project3D на чистом Rust (через вендорный `ifc_rs`).
+//!
+//! Импорт обходит иерархию project → site → building → storey → элементы, раскручивает
+//! цепочки `IfcLocalPlacement` и переводит `IfcExtrudedAreaSolid` с профилями
+//! `IfcArbitraryClosedProfileDef` / `IfcRectangleProfileDef` в `ElementGeom::Extrusion`,
+//! а `IfcTriangulatedFaceSet` — в `ElementGeom::Mesh`. Стержни `IfcReinforcingBar`,
+//! агрегированные в элемент (`IfcRelAggregates`), становятся его `rebars`.
+//! Всё, что в файле задано в метрах (или других единицах), пересчитываем в мм.
+//!
+//! Экспорт собирает файл через `IfcProjectBuilder`: модель → здание, этажи — по отметкам,
+//! экструзии → `IfcSlab`/`IfcWall`, прочая геометрия → тесселированная плита,
+//! арматура → `IfcReinforcingBar` (`IfcSweptDiskSolid` по оси стержня).
+
 use anyhow::{anyhow, Result};
-use std::ffi::CString;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bevy_math::{DVec2, DVec3};
+use ifc_rs::prelude::*;
 
-// Тянем С++ FFI из ifc_core, импорт переименовываем, чтобы не конфликтовал с нашей функцией.
-use ifc_core::{
-    extrusions_len, extrusions_ptr, import_ifc as cxx_import_ifc, ExtrusionRaw, FileRaw,
+use crate::model3d::{
+    Element3D, ElementGeom, Material, MaterialId, MaterialKind, Meta, Model3D, Project3D, Pt3,
+    Rebar, RebarPath,
 };
+use crate::section::rebar_points;
+use crate::Pt2;
+
+/// Property set для свойств `Meta` без префикса «Pset.».
+const PSET_DEFAULT: &str = "CAD_Properties";
+/// Property set с описанием материала (`MaterialKind`), которого нет в `IfcMaterial`.
+const PSET_MATERIAL: &str = "CAD_Material";
+/// Property set стержня с числом стержней (`Rebar::count`).
+const PSET_REBAR: &str = "CAD_Rebar";
+
+/// Аффинная матрица 4×4 (row-major, как `Element3D::xform`), в единицах файла.
+type M4 = [[f64; 4]; 4];
 
-use crate::model3d::{Element3D, ElementGeom, Model3D, Project3D};
-use crate::Pt2 as Pt2f; // твой 2D-тип с f32
+/// Плоскость профиля: origin, оси u и v, координаты точек в (u, v).
+type PlanarProfile = ([f64; 3], [f64; 3], [f64; 3], Vec<[f64; 2]>);
+
+const IDENTITY: M4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
 
 /// Импорт IFC → в наш Project3D.
-pub fn import_ifc(path: &str) -> Result<Project3D> {
-    // C-строка для C++ функции
-    let cpath = CString::new(path)?;
-    // unique_ptr<FileRaw>
-    let file_up = unsafe { cxx_import_ifc(cpath.as_ptr()) };
-
-    // Берём &FileRaw, пока жив unique_ptr
-    let raw: &FileRaw = file_up
+///
+/// Каждое здание становится отдельной `Model3D`; каждое тело-экструзия элемента —
+/// отдельным `Element3D` (имя и property sets копируются в `meta`).
+pub fn import_ifc(path: impl AsRef<Path>) -> Result<Project3D> {
+    let ifc = IFC::from_file(path)?;
+    import_ifc_model(ifc)
+}
+
+/// То же, что [`import_ifc`], но из уже прочитанного текста STEP.
+pub fn import_ifc_str(text: &str) -> Result<Project3D> {
+    let ifc: IFC = text.parse()?;
+    import_ifc_model(ifc)
+}
+
+fn import_ifc_model(ifc: IFC) -> Result<Project3D> {
+    let ex = IfcExtractor::from(ifc);
+    let mut importer = Importer {
+        ex: &ex,
+        to_mm: 1000.0,
+        next_id: 1,
+    };
+
+    let mut project = Project3D::default();
+    let projects: Vec<_> = ex.projects().collect();
+    if projects.is_empty() {
+        return Err(anyhow!("IFC: no IfcProject found"));
+    }
+
+    for (project_id, p) in projects {
+        importer.to_mm = length_unit_to_mm(&ex, p);
+        for (site_id, _) in ex.relations_of::<Project, Site>(project_id) {
+            for (building_id, building) in ex.relations_of::<Site, Building>(site_id) {
+                let mut model = Model3D {
+                    name: root_name(building).unwrap_or_else(|| "IfcBuilding".into()),
+                    ..Default::default()
+                };
+
+                // элементы, привязанные прямо к зданию (без этажа)
+                importer.collect_contained(building_id, None, &mut model);
+
+                for (storey_id, storey) in ex.relations_of::<Building, Storey>(building_id) {
+                    let storey_info = StoreyInfo {
+                        name: root_name(storey).unwrap_or_default(),
+                        elevation_mm: storey_elevation(&ex, storey) * importer.to_mm,
+                    };
+                    importer.collect_contained(storey_id, Some(&storey_info), &mut model);
+                }
+
+                project.models.push(model);
+            }
+        }
+    }
+
+    Ok(project)
+}
+
+struct StoreyInfo {
+    name: String,
+    elevation_mm: f64,
+}
+
+/// Тело элемента в локальной системе (единицы файла).
+enum Body {
+    /// Замкнутый 2D-профиль и глубина экструзии по локальной оси Z.
+    Extrusion { profile: Vec<[f64; 2]>, depth: f64 },
+    /// Треугольники.
+    Mesh {
+        positions: Vec<[f64; 3]>,
+        indices: Vec<u32>,
+    },
+}
+
+struct Importer<'a> {
+    ex: &'a IfcExtractor,
+    /// множитель «единица длины файла → мм»
+    to_mm: f64,
+    next_id: u64,
+}
+
+impl Importer<'_> {
+    fn collect_contained<S: Structure>(
+        &mut self,
+        structure: TypedId<S>,
+        storey: Option<&StoreyInfo>,
+        model: &mut Model3D,
+    ) {
+        let ex = self.ex;
+        for id in ex.contained_structures(structure) {
+            let Some((product, ifc_type)) = ex
+                .data
+                .get_untyped(id)
+                .to_structure()
+                .and_then(|s| s.structure_type())
+                .map(product_of)
+            else {
+                continue;
+            };
+
+            let placement = product
+                .object_placement
+                .custom()
+                .map(|pid| resolve_placement(ex, *pid))
+                .unwrap_or(IDENTITY);
+
+            let mut meta = Meta::default();
+            meta.props.insert("ifc:type".into(), ifc_type.into());
+            meta.props.insert(
+                "ifc:guid".into(),
+                product.global_id.to_string().trim_matches('\'').to_string(),
+            );
+            if let Some(s) = storey {
+                meta.props.insert("ifc:storey".into(), s.name.clone());
+                meta.props
+                    .insert("ifc:storey_elevation".into(), format!("{}", s.elevation_mm));
+            }
+            collect_property_sets(ex, id, &mut meta);
+            let material = import_material(ex, id, &mut meta, model);
+
+            let name = root_name(product).unwrap_or_else(|| ifc_type.to_string());
+            let first = model.elements.len();
+
+            for (xf, body) in bodies_of(ex, product) {
+                let k = self.to_mm;
+                let geom = match body {
+                    Body::Extrusion { profile, depth } => ElementGeom::Extrusion {
+                        profile: profile
+                            .iter()
+                            .map(|p| Pt2::new((p[0] * k) as f32, (p[1] * k) as f32))
+                            .collect(),
+                        height: (depth * k) as f32,
+                    },
+                    Body::Mesh { positions, indices } => ElementGeom::Mesh {
+                        positions: positions
+                            .iter()
+                            .map(|p| {
+                                Pt3::new((p[0] * k) as f32, (p[1] * k) as f32, (p[2] * k) as f32)
+                            })
+                            .collect(),
+                        indices,
+                    },
+                };
+                model.elements.push(Element3D {
+                    id: self.next_id,
+                    name: name.clone(),
+                    xform: matrix_to_mm(&mul(&placement, &xf), k),
+                    geom,
+                    material,
+                    rebars: vec![],
+                    meta: meta.clone(),
+                });
+                self.next_id += 1;
+            }
+
+            // стержни — в систему первого тела элемента
+            if let Some(host) = model.elements.get_mut(first) {
+                host.rebars = import_rebars(ex, id, &host.xform, self.to_mm);
+            }
+        }
+    }
+}
+
+/// `IfcReinforcingBar`, агрегированные в `host`, → стержни в координатах элемента
+/// с матрицей `xform` (мм).
+fn import_rebars(ifc: &IFC, host: Id, xform: &[[f32; 4]; 4], to_mm: f64) -> Vec<Rebar> {
+    let Some(to_local) = invert_affine(&xform.map(|row| row.map(f64::from))) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for (_, rel) in ifc.data.find_all_of_type::<RelAggregates>() {
+        if rel.relating_object != host {
+            continue;
+        }
+        for bar_id in rel.related_objects.0.iter() {
+            let Some(bar) = ifc
+                .data
+                .get_untyped(*bar_id)
+                .downcast_ref::<ReinforcingBar>()
+            else {
+                continue;
+            };
+            let mut placement = bar
+                .object_placement
+                .custom()
+                .map(|pid| resolve_placement(ifc, *pid))
+                .unwrap_or(IDENTITY);
+            for row in placement.iter_mut().take(3) {
+                row[3] *= to_mm;
+            }
+            let m = mul(&to_local, &placement);
+
+            let Some((axis, radius)) = bar.shapes(ifc).into_iter().find_map(|shape| {
+                shape.items(ifc).find_map(|item| match item {
+                    ShapeItemEnum::SweptDiskSolid(solid) => {
+                        let mapped = solid.mappings(ifc);
+                        let pts: Vec<[f64; 3]> = match mapped.directrix? {
+                            Points::D2(pts) => pts.iter().map(|p| [p.x, p.y, 0.0]).collect(),
+                            Points::D3(pts) => pts.iter().map(|p| [p.x, p.y, p.z]).collect(),
+                        };
+                        Some((pts, mapped.radius))
+                    }
+                    _ => None,
+                })
+            }) else {
+                continue;
+            };
+            if axis.len() < 2 {
+                continue;
+            }
+
+            let mut meta = Meta::default();
+            collect_property_sets(ifc, *bar_id, &mut meta);
+            let count = meta
+                .props
+                .remove(&format!("{PSET_REBAR}.Count"))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1);
+            let diameter = bar
+                .nominal_diameter
+                .custom()
+                .map(|d| d.0)
+                .unwrap_or(radius * 2.0);
+
+            out.push(Rebar {
+                id: out.len() as u64 + 1,
+                diameter_mm: (diameter * to_mm) as f32,
+                path: RebarPath::Polyline(
+                    axis.iter()
+                        .map(|p| {
+                            let q = transform_point(&m, [p[0] * to_mm, p[1] * to_mm, p[2] * to_mm]);
+                            Pt3::new(q[0] as f32, q[1] as f32, q[2] as f32)
+                        })
+                        .collect(),
+                ),
+                count,
+                meta,
+            });
+        }
+    }
+    out
+}
+
+/// Все тела продукта: (матрица тела относительно placement, тело).
+fn bodies_of(ifc: &IFC, product: &Product) -> Vec<(M4, Body)> {
+    let mut out = Vec::new();
+    for shape in product.shapes(ifc) {
+        collect_shape_bodies(ifc, shape, &IDENTITY, &mut out);
+    }
+    out
+}
+
+fn collect_shape_bodies(
+    ifc: &IFC,
+    shape: &ShapeRepresentation,
+    parent: &M4,
+    out: &mut Vec<(M4, Body)>,
+) {
+    for item in shape.items(ifc) {
+        match item {
+            ShapeItemEnum::ExtrudedAreaSolid(solid) => {
+                if let Some((m, profile, depth)) = extrusion(ifc, solid) {
+                    out.push((mul(parent, &m), Body::Extrusion { profile, depth }));
+                }
+            }
+            ShapeItemEnum::TriangulatedFaceSet(face_set) => {
+                let positions: Vec<[f64; 3]> = face_set
+                    .points(ifc)
+                    .iter()
+                    .map(|p| [p.x, p.y, p.z])
+                    .collect();
+                let indices: Vec<u32> = face_set
+                    .triangles()
+                    .into_iter()
+                    .filter(|t| t.iter().all(|&i| i < positions.len()))
+                    .flat_map(|t| t.map(|i| i as u32))
+                    .collect();
+                if !indices.is_empty() {
+                    out.push((*parent, Body::Mesh { positions, indices }));
+                }
+            }
+            ShapeItemEnum::MappedItem(mapped) => {
+                let ((origin, inner), transform) = mapped.mappings(ifc);
+                // target ∘ origin⁻¹: геометрия карты задана относительно её origin
+                let origin_m = axis3d_matrix(&origin);
+                let target_m = match transform {
+                    MappedTransform::Uniform(t) => transform_matrix(&t, None),
+                    MappedTransform::NonUniform(t) => {
+                        transform_matrix(&t, Some((t.scale_y, t.scale_z)))
+                    }
+                };
+                let m = mul(parent, &mul(&target_m, &invert_rigid(&origin_m)));
+                collect_shape_bodies(ifc, inner, &m, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `IfcExtrudedAreaSolid` → (матрица, замкнутый 2D-профиль, глубина).
+///
+/// Матрица переводит (u, v, w) профиля в систему `Position` тела: столбцы — оси
+/// плоскости профиля и единичное направление экструзии (w ∈ [0, depth]).
+fn extrusion(ifc: &IFC, solid: &ExtrudedAreaSolid) -> Option<(M4, Vec<[f64; 2]>, f64)> {
+    let mapped = solid.mappings(ifc);
+    let position = mapped
+        .position
         .as_ref()
-        .ok_or_else(|| anyhow!("IFC import failed (null from C++)"))?;
+        .map(axis3d_matrix)
+        .unwrap_or(IDENTITY);
+
+    let d = normalize3([
+        mapped.extruded_direction.x,
+        mapped.extruded_direction.y,
+        mapped.extruded_direction.z,
+    ]);
 
-    // Читаем экструдии
-    let len = extrusions_len(raw) as usize;
-    let ptr = extrusions_ptr(raw);
-    let extrs: &[ExtrusionRaw] = unsafe { std::slice::from_raw_parts(ptr, len) };
+    // плоскость профиля: origin + базис (u, v) в координатах Position
+    let (origin, u, v, profile) = match &mapped.profile_def {
+        MappedProfileDef::Rectangle(rect) => {
+            let (hx, hy) = (rect.x_dim * 0.5, rect.y_dim * 0.5);
+            let (loc, dir_x) = match &rect.axis {
+                Some(AxisMappings::D2(a)) => (
+                    [a.location.x, a.location.y],
+                    a.local_x.map(|d| [d.x, d.y]).unwrap_or([1.0, 0.0]),
+                ),
+                Some(AxisMappings::D3(a)) => (
+                    [a.location.x, a.location.y],
+                    a.local_x.map(|d| [d.x, d.y]).unwrap_or([1.0, 0.0]),
+                ),
+                None => ([0.0, 0.0], [1.0, 0.0]),
+            };
+            let dx = normalize2(dir_x);
+            let dy = [-dx[1], dx[0]];
+            let pts = [[-hx, -hy], [hx, -hy], [hx, hy], [-hx, hy]]
+                .iter()
+                .map(|p| {
+                    [
+                        loc[0] + p[0] * dx[0] + p[1] * dy[0],
+                        loc[1] + p[0] * dx[1] + p[1] * dy[1],
+                    ]
+                })
+                .collect();
+            ([0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], pts)
+        }
+        MappedProfileDef::Arbitrary(arb) => match &arb.points {
+            Points::D2(pts) => (
+                [0.0; 3],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                pts.iter().map(|p| [p.x, p.y]).collect(),
+            ),
+            Points::D3(pts) => {
+                let pts: Vec<[f64; 3]> = pts.iter().map(|p| [p.x, p.y, p.z]).collect();
+                planar_profile(&pts, d)?
+            }
+        },
+    };
+
+    let mut profile: Vec<[f64; 2]> = profile;
+    if profile.len() < 3 {
+        return None;
+    }
+    // профиль храним замкнутым (первая точка повторяется в конце)
+    if profile.first() != profile.last() {
+        profile.push(profile[0]);
+    }
+
+    let local: M4 = [
+        [u[0], v[0], d[0], origin[0]],
+        [u[1], v[1], d[1], origin[1]],
+        [u[2], v[2], d[2], origin[2]],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    Some((mul(&position, &local), profile, mapped.depth))
+}
 
-    let mut model = Model3D::default();
-    let mut id_counter: u64 = 1;
+/// 3D-контур в плоскости → (origin, u, v, 2D-координаты).
+/// Нормаль плоскости (u × v) разворачиваем по направлению экструзии `dir`.
+fn planar_profile(pts: &[[f64; 3]], dir: [f64; 3]) -> Option<PlanarProfile> {
+    if pts.len() < 3 {
+        return None;
+    }
+    // нормаль по Ньюэллу — устойчива к невыпуклым контурам
+    let mut n = [0.0; 3];
+    for i in 0..pts.len() {
+        let a = pts[i];
+        let b = pts[(i + 1) % pts.len()];
+        n[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        n[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        n[2] += (a[0] - b[0]) * (a[1] + b[1]);
+    }
+    if dot3(n, n) < 1e-24 {
+        return None;
+    }
+    let mut n = normalize3(n);
+    if dot3(n, dir) < 0.0 {
+        n = scale3(n, -1.0);
+    }
+    let o = pts[0];
+    let first = pts
+        .iter()
+        .find(|p| dot3(sub3(**p, o), sub3(**p, o)) > 1e-18)?;
+    let u = normalize3(sub3(*first, o));
+    let v = cross3(n, u);
+    let uv = pts
+        .iter()
+        .map(|p| {
+            let d = sub3(*p, o);
+            [dot3(d, u), dot3(d, v)]
+        })
+        .collect();
+    Some((o, u, v, uv))
+}
+
+// ----------------------------- placement -----------------------------
+
+/// Мировая матрица `IfcLocalPlacement` с учётом всей цепочки `PlacementRelTo`.
+fn resolve_placement(ifc: &IFC, placement: Id) -> M4 {
+    let mut chain = Vec::new();
+    let mut cur = Some(placement);
+    // защита от циклов в битых файлах
+    while let Some(id) = cur.take() {
+        if chain.len() > 64 {
+            break;
+        }
+        let Some(lp) = ifc.data.get_untyped(id).downcast_ref::<LocalPlacement>() else {
+            break;
+        };
+        chain.push(relative_placement_matrix(ifc, lp.relative_placement));
+        cur = lp.placement_rel_to.custom().map(|p| p.id());
+    }
+    chain
+        .iter()
+        .rev()
+        .fold(IDENTITY, |acc, local| mul(&acc, local))
+}
+
+fn relative_placement_matrix(ifc: &IFC, id: Id) -> M4 {
+    let untyped = ifc.data.get_untyped(id);
+    if let Some(a3) = untyped.downcast_ref::<Axis3D>() {
+        axis3d_matrix(&a3.mappings(ifc))
+    } else if let Some(a2) = untyped.downcast_ref::<Axis2D>() {
+        let a = a2.mappings(ifc);
+        let x = normalize2(a.local_x.map(|d| [d.x, d.y]).unwrap_or([1.0, 0.0]));
+        [
+            [x[0], -x[1], 0.0, a.location.x],
+            [x[1], x[0], 0.0, a.location.y],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    } else {
+        IDENTITY
+    }
+}
 
-    for ex in extrs {
-        // профиль
-        let pts = unsafe { std::slice::from_raw_parts(ex.profile.pts, ex.profile.len as usize) };
-        let profile: Vec<Pt2f> = pts
+/// `IfcAxis2Placement3D` → матрица (X = RefDirection, Z = Axis, Y = Z × X).
+fn axis3d_matrix(a: &MappedAxis3D<'_>) -> M4 {
+    let z = normalize3(
+        a.local_z
+            .map(|d| [d.x, d.y, d.z])
+            .unwrap_or([0.0, 0.0, 1.0]),
+    );
+    let x_ref = a.local_x.map(|d| [d.x, d.y, d.z]).unwrap_or_else(|| {
+        if z[0].abs() < 0.9 {
+            [1.0, 0.0, 0.0]
+        } else {
+            [0.0, 1.0, 0.0]
+        }
+    });
+    // ортогонализуем RefDirection к оси Z
+    let x = normalize3(sub3(x_ref, scale3(z, dot3(x_ref, z))));
+    let y = cross3(z, x);
+    let o = [a.location.x, a.location.y, a.location.z];
+    [
+        [x[0], y[0], z[0], o[0]],
+        [x[1], y[1], z[1], o[1]],
+        [x[2], y[2], z[2], o[2]],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+/// `IfcCartesianTransformationOperator3D(nonUniform)` → матрица.
+fn transform_matrix(
+    t: &TransformBaseMapping<'_>,
+    scale_yz: Option<(Option<f64>, Option<f64>)>,
+) -> M4 {
+    let z = normalize3(t.axis_z.map(|d| [d.x, d.y, d.z]).unwrap_or([0.0, 0.0, 1.0]));
+    let x_ref = t.axis_x.map(|d| [d.x, d.y, d.z]).unwrap_or([1.0, 0.0, 0.0]);
+    let x = normalize3(sub3(x_ref, scale3(z, dot3(x_ref, z))));
+    let y = cross3(z, x);
+    let s = t.scale.unwrap_or(1.0);
+    let (sy, sz) = match scale_yz {
+        Some((sy, sz)) => (sy.unwrap_or(s), sz.unwrap_or(s)),
+        None => (s, s),
+    };
+    let o = t.translation.map(|p| [p.x, p.y, p.z]).unwrap_or([0.0; 3]);
+    [
+        [x[0] * s, y[0] * sy, z[0] * sz, o[0]],
+        [x[1] * s, y[1] * sy, z[1] * sz, o[1]],
+        [x[2] * s, y[2] * sy, z[2] * sz, o[2]],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+// ----------------------------- meta -----------------------------
+
+fn product_of(st: StructureType<'_>) -> (&Product, &'static str) {
+    match st {
+        StructureType::Wall(w) => (w, "IfcWall"),
+        StructureType::Slab(s) => (s, "IfcSlab"),
+        StructureType::Roof(r) => (r, "IfcRoof"),
+        StructureType::Window(w) => (w, "IfcWindow"),
+        StructureType::Door(d) => (d, "IfcDoor"),
+        StructureType::ShadingDevice(s) => (s, "IfcShadingDevice"),
+    }
+}
+
+fn root_name(root: &Root) -> Option<String> {
+    root.name.custom().map(|s| s.0.clone())
+}
+
+/// Все `IfcPropertySet`, привязанные к объекту, → `meta.props["<pset>.<prop>"]`.
+fn collect_property_sets(ifc: &IFC, object: Id, meta: &mut Meta) {
+    for (_, rel) in ifc.data.find_all_of_type::<RelDefinesByProperties>() {
+        if !rel.related_objects.0.contains(&object) {
+            continue;
+        }
+        let Some(pset) = ifc
+            .data
+            .get_untyped(rel.relating_property_definition)
+            .downcast_ref::<PropertySet>()
+        else {
+            continue;
+        };
+        let pset_name = root_name(pset).unwrap_or_default();
+        for prop_id in pset.properties.0.iter() {
+            let prop = ifc.data.get(*prop_id);
+            let Some(name) = prop.name.custom().map(|s| s.0.clone()) else {
+                continue;
+            };
+            // свойства без набора уходили в PSET_DEFAULT — возвращаем им исходный ключ
+            let key = if pset_name == PSET_DEFAULT {
+                name
+            } else {
+                format!("{pset_name}.{name}")
+            };
+            meta.props.insert(key, ifc_value_string(&prop.value));
+        }
+    }
+}
+
+/// Материал элемента: имя из `IfcRelAssociatesMaterial`, вид — из `CAD_Material`
+/// (ключи набора из `meta` убираются). Материал регистрируется в `model.materials`.
+fn import_material(ifc: &IFC, object: Id, meta: &mut Meta, model: &mut Model3D) -> MaterialId {
+    let prefix = format!("{PSET_MATERIAL}.");
+    let keys: Vec<String> = meta
+        .props
+        .keys()
+        .filter(|k| k.starts_with(&prefix))
+        .cloned()
+        .collect();
+    let cad: BTreeMap<String, String> = keys
+        .into_iter()
+        .filter_map(|k| {
+            let v = meta.props.remove(&k)?;
+            Some((k[prefix.len()..].to_string(), v))
+        })
+        .collect();
+
+    let Some(name) = associated_material_name(ifc, object).or_else(|| cad.get("Name").cloned())
+    else {
+        return 0;
+    };
+    if let Some(m) = model.materials.iter().find(|m| m.name == name) {
+        return m.id;
+    }
+
+    let kind = match cad.get("Kind").map(String::as_str) {
+        Some("Steel") => MaterialKind::Steel {
+            fy_mpa: cad
+                .get("FyMpa")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+        },
+        _ => MaterialKind::Concrete {
+            grade: cad.get("Grade").cloned().unwrap_or_default(),
+        },
+    };
+    let id = model.materials.iter().map(|m| m.id).max().unwrap_or(0) + 1;
+    model.materials.push(Material { id, name, kind });
+    id
+}
+
+/// Имя первого `IfcMaterial`, связанного с объектом (напрямую или через слои).
+fn associated_material_name(ifc: &IFC, object: Id) -> Option<String> {
+    let material_name = |m: TypedId<ifc_rs::prelude::Material>| {
+        ifc.data.get(m).material.custom().map(|s| s.0.clone())
+    };
+    let layer_set_name = |set: &MaterialLayerSet| {
+        set.material_layers
             .iter()
-            .map(|p| Pt2f::new(p.x as f32, p.y as f32))
-            .collect();
+            .find_map(|l| ifc.data.get(*l).material.custom().copied())
+            .and_then(material_name)
+    };
+
+    ifc.data
+        .find_all_of_type::<RelAssociatesMaterial>()
+        .filter(|(_, rel)| rel.related_objects.0.contains(&object))
+        .find_map(|(_, rel)| {
+            let m = ifc.data.get_untyped(rel.relating_material);
+            if let Some(usage) = m.downcast_ref::<MaterialLayerSetUsage>() {
+                layer_set_name(ifc.data.get(usage.spatial_element_structure))
+            } else if let Some(set) = m.downcast_ref::<MaterialLayerSet>() {
+                layer_set_name(set)
+            } else {
+                m.downcast_ref::<ifc_rs::prelude::Material>()
+                    .and_then(|m| m.material.custom().map(|s| s.0.clone()))
+            }
+        })
+}
+
+fn ifc_value_string(v: &IfcValue) -> String {
+    match v {
+        IfcValue::Label(LabelValue(s)) | IfcValue::Identifier(IdentifierValue(s)) => s.0.clone(),
+        IfcValue::Real(RealValue(r))
+        | IfcValue::ThermalTransmittance(ThermalTransmittanceValue(r)) => r.0.to_string(),
+        IfcValue::Integer(IntegerValue(i)) => i.0.to_string(),
+        IfcValue::Bool(BoolValue(b)) => match b.to_string().as_str() {
+            ".TRUE." => "true".into(),
+            ".FALSE." => "false".into(),
+            _ => "unknown".into(),
+        },
+    }
+}
+
+fn storey_elevation(ifc: &IFC, storey: &Storey) -> f64 {
+    if let Some(e) = storey.elevation.custom() {
+        return e.0;
+    }
+    // если Elevation не задан — берём Z мирового placement этажа
+    storey
+        .object_placement
+        .custom()
+        .map(|pid| resolve_placement(ifc, *pid)[2][3])
+        .unwrap_or(0.0)
+}
+
+/// Множитель единицы длины проекта к мм (по умолчанию IFC — метры).
+fn length_unit_to_mm(ifc: &IFC, project: &Project) -> f64 {
+    let Some(assignment) = project.units_in_context.custom() else {
+        return 1000.0;
+    };
+    for unit_id in ifc.data.get(*assignment).units.0.iter() {
+        let Some(si) = ifc.data.get_untyped(*unit_id).downcast_ref::<SiUnit>() else {
+            continue;
+        };
+        if !matches!(si.unit_type.custom(), Some(IfcUnitEnum::LengthUnit)) {
+            continue;
+        }
+        return match si.prefix.custom() {
+            Some(IfcPrefix::Milli) => 1.0,
+            Some(IfcPrefix::Centi) => 10.0,
+            Some(IfcPrefix::Deci) => 100.0,
+            Some(IfcPrefix::Kilo) => 1.0e6,
+            _ => 1000.0,
+        };
+    }
+    1000.0
+}
 
-        // матрица 4x4
-        let mut xf = [[0.0f32; 4]; 4];
-        for r in 0..4 {
-            for c in 0..4 {
-                xf[r][c] = ex.xform[r * 4 + c] as f32;
+// ----------------------------- экспорт -----------------------------
+
+/// Экспорт Project3D → IFC-файл.
+pub fn export_ifc(project: &Project3D, path: impl AsRef<Path>) -> Result<()> {
+    std::fs::write(path, export_ifc_string(project))?;
+    Ok(())
+}
+
+/// Экспорт Project3D → текст IFC (STEP), читаемый обратно через `IFC::from_str`.
+///
+/// Каждая `Model3D` — отдельное здание; элементы раскладываются по этажам по отметке
+/// (`ifc:storey_elevation` из импорта или Z базы элемента). Экструзии с
+/// `ifc:type = IfcWall` пишутся стенами, остальные — плитами; сетки, свипы и B-Rep —
+/// тесселированными плитами. `Meta::props` уходят в property sets, вид материала —
+/// в набор `CAD_Material`. Стержни — `IfcReinforcingBar` в составе элемента
+/// (NURBS-оси — ломаной), число стержней — в наборе `CAD_Rebar`.
+pub fn export_ifc_string(project: &Project3D) -> String {
+    let mut builder = IfcProjectBuilder::new(
+        ApplicationInfo {
+            developer: Organization::new(None, "rust-cad", None),
+            version: env!("CARGO_PKG_VERSION"),
+            name: "rust-cad",
+            short_name: "rust-cad",
+        },
+        OwnerInfo {
+            owner: Person::empty(),
+            organization_name: "rust-cad",
+        },
+        "Project",
+    );
+
+    {
+        let mut site = builder.new_site("Site", DVec3::ZERO);
+        for model in &project.models {
+            let mut building = site.new_building(&model.name, DVec3::ZERO);
+            for level in storey_levels(model) {
+                let mut storey = building.new_storey(&level.name, level.elevation_mm / 1000.0);
+                let mut types = ElementTypes::default();
+                for el in level.elements {
+                    export_element(&mut storey, &mut types, model, el, level.elevation_mm);
+                }
             }
         }
+    }
 
-        model.elements.push(Element3D {
-            id: {
-                let id = id_counter;
-                id_counter += 1;
-                id
-            },
-            name: "IFC Extrusion".into(),
-            xform: xf,
-            geom: ElementGeom::Extrusion {
-                profile,
-                height: ex.height as f32,
-            },
-            material: 0,
-            rebars: vec![],
-            meta: Default::default(),
+    builder.build()
+}
+
+/// Этаж при экспорте: имя, отметка (мм) и его элементы.
+struct Level<'a> {
+    name: String,
+    elevation_mm: f64,
+    elements: Vec<&'a Element3D>,
+}
+
+fn storey_levels(model: &Model3D) -> Vec<Level<'_>> {
+    let mut levels: Vec<Level> = Vec::new();
+    let mut rest = Vec::new();
+
+    for el in &model.elements {
+        let Some(elev) = el
+            .meta
+            .props
+            .get("ifc:storey_elevation")
+            .and_then(|v| v.parse::<f64>().ok())
+        else {
+            rest.push(el);
+            continue;
+        };
+        match levels
+            .iter_mut()
+            .find(|l| (l.elevation_mm - elev).abs() < 1e-3)
+        {
+            Some(level) => level.elements.push(el),
+            None => levels.push(Level {
+                name: el
+                    .meta
+                    .props
+                    .get("ifc:storey")
+                    .cloned()
+                    .unwrap_or_else(|| level_name(elev)),
+                elevation_mm: elev,
+                elements: vec![el],
+            }),
+        }
+    }
+
+    // элементам ниже всех этажей нужен этаж +0.000 (или ниже, если база отрицательная)
+    let lowest = rest
+        .iter()
+        .map(|el| el.xform[2][3] as f64)
+        .filter(|&z| !levels.iter().any(|l| l.elevation_mm <= z + 1e-3))
+        .reduce(f64::min);
+    if let Some(z) = lowest {
+        let elevation_mm = z.min(0.0);
+        levels.push(Level {
+            name: level_name(elevation_mm),
+            elevation_mm,
+            elements: vec![],
         });
     }
+    levels.sort_by(|a, b| a.elevation_mm.total_cmp(&b.elevation_mm));
+
+    // без этажа: самый высокий этаж, отметка которого не выше базы элемента
+    for el in rest {
+        let z = el.xform[2][3] as f64;
+        let i = levels
+            .iter()
+            .rposition(|l| l.elevation_mm <= z + 1e-3)
+            .unwrap_or(0);
+        levels[i].elements.push(el);
+    }
+    levels
+}
+
+/// Отметка в виде «+3.000» (м).
+fn level_name(elevation_mm: f64) -> String {
+    format!("{:+.3}", elevation_mm / 1000.0)
+}
+
+/// Наборы слоёв и типы элементов этажа (ключ — материал и толщина в мкм).
+#[derive(Default)]
+struct ElementTypes {
+    layers:
+        BTreeMap<(MaterialId, i64), (TypedId<MaterialLayerSet>, TypedId<MaterialLayerSetUsage>)>,
+    slab_types: BTreeMap<(MaterialId, i64), TypedId<SlabType>>,
+    wall_types: BTreeMap<(MaterialId, i64), TypedId<WallType>>,
+}
+
+impl ElementTypes {
+    fn layer_set(
+        &mut self,
+        storey: &mut IfcStoreyBuilder,
+        material: Option<&Material>,
+        thickness_m: f64,
+    ) -> (
+        (MaterialId, i64),
+        TypedId<MaterialLayerSet>,
+        TypedId<MaterialLayerSetUsage>,
+    ) {
+        let key = (
+            material.map(|m| m.id).unwrap_or(0),
+            (thickness_m * 1e6).round() as i64,
+        );
+        let (set, usage) = *self.layers.entry(key).or_insert_with(|| {
+            let name = material.map(|m| m.name.as_str()).unwrap_or("Undefined");
+            let layer =
+                storey.material_layer(name, MaterialLayer::new(thickness_m, false).name(name));
+            let set = storey.material_layer_set([layer]);
+            let usage = storey.material_layer_set_usage(
+                set,
+                LayerSetDirectionEnum::Axis3,
+                DirectionSenseEnum::Positive,
+                0.0,
+            );
+            (set, usage)
+        });
+        (key, set, usage)
+    }
+
+    fn slab_type(
+        &mut self,
+        storey: &mut IfcStoreyBuilder,
+        material: Option<&Material>,
+        thickness_m: f64,
+    ) -> (TypedId<MaterialLayerSetUsage>, TypedId<SlabType>) {
+        let (key, set, usage) = self.layer_set(storey, material, thickness_m);
+        let ty = *self.slab_types.entry(key).or_insert_with(|| {
+            let name = type_name(material, thickness_m);
+            storey.slab_type(set, &name, SlabTypeEnum::NotDefined)
+        });
+        (usage, ty)
+    }
+
+    fn wall_type(
+        &mut self,
+        storey: &mut IfcStoreyBuilder,
+        material: Option<&Material>,
+        thickness_m: f64,
+    ) -> (TypedId<MaterialLayerSetUsage>, TypedId<WallType>) {
+        let (key, set, usage) = self.layer_set(storey, material, thickness_m);
+        let ty = *self.wall_types.entry(key).or_insert_with(|| {
+            let name = type_name(material, thickness_m);
+            storey.wall_type(set, &name, WallTypeEnum::NotDefined)
+        });
+        (usage, ty)
+    }
+}
+
+fn type_name(material: Option<&Material>, thickness_m: f64) -> String {
+    let name = material.map(|m| m.name.as_str()).unwrap_or("Undefined");
+    format!("{name} {:.0}", thickness_m * 1000.0)
+}
+
+fn export_element(
+    storey: &mut IfcStoreyBuilder,
+    types: &mut ElementTypes,
+    model: &Model3D,
+    el: &Element3D,
+    elevation_mm: f64,
+) {
+    let material = model.materials.iter().find(|m| m.id == el.material);
+    let xf = el.xform.map(|row| row.map(f64::from));
+    // линейная часть xform и перенос относительно этажа (м)
+    let lin = |p: [f64; 3]| -> DVec3 {
+        DVec3::new(
+            xf[0][0] * p[0] + xf[0][1] * p[1] + xf[0][2] * p[2],
+            xf[1][0] * p[0] + xf[1][1] * p[1] + xf[1][2] * p[2],
+            xf[2][0] * p[0] + xf[2][1] * p[1] + xf[2][2] * p[2],
+        )
+    };
+    let placement = DVec3::new(xf[0][3], xf[1][3], xf[2][3] - elevation_mm) / 1000.0;
+    let is_wall = el.meta.props.get("ifc:type").map(String::as_str) == Some("IfcWall");
+
+    match &el.geom {
+        ElementGeom::Extrusion { profile, height } => {
+            let mut pts: Vec<Pt2> = profile.clone();
+            if pts.len() > 1 && pts.first() == pts.last() {
+                pts.pop();
+            }
+            let dir = lin([0.0, 0.0, *height as f64]);
+            let depth_m = dir.length() / 1000.0;
+            if pts.len() < 3 || depth_m <= 1e-9 {
+                return;
+            }
+
+            let identity = (0..3).all(|r| (0..3).all(|c| (xf[r][c] - IDENTITY[r][c]).abs() < 1e-6));
+            if is_wall {
+                let (usage, ty) = types.wall_type(storey, material, depth_m);
+                let id = storey
+                    .arbitrary_wall(
+                        usage,
+                        ty,
+                        &el.name,
+                        ArbitraryWallParameter {
+                            coords: pts
+                                .iter()
+                                .map(|p| lin([p.x as f64, p.y as f64, 0.0]) / 1000.0)
+                                .collect(),
+                            direction: dir.normalize(),
+                            placement,
+                        },
+                    )
+                    .finish();
+                export_properties(storey, id, &el.meta, material);
+                export_rebars(storey, id, el, elevation_mm);
+            } else if identity && *height > 0.0 {
+                let (usage, ty) = types.slab_type(storey, material, depth_m);
+                let id = storey
+                    .horizontal_arbitrary_slab(
+                        usage,
+                        ty,
+                        &el.name,
+                        HorizontalArbitrarySlabParameter {
+                            coords: pts
+                                .iter()
+                                .map(|p| DVec2::new(p.x as f64, p.y as f64) / 1000.0)
+                                .collect(),
+                            placement,
+                        },
+                    )
+                    .finish();
+                export_properties(storey, id, &el.meta, material);
+                export_rebars(storey, id, el, elevation_mm);
+            } else {
+                let (usage, ty) = types.slab_type(storey, material, depth_m);
+                let id = storey
+                    .arbitrary_slab(
+                        usage,
+                        ty,
+                        &el.name,
+                        ArbitrarySlabParameter {
+                            coords: pts
+                                .iter()
+                                .map(|p| lin([p.x as f64, p.y as f64, 0.0]) / 1000.0)
+                                .collect(),
+                            direction: dir.normalize(),
+                            placement,
+                        },
+                    )
+                    .finish();
+                export_properties(storey, id, &el.meta, material);
+                export_rebars(storey, id, el, elevation_mm);
+            }
+        }
+        _ => {
+            // прочая геометрия — сеткой в системе этажа
+            let mesh = el.triangulate(16);
+            if mesh.is_empty() {
+                return;
+            }
+            let coords: Vec<DVec3> = mesh
+                .positions
+                .iter()
+                .map(|p| DVec3::new(p[0] as f64, p[1] as f64, p[2] as f64 - elevation_mm) / 1000.0)
+                .collect();
+            let (zmin, zmax) = coords.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+                (lo.min(p.z), hi.max(p.z))
+            });
+            let (usage, ty) = types.slab_type(storey, material, zmax - zmin);
+            let id = storey
+                .triangulated_slab(
+                    usage,
+                    ty,
+                    &el.name,
+                    TriangulatedSlabParameter {
+                        coords,
+                        triangles: mesh
+                            .indices
+                            .chunks_exact(3)
+                            .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+                            .collect(),
+                        placement: DVec3::ZERO,
+                    },
+                )
+                .finish();
+            export_properties(storey, id, &el.meta, material);
+            export_rebars(storey, id, el, elevation_mm);
+        }
+    }
+}
+
+/// Стержни элемента → `IfcReinforcingBar` в составе `host`; ось — в системе этажа (м).
+fn export_rebars<T: IfcType>(
+    storey: &mut IfcStoreyBuilder,
+    host: TypedId<T>,
+    el: &Element3D,
+    elevation_mm: f64,
+) {
+    let xf = el.xform.map(|row| row.map(f64::from));
+    for r in &el.rebars {
+        let coords: Vec<DVec3> = rebar_points(&r.path)
+            .into_iter()
+            .map(|p| {
+                let q = transform_point(&xf, p.map(f64::from));
+                DVec3::new(q[0], q[1], q[2] - elevation_mm) / 1000.0
+            })
+            .collect();
+        if coords.len() < 2 || r.diameter_mm <= 0.0 {
+            continue;
+        }
+        let id = storey.reinforcing_bar(
+            host,
+            &format!("Ø{}", r.diameter_mm),
+            ReinforcingBarParameter {
+                coords,
+                diameter: r.diameter_mm as f64 / 1000.0,
+                placement: DVec3::ZERO,
+            },
+        );
+        let mut meta = r.meta.clone();
+        meta.props
+            .insert(format!("{PSET_REBAR}.Count"), r.count.to_string());
+        export_properties(storey, id, &meta, None);
+    }
+}
+
+/// `Meta::props` → property sets: ключ «Pset.Name» — в набор Pset, без точки — в
+/// `CAD_Properties`; служебные ключи `ifc:*` не пишем.
+fn export_properties<T: IfcType>(
+    storey: &mut IfcStoreyBuilder,
+    object: TypedId<T>,
+    meta: &Meta,
+    material: Option<&Material>,
+) {
+    let mut psets: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    for (key, value) in &meta.props {
+        if key.starts_with("ifc:") {
+            continue;
+        }
+        let (pset, name) = key.split_once('.').unwrap_or((PSET_DEFAULT, key.as_str()));
+        psets
+            .entry(pset.to_string())
+            .or_default()
+            .push((name.to_string(), value.clone()));
+    }
+    if let Some(m) = material {
+        let props = psets.entry(PSET_MATERIAL.to_string()).or_default();
+        props.push(("Name".into(), m.name.clone()));
+        match &m.kind {
+            MaterialKind::Concrete { grade } => {
+                props.push(("Kind".into(), "Concrete".into()));
+                props.push(("Grade".into(), grade.clone()));
+            }
+            MaterialKind::Steel { fy_mpa } => {
+                props.push(("Kind".into(), "Steel".into()));
+                props.push(("FyMpa".into(), fy_mpa.to_string()));
+            }
+        }
+    }
+
+    for (pset, props) in psets {
+        let mut set = storey.add_properties(&pset);
+        for (name, value) in props {
+            let _ = set.single_property(&name, property_value(value), None);
+        }
+        let set = set.finish();
+        storey.relate_properties_object(set, object);
+    }
+}
+
+/// Строка свойства → `IfcBoolean`/`IfcInteger`/`IfcReal`, если она читается обратно
+/// той же строкой, иначе `IfcLabel`.
+fn property_value(value: String) -> IfcValue {
+    match value.as_str() {
+        "true" => return IfcValue::Bool(BoolValue(true.into())),
+        "false" => return IfcValue::Bool(BoolValue(false.into())),
+        _ => {}
+    }
+    if let Ok(i) = value.parse::<i64>() {
+        if i.to_string() == value {
+            return IfcValue::Integer(IntegerValue(i.into()));
+        }
+    }
+    if let Ok(r) = value.parse::<f64>() {
+        // длинные дроби ifc_rs пишет с 15 значащими цифрами — такие оставляем строкой
+        if r.is_finite() && r.to_string() == value && format!("{r:.14E}").parse() == Ok(r) {
+            return IfcValue::Real(RealValue(r.into()));
+        }
+    }
+    IfcValue::Label(LabelValue(value.into()))
+}
+
+// ----------------------------- матрицы -----------------------------
 
-    Ok(Project3D {
-        models: vec![model],
-    })
+fn mul(a: &M4, b: &M4) -> M4 {
+    let mut r = [[0.0; 4]; 4];
+    for (i, row) in r.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    r
+}
+
+/// Обратная для матрицы вида [R | t] с ортонормированной R.
+fn invert_rigid(m: &M4) -> M4 {
+    let mut r = IDENTITY;
+    for (i, row) in r.iter_mut().take(3).enumerate() {
+        for (j, cell) in row.iter_mut().take(3).enumerate() {
+            *cell = m[j][i];
+        }
+        row[3] = -(0..3).map(|k| row[k] * m[k][3]).sum::<f64>();
+    }
+    r
+}
+
+/// Обратная для произвольной аффинной матрицы (None — если вырождена).
+fn invert_affine(m: &M4) -> Option<M4> {
+    let a = |r: usize, c: usize| m[r][c];
+    let cof = |r: usize, c: usize| {
+        let (r0, r1) = ((r + 1) % 3, (r + 2) % 3);
+        let (c0, c1) = ((c + 1) % 3, (c + 2) % 3);
+        a(r0, c0) * a(r1, c1) - a(r0, c1) * a(r1, c0)
+    };
+    let det: f64 = (0..3).map(|c| a(0, c) * cof(0, c)).sum();
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let mut r = IDENTITY;
+    for (i, row) in r.iter_mut().take(3).enumerate() {
+        for (j, cell) in row.iter_mut().take(3).enumerate() {
+            *cell = cof(j, i) / det;
+        }
+        row[3] = -(0..3).map(|k| row[k] * m[k][3]).sum::<f64>();
+    }
+    Some(r)
+}
+
+fn transform_point(m: &M4, p: [f64; 3]) -> [f64; 3] {
+    let row = |r: usize| m[r][0] * p[0] + m[r][1] * p[1] + m[r][2] * p[2] + m[r][3];
+    [row(0), row(1), row(2)]
+}
+
+/// Перевод матрицы из единиц файла в мм: линейная часть та же, перенос × k.
+fn matrix_to_mm(m: &M4, k: f64) -> [[f32; 4]; 4] {
+    let mut out = [[0.0f32; 4]; 4];
+    for r in 0..4 {
+        for c in 0..4 {
+            let v = if c == 3 && r < 3 {
+                m[r][c] * k
+            } else {
+                m[r][c]
+            };
+            out[r][c] = v as f32;
+        }
+    }
+    out
+}
+
+fn dot3(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+fn sub3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+fn scale3(a: [f64; 3], k: f64) -> [f64; 3] {
+    [a[0] * k, a[1] * k, a[2] * k]
+}
+fn cross3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+fn normalize3(v: [f64; 3]) -> [f64; 3] {
+    let l = dot3(v, v).sqrt();
+    if l < 1e-12 {
+        v
+    } else {
+        scale3(v, 1.0 / l)
+    }
+}
+fn normalize2(v: [f64; 2]) -> [f64; 2] {
+    let l = (v[0] * v[0] + v[1] * v[1]).sqrt();
+    if l < 1e-12 {
+        [1.0, 0.0]
+    } else {
+        [v[0] / l, v[1] / l]
+    }
 }
@@ -0,0 +1,314 @@
+//! Пересечение кривых по их truck-представлению (`EntityKind::to_truck`).
+//!
+//! Обе кривые раскладываются на куски Безье; пары кусков с пересекающимися габаритами
+//! делятся пополам, пока не станут почти прямыми. Пересечение хорд даёт начальное
+//! приближение для `search_intersection_parameter` (Ньютон по обоим параметрам).
+
+use crate::{EntityKind, Pt2, TruckCurve2};
+use cryxtal_geometry::prelude::algo::curve::search_intersection_parameter;
+use cryxtal_geometry::prelude::*;
+
+/// Точек на пролёт узлового вектора при грубом разбиении (проекция точки, габариты).
+const SPAN_SAMPLES: usize = 12;
+/// Кусок считается прямым, если контрольные точки не дальше этого от хорды (× масштаб).
+const FLAT_TOL: f64 = 1e-4;
+/// Предел деления пар кусков (касания, наложения).
+const MAX_DEPTH: usize = 48;
+/// Предел числа пар в работе — дальше пары сразу идут в Ньютон.
+const MAX_PAIRS: usize = 4096;
+
+/// Точка пересечения двух кривых и её параметры на обеих truck-кривых
+/// (у отрезка 0..1, у NURBS — узловой, у дуг и полилиний — параметр их NURBS-образа).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    pub point: Pt2,
+    pub t_a: f64,
+    pub t_b: f64,
+}
+
+impl ParametricCurve for TruckCurve2 {
+    type Point = Point2;
+    type Vector = Vector2;
+    fn subs(&self, t: f64) -> Point2 {
+        match self {
+            TruckCurve2::BSpline(c) => c.subs(t),
+            TruckCurve2::Nurbs(c) => c.subs(t),
+        }
+    }
+    fn der(&self, t: f64) -> Vector2 {
+        match self {
+            TruckCurve2::BSpline(c) => c.der(t),
+            TruckCurve2::Nurbs(c) => c.der(t),
+        }
+    }
+    fn der2(&self, t: f64) -> Vector2 {
+        match self {
+            TruckCurve2::BSpline(c) => c.der2(t),
+            TruckCurve2::Nurbs(c) => c.der2(t),
+        }
+    }
+    fn der_n(&self, n: usize, t: f64) -> Vector2 {
+        match self {
+            TruckCurve2::BSpline(c) => c.der_n(n, t),
+            TruckCurve2::Nurbs(c) => c.der_n(n, t),
+        }
+    }
+    fn parameter_range(&self) -> ParameterRange {
+        match self {
+            TruckCurve2::BSpline(c) => c.parameter_range(),
+            TruckCurve2::Nurbs(c) => c.parameter_range(),
+        }
+    }
+}
+
+impl BoundedCurve for TruckCurve2 {}
+
+impl TruckCurve2 {
+    pub(crate) fn degree(&self) -> usize {
+        match self {
+            TruckCurve2::BSpline(c) => c.degree(),
+            TruckCurve2::Nurbs(c) => c.degree(),
+        }
+    }
+
+    /// Параметры грубого разбиения: по `SPAN_SAMPLES` на каждый ненулевой пролёт
+    /// (у ломаных — только узлы).
+    pub(crate) fn sample_params(&self) -> Vec<f64> {
+        let knots: &[f64] = match self {
+            TruckCurve2::BSpline(c) => c.knot_vec(),
+            TruckCurve2::Nurbs(c) => c.knot_vec(),
+        };
+        let per_span = if self.degree() <= 1 { 1 } else { SPAN_SAMPLES };
+        let mut out = Vec::new();
+        for w in knots.windows(2) {
+            if w[1] - w[0] > 1e-12 {
+                out.extend(
+                    (0..per_span).map(|i| w[0] + (w[1] - w[0]) * i as f64 / per_span as f64),
+                );
+            }
+        }
+        out.push(self.range_tuple().1);
+        out
+    }
+
+    /// Та же кривая в однородных координатах (x·w, y·w, w).
+    fn homogeneous(&self) -> BSplineCurve<Vector3> {
+        match self {
+            TruckCurve2::BSpline(c) => BSplineCurve::new(
+                c.knot_vec().clone(),
+                c.control_points()
+                    .iter()
+                    .map(|p| Vector3::new(p.x, p.y, 1.0))
+                    .collect(),
+            ),
+            TruckCurve2::Nurbs(c) => c.non_rationalized().clone(),
+        }
+    }
+}
+
+/// Кусок Безье с габаритами контрольного многоугольника (веса положительны — кривая
+/// внутри) и признаком «почти прямой».
+struct Piece {
+    curve: BSplineCurve<Vector3>,
+    range: (f64, f64),
+    lo: Point2,
+    hi: Point2,
+    flat: bool,
+}
+
+impl Piece {
+    fn new(curve: BSplineCurve<Vector3>, flat_tol: f64) -> Self {
+        let range = curve.range_tuple();
+        let pts: Vec<Point2> = curve.control_points().iter().map(|v| dehomog(*v)).collect();
+        let (mut lo, mut hi) = (pts[0], pts[0]);
+        for p in &pts {
+            lo = Point2::new(lo.x.min(p.x), lo.y.min(p.y));
+            hi = Point2::new(hi.x.max(p.x), hi.y.max(p.y));
+        }
+        let (a, b) = (pts[0], pts[pts.len() - 1]);
+        let (chord, len) = (b - a, (b - a).magnitude());
+        let flat = pts.iter().all(|p| {
+            let w = p - a;
+            let off = if len > 0.0 {
+                (w.x * chord.y - w.y * chord.x).abs() / len
+            } else {
+                w.magnitude()
+            };
+            off <= flat_tol
+        });
+        Piece {
+            curve,
+            range,
+            lo,
+            hi,
+            flat,
+        }
+    }
+
+    fn split(self, flat_tol: f64) -> (Piece, Piece) {
+        let mut left = self.curve;
+        let right = left.cut((self.range.0 + self.range.1) * 0.5);
+        (Piece::new(left, flat_tol), Piece::new(right, flat_tol))
+    }
+
+    fn size(&self) -> f64 {
+        (self.hi - self.lo).magnitude()
+    }
+
+    fn overlaps(&self, other: &Piece, margin: f64) -> bool {
+        self.lo.x <= other.hi.x + margin
+            && other.lo.x <= self.hi.x + margin
+            && self.lo.y <= other.hi.y + margin
+            && other.lo.y <= self.hi.y + margin
+    }
+
+    fn chord(&self) -> (Point2, Point2) {
+        let pts = self.curve.control_points();
+        (dehomog(pts[0]), dehomog(pts[pts.len() - 1]))
+    }
+
+    fn copy(&self, flat_tol: f64) -> Piece {
+        Piece::new(self.curve.clone(), flat_tol)
+    }
+}
+
+fn dehomog(v: Vector3) -> Point2 {
+    Point2::new(v.x / v.z, v.y / v.z)
+}
+
+/// Все точки пересечения двух кривых (отрезков, дуг, окружностей, эллипсов, полилиний,
+/// рациональных NURBS) по возрастанию параметра на `a`. Наложения и касания без
+/// перехода не ищутся; для не-кривых — пусто.
+pub fn intersect(a: &EntityKind, b: &EntityKind) -> Vec<Intersection> {
+    let (Some(ca), Some(cb)) = (a.to_truck(), b.to_truck()) else {
+        return vec![];
+    };
+    let (ha, hb) = (ca.homogeneous(), cb.homogeneous());
+    let scale = ha
+        .control_points()
+        .iter()
+        .chain(hb.control_points())
+        .map(|v| dehomog(*v))
+        .fold(1.0_f64, |m, p| m.max(p.x.abs()).max(p.y.abs()));
+    let flat_tol = FLAT_TOL * scale;
+    let margin = 1e-9 * scale;
+    let pieces = |h: BSplineCurve<Vector3>| -> Vec<Piece> {
+        h.bezier_decomposition()
+            .into_iter()
+            .filter(|b| b.range_tuple().1 - b.range_tuple().0 > 1e-12)
+            .map(|b| Piece::new(b, flat_tol))
+            .collect()
+    };
+    let (pa, pb) = (pieces(ha), pieces(hb));
+
+    // делим пары кусков до прямых — пересечения хорд дают начальные приближения
+    let mut hints = Vec::new();
+    let mut stack: Vec<(Piece, Piece, usize)> = Vec::new();
+    for p in &pa {
+        for q in pb.iter().filter(|q| p.overlaps(q, margin)) {
+            stack.push((p.copy(flat_tol), q.copy(flat_tol), 0));
+        }
+    }
+    while let Some((p, q, depth)) = stack.pop() {
+        if !p.overlaps(&q, margin) {
+            continue;
+        }
+        if (p.flat && q.flat) || depth >= MAX_DEPTH || stack.len() >= MAX_PAIRS {
+            let ((a0, a1), (b0, b1)) = (p.chord(), q.chord());
+            let (s, u) = match segment_params(a0, a1, b0, b1) {
+                Some(su) => su,
+                None if p.flat && q.flat => continue,
+                None => (0.5, 0.5),
+            };
+            hints.push((
+                p.range.0 + (p.range.1 - p.range.0) * s,
+                q.range.0 + (q.range.1 - q.range.0) * u,
+            ));
+            continue;
+        }
+        // делим больший из непрямых
+        if !p.flat && (q.flat || p.size() >= q.size()) {
+            let (l, r) = p.split(flat_tol);
+            stack.push((l, q.copy(flat_tol), depth + 1));
+            stack.push((r, q, depth + 1));
+        } else {
+            let (l, r) = q.split(flat_tol);
+            stack.push((p.copy(flat_tol), l, depth + 1));
+            stack.push((p, r, depth + 1));
+        }
+    }
+
+    let tol = 1e-9 * scale;
+    let (ra, rb) = (ca.range_tuple(), cb.range_tuple());
+    let inside = |&(s, u): &(f64, f64)| {
+        let (ea, eb) = (1e-9 * (ra.1 - ra.0), 1e-9 * (rb.1 - rb.0));
+        (ra.0 - ea..=ra.1 + ea).contains(&s) && (rb.0 - eb..=rb.1 + eb).contains(&u)
+    };
+    let mut out: Vec<Intersection> = Vec::new();
+    for hint in hints {
+        let found = search_intersection_parameter(&ca, &cb, hint, 32)
+            .filter(inside)
+            .or_else(|| refine(&ca, &cb, hint, tol));
+        let Some((s, u)) = found else {
+            continue;
+        };
+        let (s, u) = (s.clamp(ra.0, ra.1), u.clamp(rb.0, rb.1));
+        let p = ca.subs(s);
+        let dup = out.iter().any(|x| {
+            (x.point.x as f64 - p.x).abs() <= 1e-6 * scale
+                && (x.point.y as f64 - p.y).abs() <= 1e-6 * scale
+        });
+        if !dup {
+            out.push(Intersection {
+                point: Pt2::from(p),
+                t_a: s,
+                t_b: u,
+            });
+        }
+    }
+    out.sort_by(|x, y| x.t_a.total_cmp(&y.t_a));
+    out
+}
+
+/// Параметры пересечения хорд [a0,a1] и [b0,b1] с допуском на концах (кривая рядом
+/// с хордой может пересечься чуть за её концом); `None` — не пересекаются.
+fn segment_params(a0: Point2, a1: Point2, b0: Point2, b1: Point2) -> Option<(f64, f64)> {
+    let (da, db, w) = (a1 - a0, b1 - b0, b0 - a0);
+    let den = da.x * db.y - da.y * db.x;
+    if den.abs() <= 1e-14 * da.magnitude2().max(db.magnitude2()) {
+        return None;
+    }
+    let s = (w.x * db.y - w.y * db.x) / den;
+    let u = (w.x * da.y - w.y * da.x) / den;
+    const EPS: f64 = 0.05;
+    ((-EPS..=1.0 + EPS).contains(&s) && (-EPS..=1.0 + EPS).contains(&u))
+        .then(|| (s.clamp(0.0, 1.0), u.clamp(0.0, 1.0)))
+}
+
+/// Ньютон для A(s) = B(u) с удержанием в областях определения — когда свободный
+/// уходит за конец кривой (пересечение в самом конце).
+fn refine(
+    a: &TruckCurve2,
+    b: &TruckCurve2,
+    (mut s, mut u): (f64, f64),
+    tol: f64,
+) -> Option<(f64, f64)> {
+    let (ra, rb) = (a.range_tuple(), b.range_tuple());
+    for _ in 0..32 {
+        let d = a.subs(s) - b.subs(u);
+        if d.magnitude() <= tol {
+            return Some((s, u));
+        }
+        let (da, db) = (a.der(s), b.der(u));
+        // [da  -db] · (ds, du) = -d
+        let det = -da.x * db.y + da.y * db.x;
+        if det.abs() < 1e-300 {
+            return None;
+        }
+        let ds = (d.x * db.y - d.y * db.x) / det;
+        let du = (d.x * da.y - da.x * d.y) / det;
+        s = (s + ds).clamp(ra.0, ra.1);
+        u = (u + du).clamp(rb.0, rb.1);
+    }
+    ((a.subs(s) - b.subs(u)).magnitude() <= tol * 1e3).then_some((s, u))
+}
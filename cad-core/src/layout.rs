@@ -0,0 +1,308 @@
+// cad-core/src/layout.rs
+//! Оформление листов по ГОСТ 21.101 / 2.104: рамка (поле 20 мм слева, 5 мм
+//! с остальных сторон) и основная надпись — форма 3 на первом листе комплекта,
+//! форма 6 на последующих. Надпись заполняется из [`Project3D::title`].
+//! Комплект выводится в SVG и DXF (пространство листа) по файлу на лист,
+//! в PDF — одним файлом ([`sheet_set_pdf`](crate::sheet_set_pdf)).
+
+use crate::dxf_io::export_dxf_paper;
+use crate::model3d::Project3D;
+use crate::{make_text, Annot, Document, Entity, EntityKind, Layer, LineWeight, Pt2, Sheet};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Слой рамки и основных линий штампа.
+pub const SHEET_FRAME_LAYER: &str = "FRAME";
+/// Слой тонких линий и надписей штампа.
+pub const SHEET_TITLE_LAYER: &str = "TITLE";
+
+/// Поле подшивки слева, мм.
+const BINDING_MM: f32 = 20.0;
+/// Поле с остальных сторон, мм.
+const MARGIN_MM: f32 = 5.0;
+/// Ширина основной надписи, мм.
+const TITLE_W_MM: f32 = 185.0;
+/// Высота формы 3 (первый лист), мм.
+const FORM3_H_MM: f32 = 55.0;
+/// Высота формы 6 (последующие листы), мм.
+const FORM6_H_MM: f32 = 15.0;
+/// Высота строки граф изменений и подписей, мм.
+const ROW_MM: f32 = 5.0;
+/// Графы левой части: ширина и заголовок.
+const LEFT_COLS: [(f32, &str); 6] = [
+    (10.0, "Изм."),
+    (10.0, "Кол.уч."),
+    (10.0, "Лист"),
+    (10.0, "№ док."),
+    (15.0, "Подп."),
+    (10.0, "Дата"),
+];
+/// Строк подписей в форме 3.
+const SIGNATURE_ROWS: usize = 5;
+/// Высоты шрифта, мм.
+const SMALL_MM: f32 = 2.5;
+const TEXT_MM: f32 = 3.5;
+const LARGE_MM: f32 = 5.0;
+/// Ширина знака в долях высоты (шрифт типа Б) — для центровки.
+const CHAR_WIDTH: f32 = 0.6;
+
+/// Сведения основной надписи, общие для комплекта.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TitleInfo {
+    /// Обозначение (шифр) комплекта, графа 1.
+    pub designation: String,
+    /// Наименование объекта строительства, графа 2.
+    pub object: String,
+    /// Стадия: П, Р.
+    pub stage: String,
+    /// Наименование организации, графа 9.
+    pub organization: String,
+    /// Подписи сверху вниз (Разраб., Пров., Н.контр., ГИП…), не больше пяти.
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Signature {
+    pub role: String,
+    pub name: String,
+    pub date: String,
+}
+
+impl Sheet {
+    /// Лист в оформлении: [`Sheet::to_document`], рамка и основная надпись
+    /// (лист `number` из `count`). Если штамп ставится блоком из `model`
+    /// ([`Annot::Block`]), своя надпись не рисуется — только рамка.
+    pub fn render(
+        &self,
+        model: &Document,
+        project: &Project3D,
+        number: usize,
+        count: usize,
+    ) -> Document {
+        let mut doc = self.to_document(model, project);
+        doc.add_layer(Layer {
+            lineweight: LineWeight::Width(50),
+            ..Layer::new(SHEET_FRAME_LAYER)
+        });
+        doc.add_layer(Layer {
+            lineweight: LineWeight::Width(18),
+            ..Layer::new(SHEET_TITLE_LAYER)
+        });
+
+        let (w, h) = self.size_mm;
+        let mut st = Stamp {
+            doc: &mut doc,
+            at: Pt2::new(0.0, 0.0),
+        };
+        // линия обреза и внутренняя рамка
+        st.rect(false, 0.0, 0.0, w, h);
+        st.rect(true, BINDING_MM, MARGIN_MM, w - MARGIN_MM, h - MARGIN_MM);
+
+        let custom = self
+            .annots
+            .iter()
+            .any(|a| matches!(a, Annot::Block { name, .. } if model.block(name).is_some()));
+        if !custom {
+            st.at = Pt2::new(w - MARGIN_MM - TITLE_W_MM, MARGIN_MM);
+            if number <= 1 {
+                st.form3(&project.title, &self.name, number, count);
+            } else {
+                st.form6(&project.title, number);
+            }
+        }
+        doc
+    }
+}
+
+/// Листы комплекта в оформлении, нумерация — по порядку.
+pub fn render_sheets(model: &Document, project: &Project3D, sheets: &[Sheet]) -> Vec<Document> {
+    sheets
+        .iter()
+        .enumerate()
+        .map(|(i, s)| s.render(model, project, i + 1, sheets.len()))
+        .collect()
+}
+
+/// Комплект в `dir/sheet-NN.svg`, по файлу на лист; пути файлов.
+pub fn export_sheet_set_svg(
+    model: &Document,
+    project: &Project3D,
+    sheets: &[Sheet],
+    dir: &str,
+) -> Result<Vec<String>> {
+    export_each(model, project, sheets, dir, "svg", |doc, size, path| {
+        std::fs::write(path, doc.export_svg_page(size))?;
+        Ok(())
+    })
+}
+
+/// Комплект в `dir/sheet-NN.dxf`, по файлу на лист в пространстве листа.
+pub fn export_sheet_set_dxf(
+    model: &Document,
+    project: &Project3D,
+    sheets: &[Sheet],
+    dir: &str,
+) -> Result<Vec<String>> {
+    export_each(model, project, sheets, dir, "dxf", |doc, size, path| {
+        export_dxf_paper(doc, size, path)
+    })
+}
+
+fn export_each(
+    model: &Document,
+    project: &Project3D,
+    sheets: &[Sheet],
+    dir: &str,
+    ext: &str,
+    write: impl Fn(&Document, (f32, f32), &str) -> Result<()>,
+) -> Result<Vec<String>> {
+    let docs = render_sheets(model, project, sheets);
+    let mut paths = Vec::with_capacity(docs.len());
+    for (i, (doc, s)) in docs.iter().zip(sheets).enumerate() {
+        let path = format!("{dir}/sheet-{:02}.{ext}", i + 1);
+        write(doc, s.size_mm, &path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Построитель штампа: координаты граф — от левого нижнего угла `at`.
+struct Stamp<'a> {
+    doc: &'a mut Document,
+    at: Pt2,
+}
+
+impl Stamp<'_> {
+    fn line(&mut self, thick: bool, x0: f32, y0: f32, x1: f32, y1: f32) {
+        let layer = if thick {
+            SHEET_FRAME_LAYER
+        } else {
+            SHEET_TITLE_LAYER
+        };
+        let p = |x: f32, y: f32| Pt2::new(self.at.x + x, self.at.y + y);
+        let kind = EntityKind::LineSeg {
+            a: p(x0, y0),
+            b: p(x1, y1),
+        };
+        self.doc.add_entity(Entity::new(layer, kind));
+    }
+
+    fn rect(&mut self, thick: bool, x0: f32, y0: f32, x1: f32, y1: f32) {
+        self.line(thick, x0, y0, x1, y0);
+        self.line(thick, x1, y0, x1, y1);
+        self.line(thick, x1, y1, x0, y1);
+        self.line(thick, x0, y1, x0, y0);
+    }
+
+    /// Текст в графе `x, y, w, h`: по высоте — посередине, по ширине — с отступом
+    /// 1 мм или по центру (ширина оценочная, длинный текст прижимается влево).
+    fn text(&mut self, (x, y, w, h): (f32, f32, f32, f32), s: &str, height: f32, center: bool) {
+        if s.is_empty() {
+            return;
+        }
+        let len = s.chars().count() as f32 * height * CHAR_WIDTH;
+        let dx = if center {
+            ((w - len) / 2.0).max(0.5)
+        } else {
+            1.0
+        };
+        let pos = Pt2::new(self.at.x + x + dx, self.at.y + y + (h - height) / 2.0);
+        make_text(self.doc, pos, s, height, SHEET_TITLE_LAYER);
+    }
+
+    /// Заголовки граф левой части в строке `y`.
+    fn left_header(&mut self, y: f32) {
+        let mut x = 0.0;
+        for (w, name) in LEFT_COLS {
+            self.text((x, y, w, ROW_MM), name, SMALL_MM, true);
+            x += w;
+        }
+    }
+
+    /// Форма 3, 185 × 55: изменения и подписи слева, обозначение, объект,
+    /// стадия и листы, наименование изображений и организация справа.
+    fn form3(&mut self, info: &TitleInfo, sheet_name: &str, number: usize, count: usize) {
+        let left: f32 = LEFT_COLS.iter().map(|c| c.0).sum();
+        let head = ROW_MM * SIGNATURE_ROWS as f32;
+        self.rect(true, 0.0, 0.0, TITLE_W_MM, FORM3_H_MM);
+
+        // левая часть: строки по 5 мм, строка заголовков — между толстыми линиями
+        for i in 1..(FORM3_H_MM / ROW_MM) as usize {
+            let y = i as f32 * ROW_MM;
+            let thick = y == head || y == head + ROW_MM;
+            self.line(thick, 0.0, y, left, y);
+        }
+        // в подписях графы «Изм.» и «Лист» объединены с соседними
+        let mut x = 0.0;
+        for (i, (w, _)) in LEFT_COLS.iter().enumerate() {
+            x += w;
+            let y0 = if i == 0 || i == 2 { head } else { 0.0 };
+            self.line(true, x, y0, x, FORM3_H_MM);
+        }
+        self.left_header(head);
+        for (i, s) in info.signatures.iter().take(SIGNATURE_ROWS).enumerate() {
+            let y = head - (i + 1) as f32 * ROW_MM;
+            self.text((0.0, y, 20.0, ROW_MM), &s.role, SMALL_MM, false);
+            self.text((20.0, y, 20.0, ROW_MM), &s.name, SMALL_MM, false);
+            self.text((55.0, y, 10.0, ROW_MM), &s.date, SMALL_MM, true);
+        }
+
+        // правая часть
+        let mid = left + 70.0;
+        self.line(true, left, 40.0, TITLE_W_MM, 40.0);
+        self.line(true, left, head, TITLE_W_MM, head);
+        self.line(true, mid, 0.0, mid, 40.0);
+        self.line(false, mid, 35.0, TITLE_W_MM, 35.0);
+        self.text((left, 40.0, 120.0, 15.0), &info.designation, LARGE_MM, true);
+        self.text((left, head, 70.0, 15.0), &info.object, TEXT_MM, true);
+        self.text((left, 0.0, 70.0, head), sheet_name, TEXT_MM, true);
+        self.text((mid, 0.0, 50.0, head), &info.organization, TEXT_MM, true);
+
+        let number = number.max(1).to_string();
+        let count = count.to_string();
+        let cells = [
+            (15.0, "Стадия", info.stage.as_str()),
+            (15.0, "Лист", number.as_str()),
+            (20.0, "Листов", count.as_str()),
+        ];
+        let mut x = mid;
+        for (w, name, value) in cells {
+            if x > mid {
+                self.line(true, x, head, x, 40.0);
+            }
+            self.text((x, 35.0, w, ROW_MM), name, SMALL_MM, true);
+            self.text((x, head, w, 10.0), value, TEXT_MM, true);
+            x += w;
+        }
+    }
+
+    /// Форма 6, 185 × 15: изменения, обозначение и номер листа.
+    fn form6(&mut self, info: &TitleInfo, number: usize) {
+        let left: f32 = LEFT_COLS.iter().map(|c| c.0).sum();
+        let sheet_x = TITLE_W_MM - 10.0;
+        self.rect(true, 0.0, 0.0, TITLE_W_MM, FORM6_H_MM);
+        self.line(true, 0.0, ROW_MM, left, ROW_MM);
+        self.line(false, 0.0, 2.0 * ROW_MM, left, 2.0 * ROW_MM);
+        let mut x = 0.0;
+        for (w, _) in LEFT_COLS {
+            x += w;
+            self.line(true, x, 0.0, x, FORM6_H_MM);
+        }
+        self.left_header(0.0);
+        self.line(true, sheet_x, 0.0, sheet_x, FORM6_H_MM);
+        self.line(false, sheet_x, 8.0, TITLE_W_MM, 8.0);
+        self.text(
+            (left, 0.0, sheet_x - left, FORM6_H_MM),
+            &info.designation,
+            LARGE_MM,
+            true,
+        );
+        self.text((sheet_x, 8.0, 10.0, 7.0), "Лист", SMALL_MM, true);
+        self.text(
+            (sheet_x, 0.0, 10.0, 8.0),
+            &number.to_string(),
+            TEXT_MM,
+            true,
+        );
+    }
+}
@@ -1,18 +1,60 @@
+pub mod array;
+pub mod boundary;
+pub mod dim;
 pub mod doc;
 pub mod dxf_io;
+pub mod edit;
+pub mod fillet;
 pub mod geom;
-#[cfg(feature = "ifc-ffi")]
+pub mod hatch;
+pub mod history;
+#[cfg(feature = "ifc")]
 pub mod ifc;
+pub mod intersect;
+pub mod layout;
+pub mod measure;
 mod mesh;
 pub mod model3d;
+pub mod offset;
 pub mod ops;
+pub mod pdf;
+pub mod projection;
+pub mod region;
+pub mod section;
 pub mod sheet;
+pub mod sketch;
+pub mod spatial;
+pub mod store;
+pub mod style;
+pub mod table;
+#[cfg(feature = "cryxtal-brep")]
+pub mod truck_bridge;
+mod ttf;
 
+pub use array::*;
+pub use boundary::*;
+pub use dim::*;
 pub use doc::*;
+pub use edit::*;
+pub use fillet::*;
 pub use geom::*;
-#[cfg(feature = "ifc-ffi")]
-pub use ifc::import_ifc;
+pub use hatch::*;
+pub use history::{History, Transaction, HISTORY_BYTES, HISTORY_STEPS};
+#[cfg(feature = "ifc")]
+pub use ifc::{export_ifc, import_ifc};
+pub use intersect::*;
+pub use layout::*;
+pub use measure::*;
 pub use mesh::Mesh;
 pub use model3d::*;
+pub use offset::*;
 pub use ops::*;
+pub use pdf::*;
+pub use projection::*;
+pub use region::*;
 pub use sheet::*;
+pub use sketch::*;
+pub use spatial::{entity_bounds, kind_bounds, Bbox};
+pub use store::EntityStore;
+pub use style::*;
+pub use table::*;
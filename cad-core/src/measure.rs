@@ -0,0 +1,656 @@
+// cad-core/src/measure.rs
+//! Измерения 2D-геометрии: длина кривой, площадь и центр тяжести замкнутых контуров
+//! с отверстиями, наименьшее расстояние между сущностями, точная габаритная рамка.
+//!
+//! Отрезки, дуги и окружности считаются по формулам, эллипсы и NURBS — адаптивной
+//! квадратурой Гаусса по параметру. Площадь и статические моменты берутся по формуле
+//! Грина вдоль контура, поэтому дуги и кривые не заменяются ломаными.
+
+use crate::hatch::point_in_polygon;
+use crate::{
+    curve_at, curve_param, intersect, kind_bounds, polyline_segments, Bbox, Document, Entity,
+    EntityKind, HatchLoop, Pt2, TruckCurve2,
+};
+use cryxtal_geometry::prelude::*;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
+
+/// Относительная точность квадратуры.
+const QUAD_TOL: f64 = 1e-10;
+/// Предел деления промежутка интегрирования пополам.
+const QUAD_DEPTH: usize = 16;
+/// Предел уточнения пары ближайших точек.
+const CLOSEST_ITERS: usize = 64;
+
+/// Узлы и веса Гаусса — Лежандра на [−1, 1].
+const GAUSS: [(f64, f64); 5] = [
+    (0.0, 0.568_888_888_888_889),
+    (-0.538_469_310_105_683, 0.478_628_670_499_366),
+    (0.538_469_310_105_683, 0.478_628_670_499_366),
+    (-0.906_179_845_938_664, 0.236_926_885_056_189),
+    (0.906_179_845_938_664, 0.236_926_885_056_189),
+];
+
+/// Площадь, центр тяжести и периметр.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AreaProps {
+    /// У одного контура — со знаком (против часовой — плюс), у области — за вычетом отверстий.
+    pub area: f64,
+    pub centroid: Pt2,
+    pub perimeter: f64,
+}
+
+/// Наименьшее расстояние и точки, на которых оно достигается.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Closest {
+    pub distance: f64,
+    pub a: Pt2,
+    pub b: Pt2,
+}
+
+// --------------------------- интегралы вдоль кривой ---------------------------
+
+/// Интегралы вдоль кривой: длина, ∮(x dy − y dx), ∮x² dy, ∮y² dx.
+#[derive(Debug, Clone, Copy, Default)]
+struct Moments([f64; 4]);
+
+impl Moments {
+    fn add(self, o: Moments) -> Moments {
+        Moments(std::array::from_fn(|k| self.0[k] + o.0[k]))
+    }
+
+    /// Обратный обход: длина та же, остальное меняет знак.
+    fn reversed(self) -> Moments {
+        let [l, a, x, y] = self.0;
+        Moments([l, -a, -x, -y])
+    }
+
+    fn length(&self) -> f64 {
+        self.0[0]
+    }
+
+    fn area(&self) -> f64 {
+        self.0[1] / 2.0
+    }
+
+    /// Статические моменты ∬x dA и ∬y dA.
+    fn statics(&self) -> (f64, f64) {
+        (self.0[2] / 2.0, -self.0[3] / 2.0)
+    }
+}
+
+/// Параметрическая кривая в f64: точка и производная.
+enum Param {
+    Circle {
+        c: [f64; 2],
+        r: f64,
+    },
+    Ellipse {
+        c: [f64; 2],
+        u: [f64; 2],
+        v: [f64; 2],
+    },
+    Truck(TruckCurve2),
+}
+
+impl Param {
+    fn eval(&self, t: f64) -> ([f64; 2], [f64; 2]) {
+        match self {
+            Param::Circle { c, r } => {
+                let (s, co) = t.sin_cos();
+                ([c[0] + r * co, c[1] + r * s], [-r * s, r * co])
+            }
+            Param::Ellipse { c, u, v } => {
+                let (s, co) = t.sin_cos();
+                (
+                    [c[0] + u[0] * co + v[0] * s, c[1] + u[1] * co + v[1] * s],
+                    [-u[0] * s + v[0] * co, -u[1] * s + v[1] * co],
+                )
+            }
+            Param::Truck(c) => {
+                let (p, d) = (c.subs(t), c.der(t));
+                ([p.x, p.y], [d.x, d.y])
+            }
+        }
+    }
+
+    fn integrand(&self, t: f64) -> [f64; 4] {
+        let ([x, y], [dx, dy]) = self.eval(t);
+        [dx.hypot(dy), x * dy - y * dx, x * x * dy, y * y * dx]
+    }
+
+    fn gauss(&self, a: f64, b: f64) -> Moments {
+        let (m, h) = ((a + b) / 2.0, (b - a) / 2.0);
+        let mut s = [0.0; 4];
+        for (x, w) in GAUSS {
+            let v = self.integrand(m + h * x);
+            for k in 0..4 {
+                s[k] += w * h * v[k];
+            }
+        }
+        Moments(s)
+    }
+
+    /// Адаптивно: промежуток делится, пока половины не сойдутся с целым.
+    fn integrate(&self, a: f64, b: f64, whole: Moments, depth: usize) -> Moments {
+        let m = (a + b) / 2.0;
+        let (l, r) = (self.gauss(a, m), self.gauss(m, b));
+        let sum = l.add(r);
+        // масштаб для компонент, которые на куске почти нулевые: длина × размер
+        let ([x, y], _) = self.eval(m);
+        let size = x.hypot(y) + sum.length();
+        let ok = (0..4).all(|k| {
+            let scale =
+                l.0[k].abs() + r.0[k].abs() + 1e-3 * sum.length() * size.powi(k.min(2) as i32);
+            (sum.0[k] - whole.0[k]).abs() <= QUAD_TOL * scale
+        });
+        if ok || depth == 0 {
+            return sum;
+        }
+        self.integrate(a, m, l, depth - 1)
+            .add(self.integrate(m, b, r, depth - 1))
+    }
+
+    /// Интегралы на [t0, t1] с опорными точками `breaks` внутри (узлы, четверти дуг).
+    fn moments(&self, breaks: &[f64]) -> Moments {
+        breaks
+            .windows(2)
+            .filter(|w| w[1] != w[0])
+            .map(|w| self.integrate(w[0], w[1], self.gauss(w[0], w[1]), QUAD_DEPTH))
+            .fold(Moments::default(), Moments::add)
+    }
+}
+
+fn line_moments(a: Pt2, b: Pt2) -> Moments {
+    let (ax, ay, bx, by) = (a.x as f64, a.y as f64, b.x as f64, b.y as f64);
+    let (dx, dy) = (bx - ax, by - ay);
+    Moments([
+        dx.hypot(dy),
+        ax * by - bx * ay,
+        dy * (ax * ax + ax * dx + dx * dx / 3.0),
+        dx * (ay * ay + ay * dy + dy * dy / 3.0),
+    ])
+}
+
+/// Разбиение угла от `s` до `e` (в любую сторону) на куски не больше 45°.
+fn angle_breaks(s: f64, e: f64) -> Vec<f64> {
+    let n = ((e - s).abs() / FRAC_PI_4).ceil().max(1.0) as usize;
+    (0..=n).map(|i| s + (e - s) * i as f64 / n as f64).collect()
+}
+
+fn xy(p: Pt2) -> [f64; 2] {
+    [p.x as f64, p.y as f64]
+}
+
+/// Интегралы кривой в её собственном направлении и её концы; `None` для не-кривых.
+fn curve_moments(kind: &EntityKind) -> Option<(Moments, Pt2, Pt2)> {
+    let arc = |c: Pt2, r: f32| Param::Circle {
+        c: xy(c),
+        r: r as f64,
+    };
+    let m = match kind {
+        EntityKind::LineSeg { a, b } => return Some((line_moments(*a, *b), *a, *b)),
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let (s, e) = (*start_angle as f64, *end_angle as f64);
+            arc(*center, *radius).moments(&angle_breaks(s, e))
+        }
+        EntityKind::Circle { center, radius } => {
+            arc(*center, *radius).moments(&angle_breaks(0.0, TAU))
+        }
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            start_param,
+            end_param,
+        } => {
+            let (u, r) = (xy(*major), *ratio as f64);
+            let p = Param::Ellipse {
+                c: xy(*center),
+                u,
+                v: [-r * u[1], r * u[0]],
+            };
+            p.moments(&angle_breaks(*start_param as f64, *end_param as f64))
+        }
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => {
+            let segs = polyline_segments(pts, bulges, *closed);
+            let m = segs
+                .iter()
+                .filter_map(curve_moments)
+                .fold(Moments::default(), |acc, (m, _, _)| acc.add(m));
+            let ends = (
+                segs.first().and_then(curve_ends),
+                segs.last().and_then(curve_ends),
+            );
+            return match ends {
+                (Some((a, _)), Some((_, b))) => Some((m, a, b)),
+                _ => None,
+            };
+        }
+        EntityKind::NurbsCurve2D { .. } => {
+            let c = kind.to_truck()?;
+            let knots: Vec<f64> = match &c {
+                TruckCurve2::BSpline(b) => b.knot_vec().to_vec(),
+                TruckCurve2::Nurbs(n) => n.knot_vec().to_vec(),
+            };
+            let (t0, t1) = c.range_tuple();
+            let mut breaks: Vec<f64> = knots
+                .into_iter()
+                .filter(|k| (t0..=t1).contains(k))
+                .collect();
+            breaks.dedup();
+            Param::Truck(c).moments(&breaks)
+        }
+        _ => return None,
+    };
+    let (a, b) = curve_ends(kind)?;
+    Some((m, a, b))
+}
+
+/// Начало и конец кривой в её направлении.
+fn curve_ends(kind: &EntityKind) -> Option<(Pt2, Pt2)> {
+    match kind {
+        EntityKind::LineSeg { a, b } => Some((*a, *b)),
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let at = |t: f32| Pt2::new(center.x + radius * t.cos(), center.y + radius * t.sin());
+            Some((at(*start_angle), at(*end_angle)))
+        }
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            start_param,
+            end_param,
+        } => Some((
+            crate::ellipse_point(*center, *major, *ratio, *start_param),
+            crate::ellipse_point(*center, *major, *ratio, *end_param),
+        )),
+        EntityKind::Circle { .. }
+        | EntityKind::Polyline { .. }
+        | EntityKind::NurbsCurve2D { .. } => {
+            let (t0, t1) = crate::curve_range(kind)?;
+            Some((curve_at(kind, t0)?, curve_at(kind, t1)?))
+        }
+        _ => None,
+    }
+}
+
+// --------------------------- длина и площадь ---------------------------
+
+/// Длина кривой: отрезки, дуги, окружности и полилинии — точно, эллипсы и NURBS —
+/// квадратурой; у штриховки — периметр всех контуров. `None` для текста, вставок,
+/// размеров и таблиц.
+pub fn curve_length(kind: &EntityKind) -> Option<f64> {
+    match kind {
+        EntityKind::LineSeg { a, b } => {
+            Some((b.x as f64 - a.x as f64).hypot(b.y as f64 - a.y as f64))
+        }
+        EntityKind::Arc {
+            radius,
+            start_angle,
+            end_angle,
+            ..
+        } => Some(*radius as f64 * (*end_angle as f64 - *start_angle as f64).abs()),
+        EntityKind::Circle { radius, .. } => Some(*radius as f64 * TAU),
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => polyline_segments(pts, bulges, *closed)
+            .iter()
+            .map(curve_length)
+            .sum(),
+        EntityKind::Ellipse { .. } | EntityKind::NurbsCurve2D { .. } => {
+            curve_moments(kind).map(|(m, _, _)| m.length())
+        }
+        EntityKind::Hatch { loops, .. } => {
+            loops.iter().flat_map(|l| &l.edges).map(curve_length).sum()
+        }
+        EntityKind::Text { .. }
+        | EntityKind::Insert { .. }
+        | EntityKind::Dimension { .. }
+        | EntityKind::Table { .. } => None,
+    }
+}
+
+/// Интегралы контура с рёбрами, развёрнутыми в цепочку «конец к началу»
+/// (направление каждого ребра в исходном списке любое).
+fn loop_moments(edges: &[EntityKind]) -> Option<Moments> {
+    let parts: Vec<(Moments, Pt2, Pt2)> = edges.iter().map(curve_moments).collect::<Option<_>>()?;
+    let d = |p: Pt2, q: Pt2| (p.x - q.x).hypot(p.y - q.y);
+    let mut total = Moments::default();
+    // конец предыдущего ребра; первое ребро ориентируется по второму
+    let mut end = match parts.get(1) {
+        Some(&(_, a1, b1)) => {
+            let (_, a0, b0) = parts[0];
+            let gap = |p: Pt2| d(p, a1).min(d(p, b1));
+            if gap(a0) < gap(b0) {
+                a0
+            } else {
+                b0
+            }
+        }
+        None => parts.first()?.1,
+    };
+    for &(m, a, b) in &parts {
+        if d(end, a) <= d(end, b) {
+            total = total.add(m);
+            end = b;
+        } else {
+            total = total.add(m.reversed());
+            end = a;
+        }
+    }
+    Some(total)
+}
+
+/// Площадь со знаком (против часовой — плюс), центр тяжести и периметр одного
+/// замкнутого контура. `None`, если среди рёбер есть не-кривые или контур вырожден.
+pub fn loop_props(edges: &[EntityKind]) -> Option<AreaProps> {
+    let m = loop_moments(edges)?;
+    let area = m.area();
+    if area.abs() <= f64::EPSILON * m.length().powi(2) {
+        return None;
+    }
+    let (sx, sy) = m.statics();
+    Some(AreaProps {
+        area,
+        centroid: Pt2::new((sx / area) as f32, (sy / area) as f32),
+        perimeter: m.length(),
+    })
+}
+
+/// Область из контуров: контур внутри нечётного числа других — отверстие. Площадь
+/// положительна и без отверстий, периметр — сумма всех контуров.
+pub fn region_props(loops: &[HatchLoop]) -> Option<AreaProps> {
+    let polys: Vec<Vec<Pt2>> = loops.iter().map(HatchLoop::polygon).collect();
+    let (mut area, mut sx, mut sy, mut perimeter) = (0.0, 0.0, 0.0, 0.0);
+    for (i, l) in loops.iter().enumerate() {
+        let Some(m) = loop_moments(&l.edges) else {
+            continue;
+        };
+        let Some(&probe) = polys[i].first() else {
+            continue;
+        };
+        let depth = (0..polys.len())
+            .filter(|&j| j != i && point_in_polygon(probe, &polys[j]))
+            .count();
+        // к обходу против часовой, отверстия — с минусом
+        let sign = m.area().signum() * if depth % 2 == 0 { 1.0 } else { -1.0 };
+        let (x, y) = m.statics();
+        area += sign * m.area();
+        sx += sign * x;
+        sy += sign * y;
+        perimeter += m.length();
+    }
+    (area.abs() > f64::EPSILON).then(|| AreaProps {
+        area,
+        centroid: Pt2::new((sx / area) as f32, (sy / area) as f32),
+        perimeter,
+    })
+}
+
+/// Площадь сущности: окружность, полный эллипс, замкнутые полилиния и NURBS,
+/// штриховка (с отверстиями). Площадь кривой — со знаком по её обходу.
+pub fn entity_area(kind: &EntityKind) -> Option<AreaProps> {
+    match kind {
+        EntityKind::Hatch { loops, .. } => region_props(loops),
+        k if crate::is_closed_curve(k) => loop_props(std::slice::from_ref(k)),
+        _ => None,
+    }
+}
+
+// --------------------------- расстояние ---------------------------
+
+/// Ближайшая к `p` точка кривой; у текста — точка вставки.
+fn nearest_on(kind: &EntityKind, p: Pt2) -> Option<Pt2> {
+    match kind {
+        EntityKind::Text { pos, .. } => Some(*pos),
+        k => curve_at(k, curve_param(k, p)?),
+    }
+}
+
+/// Точки грубого перебора: узлы и выборка по пролётам.
+fn probes(kind: &EntityKind) -> Vec<Pt2> {
+    match kind {
+        EntityKind::Text { pos, .. } => vec![*pos],
+        EntityKind::Polyline { pts, .. } => pts.clone(),
+        k => match k.to_truck() {
+            Some(c) => c
+                .sample_params()
+                .into_iter()
+                .map(|t| Pt2::from(c.subs(t)))
+                .collect(),
+            None => vec![],
+        },
+    }
+}
+
+fn distance(a: Pt2, b: Pt2) -> f64 {
+    (a.x as f64 - b.x as f64).hypot(a.y as f64 - b.y as f64)
+}
+
+/// Наименьшее расстояние между двумя кривыми (текст — точкой вставки). Пересекающиеся
+/// кривые — ноль в первой точке пересечения.
+pub fn curve_distance(a: &EntityKind, b: &EntityKind) -> Option<Closest> {
+    if let Some(x) = intersect(a, b).first() {
+        return Some(Closest {
+            distance: 0.0,
+            a: x.point,
+            b: x.point,
+        });
+    }
+    let pair = |pa: Pt2, pb: Pt2| Closest {
+        distance: distance(pa, pb),
+        a: pa,
+        b: pb,
+    };
+    let from_a = probes(a)
+        .into_iter()
+        .filter_map(|p| Some(pair(p, nearest_on(b, p)?)));
+    let from_b = probes(b)
+        .into_iter()
+        .filter_map(|q| Some(pair(nearest_on(a, q)?, q)));
+    let mut best = from_a
+        .chain(from_b)
+        .min_by(|x, y| x.distance.total_cmp(&y.distance))?;
+    // поочерёдные проекции сходятся к местному минимуму
+    for _ in 0..CLOSEST_ITERS {
+        let Some(pa) = nearest_on(a, best.b) else {
+            break;
+        };
+        let Some(pb) = nearest_on(b, pa) else {
+            break;
+        };
+        let next = pair(pa, pb);
+        if next.distance >= best.distance - 1e-9 * (1.0 + best.distance) {
+            if next.distance < best.distance {
+                best = next;
+            }
+            break;
+        }
+        best = next;
+    }
+    Some(best)
+}
+
+/// Кривые сущности для измерения расстояния: вставки, размеры и таблицы — развёрнутая
+/// геометрия, штриховка — рёбра контуров.
+fn measured_curves(doc: &Document, e: &Entity) -> Vec<EntityKind> {
+    let kinds: Vec<EntityKind> = if e.kind.is_block_ref() {
+        doc.insert_geometry(e).into_iter().map(|s| s.kind).collect()
+    } else {
+        vec![e.kind.clone()]
+    };
+    kinds
+        .into_iter()
+        .flat_map(|k| match k {
+            EntityKind::Hatch { loops, .. } => loops.into_iter().flat_map(|l| l.edges).collect(),
+            k => vec![k],
+        })
+        .collect()
+}
+
+/// Наименьшее расстояние между двумя сущностями документа.
+pub fn entity_distance(doc: &Document, a: &Entity, b: &Entity) -> Option<Closest> {
+    let (ka, kb) = (measured_curves(doc, a), measured_curves(doc, b));
+    ka.iter()
+        .flat_map(|x| kb.iter().filter_map(move |y| curve_distance(x, y)))
+        .min_by(|x, y| x.distance.total_cmp(&y.distance))
+}
+
+// --------------------------- габариты ---------------------------
+
+/// Значения `base + k·step` между `s` и `e`.
+fn crossings(s: f64, e: f64, base: f64, step: f64) -> impl Iterator<Item = f64> {
+    let (lo, hi) = (s.min(e), s.max(e));
+    let first = ((lo - base) / step).ceil() as i64;
+    (first..)
+        .map(move |k| base + k as f64 * step)
+        .take_while(move |t| *t <= hi)
+        .take(8)
+}
+
+fn points_box(pts: impl IntoIterator<Item = Pt2>) -> Option<Bbox> {
+    pts.into_iter()
+        .filter(|p| p.x.is_finite() && p.y.is_finite())
+        .map(|p| Bbox { min: p, max: p })
+        .reduce(|a, b| a.union(&b))
+}
+
+/// Точная рамка кривой — по крайним точкам, без центров дуг и управляющих точек.
+/// Текст — точкой вставки; `None` для вставок, размеров и таблиц, см. [`entity_extents`].
+pub fn curve_extents(kind: &EntityKind) -> Option<Bbox> {
+    match kind {
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let (s, e) = (*start_angle as f64, *end_angle as f64);
+            let c = Param::Circle {
+                c: xy(*center),
+                r: *radius as f64,
+            };
+            let at = |t: f64| {
+                let ([x, y], _) = c.eval(t);
+                Pt2::new(x as f32, y as f32)
+            };
+            points_box(
+                [s, e]
+                    .into_iter()
+                    .chain(crossings(s, e, 0.0, FRAC_PI_2))
+                    .map(at),
+            )
+        }
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            start_param,
+            end_param,
+        } => {
+            let (s, e) = (*start_param as f64, *end_param as f64);
+            let (u, r) = (xy(*major), *ratio as f64);
+            let v = [-r * u[1], r * u[0]];
+            let p = Param::Ellipse {
+                c: xy(*center),
+                u,
+                v,
+            };
+            let at = |t: f64| {
+                let ([x, y], _) = p.eval(t);
+                Pt2::new(x as f32, y as f32)
+            };
+            // x' = 0 при tan t = v.x / u.x, y' = 0 при tan t = v.y / u.y
+            let tx = v[0].atan2(u[0]);
+            let ty = v[1].atan2(u[1]);
+            let ts = [s, e]
+                .into_iter()
+                .chain(crossings(s, e, tx, PI))
+                .chain(crossings(s, e, ty, PI));
+            points_box(ts.map(at))
+        }
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => polyline_segments(pts, bulges, *closed)
+            .iter()
+            .filter_map(curve_extents)
+            .chain(points_box(pts.iter().copied()))
+            .reduce(|a, b| a.union(&b)),
+        EntityKind::NurbsCurve2D { .. } => {
+            let c = kind.to_truck()?;
+            let ts = c.sample_params();
+            let mut pts: Vec<Pt2> = ts.iter().map(|t| Pt2::from(c.subs(*t))).collect();
+            // корни производной по каждой оси между соседними пробами — делением пополам
+            for axis in 0..2 {
+                let der = |t: f64| {
+                    let d = c.der(t);
+                    if axis == 0 {
+                        d.x
+                    } else {
+                        d.y
+                    }
+                };
+                for w in ts.windows(2) {
+                    let (mut lo, mut hi) = (w[0], w[1]);
+                    let (dl, dh) = (der(lo), der(hi));
+                    if dl == 0.0 || dl.signum() == dh.signum() {
+                        continue;
+                    }
+                    for _ in 0..60 {
+                        let m = (lo + hi) / 2.0;
+                        if der(m).signum() == dl.signum() {
+                            lo = m;
+                        } else {
+                            hi = m;
+                        }
+                    }
+                    pts.push(Pt2::from(c.subs((lo + hi) / 2.0)));
+                }
+            }
+            points_box(pts)
+        }
+        EntityKind::Hatch { loops, .. } => loops
+            .iter()
+            .flat_map(|l| &l.edges)
+            .filter_map(curve_extents)
+            .reduce(|a, b| a.union(&b)),
+        EntityKind::Insert { .. } | EntityKind::Dimension { .. } | EntityKind::Table { .. } => None,
+        k => kind_bounds(k),
+    }
+}
+
+/// Точная рамка сущности; вставки, размеры и таблицы — по развёрнутой геометрии.
+pub fn entity_extents(doc: &Document, e: &Entity) -> Option<Bbox> {
+    if !e.kind.is_block_ref() {
+        return curve_extents(&e.kind);
+    }
+    doc.insert_geometry(e)
+        .iter()
+        .filter_map(|s| curve_extents(&s.kind))
+        .reduce(|a, b| a.union(&b))
+}
+
+/// Общая рамка набора сущностей (всего чертежа, выделения).
+pub fn extents<'a>(doc: &Document, ents: impl IntoIterator<Item = &'a Entity>) -> Option<Bbox> {
+    ents.into_iter()
+        .filter_map(|e| entity_extents(doc, e))
+        .reduce(|a, b| a.union(&b))
+}
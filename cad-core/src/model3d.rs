@@ -11,14 +11,14 @@ use crate::mesh::Mesh;
 use cryxtal_geometry::prelude::*; // BSplineCurve, KnotVec, Point3, ..
 
 #[cfg(feature = "cryxtal-brep")]
-use cryxtal_modeling::Solid;
+use cryxtal_modeling::topology::Solid;
 
 #[cfg(not(feature = "cryxtal-brep"))]
 #[derive(Debug, Clone)]
 pub struct SolidStub; // лёгкая заглушка, когда cryxtal_modeling не подключён
 
 /// Удобная 3D-точка в мм (без cgmath)
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
 pub struct Pt3 {
     pub x: f32,
     pub y: f32,
@@ -37,6 +37,9 @@ pub type MaterialId = u32;
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Project3D {
     pub models: Vec<Model3D>,
+    /// Сведения для основной надписи листов.
+    #[serde(default)]
+    pub title: crate::TitleInfo,
 }
 
 /// Одна 3D-модель
@@ -222,57 +225,63 @@ impl Element3D {
                 triangulate_from_mesh(positions, indices, self.xform)
             }
             #[cfg(feature = "cryxtal-brep")]
-            ElementGeom::Brep(_) => Mesh::default(), // TODO: триангуляция B-Rep
+            ElementGeom::Brep(solid) => triangulate_brep(solid, self.xform),
             #[cfg(not(feature = "cryxtal-brep"))]
             ElementGeom::Brep(_) => Mesh::default(),
         }
     }
 }
 
-fn apply_xform(p: [f32; 3], m: [[f32; 4]; 4]) -> [f32; 3] {
+pub(crate) fn apply_xform(p: [f32; 3], m: [[f32; 4]; 4]) -> [f32; 3] {
     let x = p[0] * m[0][0] + p[1] * m[0][1] + p[2] * m[0][2] + m[0][3];
     let y = p[0] * m[1][0] + p[1] * m[1][1] + p[2] * m[1][2] + m[1][3];
     let z = p[0] * m[2][0] + p[1] * m[2][1] + p[2] * m[2][2] + m[2][3];
     [x, y, z]
 }
 
-fn triangulate_extrusion(poly: &Vec<crate::Pt2>, h: f32, xf: [[f32; 4]; 4]) -> Mesh {
-    // MVP: предполагаем, что poly — замкнутый и без самопересечений.
+fn triangulate_extrusion(poly: &[crate::Pt2], h: f32, xf: [[f32; 4]; 4]) -> Mesh {
+    // контур без повтора первой точки, против часовой — тогда все грани наружу
+    let mut ring: Vec<crate::Pt2> = poly.to_vec();
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    let area2: f32 = (0..ring.len())
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+    if area2 < 0.0 {
+        ring.reverse();
+    }
     let mut m = Mesh::default();
-    if poly.len() < 2 {
+    if ring.len() < 3 {
         return m;
     }
 
     // вершины: нижний и верхний контуры
-    let base_idx = 0u32;
-    for k in 0..2 {
-        let z = if k == 0 { 0.0 } else { h };
-        for p in poly {
-            let v = apply_xform([p.x, p.y, z], xf);
-            m.positions.push(v);
+    for z in [0.0, h] {
+        for p in &ring {
+            m.positions.push(apply_xform([p.x, p.y, z], xf));
         }
     }
 
-    // боковые индексы
-    let n = poly.len() as u32;
-    for i in 0..(n - 1) {
-        let a0 = base_idx + i;
-        let b0 = base_idx + i + 1;
-        let a1 = base_idx + n + i;
-        let b1 = base_idx + n + i + 1;
-        m.indices.extend_from_slice(&[a0, b0, b1, a0, b1, a1]);
+    // боковые грани, включая замыкающую
+    let n = ring.len() as u32;
+    for i in 0..n {
+        let j = (i + 1) % n;
+        m.indices.extend_from_slice(&[i, j, n + j, i, n + j, n + i]);
     }
 
-    // крышки (фан к нулевому)
-    // низ
-    for i in 1..(n - 1) {
-        m.indices
-            .extend_from_slice(&[base_idx, base_idx + i, base_idx + i + 1]);
-    }
-    // верх
-    let top = base_idx + n;
-    for i in 1..(n - 1) {
-        m.indices.extend_from_slice(&[top, top + i + 1, top + i]); // обратная ориентация
+    // крышки: невыпуклый контур — через earcut; низ смотрит вниз, верх — вверх
+    let coords: Vec<f64> = ring.iter().flat_map(|p| [p.x as f64, p.y as f64]).collect();
+    let tris = earcutr::earcut(&coords, &[], 2).unwrap_or_default();
+    for t in tris.chunks_exact(3) {
+        let (a, b, c) = (t[0] as u32, t[1] as u32, t[2] as u32);
+        let (pa, pb, pc) = (ring[t[0]], ring[t[1]], ring[t[2]]);
+        let ccw = (pb.x - pa.x) * (pc.y - pa.y) - (pb.y - pa.y) * (pc.x - pa.x) > 0.0;
+        let (b, c) = if ccw { (b, c) } else { (c, b) };
+        m.indices.extend_from_slice(&[a, c, b, n + a, n + b, n + c]);
     }
 
     // примитивные нормали
@@ -280,6 +289,29 @@ fn triangulate_extrusion(poly: &Vec<crate::Pt2>, h: f32, xf: [[f32; 4]; 4]) -> M
     m
 }
 
+/// Допуск триангуляции B-Rep, мм.
+#[cfg(feature = "cryxtal-brep")]
+pub(crate) const BREP_TOL: f64 = 1.0;
+
+#[cfg(feature = "cryxtal-brep")]
+fn triangulate_brep(solid: &Solid, xf: [[f32; 4]; 4]) -> Mesh {
+    use cryxtal_meshalgo::tessellation::{MeshableShape, MeshedShape};
+    let poly = solid.triangulation(BREP_TOL).to_polygon();
+    let mut m = Mesh {
+        positions: poly
+            .positions()
+            .iter()
+            .map(|p| apply_xform([p.x as f32, p.y as f32, p.z as f32], xf))
+            .collect(),
+        ..Mesh::default()
+    };
+    for t in poly.faces().triangle_iter() {
+        m.indices.extend(t.map(|v| v.pos as u32));
+    }
+    m.normals = vec![[0.0, 1.0, 0.0]; m.positions.len()];
+    m
+}
+
 fn triangulate_tube(path: &Vec<Pt3>, r: f32, sides: u32, xf: [[f32; 4]; 4]) -> Mesh {
     // MVP: набор колец перпендикулярно оси звена, без скруглений в коленах.
     let mut m = Mesh::default();
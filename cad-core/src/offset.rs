@@ -0,0 +1,481 @@
+//! Эквидистанта (смещение) кривых на расстояние со знаком: плюс — вправо по ходу
+//! кривой (наружу у обходимых против часовой контуров, окружностей и дуг), минус — влево.
+//!
+//! Отрезки, дуги и окружности смещаются точно. Полилиния — посегментно, с сопряжением
+//! выпуклых углов (`OffsetJoin`); эллипс и NURBS — кубическим сплайном в пределах допуска
+//! (`BSplineCurve::cubic_approximation`). Затем сырая эквидистанта режется в точках
+//! самопересечения, куски ближе `|dist|` к исходной кривой выбрасываются, остальные
+//! сшиваются.
+
+use crate::edit::{from_homogeneous, homogeneous, reversed};
+use crate::{
+    bulge_arc, curve_at, curve_param, curve_range, cut_params, join_curves, sub_curve, EntityKind,
+    Pt2, TruckCurve2,
+};
+use cryxtal_geometry::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Допуск приближения эквидистанты NURBS и эллипсов по умолчанию.
+pub const OFFSET_TOL: f64 = 1e-3;
+/// Предел длины острого сопряжения (от вершины, в долях расстояния) — дальше срез.
+const MITER_LIMIT: f32 = 4.0;
+
+/// Сопряжение смещённых сегментов полилинии на выпуклом углу.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OffsetJoin {
+    /// Продолжение сегментов до пересечения (срез, если угол слишком острый)
+    #[default]
+    Miter,
+    /// Дуга радиусом `|dist|` вокруг вершины
+    Round,
+    /// Прямой срез
+    Bevel,
+}
+
+/// Эквидистанта кривой. Пусто, если кривая выродилась (дуга радиуса меньше `-dist`);
+/// `None` для не-кривых и при неудаче приближения.
+pub fn offset_curve(
+    kind: &EntityKind,
+    dist: f32,
+    join: OffsetJoin,
+    tol: f64,
+) -> Option<Vec<EntityKind>> {
+    curve_range(kind)?;
+    if dist == 0.0 {
+        return Some(vec![kind.clone()]);
+    }
+    match kind {
+        EntityKind::LineSeg { a, b } => {
+            let Some(n) = right_normal(sub(*b, *a)) else {
+                return Some(vec![]);
+            };
+            let n = scale(n, dist);
+            Some(vec![EntityKind::LineSeg {
+                a: add(*a, n),
+                b: add(*b, n),
+            }])
+        }
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let r = radius + dist;
+            Some(if r > 0.0 {
+                vec![EntityKind::Arc {
+                    center: *center,
+                    radius: r,
+                    start_angle: start_angle.min(*end_angle),
+                    end_angle: start_angle.max(*end_angle),
+                }]
+            } else {
+                vec![]
+            })
+        }
+        EntityKind::Circle { center, radius } => {
+            let r = radius + dist;
+            Some(if r > 0.0 {
+                vec![EntityKind::Circle {
+                    center: *center,
+                    radius: r,
+                }]
+            } else {
+                vec![]
+            })
+        }
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => {
+            let raw = polyline_raw(pts, bulges, *closed, dist, join);
+            Some(
+                join_curves(&clip(kind, raw, dist, tol))
+                    .into_iter()
+                    .map(|(_, k)| k)
+                    .collect(),
+            )
+        }
+        EntityKind::Ellipse { .. } | EntityKind::NurbsCurve2D { .. } => {
+            let raw = nurbs_raw(&kind.to_truck()?, dist as f64, tol)?;
+            Some(chain_nurbs(clip(kind, raw, dist, tol), tol))
+        }
+        _ => None,
+    }
+}
+
+// --------------------------- полилиния ---------------------------
+
+/// Кусок сырой эквидистанты; у сопряжения выпуклого угла — вершина этого угла.
+type RawPiece = (EntityKind, Option<Pt2>);
+
+/// Смещённый сегмент: кусок (нет — дуга выродилась в точку), концы и касательные в них.
+struct OffsetSeg {
+    piece: Option<EntityKind>,
+    p0: Pt2,
+    p1: Pt2,
+    t0: Pt2,
+    t1: Pt2,
+}
+
+fn offset_segment(a: Pt2, b: Pt2, bulge: f32, dist: f32) -> Option<OffsetSeg> {
+    match bulge_arc(a, b, bulge) {
+        None => {
+            let t = unit(sub(b, a))?;
+            let n = scale(right_normal(t)?, dist);
+            let (p0, p1) = (add(a, n), add(b, n));
+            Some(OffsetSeg {
+                piece: Some(EntityKind::LineSeg { a: p0, b: p1 }),
+                p0,
+                p1,
+                t0: t,
+                t1: t,
+            })
+        }
+        Some((c, r, s, e)) => {
+            // справа по ходу у дуги против часовой — наружу
+            let ccw = e >= s;
+            let r = if ccw { r + dist } else { r - dist };
+            let tangent = |a: f32| {
+                let t = Pt2::new(-a.sin(), a.cos());
+                if ccw {
+                    t
+                } else {
+                    scale(t, -1.0)
+                }
+            };
+            let at = |a: f32| Pt2::new(c.x + r.max(0.0) * a.cos(), c.y + r.max(0.0) * a.sin());
+            Some(OffsetSeg {
+                piece: (r > 0.0).then(|| EntityKind::Arc {
+                    center: c,
+                    radius: r,
+                    start_angle: s.min(e),
+                    end_angle: s.max(e),
+                }),
+                p0: at(s),
+                p1: at(e),
+                t0: tangent(s),
+                t1: tangent(e),
+            })
+        }
+    }
+}
+
+/// Сырая эквидистанта полилинии: смещённые сегменты и сопряжения между ними (с вершиной,
+/// которую сопряжение обходит). На вогнутых углах концы соединяются отрезком — он и нахлёст
+/// сегментов уйдут при обрезке.
+fn polyline_raw(
+    pts: &[Pt2],
+    bulges: &[f32],
+    closed: bool,
+    dist: f32,
+    join: OffsetJoin,
+) -> Vec<RawPiece> {
+    let n = pts.len();
+    let count = if closed && n > 2 {
+        n
+    } else {
+        n.saturating_sub(1)
+    };
+    let segs: Vec<OffsetSeg> = (0..count)
+        .filter_map(|i| {
+            let bulge = bulges.get(i).copied().unwrap_or(0.0);
+            offset_segment(pts[i], pts[(i + 1) % n], bulge, dist)
+        })
+        .collect();
+    let mut out = Vec::new();
+    for (i, seg) in segs.iter().enumerate() {
+        out.extend(seg.piece.clone().map(|k| (k, None)));
+        let next = if i + 1 < segs.len() {
+            &segs[i + 1]
+        } else if closed && segs.len() > 1 {
+            &segs[0]
+        } else {
+            continue;
+        };
+        let (pieces, vertex) = corner(seg, next, dist, join);
+        out.extend(pieces.into_iter().map(|k| (k, vertex)));
+    }
+    out
+}
+
+/// Сопряжение конца сегмента `a` с началом `b`; для выпуклого угла — и его вершина.
+fn corner(
+    a: &OffsetSeg,
+    b: &OffsetSeg,
+    dist: f32,
+    join: OffsetJoin,
+) -> (Vec<EntityKind>, Option<Pt2>) {
+    let (p, q) = (a.p1, b.p0);
+    if len(sub(q, p)) <= 1e-6 * dist.abs() {
+        return (vec![], None);
+    }
+    let bevel = vec![EntityKind::LineSeg { a: p, b: q }];
+    // выпуклый (со стороны смещения) угол — поворот в обратную смещению сторону
+    let turn = cross(a.t1, b.t0);
+    if turn * dist <= 0.0 {
+        return (bevel, None);
+    }
+    // вершина исходной полилинии — на расстоянии |dist| от обоих концов по нормалям
+    let v = sub(
+        p,
+        scale(right_normal(a.t1).unwrap_or(Pt2::new(0.0, 0.0)), dist),
+    );
+    let pieces = match join {
+        OffsetJoin::Bevel => bevel,
+        OffsetJoin::Round => {
+            let (ap, aq) = (angle(sub(p, v)), angle(sub(q, v)));
+            let mut d = aq - ap;
+            while d > std::f32::consts::PI {
+                d -= std::f32::consts::TAU;
+            }
+            while d < -std::f32::consts::PI {
+                d += std::f32::consts::TAU;
+            }
+            vec![EntityKind::Arc {
+                center: v,
+                radius: dist.abs(),
+                start_angle: ap.min(ap + d),
+                end_angle: ap.max(ap + d),
+            }]
+        }
+        OffsetJoin::Miter => {
+            // p + s·t1 = q − u·t0
+            let den = cross(a.t1, b.t0);
+            let s = cross(sub(q, p), b.t0) / den;
+            let m = add(p, scale(a.t1, s));
+            if !s.is_finite() || s < 0.0 || len(sub(m, v)) > MITER_LIMIT * dist.abs() {
+                bevel
+            } else {
+                vec![
+                    EntityKind::LineSeg { a: p, b: m },
+                    EntityKind::LineSeg { a: m, b: q },
+                ]
+            }
+        }
+    };
+    (pieces, Some(v))
+}
+
+// --------------------------- эллипс и NURBS ---------------------------
+
+/// Эквидистанта truck-кривой как параметрическая кривая: C(t) + d·n(t),
+/// n — правая единичная нормаль.
+#[derive(Clone)]
+struct OffsetCurve<'a> {
+    base: &'a TruckCurve2,
+    dist: f64,
+}
+
+impl OffsetCurve<'_> {
+    fn step(&self) -> f64 {
+        let (t0, t1) = self.base.range_tuple();
+        1e-6 * (t1 - t0).max(1e-9)
+    }
+}
+
+impl ParametricCurve for OffsetCurve<'_> {
+    type Point = Point2;
+    type Vector = Vector2;
+    fn subs(&self, t: f64) -> Point2 {
+        let d = self.base.der(t);
+        let m = d.magnitude().max(1e-300);
+        self.base.subs(t) + Vector2::new(d.y, -d.x) * (self.dist / m)
+    }
+    fn der(&self, t: f64) -> Vector2 {
+        // n = R(C')/|C'|,  n' = R(C'')/|C'| − R(C')·(C'·C'')/|C'|³,  R(x, y) = (y, −x)
+        let (d1, d2) = (self.base.der(t), self.base.der2(t));
+        let m = d1.magnitude().max(1e-300);
+        let dn =
+            Vector2::new(d2.y, -d2.x) / m - Vector2::new(d1.y, -d1.x) * (d1.dot(d2) / (m * m * m));
+        d1 + dn * self.dist
+    }
+    fn der2(&self, t: f64) -> Vector2 {
+        let h = self.step();
+        (self.der(t + h) - self.der(t - h)) / (2.0 * h)
+    }
+    fn der_n(&self, n: usize, t: f64) -> Vector2 {
+        match n {
+            0 => self.subs(t).to_vec(),
+            1 => self.der(t),
+            2 => self.der2(t),
+            _ => {
+                let h = self.step();
+                (self.der_n(n - 1, t + h) - self.der_n(n - 1, t - h)) / (2.0 * h)
+            }
+        }
+    }
+    fn parameter_range(&self) -> ParameterRange {
+        self.base.parameter_range()
+    }
+}
+
+/// Сырая эквидистанта: кубические приближения по пролётам исходной кривой, разложенные
+/// на куски Безье.
+fn nurbs_raw(base: &TruckCurve2, dist: f64, tol: f64) -> Option<Vec<RawPiece>> {
+    let oc = OffsetCurve { base, dist };
+    let knots: &[f64] = match base {
+        TruckCurve2::BSpline(c) => c.knot_vec(),
+        TruckCurve2::Nurbs(c) => c.knot_vec(),
+    };
+    let mut out = Vec::new();
+    for w in knots.windows(2).filter(|w| w[1] - w[0] > 1e-12) {
+        let (lo, hi) = (w[0], w[1]);
+        // допуск на производную — от её масштаба на пролёте
+        let speed = (0..=8)
+            .map(|i| oc.der(lo + (hi - lo) * i as f64 / 8.0).magnitude())
+            .fold(0.0, f64::max);
+        let approx = BSplineCurve::cubic_approximation(&oc, (lo, hi), tol, 0.05 * speed + tol, 24)
+            .or_else(|| BSplineCurve::cubic_approximation(&oc, (lo, hi), tol, f64::INFINITY, 24))?;
+        out.extend(
+            approx
+                .bezier_decomposition()
+                .iter()
+                .map(|c| (nurbs_kind(c), None)),
+        );
+    }
+    Some(out)
+}
+
+fn nurbs_kind(c: &BSplineCurve<Point2>) -> EntityKind {
+    EntityKind::NurbsCurve2D {
+        degree: c.degree(),
+        knots: c.knot_vec().to_vec(),
+        ctrl_pts: c.control_points().iter().map(|p| Pt2::from(*p)).collect(),
+        weights: None,
+    }
+}
+
+/// Сшить куски NURBS по совпадающим концам в кривые.
+fn chain_nurbs(pieces: Vec<EntityKind>, tol: f64) -> Vec<EntityKind> {
+    let mut curves: Vec<Option<BSplineCurve<Vector3>>> = pieces
+        .iter()
+        .map(|k| homogeneous(k).map(|(h, _)| h))
+        .collect();
+    let ends = |c: &BSplineCurve<Vector3>| {
+        let (t0, t1) = c.range_tuple();
+        (c.subs(t0), c.subs(t1))
+    };
+    let near = |a: Vector3, b: Vector3| (a.x / a.z - b.x / b.z).hypot(a.y / a.z - b.y / b.z) <= tol;
+    let mut out = Vec::new();
+    for first in 0..curves.len() {
+        let Some(mut chain) = curves[first].take() else {
+            continue;
+        };
+        loop {
+            let (_, tail) = ends(&chain);
+            let next = curves.iter().position(|c| {
+                c.as_ref().is_some_and(|c| {
+                    let (s, e) = ends(c);
+                    near(tail, s) || near(tail, e)
+                })
+            });
+            let Some(i) = next else {
+                break;
+            };
+            let mut c = curves[i].take().unwrap();
+            if !near(tail, ends(&c).0) {
+                c = reversed(&c);
+            }
+            // параметр и первая точка продолжения — ровно там, где кончилась цепочка
+            let (c0, _) = c.range_tuple();
+            c.knot_translate(chain.range_tuple().1 - c0);
+            let last = *chain.control_points().last().unwrap();
+            if let Some(p) = c.control_points_mut().next() {
+                *p = last;
+            }
+            match chain.try_concat(&c) {
+                Ok(joined) => chain = joined,
+                Err(_) => {
+                    out.push(chain);
+                    chain = c;
+                }
+            }
+        }
+        out.push(chain);
+    }
+    out.into_iter()
+        .map(|mut c| {
+            c.optimize();
+            from_homogeneous(&c, false)
+        })
+        .collect()
+}
+
+// --------------------------- обрезка самопересечений ---------------------------
+
+/// Разрезать куски сырой эквидистанты друг о друга и оставить лишь те, что не ближе
+/// `|dist|` к исходной кривой. Сопряжение угла ближе к своей вершине (срез) — не в счёт.
+fn clip(orig: &EntityKind, raw: Vec<RawPiece>, dist: f32, tol: f64) -> Vec<EntityKind> {
+    let keep_from = dist.abs() as f64 * (1.0 - 1e-4) - tol;
+    let mut out = Vec::new();
+    for (i, (piece, vertex)) in raw.iter().enumerate() {
+        let Some((r0, r1)) = curve_range(piece) else {
+            continue;
+        };
+        let others: Vec<EntityKind> = raw
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, (k, _))| k.clone())
+            .collect();
+        let eps = 1e-7 * (r1 - r0).max(1.0);
+        let mut ts = vec![r0];
+        ts.extend(
+            cut_params(piece, &others)
+                .into_iter()
+                .filter(|t| *t > r0 + eps && *t < r1 - eps),
+        );
+        ts.push(r1);
+        for w in ts.windows(2) {
+            let Some(mid) = curve_at(piece, (w[0] + w[1]) * 0.5) else {
+                continue;
+            };
+            let Some(foot) = curve_param(orig, mid).and_then(|t| curve_at(orig, t)) else {
+                continue;
+            };
+            let at_vertex = vertex.is_some_and(|v| len(sub(foot, v)) as f64 <= tol);
+            if at_vertex || len(sub(mid, foot)) as f64 >= keep_from {
+                out.extend(sub_curve(piece, w[0], w[1]));
+            }
+        }
+    }
+    out
+}
+
+// --------------------------- мелочи ---------------------------
+
+fn add(a: Pt2, b: Pt2) -> Pt2 {
+    Pt2::new(a.x + b.x, a.y + b.y)
+}
+
+fn sub(a: Pt2, b: Pt2) -> Pt2 {
+    Pt2::new(a.x - b.x, a.y - b.y)
+}
+
+fn scale(a: Pt2, k: f32) -> Pt2 {
+    Pt2::new(a.x * k, a.y * k)
+}
+
+fn cross(a: Pt2, b: Pt2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn len(a: Pt2) -> f32 {
+    a.x.hypot(a.y)
+}
+
+fn angle(a: Pt2) -> f32 {
+    a.y.atan2(a.x)
+}
+
+fn unit(a: Pt2) -> Option<Pt2> {
+    let l = len(a);
+    (l > 0.0).then(|| scale(a, 1.0 / l))
+}
+
+/// Единичная нормаль вправо от направления `d`.
+fn right_normal(d: Pt2) -> Option<Pt2> {
+    unit(Pt2::new(d.y, -d.x))
+}
@@ -1,5 +1,10 @@
 // cad-core/src/ops.rs
-use crate::{Document, Entity, EntityKind, Pt2};
+use crate::{
+    corner_curves, corner_polyline, curve_range, detect_boundary, extend_curve, join_curves,
+    offset_curve, relink_dimensions, split_curve, trim_curve, Affine2, Attrib, CornerJoint,
+    DimKind, DimLink, DimPoint, Document, Entity, EntityKind, HatchFill, HatchLoop, OffsetJoin,
+    Pt2, OFFSET_TOL,
+};
 use anyhow::{anyhow, Result};
 
 // math + truck
@@ -18,11 +23,7 @@ pub fn snap_to_grid(p: Pt2, step: f32) -> Pt2 {
 }
 
 pub fn make_line(doc: &mut Document, a: Pt2, b: Pt2, layer: &str) -> u64 {
-    doc.add_entity(Entity {
-        id: 0,
-        layer: layer.into(),
-        kind: EntityKind::LineSeg { a, b },
-    })
+    doc.add_entity(Entity::new(layer, EntityKind::LineSeg { a, b }))
 }
 
 pub fn make_arc(
@@ -33,27 +34,55 @@ pub fn make_arc(
     end: f32,
     layer: &str,
 ) -> u64 {
-    doc.add_entity(Entity {
-        id: 0,
-        layer: layer.into(),
-        kind: EntityKind::Arc {
+    doc.add_entity(Entity::new(
+        layer,
+        EntityKind::Arc {
             center,
             radius,
             start_angle: start,
             end_angle: end,
         },
-    })
+    ))
+}
+
+pub fn make_circle(doc: &mut Document, center: Pt2, radius: f32, layer: &str) -> u64 {
+    doc.add_entity(Entity::new(layer, EntityKind::Circle { center, radius }))
+}
+
+/// Эллипс/эллиптическая дуга: `major` — вектор большой полуоси, параметры в радианах.
+pub fn make_ellipse(
+    doc: &mut Document,
+    center: Pt2,
+    major: Pt2,
+    ratio: f32,
+    start_param: f32,
+    end_param: f32,
+    layer: &str,
+) -> u64 {
+    doc.add_entity(Entity::new(
+        layer,
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            start_param,
+            end_param,
+        },
+    ))
 }
 
 pub fn make_polyline(doc: &mut Document, pts: Vec<Pt2>, closed: bool, layer: &str) -> Result<u64> {
     if pts.len() < 2 {
         return Err(anyhow!("Polyline requires at least 2 points"));
     }
-    Ok(doc.add_entity(Entity {
-        id: 0,
-        layer: layer.into(),
-        kind: EntityKind::Polyline { pts, closed },
-    }))
+    Ok(doc.add_entity(Entity::new(
+        layer,
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges: vec![],
+        },
+    )))
 }
 
 /// Создать открытый равномерный B-сплайн (веса опциональны; при рендере пока игнорируются)
@@ -87,16 +116,15 @@ pub fn make_nurbs_open_uniform(
     }
     knots.extend(std::iter::repeat(1.0).take(degree + 1));
 
-    Ok(doc.add_entity(Entity {
-        id: 0,
-        layer: layer.into(),
-        kind: EntityKind::NurbsCurve2D {
+    Ok(doc.add_entity(Entity::new(
+        layer,
+        EntityKind::NurbsCurve2D {
             degree,
             knots,
             ctrl_pts,
             weights,
         },
-    }))
+    )))
 }
 
 pub fn nurbs_from_polyline(doc: &mut Document, poly: &[Pt2], layer: &str) -> Result<u64> {
@@ -106,6 +134,88 @@ pub fn nurbs_from_polyline(doc: &mut Document, poly: &[Pt2], layer: &str) -> Res
     make_nurbs_open_uniform(doc, 2, poly.to_vec(), None, layer)
 }
 
+/// Вставить блок `block`; атрибуты заполняются значениями по умолчанию из ATTDEF.
+pub fn make_insert(
+    doc: &mut Document,
+    block: &str,
+    pos: Pt2,
+    scale: Pt2,
+    rotation: f32,
+    layer: &str,
+) -> Result<u64> {
+    let def = doc
+        .block(block)
+        .ok_or_else(|| anyhow!("Block '{block}' is not defined"))?;
+    let tr = Affine2::insert(pos, scale, rotation, def.base);
+    let attribs = def
+        .attdefs
+        .iter()
+        .map(|a| Attrib {
+            pos: tr.apply(a.pos),
+            height: a.height * tr.mean_scale(),
+            ..a.clone()
+        })
+        .collect();
+    Ok(doc.add_entity(Entity::new(
+        layer,
+        EntityKind::Insert {
+            block: block.into(),
+            pos,
+            scale,
+            rotation,
+            attribs,
+        },
+    )))
+}
+
+/// Штриховка по готовым контурам (первый — внешний, остальные — острова).
+pub fn make_hatch(
+    doc: &mut Document,
+    loops: Vec<HatchLoop>,
+    fill: HatchFill,
+    layer: &str,
+) -> Result<u64> {
+    if loops.first().is_none_or(|l| l.polygon().len() < 3) {
+        return Err(anyhow!("Hatch requires a closed outer boundary"));
+    }
+    Ok(doc.add_entity(Entity::new(layer, EntityKind::Hatch { loops, fill })))
+}
+
+/// Штриховка области вокруг точки `p`: контур ищется по геометрии документа.
+pub fn hatch_at_point(doc: &mut Document, p: Pt2, fill: HatchFill, layer: &str) -> Result<u64> {
+    let loops =
+        detect_boundary(doc, p).ok_or_else(|| anyhow!("No closed boundary around the point"))?;
+    make_hatch(doc, loops, fill, layer)
+}
+
+/// Размер по определяющим точкам (их число — `DimKind::point_count`) и положению
+/// размерной линии; стиль — текущий "Standard".
+pub fn make_dimension(
+    doc: &mut Document,
+    kind: DimKind,
+    pts: Vec<DimPoint>,
+    line_pos: Pt2,
+    layer: &str,
+) -> Result<u64> {
+    if pts.len() != kind.point_count() {
+        return Err(anyhow!(
+            "{kind:?} dimension requires {} points, got {}",
+            kind.point_count(),
+            pts.len()
+        ));
+    }
+    Ok(doc.add_entity(Entity::new(
+        layer,
+        EntityKind::Dimension {
+            kind,
+            pts,
+            line_pos,
+            text: String::new(),
+            style: String::new(),
+        },
+    )))
+}
+
 /// Утилита для добавления текста (опционально)
 pub fn make_text(
     doc: &mut Document,
@@ -114,15 +224,14 @@ pub fn make_text(
     height: f32,
     layer: &str,
 ) -> u64 {
-    doc.add_entity(Entity {
-        id: 0,
-        layer: layer.into(),
-        kind: EntityKind::Text {
+    doc.add_entity(Entity::new(
+        layer,
+        EntityKind::Text {
             pos,
             content: content.into(),
             height,
         },
-    })
+    ))
 }
 
 /// Семплируем NURBS/BSpline из Entity в полилинию (веса пока игнорируем).
@@ -173,7 +282,9 @@ pub fn translate_entity(ent: &mut Entity, dx: f32, dy: f32) {
             shift(a, dx, dy);
             shift(b, dx, dy);
         }
-        EntityKind::Arc { center, .. } => {
+        EntityKind::Arc { center, .. }
+        | EntityKind::Circle { center, .. }
+        | EntityKind::Ellipse { center, .. } => {
             shift(center, dx, dy);
         }
         EntityKind::Polyline { pts, .. } => {
@@ -189,5 +300,302 @@ pub fn translate_entity(ent: &mut Entity, dx: f32, dy: f32) {
         EntityKind::Text { pos, .. } => {
             shift(pos, dx, dy);
         } // ← добавлено
+        EntityKind::Insert { pos, attribs, .. } => {
+            shift(pos, dx, dy);
+            for a in attribs {
+                shift(&mut a.pos, dx, dy);
+            }
+        }
+        EntityKind::Hatch { .. } => {
+            ent.kind = ent.kind.transformed(&Affine2::translation(dx, dy));
+        }
+        EntityKind::Dimension { pts, line_pos, .. } => {
+            for p in pts {
+                shift(&mut p.pos, dx, dy);
+            }
+            shift(line_pos, dx, dy);
+        }
+        EntityKind::Table { pos, .. } => {
+            shift(pos, dx, dy);
+        }
+    }
+}
+
+/// Сдвинуть сущности `ids` и обновить размеры, привязанные к ним. Привязанные точки
+/// размера, сдвинутого без своих объектов, остаются на объектах.
+pub fn move_entities(doc: &mut Document, ids: &[u64], dx: f32, dy: f32) {
+    let mut moved_dims = Vec::new();
+    for &id in ids {
+        let Some(e) = doc.entity_mut(id) else {
+            continue;
+        };
+        translate_entity(e, dx, dy);
+        if let EntityKind::Dimension { .. } = e.kind {
+            moved_dims.push(id);
+        }
+    }
+    relink_dimensions(doc, Some(ids), &moved_dims);
+}
+
+/// Применить `tr` к сущностям `ids` на месте (дуги при неравномерном масштабе
+/// становятся эллиптическими, у NURBS преобразуются управляющие точки). Размеры
+/// пересчитываются как при [`move_entities`].
+pub fn transform_entities(doc: &mut Document, ids: &[u64], tr: &Affine2) {
+    let mut moved_dims = Vec::new();
+    for &id in ids {
+        let Some(e) = doc.entity_mut(id) else {
+            continue;
+        };
+        e.kind = e.kind.transformed(tr);
+        if let EntityKind::Dimension { .. } = e.kind {
+            moved_dims.push(id);
+        }
+    }
+    relink_dimensions(doc, Some(ids), &moved_dims);
+}
+
+/// Повернуть сущности на `angle` радиан вокруг `center`.
+pub fn rotate_entities(doc: &mut Document, ids: &[u64], center: Pt2, angle: f32) {
+    transform_entities(doc, ids, &Affine2::rotation(center, angle));
+}
+
+/// Масштабировать сущности относительно `center`; разные `sx`, `sy` — растяжение.
+pub fn scale_entities(doc: &mut Document, ids: &[u64], center: Pt2, sx: f32, sy: f32) {
+    transform_entities(doc, ids, &Affine2::scaling(center, sx, sy));
+}
+
+/// Отразить сущности относительно прямой `a`–`b`. С `keep_source` исходные остаются,
+/// а отражаются копии. Возвращает id отражённых.
+pub fn mirror_entities(
+    doc: &mut Document,
+    ids: &[u64],
+    a: Pt2,
+    b: Pt2,
+    keep_source: bool,
+) -> Vec<u64> {
+    let tr = Affine2::mirror(a, b);
+    if keep_source {
+        return copy_entities(doc, ids, &tr);
+    }
+    transform_entities(doc, ids, &tr);
+    ids.iter()
+        .copied()
+        .filter(|&id| doc.entity(id).is_some())
+        .collect()
+}
+
+/// Копии сущностей `ids`, преобразованные `tr`, в порядке `ids`. Размеры копии
+/// привязываются к копиям своих объектов; привязки к объектам вне набора снимаются.
+/// Возвращает id копий.
+pub fn copy_entities(doc: &mut Document, ids: &[u64], tr: &Affine2) -> Vec<u64> {
+    let src: Vec<Entity> = ids
+        .iter()
+        .filter_map(|&id| doc.entity(id).cloned())
+        .collect();
+    let new_ids: Vec<u64> = src
+        .iter()
+        .map(|e| {
+            doc.add_entity(Entity {
+                kind: e.kind.transformed(tr),
+                ..e.clone()
+            })
+        })
+        .collect();
+    let remap: std::collections::HashMap<u64, u64> = src
+        .iter()
+        .map(|e| e.id)
+        .zip(new_ids.iter().copied())
+        .collect();
+    for &id in &new_ids {
+        let Some(EntityKind::Dimension { pts, .. }) = doc.entity(id).map(|e| &e.kind) else {
+            continue;
+        };
+        if pts.iter().all(|p| p.link.is_none()) {
+            continue;
+        }
+        if let Some(EntityKind::Dimension { pts, .. }) = doc.entity_mut(id).map(|e| &mut e.kind) {
+            for p in pts {
+                p.link = p.link.and_then(|l| {
+                    Some(DimLink {
+                        entity: *remap.get(&l.entity)?,
+                        ..l
+                    })
+                });
+            }
+        }
+    }
+    new_ids
+}
+
+/// Геометрия границ для обрезки/продления: пустой список — все прочие сущности;
+/// вставки разворачиваются до примитивов.
+fn boundary_kinds(doc: &Document, id: u64, boundaries: &[u64]) -> Vec<EntityKind> {
+    doc.entities
+        .iter()
+        .filter(|e| {
+            if boundaries.is_empty() {
+                e.id != id
+            } else {
+                boundaries.contains(&e.id)
+            }
+        })
+        .flat_map(|e| {
+            if e.kind.is_block_ref() {
+                doc.insert_geometry(e).into_iter().map(|e| e.kind).collect()
+            } else {
+                vec![e.kind.clone()]
+            }
+        })
+        .collect()
+}
+
+/// Заменить сущность `id` кусками: первый сохраняет id, остальные добавляются с теми же
+/// свойствами; без кусков сущность удаляется. Возвращает id кусков.
+fn replace_with_pieces(doc: &mut Document, id: u64, pieces: Vec<EntityKind>) -> Vec<u64> {
+    let Some(proto) = doc.entity(id).cloned() else {
+        return vec![];
+    };
+    let mut pieces = pieces.into_iter();
+    let Some(first) = pieces.next() else {
+        doc.remove_entity(id);
+        relink_dimensions(doc, Some(&[id]), &[]);
+        return vec![];
+    };
+    if let Some(e) = doc.entity_mut(id) {
+        e.kind = first;
+    }
+    let mut ids = vec![id];
+    for kind in pieces {
+        ids.push(doc.add_entity(Entity {
+            kind,
+            ..proto.clone()
+        }));
+    }
+    relink_dimensions(doc, Some(&[id]), &[]);
+    ids
+}
+
+fn curve_kind(doc: &Document, id: u64) -> Result<EntityKind> {
+    let ent = doc
+        .entity(id)
+        .ok_or_else(|| anyhow!("Entity {id} not found"))?;
+    curve_range(&ent.kind).ok_or_else(|| anyhow!("Entity {id} is not a curve"))?;
+    Ok(ent.kind.clone())
+}
+
+/// Обрезать кривую `id` по границам `boundaries` (пусто — все прочие объекты):
+/// удаляется участок между ближайшими пересечениями вокруг `pick`.
+/// Возвращает id оставшихся кусков (первый — прежний id).
+pub fn trim(doc: &mut Document, id: u64, boundaries: &[u64], pick: Pt2) -> Result<Vec<u64>> {
+    let kind = curve_kind(doc, id)?;
+    let cutters = boundary_kinds(doc, id, boundaries);
+    let pieces =
+        trim_curve(&kind, &cutters, pick).ok_or_else(|| anyhow!("No intersections to trim at"))?;
+    Ok(replace_with_pieces(doc, id, pieces))
+}
+
+/// Продлить ближайший к `pick` конец кривой `id` до первой из границ `boundaries`
+/// (пусто — все прочие объекты).
+pub fn extend(doc: &mut Document, id: u64, boundaries: &[u64], pick: Pt2) -> Result<()> {
+    let kind = curve_kind(doc, id)?;
+    let bounds = boundary_kinds(doc, id, boundaries);
+    let ext = extend_curve(&kind, &bounds, pick)
+        .ok_or_else(|| anyhow!("No boundary to extend entity {id} to"))?;
+    replace_with_pieces(doc, id, vec![ext]);
+    Ok(())
+}
+
+/// Разбить кривую `id` в ближайшей к `p` точке. Замкнутая полилиния или NURBS
+/// размыкается в этой точке; окружность и полный эллипс разбить одной точкой нельзя.
+pub fn break_at_point(doc: &mut Document, id: u64, p: Pt2) -> Result<Vec<u64>> {
+    let kind = curve_kind(doc, id)?;
+    let pieces = split_curve(&kind, p)
+        .ok_or_else(|| anyhow!("Cannot break entity {id} at ({}, {})", p.x, p.y))?;
+    Ok(replace_with_pieces(doc, id, pieces))
+}
+
+/// Сшить касающиеся концами отрезки, дуги и открытые полилинии из `ids`. Каждая цепочка
+/// становится одной сущностью со свойствами первого куска. Возвращает id результатов.
+pub fn join(doc: &mut Document, ids: &[u64]) -> Result<Vec<u64>> {
+    let ents: Vec<Entity> = doc
+        .entities
+        .iter()
+        .filter(|e| ids.contains(&e.id))
+        .cloned()
+        .collect();
+    let kinds: Vec<EntityKind> = ents.iter().map(|e| e.kind.clone()).collect();
+    let chains = join_curves(&kinds);
+    if chains.is_empty() {
+        return Err(anyhow!("Nothing to join"));
+    }
+    let mut out = Vec::new();
+    for (used, kind) in chains {
+        let keep = ents[used[0]].id;
+        for &i in &used[1..] {
+            doc.remove_entity(ents[i].id);
+        }
+        out.extend(replace_with_pieces(doc, keep, vec![kind]));
+    }
+    Ok(out)
+}
+
+/// Эквидистанта сущности `id` на `dist` (плюс — вправо по ходу кривой, наружу у
+/// окружностей и дуг) с теми же свойствами. Возвращает id новых сущностей.
+pub fn offset(doc: &mut Document, id: u64, dist: f32, join: OffsetJoin) -> Result<Vec<u64>> {
+    let ent = doc
+        .entity(id)
+        .ok_or_else(|| anyhow!("Entity {id} not found"))?
+        .clone();
+    let pieces = offset_curve(&ent.kind, dist, join, OFFSET_TOL)
+        .ok_or_else(|| anyhow!("Entity {id} cannot be offset"))?;
+    if pieces.is_empty() {
+        return Err(anyhow!("Offset of entity {id} by {dist} collapses"));
+    }
+    Ok(pieces
+        .into_iter()
+        .map(|kind| {
+            doc.add_entity(Entity {
+                kind,
+                ..ent.clone()
+            })
+        })
+        .collect())
+}
+
+/// Скруглить угол между кривыми `id_a` и `id_b` (отрезки, дуги, открытые полилинии) дугой
+/// радиуса `radius`: обе обрезаются или продлеваются до точек касания. Одна и та же
+/// полилиния дважды — скругляются все её вершины. Возвращает id вставленной дуги
+/// (нет при нулевом радиусе и для вершин полилинии).
+pub fn fillet(doc: &mut Document, id_a: u64, id_b: u64, radius: f32) -> Result<Option<u64>> {
+    corner(doc, id_a, id_b, CornerJoint::Fillet(radius))
+}
+
+/// Срезать угол между кривыми `id_a` и `id_b` отрезком на расстояниях `d1` и `d2` от угла
+/// вдоль каждой. Одна и та же полилиния дважды — срезаются все её вершины. Возвращает id
+/// отрезка фаски.
+pub fn chamfer(doc: &mut Document, id_a: u64, id_b: u64, d1: f32, d2: f32) -> Result<Option<u64>> {
+    corner(doc, id_a, id_b, CornerJoint::Chamfer(d1, d2))
+}
+
+fn corner(doc: &mut Document, id_a: u64, id_b: u64, joint: CornerJoint) -> Result<Option<u64>> {
+    let a = curve_kind(doc, id_a)?;
+    if id_a == id_b {
+        let kind = corner_polyline(&a, joint)
+            .ok_or_else(|| anyhow!("No vertex of entity {id_a} can take the corner"))?;
+        replace_with_pieces(doc, id_a, vec![kind]);
+        return Ok(None);
     }
+    let b = curve_kind(doc, id_b)?;
+    let cut = corner_curves(&a, &b, joint).ok_or_else(|| {
+        anyhow!("Entities {id_a} and {id_b} do not form a corner that fits {joint:?}")
+    })?;
+    let proto = doc
+        .entity(id_a)
+        .cloned()
+        .ok_or_else(|| anyhow!("Entity {id_a} not found"))?;
+    replace_with_pieces(doc, id_a, vec![cut.a]);
+    replace_with_pieces(doc, id_b, vec![cut.b]);
+    Ok(cut
+        .joint
+        .map(|kind| doc.add_entity(Entity { kind, ..proto })))
 }
@@ -0,0 +1,670 @@
+//! Экспорт в PDF без внешних библиотек: чертёж в масштабе на одной странице и
+//! листы — по странице на лист. Кривые идут векторными путями (дуги и эллипсы —
+//! кривыми Безье), веса линий — в мм бумаги, типы линий — штриховым шаблоном PDF.
+//! Текст набирается встроенным шрифтом DejaVu Sans (кириллица, ⌀, № и пр.):
+//! в файл попадает подмножество использованных глифов (`/FontFile2`), коды строк —
+//! номера глифов (Identity-H), а `/ToUnicode` возвращает тексту поиск и копирование.
+
+use crate::hatch::curve_points;
+use crate::model3d::Project3D;
+use crate::ttf::TrueType;
+use crate::{
+    bulge_arc, entity_bounds, pattern_segments, render_sheets, Affine2, Bbox, Color, Document,
+    Entity, EntityKind, HatchFill, Pt2, Sheet,
+};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::fmt::Write as _;
+use std::sync::OnceLock;
+
+/// Пунктов в миллиметре.
+const PT_PER_MM: f32 = 72.0 / 25.4;
+/// Поле вокруг чертежа на странице документа, мм.
+const MARGIN_MM: f32 = 10.0;
+/// Встроенный шрифт: DejaVu Sans, урезанный `fonts/subset.py` до латиницы, греческого,
+/// кириллицы и знаков (лицензия — `fonts/LICENSE-DejaVu.txt`).
+static FONT_DATA: &[u8] = include_bytes!("../fonts/DejaVuSans-CAD.ttf");
+const FONT_NAME: &str = "DejaVuSans";
+/// Предел вложенности блоков при развёртке вставок.
+const MAX_BLOCK_DEPTH: usize = 16;
+/// Наибольшая сторона страницы в единицах пользователя (200 дюймов); страница
+/// крупнее задаётся в укрупнённых единицах `/UserUnit`.
+const MAX_PAGE_PT: f32 = 14_400.0;
+/// Пустой документ печатается на A4.
+const A4_MM: (f32, f32) = (210.0, 297.0);
+
+/// Чертёж на одной странице в масштабе 1:`scale` (единицы чертежа — мм). Страница
+/// охватывает видимые сущности с полем 10 мм.
+pub fn document_pdf(doc: &Document, scale: f32) -> Vec<u8> {
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let bounds = doc
+        .entities
+        .iter()
+        .filter(|e| doc.is_layer_visible(&e.layer))
+        .filter_map(|e| entity_bounds(doc, e))
+        .reduce(|a, b| a.union(&b));
+    let mut pdf = PdfWriter::new();
+    match bounds {
+        Some(Bbox { min, max }) => {
+            let k = 1.0 / scale;
+            let size = (
+                (max.x - min.x) * k + 2.0 * MARGIN_MM,
+                (max.y - min.y) * k + 2.0 * MARGIN_MM,
+            );
+            let tr = Affine2::scaling(Pt2::new(0.0, 0.0), k, k).then(&Affine2::translation(
+                MARGIN_MM - min.x * k,
+                MARGIN_MM - min.y * k,
+            ));
+            pdf.add_page(doc, size, &tr);
+        }
+        None => pdf.add_page(doc, A4_MM, &Affine2::IDENTITY),
+    }
+    pdf.finish()
+}
+
+/// Листы, по странице на каждый, размером `size_mm`. Блоки и типы линий — из `model`,
+/// виды — проекции `project`.
+pub fn sheets_pdf(model: &Document, project: &Project3D, sheets: &[Sheet]) -> Vec<u8> {
+    let mut pdf = PdfWriter::new();
+    for s in sheets {
+        pdf.add_page(
+            &s.to_document(model, project),
+            s.size_mm,
+            &Affine2::IDENTITY,
+        );
+    }
+    pdf.finish()
+}
+
+/// Комплект листов в оформлении ([`render_sheets`]): рамка и основная надпись
+/// на каждой странице.
+pub fn sheet_set_pdf(model: &Document, project: &Project3D, sheets: &[Sheet]) -> Vec<u8> {
+    let mut pdf = PdfWriter::new();
+    for (doc, s) in render_sheets(model, project, sheets).iter().zip(sheets) {
+        pdf.add_page(doc, s.size_mm, &Affine2::IDENTITY);
+    }
+    pdf.finish()
+}
+
+/// [`document_pdf`] в файл.
+pub fn export_pdf(doc: &Document, path: &str, scale: f32) -> Result<()> {
+    std::fs::write(path, document_pdf(doc, scale))?;
+    Ok(())
+}
+
+/// [`sheets_pdf`] в файл.
+pub fn export_sheets_pdf(
+    model: &Document,
+    project: &Project3D,
+    sheets: &[Sheet],
+    path: &str,
+) -> Result<()> {
+    std::fs::write(path, sheets_pdf(model, project, sheets))?;
+    Ok(())
+}
+
+/// [`sheet_set_pdf`] в файл.
+pub fn export_sheet_set_pdf(
+    model: &Document,
+    project: &Project3D,
+    sheets: &[Sheet],
+    path: &str,
+) -> Result<()> {
+    std::fs::write(path, sheet_set_pdf(model, project, sheets))?;
+    Ok(())
+}
+
+fn font() -> &'static TrueType<'static> {
+    static FONT: OnceLock<TrueType<'static>> = OnceLock::new();
+    FONT.get_or_init(|| TrueType::parse(FONT_DATA).expect("встроенный шрифт"))
+}
+
+/// Высота прописных в долях кегля (по глифу «H»): высота текста в CAD — по прописным.
+fn cap_height() -> f32 {
+    let f = font();
+    let h = f.glyph('H').map_or(0, |g| f.y_max(g));
+    if h > 0 {
+        h as f32 / f.units_per_em as f32
+    } else {
+        0.73
+    }
+}
+
+/// Многостраничный PDF: каждая страница — документ, пересчитанный в мм страницы.
+#[derive(Debug, Default)]
+pub struct PdfWriter {
+    /// Размер страницы в мм, её `/UserUnit` и поток команд
+    pages: Vec<((f32, f32), f32, String)>,
+    glyphs: Glyphs,
+}
+
+/// Использованные глифы шрифта: CID `i + 1` — глиф `used[i]` и первый набранный им
+/// символ; CID 0 — `.notdef`.
+#[derive(Debug, Default)]
+struct Glyphs {
+    used: Vec<(u16, char)>,
+    cids: HashMap<u16, u16>,
+}
+
+impl Glyphs {
+    /// Строка в кодах CID шестнадцатеричной строкой PDF; символы, которых нет
+    /// в шрифте, — «?».
+    fn encode(&mut self, s: &str) -> String {
+        let f = font();
+        let mut out = String::from("<");
+        for ch in s.chars() {
+            let (gid, ch) = match f.glyph(ch) {
+                Some(g) => (g, ch),
+                None => (f.glyph('?').unwrap_or(0), '?'),
+            };
+            let cid = *self.cids.entry(gid).or_insert_with(|| {
+                self.used.push((gid, ch));
+                self.used.len() as u16
+            });
+            let _ = write!(out, "{cid:04X}");
+        }
+        out.push('>');
+        out
+    }
+
+    /// Метка подмножества из шести прописных букв — своя у каждого набора глифов.
+    fn tag(&self) -> String {
+        let mut h = self
+            .used
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325u64, |h, (g, _)| {
+                (h ^ *g as u64).wrapping_mul(0x0100_0000_01b3)
+            });
+        (0..6)
+            .map(|_| {
+                let c = (b'A' + (h % 26) as u8) as char;
+                h /= 26;
+                c
+            })
+            .collect()
+    }
+
+    /// `/W`: ширины всех CID в тысячных кегля.
+    fn widths(&self) -> String {
+        let f = font();
+        let w = |g: u16| (f.advance(g) as f32 * 1000.0 / f.units_per_em as f32).round();
+        let all: Vec<String> = std::iter::once(0)
+            .chain(self.used.iter().map(|(g, _)| *g))
+            .map(|g| w(g).to_string())
+            .collect();
+        format!("[0 [{}]]", all.join(" "))
+    }
+
+    /// Поток `/ToUnicode`: CID → UTF-16BE, блоками не длиннее 100 строк.
+    fn to_unicode(&self) -> String {
+        let mut out = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+             /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+             1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+        );
+        for (block, chunk) in self.used.chunks(100).enumerate() {
+            let _ = writeln!(out, "{} beginbfchar", chunk.len());
+            for (i, (_, ch)) in chunk.iter().enumerate() {
+                let _ = write!(out, "<{:04X}> <", block * 100 + i + 1);
+                for u in ch.encode_utf16(&mut [0; 2]) {
+                    let _ = write!(out, "{u:04X}");
+                }
+                out.push_str(">\n");
+            }
+            out.push_str("endbfchar\n");
+        }
+        out.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+        out
+    }
+
+    /// Подмножество шрифта шестнадцатеричным текстом (`/ASCIIHexDecode`) и длина
+    /// в байтах до кодирования.
+    fn font_file(&self) -> (String, usize) {
+        let gids: Vec<u16> = std::iter::once(0)
+            .chain(self.used.iter().map(|(g, _)| *g))
+            .collect();
+        let data = font().subset(&gids);
+        let mut hex = String::with_capacity(data.len() * 2 + data.len() / 32 + 2);
+        for line in data.chunks(64) {
+            for b in line {
+                let _ = write!(hex, "{b:02x}");
+            }
+            hex.push('\n');
+        }
+        hex.push_str(">\n");
+        (hex, data.len())
+    }
+}
+
+impl PdfWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Страница `size_mm` с видимыми сущностями `doc`; `tr` переводит координаты
+    /// чертежа в мм от левого нижнего угла страницы.
+    pub fn add_page(&mut self, doc: &Document, size_mm: (f32, f32), tr: &Affine2) {
+        let mut page = Page {
+            out: String::new(),
+            glyphs: &mut self.glyphs,
+            tr: *tr,
+            stroke: None,
+            fill: None,
+            width: None,
+            dash: None,
+        };
+        let unit = (size_mm.0.max(size_mm.1) * PT_PER_MM / MAX_PAGE_PT)
+            .ceil()
+            .max(1.0);
+        // дальше всё в мм; скруглённые концы — чтобы точки шаблонов были видны
+        let k = PT_PER_MM / unit;
+        let _ = writeln!(page.out, "{k:.6} 0 0 {k:.6} 0 0 cm 1 J 1 j");
+        for e in &doc.entities {
+            page.entity(doc, e, 0);
+        }
+        self.pages.push((size_mm, unit, page.out));
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Собрать файл.
+    pub fn finish(self) -> Vec<u8> {
+        // 1 — каталог, 2 — дерево страниц, 3 — шрифт, 4 — его глифы (CIDFont),
+        // 5 — сведения, 6 — описание шрифта, 7 — подмножество TrueType, 8 — ToUnicode,
+        // дальше по паре на страницу: сама страница и её поток
+        let f = font();
+        let name = format!("{}+{FONT_NAME}", self.glyphs.tag());
+        let em = |v: i16| (v as f32 * 1000.0 / f.units_per_em as f32).round();
+        let (font_file, font_len) = self.glyphs.font_file();
+        let to_unicode = self.glyphs.to_unicode();
+        let mut objs: Vec<String> = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".into(),
+            String::new(),
+            format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /{name} /Encoding /Identity-H \
+                 /DescendantFonts [4 0 R] /ToUnicode 8 0 R >>"
+            ),
+            format!(
+                "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{name} \
+                 /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                 /FontDescriptor 6 0 R /CIDToGIDMap /Identity /W {} >>",
+                self.glyphs.widths()
+            ),
+            "<< /Producer (rust-cad) >>".into(),
+            format!(
+                "<< /Type /FontDescriptor /FontName /{name} /Flags 32 /FontBBox [{}] \
+                 /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 \
+                 /FontFile2 7 0 R >>",
+                f.bbox.map(em).map(|v| v.to_string()).join(" "),
+                em(f.ascent),
+                em(f.descent),
+                (cap_height() * 1000.0).round()
+            ),
+            format!(
+                "<< /Length {} /Length1 {font_len} /Filter /ASCIIHexDecode >>\n\
+                 stream\n{font_file}endstream",
+                font_file.len()
+            ),
+            format!(
+                "<< /Length {} >>\nstream\n{to_unicode}endstream",
+                to_unicode.len()
+            ),
+        ];
+        let kids: Vec<String> = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", objs.len() + 1 + 2 * i))
+            .collect();
+        objs[1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            self.pages.len()
+        );
+        // /UserUnit появился в PDF 1.6
+        let version = if self.pages.iter().any(|p| p.1 > 1.0) {
+            "1.6"
+        } else {
+            "1.4"
+        };
+        for ((w, h), unit, content) in self.pages {
+            let n = objs.len() + 1;
+            let user_unit = if unit > 1.0 {
+                format!(" /UserUnit {}", num(unit))
+            } else {
+                String::new()
+            };
+            objs.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}]{user_unit} \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                num(w * PT_PER_MM / unit),
+                num(h * PT_PER_MM / unit),
+                n + 1
+            ));
+            objs.push(format!(
+                "<< /Length {} >>\nstream\n{content}endstream",
+                content.len()
+            ));
+        }
+
+        let mut out = format!("%PDF-{version}\n");
+        let mut offsets = Vec::with_capacity(objs.len());
+        for (i, o) in objs.iter().enumerate() {
+            offsets.push(out.len());
+            let _ = writeln!(out, "{} 0 obj\n{o}\nendobj", i + 1);
+        }
+        let xref = out.len();
+        let _ = writeln!(out, "xref\n0 {}\n0000000000 65535 f ", objs.len() + 1);
+        for off in offsets {
+            let _ = writeln!(out, "{off:010} 00000 n ");
+        }
+        let _ = write!(
+            out,
+            "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objs.len() + 1
+        );
+        out.into_bytes()
+    }
+}
+
+/// Поток команд страницы с текущим состоянием пера (меняется только при отличии).
+struct Page<'a> {
+    out: String,
+    glyphs: &'a mut Glyphs,
+    tr: Affine2,
+    stroke: Option<[u8; 3]>,
+    fill: Option<[u8; 3]>,
+    width: Option<f32>,
+    dash: Option<Vec<f32>>,
+}
+
+impl Page<'_> {
+    fn entity(&mut self, doc: &Document, e: &Entity, depth: usize) {
+        if !doc.is_layer_visible(&e.layer) {
+            return;
+        }
+        if e.kind.is_block_ref() {
+            if depth < MAX_BLOCK_DEPTH {
+                for sub in doc.explode_insert(e) {
+                    self.entity(doc, &sub, depth + 1);
+                }
+            }
+            return;
+        }
+        let st = doc.entity_style(e);
+        let rgb = paper_rgb(st.color);
+        // вес — на бумаге, шаблон — в единицах чертежа
+        let k = self.tr.mean_scale();
+        let dash: Vec<f32> = st.pattern.iter().map(|v| v * k).collect();
+
+        match &e.kind {
+            EntityKind::Text {
+                pos,
+                content,
+                height,
+            } => self.text(*pos, *height, content, rgb),
+            EntityKind::Hatch { loops, fill } => {
+                let polys: Vec<Vec<Pt2>> = loops
+                    .iter()
+                    .map(|l| l.polygon())
+                    .filter(|p| p.len() >= 3)
+                    .collect();
+                match fill {
+                    HatchFill::Solid => {
+                        self.set_fill(rgb);
+                        for poly in &polys {
+                            self.polyline(poly, true);
+                        }
+                        if !polys.is_empty() {
+                            self.out.push_str("f*\n");
+                        }
+                    }
+                    HatchFill::Pattern { lines, .. } => {
+                        let segs = pattern_segments(&polys, lines);
+                        if !segs.is_empty() {
+                            self.set_pen(rgb, st.weight_mm, &[]);
+                            for s in &segs {
+                                self.polyline(s, false);
+                            }
+                            self.out.push_str("S\n");
+                        }
+                    }
+                }
+            }
+            kind => {
+                self.set_pen(rgb, st.weight_mm, &dash);
+                if self.path(kind) {
+                    self.out.push_str("S\n");
+                }
+            }
+        }
+    }
+
+    fn set_pen(&mut self, rgb: [u8; 3], width: f32, dash: &[f32]) {
+        if self.stroke != Some(rgb) {
+            let [r, g, b] = rgb.map(|c| num(c as f32 / 255.0));
+            let _ = writeln!(self.out, "{r} {g} {b} RG");
+            self.stroke = Some(rgb);
+        }
+        if self.width != Some(width) {
+            let _ = writeln!(self.out, "{} w", num(width));
+            self.width = Some(width);
+        }
+        if self.dash.as_deref() != Some(dash) {
+            let _ = writeln!(self.out, "{} 0 d", dash_array(dash));
+            self.dash = Some(dash.to_vec());
+        }
+    }
+
+    fn set_fill(&mut self, rgb: [u8; 3]) {
+        if self.fill != Some(rgb) {
+            let [r, g, b] = rgb.map(|c| num(c as f32 / 255.0));
+            let _ = writeln!(self.out, "{r} {g} {b} rg");
+            self.fill = Some(rgb);
+        }
+    }
+
+    fn point(&mut self, p: Pt2, op: &str) {
+        let p = self.tr.apply(p);
+        let _ = writeln!(self.out, "{} {} {op}", num(p.x), num(p.y));
+    }
+
+    fn polyline(&mut self, pts: &[Pt2], closed: bool) {
+        let Some(first) = pts.first() else {
+            return;
+        };
+        self.point(*first, "m");
+        for p in &pts[1..] {
+            self.point(*p, "l");
+        }
+        if closed {
+            self.out.push_str("h\n");
+        }
+    }
+
+    /// Дуга эллипса `c + u·cos t + v·sin t` от `t0` на `sweep` (со знаком) кривыми
+    /// Безье не больше четверти оборота; перо уже в начальной точке.
+    fn arc(&mut self, c: Pt2, u: Pt2, v: Pt2, t0: f32, sweep: f32) {
+        let at = |t: f32| {
+            let (s, co) = t.sin_cos();
+            Pt2::new(c.x + u.x * co + v.x * s, c.y + u.y * co + v.y * s)
+        };
+        let tangent = |t: f32| {
+            let (s, co) = t.sin_cos();
+            Pt2::new(-u.x * s + v.x * co, -u.y * s + v.y * co)
+        };
+        let n = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let h = sweep / n as f32;
+        let k = 4.0 / 3.0 * (h / 4.0).tan();
+        for i in 0..n {
+            let (a, b) = (t0 + h * i as f32, t0 + h * (i + 1) as f32);
+            let (pa, pb, da, db) = (at(a), at(b), tangent(a), tangent(b));
+            let c1 = self.tr.apply(Pt2::new(pa.x + da.x * k, pa.y + da.y * k));
+            let c2 = self.tr.apply(Pt2::new(pb.x - db.x * k, pb.y - db.y * k));
+            let end = self.tr.apply(pb);
+            let _ = writeln!(
+                self.out,
+                "{} {} {} {} {} {} c",
+                num(c1.x),
+                num(c1.y),
+                num(c2.x),
+                num(c2.y),
+                num(end.x),
+                num(end.y)
+            );
+        }
+    }
+
+    /// Путь кривой; `false`, если рисовать нечего.
+    fn path(&mut self, kind: &EntityKind) -> bool {
+        let circle = |r: f32| (Pt2::new(r, 0.0), Pt2::new(0.0, r));
+        match kind {
+            EntityKind::Arc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                let (s, mut e) = (*start_angle, *end_angle);
+                if e < s {
+                    e += TAU * ((s - e) / TAU).ceil();
+                }
+                let (u, v) = circle(*radius);
+                self.point(
+                    Pt2::new(center.x + radius * s.cos(), center.y + radius * s.sin()),
+                    "m",
+                );
+                self.arc(*center, u, v, s, e - s);
+            }
+            EntityKind::Circle { center, radius } => {
+                let (u, v) = circle(*radius);
+                self.point(Pt2::new(center.x + radius, center.y), "m");
+                self.arc(*center, u, v, 0.0, TAU);
+                self.out.push_str("h\n");
+            }
+            EntityKind::Ellipse {
+                center,
+                major,
+                ratio,
+                start_param,
+                end_param,
+            } => {
+                let u = *major;
+                let v = Pt2::new(-major.y * ratio, major.x * ratio);
+                let (s, co) = start_param.sin_cos();
+                self.point(
+                    Pt2::new(center.x + u.x * co + v.x * s, center.y + u.y * co + v.y * s),
+                    "m",
+                );
+                self.arc(*center, u, v, *start_param, end_param - start_param);
+            }
+            EntityKind::Polyline {
+                pts,
+                closed,
+                bulges,
+            } => {
+                let Some(first) = pts.first() else {
+                    return false;
+                };
+                self.point(*first, "m");
+                let n = pts.len();
+                let count = if *closed && n > 2 { n } else { n - 1 };
+                for i in 0..count {
+                    let (a, b) = (pts[i], pts[(i + 1) % n]);
+                    match bulge_arc(a, b, bulges.get(i).copied().unwrap_or(0.0)) {
+                        Some((c, r, a0, a1)) => {
+                            let (u, v) = circle(r);
+                            self.arc(c, u, v, a0, a1 - a0);
+                        }
+                        // замыкающий отрезок даст h
+                        None if i + 1 == n => {}
+                        None => self.point(b, "l"),
+                    }
+                }
+                if *closed {
+                    self.out.push_str("h\n");
+                }
+            }
+            // отрезки и сплайны — ломаной
+            kind => {
+                let pts = curve_points(kind);
+                if pts.len() < 2 {
+                    return false;
+                }
+                self.polyline(&pts, false);
+            }
+        }
+        true
+    }
+
+    /// Текст от точки базовой линии; `height` — высота прописных в единицах чертежа.
+    fn text(&mut self, pos: Pt2, height: f32, content: &str, rgb: [u8; 3]) {
+        if content.is_empty() {
+            return;
+        }
+        self.set_fill(rgb);
+        // кегль в единицах чертежа, матрица текста несёт и масштаб страницы
+        let size = height / cap_height();
+        let m = self.tr.m;
+        let p = self.tr.apply(pos);
+        let _ = writeln!(
+            self.out,
+            "BT /F1 1 Tf {} {} {} {} {} {} Tm {} Tj ET",
+            num(m[0][0] * size),
+            num(m[1][0] * size),
+            num(m[0][1] * size),
+            num(m[1][1] * size),
+            num(p.x),
+            num(p.y),
+            self.glyphs.encode(content)
+        );
+    }
+}
+
+/// Цвет на белой бумаге: ACI 7 — чёрный.
+fn paper_rgb(c: Color) -> [u8; 3] {
+    match c {
+        Color::Aci(7) | Color::ByLayer | Color::ByBlock => [0, 0, 0],
+        c => c.rgb().unwrap_or([0, 0, 0]),
+    }
+}
+
+/// Число для потока команд: до 4 знаков, без хвостовых нулей.
+fn num(v: f32) -> String {
+    let s = format!("{:.4}", if v.is_finite() { v } else { 0.0 });
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "" | "-0" => "0".into(),
+        s => s.into(),
+    }
+}
+
+/// Шаблон типа линии (штрих > 0, пробел < 0, точка = 0) → массив PDF, где штрихи и
+/// пробелы чередуются: соседние одного знака сливаются, шаблон с пробела начинается
+/// точкой нулевой длины.
+fn dash_array(pattern: &[f32]) -> String {
+    if pattern.iter().all(|v| *v >= 0.0) {
+        return "[]".into();
+    }
+    let mut arr: Vec<f32> = Vec::new();
+    for &v in pattern {
+        let gap = v < 0.0;
+        if arr.is_empty() && gap {
+            arr.push(0.0);
+        }
+        // чётные места — штрихи, нечётные — пробелы
+        if !arr.is_empty() && ((arr.len() - 1) % 2 == 1) == gap {
+            *arr.last_mut().unwrap() += v.abs();
+        } else {
+            arr.push(v.abs());
+        }
+    }
+    if arr.len() % 2 == 1 {
+        arr.push(0.0);
+    }
+    format!(
+        "[{}]",
+        arr.iter().map(|v| num(*v)).collect::<Vec<_>>().join(" ")
+    )
+}
@@ -0,0 +1,819 @@
+// cad-core/src/projection.rs
+//! Проекция 3D-модели в линии вида: ориентация по [`ViewKind`], удаление
+//! невидимых линий и деление рёбер на видимые, невидимые и очерковые.
+//!
+//! Тела берутся сеткой [`Element3D::triangulate`]. Рёбра — границы сетки,
+//! изломы граней и очерк (граница лицевых и тыльных граней); гладкие рёбра
+//! триангуляции в чертёж не попадают.
+//!
+//! Разрез (`Viewport::clip` с плоскостью): сечения штрихуются по материалу,
+//! стержни — залитыми кружками, за плоскостью — видимые линии на глубину вида.
+
+use crate::model3d::{Element3D, Id, MaterialKind, Model3D, Project3D, Pt3};
+use crate::{
+    fill_triangles, Document, Entity, EntityKind, HatchFill, HatchLoop, Layer, LineWeight,
+    LinetypeDef, Pt2, Section, ViewKind, Viewport,
+};
+use std::collections::HashMap;
+
+/// Слой видимых рёбер.
+pub const VIEW_VISIBLE_LAYER: &str = "VIEW-VISIBLE";
+/// Слой невидимых рёбер (штриховая).
+pub const VIEW_HIDDEN_LAYER: &str = "VIEW-HIDDEN";
+/// Слой очерка тел.
+pub const VIEW_SILHOUETTE_LAYER: &str = "VIEW-SILHOUETTE";
+/// Слой контуров сечений в разрезе.
+pub const VIEW_CUT_LAYER: &str = "VIEW-CUT";
+/// Слой штриховки сечений.
+pub const VIEW_HATCH_LAYER: &str = "VIEW-HATCH";
+/// Слой стержней в сечении.
+pub const VIEW_REBAR_LAYER: &str = "VIEW-REBAR";
+/// Тип линии невидимого контура, мм бумаги.
+pub const HIDDEN_LINETYPE: &str = "HIDDEN";
+
+/// Граней трубы: при 24 угол между соседними (15°) меньше порога излома.
+const TUBE_SIDES: u32 = 24;
+/// cos 20°: рёбра с меньшим изломом считаются гладкими.
+const CREASE_COS: f64 = 0.9397;
+/// Масштаб образца AR-CONC на бумаге (в acad.pat он в дюймах).
+const CONCRETE_HATCH_SCALE: f32 = 0.05;
+/// Наименьший радиус стержня на листе, мм: тонкие стержни — точкой.
+const REBAR_MIN_MM: f32 = 0.35;
+/// Допуск относительно размера модели.
+const REL_EPS: f64 = 1e-5;
+
+pub(crate) type V3 = [f64; 3];
+type P2 = (f64, f64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeClass {
+    Visible,
+    Hidden,
+    Silhouette,
+}
+
+/// Отрезок вида в мм модели (x — вправо, y — вверх по виду).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProjectedEdge {
+    pub a: Pt2,
+    pub b: Pt2,
+    pub class: EdgeClass,
+}
+
+impl ViewKind {
+    /// Оси вида в мировых координатах: вправо, вверх и к наблюдателю.
+    pub fn axes(&self) -> [[f64; 3]; 3] {
+        let (s2, s3, s6) = (2f64.sqrt(), 3f64.sqrt(), 6f64.sqrt());
+        match self {
+            ViewKind::Plan => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            // разрез пока смотрит как фасад по X
+            ViewKind::ElevX | ViewKind::Section => {
+                [[1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]]
+            }
+            ViewKind::ElevY => [[0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]],
+            ViewKind::Iso => [
+                [1.0 / s2, 1.0 / s2, 0.0],
+                [-1.0 / s6, 1.0 / s6, 2.0 / s6],
+                [1.0 / s3, -1.0 / s3, 1.0 / s3],
+            ],
+        }
+    }
+}
+
+/// Разрез в мм модели: оси вида — по плоскости сечения.
+#[derive(Debug, Clone)]
+pub struct SectionView {
+    /// Проекция точки `Section::origin`.
+    pub origin: Pt2,
+    /// Линии за секущей плоскостью (невидимые в разрезе не чертятся).
+    pub edges: Vec<ProjectedEdge>,
+    pub cuts: Vec<CutRegion>,
+    pub bars: Vec<CutBar>,
+}
+
+/// Сечение элемента: замкнутые контуры (отверстия — чёт-нечет).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutRegion {
+    pub element: Id,
+    pub loops: Vec<Vec<Pt2>>,
+}
+
+/// Стержень в сечении.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CutBar {
+    pub element: Id,
+    pub center: Pt2,
+    pub diameter: f32,
+}
+
+/// Спроецировать элементы: рёбра вида после удаления невидимых линий.
+pub fn project_elements(elements: &[&Element3D], kind: &ViewKind) -> Vec<ProjectedEdge> {
+    solve(&Scene::build(elements, kind.axes(), None))
+}
+
+/// Разрез элементов плоскостью `section`: сечения, арматура и линии за
+/// плоскостью на глубину `depth`. `None`, если плоскость не задана.
+pub fn project_section(elements: &[&Element3D], section: &Section) -> Option<SectionView> {
+    let [r, u, d] = section.axes()?;
+    let (o, _) = section.plane()?;
+    let view = |p: Pt3| {
+        let w = [p.x as f64, p.y as f64, p.z as f64];
+        Pt2::new(dot(w, r) as f32, dot(w, u) as f32)
+    };
+    let mut out = SectionView {
+        origin: Pt2::new(dot(o, r) as f32, dot(o, u) as f32),
+        edges: Vec::new(),
+        cuts: Vec::new(),
+        bars: Vec::new(),
+    };
+    for el in elements {
+        let loops: Vec<Vec<Pt2>> = section
+            .cut_element(el)
+            .into_iter()
+            .map(|l| l.into_iter().map(view).collect())
+            .collect();
+        if !loops.is_empty() {
+            out.cuts.push(CutRegion {
+                element: el.id,
+                loops,
+            });
+        }
+        for (c, diameter) in section.cut_rebars(el) {
+            out.bars.push(CutBar {
+                element: el.id,
+                center: view(c),
+                diameter,
+            });
+        }
+    }
+
+    // за плоскостью: слой [zc − depth, zc], сечения заслоняют то, что за ними
+    let zc = dot(o, d);
+    let far = if section.depth > 0.0 {
+        zc - section.depth as f64
+    } else {
+        f64::MIN
+    };
+    let mut scene = Scene::build(elements, [r, u, d], Some((far, zc)));
+    for c in &out.cuts {
+        for t in fill_triangles(&c.loops) {
+            let v = t.map(|p| {
+                scene.pts.push([p.x as f64, p.y as f64, zc]);
+                scene.pts.len() - 1
+            });
+            scene.tris.push(Tri { v });
+        }
+    }
+    out.edges = solve(&scene);
+    out.edges.retain(|e| e.class != EdgeClass::Hidden);
+    Some(out)
+}
+
+/// Удаление невидимых линий: куски рёбер сцены по классам.
+fn solve(scene: &Scene) -> Vec<ProjectedEdge> {
+    if scene.tris.is_empty() {
+        return Vec::new();
+    }
+    let grid = Grid::new(scene);
+    let mut out = Vec::new();
+    let mut hidden = Vec::new();
+    let mut stamp = vec![usize::MAX; scene.tris.len()];
+    for (k, e) in scene.edges.iter().enumerate() {
+        let (a, b) = (scene.pts[e.a], scene.pts[e.b]);
+        if (b[0] - a[0]).hypot(b[1] - a[1]) < scene.eps {
+            continue; // ребро смотрит на наблюдателя
+        }
+        let mut covered = Vec::new();
+        for t in grid.query(a, b) {
+            if stamp[t] == k {
+                continue;
+            }
+            stamp[t] = k;
+            let tri = &scene.tris[t];
+            if tri.v.contains(&e.a) && tri.v.contains(&e.b) {
+                continue;
+            }
+            if let Some(iv) = scene.occlusion(a, b, t) {
+                covered.push(iv);
+            }
+        }
+        let (a, b) = ((a[0], a[1]), (b[0], b[1]));
+        // у общих вершин глубины сходятся — щели короче 10·eps тоже закрыты
+        let gap = 10.0 * scene.eps / (b.0 - a.0).hypot(b.1 - a.1);
+        let mut hid = merge(covered);
+        if let Some(first) = hid.first_mut().filter(|h| h.0 < gap) {
+            first.0 = 0.0;
+        }
+        if let Some(last) = hid.last_mut().filter(|h| h.1 > 1.0 - gap) {
+            last.1 = 1.0;
+        }
+        hid.dedup_by(|next, prev| {
+            let close = next.0 - prev.1 < gap;
+            if close {
+                prev.1 = prev.1.max(next.1);
+            }
+            close
+        });
+        let mut t0 = 0.0;
+        for &(h0, h1) in hid.iter().chain([(1.0, 1.0)].iter()) {
+            push_piece(&mut out, a, b, t0, h0, e.class, scene.eps);
+            push_piece(&mut hidden, a, b, h0, h1, EdgeClass::Hidden, scene.eps);
+            t0 = h1;
+        }
+    }
+    // невидимое под уже начерченной линией (видимой или такой же невидимой) не чертится
+    for h in hidden {
+        let dir = (h.b.0 - h.a.0, h.b.1 - h.a.1);
+        let len2 = dir.0 * dir.0 + dir.1 * dir.1;
+        let len = len2.sqrt();
+        let param = |p: P2| ((p.0 - h.a.0) * dir.0 + (p.1 - h.a.1) * dir.1) / len2;
+        let off = |p: P2| ((p.0 - h.a.0) * dir.1 - (p.1 - h.a.1) * dir.0).abs() / len;
+        let over: Vec<(f64, f64)> = out
+            .iter()
+            .filter(|v| off(v.a) < scene.eps && off(v.b) < scene.eps)
+            .map(|v| {
+                let (s0, s1) = (param(v.a), param(v.b));
+                let pad = scene.eps / len;
+                (s0.min(s1) - pad, s0.max(s1) + pad)
+            })
+            .collect();
+        let mut t0 = 0.0;
+        for &(c0, c1) in merge(over).iter().chain([(1.0, 1.0)].iter()) {
+            push_piece(&mut out, h.a, h.b, t0, c0, EdgeClass::Hidden, scene.eps);
+            t0 = t0.max(c1);
+        }
+    }
+    out.into_iter()
+        .map(|p| ProjectedEdge {
+            a: Pt2::new(p.a.0 as f32, p.a.1 as f32),
+            b: Pt2::new(p.b.0 as f32, p.b.1 as f32),
+            class: p.class,
+        })
+        .collect()
+}
+
+/// Вид на листе: элемент `element_ref` (или вся модель, если такого нет) в
+/// масштабе вида с обрезкой по `rect_mm`. Обычный вид — по центру окна;
+/// разрез (`clip` с плоскостью) ставит `Section::origin` в `origin_mm`.
+pub fn project_viewport(project: &Project3D, vp: &Viewport) -> Vec<Entity> {
+    let all = project
+        .models
+        .iter()
+        .flat_map(|m| m.elements.iter().map(move |e| (m, e)));
+    let mut picked: Vec<(&Model3D, &Element3D)> = all
+        .clone()
+        .filter(|(_, e)| e.id == vp.element_ref)
+        .collect();
+    if picked.is_empty() {
+        picked = all.collect();
+    }
+    let elements: Vec<&Element3D> = picked.iter().map(|&(_, e)| e).collect();
+    let k = if vp.scale > 0.0 { 1.0 / vp.scale } else { 1.0 };
+
+    if let Some(sec) = &vp.clip {
+        if let Some(sv) = project_section(&elements, sec) {
+            let map = |p: Pt2| {
+                Pt2::new(
+                    sec.origin_mm.x + (p.x - sv.origin.x) * k,
+                    sec.origin_mm.y + (p.y - sv.origin.y) * k,
+                )
+            };
+            return section_entities(&sv, &picked, vp, map, k);
+        }
+    }
+
+    let edges = project_elements(&elements, &vp.kind);
+    if edges.is_empty() {
+        return Vec::new();
+    }
+    let (mut lo, mut hi) = (edges[0].a, edges[0].a);
+    for p in edges.iter().flat_map(|e| [e.a, e.b]) {
+        lo = Pt2::new(lo.x.min(p.x), lo.y.min(p.y));
+        hi = Pt2::new(hi.x.max(p.x), hi.y.max(p.y));
+    }
+    let r = vp.rect_mm;
+    let map = |p: Pt2| {
+        Pt2::new(
+            r.x + r.w / 2.0 + (p.x - (lo.x + hi.x) / 2.0) * k,
+            r.y + r.h / 2.0 + (p.y - (lo.y + hi.y) / 2.0) * k,
+        )
+    };
+    edge_entities(&edges, vp, map)
+}
+
+fn edge_entities(edges: &[ProjectedEdge], vp: &Viewport, map: impl Fn(Pt2) -> Pt2) -> Vec<Entity> {
+    edges
+        .iter()
+        .filter_map(|e| {
+            let (a, b) = clip_to_rect(map(e.a), map(e.b), vp)?;
+            let layer = match e.class {
+                EdgeClass::Visible => VIEW_VISIBLE_LAYER,
+                EdgeClass::Hidden => VIEW_HIDDEN_LAYER,
+                EdgeClass::Silhouette => VIEW_SILHOUETTE_LAYER,
+            };
+            Some(Entity::new(layer, EntityKind::LineSeg { a, b }))
+        })
+        .collect()
+}
+
+/// Разрез на листе: линии за плоскостью, штриховка и контуры сечений, стержни.
+fn section_entities(
+    sv: &SectionView,
+    picked: &[(&Model3D, &Element3D)],
+    vp: &Viewport,
+    map: impl Fn(Pt2) -> Pt2,
+    k: f32,
+) -> Vec<Entity> {
+    let mut out = edge_entities(&sv.edges, vp, &map);
+    for c in &sv.cuts {
+        let material = picked
+            .iter()
+            .find(|(_, e)| e.id == c.element)
+            .and_then(|(m, e)| m.materials.iter().find(|x| x.id == e.material))
+            .map(|x| &x.kind);
+        let loops: Vec<Vec<Pt2>> = c
+            .loops
+            .iter()
+            .map(|l| l.iter().map(|&p| map(p)).collect())
+            .collect();
+        let hatch: Vec<HatchLoop> = loops
+            .iter()
+            .map(|l| clip_polygon_to_rect(l, vp))
+            .filter(|l| l.len() >= 3)
+            .map(|l| HatchLoop::polyline(l, vec![]))
+            .collect();
+        if !hatch.is_empty() {
+            out.push(Entity::new(
+                VIEW_HATCH_LAYER,
+                EntityKind::Hatch {
+                    loops: hatch,
+                    fill: cut_fill(material),
+                },
+            ));
+        }
+        for l in &loops {
+            for (i, &a) in l.iter().enumerate() {
+                if let Some((a, b)) = clip_to_rect(a, l[(i + 1) % l.len()], vp) {
+                    out.push(Entity::new(VIEW_CUT_LAYER, EntityKind::LineSeg { a, b }));
+                }
+            }
+        }
+    }
+    let r = vp.rect_mm;
+    for b in &sv.bars {
+        let center = map(b.center);
+        if center.x < r.x || center.x > r.x + r.w || center.y < r.y || center.y > r.y + r.h {
+            continue;
+        }
+        let circle = EntityKind::Circle {
+            center,
+            radius: (b.diameter / 2.0 * k).max(REBAR_MIN_MM),
+        };
+        out.push(Entity::new(
+            VIEW_REBAR_LAYER,
+            EntityKind::Hatch {
+                loops: vec![HatchLoop {
+                    edges: vec![circle.clone()],
+                }],
+                fill: HatchFill::Solid,
+            },
+        ));
+        out.push(Entity::new(VIEW_REBAR_LAYER, circle));
+    }
+    out
+}
+
+/// Штриховка сечения по ГОСТ 2.306: бетон — «камни», металл и прочее — 45°.
+fn cut_fill(material: Option<&MaterialKind>) -> HatchFill {
+    let (name, scale) = match material {
+        Some(MaterialKind::Concrete { .. }) => ("AR-CONC", CONCRETE_HATCH_SCALE),
+        _ => ("ANSI31", 1.0),
+    };
+    HatchFill::pattern(name, 0.0, scale).unwrap_or(HatchFill::Solid)
+}
+
+/// Слои видов и штриховой тип линии (толщины по ГОСТ 2.303: s = 0,5 мм).
+pub fn add_view_layers(doc: &mut Document) {
+    if doc.linetype(HIDDEN_LINETYPE).is_none() {
+        doc.linetypes.push(LinetypeDef {
+            name: HIDDEN_LINETYPE.into(),
+            description: "Невидимый контур __ __ __".into(),
+            pattern: vec![4.0, -1.5],
+        });
+    }
+    for (name, w, lt) in [
+        (VIEW_VISIBLE_LAYER, 50, None),
+        (VIEW_HIDDEN_LAYER, 25, Some(HIDDEN_LINETYPE)),
+        (VIEW_SILHOUETTE_LAYER, 50, None),
+        (VIEW_CUT_LAYER, 70, None),
+        (VIEW_HATCH_LAYER, 18, None),
+        (VIEW_REBAR_LAYER, 18, None),
+    ] {
+        let mut l = Layer {
+            lineweight: LineWeight::Width(w),
+            ..Layer::new(name)
+        };
+        if let Some(lt) = lt {
+            l.linetype = lt.into();
+        }
+        doc.add_layer(l);
+    }
+}
+
+/// Многоугольник, обрезанный окном вида (Сазерленд — Ходжман).
+fn clip_polygon_to_rect(poly: &[Pt2], vp: &Viewport) -> Vec<Pt2> {
+    let r = vp.rect_mm;
+    // (ось, граница, внутри — меньше)
+    let sides = [
+        (0, r.x, false),
+        (0, r.x + r.w, true),
+        (1, r.y, false),
+        (1, r.y + r.h, true),
+    ];
+    let mut out = poly.to_vec();
+    for (axis, edge, below) in sides {
+        let coord = |p: Pt2| if axis == 0 { p.x } else { p.y };
+        let inside = |p: Pt2| {
+            if below {
+                coord(p) <= edge
+            } else {
+                coord(p) >= edge
+            }
+        };
+        let src = std::mem::take(&mut out);
+        for (i, &a) in src.iter().enumerate() {
+            let b = src[(i + 1) % src.len()];
+            if inside(a) {
+                out.push(a);
+            }
+            if inside(a) != inside(b) {
+                let t = (edge - coord(a)) / (coord(b) - coord(a));
+                out.push(Pt2::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t));
+            }
+        }
+    }
+    out
+}
+
+/// Отсечение отрезка окном вида (Лианг — Барски).
+fn clip_to_rect(a: Pt2, b: Pt2, vp: &Viewport) -> Option<(Pt2, Pt2)> {
+    let r = vp.rect_mm;
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let (mut t0, mut t1) = (0f32, 1f32);
+    for (p, q) in [
+        (-dx, a.x - r.x),
+        (dx, r.x + r.w - a.x),
+        (-dy, a.y - r.y),
+        (dy, r.y + r.h - a.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    (t1 - t0 > 1e-6).then(|| {
+        (
+            Pt2::new(a.x + dx * t0, a.y + dy * t0),
+            Pt2::new(a.x + dx * t1, a.y + dy * t1),
+        )
+    })
+}
+
+// ---------------------------------- сцена ----------------------------------
+
+struct Tri {
+    v: [usize; 3],
+}
+
+struct Edge {
+    a: usize,
+    b: usize,
+    class: EdgeClass,
+}
+
+/// Сетки всех элементов в координатах вида (x, y — лист, z — к наблюдателю).
+struct Scene {
+    pts: Vec<V3>,
+    tris: Vec<Tri>,
+    edges: Vec<Edge>,
+    eps: f64,
+}
+
+impl Scene {
+    /// Сетки в осях вида; `slab` — оставить только z из [lo, hi] (разрез).
+    fn build(elements: &[&Element3D], [r, u, d]: [[f64; 3]; 3], slab: Option<(f64, f64)>) -> Self {
+        let mut s = Scene {
+            pts: Vec::new(),
+            tris: Vec::new(),
+            edges: Vec::new(),
+            eps: 0.0,
+        };
+        // вершины на границах слоя — от обрезки, их рёбра не чертятся
+        let mut cut = Vec::new();
+        let mut normals = Vec::new();
+        for el in elements {
+            let m = element_mesh(el);
+            let base = s.pts.len();
+            s.pts
+                .extend(m.pts.iter().map(|&w| [dot(w, r), dot(w, u), dot(w, d)]));
+            cut.resize(s.pts.len(), false);
+            let mut split = HashMap::new();
+            for t in &m.tris {
+                let v = t.map(|i| base + i);
+                let n = cross(sub(s.pts[v[1]], s.pts[v[0]]), sub(s.pts[v[2]], s.pts[v[0]]));
+                let n = n.map(|c| c / dot(n, n).sqrt());
+                let Some((lo, hi)) = slab else {
+                    normals.push(n);
+                    s.tris.push(Tri { v });
+                    continue;
+                };
+                let mut poly = v.to_vec();
+                for (k, z, keep_below) in [(0u8, hi, true), (1, lo, false)] {
+                    poly = s.clip_polygon(&poly, z, keep_below, &mut split, k, &mut cut);
+                }
+                for i in 1..poly.len().saturating_sub(1) {
+                    normals.push(n);
+                    s.tris.push(Tri {
+                        v: [poly[0], poly[i], poly[i + 1]],
+                    });
+                }
+            }
+        }
+
+        let (mut lo, mut hi) = ([f64::MAX; 3], [f64::MIN; 3]);
+        for p in &s.pts {
+            for i in 0..3 {
+                lo[i] = lo[i].min(p[i]);
+                hi[i] = hi[i].max(p[i]);
+            }
+        }
+        let size = (0..3).map(|i| hi[i] - lo[i]).fold(0.0, f64::max);
+        s.eps = size.max(1.0) * REL_EPS;
+
+        // рёбра: смежные грани по каждой паре вершин
+        let mut adj: HashMap<(usize, usize), Vec<(usize, bool)>> = HashMap::new();
+        for (t, tri) in s.tris.iter().enumerate() {
+            for i in 0..3 {
+                let (a, b) = (tri.v[i], tri.v[(i + 1) % 3]);
+                adj.entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push((t, a < b));
+            }
+        }
+        let mut keys: Vec<_> = adj.keys().copied().collect();
+        keys.sort_unstable();
+        for (a, b) in keys {
+            if cut[a] && cut[b] {
+                continue;
+            }
+            let faces = &adj[&(a, b)];
+            let class = match faces[..] {
+                [(t1, f1), (t2, f2)] => {
+                    let n1 = normals[t1];
+                    // несогласованный обход — вторую нормаль разворачиваем
+                    let n2 = if f1 == f2 {
+                        normals[t2].map(|c| -c)
+                    } else {
+                        normals[t2]
+                    };
+                    if (n1[2] > 1e-9) != (n2[2] > 1e-9) {
+                        EdgeClass::Silhouette
+                    } else if dot(n1, n2) < CREASE_COS {
+                        EdgeClass::Visible
+                    } else {
+                        continue;
+                    }
+                }
+                // край открытой сетки или неманифолд
+                _ => EdgeClass::Visible,
+            };
+            s.edges.push(Edge { a, b, class });
+        }
+        s
+    }
+
+    /// Многоугольник по одну сторону от z = `z` (Сазерленд — Ходжман).
+    /// Точки на рёбрах общие для соседних граней: ключ — пара вершин и плоскость.
+    fn clip_polygon(
+        &mut self,
+        poly: &[usize],
+        z: f64,
+        keep_below: bool,
+        split: &mut HashMap<(usize, usize, u8), usize>,
+        k: u8,
+        cut: &mut Vec<bool>,
+    ) -> Vec<usize> {
+        let inside = |p: V3| if keep_below { p[2] <= z } else { p[2] >= z };
+        let mut out = Vec::with_capacity(poly.len() + 1);
+        for (i, &a) in poly.iter().enumerate() {
+            let b = poly[(i + 1) % poly.len()];
+            let (ia, ib) = (inside(self.pts[a]), inside(self.pts[b]));
+            if ia {
+                out.push(a);
+            }
+            if ia != ib {
+                let key = (a.min(b), a.max(b), k);
+                let v = *split.entry(key).or_insert_with(|| {
+                    let (p, q) = (self.pts[key.0], self.pts[key.1]);
+                    let t = (z - p[2]) / (q[2] - p[2]);
+                    self.pts
+                        .push([p[0] + (q[0] - p[0]) * t, p[1] + (q[1] - p[1]) * t, z]);
+                    cut.push(true);
+                    self.pts.len() - 1
+                });
+                out.push(v);
+            }
+        }
+        out
+    }
+
+    /// Участок [t0, t1] отрезка `a`–`b`, закрытый треугольником `t`.
+    fn occlusion(&self, a: V3, b: V3, t: usize) -> Option<(f64, f64)> {
+        let [p0, p1, p2] = self.tris[t].v.map(|i| self.pts[i]);
+        let (d1, d2) = (sub(p1, p0), sub(p2, p0));
+        let area = d1[0] * d2[1] - d1[1] * d2[0];
+        let len = d1[0].hypot(d1[1]).max(d2[0].hypot(d2[1]));
+        if area.abs() < self.eps * len {
+            return None; // грань видна ребром
+        }
+        // z плоскости грани: z0 + gx·(x − x0) + gy·(y − y0)
+        let gx = (d1[2] * d2[1] - d2[2] * d1[1]) / area;
+        let gy = (d2[2] * d1[0] - d1[2] * d2[0]) / area;
+        let tol = self.eps * 2.0 * (1.0 + gx.hypot(gy));
+        let ab = sub(b, a);
+
+        // ограничения вида c + k·t ≥ 0
+        let mut cons = Vec::with_capacity(4);
+        let sign = area.signum();
+        for (q, w) in [(p0, p1), (p1, p2), (p2, p0)] {
+            let e = sub(w, q);
+            let el = e[0].hypot(e[1]);
+            let c = sign * (e[0] * (a[1] - q[1]) - e[1] * (a[0] - q[0])) + self.eps * el;
+            let k = sign * (e[0] * ab[1] - e[1] * ab[0]);
+            cons.push((c, k));
+        }
+        // грань ближе к наблюдателю, чем ребро
+        let zc = p0[2] + gx * (a[0] - p0[0]) + gy * (a[1] - p0[1]) - a[2] - tol;
+        let zk = gx * ab[0] + gy * ab[1] - ab[2];
+        cons.push((zc, zk));
+
+        let (mut t0, mut t1) = (0f64, 1f64);
+        for (c, k) in cons {
+            if k.abs() < 1e-15 {
+                if c < 0.0 {
+                    return None;
+                }
+            } else if k > 0.0 {
+                t0 = t0.max(-c / k);
+            } else {
+                t1 = t1.min(-c / k);
+            }
+        }
+        (t1 > t0).then_some((t0, t1))
+    }
+}
+
+/// Сетка элемента в мире: совпадающие вершины сварены, вырожденные
+/// треугольники убраны, обход — нормалями наружу.
+pub(crate) struct ElementMesh {
+    pub pts: Vec<V3>,
+    pub tris: Vec<[usize; 3]>,
+}
+
+pub(crate) fn element_mesh(el: &Element3D) -> ElementMesh {
+    let mesh = el.triangulate(TUBE_SIDES);
+    let mut m = ElementMesh {
+        pts: Vec::new(),
+        tris: Vec::new(),
+    };
+    let mut weld: HashMap<[u32; 3], usize> = HashMap::new();
+    let ids: Vec<usize> = mesh
+        .positions
+        .iter()
+        .map(|p| {
+            *weld.entry(p.map(f32::to_bits)).or_insert_with(|| {
+                m.pts.push(p.map(f64::from));
+                m.pts.len() - 1
+            })
+        })
+        .collect();
+    let mut volume = 0.0;
+    for t in mesh.indices.chunks_exact(3) {
+        let v = [ids[t[0] as usize], ids[t[1] as usize], ids[t[2] as usize]];
+        let n = cross(sub(m.pts[v[1]], m.pts[v[0]]), sub(m.pts[v[2]], m.pts[v[0]]));
+        if dot(n, n).sqrt() < 1e-12 {
+            continue;
+        }
+        volume += dot(m.pts[v[0]], n);
+        m.tris.push(v);
+    }
+    // вывернутая сетка (зеркальная матрица и т.п.)
+    if volume < 0.0 {
+        for t in &mut m.tris {
+            t.swap(1, 2);
+        }
+    }
+    m
+}
+
+/// Равномерная сетка по проекциям треугольников.
+struct Grid {
+    lo: (f64, f64),
+    cell: f64,
+    n: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    fn new(s: &Scene) -> Self {
+        let (mut lo, mut hi) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+        for p in &s.pts {
+            lo = (lo.0.min(p[0]), lo.1.min(p[1]));
+            hi = (hi.0.max(p[0]), hi.1.max(p[1]));
+        }
+        let n = ((s.tris.len() as f64).sqrt() as usize).clamp(1, 64);
+        let cell = ((hi.0 - lo.0).max(hi.1 - lo.1) / n as f64).max(s.eps);
+        let mut g = Grid {
+            lo,
+            cell,
+            n,
+            cells: vec![Vec::new(); n * n],
+        };
+        for (t, tri) in s.tris.iter().enumerate() {
+            let [a, b, c] = tri.v.map(|i| s.pts[i]);
+            let (x0, y0) = g.index(a[0].min(b[0]).min(c[0]), a[1].min(b[1]).min(c[1]));
+            let (x1, y1) = g.index(a[0].max(b[0]).max(c[0]), a[1].max(b[1]).max(c[1]));
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    g.cells[y * n + x].push(t);
+                }
+            }
+        }
+        g
+    }
+
+    fn index(&self, x: f64, y: f64) -> (usize, usize) {
+        let i = |v: f64| (v / self.cell).floor().clamp(0.0, (self.n - 1) as f64) as usize;
+        (i(x - self.lo.0), i(y - self.lo.1))
+    }
+
+    /// Треугольники ячеек, которые задевает габарит отрезка.
+    fn query(&self, a: V3, b: V3) -> impl Iterator<Item = usize> + '_ {
+        let (x0, y0) = self.index(a[0].min(b[0]), a[1].min(b[1]));
+        let (x1, y1) = self.index(a[0].max(b[0]), a[1].max(b[1]));
+        (y0..=y1)
+            .flat_map(move |y| (x0..=x1).map(move |x| y * self.n + x))
+            .flat_map(|c| self.cells[c].iter().copied())
+    }
+}
+
+/// Кусок ребра в плоскости вида.
+struct Piece {
+    a: P2,
+    b: P2,
+    class: EdgeClass,
+}
+
+/// Добавить участок [t0, t1] отрезка `a`–`b`, если он длиннее допуска.
+fn push_piece(out: &mut Vec<Piece>, a: P2, b: P2, t0: f64, t1: f64, class: EdgeClass, eps: f64) {
+    let at = |t: f64| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+    let (t0, t1) = (t0.max(0.0), t1.min(1.0));
+    let (p, q) = (at(t0), at(t1));
+    if t1 > t0 && (q.0 - p.0).hypot(q.1 - p.1) > eps {
+        out.push(Piece { a: p, b: q, class });
+    }
+}
+
+/// Объединение интервалов, по возрастанию.
+fn merge(mut iv: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    iv.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut out: Vec<(f64, f64)> = Vec::new();
+    for (a, b) in iv {
+        match out.last_mut() {
+            Some(last) if a <= last.1 => last.1 = last.1.max(b),
+            _ => out.push((a, b)),
+        }
+    }
+    out
+}
+
+pub(crate) fn dot(a: V3, b: V3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn sub(a: V3, b: V3) -> V3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn cross(a: V3, b: V3) -> V3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
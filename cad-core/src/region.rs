@@ -0,0 +1,852 @@
+//! Области на плоскости: внешний контур с отверстиями и булевы операции над ними.
+//! Дуги и НУРБС контуров аппроксимируются ломаными (как у штриховки), операции —
+//! над ломаными: рёбра режутся в точках пересечения и отбираются по положению
+//! относительно другой области. Рёбра дуг помнят свою окружность и переживают
+//! операции, так что в `to_entities` дуги снова становятся выпуклостями полилинии,
+//! а площадь и центр тяжести считаются по дугам, а не по хордам.
+
+use crate::hatch::{curve_points, point_in_polygon};
+use crate::{loop_props, polyline_segments, AreaProps, ElementGeom, EntityKind, HatchLoop, Pt2};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// Область: внешний контур против часовой и отверстия по часовой,
+/// без повтора первой точки в конце.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub outer: Vec<Pt2>,
+    pub holes: Vec<Vec<Pt2>>,
+    /// Окружности рёбер: `arcs[0]` — внешнего контура, `arcs[1 + i]` — отверстия `i`;
+    /// ребро `j` идёт из вершины `j` в следующую. Нет записи — ребро прямое.
+    arcs: Vec<Tags>,
+}
+
+/// Окружность, на которой лежат вершины дугового ребра.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ArcOf {
+    center: Pt2,
+    radius: f32,
+}
+
+type Tags = Vec<Option<ArcOf>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionOp {
+    Union,
+    Difference,
+    Intersection,
+    Xor,
+}
+
+impl Region {
+    /// Область из ломаных; обход контуров приводится к нужному. `None`, если внешний
+    /// контур вырожден.
+    pub fn new(outer: Vec<Pt2>, holes: Vec<Vec<Pt2>>) -> Option<Self> {
+        Self::tagged(
+            (outer, vec![]),
+            holes.into_iter().map(|h| (h, vec![])).collect(),
+        )
+    }
+
+    /// Область по ломаным, собранным из отрезков аппроксимации `kinds` (с допуском
+    /// `tol`): рёбра, лежащие на дуге или окружности, получают её окружность.
+    pub(crate) fn traced(
+        outer: Vec<Pt2>,
+        holes: Vec<Vec<Pt2>>,
+        kinds: &[EntityKind],
+        tol: f32,
+    ) -> Option<Self> {
+        let arcs: Vec<(Vec<Pt2>, ArcOf)> = kinds
+            .iter()
+            .flat_map(curve_pieces)
+            .filter_map(|(p, a)| Some((p, a?)))
+            .collect();
+        let trace = |l: Vec<Pt2>| {
+            let tags = trace_arcs(&l, &arcs, tol);
+            (l, tags)
+        };
+        Self::tagged(trace(outer), holes.into_iter().map(trace).collect())
+    }
+
+    fn tagged(outer: (Vec<Pt2>, Tags), holes: Vec<(Vec<Pt2>, Tags)>) -> Option<Self> {
+        let (outer, outer_arcs) = oriented(outer, true)?;
+        let (holes, hole_arcs): (Vec<_>, Vec<_>) =
+            holes.into_iter().filter_map(|h| oriented(h, false)).unzip();
+        Some(Self {
+            outer,
+            holes,
+            arcs: std::iter::once(outer_arcs).chain(hole_arcs).collect(),
+        })
+    }
+
+    /// Область, ограниченная одной замкнутой кривой.
+    pub fn from_curve(kind: &EntityKind) -> Option<Self> {
+        Self::from_curves(std::slice::from_ref(kind), &[])
+    }
+
+    /// Внешний контур и отверстия — цепочки кривых «конец к началу» (направление
+    /// каждой кривой любое). `None`, если внешняя цепочка не замкнута.
+    pub fn from_curves(outer: &[EntityKind], holes: &[Vec<EntityKind>]) -> Option<Self> {
+        let outer = curve_loop(outer)?;
+        let holes = holes.iter().filter_map(|h| curve_loop(h)).collect();
+        Self::tagged(outer, holes)
+    }
+
+    /// Площадь за вычетом отверстий; дуговые рёбра — точно, по своим окружностям.
+    pub fn area(&self) -> f64 {
+        self.loop_props().map(|p| p.area).sum()
+    }
+
+    /// Центр тяжести; `None` у вырожденной области.
+    pub fn centroid(&self) -> Option<Pt2> {
+        let (mut a, mut sx, mut sy) = (0.0, 0.0, 0.0);
+        for p in self.loop_props() {
+            a += p.area;
+            sx += p.area * p.centroid.x as f64;
+            sy += p.area * p.centroid.y as f64;
+        }
+        (a.abs() > f64::EPSILON).then(|| Pt2::new((sx / a) as f32, (sy / a) as f32))
+    }
+
+    /// Точка внутри области (в отверстиях — нет).
+    pub fn contains(&self, p: Pt2) -> bool {
+        point_in_polygon(p, &self.outer) && !self.holes.iter().any(|h| point_in_polygon(p, h))
+    }
+
+    pub fn union(&self, other: &Region) -> Vec<Region> {
+        region_boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(other),
+            RegionOp::Union,
+        )
+    }
+
+    pub fn difference(&self, other: &Region) -> Vec<Region> {
+        region_boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(other),
+            RegionOp::Difference,
+        )
+    }
+
+    pub fn intersection(&self, other: &Region) -> Vec<Region> {
+        region_boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(other),
+            RegionOp::Intersection,
+        )
+    }
+
+    pub fn xor(&self, other: &Region) -> Vec<Region> {
+        region_boolean(
+            std::slice::from_ref(self),
+            std::slice::from_ref(other),
+            RegionOp::Xor,
+        )
+    }
+
+    /// Контуры замкнутыми полилиниями: сначала внешний, затем отверстия. Участки
+    /// дуг — сегменты с выпуклостью.
+    pub fn to_entities(&self) -> Vec<EntityKind> {
+        (0..=self.holes.len())
+            .map(|k| {
+                let (pts, bulges) = self.bulged(k);
+                EntityKind::Polyline {
+                    pts,
+                    closed: true,
+                    bulges,
+                }
+            })
+            .collect()
+    }
+
+    /// Контуры для штриховки: внешний и отверстия.
+    pub fn hatch_loops(&self) -> Vec<HatchLoop> {
+        (0..=self.holes.len())
+            .map(|k| {
+                let (pts, bulges) = self.bulged(k);
+                HatchLoop::polyline(pts, bulges)
+            })
+            .collect()
+    }
+
+    /// Экструзия по внешнему контуру; отверстия `ElementGeom::Extrusion` не поддерживает.
+    pub fn extrusion(&self, height: f32) -> ElementGeom {
+        let mut profile = self.outer.clone();
+        profile.push(self.outer[0]);
+        ElementGeom::Extrusion { profile, height }
+    }
+
+    /// Плоская грань в плоскости Z=0 — для выдавливания.
+    #[cfg(feature = "cryxtal-brep")]
+    pub fn to_face(&self) -> cryxtal_modeling::topology::Face {
+        use crate::truck_bridge::{planar_face_from_wires, wire_from_closed_polyline};
+        let holes: Vec<_> = self
+            .holes
+            .iter()
+            .map(|h| wire_from_closed_polyline(h))
+            .collect();
+        planar_face_from_wires(&wire_from_closed_polyline(&self.outer), &holes)
+    }
+
+    /// Площади контуров со знаком обхода (отверстия — с минусом) и их центры.
+    fn loop_props(&self) -> impl Iterator<Item = AreaProps> {
+        self.to_entities()
+            .into_iter()
+            .filter_map(|k| loop_props(std::slice::from_ref(&k)))
+    }
+
+    fn loops(&self) -> impl Iterator<Item = &[Pt2]> {
+        std::iter::once(&self.outer[..]).chain(self.holes.iter().map(|h| &h[..]))
+    }
+
+    /// Окружности рёбер контура `k` (0 — внешний) по числу его вершин.
+    fn tags(&self, k: usize) -> Tags {
+        let n = if k == 0 {
+            self.outer.len()
+        } else {
+            self.holes[k - 1].len()
+        };
+        let mut t = self.arcs.get(k).cloned().unwrap_or_default();
+        t.resize(n, None);
+        t
+    }
+
+    /// Контур `k` вершинами с выпуклостями: подряд идущие рёбра одной окружности
+    /// сливаются в дуговой сегмент не больше четверти окружности.
+    fn bulged(&self, k: usize) -> (Vec<Pt2>, Vec<f32>) {
+        let l = if k == 0 {
+            &self.outer
+        } else {
+            &self.holes[k - 1]
+        };
+        let tags = self.tags(k);
+        let n = l.len();
+        let tag = |i: usize| tags[i % n];
+        // начинаем на границе дуги, чтобы не разрезать её
+        let start = (0..n).find(|&i| tag(i + n - 1) != tag(i)).unwrap_or(0);
+        let (mut pts, mut bulges) = (Vec::new(), Vec::new());
+        let mut i = 0;
+        while i < n {
+            pts.push(l[(start + i) % n]);
+            let Some(arc) = tag(start + i) else {
+                bulges.push(0.0);
+                i += 1;
+                continue;
+            };
+            let c = arc.center;
+            let angle = |p: Pt2| (p.y - c.y).atan2(p.x - c.x);
+            let mut sweep = 0.0f32;
+            let mut j = i;
+            while j < n && tag(start + j) == Some(arc) {
+                let (p, q) = (l[(start + j) % n], l[(start + j + 1) % n]);
+                let d = angle(q) - angle(p);
+                let d = d - TAU * (d / TAU).round();
+                if j > i && (sweep + d).abs() > FRAC_PI_2 + 1e-3 {
+                    break;
+                }
+                sweep += d;
+                j += 1;
+            }
+            bulges.push((sweep / 4.0).tan());
+            i = j;
+        }
+        (pts, bulges)
+    }
+}
+
+/// Области из замкнутых кривых: вложенность по чётности глубины, как у заливки штриховки —
+/// чётная глубина даёт внешний контур, нечётная — его отверстие.
+pub fn regions_from_curves(kinds: &[EntityKind]) -> Vec<Region> {
+    let loops: Vec<(Vec<Pt2>, Tags)> = kinds
+        .iter()
+        .filter_map(|k| curve_loop(std::slice::from_ref(k)))
+        .collect();
+    let inside = |i: usize, j: usize| i != j && point_in_polygon(loops[i].0[0], &loops[j].0);
+    let depth: Vec<usize> = (0..loops.len())
+        .map(|i| (0..loops.len()).filter(|&j| inside(i, j)).count())
+        .collect();
+    (0..loops.len())
+        .filter(|&i| depth[i].is_multiple_of(2))
+        .filter_map(|i| {
+            let holes = (0..loops.len())
+                .filter(|&j| depth[j] == depth[i] + 1 && inside(j, i))
+                .map(|j| loops[j].clone())
+                .collect();
+            Region::tagged(loops[i].clone(), holes)
+        })
+        .collect()
+}
+
+/// Объединение набора областей (например, пятен стен).
+pub fn union_all(regions: &[Region]) -> Vec<Region> {
+    regions.iter().fold(Vec::new(), |acc, r| {
+        region_boolean(&acc, std::slice::from_ref(r), RegionOp::Union)
+    })
+}
+
+/// Булева операция над наборами областей. Области внутри каждого набора не должны
+/// перекрываться.
+pub fn region_boolean(a: &[Region], b: &[Region], op: RegionOp) -> Vec<Region> {
+    match op {
+        RegionOp::Xor => {
+            let ab = region_boolean(a, b, RegionOp::Difference);
+            let ba = region_boolean(b, a, RegionOp::Difference);
+            region_boolean(&ab, &ba, RegionOp::Union)
+        }
+        _ => clip(a, b, op),
+    }
+}
+
+// --------------------------- контуры ---------------------------
+
+/// Кривая → куски ломаной; у дуговых кусков — их окружность.
+fn curve_pieces(kind: &EntityKind) -> Vec<(Vec<Pt2>, Option<ArcOf>)> {
+    match kind {
+        EntityKind::Arc { center, radius, .. } | EntityKind::Circle { center, radius } => {
+            let arc = ArcOf {
+                center: *center,
+                radius: *radius,
+            };
+            vec![(curve_points(kind), Some(arc))]
+        }
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } if bulges.iter().any(|b| *b != 0.0) => polyline_segments(pts, bulges, *closed)
+            .iter()
+            .flat_map(curve_pieces)
+            .collect(),
+        _ => vec![(curve_points(kind), None)],
+    }
+}
+
+/// Окружности рёбер замкнутой ломаной: ребро дуговое, если оба конца и середина лежат
+/// на аппроксимации одной из дуг `arcs`.
+fn trace_arcs(l: &[Pt2], arcs: &[(Vec<Pt2>, ArcOf)], tol: f32) -> Tags {
+    let size = l
+        .iter()
+        .fold(1.0f32, |m, p| m.max(p.x.abs()).max(p.y.abs()));
+    let tol = tol.max(1e-4 * size);
+    let seg_dist = |p: Pt2, a: Pt2, b: Pt2| {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let len2 = dx * dx + dy * dy;
+        let t = if len2 > 0.0 {
+            (((p.x - a.x) * dx + (p.y - a.y) * dy) / len2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (p.x - a.x - t * dx).hypot(p.y - a.y - t * dy)
+    };
+    let on = |p: Pt2, samples: &[Pt2]| samples.windows(2).any(|w| seg_dist(p, w[0], w[1]) <= tol);
+    (0..l.len())
+        .map(|j| {
+            let (a, b) = (l[j], l[(j + 1) % l.len()]);
+            let m = Pt2::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+            arcs.iter()
+                .find(|(s, _)| [a, b, m].iter().all(|&p| on(p, s)))
+                .map(|(_, arc)| *arc)
+        })
+        .collect()
+}
+
+/// Замкнутая цепочка кривых → ломаная без повтора первой точки и окружности её рёбер.
+fn curve_loop(edges: &[EntityKind]) -> Option<(Vec<Pt2>, Tags)> {
+    let parts: Vec<(Vec<Pt2>, Option<ArcOf>)> = edges
+        .iter()
+        .flat_map(curve_pieces)
+        .filter(|(p, _)| p.len() > 1)
+        .collect();
+    let size = parts
+        .iter()
+        .flat_map(|(p, _)| p)
+        .fold(1.0f32, |m, p| m.max(p.x.abs()).max(p.y.abs()));
+    let tol = 1e-4 * size;
+    let near = |a: Pt2, b: Pt2| (a.x - b.x).hypot(a.y - b.y) <= tol;
+    let dist = |a: Pt2, b: Pt2| (a.x - b.x).hypot(a.y - b.y);
+
+    let mut out: Vec<Pt2> = Vec::new();
+    let mut tags = Vec::new();
+    for (i, (part, arc)) in parts.iter().enumerate() {
+        let (s, e) = (part[0], part[part.len() - 1]);
+        let forward = match out.last() {
+            None => match parts.get(i + 1) {
+                // первую кривую разворачиваем к следующей
+                Some((n, _)) => {
+                    let (ns, ne) = (n[0], n[n.len() - 1]);
+                    dist(e, ns).min(dist(e, ne)) <= dist(s, ns).min(dist(s, ne))
+                }
+                None => true,
+            },
+            Some(&last) => {
+                if !near(last, s) && !near(last, e) {
+                    return None;
+                }
+                dist(last, s) <= dist(last, e)
+            }
+        };
+        let mut pts = part.clone();
+        if !forward {
+            pts.reverse();
+        }
+        for p in pts {
+            if out.last().is_none_or(|q| !near(*q, p)) {
+                if !out.is_empty() {
+                    tags.push(*arc);
+                }
+                out.push(p);
+            }
+        }
+    }
+    if out.len() < 2 || !near(out[0], out[out.len() - 1]) {
+        return None;
+    }
+    out.pop();
+    (out.len() >= 3).then_some((out, tags))
+}
+
+fn signed_area(poly: &[Pt2]) -> f64 {
+    let n = poly.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (poly[i], poly[(i + 1) % n]);
+            a.x as f64 * b.y as f64 - b.x as f64 * a.y as f64
+        })
+        .sum::<f64>()
+        * 0.5
+}
+
+/// Ломаная с обходом против часовой (`ccw`) или по часовой; `None` у вырожденной.
+/// Окружности рёбер следуют за вершинами.
+fn oriented((poly, mut tags): (Vec<Pt2>, Tags), ccw: bool) -> Option<(Vec<Pt2>, Tags)> {
+    tags.resize(poly.len(), None);
+    let (mut pts, mut arcs): (Vec<Pt2>, Tags) = (Vec::new(), Vec::new());
+    for (p, t) in poly.into_iter().zip(tags) {
+        // у ребра нулевой длины окружность берёт следующее
+        match arcs.last_mut() {
+            Some(last) if pts.last() == Some(&p) => *last = t,
+            _ => {
+                pts.push(p);
+                arcs.push(t);
+            }
+        }
+    }
+    if pts.len() > 1 && pts[0] == pts[pts.len() - 1] {
+        pts.pop();
+        arcs.pop();
+    }
+    let a = signed_area(&pts);
+    if pts.len() < 3 || a == 0.0 {
+        return None;
+    }
+    if (a > 0.0) != ccw {
+        // ребро i развёрнутого контура — ребро n−2−i исходного, замыкающее остаётся
+        pts.reverse();
+        let closing = arcs.pop();
+        arcs.reverse();
+        arcs.extend(closing);
+    }
+    Some((pts, arcs))
+}
+
+// --------------------------- отсечение ---------------------------
+
+type P = [f64; 2];
+
+fn sub(a: P, b: P) -> P {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn cross(a: P, b: P) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn dot(a: P, b: P) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+/// Общие вершины: точки ближе `eps` сливаются в одну, чтобы совпадающие рёбра
+/// обеих областей совпадали и по номерам вершин.
+struct Pool {
+    pts: Vec<P>,
+    grid: HashMap<(i64, i64), Vec<usize>>,
+    eps: f64,
+}
+
+impl Pool {
+    fn cell(&self, p: P) -> (i64, i64) {
+        (
+            (p[0] / self.eps).floor() as i64,
+            (p[1] / self.eps).floor() as i64,
+        )
+    }
+
+    fn id(&mut self, p: P) -> usize {
+        let (cx, cy) = self.cell(p);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for &i in self.grid.get(&(cx + dx, cy + dy)).into_iter().flatten() {
+                    let d = sub(self.pts[i], p);
+                    if dot(d, d) <= self.eps * self.eps {
+                        return i;
+                    }
+                }
+            }
+        }
+        self.pts.push(p);
+        self.grid
+            .entry((cx, cy))
+            .or_default()
+            .push(self.pts.len() - 1);
+        self.pts.len() - 1
+    }
+}
+
+/// Положение ребра одной области относительно другой.
+#[derive(PartialEq)]
+enum Side {
+    Inside,
+    Outside,
+    /// Совпадает с ребром другой области в том же направлении.
+    Same,
+    /// Совпадает встречно.
+    Opposite,
+}
+
+type Edge = (usize, usize);
+
+fn clip(a: &[Region], b: &[Region], op: RegionOp) -> Vec<Region> {
+    let tagged = |rs: &[Region]| -> Vec<(Vec<P>, Tags)> {
+        rs.iter()
+            .flat_map(|r| {
+                r.loops().enumerate().map(|(k, l)| {
+                    (
+                        l.iter().map(|p| [p.x as f64, p.y as f64]).collect(),
+                        r.tags(k),
+                    )
+                })
+            })
+            .collect()
+    };
+    let (ta, tb) = (tagged(a), tagged(b));
+    let la: Vec<Vec<P>> = ta.iter().map(|(l, _)| l.clone()).collect();
+    let lb: Vec<Vec<P>> = tb.iter().map(|(l, _)| l.clone()).collect();
+    let size = la
+        .iter()
+        .chain(&lb)
+        .flatten()
+        .fold(1.0f64, |m, p| m.max(p[0].abs()).max(p[1].abs()));
+    let mut pool = Pool {
+        pts: Vec::new(),
+        grid: HashMap::new(),
+        eps: 1e-6 * size,
+    };
+    let mut edges = |ls: &[(Vec<P>, Tags)]| -> Vec<(Edge, Option<ArcOf>)> {
+        let mut out = Vec::new();
+        for (l, tags) in ls {
+            let ids: Vec<usize> = l.iter().map(|p| pool.id(*p)).collect();
+            for i in 0..ids.len() {
+                let (s, e) = (ids[i], ids[(i + 1) % ids.len()]);
+                if s != e {
+                    out.push(((s, e), tags[i]));
+                }
+            }
+        }
+        out
+    };
+    let (ea, eb) = (edges(&ta), edges(&tb));
+
+    // разрезы в точках пересечения и касания
+    let mut cuts_a = vec![Vec::new(); ea.len()];
+    let mut cuts_b = vec![Vec::new(); eb.len()];
+    for (i, &((a0, a1), _)) in ea.iter().enumerate() {
+        for (j, &((b0, b1), _)) in eb.iter().enumerate() {
+            let (ca, cb) = crossings(&mut pool, (a0, a1), (b0, b1));
+            cuts_a[i].extend(ca);
+            cuts_b[j].extend(cb);
+        }
+    }
+    let sa = split(&pool.pts, &ea, cuts_a);
+    let sb = split(&pool.pts, &eb, cuts_b);
+
+    let set_a: HashSet<Edge> = sa.iter().map(|(e, _)| *e).collect();
+    let set_b: HashSet<Edge> = sb.iter().map(|(e, _)| *e).collect();
+    let side = |e: Edge, other: &HashSet<Edge>, loops: &[Vec<P>]| {
+        if other.contains(&e) {
+            return Side::Same;
+        }
+        if other.contains(&(e.1, e.0)) {
+            return Side::Opposite;
+        }
+        let (p, q) = (pool.pts[e.0], pool.pts[e.1]);
+        let m = Pt2::new(((p[0] + q[0]) * 0.5) as f32, ((p[1] + q[1]) * 0.5) as f32);
+        let inside = loops.iter().filter(|l| point_in(m, l)).count() % 2 == 1;
+        if inside {
+            Side::Inside
+        } else {
+            Side::Outside
+        }
+    };
+
+    let mut kept = Vec::new();
+    for &(e, arc) in &sa {
+        let s = side(e, &set_b, &lb);
+        let keep = match op {
+            RegionOp::Union => s == Side::Outside || s == Side::Same,
+            RegionOp::Intersection => s == Side::Inside || s == Side::Same,
+            RegionOp::Difference => s == Side::Outside || s == Side::Opposite,
+            RegionOp::Xor => unreachable!(),
+        };
+        if keep {
+            kept.push((e, arc));
+        }
+    }
+    for &(e, arc) in &sb {
+        match (op, side(e, &set_a, &la)) {
+            (RegionOp::Union, Side::Outside) | (RegionOp::Intersection, Side::Inside) => {
+                kept.push((e, arc))
+            }
+            (RegionOp::Difference, Side::Inside) => kept.push(((e.1, e.0), arc)),
+            _ => {}
+        }
+    }
+    assemble(&pool, &kept)
+}
+
+fn point_in(p: Pt2, l: &[P]) -> bool {
+    let mut inside = false;
+    let n = l.len();
+    let (x, y) = (p.x as f64, p.y as f64);
+    for i in 0..n {
+        let (a, b) = (l[i], l[(i + n - 1) % n]);
+        if (a[1] > y) != (b[1] > y) && x < a[0] + (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1]) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Точки, в которых надо разрезать рёбра `a` и `b`: концы одного на другом и
+/// собственное пересечение.
+fn crossings(pool: &mut Pool, a: (usize, usize), b: (usize, usize)) -> (Vec<usize>, Vec<usize>) {
+    let eps = pool.eps;
+    let (p0, p1, q0, q1) = (pool.pts[a.0], pool.pts[a.1], pool.pts[b.0], pool.pts[b.1]);
+    let bbox = |u: P, v: P| {
+        (
+            [u[0].min(v[0]), u[1].min(v[1])],
+            [u[0].max(v[0]), u[1].max(v[1])],
+        )
+    };
+    let ((alo, ahi), (blo, bhi)) = (bbox(p0, p1), bbox(q0, q1));
+    if alo[0] > bhi[0] + eps
+        || blo[0] > ahi[0] + eps
+        || alo[1] > bhi[1] + eps
+        || blo[1] > ahi[1] + eps
+    {
+        return (vec![], vec![]);
+    }
+    let on = |q: P, s: P, e: P| {
+        let d = sub(e, s);
+        let len2 = dot(d, d);
+        let t = dot(sub(q, s), d) / len2;
+        t > 0.0 && t < 1.0 && cross(d, sub(q, s)).abs() <= eps * len2.sqrt()
+    };
+    let (mut ca, mut cb) = (Vec::new(), Vec::new());
+    for (id, q) in [(b.0, q0), (b.1, q1)] {
+        if on(q, p0, p1) {
+            ca.push(id);
+        }
+    }
+    for (id, p) in [(a.0, p0), (a.1, p1)] {
+        if on(p, q0, q1) {
+            cb.push(id);
+        }
+    }
+    let (r, s) = (sub(p1, p0), sub(q1, q0));
+    let d = cross(r, s);
+    if d.abs() > 1e-12 * dot(r, r).sqrt() * dot(s, s).sqrt() {
+        let w = sub(q0, p0);
+        let (t, u) = (cross(w, s) / d, cross(w, r) / d);
+        if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+            let x = pool.id([p0[0] + r[0] * t, p0[1] + r[1] * t]);
+            if ![a.0, a.1].contains(&x) {
+                ca.push(x);
+            }
+            if ![b.0, b.1].contains(&x) {
+                cb.push(x);
+            }
+        }
+    }
+    (ca, cb)
+}
+
+/// Рёбра, разрезанные в точках `cuts` по порядку вдоль каждого; куски наследуют
+/// окружность ребра.
+fn split(
+    pts: &[P],
+    edges: &[(Edge, Option<ArcOf>)],
+    cuts: Vec<Vec<usize>>,
+) -> Vec<(Edge, Option<ArcOf>)> {
+    let mut out = Vec::new();
+    for (&((s, e), arc), mut c) in edges.iter().zip(cuts) {
+        let d = sub(pts[e], pts[s]);
+        c.retain(|&i| i != s && i != e);
+        c.sort_by(|&i, &j| dot(sub(pts[i], pts[s]), d).total_cmp(&dot(sub(pts[j], pts[s]), d)));
+        c.dedup();
+        let chain: Vec<usize> = std::iter::once(s)
+            .chain(c)
+            .chain(std::iter::once(e))
+            .collect();
+        out.extend(chain.windows(2).map(|w| ((w[0], w[1]), arc)));
+    }
+    out
+}
+
+/// Сборка отобранных рёбер в контуры и контуров — в области. В вершинах касания
+/// берётся самый левый поворот, так что касающиеся контуры расходятся.
+fn assemble(pool: &Pool, tagged: &[(Edge, Option<ArcOf>)]) -> Vec<Region> {
+    let pts = &pool.pts;
+    let edges: Vec<Edge> = tagged.iter().map(|(e, _)| *e).collect();
+    let mut from: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, e) in edges.iter().enumerate() {
+        from.entry(e.0).or_default().push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut loops: Vec<(Vec<P>, Tags)> = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        let start = edges[first].0;
+        let mut ids = vec![start];
+        let mut arcs = Vec::new();
+        let mut cur = first;
+        let closed = loop {
+            used[cur] = true;
+            arcs.push(tagged[cur].1);
+            let (prev, v) = edges[cur];
+            if v == start {
+                break true;
+            }
+            ids.push(v);
+            let din = sub(pts[v], pts[prev]);
+            let next = from
+                .get(&v)
+                .into_iter()
+                .flatten()
+                .filter(|&&i| !used[i])
+                .max_by(|&&i, &&j| {
+                    let turn = |k: usize| {
+                        let dout = sub(pts[edges[k].1], pts[v]);
+                        let t = cross(din, dout).atan2(dot(din, dout));
+                        if t >= std::f64::consts::PI - 1e-12 {
+                            -t
+                        } else {
+                            t
+                        }
+                    };
+                    turn(i).total_cmp(&turn(j))
+                });
+            match next {
+                Some(&n) => cur = n,
+                None => break false,
+            }
+        };
+        if closed {
+            let l = simplify(ids.iter().map(|&i| pts[i]).collect(), arcs, pool.eps);
+            if l.0.len() >= 3 {
+                loops.push(l);
+            }
+        }
+    }
+
+    let area = |l: &[P]| {
+        let n = l.len();
+        (0..n).map(|i| cross(l[i], l[(i + 1) % n])).sum::<f64>() * 0.5
+    };
+    let min_area = pool.eps * pool.eps;
+    let to_pt = |l: &[P]| -> Vec<Pt2> {
+        l.iter()
+            .map(|p| Pt2::new(p[0] as f32, p[1] as f32))
+            .collect()
+    };
+    let (outers, holes): (Vec<_>, Vec<_>) = loops
+        .into_iter()
+        .map(|(l, t)| (area(&l), l, t))
+        .filter(|(a, _, _)| a.abs() > min_area)
+        .partition(|(a, _, _)| *a > 0.0);
+    let mut regions: Vec<Region> = outers
+        .iter()
+        .map(|(_, l, t)| Region {
+            outer: to_pt(l),
+            holes: vec![],
+            arcs: vec![t.clone()],
+        })
+        .collect();
+    for (_, h, t) in &holes {
+        // проба слева от ребра отверстия — в материале владеющей области
+        let (p, q) = (h[0], h[1]);
+        let d = sub(q, p);
+        let probe = Pt2::new(
+            ((p[0] + q[0]) * 0.5 - d[1] * 1e-3) as f32,
+            ((p[1] + q[1]) * 0.5 + d[0] * 1e-3) as f32,
+        );
+        let owner = outers
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, o, _))| point_in(probe, o))
+            .min_by(|(_, (a, _, _)), (_, (b, _, _))| a.total_cmp(b));
+        if let Some((i, _)) = owner {
+            regions[i].holes.push(to_pt(h));
+            regions[i].arcs.push(t.clone());
+        }
+    }
+    regions
+}
+
+/// Убирает промежуточные вершины на прямых участках. Слитое ребро остаётся дуговым,
+/// только если обе половины лежали на одной окружности.
+fn simplify(mut l: Vec<P>, mut arcs: Tags, eps: f64) -> (Vec<P>, Tags) {
+    loop {
+        let n = l.len();
+        if n < 3 {
+            return (l, arcs);
+        }
+        let keep: Vec<bool> = (0..n)
+            .map(|i| {
+                let (a, b, c) = (l[(i + n - 1) % n], l[i], l[(i + 1) % n]);
+                let (u, v) = (sub(b, a), sub(c, b));
+                cross(u, v).abs() > eps * dot(sub(c, a), sub(c, a)).sqrt() || dot(u, v) <= 0.0
+            })
+            .collect();
+        if keep.iter().all(|k| *k) {
+            return (l, arcs);
+        }
+        // соседние вершины за один проход не убираем — иначе срежется и угол
+        let mut drop = vec![false; n];
+        for i in 0..n {
+            drop[i] = !keep[i] && (i == 0 || !drop[i - 1]);
+        }
+        if drop[n - 1] && drop[0] {
+            drop[n - 1] = false;
+        }
+        let (mut pts, mut tags): (Vec<P>, Tags) = (Vec::new(), Vec::new());
+        for i in 0..n {
+            if !drop[i] {
+                pts.push(l[i]);
+                tags.push(arcs[i]);
+            }
+        }
+        // ребро убранной вершины сливается с входящим в неё
+        for i in (0..n).filter(|&i| drop[i]) {
+            let k = (0..n).filter(|&j| !drop[j] && j < i).count();
+            let into = if k == 0 { tags.len() - 1 } else { k - 1 };
+            if tags[into] != arcs[i] {
+                tags[into] = None;
+            }
+        }
+        l = pts;
+        arcs = tags;
+    }
+}
@@ -0,0 +1,153 @@
+// cad-core/src/section.rs
+//! Секущая плоскость разреза: контуры сечений тел и точки пересечения арматуры.
+//!
+//! Тела режутся сеткой [`Element3D::triangulate`], поэтому одинаково работают
+//! экструзии, сетки, трубы и B-Rep (с фичей `cryxtal-brep`). Вершина на самой
+//! плоскости относится к отброшенной стороне — так контур не двоится.
+
+use crate::model3d::{apply_xform, Element3D, Pt3, RebarPath};
+use crate::projection::{cross, dot, element_mesh, sub, V3};
+use crate::Section;
+use cryxtal_geometry::prelude::*;
+use std::collections::HashMap;
+
+/// Точек на NURBS-стержень при поиске пересечений.
+const REBAR_SAMPLES: usize = 64;
+
+impl Section {
+    /// Точка и единичная нормаль плоскости; `None`, если нормаль не задана.
+    pub(crate) fn plane(&self) -> Option<(V3, V3)> {
+        let n = [self.normal.x, self.normal.y, self.normal.z].map(f64::from);
+        let len = dot(n, n).sqrt();
+        let o = [self.origin.x, self.origin.y, self.origin.z].map(f64::from);
+        (len > 1e-9).then(|| (o, n.map(|c| c / len)))
+    }
+
+    /// Оси вида разреза: вправо, вверх (к +Z, для горизонтального разреза —
+    /// к +Y) и к наблюдателю по нормали.
+    pub fn axes(&self) -> Option<[[f64; 3]; 3]> {
+        let (_, d) = self.plane()?;
+        let along = |v: V3| {
+            let p = sub(v, d.map(|c| c * dot(v, d)));
+            let len = dot(p, p).sqrt();
+            (len > 1e-6).then(|| p.map(|c| c / len))
+        };
+        let up = along([0.0, 0.0, 1.0]).or_else(|| along([0.0, 1.0, 0.0]))?;
+        Some([cross(up, d), up, d])
+    }
+
+    /// Замкнутые контуры сечения элемента в мире. Незамкнутые цепочки
+    /// (открытая сетка) отбрасываются.
+    pub fn cut_element(&self, el: &Element3D) -> Vec<Vec<Pt3>> {
+        let Some((o, n)) = self.plane() else {
+            return Vec::new();
+        };
+        let m = element_mesh(el);
+        let s: Vec<f64> = m.pts.iter().map(|&p| dot(sub(p, o), n)).collect();
+        let side = |i: usize| s[i] >= 0.0;
+
+        // отрезок сечения в каждой пересечённой грани: пара рёбер сетки
+        let mut segs: Vec<[(usize, usize); 2]> = Vec::new();
+        for t in &m.tris {
+            let ends: Vec<(usize, usize)> = (0..3)
+                .map(|i| (t[i], t[(i + 1) % 3]))
+                .filter(|&(a, b)| side(a) != side(b))
+                .map(|(a, b)| (a.min(b), a.max(b)))
+                .collect();
+            if let [e0, e1] = ends[..] {
+                segs.push([e0, e1]);
+            }
+        }
+        let mut at: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (i, sg) in segs.iter().enumerate() {
+            for k in sg {
+                at.entry(*k).or_default().push(i);
+            }
+        }
+        let point = |(a, b): (usize, usize)| {
+            let t = s[a] / (s[a] - s[b]);
+            let (p, q) = (m.pts[a], m.pts[b]);
+            let c = |i: usize| (p[i] + (q[i] - p[i]) * t) as f32;
+            Pt3::new(c(0), c(1), c(2))
+        };
+
+        let mut used = vec![false; segs.len()];
+        let mut loops = Vec::new();
+        for start in 0..segs.len() {
+            if used[start] {
+                continue;
+            }
+            used[start] = true;
+            let first = segs[start][0];
+            let mut cur = segs[start][1];
+            let mut keys = vec![first];
+            let closed = loop {
+                if cur == first {
+                    break true;
+                }
+                keys.push(cur);
+                let Some(j) = at[&cur].iter().copied().find(|&j| !used[j]) else {
+                    break false;
+                };
+                used[j] = true;
+                cur = if segs[j][0] == cur {
+                    segs[j][1]
+                } else {
+                    segs[j][0]
+                };
+            };
+            let mut pts: Vec<Pt3> = keys.into_iter().map(point).collect();
+            // вершина на плоскости даёт одну точку с двух рёбер
+            pts.dedup();
+            if pts.len() > 1 && pts.first() == pts.last() {
+                pts.pop();
+            }
+            if closed && pts.len() >= 3 {
+                loops.push(pts);
+            }
+        }
+        loops
+    }
+
+    /// Пересечения стержней элемента с плоскостью: центр и диаметр, мм.
+    /// Пути стержней — в координатах элемента.
+    pub fn cut_rebars(&self, el: &Element3D) -> Vec<(Pt3, f32)> {
+        let Some((o, n)) = self.plane() else {
+            return Vec::new();
+        };
+        let mut out = Vec::new();
+        for r in &el.rebars {
+            let pts: Vec<V3> = rebar_points(&r.path)
+                .into_iter()
+                .map(|p| apply_xform(p, el.xform).map(f64::from))
+                .collect();
+            for w in pts.windows(2) {
+                let (sa, sb) = (dot(sub(w[0], o), n), dot(sub(w[1], o), n));
+                if (sa >= 0.0) != (sb >= 0.0) {
+                    let t = sa / (sa - sb);
+                    let c = |i: usize| (w[0][i] + (w[1][i] - w[0][i]) * t) as f32;
+                    out.push((Pt3::new(c(0), c(1), c(2)), r.diameter_mm));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Ось стержня ломаной; NURBS — равномерной выборкой по параметру.
+pub(crate) fn rebar_points(path: &RebarPath) -> Vec<[f32; 3]> {
+    match path {
+        RebarPath::Polyline(pts) => pts.iter().map(|p| [p.x, p.y, p.z]).collect(),
+        RebarPath::Nurbs { ctrl_pts, .. } if ctrl_pts.len() < 2 => Vec::new(),
+        RebarPath::Nurbs { .. } => {
+            let curve = path.to_bspline();
+            let (t0, t1) = curve.range_tuple();
+            (0..=REBAR_SAMPLES)
+                .map(|i| {
+                    let p = curve.subs(t0 + (t1 - t0) * i as f64 / REBAR_SAMPLES as f64);
+                    [p.x as f32, p.y as f32, p.z as f32]
+                })
+                .collect()
+        }
+    }
+}
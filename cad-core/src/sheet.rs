@@ -1,10 +1,18 @@
 // cad-core/src/sheet.rs
 //! 2D-листы: вьюпорты (проекции 3D), аннотации и экспорт.
 
-use super::model3d::Id;
-use crate::Pt2;
+use super::model3d::{Id, Project3D, Pt3};
+use crate::{
+    add_view_layers, make_dimension, make_polyline, make_text, project_viewport, DimKind, DimPoint,
+    Document, Entity, EntityKind, Layer, LineWeight, Pt2, Table,
+};
 use serde::{Deserialize, Serialize};
 
+/// Слой рамок видовых экранов на листе.
+pub const SHEET_VIEWPORT_LAYER: &str = "VIEWPORTS";
+/// Зазор между частями таблицы, перенесёнными вправо, мм.
+const TABLE_GAP_MM: f32 = 10.0;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Sheet {
     pub id: Id,
@@ -42,10 +50,20 @@ pub enum ViewKind {
     Iso,     // изометрия
 }
 
+/// Секущая плоскость разреза. Старые файлы без плоскости (нулевая нормаль)
+/// дают обычный вид.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Section {
-    pub origin_mm: Pt2, // точка на листе для позиционирования/оси
-                        // позже: мировая плоскость/направление, локальная матрица вида и т.п.
+    pub origin_mm: Pt2, // точка на листе, куда попадает `origin`
+    /// Точка плоскости в мире, мм.
+    #[serde(default)]
+    pub origin: Pt3,
+    /// Нормаль плоскости — к наблюдателю, в сторону отброшенной части.
+    #[serde(default)]
+    pub normal: Pt3,
+    /// Глубина вида за плоскостью, мм (0 — без ограничения).
+    #[serde(default)]
+    pub depth: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,12 +87,134 @@ pub enum Annot {
         text: String,
         h_mm: f32,
     },
+    /// Таблица с левым верхним углом `origin_mm`; выше `max_height_mm`
+    /// (если задано) продолжается правее с повтором шапки.
     Table {
         origin_mm: Pt2,
-        rows: Vec<Vec<String>>,
+        #[serde(flatten)]
+        table: Table,
+        #[serde(default)]
+        max_height_mm: f32,
     },
     Block {
         name: String,
         at_mm: Pt2,
     }, // штамп
 }
+
+impl Sheet {
+    /// Лист как чертёж в мм бумаги (начало — левый нижний угол): рамки видовых
+    /// экранов, проекции `project` в них и аннотации. Блоки штампов и типы
+    /// линий берутся из `model`.
+    pub fn to_document(&self, model: &Document, project: &Project3D) -> Document {
+        let mut doc = Document {
+            blocks: model.blocks.clone(),
+            linetypes: model.linetypes.clone(),
+            dim_styles: model.dim_styles.clone(),
+            ..Document::new()
+        };
+        doc.add_layer(Layer {
+            lineweight: LineWeight::Width(18),
+            ..Layer::new(SHEET_VIEWPORT_LAYER)
+        });
+        for vp in &self.viewports {
+            let r = vp.rect_mm;
+            let pts = vec![
+                Pt2::new(r.x, r.y),
+                Pt2::new(r.x + r.w, r.y),
+                Pt2::new(r.x + r.w, r.y + r.h),
+                Pt2::new(r.x, r.y + r.h),
+            ];
+            let _ = make_polyline(&mut doc, pts, true, SHEET_VIEWPORT_LAYER);
+        }
+        add_view_layers(&mut doc);
+        for vp in &self.viewports {
+            for e in project_viewport(project, vp) {
+                doc.add_entity(e);
+            }
+        }
+        for a in self
+            .viewports
+            .iter()
+            .flat_map(|v| &v.annots)
+            .chain(&self.annots)
+        {
+            a.draw(&mut doc);
+        }
+        doc
+    }
+}
+
+impl Annot {
+    /// Аннотация сущностями чертежа в мм листа на слое "0".
+    fn draw(&self, doc: &mut Document) {
+        match self {
+            Annot::Text {
+                pos_mm,
+                content,
+                h_mm,
+            } => {
+                make_text(doc, *pos_mm, content.clone(), *h_mm, "0");
+            }
+            Annot::DimLinear { a_mm, b_mm, off_mm } => {
+                // размерная линия — слева от направления a→b
+                let (dx, dy) = (b_mm.x - a_mm.x, b_mm.y - a_mm.y);
+                let len = dx.hypot(dy).max(1e-6);
+                let at = Pt2::new(
+                    (a_mm.x + b_mm.x) / 2.0 - dy / len * off_mm,
+                    (a_mm.y + b_mm.y) / 2.0 + dx / len * off_mm,
+                );
+                let pts = vec![DimPoint::free(*a_mm), DimPoint::free(*b_mm)];
+                let _ = make_dimension(doc, DimKind::Aligned, pts, at, "0");
+            }
+            Annot::DimRadius { c_mm, p_mm } => {
+                let pts = vec![DimPoint::free(*c_mm), DimPoint::free(*p_mm)];
+                let _ = make_dimension(doc, DimKind::Radius, pts, *p_mm, "0");
+            }
+            Annot::Leader { pts_mm, text, h_mm } => {
+                let _ = make_polyline(doc, pts_mm.clone(), false, "0");
+                if let Some(p) = pts_mm.last() {
+                    let at = Pt2::new(p.x + h_mm * 0.3, p.y + h_mm * 0.3);
+                    make_text(doc, at, text.clone(), *h_mm, "0");
+                }
+            }
+            Annot::Table {
+                origin_mm,
+                table,
+                max_height_mm,
+            } => {
+                let mut at = *origin_mm;
+                for (i, part) in table.split(*max_height_mm).into_iter().enumerate() {
+                    let width = part.layout().width();
+                    if i > 0 {
+                        let h = part.text_height;
+                        let label = Pt2::new(at.x, at.y + h);
+                        make_text(doc, label, "Продолжение таблицы", h, "0");
+                    }
+                    doc.add_entity(Entity::new(
+                        "0",
+                        EntityKind::Table {
+                            pos: at,
+                            table: part,
+                        },
+                    ));
+                    at.x += width + TABLE_GAP_MM;
+                }
+            }
+            Annot::Block { name, at_mm } => {
+                if doc.block(name).is_some() {
+                    doc.add_entity(Entity::new(
+                        "0",
+                        EntityKind::Insert {
+                            block: name.clone(),
+                            pos: *at_mm,
+                            scale: Pt2::new(1.0, 1.0),
+                            rotation: 0.0,
+                            attribs: vec![],
+                        },
+                    ));
+                }
+            }
+        }
+    }
+}
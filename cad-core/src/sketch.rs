@@ -0,0 +1,746 @@
+//! Геометрические зависимости эскиза: совпадение, горизонтальность, параллельность,
+//! касание, размеры и т.п. на отрезках, дугах, окружностях и вершинах полилиний
+//! документа. Все параметры участвующих сущностей — неизвестные одной системы
+//! невязок, которая решается демпфированным методом Ньютона (Левенберг — Марквардт).
+//! `cryxtal_base::newton::solve` здесь не подходит: он работает с векторами
+//! фиксированной размерности и квадратным обратимым якобианом, а у эскиза переменных
+//! и уравнений сколько угодно и не поровну. Поэтому цикл итераций свой.
+//! После решения по рангу якобиана считаются степени свободы, лишние и
+//! невыполнимые зависимости.
+
+use crate::{DimLink, DimSnap, Document, EntityKind, Pt2};
+use anyhow::{anyhow, Result};
+use cryxtal_base::newton::CalcOutput;
+use cryxtal_base::tolerance::TOLERANCE;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+use std::ops::Range;
+
+/// Предел итераций решателя.
+const MAX_ITER: usize = 200;
+
+/// Прямолинейный участок: отрезок (`seg` = 0) или сегмент `seg` полилинии.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LineRef {
+    pub entity: u64,
+    #[serde(default)]
+    pub seg: usize,
+}
+
+impl LineRef {
+    pub fn line(entity: u64) -> Self {
+        Self { entity, seg: 0 }
+    }
+
+    pub fn seg(entity: u64, seg: usize) -> Self {
+        Self { entity, seg }
+    }
+}
+
+/// Зависимость эскиза. Точки задаются как у размеров (`DimLink`): концы, середина
+/// отрезка, центр, вершины полилинии. Окружностями считаются Circle и Arc.
+/// Длины в единицах чертежа, углы в радианах.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Constraint {
+    Coincident(DimLink, DimLink),
+    Horizontal(LineRef),
+    Vertical(LineRef),
+    Parallel(LineRef, LineRef),
+    Perpendicular(LineRef, LineRef),
+    /// Прямая касается окружности
+    Tangent(LineRef, u64),
+    /// Окружности касаются (внешне или внутренне — как расположены сейчас)
+    TangentCircles(u64, u64),
+    /// Равные длины участков
+    EqualLength(LineRef, LineRef),
+    /// Равные радиусы
+    EqualRadius(u64, u64),
+    Distance(DimLink, DimLink, f32),
+    /// Расстояние от точки до прямой участка
+    PointLineDistance(DimLink, LineRef, f32),
+    Radius(u64, f32),
+    /// Угол от первого участка ко второму, против часовой
+    Angle(LineRef, LineRef, f32),
+    /// Точка остаётся, где была до решения
+    Fixed(DimLink),
+}
+
+/// Именованный набор зависимостей.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Sketch {
+    pub name: String,
+    pub constraints: Vec<Constraint>,
+}
+
+/// Итог решения эскиза.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveReport {
+    /// Все зависимости выполнены и ни один участок или окружность не стянуты в точку
+    pub converged: bool,
+    pub iterations: usize,
+    /// Наибольшая невязка (длина)
+    pub residual: f64,
+    /// Оставшиеся степени свободы
+    pub dof: usize,
+    /// Зависимости, ничего не добавившие к остальным (индексы в списке)
+    pub redundant: Vec<usize>,
+    /// Невыполненные зависимости — противоречат остальным или выполнены только
+    /// стягиванием участка (окружности) в точку
+    pub unsatisfied: Vec<usize>,
+    /// Сущности, которые ещё можно двигать, не нарушая зависимостей
+    pub free: Vec<u64>,
+}
+
+/// Определённость эскиза.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SketchStatus {
+    WellConstrained,
+    UnderConstrained,
+    /// Противоречивые или лишние зависимости
+    OverConstrained,
+}
+
+impl SolveReport {
+    pub fn status(&self) -> SketchStatus {
+        if !self.unsatisfied.is_empty() || !self.redundant.is_empty() {
+            SketchStatus::OverConstrained
+        } else if self.dof > 0 {
+            SketchStatus::UnderConstrained
+        } else {
+            SketchStatus::WellConstrained
+        }
+    }
+}
+
+impl Sketch {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            constraints: vec![],
+        }
+    }
+
+    /// Добавить зависимость, вернуть её индекс.
+    pub fn add(&mut self, c: Constraint) -> usize {
+        self.constraints.push(c);
+        self.constraints.len() - 1
+    }
+
+    /// Подогнать сущности документа под зависимости. Ошибка — ссылка на
+    /// несуществующую сущность или точку; невыполнимость видна в отчёте, а
+    /// документ тогда не меняется.
+    pub fn solve(&self, doc: &mut Document) -> Result<SolveReport> {
+        solve_constraints(doc, &self.constraints)
+    }
+}
+
+/// Решить зависимости `cs` на сущностях документа. Если решение сошлось,
+/// изменённые сущности записываются обратно и привязанные к ним размеры
+/// обновляются; частичное решение в документ не попадает.
+pub fn solve_constraints(doc: &mut Document, cs: &[Constraint]) -> Result<SolveReport> {
+    let sys = System::build(doc, cs)?;
+    let mut x = sys.x0.clone();
+    let (converged, iterations) = sys.levenberg_marquardt(&mut x);
+    let report = sys.report(&x, converged, iterations);
+    if report.converged {
+        let changed = sys.write_back(doc, &x);
+        crate::relink_dimensions(doc, Some(&changed), &[]);
+    }
+    Ok(report)
+}
+
+// --------------------------- переменные ---------------------------
+
+#[derive(Debug, Clone, Copy)]
+enum Geo {
+    /// ax, ay, bx, by
+    Line,
+    /// cx, cy, r
+    Circle,
+    /// cx, cy, r, a0, a1
+    Arc,
+    /// x0, y0, x1, y1, …
+    Poly { n: usize, closed: bool },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    id: u64,
+    geo: Geo,
+    off: usize,
+    len: usize,
+}
+
+struct System {
+    slots: Vec<Slot>,
+    by_id: HashMap<u64, usize>,
+    cs: Vec<Constraint>,
+    /// Положения закреплённых точек и вид касания окружностей
+    fixed: Vec<(f64, f64)>,
+    internal: Vec<bool>,
+    x0: Vec<f64>,
+    /// Характерный размер: им масштабируются угловые невязки и допуск
+    scale: f64,
+}
+
+type P = (f64, f64);
+/// Невязки и якобиан по строкам.
+type Eval = CalcOutput<Vec<f64>, Vec<Vec<f64>>>;
+
+fn sub(a: P, b: P) -> P {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn cross(a: P, b: P) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: P, b: P) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn norm(a: P) -> f64 {
+    a.0.hypot(a.1)
+}
+
+/// Угол в (−π, π].
+fn wrap(a: f64) -> f64 {
+    let a = a.rem_euclid(TAU);
+    if a > PI {
+        a - TAU
+    } else {
+        a
+    }
+}
+
+fn ids_of(c: &Constraint) -> Vec<u64> {
+    use Constraint::*;
+    match *c {
+        Coincident(p, q) | Distance(p, q, _) => vec![p.entity, q.entity],
+        Horizontal(l) | Vertical(l) => vec![l.entity],
+        Parallel(a, b) | Perpendicular(a, b) | EqualLength(a, b) | Angle(a, b, _) => {
+            vec![a.entity, b.entity]
+        }
+        Tangent(l, c) => vec![l.entity, c],
+        TangentCircles(a, b) | EqualRadius(a, b) => vec![a, b],
+        PointLineDistance(p, l, _) => vec![p.entity, l.entity],
+        Radius(c, _) => vec![c],
+        Fixed(p) => vec![p.entity],
+    }
+}
+
+/// Участки и окружности, на которые ссылается зависимость.
+fn shapes_of(c: &Constraint) -> (Vec<LineRef>, Vec<u64>) {
+    use Constraint::*;
+    match *c {
+        Horizontal(l) | Vertical(l) | PointLineDistance(_, l, _) => (vec![l], vec![]),
+        Parallel(a, b) | Perpendicular(a, b) | EqualLength(a, b) | Angle(a, b, _) => {
+            (vec![a, b], vec![])
+        }
+        Tangent(l, c) => (vec![l], vec![c]),
+        TangentCircles(a, b) | EqualRadius(a, b) => (vec![], vec![a, b]),
+        Radius(c, _) => (vec![], vec![c]),
+        Coincident(..) | Distance(..) | Fixed(_) => (vec![], vec![]),
+    }
+}
+
+impl System {
+    fn build(doc: &Document, cs: &[Constraint]) -> Result<Self> {
+        let mut sys = System {
+            slots: vec![],
+            by_id: HashMap::new(),
+            cs: cs.to_vec(),
+            fixed: vec![],
+            internal: vec![],
+            x0: vec![],
+            scale: 1.0,
+        };
+        for c in cs {
+            for id in ids_of(c) {
+                if sys.by_id.contains_key(&id) {
+                    continue;
+                }
+                let e = doc
+                    .entity(id)
+                    .ok_or_else(|| anyhow!("Entity {id} not found"))?;
+                let f = |p: Pt2| [p.x as f64, p.y as f64];
+                let (geo, vals): (Geo, Vec<f64>) = match &e.kind {
+                    EntityKind::LineSeg { a, b } => (Geo::Line, [f(*a), f(*b)].concat()),
+                    EntityKind::Circle { center, radius } => {
+                        (Geo::Circle, [&f(*center)[..], &[*radius as f64]].concat())
+                    }
+                    EntityKind::Arc {
+                        center,
+                        radius,
+                        start_angle,
+                        end_angle,
+                    } => (
+                        Geo::Arc,
+                        [
+                            &f(*center)[..],
+                            &[*radius as f64, *start_angle as f64, *end_angle as f64],
+                        ]
+                        .concat(),
+                    ),
+                    EntityKind::Polyline { pts, closed, .. } if pts.len() >= 2 => (
+                        Geo::Poly {
+                            n: pts.len(),
+                            closed: *closed,
+                        },
+                        pts.iter().flat_map(|p| f(*p)).collect(),
+                    ),
+                    _ => return Err(anyhow!("Entity {id} cannot take sketch constraints")),
+                };
+                sys.by_id.insert(id, sys.slots.len());
+                sys.slots.push(Slot {
+                    id,
+                    geo,
+                    off: sys.x0.len(),
+                    len: vals.len(),
+                });
+                sys.x0.extend(vals);
+            }
+        }
+        sys.scale = 1.0 + sys.x0.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+
+        // проверка ссылок и исходные данные зависимостей
+        let x = sys.x0.clone();
+        for c in cs {
+            use Constraint::*;
+            match *c {
+                Coincident(p, q) | Distance(p, q, _) => {
+                    sys.point(&x, p)?;
+                    sys.point(&x, q)?;
+                }
+                Horizontal(l) | Vertical(l) => {
+                    sys.line(&x, l)?;
+                }
+                Parallel(a, b) | Perpendicular(a, b) | EqualLength(a, b) | Angle(a, b, _) => {
+                    sys.line(&x, a)?;
+                    sys.line(&x, b)?;
+                }
+                Tangent(l, k) => {
+                    sys.line(&x, l)?;
+                    sys.circle(&x, k)?;
+                }
+                TangentCircles(a, b) | EqualRadius(a, b) => {
+                    sys.circle(&x, a)?;
+                    sys.circle(&x, b)?;
+                }
+                PointLineDistance(p, l, _) => {
+                    sys.point(&x, p)?;
+                    sys.line(&x, l)?;
+                }
+                Radius(k, _) => {
+                    sys.circle(&x, k)?;
+                }
+                Fixed(p) => {
+                    sys.point(&x, p)?;
+                }
+            }
+            sys.fixed.push(match *c {
+                Fixed(p) => sys.point(&x, p)?,
+                _ => (0.0, 0.0),
+            });
+            sys.internal.push(match *c {
+                TangentCircles(a, b) => {
+                    let ((ca, ra), (cb, rb)) = (sys.circle(&x, a)?, sys.circle(&x, b)?);
+                    norm(sub(ca, cb)) < ra.max(rb)
+                }
+                _ => false,
+            });
+        }
+        Ok(sys)
+    }
+
+    fn slot(&self, id: u64) -> Result<Slot> {
+        self.by_id
+            .get(&id)
+            .map(|&i| self.slots[i])
+            .ok_or_else(|| anyhow!("Entity {id} not in sketch"))
+    }
+
+    fn point(&self, x: &[f64], p: DimLink) -> Result<P> {
+        let s = self.slot(p.entity)?;
+        let v = &x[s.off..s.off + s.len];
+        let at = |i: usize| (v[2 * i], v[2 * i + 1]);
+        let on = |a: f64| (v[0] + v[2] * a.cos(), v[1] + v[2] * a.sin());
+        let pt = match (s.geo, p.snap) {
+            (Geo::Line, DimSnap::Start) => Some(at(0)),
+            (Geo::Line, DimSnap::End) => Some(at(1)),
+            (Geo::Line, DimSnap::Mid) => Some(((v[0] + v[2]) / 2.0, (v[1] + v[3]) / 2.0)),
+            (Geo::Circle | Geo::Arc, DimSnap::Center) => Some(at(0)),
+            (Geo::Arc, DimSnap::Start) => Some(on(v[3])),
+            (Geo::Arc, DimSnap::End) => Some(on(v[4])),
+            (Geo::Arc, DimSnap::Mid) => Some(on((v[3] + v[4]) / 2.0)),
+            (Geo::Poly { .. }, DimSnap::Start) => Some(at(0)),
+            (Geo::Poly { n, .. }, DimSnap::End) => Some(at(n - 1)),
+            (Geo::Poly { n, .. }, DimSnap::Vertex(i)) if i < n => Some(at(i)),
+            _ => None,
+        };
+        pt.ok_or_else(|| anyhow!("Entity {} has no {:?} point", p.entity, p.snap))
+    }
+
+    fn line(&self, x: &[f64], l: LineRef) -> Result<(P, P)> {
+        let s = self.slot(l.entity)?;
+        let v = &x[s.off..s.off + s.len];
+        let at = |i: usize| (v[2 * i], v[2 * i + 1]);
+        match s.geo {
+            Geo::Line if l.seg == 0 => Ok((at(0), at(1))),
+            Geo::Poly { n, closed } if l.seg + 1 < n || (closed && l.seg + 1 == n) => {
+                Ok((at(l.seg), at((l.seg + 1) % n)))
+            }
+            _ => Err(anyhow!("Entity {} has no segment {}", l.entity, l.seg)),
+        }
+    }
+
+    fn circle(&self, x: &[f64], id: u64) -> Result<(P, f64)> {
+        let s = self.slot(id)?;
+        let v = &x[s.off..s.off + s.len];
+        match s.geo {
+            Geo::Circle | Geo::Arc => Ok(((v[0], v[1]), v[2])),
+            _ => Err(anyhow!("Entity {id} is not a circle or arc")),
+        }
+    }
+
+    /// Невязки зависимости `k`. Ссылки проверены при сборке.
+    fn residual(&self, k: usize, x: &[f64], out: &mut Vec<f64>) {
+        use Constraint::*;
+        let pt = |p| self.point(x, p).unwrap();
+        let ln = |l| self.line(x, l).unwrap();
+        let dir = |l| {
+            let (a, b) = ln(l);
+            sub(b, a)
+        };
+        let circ = |c| self.circle(x, c).unwrap();
+        // угловые невязки переводятся в длины
+        let l = self.scale;
+        match self.cs[k] {
+            Coincident(p, q) => {
+                let d = sub(pt(p), pt(q));
+                out.extend([d.0, d.1]);
+            }
+            Horizontal(a) => out.push(dir(a).1),
+            Vertical(a) => out.push(dir(a).0),
+            Parallel(a, b) => {
+                let (u, v) = (dir(a), dir(b));
+                out.push(l * cross(u, v) / (norm(u) * norm(v)).max(1e-300));
+            }
+            Perpendicular(a, b) => {
+                let (u, v) = (dir(a), dir(b));
+                out.push(l * dot(u, v) / (norm(u) * norm(v)).max(1e-300));
+            }
+            Tangent(a, c) => {
+                let (p, q) = ln(a);
+                let (o, r) = circ(c);
+                let d = sub(q, p);
+                out.push((cross(d, sub(o, p)) / norm(d).max(1e-300)).abs() - r);
+            }
+            TangentCircles(a, b) => {
+                let ((ca, ra), (cb, rb)) = (circ(a), circ(b));
+                let d = norm(sub(ca, cb));
+                out.push(if self.internal[k] {
+                    d - (ra - rb).abs()
+                } else {
+                    d - (ra + rb)
+                });
+            }
+            EqualLength(a, b) => out.push(norm(dir(a)) - norm(dir(b))),
+            EqualRadius(a, b) => out.push(circ(a).1 - circ(b).1),
+            Distance(p, q, v) => out.push(norm(sub(pt(p), pt(q))) - v as f64),
+            PointLineDistance(p, a, v) => {
+                let (s, e) = ln(a);
+                let d = sub(e, s);
+                let h = cross(d, sub(pt(p), s)) / norm(d).max(1e-300);
+                out.push(h.abs() - v as f64);
+            }
+            Radius(c, v) => out.push(circ(c).1 - v as f64),
+            Angle(a, b, v) => {
+                let (u, w) = (dir(a), dir(b));
+                out.push(l * wrap(cross(u, w).atan2(dot(u, w)) - v as f64));
+            }
+            Fixed(p) => {
+                let d = sub(pt(p), self.fixed[k]);
+                out.extend([d.0, d.1]);
+            }
+        }
+    }
+
+    /// Переменные, от которых зависит зависимость `k`.
+    fn vars(&self, k: usize) -> Vec<usize> {
+        let mut out = Vec::new();
+        for id in ids_of(&self.cs[k]) {
+            let s = self.slots[self.by_id[&id]];
+            if !out.contains(&s.off) {
+                out.extend(s.off..s.off + s.len);
+            }
+        }
+        out
+    }
+
+    /// Невязки и якобиан (по строкам), производные — центральными разностями
+    /// только по своим переменным каждой зависимости. `rows[k]` — строки зависимости `k`.
+    fn eval(&self, x: &[f64]) -> (Eval, Vec<Range<usize>>) {
+        let n = x.len();
+        let mut value = Vec::new();
+        let mut derivation = Vec::new();
+        let mut rows = Vec::new();
+        let mut xp = x.to_vec();
+        let (mut plus, mut minus) = (Vec::new(), Vec::new());
+        for k in 0..self.cs.len() {
+            let start = value.len();
+            self.residual(k, x, &mut value);
+            let m = value.len() - start;
+            let mut jk = vec![vec![0.0; n]; m];
+            for i in self.vars(k) {
+                let h = 1e-7 * (1.0 + x[i].abs());
+                xp[i] = x[i] + h;
+                plus.clear();
+                self.residual(k, &xp, &mut plus);
+                xp[i] = x[i] - h;
+                minus.clear();
+                self.residual(k, &xp, &mut minus);
+                xp[i] = x[i];
+                for (r, row) in jk.iter_mut().enumerate() {
+                    row[i] = (plus[r] - minus[r]) / (2.0 * h);
+                }
+            }
+            derivation.extend(jk);
+            rows.push(start..start + m);
+        }
+        (CalcOutput { value, derivation }, rows)
+    }
+
+    fn residuals(&self, x: &[f64]) -> Vec<f64> {
+        let mut out = Vec::new();
+        for k in 0..self.cs.len() {
+            self.residual(k, x, &mut out);
+        }
+        out
+    }
+
+    fn tol(&self) -> f64 {
+        TOLERANCE * self.scale * 1e-2
+    }
+
+    /// Левенберг — Марквардт: демпфированные шаги, пока невязки не уложатся в
+    /// допуск. Стоп и при шаге, который не уменьшает невязку ни при каком λ.
+    /// Возвращает (сошлось, число шагов).
+    fn levenberg_marquardt(&self, x: &mut Vec<f64>) -> (bool, usize) {
+        let tol = self.tol();
+        let max_abs = |r: &[f64]| r.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+        let mut r = self.residuals(x);
+        let mut lambda = 1e-3;
+        for it in 0..MAX_ITER {
+            if max_abs(&r) <= tol {
+                return (true, it);
+            }
+            let Some((next, rn)) = self.damped_step(x, &r, &mut lambda) else {
+                return (false, it);
+            };
+            *x = next;
+            r = rn;
+        }
+        (max_abs(&r) <= tol, MAX_ITER)
+    }
+
+    /// Шаг (JᵀJ + λI)δ = −Jᵀr: λ уменьшается после удачного шага и растёт, пока
+    /// стоимость не упадёт. Демпфирование единичной матрицей даёт шаг наименьшей
+    /// длины: незакреплённая геометрия сдвигается как можно меньше. `None`, если
+    /// уменьшить стоимость не удалось.
+    fn damped_step(&self, x: &[f64], r: &[f64], lambda: &mut f64) -> Option<(Vec<f64>, Vec<f64>)> {
+        let n = x.len();
+        let cost = |r: &[f64]| r.iter().map(|v| v * v).sum::<f64>();
+        let (CalcOutput { derivation: j, .. }, _) = self.eval(x);
+        let mut a = vec![vec![0.0; n]; n];
+        let mut g = vec![0.0; n];
+        for (row, &ri) in j.iter().zip(r) {
+            let nz: Vec<usize> = (0..n).filter(|&i| row[i] != 0.0).collect();
+            for &p in &nz {
+                g[p] += row[p] * ri;
+                for &q in &nz {
+                    a[p][q] += row[p] * row[q];
+                }
+            }
+        }
+        let top = (0..n).fold(0.0f64, |m, i| m.max(a[i][i]));
+        while *lambda <= 1e12 {
+            let mut damped = a.clone();
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += *lambda * top.max(1e-12);
+            }
+            let step = solve_spd(damped, g.iter().map(|v| -v).collect());
+            let next: Vec<f64> = x.iter().zip(&step).map(|(a, b)| a + b).collect();
+            let rn = self.residuals(&next);
+            if cost(&rn) < cost(r) {
+                *lambda = (*lambda / 3.0).max(1e-12);
+                return Some((next, rn));
+            }
+            *lambda *= 4.0;
+        }
+        None
+    }
+
+    /// Ранг якобиана по зависимостям: строки по очереди ортогонализуются к уже
+    /// принятым; зависимость без новых направлений — лишняя.
+    fn report(&self, x: &[f64], converged: bool, iterations: usize) -> SolveReport {
+        let (
+            CalcOutput {
+                value: r,
+                derivation: j,
+            },
+            rows,
+        ) = self.eval(x);
+        let n = x.len();
+        let mut basis: Vec<Vec<f64>> = Vec::new();
+        let mut redundant = Vec::new();
+        let mut unsatisfied = Vec::new();
+        for (k, range) in rows.iter().enumerate() {
+            let mut added = false;
+            for ri in range.clone() {
+                let mut v = j[ri].clone();
+                let len0 = v.iter().map(|a| a * a).sum::<f64>().sqrt();
+                if len0 == 0.0 {
+                    continue;
+                }
+                for b in &basis {
+                    let d: f64 = v.iter().zip(b).map(|(a, b)| a * b).sum();
+                    v.iter_mut().zip(b).for_each(|(a, b)| *a -= d * b);
+                }
+                let len = v.iter().map(|a| a * a).sum::<f64>().sqrt();
+                if len > 1e-6 * len0 {
+                    v.iter_mut().for_each(|a| *a /= len);
+                    basis.push(v);
+                    added = true;
+                }
+            }
+            if !added {
+                redundant.push(k);
+            }
+            if r[range.clone()].iter().any(|v| v.abs() > self.tol()) || self.collapses(k, x) {
+                unsatisfied.push(k);
+            }
+        }
+        // переменная определена, если её орт лежит в пространстве строк
+        let free: Vec<u64> = self
+            .slots
+            .iter()
+            .filter(|s| {
+                (s.off..s.off + s.len).any(|i| {
+                    let proj: f64 = basis.iter().map(|b| b[i] * b[i]).sum();
+                    proj < 1.0 - 1e-6
+                })
+            })
+            .map(|s| s.id)
+            .collect();
+        SolveReport {
+            converged: converged && unsatisfied.is_empty(),
+            iterations,
+            residual: r.iter().fold(0.0f64, |m, v| m.max(v.abs())),
+            dof: n - basis.len(),
+            redundant,
+            unsatisfied,
+            free,
+        }
+    }
+
+    /// Зависимость `k` выполнена стягиванием в точку участка или окружности,
+    /// которые до решения были ненулевыми (например, горизонтальность и
+    /// вертикальность одного отрезка).
+    fn collapses(&self, k: usize, x: &[f64]) -> bool {
+        // невязки по осям в допуске дают длину до √2 допуска
+        let tol = 2.0 * self.tol();
+        let len = |x: &[f64], l| {
+            let (a, b) = self.line(x, l).unwrap();
+            norm(sub(b, a))
+        };
+        let radius = |x: &[f64], c| self.circle(x, c).unwrap().1.abs();
+        let (lines, circles) = shapes_of(&self.cs[k]);
+        lines
+            .iter()
+            .any(|&l| len(x, l) <= tol && len(&self.x0, l) > tol)
+            || circles
+                .iter()
+                .any(|&c| radius(x, c) <= tol && radius(&self.x0, c) > tol)
+    }
+
+    /// Записать решение в документ. Возвращает id изменённых сущностей.
+    fn write_back(&self, doc: &mut Document, x: &[f64]) -> Vec<u64> {
+        let mut changed = Vec::new();
+        for s in &self.slots {
+            let v = &x[s.off..s.off + s.len];
+            let p = |i: usize| Pt2::new(v[2 * i] as f32, v[2 * i + 1] as f32);
+            let Some(e) = doc.entity(s.id) else {
+                continue;
+            };
+            let kind = match (&e.kind, s.geo) {
+                (EntityKind::LineSeg { .. }, Geo::Line) => EntityKind::LineSeg { a: p(0), b: p(1) },
+                (EntityKind::Circle { .. }, Geo::Circle) => EntityKind::Circle {
+                    center: p(0),
+                    radius: v[2].abs() as f32,
+                },
+                (EntityKind::Arc { .. }, Geo::Arc) => EntityKind::Arc {
+                    center: p(0),
+                    radius: v[2].abs() as f32,
+                    start_angle: v[3] as f32,
+                    end_angle: v[4] as f32,
+                },
+                (EntityKind::Polyline { closed, bulges, .. }, Geo::Poly { n, .. }) => {
+                    EntityKind::Polyline {
+                        pts: (0..n).map(p).collect(),
+                        closed: *closed,
+                        bulges: bulges.clone(),
+                    }
+                }
+                _ => continue,
+            };
+            if kind != e.kind {
+                if let Some(e) = doc.entity_mut(s.id) {
+                    e.kind = kind;
+                    changed.push(s.id);
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Решение симметричной положительно определённой системы (Холецкий).
+/// Вырожденные направления дают нулевой шаг.
+fn solve_spd(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for k in 0..n {
+        let d = a[k][k] - (0..k).map(|i| a[k][i] * a[k][i]).sum::<f64>();
+        if d <= 1e-300 {
+            for row in a.iter_mut().skip(k) {
+                row[k] = 0.0;
+            }
+            continue;
+        }
+        let d = d.sqrt();
+        a[k][k] = d;
+        for i in k + 1..n {
+            let s = a[i][k] - (0..k).map(|j| a[i][j] * a[k][j]).sum::<f64>();
+            a[i][k] = s / d;
+        }
+    }
+    // L·y = b, затем Lᵀ·x = y
+    for i in 0..n {
+        if a[i][i] == 0.0 {
+            b[i] = 0.0;
+            continue;
+        }
+        let s = b[i] - (0..i).map(|j| a[i][j] * b[j]).sum::<f64>();
+        b[i] = s / a[i][i];
+    }
+    for i in (0..n).rev() {
+        if a[i][i] == 0.0 {
+            b[i] = 0.0;
+            continue;
+        }
+        let s = b[i] - (i + 1..n).map(|j| a[j][i] * b[j]).sum::<f64>();
+        b[i] = s / a[i][i];
+    }
+    b
+}
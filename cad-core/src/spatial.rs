@@ -0,0 +1,470 @@
+//! Пространственный индекс документа: R-дерево рамок сущностей для выбора,
+//! объектных привязок и выбора рамкой. Индекс — кэш: рамки пересчитываются для
+//! сущностей, помеченных изменёнными, при `Document::refresh_index`.
+
+use crate::store::Generation;
+use crate::{polyline_segments, Document, Entity, EntityKind, Pt2};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+/// Прямоугольная рамка в мировых координатах.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bbox {
+    pub min: Pt2,
+    pub max: Pt2,
+}
+
+impl Bbox {
+    pub fn new(a: Pt2, b: Pt2) -> Self {
+        Self {
+            min: Pt2::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Pt2::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    /// Квадрат со стороной `2·r` вокруг точки.
+    pub fn around(p: Pt2, r: f32) -> Self {
+        Self {
+            min: Pt2::new(p.x - r, p.y - r),
+            max: Pt2::new(p.x + r, p.y + r),
+        }
+    }
+
+    pub fn union(&self, o: &Bbox) -> Bbox {
+        Bbox {
+            min: Pt2::new(self.min.x.min(o.min.x), self.min.y.min(o.min.y)),
+            max: Pt2::new(self.max.x.max(o.max.x), self.max.y.max(o.max.y)),
+        }
+    }
+
+    pub fn add(&self, p: Pt2) -> Bbox {
+        self.union(&Bbox { min: p, max: p })
+    }
+
+    pub fn intersects(&self, o: &Bbox) -> bool {
+        self.min.x <= o.max.x
+            && o.min.x <= self.max.x
+            && self.min.y <= o.max.y
+            && o.min.y <= self.max.y
+    }
+
+    pub fn contains(&self, o: &Bbox) -> bool {
+        self.min.x <= o.min.x
+            && self.min.y <= o.min.y
+            && o.max.x <= self.max.x
+            && o.max.y <= self.max.y
+    }
+
+    fn area(&self) -> f64 {
+        (self.max.x - self.min.x) as f64 * (self.max.y - self.min.y) as f64
+    }
+
+    fn center(&self, axis: usize) -> f32 {
+        if axis == 0 {
+            self.min.x + self.max.x
+        } else {
+            self.min.y + self.max.y
+        }
+    }
+}
+
+fn points_bounds(pts: impl IntoIterator<Item = Pt2>) -> Option<Bbox> {
+    pts.into_iter()
+        .filter(|p| p.x.is_finite() && p.y.is_finite())
+        .fold(None, |b: Option<Bbox>, p| {
+            Some(b.map_or(Bbox { min: p, max: p }, |b| b.add(p)))
+        })
+}
+
+/// Рамка дуги против часовой от `s` до `e` вместе с центром.
+fn arc_bounds(c: Pt2, r: f32, s: f32, e: f32) -> Option<Bbox> {
+    let e = if e < s {
+        e + TAU * ((s - e) / TAU).ceil()
+    } else {
+        e
+    };
+    let at = |t: f32| Pt2::new(c.x + r * t.cos(), c.y + r * t.sin());
+    let first = (s / FRAC_PI_2).ceil() as i32;
+    let quads = (first..)
+        .map(|k| k as f32 * FRAC_PI_2)
+        .take_while(|&t| t <= e)
+        .take(5)
+        .map(at);
+    points_bounds([c, at(s), at(e)].into_iter().chain(quads))
+}
+
+/// Рамка геометрии, не требующей документа. Включает центры дуг — к ним привязываются.
+/// Для вставок и размеров — `None`, см. [`entity_bounds`].
+pub fn kind_bounds(kind: &EntityKind) -> Option<Bbox> {
+    match kind {
+        EntityKind::LineSeg { a, b } => Some(Bbox::new(*a, *b)),
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => arc_bounds(*center, *radius, *start_angle, *end_angle),
+        EntityKind::Circle { center, radius } => Some(Bbox::around(*center, radius.abs())),
+        // консервативно — по описанной окружности
+        EntityKind::Ellipse {
+            center,
+            major,
+            ratio,
+            ..
+        } => {
+            let r = major.x.hypot(major.y) * ratio.abs().max(1.0);
+            Some(Bbox::around(*center, r))
+        }
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => polyline_segments(pts, bulges, *closed)
+            .iter()
+            .filter_map(kind_bounds)
+            .chain(points_bounds(pts.iter().copied()))
+            .reduce(|a, b| a.union(&b)),
+        // кривая лежит в выпуклой оболочке управляющих точек
+        EntityKind::NurbsCurve2D { ctrl_pts, .. } => points_bounds(ctrl_pts.iter().copied()),
+        EntityKind::Text { pos, .. } => Some(Bbox {
+            min: *pos,
+            max: *pos,
+        }),
+        EntityKind::Hatch { loops, .. } => loops
+            .iter()
+            .flat_map(|l| &l.edges)
+            .filter_map(kind_bounds)
+            .reduce(|a, b| a.union(&b)),
+        EntityKind::Insert { .. } | EntityKind::Dimension { .. } | EntityKind::Table { .. } => None,
+    }
+}
+
+/// Рамка сущности; вставки и размеры — по развёрнутой геометрии (и точке вставки).
+pub fn entity_bounds(doc: &Document, e: &Entity) -> Option<Bbox> {
+    if !e.kind.is_block_ref() {
+        return kind_bounds(&e.kind);
+    }
+    let pos = match &e.kind {
+        EntityKind::Insert { pos, .. } | EntityKind::Table { pos, .. } => points_bounds([*pos]),
+        _ => None,
+    };
+    doc.insert_geometry(e)
+        .iter()
+        .filter_map(|s| kind_bounds(&s.kind))
+        .chain(pos)
+        .reduce(|a, b| a.union(&b))
+}
+
+// --------------------------- R-дерево ---------------------------
+
+/// Наибольшее число детей узла.
+const MAX_FILL: usize = 16;
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf(Vec<(Bbox, u64)>),
+    Inner(Vec<(Bbox, Node)>),
+}
+
+impl Node {
+    fn bbox(&self) -> Option<Bbox> {
+        match self {
+            Node::Leaf(v) => v.iter().map(|c| c.0).reduce(|a, b| a.union(&b)),
+            Node::Inner(v) => v.iter().map(|c| c.0).reduce(|a, b| a.union(&b)),
+        }
+    }
+
+    /// Вставка; при переполнении узел делится и возвращается отделённая половина.
+    fn insert(&mut self, b: Bbox, id: u64) -> Option<Node> {
+        match self {
+            Node::Leaf(items) => {
+                items.push((b, id));
+                (items.len() > MAX_FILL).then(|| Node::Leaf(split_half(items)))
+            }
+            Node::Inner(kids) => {
+                // ребёнок с наименьшим приростом площади, при равенстве — меньший
+                let i = (0..kids.len())
+                    .min_by(|&i, &j| {
+                        let grow = |k: usize| {
+                            let a = kids[k].0.area();
+                            (kids[k].0.union(&b).area() - a, a)
+                        };
+                        let (gi, ai) = grow(i);
+                        let (gj, aj) = grow(j);
+                        gi.total_cmp(&gj).then(ai.total_cmp(&aj))
+                    })
+                    .expect("внутренний узел без детей");
+                kids[i].0 = kids[i].0.union(&b);
+                let sib = kids[i].1.insert(b, id)?;
+                if let Some(bb) = kids[i].1.bbox() {
+                    kids[i].0 = bb;
+                }
+                let sb = sib.bbox().expect("пустая половина узла");
+                kids.push((sb, sib));
+                (kids.len() > MAX_FILL).then(|| Node::Inner(split_half(kids)))
+            }
+        }
+    }
+
+    fn remove(&mut self, b: &Bbox, id: u64) -> bool {
+        match self {
+            Node::Leaf(items) => match items.iter().position(|c| c.1 == id) {
+                Some(i) => {
+                    items.swap_remove(i);
+                    true
+                }
+                None => false,
+            },
+            Node::Inner(kids) => {
+                for i in 0..kids.len() {
+                    if kids[i].0.contains(b) && kids[i].1.remove(b, id) {
+                        match kids[i].1.bbox() {
+                            Some(bb) => kids[i].0 = bb,
+                            None => {
+                                kids.swap_remove(i);
+                            }
+                        }
+                        return true;
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    fn query(&self, b: &Bbox, out: &mut Vec<u64>) {
+        match self {
+            Node::Leaf(items) => {
+                out.extend(items.iter().filter(|c| c.0.intersects(b)).map(|c| c.1))
+            }
+            Node::Inner(kids) => {
+                for (kb, k) in kids {
+                    if kb.intersects(b) {
+                        k.query(b, out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Делит переполненный узел пополам по оси с большим разбросом центров;
+/// вторая половина возвращается.
+fn split_half<T>(items: &mut Vec<(Bbox, T)>) -> Vec<(Bbox, T)> {
+    let spread = |axis: usize| {
+        let (lo, hi) = items
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), c| {
+                (lo.min(c.0.center(axis)), hi.max(c.0.center(axis)))
+            });
+        hi - lo
+    };
+    let axis = if spread(0) >= spread(1) { 0 } else { 1 };
+    items.sort_by(|a, b| a.0.center(axis).total_cmp(&b.0.center(axis)));
+    items.split_off(items.len() / 2)
+}
+
+/// Упаковка STR: элементы группируются по полосам x, внутри полос — по y.
+fn str_groups<T>(mut items: Vec<(Bbox, T)>) -> Vec<Vec<(Bbox, T)>> {
+    let leaves = items.len().div_ceil(MAX_FILL);
+    let slices = (leaves as f64).sqrt().ceil() as usize;
+    let per_slice = slices * MAX_FILL;
+    items.sort_by(|a, b| a.0.center(0).total_cmp(&b.0.center(0)));
+    let mut out = Vec::with_capacity(leaves);
+    while !items.is_empty() {
+        let rest = items.split_off(per_slice.min(items.len()));
+        let mut slice = std::mem::replace(&mut items, rest);
+        slice.sort_by(|a, b| a.0.center(1).total_cmp(&b.0.center(1)));
+        while !slice.is_empty() {
+            let rest = slice.split_off(MAX_FILL.min(slice.len()));
+            out.push(std::mem::replace(&mut slice, rest));
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone)]
+struct RTree {
+    root: Node,
+}
+
+impl Default for RTree {
+    fn default() -> Self {
+        Self {
+            root: Node::Leaf(Vec::new()),
+        }
+    }
+}
+
+impl RTree {
+    fn bulk(items: Vec<(Bbox, u64)>) -> Self {
+        let mut level: Vec<(Bbox, Node)> = str_groups(items)
+            .into_iter()
+            .map(Node::Leaf)
+            .filter_map(|n| Some((n.bbox()?, n)))
+            .collect();
+        while level.len() > MAX_FILL {
+            level = str_groups(level)
+                .into_iter()
+                .map(Node::Inner)
+                .filter_map(|n| Some((n.bbox()?, n)))
+                .collect();
+        }
+        let root = match level.len() {
+            0 => Node::Leaf(Vec::new()),
+            1 => level.pop().unwrap().1,
+            _ => Node::Inner(level),
+        };
+        Self { root }
+    }
+
+    fn insert(&mut self, b: Bbox, id: u64) {
+        if let Some(sib) = self.root.insert(b, id) {
+            let old = std::mem::replace(&mut self.root, Node::Leaf(Vec::new()));
+            let (ob, sb) = (old.bbox().unwrap(), sib.bbox().unwrap());
+            self.root = Node::Inner(vec![(ob, old), (sb, sib)]);
+        }
+    }
+
+    fn remove(&mut self, b: &Bbox, id: u64) {
+        self.root.remove(b, id);
+        // корень с одним ребёнком заменяется ребёнком
+        while let Node::Inner(kids) = &mut self.root {
+            match kids.len() {
+                0 => self.root = Node::Leaf(Vec::new()),
+                1 => self.root = kids.pop().unwrap().1,
+                _ => break,
+            }
+        }
+    }
+
+    fn query(&self, b: &Bbox) -> Vec<u64> {
+        let mut out = Vec::new();
+        self.root.query(b, &mut out);
+        out
+    }
+}
+
+// --------------------------- индекс документа ---------------------------
+
+/// Кэш рамок сущностей документа. В сравнении документов не участвует.
+#[derive(Clone, Default)]
+pub(crate) struct SpatialIndex {
+    tree: RTree,
+    boxes: HashMap<u64, Bbox>,
+    /// Сущности без геометрии (пустые вставки и т.п.)
+    empty: HashSet<u64>,
+    /// Добавленные или изменённые, рамка ещё не посчитана
+    dirty: HashSet<u64>,
+    /// Поколение хранилища, с которым индекс согласован
+    generation: Generation,
+}
+
+impl std::fmt::Debug for SpatialIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpatialIndex")
+            .field("boxes", &self.boxes.len())
+            .field("dirty", &self.dirty.len())
+            .finish()
+    }
+}
+
+impl PartialEq for SpatialIndex {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl SpatialIndex {
+    /// Пустой индекс, согласованный с хранилищем поколения `generation`.
+    pub(crate) fn new(generation: Generation) -> Self {
+        Self {
+            generation,
+            ..Default::default()
+        }
+    }
+
+    /// Сущность добавлена или изменена.
+    pub(crate) fn invalidate(&mut self, id: u64) {
+        self.forget(id);
+        self.dirty.insert(id);
+    }
+
+    /// Сущность удалена.
+    pub(crate) fn forget(&mut self, id: u64) {
+        if let Some(b) = self.boxes.remove(&id) {
+            self.tree.remove(&b, id);
+        }
+        self.empty.remove(&id);
+        self.dirty.remove(&id);
+    }
+
+    /// Хранилище сделало одну правку из поколения `before`, и индекс о ней уже
+    /// знает. Если индекс и до неё был согласован, он согласован и после.
+    pub(crate) fn follow(&mut self, before: Generation) {
+        if self.generation == before {
+            self.generation = before.next();
+        }
+    }
+
+    /// Хранилище менялось мимо индекса (документ прочитан, собран или заменён).
+    fn stale(&self, current: Generation) -> bool {
+        self.generation != current
+    }
+
+    /// Кандидаты, чьи рамки пересекают `b`: из дерева и ещё не пересчитанные.
+    /// `None` — индексу верить нельзя, проверять надо всё.
+    pub(crate) fn query(&self, b: &Bbox, current: Generation) -> Option<HashSet<u64>> {
+        if self.stale(current) {
+            return None;
+        }
+        let mut out: HashSet<u64> = self.tree.query(b).into_iter().collect();
+        out.extend(self.dirty.iter().copied());
+        Some(out)
+    }
+
+    pub(crate) fn refresh(doc: &mut Document) {
+        let idx = &doc.index;
+        if idx.dirty.is_empty() && !idx.stale(doc.entities.generation()) {
+            return;
+        }
+        let bounds = |e: &Entity| (e.id, entity_bounds(doc, e));
+        if idx.stale(doc.entities.generation()) || idx.dirty.len() > idx.boxes.len() {
+            // много изменений — перестраиваем упаковкой
+            let all: Vec<_> = doc.entities.iter().map(bounds).collect();
+            let mut idx = SpatialIndex::new(doc.entities.generation());
+            for (id, b) in all {
+                match b {
+                    Some(b) => {
+                        idx.boxes.insert(id, b);
+                    }
+                    None => {
+                        idx.empty.insert(id);
+                    }
+                }
+            }
+            idx.tree = RTree::bulk(idx.boxes.iter().map(|(id, b)| (*b, *id)).collect());
+            doc.index = idx;
+            return;
+        }
+        let changed: Vec<_> = idx
+            .dirty
+            .iter()
+            .filter_map(|&id| doc.entities.get(id))
+            .map(bounds)
+            .collect();
+        let idx = &mut doc.index;
+        idx.dirty.clear();
+        for (id, b) in changed {
+            match b {
+                Some(b) => {
+                    idx.tree.insert(b, id);
+                    idx.boxes.insert(id, b);
+                }
+                None => {
+                    idx.empty.insert(id);
+                }
+            }
+        }
+    }
+}
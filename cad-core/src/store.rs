@@ -0,0 +1,254 @@
+//! Хранилище сущностей документа: доступ по id за O(1) при сохранённом порядке
+//! отрисовки. Удалённые оставляют дырки, которые время от времени уплотняются.
+
+use crate::Entity;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::iter::Flatten;
+use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub type Iter<'a> = Flatten<slice::Iter<'a, Option<Entity>>>;
+
+/// Номера хранилищ: у каждого нового свой, чтобы поколения разных не совпадали.
+static STORES: AtomicU64 = AtomicU64::new(1);
+
+/// Поколение хранилища: меняется при каждом изменяющем обращении. Кэши сверяют с ним,
+/// не устарели ли они.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Generation {
+    store: u64,
+    edits: u64,
+}
+
+impl Generation {
+    /// Поколение после ещё одной правки.
+    pub(crate) fn next(self) -> Self {
+        Self {
+            edits: self.edits + 1,
+            ..self
+        }
+    }
+}
+
+/// Сущности в порядке отрисовки с выдачей id. В файл пишется просто списком;
+/// при чтении счётчик id восстанавливается по максимальному, а нулевые и
+/// повторяющиеся id (из старых файлов) заменяются новыми.
+#[derive(Clone)]
+pub struct EntityStore {
+    slots: Vec<Option<Entity>>,
+    pos: HashMap<u64, usize>,
+    next_id: u64,
+    generation: Generation,
+}
+
+impl Default for EntityStore {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            pos: HashMap::new(),
+            next_id: 1,
+            generation: Generation {
+                store: STORES.fetch_add(1, Ordering::Relaxed),
+                edits: 0,
+            },
+        }
+    }
+}
+
+impl EntityStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos.is_empty()
+    }
+
+    /// Текущее поколение; каждое добавление, удаление, возврат или `get_mut`
+    /// сдвигает его ровно на одну правку.
+    pub(crate) fn generation(&self) -> Generation {
+        self.generation
+    }
+
+    /// Id, который получит следующая добавленная сущность.
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    /// Добавить в конец с новым id. Возвращает id.
+    pub fn push(&mut self, mut e: Entity) -> u64 {
+        e.id = self.next_id;
+        self.next_id += 1;
+        self.put(e)
+    }
+
+    /// Добавить в конец, сохранив id сущности, если он не нулевой и свободен;
+    /// иначе выдаётся новый. Возвращает итоговый id.
+    pub fn insert(&mut self, e: Entity) -> u64 {
+        if e.id == 0 || self.pos.contains_key(&e.id) {
+            return self.push(e);
+        }
+        self.next_id = self.next_id.max(e.id + 1);
+        self.put(e)
+    }
+
+    fn put(&mut self, e: Entity) -> u64 {
+        self.generation = self.generation.next();
+        let id = e.id;
+        self.pos.insert(id, self.slots.len());
+        self.slots.push(Some(e));
+        id
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.pos.contains_key(&id)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Entity> {
+        self.slots[*self.pos.get(&id)?].as_ref()
+    }
+
+    pub(crate) fn get_mut(&mut self, id: u64) -> Option<&mut Entity> {
+        let i = *self.pos.get(&id)?;
+        self.generation = self.generation.next();
+        self.slots[i].as_mut()
+    }
+
+    /// Удалить сущность; порядок остальных не меняется.
+    pub fn remove(&mut self, id: u64) -> Option<Entity> {
+        let i = self.pos.remove(&id)?;
+        self.generation = self.generation.next();
+        let e = self.slots[i].take();
+        if self.slots.len() > 32 && self.pos.len() * 2 < self.slots.len() {
+            self.compact();
+        }
+        e
+    }
+
+    /// Следующая за `id` сущность в порядке отрисовки.
+    pub(crate) fn next_after(&self, id: u64) -> Option<u64> {
+        let i = *self.pos.get(&id)?;
+        self.slots[i + 1..].iter().flatten().map(|e| e.id).next()
+    }
+
+    /// Вернуть сущности с их id, каждую перед своей `next` (нет или не найдена — в
+    /// конец). То же, что вернуть их по одной в этом порядке, но за один проход:
+    /// `next` может указывать и на сущность из той же пачки.
+    pub(crate) fn restore(&mut self, batch: Vec<(Entity, Option<u64>)>) {
+        self.generation = self.generation.next();
+        let ids: HashSet<u64> = batch.iter().map(|(e, _)| e.id).collect();
+        let mut before: HashMap<u64, Vec<Entity>> = HashMap::new();
+        let mut tail = Vec::new();
+        for (e, next) in batch {
+            self.next_id = self.next_id.max(e.id + 1);
+            match next.filter(|n| self.pos.contains_key(n) || ids.contains(n)) {
+                Some(n) => before.entry(n).or_default().push(e),
+                None => tail.push(e),
+            }
+        }
+        let old = std::mem::take(&mut self.slots);
+        self.slots.reserve(old.len() + ids.len());
+        // цепочки «перед» бывают длинными — обход без рекурсии
+        let mut stack = Vec::new();
+        for e in old.into_iter().flatten().chain(tail) {
+            stack.push((e, false));
+            while let Some((e, expanded)) = stack.pop() {
+                if expanded {
+                    self.slots.push(Some(e));
+                    continue;
+                }
+                let queued = before.remove(&e.id).unwrap_or_default();
+                stack.push((e, true));
+                stack.extend(queued.into_iter().rev().map(|q| (q, false)));
+            }
+        }
+        self.pos.clear();
+        self.compact();
+    }
+
+    /// Ключ порядка отрисовки: у более поздних сущностей больше. Меняется после
+    /// удалений, годится только для сортировки.
+    pub(crate) fn order(&self, id: u64) -> Option<usize> {
+        self.pos.get(&id).copied()
+    }
+
+    fn compact(&mut self) {
+        self.slots.retain(Option::is_some);
+        for (i, e) in self.slots.iter().enumerate() {
+            if let Some(e) = e {
+                self.pos.insert(e.id, i);
+            }
+        }
+    }
+
+    /// Сущности в порядке отрисовки. Позиционного доступа нет: с дырками от
+    /// удалений `n`-я сущность ищется перебором, `iter().nth(n)` это не скрывает.
+    pub fn iter(&self) -> Iter<'_> {
+        self.slots.iter().flatten()
+    }
+
+    /// Id в порядке отрисовки.
+    pub fn ids(&self) -> impl DoubleEndedIterator<Item = u64> + '_ {
+        self.iter().map(|e| e.id)
+    }
+
+    pub fn first(&self) -> Option<&Entity> {
+        self.iter().next()
+    }
+
+    pub fn last(&self) -> Option<&Entity> {
+        self.iter().next_back()
+    }
+}
+
+impl<'a> IntoIterator for &'a EntityStore {
+    type Item = &'a Entity;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<Entity> for EntityStore {
+    fn from_iter<I: IntoIterator<Item = Entity>>(iter: I) -> Self {
+        let list: Vec<Entity> = iter.into_iter().collect();
+        let mut store = Self::new();
+        // сначала счётчик за всеми id, чтобы новые не совпали с ещё не прочитанными
+        store.next_id = list.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+        for e in list {
+            store.insert(e);
+        }
+        store
+    }
+}
+
+impl std::fmt::Debug for EntityStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Равны при одинаковых сущностях в том же порядке; счётчик id не сравнивается.
+impl PartialEq for EntityStore {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Serialize for EntityStore {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for EntityStore {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Ok(Vec::<Entity>::deserialize(d)?.into_iter().collect())
+    }
+}
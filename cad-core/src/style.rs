@@ -0,0 +1,216 @@
+use crate::Pt2;
+use serde::{Deserialize, Serialize};
+
+// --------------------------- цвет ---------------------------
+
+/// Цвет в духе DXF: ПоСлою, ПоБлоку, индекс ACI (1..=255) или истинный RGB.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Color {
+    #[default]
+    ByLayer,
+    ByBlock,
+    Aci(u8),
+    Rgb([u8; 3]),
+}
+
+impl Color {
+    /// Белый/чёрный ACI 7 — цвет по умолчанию для слоёв.
+    pub const WHITE: Color = Color::Aci(7);
+
+    pub fn is_by_layer(&self) -> bool {
+        *self == Color::ByLayer
+    }
+
+    /// RGB для явного цвета; для ПоСлою/ПоБлоку — `None`.
+    /// ACI 7 отдаётся белым: на светлом фоне его принято рисовать чёрным.
+    pub fn rgb(self) -> Option<[u8; 3]> {
+        match self {
+            Color::Aci(i) => Some(aci_rgb(i)),
+            Color::Rgb(c) => Some(c),
+            Color::ByLayer | Color::ByBlock => None,
+        }
+    }
+}
+
+/// RGB цвета ACI по стандартной палитре AutoCAD.
+pub fn aci_rgb(index: u8) -> [u8; 3] {
+    match index {
+        0 | 7 => [255, 255, 255],
+        1 => [255, 0, 0],
+        2 => [255, 255, 0],
+        3 => [0, 255, 0],
+        4 => [0, 255, 255],
+        5 => [0, 0, 255],
+        6 => [255, 0, 255],
+        8 => [128, 128, 128],
+        9 => [192, 192, 192],
+        10..=249 => {
+            // 24 оттенка через 15°, в каждом — 5 яркостей × (насыщенный, бледный)
+            let hue = (index / 10 - 1) as usize;
+            let step = |k: usize| -> f32 {
+                // 0, 63, 127, 191, 255 по кругу оттенков
+                let k = k % 24;
+                match k {
+                    0..=4 => [255.0, 255.0, 255.0, 255.0, 255.0][k],
+                    5..=8 => [191.0, 127.0, 63.0, 0.0][k - 5],
+                    9..=16 => 0.0,
+                    _ => [63.0, 127.0, 191.0, 255.0, 255.0, 255.0, 255.0][k - 17],
+                }
+            };
+            // R отстаёт на 0, G — на 8, B — на 16 шагов
+            let mut c = [step(hue), step(hue + 16), step(hue + 8)];
+            let sub = index % 10;
+            if sub % 2 == 1 {
+                for v in &mut c {
+                    *v = ((*v + 255.0) / 2.0).floor();
+                }
+            }
+            let k = [1.0, 0.8, 0.6, 0.5, 0.3][(sub / 2) as usize];
+            c.map(|v| (v * k).round() as u8)
+        }
+        250 => [51, 51, 51],
+        251 => [80, 80, 80],
+        252 => [105, 105, 105],
+        253 => [130, 130, 130],
+        254 => [190, 190, 190],
+        255 => [255, 255, 255],
+    }
+}
+
+/// Ближайший индекс ACI (1..=255) к произвольному RGB.
+pub fn nearest_aci(rgb: [u8; 3]) -> u8 {
+    (1..=255u8)
+        .min_by_key(|&i| {
+            let c = aci_rgb(i);
+            (0..3)
+                .map(|k| (c[k] as i32 - rgb[k] as i32).pow(2))
+                .sum::<i32>()
+        })
+        .unwrap_or(7)
+}
+
+// --------------------------- вес линии ---------------------------
+
+/// Вес линии; `Width` — в сотых долях мм, как код 370 в DXF (25 = 0.25 мм).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum LineWeight {
+    #[default]
+    ByLayer,
+    ByBlock,
+    /// Вес по умолчанию (LWDEFAULT)
+    Default,
+    Width(u16),
+}
+
+impl LineWeight {
+    /// Вес по умолчанию в мм.
+    pub const DEFAULT_MM: f32 = 0.25;
+
+    pub fn is_by_layer(&self) -> bool {
+        *self == LineWeight::ByLayer
+    }
+
+    /// Явный вес в мм; для ПоСлою/ПоБлоку — `None`.
+    pub fn mm(self) -> Option<f32> {
+        match self {
+            LineWeight::Width(w) => Some(w as f32 / 100.0),
+            LineWeight::Default => Some(Self::DEFAULT_MM),
+            LineWeight::ByLayer | LineWeight::ByBlock => None,
+        }
+    }
+
+    /// Из кода 370: −1 ПоБлоку, −2 ПоСлою, −3 по умолчанию.
+    pub fn from_dxf(raw: i16) -> Self {
+        match raw {
+            -1 => LineWeight::ByBlock,
+            -3 => LineWeight::Default,
+            w if w >= 0 => LineWeight::Width(w as u16),
+            _ => LineWeight::ByLayer,
+        }
+    }
+
+    pub fn to_dxf(self) -> i16 {
+        match self {
+            LineWeight::ByLayer => -2,
+            LineWeight::ByBlock => -1,
+            LineWeight::Default => -3,
+            LineWeight::Width(w) => w.min(211) as i16,
+        }
+    }
+}
+
+// --------------------------- тип линии ---------------------------
+
+/// Имя типа линии «ПоБлоку»; пустое имя у сущности означает «ПоСлою».
+pub const LINETYPE_BYBLOCK: &str = "ByBlock";
+/// Сплошная линия — тип слоя по умолчанию.
+pub const LINETYPE_CONTINUOUS: &str = "Continuous";
+
+/// Определение типа линии (LTYPE). Шаблон — как в DXF: штрих > 0, пробел < 0, точка = 0,
+/// длины в единицах чертежа.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinetypeDef {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub pattern: Vec<f32>,
+}
+
+impl LinetypeDef {
+    pub fn pattern_length(&self) -> f32 {
+        self.pattern.iter().map(|v| v.abs()).sum()
+    }
+}
+
+/// Разбить ломаную на штрихи по шаблону типа линии. Точки шаблона дают отрезки нулевой
+/// длины (две одинаковые точки). Пустой или вырожденный шаблон — ломаная целиком.
+pub fn dash_polyline(pts: &[Pt2], pattern: &[f32]) -> Vec<Vec<Pt2>> {
+    let period: f32 = pattern.iter().map(|v| v.abs()).sum();
+    if pts.len() < 2 || pattern.is_empty() || period <= 1e-6 {
+        return vec![pts.to_vec()];
+    }
+    let total: f32 = pts
+        .windows(2)
+        .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+        .sum();
+    // слишком мелкий шаблон — рисуем сплошной, иначе взорвётся число штрихов
+    if total / period > 10_000.0 {
+        return vec![pts.to_vec()];
+    }
+
+    let mut out: Vec<Vec<Pt2>> = Vec::new();
+    let mut cur: Vec<Pt2> = Vec::new();
+    let (mut k, mut left) = (0usize, pattern[0].abs());
+    let mut pen = pattern[0] >= 0.0;
+    if pen {
+        cur.push(pts[0]);
+    }
+
+    for w in pts.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        let len = (b.x - a.x).hypot(b.y - a.y);
+        let mut t = 0.0;
+        while len - t > left {
+            t += left;
+            let p = Pt2::new(a.x + (b.x - a.x) * t / len, a.y + (b.y - a.y) * t / len);
+            if pen {
+                cur.push(p);
+                out.push(std::mem::take(&mut cur));
+            }
+            k = (k + 1) % pattern.len();
+            left = pattern[k].abs();
+            pen = pattern[k] >= 0.0;
+            if pen {
+                cur.push(p);
+            }
+        }
+        left -= len - t;
+        if pen {
+            cur.push(b);
+        }
+    }
+    if cur.len() >= 2 {
+        out.push(cur);
+    }
+    out
+}
@@ -0,0 +1,409 @@
+// cad-core/src/table.rs
+//! Таблица: ячейки, объединения, строки шапки и выравнивание по столбцам.
+//! Раскладка — ширины столбцов по содержимому или заданные (тогда текст
+//! переносится по словам), высоты строк — по числу строк текста; длинная
+//! таблица делится на продолжения с повтором шапки. Чертится отрезками и
+//! текстами ([`table_geometry`]), в DXF пишется как ACAD_TABLE.
+
+use crate::{Entity, EntityKind, Pt2};
+use serde::{Deserialize, Serialize};
+
+/// Ширина знака в долях высоты — оценка без метрик шрифта.
+const CHAR_WIDTH: f32 = 0.8;
+/// Шаг строк текста в ячейке в долях высоты.
+const LINE_SPACING: f32 = 1.6;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Объединение ячеек: левая верхняя и охват в строках и столбцах.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellMerge {
+    pub row: usize,
+    pub col: usize,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Table {
+    /// Текст ячеек по строкам; `\n` — принудительный перенос.
+    pub rows: Vec<Vec<String>>,
+    /// Ширины столбцов; 0 или нет значения — по самой длинной строке текста.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<f32>,
+    /// Выравнивание по столбцам (нет значения — влево); шапка — по центру.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub align: Vec<CellAlign>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub merges: Vec<CellMerge>,
+    /// Строк шапки: повторяются в каждом продолжении.
+    pub header_rows: usize,
+    pub text_height: f32,
+    /// Наименьшая высота строки.
+    pub row_height: f32,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self {
+            rows: vec![],
+            columns: vec![],
+            align: vec![],
+            merges: vec![],
+            header_rows: 0,
+            text_height: 2.5,
+            row_height: 8.0,
+        }
+    }
+}
+
+/// Ячейка раскладки: охват и текст, разбитый на строки.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaidCell {
+    pub row: usize,
+    pub col: usize,
+    pub rows: usize,
+    pub cols: usize,
+    pub lines: Vec<String>,
+    pub align: CellAlign,
+}
+
+/// Раскладка таблицы. Ячейки, закрытые объединением, в `cells` не входят.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableLayout {
+    pub widths: Vec<f32>,
+    pub heights: Vec<f32>,
+    pub cells: Vec<LaidCell>,
+}
+
+impl TableLayout {
+    pub fn width(&self) -> f32 {
+        self.widths.iter().sum()
+    }
+
+    pub fn height(&self) -> f32 {
+        self.heights.iter().sum()
+    }
+}
+
+impl Table {
+    /// Число строк и столбцов.
+    pub fn size(&self) -> (usize, usize) {
+        let cols = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        (self.rows.len(), cols.max(self.columns.len()))
+    }
+
+    /// Объединения в пределах таблицы; пересекающие уже принятые отбрасываются.
+    pub(crate) fn merge_grid(&self) -> (Vec<CellMerge>, Vec<Vec<Option<usize>>>) {
+        let (nr, nc) = self.size();
+        let mut owner = vec![vec![None; nc]; nr];
+        let mut merges = Vec::new();
+        for m in &self.merges {
+            if m.row >= nr || m.col >= nc {
+                continue;
+            }
+            let m = CellMerge {
+                rows: m.rows.clamp(1, nr - m.row),
+                cols: m.cols.clamp(1, nc - m.col),
+                ..*m
+            };
+            let span = |f: &mut dyn FnMut(usize, usize)| {
+                for r in m.row..m.row + m.rows {
+                    for c in m.col..m.col + m.cols {
+                        f(r, c);
+                    }
+                }
+            };
+            let mut free = true;
+            span(&mut |r, c| free &= owner[r][c].is_none());
+            if free && m.rows * m.cols > 1 {
+                span(&mut |r, c| owner[r][c] = Some(merges.len()));
+                merges.push(m);
+            }
+        }
+        (merges, owner)
+    }
+
+    pub(crate) fn text(&self, r: usize, c: usize) -> &str {
+        self.rows
+            .get(r)
+            .and_then(|row| row.get(c))
+            .map_or("", String::as_str)
+    }
+
+    fn text_width(&self, s: &str) -> f32 {
+        s.chars().count() as f32 * self.text_height * CHAR_WIDTH
+    }
+
+    /// Ширины столбцов, высоты строк и перенос текста.
+    pub fn layout(&self) -> TableLayout {
+        let (nr, nc) = self.size();
+        let (merges, owner) = self.merge_grid();
+        let h = self.text_height;
+        let pad = h;
+        let natural =
+            |s: &str| s.lines().map(|l| self.text_width(l)).fold(0.0, f32::max) + 2.0 * pad;
+        let fixed = |c: usize| self.columns.get(c).copied().filter(|w| *w > 0.0);
+
+        let mut widths: Vec<f32> = (0..nc)
+            .map(|c| {
+                fixed(c).unwrap_or_else(|| {
+                    (0..nr)
+                        .filter(|&r| owner[r][c].is_none())
+                        .map(|r| natural(self.text(r, c)))
+                        .fold(2.0 * pad, f32::max)
+                })
+            })
+            .collect();
+        // объединение шире своих столбцов раздвигает последний нефиксированный
+        for m in &merges {
+            let cols = m.col..m.col + m.cols;
+            let need = natural(self.text(m.row, m.col)) - widths[cols.clone()].iter().sum::<f32>();
+            if let Some(c) = cols.rev().find(|&c| fixed(c).is_none()) {
+                widths[c] += need.max(0.0);
+            }
+        }
+
+        let mut cells = Vec::new();
+        for (r, own) in owner.iter().enumerate() {
+            for (c, own) in own.iter().enumerate() {
+                let (rows, cols) = match own.map(|i| merges[i]) {
+                    Some(m) if (m.row, m.col) == (r, c) => (m.rows, m.cols),
+                    Some(_) => continue,
+                    None => (1, 1),
+                };
+                let avail = widths[c..c + cols].iter().sum::<f32>() - 2.0 * pad;
+                let align = if r < self.header_rows {
+                    CellAlign::Center
+                } else {
+                    self.align.get(c).copied().unwrap_or_default()
+                };
+                cells.push(LaidCell {
+                    row: r,
+                    col: c,
+                    rows,
+                    cols,
+                    lines: self.wrap(self.text(r, c), avail),
+                    align,
+                });
+            }
+        }
+
+        let need = |n: usize| h + (n.max(1) - 1) as f32 * h * LINE_SPACING + h;
+        let mut heights = vec![self.row_height; nr];
+        for cell in cells.iter().filter(|c| c.rows == 1) {
+            heights[cell.row] = heights[cell.row].max(need(cell.lines.len()));
+        }
+        for cell in cells.iter().filter(|c| c.rows > 1) {
+            let span = cell.row..cell.row + cell.rows;
+            let extra = need(cell.lines.len()) - heights[span].iter().sum::<f32>();
+            heights[cell.row + cell.rows - 1] += extra.max(0.0);
+        }
+        TableLayout {
+            widths,
+            heights,
+            cells,
+        }
+    }
+
+    /// Перенос по словам в ширину `avail`; слово длиннее строки режется по знакам.
+    fn wrap(&self, s: &str, avail: f32) -> Vec<String> {
+        let fits = |t: &str| self.text_width(t) <= avail + 1e-3;
+        let mut out = Vec::new();
+        for para in s.split('\n') {
+            let mut line = String::new();
+            for word in para.split_whitespace() {
+                let joined = if line.is_empty() {
+                    word.to_owned()
+                } else {
+                    format!("{line} {word}")
+                };
+                if fits(&joined) {
+                    line = joined;
+                    continue;
+                }
+                if !line.is_empty() {
+                    out.push(std::mem::take(&mut line));
+                }
+                for ch in word.chars() {
+                    line.push(ch);
+                    if !fits(&line) && line.chars().count() > 1 {
+                        line.pop();
+                        out.push(std::mem::take(&mut line));
+                        line.push(ch);
+                    }
+                }
+            }
+            out.push(line);
+        }
+        // пустая ячейка — без строк
+        if out.iter().all(String::is_empty) {
+            out.clear();
+        }
+        out
+    }
+
+    /// Таблица и её продолжения не выше `max_height` каждая. Шапка повторяется,
+    /// ширины столбцов у всех частей общие, строки одного объединения не
+    /// разрываются. `max_height <= 0` — без деления.
+    pub fn split(&self, max_height: f32) -> Vec<Table> {
+        let lay = self.layout();
+        if max_height <= 0.0 || lay.height() <= max_height {
+            return vec![self.clone()];
+        }
+        let (merges, _) = self.merge_grid();
+        let head = self.header_rows.min(self.rows.len());
+        let head_h: f32 = lay.heights[..head].iter().sum();
+
+        // группы неразрывных строк тела
+        let mut end_of = (0..self.rows.len()).collect::<Vec<usize>>();
+        for m in merges.iter().filter(|m| m.row >= head) {
+            for end in &mut end_of[m.row..m.row + m.rows] {
+                *end = (*end).max(m.row + m.rows - 1);
+            }
+        }
+        let mut pieces: Vec<(usize, usize)> = Vec::new();
+        let (mut start, mut r, mut acc) = (head, head, head_h);
+        while r < self.rows.len() {
+            let mut end = end_of[r];
+            let mut k = r;
+            while k <= end {
+                end = end.max(end_of[k]);
+                k += 1;
+            }
+            let group: f32 = lay.heights[r..=end].iter().sum();
+            if acc + group > max_height && r > start {
+                pieces.push((start, r));
+                (start, acc) = (r, head_h);
+            }
+            acc += group;
+            r = end + 1;
+        }
+        pieces.push((start, self.rows.len()));
+
+        pieces
+            .into_iter()
+            .map(|(a, b)| {
+                let keep = |r: usize| r < head || (a..b).contains(&r);
+                let shift = |r: usize| if r < head { r } else { r - a + head };
+                Table {
+                    rows: (0..self.rows.len())
+                        .filter(|&r| keep(r))
+                        .map(|r| self.rows[r].clone())
+                        .collect(),
+                    columns: lay.widths.clone(),
+                    merges: merges
+                        .iter()
+                        .filter(|m| keep(m.row))
+                        .map(|m| CellMerge {
+                            row: shift(m.row),
+                            ..*m
+                        })
+                        .collect(),
+                    ..self.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Отрезки сетки и тексты таблицы с левым верхним углом `pos`. Границы внутри
+/// объединений не рисуются, соседние куски одной линии сливаются.
+pub fn table_geometry(table: &Table, pos: Pt2) -> Vec<EntityKind> {
+    let lay = table.layout();
+    let (nr, nc) = (lay.heights.len(), lay.widths.len());
+    if nr == 0 || nc == 0 {
+        return vec![];
+    }
+    let (_, owner) = table.merge_grid();
+    let mut xs = vec![pos.x];
+    for w in &lay.widths {
+        xs.push(xs[xs.len() - 1] + w);
+    }
+    let mut ys = vec![pos.y];
+    for h in &lay.heights {
+        ys.push(ys[ys.len() - 1] - h);
+    }
+    let same = |a: (usize, usize), b: (usize, usize)| {
+        owner[a.0][a.1].is_some() && owner[a.0][a.1] == owner[b.0][b.1]
+    };
+
+    let mut out = Vec::new();
+    // граница k горизонтальная (между строками k-1 и k) или вертикальная
+    let runs = |count: usize, len: usize, open: &dyn Fn(usize, usize) -> bool| {
+        let mut segs = Vec::new();
+        for k in 0..=count {
+            let mut from = None;
+            for i in 0..=len {
+                let on = i < len && (k == 0 || k == count || open(k, i));
+                match (on, from) {
+                    (true, None) => from = Some(i),
+                    (false, Some(f)) => {
+                        segs.push((k, f, i));
+                        from = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        segs
+    };
+    for (k, a, b) in runs(nr, nc, &|k, c| !same((k - 1, c), (k, c))) {
+        out.push(EntityKind::LineSeg {
+            a: Pt2::new(xs[a], ys[k]),
+            b: Pt2::new(xs[b], ys[k]),
+        });
+    }
+    for (k, a, b) in runs(nc, nr, &|k, r| !same((r, k - 1), (r, k))) {
+        out.push(EntityKind::LineSeg {
+            a: Pt2::new(xs[k], ys[a]),
+            b: Pt2::new(xs[k], ys[b]),
+        });
+    }
+
+    let h = table.text_height;
+    for cell in &lay.cells {
+        let (x0, x1) = (xs[cell.col], xs[cell.col + cell.cols]);
+        let (top, bottom) = (ys[cell.row], ys[cell.row + cell.rows]);
+        let n = cell.lines.len();
+        let block = h + n.saturating_sub(1) as f32 * h * LINE_SPACING;
+        let first = top - (top - bottom - block) / 2.0 - h;
+        for (i, line) in cell.lines.iter().enumerate() {
+            let w = table.text_width(line);
+            let x = match cell.align {
+                CellAlign::Left => x0 + h,
+                CellAlign::Center => (x0 + x1 - w) / 2.0,
+                CellAlign::Right => x1 - h - w,
+            };
+            out.push(EntityKind::Text {
+                pos: Pt2::new(x, first - i as f32 * h * LINE_SPACING),
+                content: line.clone(),
+                height: h,
+            });
+        }
+    }
+    out
+}
+
+/// Таблица-сущность, развёрнутая в отрезки и тексты со свойствами самой таблицы.
+pub fn table_entities(ent: &Entity) -> Vec<Entity> {
+    let EntityKind::Table { pos, table } = &ent.kind else {
+        return vec![];
+    };
+    table_geometry(table, *pos)
+        .into_iter()
+        .map(|k| Entity {
+            color: ent.color,
+            linetype: ent.linetype.clone(),
+            lineweight: ent.lineweight,
+            ..Entity::new(ent.layer.clone(), k)
+        })
+        .collect()
+}
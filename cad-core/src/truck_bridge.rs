@@ -1,16 +1,17 @@
-//! Простая «склейка» наших 2D-контуров с truck: Wire → Face → Solid → TriMesh.
+//! Простая «склейка» наших 2D-контуров с cryxtal: Wire → Face → Solid → TriMesh.
 //! Держим всё максимально прямолинейно, чтобы быстро показать геометрию.
 
 use cryxtal_geometry::prelude::*;
-use cryxtal_modeling::*;
-use cryxtal_meshalgo::prelude::*;
+use cryxtal_meshalgo::tessellation::{MeshableShape, MeshedShape};
+use cryxtal_modeling::builder;
+use cryxtal_modeling::topology::{Face, Solid, Vertex, Wire};
+use cryxtal_modeling::Surface;
 
-use crate::geom::{EntityKind, Pt2 /*, TruckCurve2*/};
+use crate::geom::{EntityKind, Pt2};
+use crate::hatch::curve_points;
+use crate::model3d::BREP_TOL;
 
-fn p2(p: Pt2) -> Point2<f64> {
-    Point2::new(p.x as f64, p.y as f64)
-}
-fn p3_xy(p: Pt2, z: f64) -> Point3<f64> {
+fn p3_xy(p: Pt2, z: f64) -> Point3 {
     Point3::new(p.x as f64, p.y as f64, z)
 }
 
@@ -25,37 +26,36 @@ pub fn close_poly_if_needed(poly: &mut Vec<Pt2>) {
     }
 }
 
-/// Простейший wire из замкнутой полилинии: каждое звено — линейный BSpline степени 1.
+/// Wire из замкнутой ломаной в плоскости Z=0: каждое звено — отрезок, соседние рёбра
+/// делят вершины. Повтор первой точки в конце допускается.
 pub fn wire_from_closed_polyline(pts: &[Pt2]) -> Wire {
+    let mut pts = pts.to_vec();
+    if pts.len() > 1 && pts.first() == pts.last() {
+        pts.pop();
+    }
     assert!(pts.len() >= 3, "нужно минимум 3 точки для контура");
 
-    let mut edges = Vec::with_capacity(pts.len().saturating_sub(1));
-    for i in 0..pts.len() - 1 {
-        let a = p3_xy(pts[i], 0.0);
-        let b = p3_xy(pts[i + 1], 0.0);
-        let va = Vertex::new(a);
-        let vb = Vertex::new(b);
-
-        // Линию кодируем как B-сплайн степени 1 на двух опорных точках.
-        // Узловой вектор: [0,0,1,1].
-        let curve3d = BSplineCurve::new(KnotVec::from(vec![0.0, 0.0, 1.0, 1.0]), vec![a, b]);
-        let curve = Curve::BSplineCurve(Arc::new(curve3d));
-        edges.push(Edge::new(&va, &vb, curve));
-    }
-    Wire::from_iter(edges)
+    let vs: Vec<Vertex> = pts
+        .iter()
+        .map(|p| builder::vertex(p3_xy(*p, 0.0)))
+        .collect();
+    (0..vs.len())
+        .map(|i| builder::line(&vs[i], &vs[(i + 1) % vs.len()]))
+        .collect()
 }
 
 /// Плоская грань в плоскости Z=0 по набору проволок.
-/// Первая проволока = внешний контур, остальные — отверстия.
+/// Первая проволока = внешний контур (против часовой), остальные — отверстия (по часовой).
 pub fn planar_face_from_wires(outer: &Wire, holes: &[Wire]) -> Face {
-    let plane = Plane::new(Vector3::unit_z(), Point3::origin());
-    let surf = PlaneSurface::new(plane);
-
-    // Face::new(surface, vec![outer, ...holes], true)
+    let plane = Plane::new(
+        Point3::origin(),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+    );
     let mut all = Vec::with_capacity(1 + holes.len());
     all.push(outer.clone());
     all.extend(holes.iter().cloned());
-    Face::new(surf, all, true)
+    Face::new(all, Surface::Plane(plane))
 }
 
 /// Упрощённая версия: грань только из одной проволоки (без отверстий).
@@ -65,21 +65,21 @@ pub fn planar_face_from_wire(outer: &Wire) -> Face {
 
 /// Экструзия плоской грани на высоту `h` вдоль +Z → Solid.
 pub fn extrude_face(face: &Face, h: f64) -> Solid {
-    let dir = Vector3::new(0.0, 0.0, h);
-    face.sweep(&dir)
+    builder::tsweep(face, Vector3::new(0.0, 0.0, h))
 }
 
-/// Базовая триангуляция Solid при помощи cryxtal_meshalgo.
+/// Триангуляция Solid при помощи cryxtal_meshalgo (допуск — как у B-Rep элементов).
 pub fn mesh_from_solid(solid: &Solid) -> (Vec<[f32; 3]>, Vec<u32>) {
-    let pm: PolygonMesh = solid.triangulation(Default::default());
-    let vertices: Vec<[f32; 3]> = pm
+    let poly = solid.triangulation(BREP_TOL).to_polygon();
+    let vertices: Vec<[f32; 3]> = poly
         .positions()
         .iter()
         .map(|p| [p.x as f32, p.y as f32, p.z as f32])
         .collect();
-    let indices: Vec<u32> = pm
-        .face_iter()
-        .flat_map(|f| f.indices().iter().copied().map(|i| i as u32))
+    let indices: Vec<u32> = poly
+        .faces()
+        .triangle_iter()
+        .flat_map(|t| t.map(|v| v.pos as u32))
         .collect();
     (vertices, indices)
 }
@@ -87,16 +87,9 @@ pub fn mesh_from_solid(solid: &Solid) -> (Vec<[f32; 3]>, Vec<u32>) {
 /// Применить 4×4 матрицу (row-major) к позиции в однородных координатах.
 #[inline]
 fn transform_point(mat: &[[f32; 4]; 4], p: [f32; 3]) -> [f32; 3] {
-    let x = p[0];
-    let y = p[1];
-    let z = p[2];
-    let w = 1.0_f32;
-
-    let xp = mat[0][0] * x + mat[0][1] * y + mat[0][2] * z + mat[0][3] * w;
-    let yp = mat[1][0] * x + mat[1][1] * y + mat[1][2] * z + mat[1][3] * w;
-    let zp = mat[2][0] * x + mat[2][1] * y + mat[2][2] * z + mat[2][3] * w;
-    let wp = mat[3][0] * x + mat[3][1] * y + mat[3][2] * z + mat[3][3] * w;
-
+    let [x, y, z] = p;
+    let row = |r: usize| mat[r][0] * x + mat[r][1] * y + mat[r][2] * z + mat[r][3];
+    let (xp, yp, zp, wp) = (row(0), row(1), row(2), row(3));
     if wp != 0.0 {
         [xp / wp, yp / wp, zp / wp]
     } else {
@@ -111,36 +104,20 @@ pub fn transform_positions_inplace(positions: &mut [[f32; 3]], mat: &[[f32; 4];
     }
 }
 
-/// Полный пайплайн: внешний контур + отверстия (все в 2D XY, Z=0) → грань → экструзия → триангуляция,
-/// далее — применяем матрицу трансформации к вершинам.
+/// Полный пайплайн: внешний контур + отверстия (все в 2D XY, Z=0) → грань → экструзия →
+/// триангуляция, далее — применяем матрицу трансформации к вершинам.
 pub fn extrude_polygon_to_mesh_with_transform(
     outer: &[Pt2],
     holes: &[Vec<Pt2>],
     height: f64,
     xform_row_major: &[[f32; 4]; 4],
 ) -> (Vec<[f32; 3]>, Vec<u32>) {
-    // 1) Обеспечиваем замкнутость
-    let mut out = outer.to_vec();
-    close_poly_if_needed(&mut out);
-
-    let mut hole_wires = Vec::with_capacity(holes.len());
-    for h in holes {
-        let mut hh = h.clone();
-        close_poly_if_needed(&mut hh);
-        hole_wires.push(wire_from_closed_polyline(&hh));
-    }
-
-    // 2) Wire → Face → Solid
-    let outer_wire = wire_from_closed_polyline(&out);
-    let face = planar_face_from_wires(&outer_wire, &hole_wires);
+    let hole_wires: Vec<Wire> = holes.iter().map(|h| wire_from_closed_polyline(h)).collect();
+    let face = planar_face_from_wires(&wire_from_closed_polyline(outer), &hole_wires);
     let solid = extrude_face(&face, height);
 
-    // 3) Solid → TriMesh
     let (mut positions, indices) = mesh_from_solid(&solid);
-
-    // 4) Применяем матрицу (если рендер не умножает на GPU)
     transform_positions_inplace(&mut positions, xform_row_major);
-
     (positions, indices)
 }
 
@@ -153,109 +130,11 @@ pub fn extrude_polyline_to_mesh_with_transform(
     extrude_polygon_to_mesh_with_transform(poly_closed, &[], height, xform_row_major)
 }
 
-/// (Опционально) «псевдо-точная» проволока из произвольной EntityKind.
-/// Сейчас — через дискретизацию в полилинию (чтобы сразу работало для дуг/НУРБС).
-/// Если захочешь делать точные ребра NURBS — можно дописать отдельную ветку.
-pub fn wire_from_entity_kind_sampled(kind: &EntityKind, chord_tol: f64) -> Option<Wire> {
-    match kind {
-        EntityKind::Polyline { pts, closed } => {
-            let mut poly = pts.clone();
-            if *closed {
-                close_poly_if_needed(&mut poly);
-            }
-            if poly.len() >= 3 {
-                Some(wire_from_closed_polyline(&poly))
-            } else {
-                None
-            }
-        }
-        EntityKind::LineSeg { a, b } => {
-            let mut poly = vec![*a, *b, *a];
-            Some(wire_from_closed_polyline(&poly))
-        }
-        EntityKind::Arc { center, radius, start_angle, end_angle } => {
-            // Дискретизируем дугу в полилинию
-            let segs = ((end_angle - start_angle).abs() / std::f32::consts::PI * 24.0)
-                .max(8.0)
-                .round() as usize;
-            let c = *center;
-            let r = *radius as f64;
-            let a0 = *start_angle as f64;
-            let a1 = *end_angle as f64;
-            let mut poly = Vec::with_capacity(segs + 1);
-            for i in 0..=segs {
-                let t = i as f64 / (segs as f64);
-                let a = a0 * (1.0 - t) + a1 * t;
-                poly.push(Pt2::new((c.x as f64 + r * a.cos()) as f32,
-                                   (c.y as f64 + r * a.sin()) as f32));
-            }
-            close_poly_if_needed(&mut poly);
-            Some(wire_from_closed_polyline(&poly))
-        }
-        EntityKind::NurbsCurve2D { .. } => {
-            // Дискретизация через общую выборку из твоего слоя (если она у тебя уже есть).
-            // Здесь сделаем простую подстановку: попробуем равномерно по параметру.
-            // TODO: заменить на твою реализацию sample(kind, chord_tol).
-            let mut poly = sample_entity_kind(kind, chord_tol);
-            close_poly_if_needed(&mut poly);
-            if poly.len() >= 3 {
-                Some(wire_from_closed_polyline(&poly))
-            } else {
-                None
-            }
-        }
-        EntityKind::Text { .. } => None,
-    }
-}
-
-/// Примитивная дискретизация EntityKind → полилиния (для НУРБС/дуг).
-/// Вынесено отдельно, чтобы можно было заменить на твою «умную» выборку по допуску.
-fn sample_entity_kind(kind: &EntityKind, _chord_tol: f64) -> Vec<Pt2> {
-    match kind {
-        EntityKind::Polyline { pts, .. } => pts.clone(),
-        EntityKind::LineSeg { a, b } => vec![*a, *b],
-        EntityKind::Arc {
-            center,
-            radius,
-            start_angle,
-            end_angle,
-        } => {
-            let segs = ((end_angle - start_angle).abs() / std::f32::consts::PI * 32.0)
-                .max(12.0)
-                .round() as usize;
-            let c = *center;
-            let r = *radius as f64;
-            let a0 = *start_angle as f64;
-            let a1 = *end_angle as f64;
-            let mut poly = Vec::with_capacity(segs + 1);
-            for i in 0..=segs {
-                let t = i as f64 / (segs as f64);
-                let a = a0 * (1.0 - t) + a1 * t;
-                poly.push(Pt2::new((c.x as f64 + r * a.cos()) as f32,
-                                   (c.y as f64 + r * a.sin()) as f32));
-            }
-            poly
-        }
-        EntityKind::NurbsCurve2D {
-            degree,
-            knots,
-            ctrl_pts,
-            weights,
-        } => {
-            // Быстрый и универсальный способ: построим BSplineCurve на плоскости (без весов для простоты)
-            // и снимем точки равномерно по параметру.
-            let ctrl: Vec<Point2<f64>> = ctrl_pts.iter().map(|q| p2(*q)).collect();
-            let kv = KnotVec::from(knots.clone());
-            let bs: BSplineCurve<Point2<f64>> = BSplineCurve::new(kv, ctrl);
-            let n = 64usize.max(4 * (degree + 1));
-            (0..=n)
-                .map(|i| {
-                    let t = i as f64 / n as f64;
-                    let pt = bs.subs(t);
-                    Pt2::new(pt.x as f32, pt.y as f32)
-                })
-                .collect()
-        }
-        EntityKind::Text { .. } => vec![],
+/// Проволока из замкнутой кривой через дискретизацию в ломаную (дуги/НУРБС — как у штриховки).
+pub fn wire_from_entity_kind_sampled(kind: &EntityKind) -> Option<Wire> {
+    let mut poly = curve_points(kind);
+    if poly.len() > 1 && poly.first() == poly.last() {
+        poly.pop();
     }
+    (poly.len() >= 3).then(|| wire_from_closed_polyline(&poly))
 }
@@ -0,0 +1,305 @@
+//! Шрифт TrueType для PDF: разбор таблиц, нужных для набора текста, и подмножество
+//! глифов для встраивания (`/FontFile2`). Глифы подмножества перенумерованы подряд,
+//! составные глифы тянут за собой свои части.
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+/// Таблицы, которые попадают в подмножество (по алфавиту тегов, как в каталоге).
+const SUBSET_TABLES: [&[u8; 4]; 9] = [
+    b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"prep",
+];
+
+// флаги частей составного глифа
+const ARG_WORDS: u16 = 0x0001;
+const HAVE_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const XY_SCALE: u16 = 0x0040;
+const TWO_BY_TWO: u16 = 0x0080;
+
+pub(crate) struct TrueType<'a> {
+    tables: HashMap<[u8; 4], &'a [u8]>,
+    pub units_per_em: u16,
+    /// xMin, yMin, xMax, yMax из `head`.
+    pub bbox: [i16; 4],
+    pub ascent: i16,
+    pub descent: i16,
+    num_glyphs: u16,
+    num_h_metrics: u16,
+    long_loca: bool,
+    cmap: HashMap<char, u16>,
+}
+
+fn rd16(d: &[u8], o: usize) -> Result<u16> {
+    d.get(o..o + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| anyhow!("шрифт обрезан"))
+}
+
+fn rd32(d: &[u8], o: usize) -> Result<u32> {
+    d.get(o..o + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| anyhow!("шрифт обрезан"))
+}
+
+fn checksum(d: &[u8]) -> u32 {
+    d.chunks(4).fold(0u32, |s, c| {
+        let mut w = [0u8; 4];
+        w[..c.len()].copy_from_slice(c);
+        s.wrapping_add(u32::from_be_bytes(w))
+    })
+}
+
+impl<'a> TrueType<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        if !matches!(rd32(data, 0)?, 0x0001_0000 | 0x7472_7565) {
+            bail!("не TrueType");
+        }
+        let mut tables = HashMap::new();
+        for i in 0..rd16(data, 4)? as usize {
+            let rec = 12 + 16 * i;
+            let tag: [u8; 4] = data[rec..rec + 4].try_into()?;
+            let (off, len) = (
+                rd32(data, rec + 8)? as usize,
+                rd32(data, rec + 12)? as usize,
+            );
+            let t = data
+                .get(off..off + len)
+                .ok_or_else(|| anyhow!("таблица за концом шрифта"))?;
+            tables.insert(tag, t);
+        }
+        let table = |tag: &[u8; 4]| {
+            tables
+                .get(tag)
+                .copied()
+                .ok_or_else(|| anyhow!("нет таблицы {}", String::from_utf8_lossy(tag)))
+        };
+        let (head, hhea, maxp) = (table(b"head")?, table(b"hhea")?, table(b"maxp")?);
+        for tag in [b"glyf", b"loca", b"hmtx"] {
+            table(tag)?;
+        }
+        let bbox = [36, 38, 40, 42].map(|o| rd16(head, o).unwrap_or(0) as i16);
+        let cmap = parse_cmap(table(b"cmap")?)?;
+        Ok(Self {
+            units_per_em: rd16(head, 18)?,
+            bbox,
+            ascent: rd16(hhea, 4)? as i16,
+            descent: rd16(hhea, 6)? as i16,
+            num_glyphs: rd16(maxp, 4)?,
+            num_h_metrics: rd16(hhea, 34)?.max(1),
+            long_loca: rd16(head, 50)? == 1,
+            tables,
+            cmap,
+        })
+    }
+
+    fn table(&self, tag: &[u8; 4]) -> &'a [u8] {
+        self.tables.get(tag).copied().unwrap_or_default()
+    }
+
+    /// Глиф символа; `None`, если в шрифте его нет.
+    pub fn glyph(&self, ch: char) -> Option<u16> {
+        self.cmap.get(&ch).copied().filter(|&g| g < self.num_glyphs)
+    }
+
+    /// Ширина глифа в единицах `units_per_em`.
+    pub fn advance(&self, gid: u16) -> u16 {
+        let i = gid.min(self.num_h_metrics - 1) as usize;
+        rd16(self.table(b"hmtx"), 4 * i).unwrap_or(0)
+    }
+
+    fn lsb(&self, gid: u16) -> i16 {
+        let (g, n) = (gid as usize, self.num_h_metrics as usize);
+        let o = if g < n {
+            4 * g + 2
+        } else {
+            4 * n + 2 * (g - n)
+        };
+        rd16(self.table(b"hmtx"), o).unwrap_or(0) as i16
+    }
+
+    /// Описание глифа из `glyf`; пустое у глифов без контуров.
+    fn outline(&self, gid: u16) -> &'a [u8] {
+        let (loca, glyf) = (self.table(b"loca"), self.table(b"glyf"));
+        let at = |i: usize| -> usize {
+            if self.long_loca {
+                rd32(loca, 4 * i).unwrap_or(0) as usize
+            } else {
+                2 * rd16(loca, 2 * i).unwrap_or(0) as usize
+            }
+        };
+        let (s, e) = (at(gid as usize), at(gid as usize + 1));
+        glyf.get(s..e.max(s)).unwrap_or_default()
+    }
+
+    /// Верх глифа (yMax), 0 у пустого.
+    pub fn y_max(&self, gid: u16) -> i16 {
+        rd16(self.outline(gid), 8).unwrap_or(0) as i16
+    }
+
+    /// Шрифт из глифов `gids` (первым должен идти `.notdef`): глиф `gids[i]` получает
+    /// номер `i`, части составных глифов добавляются в конец.
+    pub fn subset(&self, gids: &[u16]) -> Vec<u8> {
+        let mut order: Vec<u16> = gids.to_vec();
+        let mut index: HashMap<u16, u16> = HashMap::new();
+        for (i, g) in order.iter().enumerate() {
+            index.entry(*g).or_insert(i as u16);
+        }
+        let mut i = 0;
+        while i < order.len() {
+            for c in components(self.outline(order[i])) {
+                if c < self.num_glyphs && !index.contains_key(&c) {
+                    index.insert(c, order.len() as u16);
+                    order.push(c);
+                }
+            }
+            i += 1;
+        }
+
+        let (mut glyf, mut loca, mut hmtx) = (Vec::new(), Vec::new(), Vec::new());
+        for &g in &order {
+            loca.extend((glyf.len() as u32).to_be_bytes());
+            let mut outline = self.outline(g).to_vec();
+            remap_components(&mut outline, &index);
+            glyf.extend(outline);
+            glyf.resize(glyf.len().next_multiple_of(4), 0);
+            hmtx.extend(self.advance(g).to_be_bytes());
+            hmtx.extend(self.lsb(g).to_be_bytes());
+        }
+        loca.extend((glyf.len() as u32).to_be_bytes());
+
+        let n = (order.len() as u16).to_be_bytes();
+        let mut head = self.table(b"head").to_vec();
+        head[8..12].fill(0);
+        head[50..52].copy_from_slice(&1u16.to_be_bytes());
+        let mut hhea = self.table(b"hhea").to_vec();
+        hhea[34..36].copy_from_slice(&n);
+        let mut maxp = self.table(b"maxp").to_vec();
+        maxp[4..6].copy_from_slice(&n);
+
+        let tables: Vec<(&[u8; 4], Vec<u8>)> = SUBSET_TABLES
+            .iter()
+            .filter_map(|&tag| {
+                let data = match tag {
+                    b"glyf" => std::mem::take(&mut glyf),
+                    b"loca" => std::mem::take(&mut loca),
+                    b"hmtx" => std::mem::take(&mut hmtx),
+                    b"head" => std::mem::take(&mut head),
+                    b"hhea" => std::mem::take(&mut hhea),
+                    b"maxp" => std::mem::take(&mut maxp),
+                    _ => self.tables.get(tag)?.to_vec(),
+                };
+                Some((tag, data))
+            })
+            .collect();
+        write_sfnt(&tables)
+    }
+}
+
+/// Каталог таблиц и сами таблицы с выравниванием на 4 байта; в `head` вписывается
+/// поправка контрольной суммы всего файла.
+fn write_sfnt(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let n = tables.len() as u16;
+    let pow = 1u16 << (15 - n.leading_zeros());
+    let mut out = Vec::new();
+    out.extend(0x0001_0000u32.to_be_bytes());
+    for v in [n, pow * 16, pow.trailing_zeros() as u16, n * 16 - pow * 16] {
+        out.extend(v.to_be_bytes());
+    }
+    let mut off = out.len() + 16 * tables.len();
+    let mut head_at = None;
+    for (tag, data) in tables {
+        if *tag == b"head" {
+            head_at = Some(off);
+        }
+        out.extend(tag.as_slice());
+        out.extend(checksum(data).to_be_bytes());
+        out.extend((off as u32).to_be_bytes());
+        out.extend((data.len() as u32).to_be_bytes());
+        off += data.len().next_multiple_of(4);
+    }
+    for (_, data) in tables {
+        out.extend(data);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    if let Some(h) = head_at {
+        let adj = 0xB1B0_AFBAu32.wrapping_sub(checksum(&out));
+        out[h + 8..h + 12].copy_from_slice(&adj.to_be_bytes());
+    }
+    out
+}
+
+/// Смещения номеров частей в описании составного глифа.
+fn component_slots(outline: &[u8]) -> Vec<usize> {
+    let mut slots = Vec::new();
+    if rd16(outline, 0).map_or(true, |c| (c as i16) >= 0) {
+        return slots;
+    }
+    let mut o = 10;
+    while let Ok(flags) = rd16(outline, o) {
+        slots.push(o + 2);
+        o += 4 + if flags & ARG_WORDS != 0 { 4 } else { 2 };
+        o += match () {
+            _ if flags & HAVE_SCALE != 0 => 2,
+            _ if flags & XY_SCALE != 0 => 4,
+            _ if flags & TWO_BY_TWO != 0 => 8,
+            _ => 0,
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    slots
+}
+
+fn components(outline: &[u8]) -> Vec<u16> {
+    component_slots(outline)
+        .into_iter()
+        .filter_map(|o| rd16(outline, o).ok())
+        .collect()
+}
+
+fn remap_components(outline: &mut [u8], index: &HashMap<u16, u16>) {
+    for o in component_slots(outline) {
+        let Ok(g) = rd16(outline, o) else { continue };
+        let new = index.get(&g).copied().unwrap_or(0);
+        outline[o..o + 2].copy_from_slice(&new.to_be_bytes());
+    }
+}
+
+/// Юникодная таблица символов формата 4 (BMP).
+fn parse_cmap(cmap: &[u8]) -> Result<HashMap<char, u16>> {
+    let mut sub = None;
+    for i in 0..rd16(cmap, 2)? as usize {
+        let rec = 4 + 8 * i;
+        let (platform, encoding) = (rd16(cmap, rec)?, rd16(cmap, rec + 2)?);
+        let off = rd32(cmap, rec + 4)? as usize;
+        if matches!((platform, encoding), (3, 1) | (0, _)) && rd16(cmap, off)? == 4 {
+            sub = Some(off);
+            break;
+        }
+    }
+    let t = &cmap[sub.ok_or_else(|| anyhow!("нет юникодной cmap"))?..];
+    let segs = rd16(t, 6)? as usize / 2;
+    let (ends, starts, deltas, ranges) = (14, 16 + 2 * segs, 16 + 4 * segs, 16 + 6 * segs);
+    let mut map = HashMap::new();
+    for s in 0..segs {
+        let (end, start) = (rd16(t, ends + 2 * s)?, rd16(t, starts + 2 * s)?);
+        let (delta, range) = (rd16(t, deltas + 2 * s)?, rd16(t, ranges + 2 * s)?);
+        for c in start..=end.min(0xfffe) {
+            let g = if range == 0 {
+                c.wrapping_add(delta)
+            } else {
+                let at = ranges + 2 * s + range as usize + 2 * (c - start) as usize;
+                match rd16(t, at)? {
+                    0 => 0,
+                    g => g.wrapping_add(delta),
+                }
+            };
+            if let (Some(ch), true) = (char::from_u32(c as u32), g != 0) {
+                map.insert(ch, g);
+            }
+        }
+    }
+    Ok(map)
+}
@@ -0,0 +1,235 @@
+use cad_core::dxf_io::{export_dxf, import_dxf};
+use cad_core::*;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+fn near(a: Pt2, b: Pt2) -> bool {
+    (a.x - b.x).hypot(a.y - b.y) < 1e-3
+}
+
+/// Марка: кружок R5 с отрезком и атрибутом номера; база в центре.
+fn mark_block() -> BlockDef {
+    BlockDef {
+        name: "MARK".into(),
+        base: Pt2::new(100.0, 100.0),
+        entities: vec![
+            Entity::new(
+                "0",
+                EntityKind::Circle {
+                    center: Pt2::new(100.0, 100.0),
+                    radius: 5.0,
+                },
+            ),
+            Entity::new(
+                "MARKS",
+                EntityKind::LineSeg {
+                    a: Pt2::new(105.0, 100.0),
+                    b: Pt2::new(115.0, 100.0),
+                },
+            ),
+        ],
+        attdefs: vec![Attrib {
+            tag: "NUM".into(),
+            value: "?".into(),
+            pos: Pt2::new(98.0, 98.0),
+            height: 3.0,
+        }],
+    }
+}
+
+fn sample_doc() -> (Document, u64) {
+    let mut doc = Document::new();
+    doc.add_block(mark_block());
+    let id = make_insert(
+        &mut doc,
+        "MARK",
+        Pt2::new(10.0, 20.0),
+        Pt2::new(2.0, 2.0),
+        FRAC_PI_2,
+        "ANNO",
+    )
+    .unwrap();
+    if let EntityKind::Insert { attribs, .. } = &mut doc.entity_mut(id).unwrap().kind {
+        attribs[0].value = "12".into();
+    }
+    (doc, id)
+}
+
+#[test]
+fn make_insert_requires_block_and_fills_attributes() {
+    let mut doc = Document::new();
+    assert!(make_insert(
+        &mut doc,
+        "NOPE",
+        Pt2::new(0.0, 0.0),
+        Pt2::new(1.0, 1.0),
+        0.0,
+        "0"
+    )
+    .is_err());
+
+    let (doc, _) = sample_doc();
+    let EntityKind::Insert { attribs, .. } = &doc.entities().first().unwrap().kind else {
+        unreachable!()
+    };
+    // (98,98) − база → (−2,−2) ×2 → (−4,−4), поворот на 90° → (4,−4), + (10,20)
+    assert!(
+        near(attribs[0].pos, Pt2::new(14.0, 16.0)),
+        "{:?}",
+        attribs[0].pos
+    );
+    assert!((attribs[0].height - 6.0).abs() < 1e-5);
+}
+
+#[test]
+fn explode_places_block_geometry_in_world() {
+    let (mut doc, id) = sample_doc();
+    let new_ids = doc.explode(id);
+    assert_eq!(new_ids.len(), 3);
+    assert!(doc.entities().iter().all(|e| e.id != id));
+
+    let circle = &doc.entities().first().unwrap();
+    assert_eq!(circle.layer, "ANNO", "layer 0 inherits the insert layer");
+    assert_eq!(
+        circle.kind,
+        EntityKind::Circle {
+            center: Pt2::new(10.0, 20.0),
+            radius: 10.0
+        }
+    );
+    let line = &doc.entities().iter().nth(1).unwrap();
+    assert_eq!(line.layer, "MARKS");
+    let EntityKind::LineSeg { a, b } = line.kind else {
+        panic!("{:?}", line.kind)
+    };
+    assert!(near(a, Pt2::new(10.0, 30.0)) && near(b, Pt2::new(10.0, 50.0)));
+    assert!(matches!(
+        &doc.entities().iter().nth(2).unwrap().kind,
+        EntityKind::Text { content, .. } if content == "12"
+    ));
+}
+
+#[test]
+fn nested_inserts_render_through_insert_geometry() {
+    let (mut doc, _) = sample_doc();
+    doc.add_block(BlockDef {
+        name: "PAIR".into(),
+        base: Pt2::new(0.0, 0.0),
+        entities: vec![Entity::new(
+            "0",
+            EntityKind::Insert {
+                block: "MARK".into(),
+                pos: Pt2::new(50.0, 0.0),
+                scale: Pt2::new(1.0, 1.0),
+                rotation: 0.0,
+                attribs: vec![],
+            },
+        )],
+        attdefs: vec![],
+    });
+    let id = make_insert(
+        &mut doc,
+        "PAIR",
+        Pt2::new(0.0, 0.0),
+        Pt2::new(1.0, 1.0),
+        0.0,
+        "0",
+    )
+    .unwrap();
+    let pair = doc.entities().iter().find(|e| e.id == id).unwrap();
+
+    let geom = doc.insert_geometry(pair);
+    assert_eq!(geom.len(), 2);
+    assert_eq!(
+        geom[0].kind,
+        EntityKind::Circle {
+            center: Pt2::new(50.0, 0.0),
+            radius: 5.0
+        }
+    );
+
+    let svg = doc.export_svg(200.0, 200.0);
+    assert!(
+        svg.contains("<circle cx='50.000' cy='0.000' r='5.000' />"),
+        "{svg}"
+    );
+    assert!(svg.contains(">12</text>"));
+}
+
+#[test]
+fn blocks_and_inserts_survive_dxf() {
+    let (doc, _) = sample_doc();
+    let path = std::env::temp_dir().join(format!("cad_core_{}_blocks.dxf", std::process::id()));
+    let path = path.to_str().unwrap().to_owned();
+    export_dxf(&doc, &path).unwrap();
+    let back = import_dxf(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let def = back.block("MARK").expect("block definition");
+    assert_eq!(def.base, Pt2::new(100.0, 100.0));
+    assert_eq!(def.entities.len(), 2);
+    assert_eq!(def.attdefs.len(), 1);
+    assert_eq!(def.attdefs[0].tag, "NUM");
+
+    assert_eq!(back.entities().len(), 1, "{:?}", back.entities());
+    let (src, dst) = (
+        &doc.entities().first().unwrap(),
+        &back.entities().first().unwrap(),
+    );
+    assert_eq!(dst.layer, "ANNO");
+    match (&src.kind, &dst.kind) {
+        (
+            EntityKind::Insert {
+                block: b0,
+                pos: p0,
+                scale: s0,
+                rotation: r0,
+                attribs: a0,
+            },
+            EntityKind::Insert {
+                block: b1,
+                pos: p1,
+                scale: s1,
+                rotation: r1,
+                attribs: a1,
+            },
+        ) => {
+            assert_eq!(b0, b1);
+            assert!(near(*p0, *p1) && near(*s0, *s1));
+            assert!((r0 - r1).abs() < 1e-5);
+            assert_eq!(a1.len(), 1);
+            assert_eq!((a1[0].tag.as_str(), a1[0].value.as_str()), ("NUM", "12"));
+            assert!(near(a0[0].pos, a1[0].pos));
+        }
+        other => panic!("{other:?}"),
+    }
+}
+
+#[test]
+fn non_uniform_scale_turns_arcs_into_ellipses() {
+    let tr = Affine2::scaling(Pt2::new(0.0, 0.0), 2.0, 1.0);
+    let arc = EntityKind::Arc {
+        center: Pt2::new(0.0, 0.0),
+        radius: 1.0,
+        start_angle: 0.0,
+        end_angle: FRAC_PI_2,
+    };
+    let img = arc.transformed(&tr);
+    let EntityKind::Ellipse { ratio, .. } = img else {
+        panic!("{img:?}")
+    };
+    assert!((ratio - 0.5).abs() < 1e-5);
+    let pts = img.sample(32);
+    assert!(near(pts[0], Pt2::new(2.0, 0.0)) && near(pts[pts.len() - 1], Pt2::new(0.0, 1.0)));
+
+    // зеркало сохраняет окружность и соответствие начала/конца дуги
+    let mirror = Affine2::scaling(Pt2::new(0.0, 0.0), -1.0, 1.0);
+    let img = arc.transformed(&mirror);
+    let EntityKind::Arc { .. } = img else {
+        panic!("{img:?}")
+    };
+    let pts = img.sample(32);
+    assert!(near(pts[0], Pt2::new(-1.0, 0.0)) && near(pts[pts.len() - 1], Pt2::new(0.0, 1.0)));
+
+    let rot = Affine2::rotation(Pt2::new(1.0, 1.0), PI);
+    assert!(near(rot.apply(Pt2::new(0.0, 0.0)), Pt2::new(2.0, 2.0)));
+}
@@ -0,0 +1,151 @@
+mod common;
+use cad_core::*;
+use common::p;
+use std::f64::consts::PI;
+
+fn line(ax: f32, ay: f32, bx: f32, by: f32) -> EntityKind {
+    EntityKind::LineSeg {
+        a: p(ax, ay),
+        b: p(bx, by),
+    }
+}
+
+fn arc(cx: f32, cy: f32, r: f32, s: f32, e: f32) -> EntityKind {
+    EntityKind::Arc {
+        center: p(cx, cy),
+        radius: r,
+        start_angle: s,
+        end_angle: e,
+    }
+}
+
+/// Паз 10×4 с полукруглыми торцами: отрезки и дуги с зазорами в концах.
+fn slot(gap: f32) -> Vec<EntityKind> {
+    use std::f32::consts::FRAC_PI_2;
+    vec![
+        line(gap, -2.0, 10.0 - gap, -2.0),
+        arc(10.0, 0.0, 2.0, -FRAC_PI_2, FRAC_PI_2),
+        line(10.0, 2.0 + gap, 0.0, 2.0),
+        arc(0.0, 0.0, 2.0, FRAC_PI_2, 3.0 * FRAC_PI_2),
+    ]
+}
+
+#[test]
+fn joins_lines_and_arcs_within_tolerance() {
+    let exact = 40.0 + 4.0 * PI;
+    let rs = find_loops(&slot(0.0), 0.0);
+    assert_eq!(rs.len(), 1);
+    assert!(rs[0].holes.is_empty());
+    assert!((rs[0].area() - exact).abs() < 0.01 * exact);
+
+    // зазоры 0.005 мм: без допуска контура нет, с допуском — тот же паз
+    assert!(find_loops(&slot(0.005), 0.0).is_empty());
+    let rs = find_loops(&slot(0.005), LOOP_TOL);
+    assert_eq!(rs.len(), 1);
+    assert!((rs[0].area() - exact).abs() < 0.01 * exact);
+    let c = rs[0].centroid().unwrap();
+    assert!((c.x - 5.0).abs() < 1e-2 && c.y.abs() < 1e-2, "{c:?}");
+}
+
+#[test]
+fn loops_keep_arc_edges() {
+    let exact = 40.0 + 4.0 * PI;
+    let rs = find_loops(&slot(0.005), LOOP_TOL);
+    assert!((rs[0].area() - exact).abs() < 0.05, "{}", rs[0].area());
+    // торцы возвращаются дугами, стороны — прямыми сегментами
+    let ents = rs[0].to_entities();
+    let EntityKind::Polyline { bulges, .. } = &ents[0] else {
+        panic!("{ents:?}");
+    };
+    assert_eq!(bulges.iter().filter(|b| **b == 0.0).count(), 2);
+    let sweep: f32 = bulges.iter().map(|b| 4.0 * b.atan()).sum();
+    assert!((sweep.abs() - 2.0 * PI as f32).abs() < 1e-2, "{bulges:?}");
+}
+
+#[test]
+fn minimal_faces_skip_dangling_edges() {
+    // квадрат, диагональ и висячий «хвост»; одна сторона не доходит до угла
+    let kinds = vec![
+        line(0.0, 0.0, 10.0, 0.0),
+        line(10.0, 0.0, 10.0, 10.0),
+        line(10.0, 10.0, 0.0, 10.0),
+        line(0.0, 10.0, 0.0, 0.004),
+        line(0.0, 0.0, 10.0, 10.0),
+        line(10.0, 10.0, 15.0, 15.0),
+    ];
+    let rs = find_loops(&kinds, LOOP_TOL);
+    assert_eq!(rs.len(), 2);
+    for r in &rs {
+        assert!((r.area() - 50.0).abs() < 0.05, "{}", r.area());
+        assert_eq!(r.outer.len(), 3);
+    }
+    // отрезок, не дотянутый до стороны, замыкается на неё
+    let t = vec![
+        line(0.0, 0.0, 10.0, 0.0),
+        line(10.0, 0.0, 10.0, 10.0),
+        line(10.0, 10.0, 0.0, 10.0),
+        line(0.0, 10.0, 0.0, 0.0),
+        line(5.0, 0.003, 5.0, 10.0),
+    ];
+    assert_eq!(find_loops(&t, LOOP_TOL).len(), 2);
+    assert_eq!(find_loops(&t, 0.0).len(), 1);
+}
+
+#[test]
+fn nested_islands_become_holes() {
+    // комната из отрезков, колонна-окружность, в ней квадратная вставка
+    let mut kinds = vec![
+        line(0.0, 0.0, 100.0, 0.0),
+        line(100.0, 0.0, 100.0, 60.0),
+        line(100.0, 60.0, 0.0, 60.0),
+        line(0.0, 60.0, 0.0, 0.0),
+        EntityKind::Circle {
+            center: p(30.0, 30.0),
+            radius: 10.0,
+        },
+    ];
+    kinds.push(EntityKind::Polyline {
+        pts: vec![p(28.0, 28.0), p(32.0, 28.0), p(32.0, 32.0), p(28.0, 32.0)],
+        closed: true,
+        bulges: vec![],
+    });
+    let mut rs = find_loops(&kinds, LOOP_TOL);
+    rs.sort_by(|a, b| b.area().total_cmp(&a.area()));
+    assert_eq!(rs.len(), 3);
+    assert_eq!(rs[0].holes.len(), 1);
+    assert!((rs[0].area() - (6000.0 - 100.0 * PI)).abs() < 1.0);
+    assert_eq!(rs[1].holes.len(), 1);
+    assert!((rs[1].area() - (100.0 * PI - 16.0)).abs() < 1.0);
+    assert!(rs[2].holes.is_empty());
+    assert!((rs[2].area() - 16.0).abs() < 1e-4);
+    assert!(rs[0].contains(p(80.0, 30.0)) && !rs[0].contains(p(30.0, 30.0)));
+}
+
+#[test]
+fn selected_entities_give_hatch_and_extrusion_profiles() {
+    let mut doc = Document::new();
+    let c = [p(0.0, 0.0), p(20.0, 0.0), p(20.0, 10.0), p(0.0, 10.0)];
+    let mut ids: Vec<u64> = (0..4)
+        .map(|i| make_line(&mut doc, c[i], c[(i + 1) % 4], "0"))
+        .collect();
+    let other = make_circle(&mut doc, p(50.0, 0.0), 3.0, "0");
+    let rs = find_loops_in(&doc, &ids, LOOP_TOL);
+    assert_eq!(rs.len(), 1);
+    assert!((rs[0].area() - 200.0).abs() < 1e-4);
+
+    let loops = rs[0].hatch_loops();
+    assert_eq!(loops.len(), 1);
+    assert_eq!(loops[0].polygon().len(), 4);
+    match rs[0].extrusion(300.0) {
+        ElementGeom::Extrusion { profile, height } => {
+            assert_eq!(height, 300.0);
+            assert_eq!(profile.len(), 5);
+            assert_eq!(profile[0], profile[4]);
+        }
+        g => panic!("{g:?}"),
+    }
+
+    ids.push(other);
+    assert_eq!(find_loops_in(&doc, &ids, LOOP_TOL).len(), 2);
+    assert!(find_loops_in(&doc, &ids[..3], LOOP_TOL).is_empty());
+}
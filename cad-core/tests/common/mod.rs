@@ -0,0 +1,119 @@
+//! Общие помощники интеграционных тестов.
+#![allow(dead_code)]
+
+use cad_core::dxf_io::{export_dxf, import_dxf};
+use cad_core::model3d::{Element3D, ElementGeom, Id, Meta};
+use cad_core::*;
+
+// -------------------------- точки --------------------------
+
+pub fn p(x: f32, y: f32) -> Pt2 {
+    Pt2::new(x, y)
+}
+
+pub fn close(a: Pt2, b: Pt2) -> bool {
+    (a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3
+}
+
+// ------------------------- примитивы ------------------------
+
+pub fn kind(doc: &Document, id: u64) -> &EntityKind {
+    &doc.entity(id).unwrap().kind
+}
+
+/// Начало и конец кривой.
+pub fn ends(k: &EntityKind) -> (Pt2, Pt2) {
+    let (t0, t1) = curve_range(k).unwrap();
+    (curve_at(k, t0).unwrap(), curve_at(k, t1).unwrap())
+}
+
+// --------------------------- DXF ---------------------------
+
+/// Документ после записи в DXF и чтения обратно.
+pub fn roundtrip(doc: &Document, name: &str) -> Document {
+    let path = std::env::temp_dir().join(format!("cad_core_{}_{name}.dxf", std::process::id()));
+    let path = path.to_str().unwrap().to_owned();
+    export_dxf(doc, &path).expect("export");
+    let back = import_dxf(&path).expect("import");
+    let _ = std::fs::remove_file(&path);
+    back
+}
+
+// ---------------------------- 3D ----------------------------
+
+pub const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Элемент, сдвинутый в `at`.
+pub fn element(id: Id, geom: ElementGeom, at: [f32; 3], material: u32) -> Element3D {
+    let mut xform = IDENTITY;
+    for i in 0..3 {
+        xform[i][3] = at[i];
+    }
+    Element3D {
+        id,
+        name: format!("E{id}"),
+        xform,
+        geom,
+        material,
+        rebars: vec![],
+        meta: Meta::default(),
+    }
+}
+
+/// Параллелепипед w × d × h с углом в `at`; профиль — без замыкающей точки.
+pub fn cuboid(id: Id, at: [f32; 3], w: f32, d: f32, h: f32) -> Element3D {
+    let profile = vec![
+        Pt2::new(0.0, 0.0),
+        Pt2::new(w, 0.0),
+        Pt2::new(w, d),
+        Pt2::new(0.0, d),
+    ];
+    element(id, ElementGeom::Extrusion { profile, height: h }, at, 0)
+}
+
+// --------------------------- PDF ---------------------------
+
+/// Тело объекта `n 0 obj`.
+pub fn object(s: &str, n: usize) -> &str {
+    let head = format!("\n{n} 0 obj\n");
+    let start = s.find(&head).unwrap() + head.len();
+    &s[start..start + s[start..].find("\nendobj").unwrap()]
+}
+
+/// Номер объекта по ссылке `key n 0 R`.
+pub fn reference(s: &str, key: &str) -> usize {
+    let i = s.find(&format!("{key} ")).unwrap() + key.len() + 1;
+    s[i..].split(' ').next().unwrap().parse().unwrap()
+}
+
+/// Строки `Tj`, прочитанные через `/ToUnicode` шрифта.
+pub fn shown_text(s: &str) -> Vec<String> {
+    let cmap = object(s, reference(s, "/ToUnicode"));
+    let mut map = std::collections::HashMap::new();
+    for l in cmap
+        .lines()
+        .filter(|l| l.starts_with('<') && l.matches('<').count() == 2)
+    {
+        let v: Vec<&str> = l.split(['<', '>', ' ']).filter(|v| !v.is_empty()).collect();
+        if v[0].len() == 4 && v[0] != "0000" {
+            let units: Vec<u16> = (0..v[1].len() / 4)
+                .map(|k| u16::from_str_radix(&v[1][4 * k..4 * k + 4], 16).unwrap())
+                .collect();
+            map.insert(v[0].to_string(), String::from_utf16(&units).unwrap());
+        }
+    }
+    s.lines()
+        .filter_map(|l| l.strip_suffix("> Tj ET"))
+        .map(|l| {
+            let hex = &l[l.rfind('<').unwrap() + 1..];
+            (0..hex.len() / 4)
+                .map(|k| map[&hex[4 * k..4 * k + 4]].clone())
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,371 @@
+mod common;
+use cad_core::*;
+use common::{close, roundtrip};
+use std::f32::consts::FRAC_PI_2;
+
+fn dim_of(doc: &Document, id: u64) -> (Vec<Pt2>, Pt2) {
+    match &doc.entities().iter().find(|e| e.id == id).unwrap().kind {
+        EntityKind::Dimension { pts, line_pos, .. } => {
+            (pts.iter().map(|p| p.pos).collect(), *line_pos)
+        }
+        k => panic!("not a dimension: {k:?}"),
+    }
+}
+
+fn texts(parts: &[EntityKind]) -> Vec<String> {
+    parts
+        .iter()
+        .filter_map(|k| match k {
+            EntityKind::Text { content, .. } => Some(content.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn measures_and_texts_per_kind() {
+    let st = DimStyle::default();
+    let a = Pt2::new(0.0, 0.0);
+    let b = Pt2::new(30.0, 40.0);
+
+    let horiz = DimKind::Linear { angle: 0.0 };
+    let vert = DimKind::Linear { angle: FRAC_PI_2 };
+    assert!((dim_measure(&horiz, &[a, b]).unwrap() - 30.0).abs() < 1e-4);
+    assert!((dim_measure(&vert, &[a, b]).unwrap() - 40.0).abs() < 1e-4);
+    assert!((dim_measure(&DimKind::Aligned, &[a, b]).unwrap() - 50.0).abs() < 1e-4);
+    assert!((dim_measure(&DimKind::Diameter, &[a, b]).unwrap() - 100.0).abs() < 1e-4);
+    assert!((dim_measure(&DimKind::Ordinate { x: false }, &[a, b]).unwrap() - 40.0).abs() < 1e-4);
+
+    let angle = dim_measure(
+        &DimKind::Angular,
+        &[a, Pt2::new(10.0, 0.0), Pt2::new(0.0, 5.0)],
+    )
+    .unwrap();
+    assert!((angle - FRAC_PI_2).abs() < 1e-5);
+
+    assert_eq!(dim_text(&DimKind::Aligned, 50.0, &st, ""), "50.00");
+    assert_eq!(dim_text(&DimKind::Radius, 12.5, &st, ""), "R12.50");
+    assert_eq!(dim_text(&DimKind::Diameter, 25.0, &st, ""), "⌀25.00");
+    assert_eq!(dim_text(&DimKind::Angular, FRAC_PI_2, &st, ""), "90.00°");
+    assert_eq!(
+        dim_text(&DimKind::Aligned, 50.0, &st, "L=<> typ."),
+        "L=50.00 typ."
+    );
+    assert_eq!(dim_text(&DimKind::Aligned, 50.0, &st, "REF"), "REF");
+}
+
+#[test]
+fn style_controls_precision_suffix_and_arrows() {
+    let mut doc = Document::new();
+    doc.add_dim_style(DimStyle {
+        name: "Arch".into(),
+        arrow: DimArrow::Tick,
+        precision: 0,
+        suffix: " mm".into(),
+        ..DimStyle::default()
+    });
+    let st = doc.dim_style("ARCH");
+    assert_eq!(st.precision, 0);
+    // неизвестный стиль — стандартный
+    assert_eq!(doc.dim_style("nope"), DimStyle::default());
+
+    let pts = [Pt2::new(0.0, 0.0), Pt2::new(1234.4, 0.0)];
+    let parts = dim_geometry(
+        &DimKind::Linear { angle: 0.0 },
+        &pts,
+        Pt2::new(0.0, 500.0),
+        "",
+        &st,
+    );
+    assert_eq!(texts(&parts), vec!["1234 mm".to_string()]);
+    // засечки — отрезки, заливок нет
+    assert!(!parts.iter().any(|k| matches!(k, EntityKind::Hatch { .. })));
+
+    let filled = dim_geometry(
+        &DimKind::Linear { angle: 0.0 },
+        &pts,
+        Pt2::new(0.0, 500.0),
+        "",
+        &DimStyle::default(),
+    );
+    let arrows = filled
+        .iter()
+        .filter(|k| matches!(k, EntityKind::Hatch { .. }))
+        .count();
+    assert_eq!(arrows, 2);
+}
+
+#[test]
+fn linear_geometry_places_dimension_line() {
+    let st = DimStyle::default();
+    let parts = dim_geometry(
+        &DimKind::Linear { angle: 0.0 },
+        &[Pt2::new(0.0, 0.0), Pt2::new(100.0, 20.0)],
+        Pt2::new(50.0, 40.0),
+        "",
+        &st,
+    );
+    // размерная линия горизонтальна и проходит через line_pos
+    let dim_line = parts.iter().find_map(|k| match k {
+        EntityKind::LineSeg { a, b } if (a.y - 40.0).abs() < 1e-4 && (b.y - 40.0).abs() < 1e-4 => {
+            Some((*a, *b))
+        }
+        _ => None,
+    });
+    let (a, b) = dim_line.expect("dimension line");
+    assert!((a.x - 0.0).abs() < 1e-4 && (b.x - 100.0).abs() < 1e-4);
+    // выносная линия от второй точки: отступ снизу, выход выше линии
+    assert!(parts
+        .iter()
+        .any(|k| matches!(k, EntityKind::LineSeg { a, b }
+        if (a.x - 100.0).abs() < 1e-4 && (a.y - 20.0 - st.ext_offset).abs() < 1e-4
+            && (b.y - 40.0 - st.ext_extend).abs() < 1e-4)));
+    assert_eq!(texts(&parts), vec!["100.00".to_string()]);
+}
+
+#[test]
+fn dimensions_follow_moved_geometry() {
+    let mut doc = Document::new();
+    let line = make_line(&mut doc, Pt2::new(0.0, 0.0), Pt2::new(100.0, 0.0), "0");
+    let circle = make_circle(&mut doc, Pt2::new(200.0, 0.0), 25.0, "0");
+    let pts = vec![
+        DimPoint::on(&doc, line, DimSnap::Start).unwrap(),
+        DimPoint::on(&doc, line, DimSnap::End).unwrap(),
+    ];
+    let lin = make_dimension(&mut doc, DimKind::Aligned, pts, Pt2::new(50.0, 10.0), "0").unwrap();
+    let pts = vec![
+        DimPoint::on(&doc, circle, DimSnap::Center).unwrap(),
+        DimPoint::on(&doc, circle, DimSnap::OnCurve(0.0)).unwrap(),
+    ];
+    let rad = make_dimension(&mut doc, DimKind::Radius, pts, Pt2::new(240.0, 0.0), "0").unwrap();
+    assert!(make_dimension(&mut doc, DimKind::Angular, vec![], Pt2::new(0.0, 0.0), "0").is_err());
+
+    // весь объект сдвинут — размер едет целиком
+    move_entities(&mut doc, &[line], 0.0, 30.0);
+    let (p, pos) = dim_of(&doc, lin);
+    assert!(close(p[0], Pt2::new(0.0, 30.0)) && close(p[1], Pt2::new(100.0, 30.0)));
+    assert!(close(pos, Pt2::new(50.0, 40.0)));
+
+    // растянули отрезок — точка следует, размерная линия на месте
+    if let EntityKind::LineSeg { b, .. } = &mut doc.entity_mut(line).unwrap().kind {
+        *b = Pt2::new(150.0, 30.0);
+    }
+    update_dimensions(&mut doc);
+    let (p, pos) = dim_of(&doc, lin);
+    assert!(close(p[1], Pt2::new(150.0, 30.0)));
+    assert!(close(pos, Pt2::new(50.0, 40.0)));
+    let parts = dimension_entities(&doc, doc.entities().iter().find(|e| e.id == lin).unwrap());
+    let parts: Vec<EntityKind> = parts.into_iter().map(|e| e.kind).collect();
+    assert_eq!(texts(&parts), vec!["150.00".to_string()]);
+
+    // размер сдвинут без окружности — точки остаются на ней
+    move_entities(&mut doc, &[rad], 5.0, 5.0);
+    let (p, _) = dim_of(&doc, rad);
+    assert!(close(p[0], Pt2::new(200.0, 0.0)) && close(p[1], Pt2::new(225.0, 0.0)));
+
+    // объект удалён — привязка снимается, точки остаются
+    doc.remove_entity(circle);
+    update_dimensions(&mut doc);
+    match &doc.entities().iter().find(|e| e.id == rad).unwrap().kind {
+        EntityKind::Dimension { pts, .. } => assert!(pts.iter().all(|p| p.link.is_none())),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn transformed_dimension_rotates_linear_direction() {
+    let kind = EntityKind::Dimension {
+        kind: DimKind::Linear { angle: 0.0 },
+        pts: vec![
+            DimPoint::free(Pt2::new(0.0, 0.0)),
+            DimPoint::free(Pt2::new(10.0, 0.0)),
+        ],
+        line_pos: Pt2::new(5.0, 5.0),
+        text: String::new(),
+        style: String::new(),
+    };
+    let tr = Affine2::insert(
+        Pt2::new(0.0, 0.0),
+        Pt2::new(1.0, 1.0),
+        FRAC_PI_2,
+        Pt2::new(0.0, 0.0),
+    );
+    match kind.transformed(&tr) {
+        EntityKind::Dimension {
+            kind: DimKind::Linear { angle },
+            pts,
+            line_pos,
+            ..
+        } => {
+            assert!((angle - FRAC_PI_2).abs() < 1e-5);
+            assert!(close(pts[1].pos, Pt2::new(0.0, 10.0)));
+            assert!(close(line_pos, Pt2::new(-5.0, 5.0)));
+        }
+        k => panic!("unexpected {k:?}"),
+    }
+}
+
+#[test]
+fn explode_dimension_gives_primitives() {
+    let mut doc = Document::new();
+    let id = make_dimension(
+        &mut doc,
+        DimKind::Diameter,
+        vec![
+            DimPoint::free(Pt2::new(0.0, 0.0)),
+            DimPoint::free(Pt2::new(10.0, 0.0)),
+        ],
+        Pt2::new(20.0, 0.0),
+        "dims",
+    )
+    .unwrap();
+    let parts = doc.explode(id);
+    assert!(!parts.is_empty());
+    assert!(doc.entities().iter().all(|e| e.layer == "dims"));
+    assert!(doc
+        .entities()
+        .iter()
+        .any(|e| matches!(&e.kind, EntityKind::Text { content, .. } if content == "⌀20.00")));
+}
+
+#[test]
+fn dimensions_survive_dxf() {
+    let mut doc = Document::new();
+    doc.add_dim_style(DimStyle {
+        name: "Arch".into(),
+        arrow: DimArrow::Tick,
+        arrow_size: 1.5,
+        text_height: 3.5,
+        precision: 1,
+        suffix: " mm".into(),
+        ..DimStyle::default()
+    });
+    let free = |x, y| DimPoint::free(Pt2::new(x, y));
+    let o = Pt2::new(0.0, 0.0);
+    make_dimension(
+        &mut doc,
+        DimKind::Linear { angle: 0.0 },
+        vec![free(0.0, 0.0), free(100.0, 20.0)],
+        Pt2::new(50.0, -15.0),
+        "0",
+    )
+    .unwrap();
+    let aligned = make_dimension(
+        &mut doc,
+        DimKind::Aligned,
+        vec![free(0.0, 0.0), free(30.0, 40.0)],
+        Pt2::new(-10.0, 30.0),
+        "0",
+    )
+    .unwrap();
+    if let EntityKind::Dimension { style, text, .. } = &mut doc.entity_mut(aligned).unwrap().kind {
+        *style = "Arch".into();
+        *text = "<> typ.".into();
+    }
+    make_dimension(
+        &mut doc,
+        DimKind::Angular,
+        vec![free(0.0, 0.0), free(10.0, 0.0), free(0.0, 10.0)],
+        Pt2::new(7.0, 7.0),
+        "0",
+    )
+    .unwrap();
+    make_dimension(
+        &mut doc,
+        DimKind::Radius,
+        vec![DimPoint::free(o), free(10.0, 0.0)],
+        Pt2::new(20.0, 0.0),
+        "0",
+    )
+    .unwrap();
+    make_dimension(
+        &mut doc,
+        DimKind::Diameter,
+        vec![free(50.0, 50.0), free(60.0, 50.0)],
+        Pt2::new(70.0, 55.0),
+        "0",
+    )
+    .unwrap();
+    make_dimension(
+        &mut doc,
+        DimKind::Ordinate { x: true },
+        vec![DimPoint::free(o), free(40.0, 10.0)],
+        Pt2::new(40.0, 30.0),
+        "0",
+    )
+    .unwrap();
+    // размер рядом со штриховкой — номера тел HATCH не должны съехать из-за стрелок
+    let sq = vec![
+        Pt2::new(200.0, 0.0),
+        Pt2::new(210.0, 0.0),
+        Pt2::new(210.0, 10.0),
+        Pt2::new(200.0, 10.0),
+    ];
+    make_hatch(
+        &mut doc,
+        vec![HatchLoop::polyline(sq, vec![])],
+        HatchFill::pattern("ANSI31", 0.0, 1.0).unwrap(),
+        "0",
+    )
+    .unwrap();
+
+    let back = roundtrip(&doc, "dims");
+    let dims: Vec<(&DimKind, &Vec<DimPoint>, &String, &String)> = back
+        .entities()
+        .iter()
+        .filter_map(|e| match &e.kind {
+            EntityKind::Dimension {
+                kind,
+                pts,
+                text,
+                style,
+                ..
+            } => Some((kind, pts, text, style)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(dims.len(), 6);
+    let kinds: Vec<DimKind> = dims.iter().map(|d| *d.0).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DimKind::Linear { angle: 0.0 },
+            DimKind::Aligned,
+            DimKind::Angular,
+            DimKind::Radius,
+            DimKind::Diameter,
+            DimKind::Ordinate { x: true },
+        ]
+    );
+    // точки каждого вида — на своих местах
+    let orig: Vec<Vec<Pt2>> = doc
+        .entities()
+        .iter()
+        .filter_map(|e| match &e.kind {
+            EntityKind::Dimension { pts, .. } => Some(pts.iter().map(|p| p.pos).collect()),
+            _ => None,
+        })
+        .collect();
+    for (d, want) in dims.iter().zip(&orig) {
+        let got: Vec<Pt2> = d.1.iter().map(|p| p.pos).collect();
+        assert_eq!(got.len(), want.len());
+        assert!(
+            got.iter().zip(want).all(|(a, b)| close(*a, *b)),
+            "{got:?} vs {want:?}"
+        );
+    }
+    assert_eq!(dims[1].2, "<> typ.");
+    assert_eq!(dims[1].3, "Arch");
+
+    let arch = back.dim_style("Arch");
+    assert_eq!(arch.arrow, DimArrow::Tick);
+    assert_eq!(arch.precision, 1);
+    assert_eq!(arch.suffix, " mm");
+    assert!((arch.text_height - 3.5).abs() < 1e-4);
+    // блоки *D не превращаются в обычные блоки
+    assert!(back.blocks().iter().all(|b| !b.name.starts_with("*D")));
+    assert!(back.entities().iter().any(|e| matches!(
+        &e.kind,
+        EntityKind::Hatch { fill: HatchFill::Pattern { name, .. }, .. } if name == "ANSI31"
+    )));
+}
@@ -0,0 +1,246 @@
+mod common;
+use cad_core::*;
+use common::roundtrip;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+fn assert_same_curve(a: &EntityKind, b: &EntityKind) {
+    let (pa, pb) = (a.sample(64), b.sample(64));
+    for p in &pa {
+        let d = pb
+            .iter()
+            .map(|q| (p.x - q.x).hypot(p.y - q.y))
+            .fold(f32::INFINITY, f32::min);
+        assert!(d < 0.05, "{a:?} -> {b:?}: point {p:?} off by {d}");
+    }
+    // концы открытых кривых должны совпасть с концами
+    let (a0, a1) = (pa[0], pa[pa.len() - 1]);
+    let ends = [pb[0], pb[pb.len() - 1]];
+    for p in [a0, a1] {
+        assert!(
+            ends.iter().any(|q| (p.x - q.x).hypot(p.y - q.y) < 1e-2),
+            "{a:?} -> {b:?}: end {p:?}"
+        );
+    }
+}
+
+#[test]
+fn arcs_circles_ellipses_survive_dxf() {
+    let mut doc = Document::new();
+    make_arc(&mut doc, Pt2::new(10.0, 20.0), 5.0, 0.25, 2.0, "0");
+    // дуга через 0°: в DXF конечный угол меньше начального
+    make_arc(&mut doc, Pt2::new(0.0, 0.0), 3.0, 1.5 * PI, 2.25 * PI, "0");
+    // по часовой — в DXF превращается в ту же дугу против часовой
+    make_arc(
+        &mut doc,
+        Pt2::new(-4.0, 1.0),
+        2.0,
+        FRAC_PI_2,
+        -FRAC_PI_2,
+        "0",
+    );
+    make_circle(&mut doc, Pt2::new(-7.0, 3.0), 12.5, "AXES");
+    make_ellipse(
+        &mut doc,
+        Pt2::new(100.0, 50.0),
+        Pt2::new(30.0, 40.0),
+        0.4,
+        0.0,
+        TAU,
+        "0",
+    );
+    make_ellipse(
+        &mut doc,
+        Pt2::new(-20.0, -20.0),
+        Pt2::new(0.0, 15.0),
+        0.5,
+        -0.5,
+        2.0,
+        "0",
+    );
+    make_line(&mut doc, Pt2::new(0.0, 0.0), Pt2::new(1.0, 1.0), "0");
+    make_polyline(
+        &mut doc,
+        vec![Pt2::new(0.0, 0.0), Pt2::new(5.0, 0.0), Pt2::new(5.0, 5.0)],
+        true,
+        "0",
+    )
+    .unwrap();
+
+    let back = roundtrip(&doc, "arcs");
+    assert_eq!(back.entities().len(), doc.entities().len());
+
+    for (src, dst) in doc.entities().iter().zip(back.entities()) {
+        assert_eq!(src.layer, dst.layer);
+        assert_eq!(
+            std::mem::discriminant(&src.kind),
+            std::mem::discriminant(&dst.kind),
+            "{:?} -> {:?}",
+            src.kind,
+            dst.kind
+        );
+        match (&src.kind, &dst.kind) {
+            (EntityKind::Circle { .. }, _) => {
+                assert_eq!(src.kind, dst.kind);
+            }
+            (EntityKind::Arc { .. } | EntityKind::Ellipse { .. }, _) => {
+                assert_same_curve(&src.kind, &dst.kind);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn full_arc_exports_as_circle() {
+    let mut doc = Document::new();
+    make_arc(&mut doc, Pt2::new(1.0, 2.0), 3.0, 0.0, TAU, "0");
+    let back = roundtrip(&doc, "full_arc");
+    assert_eq!(
+        back.entities().first().unwrap().kind,
+        EntityKind::Circle {
+            center: Pt2::new(1.0, 2.0),
+            radius: 3.0
+        }
+    );
+}
+
+#[test]
+fn ellipse_truck_curve_lies_on_ellipse() {
+    let (c, m, ratio) = (Pt2::new(3.0, -2.0), Pt2::new(8.0, 6.0), 0.25);
+    let kind = EntityKind::Ellipse {
+        center: c,
+        major: m,
+        ratio,
+        start_param: 0.3,
+        end_param: 4.0,
+    };
+    let poly = kind.sample(128);
+
+    let (a, b) = (10.0, 10.0 * ratio);
+    for p in &poly {
+        // координаты в осях эллипса
+        let (dx, dy) = (p.x - c.x, p.y - c.y);
+        let u = (dx * m.x + dy * m.y) / a;
+        let v = (-dx * m.y + dy * m.x) / a;
+        let f = (u / a).powi(2) + (v / b).powi(2);
+        assert!((f - 1.0).abs() < 1e-3, "{p:?}: {f}");
+    }
+    for (p, t) in [(poly[0], 0.3), (poly[poly.len() - 1], 4.0)] {
+        let q = ellipse_point(c, m, ratio, t);
+        assert!((p.x - q.x).hypot(p.y - q.y) < 1e-3, "t={t}: {p:?} vs {q:?}");
+    }
+}
+
+/// Прямоугольник 10×4 со скруглёнными углами R=1 (четверть окружности: bulge = tg(π/8)).
+fn rounded_rect() -> EntityKind {
+    let q = (PI / 8.0).tan();
+    EntityKind::Polyline {
+        pts: vec![
+            Pt2::new(1.0, 0.0),
+            Pt2::new(9.0, 0.0),
+            Pt2::new(10.0, 1.0),
+            Pt2::new(10.0, 3.0),
+            Pt2::new(9.0, 4.0),
+            Pt2::new(1.0, 4.0),
+            Pt2::new(0.0, 3.0),
+            Pt2::new(0.0, 1.0),
+        ],
+        closed: true,
+        bulges: vec![0.0, q, 0.0, q, 0.0, q, 0.0, q],
+    }
+}
+
+#[test]
+fn polyline_bulges_survive_dxf() {
+    let mut doc = Document::new();
+    doc.add_entity(Entity::new("0", rounded_rect()));
+    let back = roundtrip(&doc, "bulges");
+    match (
+        &doc.entities().first().unwrap().kind,
+        &back.entities().first().unwrap().kind,
+    ) {
+        (
+            EntityKind::Polyline {
+                pts: p0,
+                bulges: b0,
+                closed: c0,
+            },
+            EntityKind::Polyline {
+                pts: p1,
+                bulges: b1,
+                closed: c1,
+            },
+        ) => {
+            assert_eq!(p0, p1);
+            assert_eq!(c0, c1);
+            assert_eq!(b0.len(), b1.len());
+            for (a, b) in b0.iter().zip(b1) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+        other => panic!("{other:?}"),
+    }
+}
+
+#[test]
+fn bulge_segments_are_exact_arcs() {
+    let kind = rounded_rect();
+    let EntityKind::Polyline {
+        pts,
+        closed,
+        bulges,
+    } = &kind
+    else {
+        unreachable!()
+    };
+
+    // угол (9,0)→(10,1) — центр (9,1), против часовой
+    let segs = polyline_segments(pts, bulges, *closed);
+    assert_eq!(segs.len(), 8);
+    match segs[1] {
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            assert!((center.x - 9.0).abs() < 1e-5 && (center.y - 1.0).abs() < 1e-5);
+            assert!((radius - 1.0).abs() < 1e-5);
+            assert!((end_angle - start_angle - FRAC_PI_2).abs() < 1e-5);
+        }
+        ref other => panic!("{other:?}"),
+    }
+    // отрицательная выпуклость — по часовой, центр по другую сторону хорды
+    let (c, _, a0, a1) = bulge_arc(Pt2::new(0.0, 0.0), Pt2::new(2.0, 0.0), -1.0).unwrap();
+    assert!((c.x - 1.0).abs() < 1e-6 && c.y.abs() < 1e-6);
+    assert!((a1 - a0 + PI).abs() < 1e-5);
+
+    // все точки NURBS-кривой лежат на прямых сторонах или на дугах углов
+    let corners = [(9.0, 1.0), (9.0, 3.0), (1.0, 3.0), (1.0, 1.0)];
+    for p in kind.sample(400) {
+        let on_side = (p.y.abs() < 1e-4 || (p.y - 4.0).abs() < 1e-4) && (1.0..=9.0).contains(&p.x)
+            || (p.x.abs() < 1e-4 || (p.x - 10.0).abs() < 1e-4) && (1.0..=3.0).contains(&p.y);
+        let on_corner = corners
+            .iter()
+            .any(|&(cx, cy)| ((p.x - cx).hypot(p.y - cy) - 1.0).abs() < 1e-4);
+        assert!(on_side || on_corner, "{p:?}");
+    }
+
+    let flat = flatten_polyline(pts, bulges, *closed, 0.1);
+    assert_eq!(flat.first(), flat.last());
+    assert!(flat.len() > pts.len() + 4 * 10);
+}
+
+#[test]
+fn polyline_without_bulges_reads_old_json() {
+    let json = r#"{"pts":[{"x":0.0,"y":0.0},{"x":1.0,"y":0.0}],"closed":false}"#;
+    let kind: EntityKind = serde_json::from_str(&format!(r#"{{"Polyline":{json}}}"#)).unwrap();
+    assert_eq!(
+        kind,
+        EntityKind::Polyline {
+            pts: vec![Pt2::new(0.0, 0.0), Pt2::new(1.0, 0.0)],
+            closed: false,
+            bulges: vec![],
+        }
+    );
+}
@@ -0,0 +1,247 @@
+mod common;
+use cad_core::*;
+use common::{close, ends, kind, p};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+#[test]
+fn trims_line_between_boundaries() {
+    let mut doc = Document::new();
+    let id = make_line(&mut doc, p(0.0, 0.0), p(10.0, 0.0), "0");
+    let b1 = make_line(&mut doc, p(3.0, -1.0), p(3.0, 1.0), "0");
+    let b2 = make_circle(&mut doc, p(8.0, 0.0), 1.0, "0");
+    doc.entity_mut(id).unwrap().color = Color::Aci(1);
+
+    let ids = trim(&mut doc, id, &[b1, b2], p(5.0, 0.0)).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids[0], id);
+    let (a, b) = ends(kind(&doc, ids[0]));
+    assert!(close(a, p(0.0, 0.0)) && close(b, p(3.0, 0.0)));
+    let (a, b) = ends(kind(&doc, ids[1]));
+    assert!(close(a, p(7.0, 0.0)) && close(b, p(10.0, 0.0)));
+    let piece = doc.entities().iter().find(|e| e.id == ids[1]).unwrap();
+    assert_eq!(piece.color, Color::Aci(1));
+
+    // конец отрезка за последней границей — кусок один
+    let ids = trim(&mut doc, ids[1], &[b2], p(9.5, 0.0)).unwrap();
+    let (a, b) = ends(kind(&doc, ids[0]));
+    assert!(ids.len() == 1 && close(a, p(7.0, 0.0)) && close(b, p(9.0, 0.0)));
+
+    // без пересечений обрезать нечего
+    assert!(trim(&mut doc, id, &[b2], p(1.0, 0.0)).is_err());
+}
+
+#[test]
+fn trims_circle_arc_and_polyline() {
+    let mut doc = Document::new();
+    let c = make_circle(&mut doc, p(0.0, 0.0), 2.0, "0");
+    let l = make_line(&mut doc, p(0.0, -5.0), p(0.0, 5.0), "0");
+
+    // окружность, разрезанная прямой: остаётся левая половина
+    let ids = trim(&mut doc, c, &[l], p(2.0, 0.0)).unwrap();
+    match kind(&doc, ids[0]) {
+        EntityKind::Arc {
+            start_angle,
+            end_angle,
+            ..
+        } => {
+            assert!((start_angle - FRAC_PI_2).abs() < 1e-4);
+            assert!((end_angle - 3.0 * FRAC_PI_2).abs() < 1e-4);
+        }
+        k => panic!("expected arc, got {k:?}"),
+    }
+
+    // полилиния с дуговым сегментом: обрезка внутри дуги сохраняет кривизну
+    let pl = make_polyline(
+        &mut doc,
+        vec![p(-4.0, 3.0), p(-1.0, 3.0), p(1.0, 3.0)],
+        false,
+        "0",
+    )
+    .unwrap();
+    if let EntityKind::Polyline { bulges, .. } = &mut doc.entity_mut(pl).unwrap().kind {
+        *bulges = vec![0.0, 1.0];
+    }
+    let ids = trim(&mut doc, pl, &[l], p(0.5, 1.5)).unwrap();
+    assert_eq!(ids.len(), 1);
+    match kind(&doc, ids[0]) {
+        EntityKind::Polyline { pts, bulges, .. } => {
+            assert_eq!(pts.len(), 3);
+            assert!(close(pts[2], p(0.0, 2.0)));
+            assert!((bulges[1] - (PI / 8.0).tan()).abs() < 1e-4);
+        }
+        k => panic!("expected polyline, got {k:?}"),
+    }
+}
+
+#[test]
+fn trims_nurbs_at_intersection() {
+    let mut doc = Document::new();
+    let n = make_nurbs_open_uniform(
+        &mut doc,
+        3,
+        vec![p(0.0, 0.0), p(2.0, 4.0), p(4.0, -4.0), p(6.0, 0.0)],
+        None,
+        "0",
+    )
+    .unwrap();
+    let l = make_line(&mut doc, p(3.0, -5.0), p(3.0, 5.0), "0");
+    let ids = trim(&mut doc, n, &[l], p(5.0, 0.0)).unwrap();
+    let k = kind(&doc, ids[0]);
+    assert!(matches!(k, EntityKind::NurbsCurve2D { .. }));
+    let (a, b) = ends(k);
+    assert!(close(a, p(0.0, 0.0)));
+    assert!((b.x - 3.0).abs() < 1e-3, "{b:?}");
+}
+
+#[test]
+fn extends_line_arc_and_polyline() {
+    let mut doc = Document::new();
+    let wall = make_line(&mut doc, p(10.0, -20.0), p(10.0, 20.0), "0");
+
+    let l = make_line(&mut doc, p(0.0, 1.0), p(4.0, 1.0), "0");
+    extend(&mut doc, l, &[wall], p(3.9, 1.0)).unwrap();
+    let (a, b) = ends(kind(&doc, l));
+    assert!(close(a, p(0.0, 1.0)) && close(b, p(10.0, 1.0)));
+
+    // начало дуги R10 опускается по окружности до прямой x = 5, т.е. до 60°
+    let ring = make_line(&mut doc, p(5.0, -20.0), p(5.0, 20.0), "0");
+    let arc = make_arc(&mut doc, p(0.0, 0.0), 10.0, FRAC_PI_2, 2.0, "0");
+    extend(&mut doc, arc, &[ring], p(0.0, 10.0)).unwrap();
+    match kind(&doc, arc) {
+        EntityKind::Arc {
+            start_angle,
+            end_angle,
+            ..
+        } => {
+            assert!((start_angle - PI / 3.0).abs() < 1e-4, "{start_angle}");
+            assert!((end_angle - 2.0).abs() < 1e-6);
+        }
+        k => panic!("expected arc, got {k:?}"),
+    }
+
+    let pl = make_polyline(
+        &mut doc,
+        vec![p(0.0, -3.0), p(2.0, -3.0), p(2.0, -1.0)],
+        false,
+        "0",
+    )
+    .unwrap();
+    assert!(extend(&mut doc, pl, &[wall], p(2.0, -1.2)).is_err());
+    assert!(extend(&mut doc, pl, &[wall], p(0.1, -3.0)).is_err());
+    let back = make_line(&mut doc, p(-6.0, -20.0), p(-6.0, 20.0), "0");
+    extend(&mut doc, pl, &[back], p(0.1, -3.0)).unwrap();
+    match kind(&doc, pl) {
+        EntityKind::Polyline { pts, .. } => assert!(close(pts[0], p(-6.0, -3.0))),
+        k => panic!("expected polyline, got {k:?}"),
+    }
+}
+
+#[test]
+fn extends_nurbs_along_its_end_polynomial() {
+    let mut doc = Document::new();
+    let n = make_nurbs_open_uniform(
+        &mut doc,
+        3,
+        vec![p(0.0, 0.0), p(1.0, 1.0), p(2.0, 1.0), p(3.0, 0.5)],
+        None,
+        "0",
+    )
+    .unwrap();
+    let before = kind(&doc, n).clone();
+    let wall = make_line(&mut doc, p(5.0, -20.0), p(5.0, 20.0), "0");
+    extend(&mut doc, n, &[wall], p(3.0, 0.5)).unwrap();
+    let k = kind(&doc, n);
+    let (a, b) = ends(k);
+    assert!(close(a, p(0.0, 0.0)));
+    assert!((b.x - 5.0).abs() < 1e-3, "{b:?}");
+    // исходная часть кривой не меняется
+    let (t0, t1) = curve_range(&before).unwrap();
+    for i in 0..=8 {
+        let t = t0 + (t1 - t0) * i as f64 / 8.0;
+        let q = curve_at(&before, t).unwrap();
+        let u = curve_param(k, q).unwrap();
+        assert!(close(curve_at(k, u).unwrap(), q));
+    }
+}
+
+#[test]
+fn breaks_curves_at_point() {
+    let mut doc = Document::new();
+    let l = make_line(&mut doc, p(0.0, 0.0), p(10.0, 0.0), "0");
+    let ids = break_at_point(&mut doc, l, p(4.0, 0.3)).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert!(close(ends(kind(&doc, ids[0])).1, p(4.0, 0.0)));
+    assert!(close(ends(kind(&doc, ids[1])).0, p(4.0, 0.0)));
+    assert!(break_at_point(&mut doc, l, p(0.0, 0.0)).is_err());
+
+    let sq = make_polyline(
+        &mut doc,
+        vec![p(0.0, 0.0), p(2.0, 0.0), p(2.0, 2.0), p(0.0, 2.0)],
+        true,
+        "0",
+    )
+    .unwrap();
+    let ids = break_at_point(&mut doc, sq, p(1.0, 2.0)).unwrap();
+    match kind(&doc, ids[0]) {
+        EntityKind::Polyline { pts, closed, .. } => {
+            assert!(!closed);
+            assert_eq!(pts.len(), 6);
+            assert!(close(pts[0], p(1.0, 2.0)) && close(pts[5], p(1.0, 2.0)));
+        }
+        k => panic!("expected polyline, got {k:?}"),
+    }
+
+    let c = make_circle(&mut doc, p(0.0, 0.0), 1.0, "0");
+    assert!(break_at_point(&mut doc, c, p(1.0, 0.0)).is_err());
+}
+
+#[test]
+fn joins_touching_pieces() {
+    let mut doc = Document::new();
+    let a = make_line(&mut doc, p(0.0, 0.0), p(1.0, 0.0), "0");
+    let b = make_line(&mut doc, p(2.0, 0.0), p(1.0, 0.0), "0");
+    let c = make_arc(&mut doc, p(2.0, 1.0), 1.0, -FRAC_PI_2, 0.0, "0");
+    let d = make_line(&mut doc, p(3.0, 1.0), p(3.0, 3.0), "0");
+    let ids = join(&mut doc, &[d, b, a, c]).unwrap();
+    assert_eq!(ids, vec![a]);
+    assert_eq!(doc.entities().len(), 1);
+    match kind(&doc, a) {
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => {
+            assert!(!closed);
+            // соосные отрезки слиты в один
+            assert_eq!(pts.len(), 4);
+            assert_eq!(bulges.iter().filter(|b| **b != 0.0).count(), 1);
+        }
+        k => panic!("expected polyline, got {k:?}"),
+    }
+
+    // две дуги одной окружности дают одну дугу
+    let mut doc = Document::new();
+    let a = make_arc(&mut doc, p(0.0, 0.0), 1.0, 0.0, 1.0, "0");
+    let b = make_arc(&mut doc, p(0.0, 0.0), 1.0, 1.0, 2.5, "0");
+    let ids = join(&mut doc, &[a, b]).unwrap();
+    match kind(&doc, ids[0]) {
+        EntityKind::Arc {
+            start_angle,
+            end_angle,
+            ..
+        } => assert!(start_angle.abs() < 1e-4 && (end_angle - 2.5).abs() < 1e-4),
+        k => panic!("expected arc, got {k:?}"),
+    }
+
+    // замкнутый контур
+    let mut doc = Document::new();
+    let sq = [p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0), p(0.0, 1.0)];
+    let ids: Vec<u64> = (0..4)
+        .map(|i| make_line(&mut doc, sq[i], sq[(i + 1) % 4], "0"))
+        .collect();
+    let out = join(&mut doc, &ids).unwrap();
+    assert!(matches!(
+        kind(&doc, out[0]),
+        EntityKind::Polyline { pts, closed: true, .. } if pts.len() == 4
+    ));
+}
@@ -0,0 +1,235 @@
+mod common;
+use cad_core::*;
+use common::{ends, p};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+fn close(a: Pt2, b: Pt2) -> bool {
+    (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4
+}
+
+fn touches(k: &EntityKind, q: Pt2) -> bool {
+    let (s, e) = ends(k);
+    close(s, q) || close(e, q)
+}
+
+#[test]
+fn fillets_and_chamfers_two_lines() {
+    // до угла в начале координат не доходят: кривые продлеваются
+    let a = EntityKind::LineSeg {
+        a: p(10.0, 0.0),
+        b: p(2.0, 0.0),
+    };
+    let b = EntityKind::LineSeg {
+        a: p(0.0, 3.0),
+        b: p(0.0, 10.0),
+    };
+    let cut = corner_curves(&a, &b, CornerJoint::Fillet(1.0)).unwrap();
+    assert_eq!(
+        cut.a,
+        EntityKind::LineSeg {
+            a: p(10.0, 0.0),
+            b: p(1.0, 0.0)
+        }
+    );
+    assert_eq!(
+        cut.b,
+        EntityKind::LineSeg {
+            a: p(0.0, 1.0),
+            b: p(0.0, 10.0)
+        }
+    );
+    match cut.joint.unwrap() {
+        EntityKind::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            assert!(close(center, p(1.0, 1.0)));
+            assert!((radius - 1.0).abs() < 1e-5);
+            assert!((start_angle.rem_euclid(2.0 * PI) - PI).abs() < 1e-4);
+            assert!((end_angle - start_angle - FRAC_PI_2).abs() < 1e-4);
+        }
+        k => panic!("{k:?}"),
+    }
+
+    let cut = corner_curves(&a, &b, CornerJoint::Chamfer(2.0, 3.0)).unwrap();
+    assert!(touches(&cut.a, p(2.0, 0.0)) && touches(&cut.b, p(0.0, 3.0)));
+    assert_eq!(
+        cut.joint,
+        Some(EntityKind::LineSeg {
+            a: p(2.0, 0.0),
+            b: p(0.0, 3.0)
+        })
+    );
+
+    // нулевой радиус — просто угол
+    let cut = corner_curves(&a, &b, CornerJoint::Fillet(0.0)).unwrap();
+    assert!(touches(&cut.a, p(0.0, 0.0)) && touches(&cut.b, p(0.0, 0.0)));
+    assert!(cut.joint.is_none());
+
+    // радиус длиннее отрезка и параллельные прямые не сопрягаются
+    assert!(corner_curves(&a, &b, CornerJoint::Fillet(20.0)).is_none());
+    let par = EntityKind::LineSeg {
+        a: p(0.0, 5.0),
+        b: p(10.0, 5.0),
+    };
+    assert!(corner_curves(&a, &par, CornerJoint::Fillet(1.0)).is_none());
+}
+
+#[test]
+fn crossing_lines_keep_longer_parts() {
+    let a = EntityKind::LineSeg {
+        a: p(-2.0, 0.0),
+        b: p(10.0, 0.0),
+    };
+    let b = EntityKind::LineSeg {
+        a: p(0.0, 8.0),
+        b: p(0.0, -3.0),
+    };
+    let cut = corner_curves(&a, &b, CornerJoint::Fillet(1.0)).unwrap();
+    assert_eq!(ends(&cut.a).1, p(10.0, 0.0));
+    assert!(touches(&cut.a, p(1.0, 0.0)));
+    assert!(touches(&cut.b, p(0.0, 1.0)) && touches(&cut.b, p(0.0, 8.0)));
+}
+
+#[test]
+fn fillet_between_line_and_arc_is_tangent() {
+    let line = EntityKind::LineSeg {
+        a: p(-6.0, 2.0),
+        b: p(6.0, 2.0),
+    };
+    let arc = EntityKind::Arc {
+        center: p(0.0, 0.0),
+        radius: 4.0,
+        start_angle: 0.2,
+        end_angle: 1.2,
+    };
+    let r = 0.5;
+    let cut = corner_curves(&line, &arc, CornerJoint::Fillet(r)).unwrap();
+    let EntityKind::Arc {
+        center: fc,
+        radius: fr,
+        ..
+    } = cut.joint.clone().unwrap()
+    else {
+        panic!("{:?}", cut.joint);
+    };
+    assert!((fr - r).abs() < 1e-4);
+    // касание: центр на расстоянии r от прямой и R ± r от центра дуги
+    assert!(((fc.y - 2.0).abs() - r).abs() < 1e-4);
+    let d = fc.x.hypot(fc.y);
+    assert!((d - (4.0 - r)).abs() < 1e-4 || (d - (4.0 + r)).abs() < 1e-4);
+    let (js, je) = ends(cut.joint.as_ref().unwrap());
+    assert!(touches(&cut.a, js) || touches(&cut.a, je));
+    assert!(touches(&cut.b, js) || touches(&cut.b, je));
+    assert!(matches!(cut.a, EntityKind::LineSeg { .. }));
+    assert!(matches!(cut.b, EntityKind::Arc { radius, .. } if radius == 4.0));
+}
+
+#[test]
+fn rounds_every_polyline_vertex() {
+    let sq = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)],
+        closed: true,
+        bulges: vec![],
+    };
+    let EntityKind::Polyline {
+        pts,
+        closed,
+        bulges,
+    } = corner_polyline(&sq, CornerJoint::Fillet(2.0)).unwrap()
+    else {
+        panic!()
+    };
+    assert!(closed);
+    assert_eq!(pts.len(), 8);
+    let quarter = (PI / 8.0).tan();
+    assert_eq!(
+        bulges
+            .iter()
+            .filter(|b| (**b - quarter).abs() < 1e-4)
+            .count(),
+        4
+    );
+    for c in [p(2.0, 0.0), p(8.0, 0.0), p(10.0, 2.0), p(0.0, 8.0)] {
+        assert!(pts.iter().any(|q| close(*q, c)), "{c:?} not in {pts:?}");
+    }
+
+    // открытая: срезается только внутренняя вершина; слишком длинная фаска не помещается
+    let l = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(4.0, 0.0), p(4.0, 4.0)],
+        closed: false,
+        bulges: vec![],
+    };
+    assert_eq!(
+        corner_polyline(&l, CornerJoint::Chamfer(1.0, 2.0)),
+        Some(EntityKind::Polyline {
+            pts: vec![p(0.0, 0.0), p(3.0, 0.0), p(4.0, 2.0), p(4.0, 4.0)],
+            closed: false,
+            bulges: vec![],
+        })
+    );
+    assert!(corner_polyline(&l, CornerJoint::Chamfer(5.0, 1.0)).is_none());
+}
+
+#[test]
+fn fillet_and_chamfer_commands() {
+    let mut doc = Document::new();
+    let a = make_line(&mut doc, p(0.0, 0.0), p(10.0, 0.0), "walls");
+    let b = make_polyline(
+        &mut doc,
+        vec![p(12.0, 8.0), p(10.0, 6.0), p(10.0, 1.0)],
+        false,
+        "0",
+    )
+    .unwrap();
+    let arc = fillet(&mut doc, a, b, 1.0).unwrap().unwrap();
+    let e = doc.entities().iter().find(|e| e.id == arc).unwrap();
+    assert_eq!(e.layer, "walls");
+    assert!(matches!(e.kind, EntityKind::Arc { .. }));
+    let kind = |doc: &Document, id| {
+        doc.entities()
+            .iter()
+            .find(|e| e.id == id)
+            .unwrap()
+            .kind
+            .clone()
+    };
+    assert!(touches(&kind(&doc, a), p(9.0, 0.0)));
+    match kind(&doc, b) {
+        EntityKind::Polyline { pts, .. } => {
+            assert_eq!(pts.len(), 3);
+            assert!(close(pts[2], p(10.0, 1.0)), "{pts:?}");
+        }
+        k => panic!("{k:?}"),
+    }
+
+    let c = make_line(&mut doc, p(0.0, 5.0), p(0.0, 20.0), "0");
+    let bevel = chamfer(&mut doc, a, c, 1.0, 1.0).unwrap().unwrap();
+    assert_eq!(
+        kind(&doc, bevel),
+        EntityKind::LineSeg {
+            a: p(1.0, 0.0),
+            b: p(0.0, 1.0)
+        }
+    );
+    assert_eq!(doc.entities().len(), 5);
+
+    // одна полилиния дважды — все её вершины
+    let sq = make_polyline(
+        &mut doc,
+        vec![p(20.0, 0.0), p(30.0, 0.0), p(30.0, 10.0)],
+        false,
+        "0",
+    )
+    .unwrap();
+    assert_eq!(fillet(&mut doc, sq, sq, 1.0).unwrap(), None);
+    assert!(
+        matches!(kind(&doc, sq), EntityKind::Polyline { ref bulges, .. } if !bulges.is_empty())
+    );
+
+    assert!(fillet(&mut doc, a, b, 50.0).is_err());
+    let t = make_text(&mut doc, p(0.0, 0.0), "A", 1.0, "0");
+    assert!(chamfer(&mut doc, a, t, 1.0, 1.0).is_err());
+}
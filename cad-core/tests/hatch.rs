@@ -0,0 +1,339 @@
+mod common;
+use cad_core::*;
+use common::roundtrip;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+fn area(poly: &[Pt2]) -> f32 {
+    let n = poly.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (poly[i], poly[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+        * 0.5
+}
+
+/// Прямоугольник 100×60 из отрезков с окружностью R10 внутри.
+fn room_with_column() -> Document {
+    let mut doc = Document::new();
+    let c = [
+        Pt2::new(0.0, 0.0),
+        Pt2::new(100.0, 0.0),
+        Pt2::new(100.0, 60.0),
+        Pt2::new(0.0, 60.0),
+    ];
+    for i in 0..4 {
+        make_line(&mut doc, c[i], c[(i + 1) % 4], "0");
+    }
+    make_circle(&mut doc, Pt2::new(30.0, 30.0), 10.0, "0");
+    doc
+}
+
+#[test]
+fn boundary_with_island_is_detected() {
+    let doc = room_with_column();
+    let loops = detect_boundary(&doc, Pt2::new(70.0, 20.0)).expect("boundary");
+    assert_eq!(loops.len(), 2);
+    assert!((area(&loops[0].polygon()).abs() - 6000.0).abs() < 1.0);
+    // остров — окружность
+    let island = loops[1].polygon();
+    assert!((area(&island).abs() - PI * 100.0).abs() < 2.0);
+    for p in &island {
+        assert!(((p.x - 30.0).hypot(p.y - 30.0) - 10.0).abs() < 1e-3);
+    }
+
+    // внутри окружности — сама окружность, без островов
+    let inner = detect_boundary(&doc, Pt2::new(30.0, 30.0)).expect("circle");
+    assert_eq!(inner.len(), 1);
+    assert!((area(&inner[0].polygon()).abs() - PI * 100.0).abs() < 2.0);
+
+    // снаружи — ничего
+    assert!(detect_boundary(&doc, Pt2::new(200.0, 0.0)).is_none());
+}
+
+#[test]
+fn boundary_splits_crossing_lines() {
+    let mut doc = room_with_column();
+    // перегородка делит комнату; висячий хвост наружу не мешает
+    make_line(&mut doc, Pt2::new(60.0, -10.0), Pt2::new(60.0, 60.0), "0");
+    let loops = detect_boundary(&doc, Pt2::new(80.0, 30.0)).expect("boundary");
+    assert_eq!(loops.len(), 1);
+    assert!((area(&loops[0].polygon()).abs() - 40.0 * 60.0).abs() < 1.0);
+}
+
+#[test]
+fn pattern_stays_inside_and_skips_island() {
+    let doc = room_with_column();
+    let loops = detect_boundary(&doc, Pt2::new(70.0, 20.0)).unwrap();
+    let polygons: Vec<Vec<Pt2>> = loops.iter().map(|l| l.polygon()).collect();
+    let HatchFill::Pattern { lines, .. } = HatchFill::pattern("ANSI31", 0.0, 1.0).unwrap() else {
+        unreachable!()
+    };
+    let segs = pattern_segments(&polygons, &lines);
+    assert!(segs.len() > 20);
+    for [a, b] in &segs {
+        let m = Pt2::new((a.x + b.x) * 0.5, (a.y + b.y) * 0.5);
+        assert!((-1e-3..=100.001).contains(&m.x) && (-1e-3..=60.001).contains(&m.y));
+        assert!((m.x - 30.0).hypot(m.y - 30.0) >= 10.0 - 0.05, "{m:?}");
+        // ANSI31 — под 45°
+        assert!(((b.y - a.y) - (b.x - a.x)).abs() < 1e-3);
+    }
+
+    let tris = fill_triangles(&polygons);
+    let filled: f32 = tris.iter().map(|t| area(t).abs()).sum();
+    assert!((filled - (6000.0 - PI * 100.0)).abs() < 2.0, "{filled}");
+}
+
+#[test]
+fn hatch_at_point_adds_entity() {
+    let mut doc = room_with_column();
+    let id = hatch_at_point(&mut doc, Pt2::new(70.0, 20.0), HatchFill::Solid, "0").unwrap();
+    assert!(matches!(
+        doc.entities().last().unwrap().kind,
+        EntityKind::Hatch { ref loops, .. } if loops.len() == 2
+    ));
+    assert_eq!(doc.entities().last().unwrap().id, id);
+    assert!(hatch_at_point(&mut doc, Pt2::new(-5.0, -5.0), HatchFill::Solid, "0").is_err());
+}
+
+fn edge_loop() -> HatchLoop {
+    // контур из рёбер: отрезок, дуга, сплайн и эллиптическая дуга по часовой
+    HatchLoop {
+        edges: vec![
+            EntityKind::LineSeg {
+                a: Pt2::new(0.0, 0.0),
+                b: Pt2::new(10.0, 0.0),
+            },
+            EntityKind::Arc {
+                center: Pt2::new(10.0, 5.0),
+                radius: 5.0,
+                start_angle: -FRAC_PI_2,
+                end_angle: FRAC_PI_2,
+            },
+            EntityKind::NurbsCurve2D {
+                degree: 2,
+                knots: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+                ctrl_pts: vec![
+                    Pt2::new(10.0, 10.0),
+                    Pt2::new(5.0, 14.0),
+                    Pt2::new(0.0, 10.0),
+                ],
+                weights: None,
+            },
+            EntityKind::Ellipse {
+                center: Pt2::new(0.0, 5.0),
+                major: Pt2::new(0.0, 5.0),
+                ratio: 0.4,
+                start_param: 0.0,
+                end_param: -PI,
+            },
+        ],
+    }
+}
+
+#[test]
+fn hatches_survive_dxf() {
+    let mut doc = Document::new();
+    let q = (PI / 8.0).tan();
+    let rounded = HatchLoop::polyline(
+        vec![
+            Pt2::new(1.0, 0.0),
+            Pt2::new(9.0, 0.0),
+            Pt2::new(10.0, 1.0),
+            Pt2::new(10.0, 3.0),
+            Pt2::new(0.0, 3.0),
+        ],
+        vec![0.0, q, 0.0, 0.0, 0.0],
+    );
+    make_line(&mut doc, Pt2::new(0.0, 0.0), Pt2::new(1.0, 1.0), "0");
+    make_hatch(&mut doc, vec![rounded], HatchFill::Solid, "0").unwrap();
+    let custom = HatchFill::custom(
+        "MYDASH",
+        vec![HatchLine::from_pat(
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+            2.0,
+            &[3.0, -1.0, 0.0, -1.0],
+        )],
+        0.5,
+        2.0,
+    );
+    let ent = make_hatch(&mut doc, vec![edge_loop()], custom, "0").unwrap();
+    doc.entity_mut(ent).unwrap().color = Color::Aci(3);
+    make_hatch(
+        &mut doc,
+        vec![HatchLoop::polyline(
+            vec![
+                Pt2::new(20.0, 0.0),
+                Pt2::new(30.0, 0.0),
+                Pt2::new(25.0, 8.0),
+            ],
+            vec![],
+        )],
+        HatchFill::pattern("AR-CONC", 0.3, 0.05).unwrap(),
+        "0",
+    )
+    .unwrap();
+    // штриховка в блоке
+    doc.add_block(BlockDef {
+        name: "B".into(),
+        base: Pt2::new(0.0, 0.0),
+        entities: vec![Entity::new(
+            "0",
+            EntityKind::Hatch {
+                loops: vec![HatchLoop::polyline(
+                    vec![Pt2::new(0.0, 0.0), Pt2::new(1.0, 0.0), Pt2::new(0.0, 1.0)],
+                    vec![],
+                )],
+                fill: HatchFill::Solid,
+            },
+        )],
+        attdefs: vec![],
+    });
+
+    let back = roundtrip(&doc, "hatch");
+    let hatches = |d: &Document| -> Vec<Entity> {
+        d.entities()
+            .iter()
+            .filter(|e| matches!(e.kind, EntityKind::Hatch { .. }))
+            .cloned()
+            .collect()
+    };
+    let (src, dst) = (hatches(&doc), hatches(&back));
+    assert_eq!(src.len(), 3);
+    assert_eq!(dst.len(), 3);
+    assert_eq!(back.entities().len(), doc.entities().len());
+    // штриховки читаются первыми — рисуются под остальным
+    assert!(matches!(
+        back.entities().first().unwrap().kind,
+        EntityKind::Hatch { .. }
+    ));
+
+    for (a, b) in src.iter().zip(&dst) {
+        assert_eq!(a.color, b.color);
+        let (
+            EntityKind::Hatch {
+                loops: la,
+                fill: fa,
+            },
+            EntityKind::Hatch {
+                loops: lb,
+                fill: fb,
+            },
+        ) = (&a.kind, &b.kind)
+        else {
+            unreachable!()
+        };
+        assert_eq!(la.len(), lb.len());
+        for (x, y) in la.iter().zip(lb) {
+            let (px, py) = (x.polygon(), y.polygon());
+            assert!((area(&px) - area(&py)).abs() < 1e-2, "{x:?} -> {y:?}");
+            for p in &px {
+                let d = py
+                    .iter()
+                    .map(|q| (p.x - q.x).hypot(p.y - q.y))
+                    .fold(f32::INFINITY, f32::min);
+                assert!(d < 0.05, "{p:?} off by {d}");
+            }
+        }
+        match (fa, fb) {
+            (HatchFill::Solid, HatchFill::Solid) => {}
+            (
+                HatchFill::Pattern {
+                    name: na,
+                    lines: ka,
+                    scale: sa,
+                    ..
+                },
+                HatchFill::Pattern {
+                    name: nb,
+                    lines: kb,
+                    scale: sb,
+                    ..
+                },
+            ) => {
+                assert_eq!(na, nb);
+                assert!((sa - sb).abs() < 1e-5);
+                assert_eq!(ka.len(), kb.len());
+                for (u, v) in ka.iter().zip(kb) {
+                    assert!((u.angle - v.angle).abs() < 1e-4);
+                    assert!((u.offset.x - v.offset.x).abs() < 1e-3);
+                    assert!((u.offset.y - v.offset.y).abs() < 1e-3);
+                    assert_eq!(u.dashes.len(), v.dashes.len());
+                }
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+
+    let block = back.block("B").expect("block");
+    assert!(matches!(
+        block.entities[0].kind,
+        EntityKind::Hatch {
+            fill: HatchFill::Solid,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn svg_uses_pattern_fill() {
+    let mut doc = room_with_column();
+    hatch_at_point(
+        &mut doc,
+        Pt2::new(70.0, 20.0),
+        HatchFill::pattern("ANSI37", 0.0, 1.0).unwrap(),
+        "0",
+    )
+    .unwrap();
+    hatch_at_point(&mut doc, Pt2::new(30.0, 30.0), HatchFill::Solid, "0").unwrap();
+    let svg = doc.export_svg(200.0, 100.0);
+    assert_eq!(svg.matches("<pattern").count(), 2);
+    assert_eq!(svg.matches("fill='url(#").count(), 2);
+    assert!(svg.contains("fill-rule='evenodd'"));
+    assert!(svg.contains("fill='black'"));
+}
+
+#[test]
+fn hatch_moves_with_pattern() {
+    let fill = HatchFill::pattern("ANSI31", 0.0, 1.0).unwrap();
+    let kind = EntityKind::Hatch {
+        loops: vec![HatchLoop::polyline(
+            vec![
+                Pt2::new(0.0, 0.0),
+                Pt2::new(10.0, 0.0),
+                Pt2::new(10.0, 10.0),
+            ],
+            vec![],
+        )],
+        fill,
+    };
+    let tr = Affine2::rotation(Pt2::new(0.0, 0.0), FRAC_PI_2).then(&Affine2::scaling(
+        Pt2::new(0.0, 0.0),
+        2.0,
+        2.0,
+    ));
+    let EntityKind::Hatch { loops, fill } = kind.transformed(&tr) else {
+        unreachable!()
+    };
+    let poly = loops[0].polygon();
+    assert!((poly[1].x).abs() < 1e-4 && (poly[1].y - 20.0).abs() < 1e-4);
+    let HatchFill::Pattern {
+        angle,
+        scale,
+        lines,
+        ..
+    } = fill
+    else {
+        unreachable!()
+    };
+    assert!((angle - FRAC_PI_2).abs() < 1e-5);
+    assert!((scale - 2.0).abs() < 1e-5);
+    // 45° + 90°, шаг вдвое больше
+    assert!((lines[0].angle - 3.0 * PI / 4.0).abs() < 1e-4);
+    let step = lines[0].offset.x.hypot(lines[0].offset.y);
+    assert!((step - 2.0 * 3.175).abs() < 1e-3);
+}
@@ -0,0 +1,218 @@
+mod common;
+use cad_core::*;
+use common::p;
+
+fn ids(doc: &Document) -> Vec<u64> {
+    doc.entities().ids().collect()
+}
+
+#[test]
+fn undo_and_redo_restore_states() {
+    let mut doc = Document::new();
+    let mut h = History::new();
+    let a = h.run(&mut doc, "Line", |d| {
+        make_line(d, p(0.0, 0.0), p(10.0, 0.0), "0")
+    });
+    let b = h.run(&mut doc, "Circle", |d| {
+        make_circle(d, p(5.0, 5.0), 2.0, "0")
+    });
+    let c = h.run(&mut doc, "Line", |d| {
+        make_line(d, p(0.0, 5.0), p(10.0, 5.0), "0")
+    });
+    let before = doc.clone();
+
+    // правка, удаление из середины и добавление — одним шагом
+    h.run(&mut doc, "Edit", |d| {
+        move_entities(d, &[a], 1.0, 1.0);
+        d.remove_entity(b);
+        make_line(d, p(1.0, 1.0), p(2.0, 2.0), "0");
+    });
+    let after = doc.clone();
+    assert_eq!(h.undo_list().count(), 4);
+
+    assert_eq!(h.undo(&mut doc).as_deref(), Some("Edit"));
+    assert_eq!(doc, before);
+    assert_eq!(ids(&doc), vec![a, b, c]);
+    assert_eq!(h.redo(&mut doc).as_deref(), Some("Edit"));
+    assert_eq!(doc, after);
+
+    while h.undo(&mut doc).is_some() {}
+    assert!(doc.entities().is_empty());
+    assert_eq!(
+        h.redo_list().map(|t| t.label()).collect::<Vec<_>>()[..2],
+        ["Line", "Circle"]
+    );
+    while h.redo(&mut doc).is_some() {}
+    assert_eq!(doc, after);
+
+    // новая правка после отмены сбрасывает повтор
+    h.undo(&mut doc);
+    h.run(&mut doc, "Circle", |d| {
+        make_circle(d, p(0.0, 0.0), 1.0, "0")
+    });
+    assert!(!h.can_redo());
+}
+
+#[test]
+fn drag_is_one_step() {
+    let mut doc = Document::new();
+    let mut h = History::new();
+    let a = h.run(&mut doc, "Line", |d| {
+        make_line(d, p(0.0, 0.0), p(10.0, 0.0), "0")
+    });
+    let d0 = h.run(&mut doc, "Dimension", |d| {
+        let pts = vec![
+            DimPoint::on(d, a, DimSnap::Start).unwrap(),
+            DimPoint::on(d, a, DimSnap::End).unwrap(),
+        ];
+        make_dimension(d, DimKind::Aligned, pts, p(5.0, 5.0), "0").unwrap()
+    });
+    let before = doc.clone();
+    for _ in 0..50 {
+        h.begin(&mut doc, "Move");
+        move_entities(&mut doc, &[a], 0.5, 0.0);
+    }
+    assert!(h.is_open());
+    assert!(h.commit(&mut doc));
+    assert_eq!(h.undo_list().count(), 3);
+    // шаг хранит по одному состоянию на сущность, а не на каждый сдвиг
+    assert_eq!(h.undo_list().last().unwrap().len(), 2);
+
+    h.undo(&mut doc);
+    assert_eq!(doc, before);
+    h.redo(&mut doc);
+    match &doc.entity(d0).unwrap().kind {
+        EntityKind::Dimension { pts, .. } => assert!((pts[1].pos.x - 35.0).abs() < 1e-4),
+        k => panic!("{k:?}"),
+    }
+    // транзакция без изменений в историю не попадает
+    h.begin(&mut doc, "Nothing");
+    let _ = doc.entity_mut(a);
+    assert!(!h.commit(&mut doc));
+    assert_eq!(h.undo_list().count(), 3);
+}
+
+#[test]
+fn trims_layers_and_blocks_undo() {
+    let mut doc = Document::new();
+    let mut h = History::new();
+    let (a, b) = h.run(&mut doc, "Lines", |d| {
+        (
+            make_line(d, p(0.0, 0.0), p(10.0, 0.0), "0"),
+            make_line(d, p(5.0, -5.0), p(5.0, 5.0), "0"),
+        )
+    });
+    let before = doc.clone();
+    h.run(&mut doc, "Break", |d| {
+        break_at_point(d, a, p(3.0, 0.0)).unwrap()
+    });
+    assert_eq!(doc.entities().len(), 3);
+    h.run(&mut doc, "Layer", |d| {
+        d.add_layer(Layer::new("walls"));
+        d.layer_mut("0").unwrap().visible = false;
+    });
+    h.run(&mut doc, "Block", |d| {
+        d.add_block(BlockDef {
+            name: "B".into(),
+            base: p(0.0, 0.0),
+            entities: vec![],
+            attdefs: vec![],
+        })
+    });
+    h.undo(&mut doc);
+    assert!(doc.block("B").is_none());
+    h.undo(&mut doc);
+    assert!(doc.layer("walls").is_none() && doc.is_layer_visible("0"));
+    h.undo(&mut doc);
+    assert_eq!(doc, before);
+    assert_eq!(ids(&doc), vec![a, b]);
+
+    // отменённые изменения видны индексу
+    doc.refresh_index();
+    assert_eq!(doc.entities_near(p(8.0, 0.0), 0.5).len(), 1);
+}
+
+#[test]
+fn failed_edit_rolls_back() {
+    let mut doc = Document::new();
+    let mut h = History::new();
+    let a = make_line(&mut doc, p(0.0, 0.0), p(10.0, 0.0), "0");
+    let before = doc.clone();
+    h.begin(&mut doc, "Trim");
+    move_entities(&mut doc, &[a], 5.0, 0.0);
+    assert!(trim(&mut doc, a, &[], p(1.0, 0.0)).is_err());
+    h.rollback(&mut doc);
+    assert_eq!(doc, before);
+    assert!(!h.can_undo());
+}
+
+#[test]
+fn memory_is_bounded() {
+    let mut doc = Document::new();
+    let mut h = History::with_limits(3, usize::MAX);
+    for i in 0..10 {
+        h.run(&mut doc, format!("L{i}"), |d| {
+            make_line(d, p(i as f32, 0.0), p(i as f32, 1.0), "0")
+        });
+    }
+    let labels: Vec<_> = h.undo_list().map(|t| t.label().to_string()).collect();
+    assert_eq!(labels, ["L7", "L8", "L9"]);
+
+    // малый бюджет: остаётся только последний шаг; добавленное живёт в документе,
+    // а удалённое — в истории
+    let mut h = History::with_limits(100, 1);
+    let big: Vec<Pt2> = (0..1000).map(|i| p(i as f32, 0.0)).collect();
+    for _ in 0..3 {
+        let id = make_polyline(&mut doc, big.clone(), false, "0").unwrap();
+        h.run(&mut doc, "Erase", |d| d.remove_entity(id));
+    }
+    assert_eq!(h.undo_list().count(), 1);
+    assert!(h.bytes() > 8000);
+    // отмена и повтор пределы тоже соблюдают
+    for _ in 0..5 {
+        h.undo(&mut doc);
+        h.redo(&mut doc);
+        assert_eq!(h.undo_list().count(), 1);
+    }
+
+    // открытие файла отменяется целиком
+    let before = doc.clone();
+    h.replace(&mut doc, Document::new(), "Open");
+    assert!(doc.entities().is_empty());
+    h.undo(&mut doc);
+    assert_eq!(doc, before);
+}
+
+#[test]
+fn bulk_delete_undo_is_linear() {
+    let mut doc = Document::new();
+    for i in 0..10_000 {
+        let x = (i % 100) as f32;
+        make_line(&mut doc, p(x, 0.0), p(x, 1.0), "0");
+    }
+    let all = ids(&doc);
+    let mut h = History::new();
+
+    // всё, кроме каждой десятой — вперемешку с оставшимися
+    let gone: Vec<u64> = all.iter().copied().filter(|id| id % 10 != 0).collect();
+    h.run(&mut doc, "Erase", |d| {
+        for &id in &gone {
+            d.remove_entity(id);
+        }
+    });
+    assert_eq!(doc.entities().len(), 1000);
+
+    let t = std::time::Instant::now();
+    h.undo(&mut doc);
+    // по-квадратичному это секунды даже в сборке без оптимизаций
+    assert!(t.elapsed().as_millis() < 500, "{:?}", t.elapsed());
+    assert_eq!(ids(&doc), all);
+
+    h.redo(&mut doc);
+    assert_eq!(doc.entities().len(), 1000);
+    h.undo(&mut doc);
+    assert_eq!(ids(&doc), all);
+    doc.refresh_index();
+    let near = doc.entities_in_rect(&Bbox::new(p(41.5, -1.0), p(42.5, 2.0)));
+    assert_eq!(near.len(), 100);
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "ifc")]
+
+use cad_core::ifc::import_ifc_str;
+use cad_core::*;
+
+/// Здание сдвинуто на 1 м по X, этаж — на 3 м вверх; стена повёрнута на 90° внутри
+/// этажа, профиль — `IfcPolyline`; плита — `IfcMappedItem` со сдвигом карты.
+/// Единицы файла — миллиметры.
+const FIXTURE: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''),'2;1');
+FILE_NAME('','2026-01-01T00:00:00',(''),(''),'','','');
+FILE_SCHEMA(('IFC4X3_ADD2'));
+ENDSEC;
+
+DATA;
+#1= IFCORGANIZATION($,'test',$,$,$);
+#2= IFCAPPLICATION(#1,'0.1.0','test','test');
+#3= IFCPERSON($,$,$,$,$,$,$,$);
+#4= IFCORGANIZATION($,'test',$,$,$);
+#5= IFCPERSONANDORGANIZATION(#3,#4,$);
+#6= IFCOWNERHISTORY(#5,#2,$,.ADDED.,0,#5,#2,0);
+#7= IFCSIUNIT(*,.LENGTHUNIT.,.MILLI.,.METRE.);
+#8= IFCSIUNIT(*,.PLANEANGLEUNIT.,$,.RADIAN.);
+#10= IFCCARTESIANPOINT((0.,0.,0.));
+#11= IFCAXIS2PLACEMENT3D(#10,$,$);
+#12= IFCGEOMETRICREPRESENTATIONCONTEXT('Body','Model',3,$,#11,$);
+#13= IFCUNITASSIGNMENT((#7,#8));
+#14= IFCPROJECT('0lltEKS4n4tQdwuP1$zagZ',#6,'Project',$,$,$,$,(#12),#13);
+#15= IFCGEOMETRICREPRESENTATIONSUBCONTEXT('Body','Model',*,*,*,*,#12,$,.MODEL_VIEW.,$);
+#16= IFCCARTESIANPOINT((0.,0.,0.));
+#17= IFCAXIS2PLACEMENT3D(#16,$,$);
+#18= IFCLOCALPLACEMENT($,#17);
+#19= IFCSITE('24ZlluOD932R5MqOLrcE4$',#6,'Site',$,$,#18,$,$,$,$,$,$,$,$);
+#20= IFCCARTESIANPOINT((1000.,0.,0.));
+#21= IFCAXIS2PLACEMENT3D(#20,$,$);
+#22= IFCLOCALPLACEMENT(#18,#21);
+#23= IFCBUILDING('3guPFC4jP0AQijvxZqv7uT',#6,'Корпус А',$,$,#22,$,$,$,$,$,$);
+#24= IFCCARTESIANPOINT((0.,0.,3000.));
+#25= IFCAXIS2PLACEMENT3D(#24,$,$);
+#26= IFCLOCALPLACEMENT(#22,#25);
+#27= IFCBUILDINGSTOREY('1IEIq$ufP6MQt9llFwgHHe',#6,'Этаж 2',$,$,#26,$,$,$,$);
+#30= IFCCARTESIANPOINT((0.,0.));
+#31= IFCCARTESIANPOINT((4000.,0.));
+#32= IFCCARTESIANPOINT((4000.,200.));
+#33= IFCCARTESIANPOINT((0.,200.));
+#34= IFCPOLYLINE((#30,#31,#32,#33,#30));
+#35= IFCARBITRARYCLOSEDPROFILEDEF(.AREA.,$,#34);
+#36= IFCDIRECTION((0.,0.,1.));
+#37= IFCEXTRUDEDAREASOLID(#35,$,#36,2800.);
+#38= IFCSHAPEREPRESENTATION(#15,'Body','SweptSolid',(#37));
+#39= IFCPRODUCTDEFINITIONSHAPE($,$,(#38));
+#40= IFCCARTESIANPOINT((500.,0.,0.));
+#41= IFCDIRECTION((0.,0.,1.));
+#42= IFCDIRECTION((0.,1.,0.));
+#43= IFCAXIS2PLACEMENT3D(#40,#41,#42);
+#44= IFCLOCALPLACEMENT(#26,#43);
+#45= IFCWALL('0Siuhy1DTEbhw1qTAynpap',#6,'Стена 1',$,$,#44,#39,$,$);
+#50= IFCCARTESIANPOINT((0.,0.));
+#51= IFCCARTESIANPOINT((3000.,0.));
+#52= IFCCARTESIANPOINT((3000.,2000.));
+#53= IFCCARTESIANPOINT((0.,2000.));
+#54= IFCPOLYLINE((#50,#51,#52,#53,#50));
+#55= IFCARBITRARYCLOSEDPROFILEDEF(.AREA.,$,#54);
+#56= IFCEXTRUDEDAREASOLID(#55,$,#36,200.);
+#57= IFCSHAPEREPRESENTATION(#15,'Body','SweptSolid',(#56));
+#58= IFCCARTESIANPOINT((0.,0.,0.));
+#59= IFCAXIS2PLACEMENT3D(#58,$,$);
+#60= IFCREPRESENTATIONMAP(#59,#57);
+#61= IFCCARTESIANPOINT((2000.,0.,0.));
+#62= IFCCARTESIANTRANSFORMATIONOPERATOR3D($,$,#61,$,$);
+#63= IFCMAPPEDITEM(#60,#62);
+#64= IFCSHAPEREPRESENTATION(#15,'Body','MappedRepresentation',(#63));
+#65= IFCPRODUCTDEFINITIONSHAPE($,$,(#64));
+#66= IFCCARTESIANPOINT((0.,0.,0.));
+#67= IFCAXIS2PLACEMENT3D(#66,$,$);
+#68= IFCLOCALPLACEMENT(#26,#67);
+#69= IFCSLAB('2j2oxlIQ54peZEmMzeGIdE',#6,'П-1',$,$,#68,#65,$,$);
+#70= IFCRELCONTAINEDINSPATIALSTRUCTURE('2X9REtFuD4aAEYkPqukSJC',#6,$,$,(#45,#69),#27);
+#71= IFCRELAGGREGATES('1bkm1z1aPDIOAuvQGCntAI',$,$,$,#23,(#27));
+#72= IFCRELAGGREGATES('217t7XQ2nAAQ3Ca65V5f2J',$,$,$,#19,(#23));
+#73= IFCRELAGGREGATES('1GkvswpBf6QQ15DPkf3qW$',$,$,$,#14,(#19));
+ENDSEC;
+
+END-ISO-10303-21;
+"#;
+
+fn near_xform(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> bool {
+    a.iter()
+        .flatten()
+        .zip(b.iter().flatten())
+        .all(|(x, y)| (x - y).abs() < 1e-3)
+}
+
+#[test]
+fn ifc_fixture_import() {
+    let project = import_ifc_str(FIXTURE).expect("fixture must parse");
+    assert_eq!(project.models.len(), 1);
+    let model = &project.models[0];
+    assert_eq!(model.name, "Корпус А");
+    assert_eq!(model.elements.len(), 2);
+
+    // цепочка placement: здание (+1000 X) → этаж (+3000 Z) → стена (+500 X, поворот 90°)
+    let wall = model.elements.iter().find(|e| e.name == "Стена 1").unwrap();
+    assert_eq!(wall.meta.props["ifc:type"], "IfcWall");
+    assert_eq!(wall.meta.props["ifc:storey"], "Этаж 2");
+    let expected = [
+        [0.0, -1.0, 0.0, 1500.0],
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 3000.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    assert!(near_xform(&wall.xform, &expected), "{:?}", wall.xform);
+    let ElementGeom::Extrusion { profile, height } = &wall.geom else {
+        panic!("wall must stay an extrusion: {:?}", wall.geom);
+    };
+    assert_eq!(*height, 2800.0);
+    assert_eq!(
+        profile,
+        &vec![
+            Pt2::new(0.0, 0.0),
+            Pt2::new(4000.0, 0.0),
+            Pt2::new(4000.0, 200.0),
+            Pt2::new(0.0, 200.0),
+            Pt2::new(0.0, 0.0),
+        ]
+    );
+
+    // MappedItem: сдвиг оператора карты добавляется к placement плиты
+    let slab = model.elements.iter().find(|e| e.name == "П-1").unwrap();
+    assert_eq!(slab.meta.props["ifc:type"], "IfcSlab");
+    let expected = [
+        [1.0, 0.0, 0.0, 3000.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 3000.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    assert!(near_xform(&slab.xform, &expected), "{:?}", slab.xform);
+    let ElementGeom::Extrusion { profile, height } = &slab.geom else {
+        panic!("slab must stay an extrusion: {:?}", slab.geom);
+    };
+    assert_eq!(*height, 200.0);
+    assert_eq!(profile.len(), 5);
+    assert_eq!(profile[2], Pt2::new(3000.0, 2000.0));
+}
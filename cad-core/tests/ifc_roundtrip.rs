@@ -0,0 +1,306 @@
+#![cfg(feature = "ifc")]
+
+use cad_core::ifc::{export_ifc_string, import_ifc_str};
+use cad_core::*;
+use std::collections::BTreeMap;
+
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+fn rect(w: f32, h: f32) -> Vec<Pt2> {
+    vec![
+        Pt2::new(0.0, 0.0),
+        Pt2::new(w, 0.0),
+        Pt2::new(w, h),
+        Pt2::new(0.0, h),
+        Pt2::new(0.0, 0.0),
+    ]
+}
+
+fn element(
+    id: u64,
+    name: &str,
+    xform: [[f32; 4]; 4],
+    geom: ElementGeom,
+    material: u32,
+) -> Element3D {
+    Element3D {
+        id,
+        name: name.into(),
+        xform,
+        geom,
+        material,
+        rebars: vec![],
+        meta: Meta::default(),
+    }
+}
+
+fn sample_project() -> Project3D {
+    let mut slab = element(
+        1,
+        "П-1",
+        IDENTITY,
+        ElementGeom::Extrusion {
+            profile: rect(6000.0, 4000.0),
+            height: 200.0,
+        },
+        1,
+    );
+    slab.meta
+        .props
+        .insert("Pset_SlabCommon.Reference".into(), "ПМ-1".into());
+    slab.meta.props.insert("mark".into(), "П1".into());
+    slab.meta
+        .props
+        .insert("Pset_SlabCommon.LoadBearing".into(), "true".into());
+    slab.meta.props.insert("floors".into(), "3".into());
+    slab.meta.props.insert("area".into(), "24.5".into());
+    slab.meta.props.insert("code".into(), "007".into());
+
+    let mut wall = element(
+        2,
+        "Стена 1",
+        [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 3000.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        ElementGeom::Extrusion {
+            profile: rect(6000.0, 250.0),
+            height: 2800.0,
+        },
+        1,
+    );
+    wall.meta.props.insert("ifc:type".into(), "IfcWall".into());
+    wall.meta.props.insert("ifc:storey".into(), "Этаж 2".into());
+    wall.meta
+        .props
+        .insert("ifc:storey_elevation".into(), "3000".into());
+
+    // колонна, повёрнутая на 90° вокруг Z
+    let mut column = element(
+        3,
+        "К-1",
+        [
+            [0.0, -1.0, 0.0, 1000.0],
+            [1.0, 0.0, 0.0, 2000.0],
+            [0.0, 0.0, 1.0, 3200.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        ElementGeom::Extrusion {
+            profile: rect(400.0, 300.0),
+            height: 2600.0,
+        },
+        1,
+    );
+
+    // продольные стержни по углам и хомут-NURBS
+    for (i, (x, y)) in [(50.0, 50.0), (350.0, 50.0), (350.0, 250.0), (50.0, 250.0)]
+        .into_iter()
+        .enumerate()
+    {
+        column.rebars.push(Rebar {
+            id: i as u64 + 1,
+            diameter_mm: 16.0,
+            path: RebarPath::Polyline(vec![Pt3::new(x, y, 50.0), Pt3::new(x, y, 2550.0)]),
+            count: 1,
+            meta: Meta::default(),
+        });
+    }
+    let mut stirrup = Rebar {
+        id: 5,
+        diameter_mm: 8.0,
+        path: RebarPath::Nurbs {
+            degree: 2,
+            knots: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            ctrl_pts: vec![
+                Pt3::new(30.0, 30.0, 300.0),
+                Pt3::new(370.0, 30.0, 300.0),
+                Pt3::new(370.0, 270.0, 300.0),
+            ],
+            weights: None,
+        },
+        count: 12,
+        meta: Meta::default(),
+    };
+    stirrup.meta.props.insert(
+        "Pset_ReinforcingBarCommon.BarRole".into(),
+        "LIGATURE".into(),
+    );
+    column.rebars.push(stirrup);
+
+    let mesh = element(
+        4,
+        "Закладная",
+        [
+            [1.0, 0.0, 0.0, 500.0],
+            [0.0, 1.0, 0.0, 500.0],
+            [0.0, 0.0, 1.0, 200.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        ElementGeom::Mesh {
+            positions: vec![
+                Pt3::new(0.0, 0.0, 0.0),
+                Pt3::new(100.0, 0.0, 0.0),
+                Pt3::new(0.0, 100.0, 0.0),
+                Pt3::new(0.0, 0.0, 100.0),
+            ],
+            indices: vec![0, 2, 1, 0, 1, 3, 1, 2, 3, 0, 3, 2],
+        },
+        2,
+    );
+
+    Project3D {
+        models: vec![Model3D {
+            name: "Корпус 1".into(),
+            elements: vec![slab, wall, column, mesh],
+            materials: vec![
+                Material {
+                    id: 1,
+                    name: "Бетон B25".into(),
+                    kind: MaterialKind::Concrete {
+                        grade: "B25".into(),
+                    },
+                },
+                Material {
+                    id: 2,
+                    name: "С245".into(),
+                    kind: MaterialKind::Steel { fy_mpa: 245.0 },
+                },
+            ],
+        }],
+        ..Default::default()
+    }
+}
+
+/// Каждая вершина одной сетки совпадает с какой-то вершиной другой (и наоборот).
+fn assert_same_vertices(name: &str, a: &Mesh, b: &Mesh) {
+    let near = |p: &[f32; 3], set: &[[f32; 3]]| {
+        set.iter()
+            .any(|q| (0..3).all(|i| (p[i] - q[i]).abs() < 0.05))
+    };
+    for p in &a.positions {
+        assert!(near(p, &b.positions), "{name}: vertex {p:?} lost");
+    }
+    for p in &b.positions {
+        assert!(near(p, &a.positions), "{name}: extra vertex {p:?}");
+    }
+}
+
+fn near3(a: Pt3, b: Pt3) -> bool {
+    (a.x - b.x).abs() < 0.05 && (a.y - b.y).abs() < 0.05 && (a.z - b.z).abs() < 0.05
+}
+
+fn user_props(meta: &Meta) -> BTreeMap<String, String> {
+    meta.props
+        .iter()
+        .filter(|(k, _)| !k.starts_with("ifc:"))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+#[test]
+fn ifc_export_import_roundtrip() {
+    let original = sample_project();
+    let text = export_ifc_string(&original);
+    let imported = import_ifc_str(&text).expect("exported IFC must parse back");
+
+    assert_eq!(imported.models.len(), 1);
+    let (src, dst) = (&original.models[0], &imported.models[0]);
+    assert_eq!(dst.name, src.name);
+    assert_eq!(dst.elements.len(), src.elements.len());
+
+    for el in &src.elements {
+        let back = dst
+            .elements
+            .iter()
+            .find(|e| e.name == el.name)
+            .unwrap_or_else(|| panic!("element {} missing", el.name));
+
+        assert_same_vertices(&el.name, &el.triangulate(16), &back.triangulate(16));
+        assert_eq!(user_props(&back.meta), user_props(&el.meta), "{}", el.name);
+
+        let mat_src = src.materials.iter().find(|m| m.id == el.material).unwrap();
+        let mat_dst = dst
+            .materials
+            .iter()
+            .find(|m| m.id == back.material)
+            .unwrap();
+        assert_eq!(mat_dst.name, mat_src.name);
+        assert_eq!(format!("{:?}", mat_dst.kind), format!("{:?}", mat_src.kind));
+    }
+
+    let wall = dst.elements.iter().find(|e| e.name == "Стена 1").unwrap();
+    assert_eq!(wall.meta.props["ifc:type"], "IfcWall");
+    assert_eq!(wall.meta.props["ifc:storey"], "Этаж 2");
+    let column = dst.elements.iter().find(|e| e.name == "К-1").unwrap();
+    assert_eq!(column.meta.props["ifc:storey"], "Этаж 2");
+    let slab = dst.elements.iter().find(|e| e.name == "П-1").unwrap();
+    assert_eq!(slab.meta.props["ifc:type"], "IfcSlab");
+    assert_eq!(slab.meta.props["ifc:storey"], "+0.000");
+}
+
+#[test]
+fn ifc_rebars_and_typed_properties() {
+    let original = sample_project();
+    let text = export_ifc_string(&original);
+    assert_eq!(text.matches("IFCREINFORCINGBAR(").count(), 5);
+    assert!(
+        text.contains("IFCBOOLEAN(.TRUE.)"),
+        "LoadBearing must be boolean"
+    );
+    assert!(text.contains("IFCINTEGER(3)") && text.contains("IFCINTEGER(12)"));
+    assert!(text.contains("IFCREAL(24.5)"));
+    // строка, которая не переживает разбор числом, остаётся меткой
+    assert!(text.contains("IFCLABEL('007')"));
+
+    let imported = import_ifc_str(&text).expect("exported IFC must parse back");
+    let src = original.models[0]
+        .elements
+        .iter()
+        .find(|e| e.name == "К-1")
+        .unwrap();
+    let dst = imported.models[0]
+        .elements
+        .iter()
+        .find(|e| e.name == "К-1")
+        .unwrap();
+    assert_eq!(dst.rebars.len(), src.rebars.len());
+
+    // пути — в координатах элемента, как в исходнике
+    for (a, b) in src.rebars.iter().zip(&dst.rebars).take(4) {
+        assert_eq!(b.diameter_mm, a.diameter_mm);
+        assert_eq!(b.count, a.count);
+        let (RebarPath::Polyline(pa), RebarPath::Polyline(pb)) = (&a.path, &b.path) else {
+            panic!("straight bar must stay a polyline");
+        };
+        assert_eq!(pa.len(), pb.len());
+        for (p, q) in pa.iter().zip(pb) {
+            assert!(near3(*p, *q), "{p:?} vs {q:?}");
+        }
+    }
+    let stirrup = &dst.rebars[4];
+    assert_eq!((stirrup.diameter_mm, stirrup.count), (8.0, 12));
+    assert_eq!(user_props(&stirrup.meta), user_props(&src.rebars[4].meta));
+    let RebarPath::Polyline(pts) = &stirrup.path else {
+        panic!("NURBS axis is exported as a polyline");
+    };
+    assert!(near3(pts[0], Pt3::new(30.0, 30.0, 300.0)));
+    assert!(near3(*pts.last().unwrap(), Pt3::new(370.0, 270.0, 300.0)));
+
+    let slab = imported.models[0]
+        .elements
+        .iter()
+        .find(|e| e.name == "П-1")
+        .unwrap();
+    assert!(slab.rebars.is_empty());
+    assert_eq!(slab.meta.props["Pset_SlabCommon.LoadBearing"], "true");
+    assert_eq!(slab.meta.props["floors"], "3");
+    assert_eq!(slab.meta.props["area"], "24.5");
+    assert_eq!(slab.meta.props["code"], "007");
+}
@@ -0,0 +1,251 @@
+use cad_core::*;
+use cryxtal_geometry::prelude::*;
+use proptest::prelude::*;
+
+/// Пересечения ломаных — эталон по плотной выборке кривых: (точка, угол между кривыми).
+fn sampled_crossings(a: &EntityKind, b: &EntityKind) -> Vec<(Pt2, f32)> {
+    let (pa, pb) = (a.sample(800), b.sample(800));
+    let mut out = Vec::new();
+    for s in pa.windows(2) {
+        for t in pb.windows(2) {
+            let (da, db) = (
+                (s[1].x - s[0].x, s[1].y - s[0].y),
+                (t[1].x - t[0].x, t[1].y - t[0].y),
+            );
+            let den = da.0 * db.1 - da.1 * db.0;
+            if den == 0.0 {
+                continue;
+            }
+            let w = (t[0].x - s[0].x, t[0].y - s[0].y);
+            let u = (w.0 * db.1 - w.1 * db.0) / den;
+            let v = (w.0 * da.1 - w.1 * da.0) / den;
+            if (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v) {
+                let sin = den / (da.0.hypot(da.1) * db.0.hypot(db.1));
+                out.push((
+                    Pt2::new(s[0].x + da.0 * u, s[0].y + da.1 * u),
+                    sin.abs().asin(),
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn angle_at(a: &EntityKind, b: &EntityKind, x: &Intersection) -> f64 {
+    let (da, db) = (
+        a.to_truck().unwrap().der(x.t_a),
+        b.to_truck().unwrap().der(x.t_b),
+    );
+    let sin = (da.x * db.y - da.y * db.x) / (da.magnitude() * db.magnitude());
+    sin.abs().asin()
+}
+
+fn dist(a: Pt2, b: Pt2) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+/// Найденные точки лежат на обеих кривых при своих параметрах, а с эталоном совпадают
+/// все пересечения под заметным углом (касательные выборка различает плохо).
+fn check_against_sampling(a: &EntityKind, b: &EntityKind) -> Result<(), TestCaseError> {
+    let found = intersect(a, b);
+    let (ca, cb) = (a.to_truck().unwrap(), b.to_truck().unwrap());
+    for x in &found {
+        prop_assert!(dist(Pt2::from(ca.subs(x.t_a)), x.point) < 1e-4, "{x:?}");
+        prop_assert!(dist(Pt2::from(cb.subs(x.t_b)), x.point) < 1e-4, "{x:?}");
+    }
+    let reference = sampled_crossings(a, b);
+    for (p, ang) in &reference {
+        if *ang > 0.1 {
+            prop_assert!(
+                found.iter().any(|x| dist(x.point, *p) < 1e-2),
+                "missed {p:?} in {found:?}"
+            );
+        }
+    }
+    for x in &found {
+        if angle_at(a, b, x) > 0.1 {
+            prop_assert!(
+                reference.iter().any(|(p, _)| dist(x.point, *p) < 1e-2),
+                "extra {x:?}"
+            );
+        }
+    }
+    Ok(())
+}
+
+fn quarter_circle_nurbs(r: f32) -> EntityKind {
+    EntityKind::NurbsCurve2D {
+        degree: 2,
+        knots: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        ctrl_pts: vec![Pt2::new(r, 0.0), Pt2::new(r, r), Pt2::new(0.0, r)],
+        weights: Some(vec![1.0, std::f64::consts::FRAC_1_SQRT_2, 1.0]),
+    }
+}
+
+#[test]
+fn crossings_for_every_kind_pair() {
+    let kinds = [
+        EntityKind::LineSeg {
+            a: Pt2::new(-5.0, -1.0),
+            b: Pt2::new(5.0, 2.0),
+        },
+        EntityKind::Arc {
+            center: Pt2::new(-0.5, 0.0),
+            radius: 2.0,
+            start_angle: -1.0,
+            end_angle: 3.5,
+        },
+        EntityKind::Circle {
+            center: Pt2::new(-0.5, 0.5),
+            radius: 1.5,
+        },
+        EntityKind::Ellipse {
+            center: Pt2::new(0.0, 0.0),
+            major: Pt2::new(3.0, 1.0),
+            ratio: 0.4,
+            start_param: 0.0,
+            end_param: std::f32::consts::TAU,
+        },
+        EntityKind::Polyline {
+            pts: vec![
+                Pt2::new(-2.5, 2.0),
+                Pt2::new(-1.0, -2.0),
+                Pt2::new(2.0, 1.5),
+                Pt2::new(4.0, -1.0),
+            ],
+            closed: false,
+            bulges: vec![0.0, -0.4, -0.3],
+        },
+        quarter_circle_nurbs(1.8),
+    ];
+    for (i, a) in kinds.iter().enumerate() {
+        for b in &kinds[i + 1..] {
+            let found = intersect(a, b);
+            assert!(!found.is_empty(), "{a:?} x {b:?}");
+            check_against_sampling(a, b).unwrap();
+            // перестановка кривых меняет местами параметры
+            let back = intersect(b, a);
+            assert_eq!(found.len(), back.len());
+            for x in &found {
+                assert!(back
+                    .iter()
+                    .any(|y| dist(x.point, y.point) < 1e-4 && (x.t_a - y.t_b).abs() < 1e-5));
+            }
+        }
+    }
+}
+
+#[test]
+fn exact_points_and_parameters() {
+    let line = EntityKind::LineSeg {
+        a: Pt2::new(-4.0, 0.0),
+        b: Pt2::new(4.0, 0.0),
+    };
+    let circle = EntityKind::Circle {
+        center: Pt2::new(0.0, 0.0),
+        radius: 2.0,
+    };
+    let found = intersect(&line, &circle);
+    assert_eq!(found.len(), 2);
+    assert!(dist(found[0].point, Pt2::new(-2.0, 0.0)) < 1e-5);
+    assert!(dist(found[1].point, Pt2::new(2.0, 0.0)) < 1e-5);
+    assert!((found[0].t_a - 0.25).abs() < 1e-9 && (found[1].t_a - 0.75).abs() < 1e-9);
+
+    // касание концами и отсутствие пересечений
+    let touching = EntityKind::LineSeg {
+        a: Pt2::new(4.0, 0.0),
+        b: Pt2::new(4.0, 3.0),
+    };
+    let found = intersect(&line, &touching);
+    assert_eq!(found.len(), 1);
+    assert!((found[0].t_a - 1.0).abs() < 1e-9 && found[0].t_b.abs() < 1e-9);
+    let far = EntityKind::Circle {
+        center: Pt2::new(0.0, 10.0),
+        radius: 1.0,
+    };
+    assert!(intersect(&line, &far).is_empty());
+    let text = EntityKind::Text {
+        pos: Pt2::new(0.0, 0.0),
+        content: "x".into(),
+        height: 1.0,
+    };
+    assert!(intersect(&line, &text).is_empty());
+
+    // рациональная четверть окружности и биссектриса
+    let diag = EntityKind::LineSeg {
+        a: Pt2::new(0.0, 0.0),
+        b: Pt2::new(3.0, 3.0),
+    };
+    let found = intersect(&quarter_circle_nurbs(2.0), &diag);
+    assert_eq!(found.len(), 1);
+    let h = std::f32::consts::SQRT_2;
+    assert!(dist(found[0].point, Pt2::new(h, h)) < 1e-5);
+}
+
+fn pt() -> impl Strategy<Value = Pt2> {
+    (-10f32..10.0, -10f32..10.0).prop_map(|(x, y)| Pt2::new(x, y))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn line_circle_matches_analytic(a in pt(), b in pt(), c in pt(), r in 0.5f32..8.0) {
+        prop_assume!(dist(a, b) > 0.1);
+        let line = EntityKind::LineSeg { a, b };
+        let circle = EntityKind::Circle { center: c, radius: r };
+        // корни |a + t(b − a) − c|² = r² внутри [0, 1]
+        let (d, w) = ((b.x - a.x) as f64, (b.y - a.y) as f64);
+        let (fx, fy) = ((a.x - c.x) as f64, (a.y - c.y) as f64);
+        let (qa, qb, qc) = (d * d + w * w, 2.0 * (fx * d + fy * w), fx * fx + fy * fy - (r * r) as f64);
+        let disc = qb * qb - 4.0 * qa * qc;
+        prop_assume!(disc.abs() > 1e-3 * qa);
+        let mut roots: Vec<f64> = if disc > 0.0 {
+            let s = disc.sqrt();
+            vec![(-qb - s) / (2.0 * qa), (-qb + s) / (2.0 * qa)]
+        } else {
+            vec![]
+        };
+        prop_assume!(roots.iter().all(|t| (t.abs() > 1e-4) && ((t - 1.0).abs() > 1e-4)));
+        roots.retain(|t| (0.0..=1.0).contains(t));
+        let found = intersect(&line, &circle);
+        prop_assert_eq!(found.len(), roots.len(), "{:?} vs {:?}", found, roots);
+        for (x, t) in found.iter().zip(&roots) {
+            prop_assert!((x.t_a - t).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rational_nurbs_matches_sampling(
+        ctrl in prop::array::uniform5(pt()),
+        weights in prop::array::uniform5(0.3f64..3.0),
+        a in pt(),
+        b in pt(),
+    ) {
+        let nurbs = EntityKind::NurbsCurve2D {
+            degree: 3,
+            knots: vec![0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0],
+            ctrl_pts: ctrl.to_vec(),
+            weights: Some(weights.to_vec()),
+        };
+        check_against_sampling(&nurbs, &EntityKind::LineSeg { a, b })?;
+    }
+
+    #[test]
+    fn arc_polyline_matches_sampling(
+        c in pt(),
+        r in 0.5f32..8.0,
+        angles in (-3.0f32..3.0, 0.5f32..6.0),
+        pts in prop::array::uniform4(pt()),
+        bulges in prop::array::uniform3(-1.5f32..1.5),
+    ) {
+        let arc = EntityKind::Arc {
+            center: c,
+            radius: r,
+            start_angle: angles.0,
+            end_angle: angles.0 + angles.1,
+        };
+        let poly = EntityKind::Polyline { pts: pts.to_vec(), closed: false, bulges: bulges.to_vec() };
+        check_against_sampling(&arc, &poly)?;
+    }
+}
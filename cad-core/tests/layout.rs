@@ -0,0 +1,257 @@
+mod common;
+use cad_core::dxf_io::import_dxf;
+use cad_core::model3d::{Id, Project3D};
+use cad_core::*;
+use common::p;
+
+fn sheet(id: Id, size_mm: (f32, f32), annots: Vec<Annot>) -> Sheet {
+    Sheet {
+        id,
+        name: format!("План на отм. 0.000, лист {id}"),
+        size_mm,
+        viewports: vec![],
+        annots,
+    }
+}
+
+fn project() -> Project3D {
+    Project3D {
+        title: TitleInfo {
+            designation: "123-2026-КЖ".into(),
+            object: "Жилой дом".into(),
+            stage: "Р".into(),
+            organization: "ООО Проект".into(),
+            signatures: vec![
+                Signature {
+                    role: "Разраб.".into(),
+                    name: "Петров".into(),
+                    date: "10.26".into(),
+                },
+                Signature {
+                    role: "Пров.".into(),
+                    name: "Сидоров".into(),
+                    date: "10.26".into(),
+                },
+            ],
+        },
+        ..Default::default()
+    }
+}
+
+fn texts(doc: &Document) -> Vec<(Pt2, &str)> {
+    doc.entities()
+        .iter()
+        .filter_map(|e| match &e.kind {
+            EntityKind::Text { pos, content, .. } => Some((*pos, content.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn lines(doc: &Document, layer: &str) -> Vec<(Pt2, Pt2)> {
+    doc.entities()
+        .iter()
+        .filter(|e| e.layer == layer)
+        .filter_map(|e| match e.kind {
+            EntityKind::LineSeg { a, b } => Some((a, b)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Охват отрезков: (min, max).
+fn extent(segs: &[(Pt2, Pt2)]) -> (Pt2, Pt2) {
+    segs.iter().flat_map(|(a, b)| [a, b]).fold(
+        (p(f32::MAX, f32::MAX), p(f32::MIN, f32::MIN)),
+        |(lo, hi), q| {
+            (
+                p(lo.x.min(q.x), lo.y.min(q.y)),
+                p(hi.x.max(q.x), hi.y.max(q.y)),
+            )
+        },
+    )
+}
+
+#[test]
+fn first_sheet_has_form3() {
+    let doc = sheet(1, (420.0, 297.0), vec![]).render(&Document::new(), &project(), 1, 2);
+    assert_eq!(
+        doc.layer(SHEET_FRAME_LAYER).unwrap().lineweight,
+        LineWeight::Width(50)
+    );
+
+    // внутренняя рамка: поле 20 мм слева, 5 мм с остальных сторон
+    let thick = lines(&doc, SHEET_FRAME_LAYER);
+    assert_eq!(extent(&thick), (p(20.0, 5.0), p(415.0, 292.0)));
+    // линия обреза — по краю листа
+    let (lo, hi) = extent(&lines(&doc, SHEET_TITLE_LAYER));
+    assert_eq!((lo, hi), (p(0.0, 0.0), p(420.0, 297.0)));
+
+    // штамп 185 × 55 в правом нижнем углу рамки
+    let stamp: Vec<_> = thick
+        .iter()
+        .copied()
+        .filter(|(a, b)| a.x >= 230.0 && b.x >= 230.0 && a.y < 100.0 && b.y < 100.0)
+        .collect();
+    assert_eq!(extent(&stamp), (p(230.0, 5.0), p(415.0, 60.0)));
+
+    let t = texts(&doc);
+    let find = |s: &str| t.iter().find(|(_, c)| *c == s).map(|(q, _)| *q);
+    for s in [
+        "123-2026-КЖ",
+        "Жилой дом",
+        "План на отм. 0.000, лист 1",
+        "ООО Проект",
+        "Р",
+        "Стадия",
+        "Листов",
+        "Изм.",
+        "Петров",
+        "Сидоров",
+    ] {
+        let q = find(s).unwrap_or_else(|| panic!("{s}: {t:?}"));
+        assert!(
+            q.x > 230.0 && q.x < 415.0 && q.y > 5.0 && q.y < 60.0,
+            "{s}: {q:?}"
+        );
+    }
+    // обозначение — в верхней графе, первая подпись — под строкой заголовков
+    assert!(find("123-2026-КЖ").unwrap().y > 45.0);
+    let (dev, check) = (find("Петров").unwrap(), find("Сидоров").unwrap());
+    assert!(dev.y > check.y && dev.y > 25.0 && dev.y < 30.0, "{dev:?}");
+    // лист 1 из 2
+    assert!(t.iter().any(|(_, c)| *c == "1") && t.iter().any(|(_, c)| *c == "2"));
+}
+
+#[test]
+fn next_sheets_use_form6() {
+    let sheets = vec![
+        sheet(1, (297.0, 210.0), vec![]),
+        sheet(2, (297.0, 210.0), vec![]),
+    ];
+    let docs = render_sheets(&Document::new(), &project(), &sheets);
+    assert_eq!(docs.len(), 2);
+    let doc = &docs[1];
+    let stamp: Vec<_> = lines(doc, SHEET_FRAME_LAYER)
+        .into_iter()
+        .filter(|(a, b)| a.x >= 107.0 && b.x >= 107.0 && a.y < 100.0 && b.y < 100.0)
+        .collect();
+    assert_eq!(extent(&stamp), (p(107.0, 5.0), p(292.0, 20.0)));
+
+    let t = texts(doc);
+    assert!(t.iter().any(|(_, c)| *c == "123-2026-КЖ"));
+    assert!(t.iter().any(|(_, c)| *c == "2"));
+    // графы первого листа не повторяются
+    for s in ["Жилой дом", "Петров", "Стадия", "ООО Проект"] {
+        assert!(t.iter().all(|(_, c)| *c != s), "{s}");
+    }
+}
+
+#[test]
+fn model_stamp_block_replaces_title() {
+    let mut model = Document::new();
+    model.add_block(BlockDef {
+        name: "штамп".into(),
+        base: p(0.0, 0.0),
+        entities: vec![Entity::new(
+            "0",
+            EntityKind::LineSeg {
+                a: p(0.0, 0.0),
+                b: p(185.0, 0.0),
+            },
+        )],
+        attdefs: vec![],
+    });
+    let stamp = Annot::Block {
+        name: "штамп".into(),
+        at_mm: p(230.0, 5.0),
+    };
+    let s = sheet(1, (420.0, 297.0), vec![stamp.clone()]);
+    let doc = s.render(&model, &project(), 1, 1);
+    // только рамка и вставка своего штампа
+    assert!(texts(&doc).is_empty());
+    assert_eq!(lines(&doc, SHEET_FRAME_LAYER).len(), 4);
+    assert!(doc.entities().iter().any(|e| e.kind.is_block_ref()));
+
+    // блока в модели нет — рисуется своя надпись
+    let doc = s.render(&Document::new(), &project(), 1, 1);
+    assert!(!texts(&doc).is_empty());
+}
+
+#[test]
+fn svg_page_in_millimetres() {
+    let s = sheet(1, (297.0, 210.0), vec![]);
+    let svg = s
+        .render(&Document::new(), &project(), 1, 1)
+        .export_svg_page(s.size_mm);
+    assert!(svg.contains("width='297mm' height='210mm' viewBox='0 0 297 210'"));
+    // ось Y вверх, текст отзеркален обратно
+    assert!(svg.contains("<g transform='matrix(1 0 0 -1 0 210)'"));
+    assert!(svg.contains("<text transform='matrix(1 0 0 -1 "));
+    assert!(svg.contains(">123-2026-КЖ</text>"));
+    // основные линии — 0,5 мм, тонкие — 0,18 мм
+    assert!(svg.contains("stroke-width='0.500'"));
+    assert!(svg.contains("stroke-width='0.180'"));
+
+    // обычный экспорт не меняется
+    let plain = s
+        .render(&Document::new(), &project(), 1, 1)
+        .export_svg(800.0, 600.0);
+    assert!(!plain.contains("matrix(") && plain.contains("<text x='"));
+}
+
+#[test]
+fn sheet_set_files() {
+    let dir = std::env::temp_dir().join(format!("cad_core_{}_sheets", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir = dir.to_str().unwrap();
+    let sheets = vec![
+        sheet(
+            1,
+            (420.0, 297.0),
+            vec![Annot::Text {
+                pos_mm: p(30.0, 250.0),
+                content: "Примечания".into(),
+                h_mm: 5.0,
+            }],
+        ),
+        sheet(2, (297.0, 210.0), vec![]),
+    ];
+    let model = Document::new();
+
+    // DXF: сущности в пространстве листа (код 67), границы листа — по размеру
+    let paths = export_sheet_set_dxf(&model, &project(), &sheets, dir).unwrap();
+    assert_eq!(paths.len(), 2);
+    assert!(paths[1].ends_with("sheet-02.dxf"));
+    let text = std::fs::read_to_string(&paths[0]).unwrap();
+    let pairs: Vec<(&str, &str)> = text
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .chunks(2)
+        .map(|c| (c[0], c[1]))
+        .collect();
+    assert!(pairs.contains(&("67", "1")));
+    let after = |var: &str| pairs[pairs.iter().position(|q| q.1 == var).unwrap() + 1];
+    assert_eq!(after("$PLIMMAX"), ("10", "420.0"));
+    assert_eq!(after("$TILEMODE"), ("70", "0"));
+    let doc = render_sheets(&model, &project(), &sheets).remove(0);
+    let back = import_dxf(&paths[0]).unwrap();
+    assert_eq!(back.entities().len(), doc.entities().len());
+    assert!(texts(&back).iter().any(|(_, c)| *c == "Примечания"));
+
+    // SVG — по файлу на лист
+    let paths = export_sheet_set_svg(&model, &project(), &sheets, dir).unwrap();
+    let svg = std::fs::read_to_string(&paths[1]).unwrap();
+    assert!(svg.contains("width='297mm'"));
+
+    // PDF — одним файлом, по странице на лист
+    let pdf = String::from_utf8_lossy(&sheet_set_pdf(&model, &project(), &sheets)).into_owned();
+    assert!(pdf.contains("/Count 2"));
+    let shown = common::shown_text(&pdf);
+    assert_eq!(
+        shown.iter().filter(|t| t.starts_with("123-2026-")).count(),
+        2
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,307 @@
+mod common;
+use cad_core::*;
+use common::p;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+fn close(a: f64, b: f64, tol: f64) -> bool {
+    (a - b).abs() <= tol * (1.0 + b.abs())
+}
+
+fn near(a: Pt2, b: Pt2) -> bool {
+    (a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3
+}
+
+fn line(a: Pt2, b: Pt2) -> EntityKind {
+    EntityKind::LineSeg { a, b }
+}
+
+fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> EntityKind {
+    EntityKind::Polyline {
+        pts: vec![p(x0, y0), p(x1, y0), p(x1, y1), p(x0, y1)],
+        closed: true,
+        bulges: vec![],
+    }
+}
+
+/// Парабола y = x² на [0, 1] квадратичным Безье.
+fn parabola() -> EntityKind {
+    EntityKind::NurbsCurve2D {
+        degree: 2,
+        knots: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        ctrl_pts: vec![p(0.0, 0.0), p(0.5, 0.0), p(1.0, 1.0)],
+        weights: None,
+    }
+}
+
+#[test]
+fn curve_lengths() {
+    assert_eq!(curve_length(&line(p(0.0, 0.0), p(3.0, 4.0))), Some(5.0));
+    // дуга по часовой — та же длина
+    for (s, e) in [(0.0, FRAC_PI_2), (FRAC_PI_2, 0.0)] {
+        let arc = EntityKind::Arc {
+            center: p(1.0, 1.0),
+            radius: 2.0,
+            start_angle: s,
+            end_angle: e,
+        };
+        assert!(close(
+            curve_length(&arc).unwrap(),
+            std::f64::consts::PI,
+            1e-6
+        ));
+    }
+    let circle = EntityKind::Circle {
+        center: p(0.0, 0.0),
+        radius: 1.0,
+    };
+    assert!(close(
+        curve_length(&circle).unwrap(),
+        std::f64::consts::TAU,
+        1e-12
+    ));
+
+    // полилиния: отрезок 2 и полуокружность на хорде 2
+    let pl = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(2.0, 0.0), p(2.0, 2.0)],
+        closed: false,
+        bulges: vec![0.0, 1.0],
+    };
+    assert!(close(
+        curve_length(&pl).unwrap(),
+        2.0 + std::f64::consts::PI,
+        1e-6
+    ));
+
+    // эллипс 2 × 1: периметр через полный эллиптический интеграл
+    let ellipse = EntityKind::Ellipse {
+        center: p(5.0, 5.0),
+        major: p(0.0, 2.0),
+        ratio: 0.5,
+        start_param: 0.0,
+        end_param: 2.0 * PI,
+    };
+    assert!(close(
+        curve_length(&ellipse).unwrap(),
+        9.688_448_220_547_675,
+        1e-6
+    ));
+    // парабола: (2√5 + arsh 2) / 4
+    assert!(close(
+        curve_length(&parabola()).unwrap(),
+        1.478_942_857_544_597,
+        1e-9
+    ));
+
+    assert_eq!(
+        curve_length(&EntityKind::Text {
+            pos: p(0.0, 0.0),
+            content: "A".into(),
+            height: 2.5,
+        }),
+        None
+    );
+}
+
+#[test]
+fn loop_area_and_centroid() {
+    let sq = rect(0.0, 0.0, 4.0, 2.0);
+    let props = loop_props(std::slice::from_ref(&sq)).unwrap();
+    assert!(close(props.area, 8.0, 1e-12) && close(props.perimeter, 12.0, 1e-12));
+    assert!(near(props.centroid, p(2.0, 1.0)));
+
+    // по часовой — площадь отрицательна, центр тот же
+    let cw = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(0.0, 2.0), p(4.0, 2.0), p(4.0, 0.0)],
+        closed: true,
+        bulges: vec![],
+    };
+    let props = loop_props(&[cw]).unwrap();
+    assert!(close(props.area, -8.0, 1e-12) && near(props.centroid, p(2.0, 1.0)));
+
+    // «D»: квадрат 2 × 2 и полукруг справа, рёбра вразнобой по направлению
+    let d = [
+        line(p(0.0, 0.0), p(2.0, 0.0)),
+        EntityKind::Arc {
+            center: p(2.0, 1.0),
+            radius: 1.0,
+            start_angle: -FRAC_PI_2,
+            end_angle: FRAC_PI_2,
+        },
+        line(p(0.0, 2.0), p(2.0, 2.0)),
+        line(p(0.0, 0.0), p(0.0, 2.0)),
+    ];
+    let props = loop_props(&d).unwrap();
+    let half = std::f64::consts::FRAC_PI_2;
+    assert!(close(props.area.abs(), 4.0 + half, 1e-6), "{props:?}");
+    // центр полукруга — на 4r / 3π правее диаметра
+    let cx = (4.0 * 1.0 + half * (2.0 + 4.0 / (3.0 * std::f64::consts::PI))) / (4.0 + half);
+    assert!((props.centroid.x as f64 - cx).abs() < 1e-5, "{props:?}");
+    assert!((props.centroid.y - 1.0).abs() < 1e-5);
+    assert!(close(props.perimeter, 6.0 + std::f64::consts::PI, 1e-6));
+}
+
+#[test]
+fn region_with_hole() {
+    let outer = HatchLoop {
+        edges: vec![rect(0.0, 0.0, 10.0, 10.0)],
+    };
+    let hole = HatchLoop {
+        edges: vec![EntityKind::Circle {
+            center: p(7.0, 5.0),
+            radius: 2.0,
+        }],
+    };
+    // обход отверстия не важен — оно вложено
+    let props = region_props(&[hole.clone(), outer.clone()]).unwrap();
+    let disc = 4.0 * std::f64::consts::PI;
+    assert!(close(props.area, 100.0 - disc, 1e-9), "{props:?}");
+    let cx = (100.0 * 5.0 - disc * 7.0) / (100.0 - disc);
+    assert!((props.centroid.x as f64 - cx).abs() < 1e-4 && (props.centroid.y - 5.0).abs() < 1e-4);
+    assert!(close(props.perimeter, 40.0 + disc, 1e-9));
+
+    // остров в отверстии снова прибавляется
+    let island = HatchLoop {
+        edges: vec![rect(6.5, 4.5, 7.5, 5.5)],
+    };
+    let props = region_props(&[outer.clone(), hole.clone(), island]).unwrap();
+    assert!(close(props.area, 100.0 - disc + 1.0, 1e-9));
+
+    // то же через штриховку
+    let hatch = EntityKind::Hatch {
+        loops: vec![outer, hole],
+        fill: HatchFill::Solid,
+    };
+    assert!(close(entity_area(&hatch).unwrap().area, 100.0 - disc, 1e-9));
+}
+
+#[test]
+fn entity_areas() {
+    let ellipse = EntityKind::Ellipse {
+        center: p(1.0, 2.0),
+        major: p(3.0, 0.0),
+        ratio: 0.5,
+        start_param: 0.0,
+        end_param: 2.0 * PI,
+    };
+    let props = entity_area(&ellipse).unwrap();
+    assert!(
+        close(props.area, std::f64::consts::PI * 3.0 * 1.5, 1e-6),
+        "{props:?}"
+    );
+    assert!(near(props.centroid, p(1.0, 2.0)));
+
+    // замкнутый NURBS: квадрат степени 1
+    let nurbs = EntityKind::NurbsCurve2D {
+        degree: 1,
+        knots: vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 4.0],
+        ctrl_pts: vec![
+            p(0.0, 0.0),
+            p(1.0, 0.0),
+            p(1.0, 1.0),
+            p(0.0, 1.0),
+            p(0.0, 0.0),
+        ],
+        weights: None,
+    };
+    let props = entity_area(&nurbs).unwrap();
+    assert!(close(props.area, 1.0, 1e-9) && near(props.centroid, p(0.5, 0.5)));
+
+    // открытая полилиния и отрезок площади не имеют
+    let open = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0)],
+        closed: false,
+        bulges: vec![],
+    };
+    assert_eq!(entity_area(&open), None);
+    assert_eq!(entity_area(&line(p(0.0, 0.0), p(1.0, 1.0))), None);
+}
+
+#[test]
+fn minimum_distance() {
+    let c = |x: f32, r: f32| EntityKind::Circle {
+        center: p(x, 0.0),
+        radius: r,
+    };
+    let d = curve_distance(&c(0.0, 2.0), &c(10.0, 3.0)).unwrap();
+    assert!(close(d.distance, 5.0, 1e-5), "{d:?}");
+    assert!(near(d.a, p(2.0, 0.0)) && near(d.b, p(7.0, 0.0)));
+
+    // отрезки: ближе всего конец одного к середине другого
+    let a = line(p(0.0, 0.0), p(10.0, 0.0));
+    let b = line(p(5.0, 3.0), p(8.0, 7.0));
+    let d = curve_distance(&a, &b).unwrap();
+    assert!(close(d.distance, 3.0, 1e-6) && near(d.a, p(5.0, 0.0)));
+
+    // пересекаются — ноль
+    let x = line(p(5.0, -1.0), p(5.0, 1.0));
+    assert_eq!(curve_distance(&a, &x).unwrap().distance, 0.0);
+
+    // парабола и точка текста над ней
+    let t = EntityKind::Text {
+        pos: p(0.0, 1.0),
+        content: "A".into(),
+        height: 2.5,
+    };
+    let d = curve_distance(&parabola(), &t).unwrap();
+    // min (x² + (x² − 1)²) при x² = 1/2
+    assert!(close(d.distance, 0.75f64.sqrt(), 1e-5), "{d:?}");
+
+    // вставка блока — по его геометрии
+    let mut doc = Document::new();
+    doc.add_block(BlockDef {
+        name: "B".into(),
+        base: p(0.0, 0.0),
+        entities: vec![Entity::new("0", c(0.0, 1.0))],
+        attdefs: vec![],
+    });
+    let ins = make_insert(&mut doc, "B", p(20.0, 0.0), p(1.0, 1.0), 0.0, "0").unwrap();
+    let ln = make_line(&mut doc, p(0.0, -5.0), p(0.0, 5.0), "0");
+    let (ins, ln) = (doc.entity(ins).unwrap(), doc.entity(ln).unwrap());
+    let d = entity_distance(&doc, ln, ins).unwrap();
+    assert!(close(d.distance, 19.0, 1e-5), "{d:?}");
+}
+
+#[test]
+fn tight_extents() {
+    // четверть дуги — без центра
+    let arc = EntityKind::Arc {
+        center: p(0.0, 0.0),
+        radius: 2.0,
+        start_angle: 0.25,
+        end_angle: FRAC_PI_2 - 0.25,
+    };
+    let b = curve_extents(&arc).unwrap();
+    assert!(b.min.x > 0.4 && b.min.y > 0.4 && (b.max.x - 2.0 * 0.25f32.cos()).abs() < 1e-5);
+    assert!(kind_bounds(&arc).unwrap().min == p(0.0, 0.0));
+
+    // повёрнутый на 90° эллипс: полуоси 1 по X и 2 по Y
+    let ellipse = EntityKind::Ellipse {
+        center: p(0.0, 0.0),
+        major: p(0.0, 2.0),
+        ratio: 0.5,
+        start_param: 0.0,
+        end_param: 2.0 * PI,
+    };
+    let b = curve_extents(&ellipse).unwrap();
+    assert!(
+        near(b.min, p(-1.0, -2.0)) && near(b.max, p(1.0, 2.0)),
+        "{b:?}"
+    );
+
+    // NURBS: по кривой, а не по управляющим точкам
+    let s = EntityKind::NurbsCurve2D {
+        degree: 2,
+        knots: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        ctrl_pts: vec![p(0.0, 0.0), p(1.0, 2.0), p(2.0, 0.0)],
+        weights: None,
+    };
+    let b = curve_extents(&s).unwrap();
+    assert!(near(b.max, p(2.0, 1.0)), "{b:?}");
+
+    let mut doc = Document::new();
+    make_circle(&mut doc, p(0.0, 0.0), 1.0, "0");
+    make_line(&mut doc, p(3.0, 0.0), p(5.0, 4.0), "0");
+    let all = extents(&doc, doc.entities()).unwrap();
+    assert_eq!((all.min, all.max), (p(-1.0, -1.0), p(5.0, 4.0)));
+    assert_eq!(extents(&doc, &[]), None);
+}
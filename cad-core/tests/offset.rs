@@ -0,0 +1,221 @@
+mod common;
+use cad_core::*;
+use common::{close, p};
+
+/// Расстояние от точки до кривой.
+fn distance_to(kind: &EntityKind, q: Pt2) -> f32 {
+    let f = curve_at(kind, curve_param(kind, q).unwrap()).unwrap();
+    (f.x - q.x).hypot(f.y - q.y)
+}
+
+fn polyline_pts(k: &EntityKind) -> (&[Pt2], bool, &[f32]) {
+    match k {
+        EntityKind::Polyline {
+            pts,
+            closed,
+            bulges,
+        } => (pts, *closed, bulges),
+        k => panic!("expected polyline, got {k:?}"),
+    }
+}
+
+#[test]
+fn offsets_lines_arcs_and_circles_exactly() {
+    let line = EntityKind::LineSeg {
+        a: p(0.0, 0.0),
+        b: p(10.0, 0.0),
+    };
+    let out = offset_curve(&line, 2.0, OffsetJoin::Miter, OFFSET_TOL).unwrap();
+    assert_eq!(
+        out,
+        vec![EntityKind::LineSeg {
+            a: p(0.0, -2.0),
+            b: p(10.0, -2.0)
+        }]
+    );
+
+    let arc = EntityKind::Arc {
+        center: p(0.0, 0.0),
+        radius: 3.0,
+        start_angle: 0.0,
+        end_angle: 1.0,
+    };
+    match &offset_curve(&arc, 1.0, OffsetJoin::Miter, OFFSET_TOL).unwrap()[..] {
+        [EntityKind::Arc { radius, .. }] => assert_eq!(*radius, 4.0),
+        k => panic!("{k:?}"),
+    }
+    assert!(offset_curve(&arc, -3.5, OffsetJoin::Miter, OFFSET_TOL)
+        .unwrap()
+        .is_empty());
+
+    let circle = EntityKind::Circle {
+        center: p(1.0, 1.0),
+        radius: 2.0,
+    };
+    match &offset_curve(&circle, -0.5, OffsetJoin::Miter, OFFSET_TOL).unwrap()[..] {
+        [EntityKind::Circle { radius, .. }] => assert_eq!(*radius, 1.5),
+        k => panic!("{k:?}"),
+    }
+    let text = EntityKind::Text {
+        pos: p(0.0, 0.0),
+        content: "A".into(),
+        height: 1.0,
+    };
+    assert!(offset_curve(&text, 1.0, OffsetJoin::Miter, OFFSET_TOL).is_none());
+}
+
+#[test]
+fn square_joins_outward_and_inward() {
+    // квадрат против часовой: плюс — наружу
+    let sq = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)],
+        closed: true,
+        bulges: vec![],
+    };
+
+    let out = offset_curve(&sq, 1.0, OffsetJoin::Miter, OFFSET_TOL).unwrap();
+    assert_eq!(out.len(), 1);
+    let (pts, closed, _) = polyline_pts(&out[0]);
+    assert!(closed);
+    assert_eq!(pts.len(), 4);
+    for c in [p(-1.0, -1.0), p(11.0, -1.0), p(11.0, 11.0), p(-1.0, 11.0)] {
+        assert!(pts.iter().any(|q| close(*q, c)), "{c:?} not in {pts:?}");
+    }
+
+    let out = offset_curve(&sq, 1.0, OffsetJoin::Round, OFFSET_TOL).unwrap();
+    let (pts, closed, bulges) = polyline_pts(&out[0]);
+    assert!(closed);
+    assert_eq!(pts.len(), 8);
+    let quarter = (std::f32::consts::PI / 8.0).tan();
+    assert_eq!(
+        bulges
+            .iter()
+            .filter(|b| (b.abs() - quarter).abs() < 1e-4)
+            .count(),
+        4
+    );
+
+    let out = offset_curve(&sq, 1.0, OffsetJoin::Bevel, OFFSET_TOL).unwrap();
+    let (pts, _, bulges) = polyline_pts(&out[0]);
+    assert_eq!(pts.len(), 8);
+    assert!(bulges.is_empty());
+
+    // внутрь нахлёсты сегментов срезаются по углам
+    let out = offset_curve(&sq, -1.0, OffsetJoin::Round, OFFSET_TOL).unwrap();
+    assert_eq!(out.len(), 1);
+    let (pts, closed, _) = polyline_pts(&out[0]);
+    assert!(closed);
+    assert_eq!(pts.len(), 4);
+    for c in [p(1.0, 1.0), p(9.0, 1.0), p(9.0, 9.0), p(1.0, 9.0)] {
+        assert!(pts.iter().any(|q| close(*q, c)), "{c:?} not in {pts:?}");
+    }
+}
+
+#[test]
+fn cleans_polyline_self_intersections() {
+    // ступенька: смещённый короткий подъём пересекает следующий сегмент
+    let step = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 1.0), p(20.0, 1.0)],
+        closed: false,
+        bulges: vec![],
+    };
+    let out = offset_curve(&step, 2.0, OffsetJoin::Miter, OFFSET_TOL).unwrap();
+    assert_eq!(out.len(), 1);
+    let (pts, closed, _) = polyline_pts(&out[0]);
+    assert!(!closed);
+    let expect = [p(0.0, -2.0), p(12.0, -2.0), p(12.0, -1.0), p(20.0, -1.0)];
+    assert_eq!(pts.len(), expect.len(), "{pts:?}");
+    let forward = pts.iter().zip(&expect).all(|(a, b)| close(*a, *b));
+    let backward = pts.iter().rev().zip(&expect).all(|(a, b)| close(*a, *b));
+    assert!(forward || backward, "{pts:?}");
+
+    // узкий паз: смещение шире паза не оставляет ничего
+    let slot = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 2.0), p(0.0, 2.0)],
+        closed: false,
+        bulges: vec![],
+    };
+    assert!(offset_curve(&slot, -1.5, OffsetJoin::Miter, OFFSET_TOL)
+        .unwrap()
+        .is_empty());
+    let out = offset_curve(&slot, -0.8, OffsetJoin::Miter, OFFSET_TOL).unwrap();
+    let (pts, _, _) = polyline_pts(&out[0]);
+    assert_eq!(pts.len(), 4);
+    assert!(pts.iter().any(|q| close(*q, p(9.2, 0.8))));
+}
+
+#[test]
+fn polyline_arc_segments_keep_curvature() {
+    let pl = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(4.0, 0.0), p(4.0, 4.0)],
+        closed: false,
+        bulges: vec![0.0, 1.0],
+    };
+    let out = offset_curve(&pl, 0.5, OffsetJoin::Round, OFFSET_TOL).unwrap();
+    assert_eq!(out.len(), 1);
+    for t in 0..=40 {
+        let (r0, r1) = curve_range(&out[0]).unwrap();
+        let q = curve_at(&out[0], r0 + (r1 - r0) * t as f64 / 40.0).unwrap();
+        assert!((distance_to(&pl, q) - 0.5).abs() < 1e-3, "{q:?}");
+    }
+}
+
+#[test]
+fn nurbs_and_ellipse_offsets_stay_within_tolerance() {
+    let nurbs = EntityKind::NurbsCurve2D {
+        degree: 3,
+        knots: vec![0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0],
+        ctrl_pts: vec![
+            p(0.0, 0.0),
+            p(2.0, 3.0),
+            p(5.0, -1.0),
+            p(7.0, 2.0),
+            p(9.0, 0.0),
+        ],
+        weights: Some(vec![1.0, 2.0, 1.0, 0.5, 1.0]),
+    };
+    // узкий эллипс: внутрь дальше радиуса кривизны на концах (0.2) — «ласточкины хвосты»
+    let ellipse = EntityKind::Ellipse {
+        center: p(0.0, 0.0),
+        major: p(5.0, 0.0),
+        ratio: 0.2,
+        start_param: 0.0,
+        end_param: std::f32::consts::TAU,
+    };
+    for (kind, d) in [
+        (&nurbs, 0.7),
+        (&nurbs, -0.4),
+        (&ellipse, 0.5),
+        (&ellipse, -0.5),
+    ] {
+        let out = offset_curve(kind, d, OffsetJoin::Miter, OFFSET_TOL).unwrap();
+        assert_eq!(out.len(), 1, "{kind:?} by {d}");
+        assert!(matches!(out[0], EntityKind::NurbsCurve2D { .. }));
+        let (r0, r1) = curve_range(&out[0]).unwrap();
+        for i in 0..=200 {
+            let q = curve_at(&out[0], r0 + (r1 - r0) * i as f64 / 200.0).unwrap();
+            let err = (distance_to(kind, q) - d.abs()).abs();
+            assert!(
+                err < 5.0 * OFFSET_TOL as f32,
+                "{kind:?} by {d}: {q:?} off by {err}"
+            );
+        }
+    }
+}
+
+#[test]
+fn offset_command_copies_properties() {
+    let mut doc = Document::new();
+    let id = make_circle(&mut doc, p(0.0, 0.0), 2.0, "walls");
+    doc.entity_mut(id).unwrap().color = Color::Aci(3);
+    let ids = offset(&mut doc, id, 0.5, OffsetJoin::Miter).unwrap();
+    assert_eq!(ids.len(), 1);
+    let e = doc.entities().iter().find(|e| e.id == ids[0]).unwrap();
+    assert_eq!(e.layer, "walls");
+    assert_eq!(e.color, Color::Aci(3));
+    assert_eq!(doc.entities().len(), 2);
+
+    assert!(offset(&mut doc, id, -2.5, OffsetJoin::Miter).is_err());
+    let t = make_text(&mut doc, p(0.0, 0.0), "A", 1.0, "0");
+    assert!(offset(&mut doc, t, 1.0, OffsetJoin::Miter).is_err());
+}
@@ -0,0 +1,306 @@
+mod common;
+use cad_core::model3d::{Id, Project3D};
+use cad_core::*;
+use common::{object, p, reference, shown_text};
+
+fn text(pdf: &[u8]) -> String {
+    String::from_utf8(pdf.to_vec()).unwrap()
+}
+
+/// Размеры страниц из /MediaBox, пт.
+fn media_boxes(s: &str) -> Vec<(f32, f32)> {
+    s.match_indices("/MediaBox [0 0 ")
+        .map(|(i, m)| {
+            let rest = &s[i + m.len()..];
+            let v: Vec<f32> = rest[..rest.find(']').unwrap()]
+                .split_whitespace()
+                .map(|v| v.parse().unwrap())
+                .collect();
+            (v[0], v[1])
+        })
+        .collect()
+}
+
+fn mm(pt: f32) -> f32 {
+    pt * 25.4 / 72.0
+}
+
+#[test]
+fn file_structure_is_valid() {
+    let mut doc = Document::new();
+    make_line(&mut doc, p(0.0, 0.0), p(100.0, 50.0), "0");
+    let s = text(&document_pdf(&doc, 1.0));
+    assert!(s.starts_with("%PDF-1.4\n") && s.ends_with("%%EOF\n"));
+
+    // таблица xref указывает ровно на начала объектов
+    let start: usize = s[s.rfind("startxref\n").unwrap() + 10..]
+        .lines()
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(s[start..].starts_with("xref\n0 "));
+    let mut lines = s[start..].lines().skip(1);
+    let count: usize = lines.next().unwrap()[2..].parse().unwrap();
+    let offsets: Vec<usize> = lines
+        .skip(1)
+        .take(count - 1)
+        .map(|l| l[..10].parse().unwrap())
+        .collect();
+    for (i, off) in offsets.iter().enumerate() {
+        assert!(s[*off..].starts_with(&format!("{} 0 obj\n", i + 1)));
+    }
+    // длина потока совпадает с объявленной
+    let i = s.find("/Length ").unwrap() + 8;
+    let len: usize = s[i..s[i..].find(' ').unwrap() + i].parse().unwrap();
+    let body = s.find("stream\n").unwrap() + 7;
+    assert!(s[body + len..].starts_with("endstream"));
+    assert!(s.contains("+DejaVuSans /Encoding /Identity-H"));
+}
+
+#[test]
+fn document_page_follows_scale() {
+    let mut doc = Document::new();
+    make_line(&mut doc, p(-2000.0, 0.0), p(8000.0, 0.0), "0");
+    make_circle(&mut doc, p(0.0, 0.0), 1000.0, "0");
+    // 10 000 × 2000 мм в 1:100 — 100 × 20 мм и поле по 10 мм
+    let s = text(&document_pdf(&doc, 100.0));
+    let (w, h) = media_boxes(&s)[0];
+    assert!((mm(w) - 120.0).abs() < 0.01 && (mm(h) - 40.0).abs() < 0.01);
+    // окружность — кривыми Безье, линия — отрезком от поля
+    assert_eq!(s.matches(" c\n").count(), 4);
+    assert!(s.contains("10 20 m\n110 20 l\nS"));
+
+    // скрытые слои не печатаются и не раздувают страницу
+    let mut l = Layer::new("hidden");
+    l.visible = false;
+    doc.add_layer(l);
+    make_line(&mut doc, p(0.0, 0.0), p(0.0, 90000.0), "hidden");
+    assert_eq!(media_boxes(&text(&document_pdf(&doc, 100.0)))[0], (w, h));
+}
+
+#[test]
+fn oversized_page_uses_user_unit() {
+    let mut doc = Document::new();
+    make_line(&mut doc, p(0.0, 0.0), p(10000.0, 2000.0), "0");
+    // 10 м в 1:1 — больше 200 дюймов: страница в единицах по 2 пт
+    let s = text(&document_pdf(&doc, 1.0));
+    assert!(s.starts_with("%PDF-1.6\n"));
+    assert!(s.contains("/UserUnit 2 "));
+    let (w, h) = media_boxes(&s)[0];
+    assert!(w <= 14_400.0 && h <= 14_400.0);
+    assert!((mm(2.0 * w) - 10_020.0).abs() < 0.1 && (mm(2.0 * h) - 2020.0).abs() < 0.1);
+    assert!(s.contains("1.417323 0 0 1.417323 0 0 cm"));
+}
+
+#[test]
+fn weights_colors_and_linetypes() {
+    let mut doc = Document::new();
+    doc.linetypes.push(LinetypeDef {
+        name: "DASHDOT".into(),
+        description: String::new(),
+        pattern: vec![10.0, -2.0, 0.0, -2.0],
+    });
+    doc.linetypes.push(LinetypeDef {
+        name: "GAPFIRST".into(),
+        description: String::new(),
+        pattern: vec![-1.0, 4.0, -1.0],
+    });
+    let a = make_line(&mut doc, p(0.0, 0.0), p(100.0, 0.0), "0");
+    let b = make_arc(&mut doc, p(0.0, 0.0), 50.0, 0.0, 3.0, "0");
+    let c = make_line(&mut doc, p(0.0, 10.0), p(100.0, 10.0), "0");
+    {
+        let e = doc.entity_mut(a).unwrap();
+        e.lineweight = LineWeight::Width(50);
+        e.color = Color::Aci(1);
+        e.linetype = "DASHDOT".into();
+    }
+    doc.entity_mut(b).unwrap().color = Color::Rgb([0, 128, 255]);
+    doc.entity_mut(c).unwrap().linetype = "GAPFIRST".into();
+
+    // 1:2 — вес на бумаге прежний, шаблон вдвое короче
+    let s = text(&document_pdf(&doc, 2.0));
+    assert!(s.contains("1 0 0 RG\n0.5 w\n[5 1 0 1] 0 d"), "{s}");
+    assert!(s.contains("0 0.502 1 RG\n0.25 w\n[] 0 d"));
+    assert!(s.contains("[0 0.5 2 0.5] 0 d"));
+    // дуга 3 рад — двумя кривыми
+    assert_eq!(s.matches(" c\n").count(), 2);
+}
+
+#[test]
+fn text_hatch_and_blocks() {
+    let mut doc = Document::new();
+    make_text(&mut doc, p(0.0, 0.0), "Шаг (a) ⌀12 №3", 3.5, "0");
+    let sq = vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)];
+    let mut hatch = Entity::new(
+        "0",
+        EntityKind::Hatch {
+            loops: vec![HatchLoop::polyline(sq, vec![])],
+            fill: HatchFill::Solid,
+        },
+    );
+    hatch.color = Color::Aci(8);
+    doc.add_entity(hatch);
+    doc.add_block(BlockDef {
+        name: "B".into(),
+        base: p(0.0, 0.0),
+        entities: vec![Entity::new(
+            "0",
+            EntityKind::Circle {
+                center: p(0.0, 0.0),
+                radius: 1.0,
+            },
+        )],
+        attdefs: vec![],
+    });
+    doc.add_entity(Entity::new(
+        "0",
+        EntityKind::Insert {
+            block: "B".into(),
+            pos: p(50.0, 50.0),
+            scale: p(2.0, 2.0),
+            rotation: 0.0,
+            attribs: vec![],
+        },
+    ));
+
+    let s = text(&document_pdf(&doc, 1.0));
+    // коды — номера глифов подмножества, ToUnicode возвращает исходный текст
+    assert!(s.contains("10 10 Tm <00010002000300040005"), "{s}");
+    assert_eq!(shown_text(&s), ["Шаг (a) ⌀12 №3"]);
+    // кегль по высоте прописных
+    assert!(s.contains("BT /F1 1 Tf 4.8011 0 0 4.8011 10 10 Tm"));
+    // заливка — чётно-нечётная
+    assert!(s.contains("0.502 0.502 0.502 rg\n") && s.contains("h\nf*\n"));
+    // вставка развёрнута: окружность радиуса 2
+    assert_eq!(s.matches(" c\n").count(), 4);
+}
+
+#[test]
+fn sheets_are_pages() {
+    let mut model = Document::new();
+    model.add_block(BlockDef {
+        name: "штамп".into(),
+        base: p(0.0, 0.0),
+        entities: vec![Entity::new(
+            "0",
+            EntityKind::LineSeg {
+                a: p(0.0, 0.0),
+                b: p(185.0, 0.0),
+            },
+        )],
+        attdefs: vec![],
+    });
+    let sheet = |id: Id, size_mm, annots| Sheet {
+        id,
+        name: format!("Лист {id}"),
+        size_mm,
+        viewports: vec![Viewport {
+            id: 1,
+            element_ref: 0,
+            kind: ViewKind::Plan,
+            rect_mm: RectMM {
+                x: 20.0,
+                y: 20.0,
+                w: 100.0,
+                h: 80.0,
+            },
+            scale: 100.0,
+            clip: None,
+            annots: vec![],
+        }],
+        annots,
+    };
+    let sheets = vec![
+        sheet(
+            1,
+            (420.0, 297.0),
+            vec![
+                Annot::Block {
+                    name: "штамп".into(),
+                    at_mm: p(230.0, 5.0),
+                },
+                Annot::Table {
+                    origin_mm: p(250.0, 280.0),
+                    table: Table {
+                        rows: vec![vec!["Поз.".into(), "Наименование".into()]],
+                        ..Default::default()
+                    },
+                    max_height_mm: 0.0,
+                },
+            ],
+        ),
+        sheet(
+            2,
+            (297.0, 210.0),
+            vec![Annot::DimLinear {
+                a_mm: p(20.0, 20.0),
+                b_mm: p(120.0, 20.0),
+                off_mm: -8.0,
+            }],
+        ),
+    ];
+
+    let doc = sheets[0].to_document(&model, &Project3D::default());
+    assert!(doc.layer(SHEET_VIEWPORT_LAYER).is_some());
+    // рамка, вставка штампа и таблица
+    assert_eq!(doc.entities().len(), 3);
+
+    let s = text(&sheets_pdf(&model, &Project3D::default(), &sheets));
+    assert!(s.contains("/Count 2"));
+    let boxes = media_boxes(&s);
+    assert_eq!(boxes.len(), 2);
+    assert!((mm(boxes[0].0) - 420.0).abs() < 0.01 && (mm(boxes[0].1) - 297.0).abs() < 0.01);
+    assert!((mm(boxes[1].0) - 297.0).abs() < 0.01);
+    // рамка вида тонкая, штамп из блока модели — на месте
+    assert!(s.contains("0.18 w\n[] 0 d\n20 20 m\n120 20 l\n120 100 l\n20 100 l\nh\nS"));
+    assert!(s.contains("230 5 m\n415 5 l\nS"));
+    // размер развёрнут, текст — длина в мм
+    assert!(shown_text(&s).iter().any(|t| t == "100.00"));
+}
+
+#[test]
+fn font_subset_is_embedded() {
+    let mut doc = Document::new();
+    make_text(&mut doc, p(0.0, 0.0), "Фундамент Ø12 — ёж", 5.0, "0");
+    let s = text(&document_pdf(&doc, 1.0));
+    assert!(s.contains("/Subtype /Type0") && s.contains("/CIDToGIDMap /Identity"));
+    let descriptor = object(&s, reference(&s, "/FontDescriptor"));
+    assert!(descriptor.contains("/Flags 32") && descriptor.contains("/CapHeight 729"));
+
+    // программа шрифта — TrueType с нужными таблицами
+    let file = object(&s, reference(&s, "/FontFile2"));
+    let len1: usize = file[file.find("/Length1 ").unwrap() + 9..]
+        .split(' ')
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let body = &file[file.find("stream\n").unwrap() + 7..];
+    let hex: String = body[..body.find('>').unwrap()].split_whitespace().collect();
+    let ttf: Vec<u8> = (0..hex.len() / 2)
+        .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+        .collect();
+    assert_eq!(ttf.len(), len1);
+    assert_eq!(ttf[..4], [0, 1, 0, 0]);
+    let be16 = |o: usize| u16::from_be_bytes([ttf[o], ttf[o + 1]]) as usize;
+    let be32 = |o: usize| u32::from_be_bytes(ttf[o..o + 4].try_into().unwrap()) as usize;
+    let tables: std::collections::HashMap<&[u8], (usize, usize)> = (0..be16(4))
+        .map(|i| {
+            let r = 12 + 16 * i;
+            (&ttf[r..r + 4], (be32(r + 8), be32(r + 12)))
+        })
+        .collect();
+    for tag in [&b"head"[..], b"hhea", b"maxp", b"hmtx", b"loca", b"glyf"] {
+        assert!(tables.contains_key(tag), "{}", String::from_utf8_lossy(tag));
+    }
+    // .notdef и 15 разных символов (пробел один), составные глифы добавляют части
+    let glyphs = be16(tables[&b"maxp"[..]].0 + 4);
+    assert!(glyphs >= 16, "{glyphs}");
+    // у «Ф» (CID 1) есть контуры
+    let loca = tables[&b"loca"[..]].0;
+    assert!(be32(loca + 8) > be32(loca + 4));
+    assert_eq!(shown_text(&s), ["Фундамент Ø12 — ёж"]);
+    let w = object(&s, 4);
+    assert!(w.contains("/W [0 ["), "{w}");
+}
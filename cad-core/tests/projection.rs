@@ -0,0 +1,181 @@
+mod common;
+use cad_core::model3d::{ElementGeom, Model3D, Project3D, Pt3};
+use cad_core::*;
+use common::{cuboid, element};
+
+fn dist(a: Pt2, b: Pt2) -> f32 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+fn count(edges: &[ProjectedEdge], class: EdgeClass) -> usize {
+    edges.iter().filter(|e| e.class == class).count()
+}
+
+fn length(edges: &[ProjectedEdge], class: EdgeClass) -> f32 {
+    edges
+        .iter()
+        .filter(|e| e.class == class)
+        .map(|e| dist(e.a, e.b))
+        .sum()
+}
+
+#[test]
+fn plan_of_box_is_outline() {
+    let b = cuboid(1, [0.0, 0.0, 0.0], 3000.0, 2000.0, 1000.0);
+    let edges = project_elements(&[&b], &ViewKind::Plan);
+    // очерк по верхней грани; нижние рёбра под ним не дублируются
+    assert_eq!(edges.len(), 4, "{edges:?}");
+    assert_eq!(count(&edges, EdgeClass::Silhouette), 4);
+    assert!((length(&edges, EdgeClass::Silhouette) - 10000.0).abs() < 0.1);
+
+    // профиль с повтором первой точки даёт то же тело
+    let ElementGeom::Extrusion { profile, .. } = &b.geom else {
+        unreachable!()
+    };
+    let mut closed = profile.clone();
+    closed.push(closed[0]);
+    let c = element(
+        2,
+        ElementGeom::Extrusion {
+            profile: closed,
+            height: 1000.0,
+        },
+        [0.0; 3],
+        0,
+    );
+    assert_eq!(project_elements(&[&c], &ViewKind::Plan).len(), 4);
+}
+
+#[test]
+fn iso_box_edge_classes() {
+    let b = cuboid(1, [0.0, 0.0, 0.0], 3000.0, 2000.0, 1000.0);
+    let edges = project_elements(&[&b], &ViewKind::Iso);
+    assert_eq!(count(&edges, EdgeClass::Silhouette), 6, "{edges:?}");
+    assert_eq!(count(&edges, EdgeClass::Visible), 3, "{edges:?}");
+    assert_eq!(count(&edges, EdgeClass::Hidden), 3);
+    // невидимые рёбра — из дальнего нижнего угла (0, 2000, 0)
+    let corner = Pt2::new(2000.0 / 2f32.sqrt(), 2000.0 / 6f32.sqrt());
+    assert!(edges
+        .iter()
+        .filter(|e| e.class == EdgeClass::Hidden)
+        .all(|e| dist(e.a, corner) < 0.1 || dist(e.b, corner) < 0.1));
+}
+
+#[test]
+fn box_behind_box_in_elevation() {
+    // стена спереди, за ней колонна выше стены
+    let wall = cuboid(1, [0.0, 0.0, 0.0], 3000.0, 1000.0, 3000.0);
+    let column = cuboid(2, [1000.0, 2000.0, 1000.0], 1000.0, 1000.0, 3000.0);
+    let edges = project_elements(&[&wall, &column], &ViewKind::ElevX);
+    // за стеной: низ колонны и нижние 2 м её граней
+    assert!(
+        (length(&edges, EdgeClass::Hidden) - 5000.0).abs() < 0.5,
+        "{edges:?}"
+    );
+    // очерк стены и выступающая часть колонны
+    assert!((length(&edges, EdgeClass::Silhouette) - 15000.0).abs() < 0.5);
+    assert_eq!(count(&edges, EdgeClass::Visible), 0);
+
+    // сбоку (вид по Y) колонна стоит дальше по оси — обе видны целиком
+    let edges = project_elements(&[&wall, &column], &ViewKind::ElevY);
+    assert_eq!(count(&edges, EdgeClass::Hidden), 0, "{edges:?}");
+}
+
+#[test]
+fn tube_has_two_silhouettes() {
+    let bar = element(
+        1,
+        ElementGeom::SweepCylinder {
+            path: vec![Pt3::new(0.0, 0.0, 0.0), Pt3::new(1000.0, 0.0, 0.0)],
+            radius: 50.0,
+        },
+        [0.0; 3],
+        0,
+    );
+    let edges = project_elements(&[&bar], &ViewKind::Plan);
+    let sil: Vec<_> = edges
+        .iter()
+        .filter(|e| e.class == EdgeClass::Silhouette)
+        .collect();
+    assert_eq!(sil.len(), 2, "{edges:?}");
+    for e in sil {
+        assert!((e.a.y.abs() - 50.0).abs() < 1e-3 && (e.b.x - e.a.x).abs() > 999.9);
+    }
+    // гладкая поверхность без рёбер сетки, торцы — отрезками поперёк
+    assert_eq!(count(&edges, EdgeClass::Hidden), 0);
+    assert!(edges
+        .iter()
+        .filter(|e| e.class == EdgeClass::Visible)
+        .all(|e| e.a.x.abs() < 1e-3 || (e.a.x - 1000.0).abs() < 1e-3));
+}
+
+#[test]
+fn viewport_on_sheet() {
+    let project = Project3D {
+        models: vec![Model3D {
+            name: "M".into(),
+            elements: vec![
+                cuboid(7, [0.0, 0.0, 0.0], 20000.0, 4000.0, 3000.0),
+                cuboid(8, [50000.0, 0.0, 0.0], 1000.0, 1000.0, 1000.0),
+            ],
+            materials: vec![],
+        }],
+        ..Default::default()
+    };
+    let rect = RectMM {
+        x: 20.0,
+        y: 20.0,
+        w: 100.0,
+        h: 80.0,
+    };
+    let sheet = Sheet {
+        id: 1,
+        name: "Лист 1".into(),
+        size_mm: (297.0, 210.0),
+        viewports: vec![Viewport {
+            id: 1,
+            element_ref: 7,
+            kind: ViewKind::Plan,
+            rect_mm: rect,
+            scale: 100.0,
+            clip: None,
+            annots: vec![],
+        }],
+        annots: vec![],
+    };
+    let doc = sheet.to_document(&Document::new(), &project);
+    assert_eq!(
+        doc.layer(VIEW_HIDDEN_LAYER).unwrap().linetype,
+        HIDDEN_LINETYPE
+    );
+    assert!(doc.linetype(HIDDEN_LINETYPE).is_some());
+
+    // только элемент 7: 200 × 40 мм по центру окна, обрезан по ширине
+    let lines: Vec<(Pt2, Pt2)> = doc
+        .entities()
+        .iter()
+        .filter(|e| e.layer == VIEW_SILHOUETTE_LAYER)
+        .map(|e| match e.kind {
+            EntityKind::LineSeg { a, b } => (a, b),
+            ref k => panic!("{k:?}"),
+        })
+        .collect();
+    assert_eq!(lines.len(), 2, "{lines:?}");
+    for (a, b) in lines {
+        assert!(
+            (a.y - b.y).abs() < 1e-4 && ((a.y - 40.0).abs() < 1e-3 || (a.y - 80.0).abs() < 1e-3)
+        );
+        assert!((a.x.min(b.x) - 20.0).abs() < 1e-3 && (a.x.max(b.x) - 120.0).abs() < 1e-3);
+    }
+
+    // без ссылки на элемент — вся модель
+    let mut whole = sheet.clone();
+    whole.viewports[0].element_ref = 0;
+    whole.viewports[0].scale = 1000.0;
+    let n = whole
+        .to_document(&Document::new(), &project)
+        .entities()
+        .len();
+    // рамка + 4 линии стены + 4 линии колонны
+    assert_eq!(n, 9);
+}
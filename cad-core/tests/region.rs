@@ -0,0 +1,243 @@
+mod common;
+use cad_core::*;
+use common::p;
+use std::f64::consts::PI;
+
+fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Region {
+    Region::new(vec![p(x0, y0), p(x1, y0), p(x1, y1), p(x0, y1)], vec![]).unwrap()
+}
+
+fn total_area(rs: &[Region]) -> f64 {
+    rs.iter().map(Region::area).sum()
+}
+
+fn has(poly: &[Pt2], q: Pt2) -> bool {
+    poly.iter()
+        .any(|c| (c.x - q.x).abs() < 1e-4 && (c.y - q.y).abs() < 1e-4)
+}
+
+#[test]
+fn overlapping_squares() {
+    let a = rect(0.0, 0.0, 10.0, 10.0);
+    let b = rect(5.0, 5.0, 15.0, 15.0);
+
+    let u = a.union(&b);
+    assert_eq!(u.len(), 1);
+    assert!((u[0].area() - 175.0).abs() < 1e-6);
+    assert_eq!(u[0].outer.len(), 8, "{:?}", u[0].outer);
+    assert!(has(&u[0].outer, p(10.0, 5.0)) && has(&u[0].outer, p(5.0, 10.0)));
+
+    let d = a.difference(&b);
+    assert_eq!(d.len(), 1);
+    assert!((d[0].area() - 75.0).abs() < 1e-6);
+    assert_eq!(d[0].outer.len(), 6);
+
+    let i = a.intersection(&b);
+    assert_eq!(i.len(), 1);
+    assert_eq!(i[0].outer.len(), 4);
+    assert!((i[0].area() - 25.0).abs() < 1e-6);
+    let c = i[0].centroid().unwrap();
+    assert!((c.x - 7.5).abs() < 1e-5 && (c.y - 7.5).abs() < 1e-5);
+
+    let x = a.xor(&b);
+    assert_eq!(x.len(), 2);
+    assert!((total_area(&x) - 150.0).abs() < 1e-6);
+}
+
+#[test]
+fn holes_shared_edges_and_touching_corners() {
+    let slab = rect(0.0, 0.0, 10.0, 10.0);
+    let opening = rect(2.0, 2.0, 4.0, 4.0);
+    let d = slab.difference(&opening);
+    assert_eq!(d.len(), 1);
+    assert_eq!(d[0].holes.len(), 1);
+    assert!((d[0].area() - 96.0).abs() < 1e-6);
+    assert!(!d[0].contains(p(3.0, 3.0)) && d[0].contains(p(5.0, 5.0)));
+    // отверстие заполняется обратно объединением
+    let back = d[0].union(&opening);
+    assert_eq!(back.len(), 1);
+    assert!(back[0].holes.is_empty());
+    assert_eq!(back[0].outer.len(), 4);
+
+    // стены встык по общей стороне сливаются в один прямоугольник
+    let walls = union_all(&[
+        rect(0.0, 0.0, 5.0, 1.0),
+        rect(5.0, 0.0, 10.0, 1.0),
+        rect(2.0, 0.0, 7.0, 1.0),
+    ]);
+    assert_eq!(walls.len(), 1);
+    assert_eq!(walls[0].outer.len(), 4, "{:?}", walls[0].outer);
+    assert!((walls[0].area() - 10.0).abs() < 1e-6);
+
+    // касание углом — две отдельные области; пересечение пусто
+    let a = rect(0.0, 0.0, 1.0, 1.0);
+    let b = rect(1.0, 1.0, 2.0, 2.0);
+    let u = a.union(&b);
+    assert_eq!(u.len(), 2);
+    assert!(u.iter().all(|r| r.outer.len() == 4));
+    assert!(a.intersection(&b).is_empty());
+    // совпадающие области
+    assert!(a.difference(&a).is_empty());
+    assert_eq!(a.union(&a), vec![a.clone()]);
+}
+
+#[test]
+fn regions_from_closed_curves() {
+    let outer = EntityKind::Circle {
+        center: p(0.0, 0.0),
+        radius: 10.0,
+    };
+    let hole = EntityKind::Circle {
+        center: p(0.0, 0.0),
+        radius: 4.0,
+    };
+    let island = EntityKind::Polyline {
+        pts: vec![p(-1.0, -1.0), p(1.0, -1.0), p(1.0, 1.0), p(-1.0, 1.0)],
+        closed: true,
+        bulges: vec![],
+    };
+    let rs = regions_from_curves(&[island.clone(), outer.clone(), hole.clone()]);
+    assert_eq!(rs.len(), 2);
+    let ring = rs.iter().find(|r| r.holes.len() == 1).unwrap();
+    // дуговые рёбра считаются по окружностям, а не по хордам
+    let exact = PI * (100.0 - 16.0);
+    assert!((ring.area() - exact).abs() < 1e-3, "{}", ring.area());
+    let disk = Region::from_curve(&outer).unwrap();
+    let props = entity_area(&outer).unwrap();
+    assert!((disk.area() - props.area).abs() < 1e-3);
+    let c = ring.centroid().unwrap();
+    assert!(c.x.abs() < 1e-3 && c.y.abs() < 1e-3);
+    assert!(rs.iter().any(|r| (r.area() - 4.0).abs() < 1e-6));
+
+    // D-образный контур из отрезков и дуги, одна кривая развёрнута
+    let d = Region::from_curves(
+        &[
+            EntityKind::LineSeg {
+                a: p(0.0, -2.0),
+                b: p(0.0, 2.0),
+            },
+            EntityKind::Arc {
+                center: p(0.0, 0.0),
+                radius: 2.0,
+                start_angle: -std::f32::consts::FRAC_PI_2,
+                end_angle: std::f32::consts::FRAC_PI_2,
+            },
+        ],
+        &[],
+    )
+    .unwrap();
+    assert!((d.area() - 2.0 * PI).abs() < 0.01);
+    assert!(d.centroid().unwrap().x > 0.8);
+
+    // незамкнутая цепочка и полилиния с выпуклостями
+    assert!(Region::from_curve(&EntityKind::LineSeg {
+        a: p(0.0, 0.0),
+        b: p(1.0, 0.0)
+    })
+    .is_none());
+    let slot = Region::from_curve(&EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(4.0, 0.0), p(4.0, 2.0), p(0.0, 2.0)],
+        closed: true,
+        bulges: vec![0.0, 1.0, 0.0, 1.0],
+    })
+    .unwrap();
+    assert!((slot.area() - (8.0 + PI)).abs() < 0.01);
+}
+
+#[test]
+fn curved_booleans_and_entities() {
+    let slab = Region::from_curve(&EntityKind::Circle {
+        center: p(0.0, 0.0),
+        radius: 5.0,
+    })
+    .unwrap();
+    let cut = rect(0.0, -10.0, 10.0, 10.0);
+    let half = slab.difference(&cut);
+    assert_eq!(half.len(), 1);
+    assert!((half[0].area() - slab.area() / 2.0).abs() < 1e-3);
+    let c = half[0].centroid().unwrap();
+    // центр тяжести полукруга: 4R/3π
+    assert!((c.x as f64 + 20.0 / (3.0 * PI)).abs() < 0.01, "{c:?}");
+
+    let both = slab.intersection(&cut)[0].union(&half[0]);
+    assert_eq!(both.len(), 1);
+    assert!((both[0].area() - slab.area()).abs() < 1e-3);
+
+    let ring = slab.difference(&rect(-1.0, -1.0, 1.0, 1.0));
+    let ents = ring[0].to_entities();
+    assert_eq!(ents.len(), 2);
+    // окружность возвращается четырьмя дугами, квадрат — прямыми
+    assert!(matches!(&ents[0], EntityKind::Polyline { pts, bulges, .. }
+        if pts.len() == 4 && bulges.iter().all(|b| (b - (PI as f32 / 8.0).tan()).abs() < 1e-4)));
+    assert!(
+        matches!(&ents[1], EntityKind::Polyline { pts, closed: true, bulges }
+        if pts.len() == 4 && bulges.iter().all(|b| *b == 0.0))
+    );
+    let back = Region::from_curves(&ents[..1], &[ents[1..].to_vec()]).unwrap();
+    assert_eq!(back.outer.len(), ring[0].outer.len());
+    assert_eq!(back.holes, ring[0].holes);
+    assert!((back.area() - ring[0].area()).abs() < 1e-3);
+
+    // после вычитания дуга полукруга остаётся дугой, срез — прямым сегментом
+    let ents = half[0].to_entities();
+    let EntityKind::Polyline { pts, bulges, .. } = &ents[0] else {
+        panic!("{ents:?}");
+    };
+    assert_eq!(bulges.iter().filter(|b| **b == 0.0).count(), 1);
+    assert!(bulges.iter().all(|b| *b >= 0.0));
+    assert!(pts.iter().all(|q| (q.x.hypot(q.y) - 5.0).abs() < 1e-4));
+    let arcs: f32 = bulges.iter().map(|b| 4.0 * b.atan()).sum();
+    assert!((arcs - PI as f32).abs() < 1e-4);
+    let again = Region::from_curves(&ents, &[]).unwrap();
+    assert!((again.area() - half[0].area()).abs() < 1e-3);
+}
+
+#[cfg(feature = "cryxtal-brep")]
+#[test]
+fn region_face_extrudes() {
+    let d = rect(0.0, 0.0, 10.0, 10.0).difference(&rect(2.0, 2.0, 4.0, 4.0));
+    let face = d[0].to_face();
+    assert_eq!(face.boundaries().len(), 2);
+    let solid = cad_core::truck_bridge::extrude_face(&face, 3.0);
+    assert_eq!(solid.boundaries()[0].len(), 10);
+}
+
+#[cfg(feature = "cryxtal-brep")]
+#[test]
+fn polygon_extrudes_to_mesh() {
+    use cad_core::truck_bridge::extrude_polygon_to_mesh_with_transform;
+    let sq = |a: f32, b: f32| {
+        vec![
+            Pt2::new(a, a),
+            Pt2::new(b, a),
+            Pt2::new(b, b),
+            Pt2::new(a, b),
+        ]
+    };
+    let mut hole = sq(2.0, 4.0);
+    hole.reverse();
+    let xf = [
+        [1.0, 0.0, 0.0, 100.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 5.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+    let (pos, idx) = extrude_polygon_to_mesh_with_transform(&sq(0.0, 10.0), &[hole], 3.0, &xf);
+    assert!(!idx.is_empty() && idx.len() % 3 == 0);
+    assert!(idx.iter().all(|&i| (i as usize) < pos.len()));
+    assert!(pos
+        .iter()
+        .all(|p| (100.0..=110.0).contains(&p[0]) && (5.0..=8.0).contains(&p[2])));
+
+    // объём по теореме Гаусса: (100 − 4) × 3
+    let volume: f64 = idx
+        .chunks_exact(3)
+        .map(|t| {
+            let [a, b, c] = [0, 1, 2].map(|k| pos[t[k] as usize].map(f64::from));
+            a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+                + a[2] * (b[0] * c[1] - b[1] * c[0])
+        })
+        .sum::<f64>()
+        / 6.0;
+    assert!((volume.abs() - 288.0).abs() < 1e-3, "{volume}");
+}
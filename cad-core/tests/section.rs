@@ -0,0 +1,277 @@
+mod common;
+use cad_core::model3d::{
+    Element3D, Material, MaterialKind, Meta, Model3D, Project3D, Pt3, Rebar, RebarPath,
+};
+use cad_core::*;
+use common::cuboid;
+
+fn section(origin: Pt3, normal: Pt3, depth: f32) -> Section {
+    Section {
+        origin_mm: Pt2::new(0.0, 0.0),
+        origin,
+        normal,
+        depth,
+    }
+}
+
+fn area(poly: &[Pt2]) -> f32 {
+    (0..poly.len())
+        .map(|i| {
+            let (a, b) = (poly[i], poly[(i + 1) % poly.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+/// Балка 6 м по X, сечение 300 × 500, два стержня ⌀20 понизу.
+fn beam() -> Element3D {
+    let mut b = cuboid(1, [0.0, 0.0, 0.0], 6000.0, 300.0, 500.0);
+    for (id, y) in [(1, 50.0), (2, 250.0)] {
+        b.rebars.push(Rebar {
+            id,
+            diameter_mm: 20.0,
+            path: RebarPath::Polyline(vec![Pt3::new(30.0, y, 50.0), Pt3::new(5970.0, y, 50.0)]),
+            count: 1,
+            meta: Meta::default(),
+        });
+    }
+    b
+}
+
+#[test]
+fn plane_cuts_box_into_rectangle() {
+    let b = cuboid(1, [0.0, 0.0, 0.0], 3000.0, 2000.0, 1000.0);
+    let s = section(Pt3::new(0.0, 1000.0, 0.0), Pt3::new(0.0, -1.0, 0.0), 0.0);
+    let loops = s.cut_element(&b);
+    assert_eq!(loops.len(), 1, "{loops:?}");
+    assert!(loops[0].iter().all(|p| (p.y - 1000.0).abs() < 1e-3));
+
+    // оси разреза по нормали −Y — как у фасада по X
+    let [r, u, d] = s.axes().unwrap();
+    assert_eq!(
+        (r, u, d),
+        (ViewKind::ElevX.axes()[0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0])
+    );
+    let sv = project_section(&[&b], &s).unwrap();
+    assert!((area(&sv.cuts[0].loops[0]).abs() - 3000.0 * 1000.0).abs() < 1.0);
+
+    // горизонтальный разрез смотрит как план
+    let s = section(Pt3::new(0.0, 0.0, 500.0), Pt3::new(0.0, 0.0, 1.0), 0.0);
+    assert_eq!(s.axes().unwrap(), ViewKind::Plan.axes());
+    // плоскость мимо тела и плоскость без нормали
+    let miss = section(Pt3::new(0.0, 0.0, 2000.0), Pt3::new(0.0, 0.0, 1.0), 0.0);
+    assert!(miss.cut_element(&b).is_empty());
+    let none = section(Pt3::new(0.0, 0.0, 0.0), Pt3::new(0.0, 0.0, 0.0), 0.0);
+    assert!(none.cut_element(&b).is_empty() && project_section(&[&b], &none).is_none());
+}
+
+#[test]
+fn rebars_cut_as_points() {
+    let b = beam();
+    let s = section(Pt3::new(3000.0, 0.0, 0.0), Pt3::new(1.0, 0.0, 0.0), 0.0);
+    let bars = s.cut_rebars(&b);
+    assert_eq!(bars.len(), 2);
+    assert!(bars
+        .iter()
+        .all(|(c, d)| (c.x - 3000.0).abs() < 1e-3 && (c.z - 50.0).abs() < 1e-3 && *d == 20.0));
+
+    // кривой стержень — по выборке NURBS; матрица элемента применяется
+    let mut bent = beam();
+    bent.xform[2][3] = 1000.0;
+    bent.rebars[0].path = RebarPath::Nurbs {
+        degree: 2,
+        knots: vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        ctrl_pts: vec![
+            Pt3::new(0.0, 50.0, 50.0),
+            Pt3::new(3000.0, 50.0, 450.0),
+            Pt3::new(6000.0, 50.0, 50.0),
+        ],
+        weights: None,
+    };
+    let bars = s.cut_rebars(&bent);
+    assert_eq!(bars.len(), 2);
+    // вершина параболы: z = 50 + 400 / 2
+    assert!((bars[0].0.z - 1250.0).abs() < 1.0, "{bars:?}");
+    assert!((bars[1].0.z - 1050.0).abs() < 1e-3);
+}
+
+#[test]
+fn beyond_the_cut_and_depth() {
+    // разрез по стене; колонна за стеной выше неё
+    let wall = cuboid(1, [0.0, 0.0, 0.0], 3000.0, 1000.0, 3000.0);
+    let column = cuboid(2, [1000.0, 2000.0, 0.0], 1000.0, 1000.0, 4000.0);
+    let s = section(Pt3::new(0.0, 500.0, 0.0), Pt3::new(0.0, -1.0, 0.0), 0.0);
+    let sv = project_section(&[&wall, &column], &s).unwrap();
+    assert_eq!(sv.cuts.len(), 1);
+    assert_eq!(sv.cuts[0].element, 1);
+    // за сечением видна только верхняя часть колонны, невидимого нет
+    assert!(sv.edges.iter().all(|e| e.class != EdgeClass::Hidden));
+    let len: f32 = sv
+        .edges
+        .iter()
+        .map(|e| (e.b.x - e.a.x).hypot(e.b.y - e.a.y))
+        .sum();
+    assert!((len - 3000.0).abs() < 0.5, "{:?}", sv.edges);
+    assert!(sv.edges.iter().all(|e| e.a.y >= 2999.9 && e.b.y >= 2999.9));
+
+    // глубина 1 м не достаёт до колонны
+    let s = section(Pt3::new(0.0, 500.0, 0.0), Pt3::new(0.0, -1.0, 0.0), 1000.0);
+    let sv = project_section(&[&wall, &column], &s).unwrap();
+    assert!(sv.edges.is_empty(), "{:?}", sv.edges);
+}
+
+#[test]
+fn section_viewport_on_sheet() {
+    let mut b = beam();
+    b.material = 1;
+    let mut plate = cuboid(2, [0.0, 300.0, 0.0], 6000.0, 20.0, 500.0);
+    plate.material = 2;
+    let project = Project3D {
+        models: vec![Model3D {
+            name: "M".into(),
+            elements: vec![b, plate],
+            materials: vec![
+                Material {
+                    id: 1,
+                    name: "B25".into(),
+                    kind: MaterialKind::Concrete {
+                        grade: "B25".into(),
+                    },
+                },
+                Material {
+                    id: 2,
+                    name: "C245".into(),
+                    kind: MaterialKind::Steel { fy_mpa: 240.0 },
+                },
+            ],
+        }],
+        ..Default::default()
+    };
+    let sheet = Sheet {
+        id: 1,
+        name: "Разрез 1-1".into(),
+        size_mm: (297.0, 210.0),
+        viewports: vec![Viewport {
+            id: 1,
+            element_ref: 0,
+            kind: ViewKind::Section,
+            rect_mm: RectMM {
+                x: 20.0,
+                y: 20.0,
+                w: 100.0,
+                h: 80.0,
+            },
+            scale: 10.0,
+            clip: Some(Section {
+                origin_mm: Pt2::new(50.0, 40.0),
+                origin: Pt3::new(3000.0, 0.0, 0.0),
+                normal: Pt3::new(1.0, 0.0, 0.0),
+                depth: 0.0,
+            }),
+            annots: vec![],
+        }],
+        annots: vec![],
+    };
+    let doc = sheet.to_document(&Document::new(), &project);
+    let on = |layer: &str| -> Vec<&Entity> {
+        doc.entities().iter().filter(|e| e.layer == layer).collect()
+    };
+
+    // бетон и сталь — разными образцами
+    let fills: Vec<String> = on(VIEW_HATCH_LAYER)
+        .iter()
+        .map(|e| match &e.kind {
+            EntityKind::Hatch {
+                fill: HatchFill::Pattern { name, .. },
+                ..
+            } => name.clone(),
+            k => panic!("{k:?}"),
+        })
+        .collect();
+    assert_eq!(fills, ["AR-CONC", "ANSI31"]);
+
+    // начало плоскости (y = 0, z = 0) — в origin_mm; смотрим на −X, вправо +Y
+    let cut: Vec<(Pt2, Pt2)> = on(VIEW_CUT_LAYER)
+        .iter()
+        .map(|e| match e.kind {
+            EntityKind::LineSeg { a, b } => (a, b),
+            ref k => panic!("{k:?}"),
+        })
+        .collect();
+    let xs = cut.iter().flat_map(|(a, b)| [a.x, b.x]);
+    let (lo, hi) = xs.fold((f32::MAX, f32::MIN), |(l, h), x| (l.min(x), h.max(x)));
+    assert!(
+        (lo - 50.0).abs() < 1e-3 && (hi - 82.0).abs() < 1e-3,
+        "{cut:?}"
+    );
+
+    // стержни ⌀20 в 1:10 — окружности радиуса 1 мм с заливкой
+    let bars = on(VIEW_REBAR_LAYER);
+    assert_eq!(bars.len(), 4);
+    let radii: Vec<f32> = bars
+        .iter()
+        .filter_map(|e| match e.kind {
+            EntityKind::Circle { radius, .. } => Some(radius),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(radii, [1.0, 1.0]);
+    let centers: Vec<Pt2> = bars
+        .iter()
+        .filter_map(|e| match e.kind {
+            EntityKind::Circle { center, .. } => Some(center),
+            _ => None,
+        })
+        .collect();
+    for (c, x) in centers.iter().zip([55.0, 75.0]) {
+        assert!(
+            (c.x - x).abs() < 1e-3 && (c.y - 45.0).abs() < 1e-3,
+            "{centers:?}"
+        );
+    }
+
+    // старый файл без плоскости — обычный вид
+    let json = r#"{"origin_mm":{"x":1.0,"y":2.0}}"#;
+    let old: Section = serde_json::from_str(json).unwrap();
+    let mut plain = sheet.clone();
+    plain.viewports[0].clip = Some(old);
+    let doc = plain.to_document(&Document::new(), &project);
+    assert!(doc.entities().iter().all(|e| e.layer != VIEW_CUT_LAYER));
+    assert!(doc
+        .entities()
+        .iter()
+        .any(|e| e.layer == VIEW_SILHOUETTE_LAYER));
+}
+
+#[cfg(feature = "cryxtal-brep")]
+#[test]
+fn brep_with_hole() {
+    use cad_core::model3d::ElementGeom;
+    use cad_core::truck_bridge::*;
+    use common::element;
+    let sq = |a: f32, b: f32| {
+        vec![
+            Pt2::new(a, a),
+            Pt2::new(b, a),
+            Pt2::new(b, b),
+            Pt2::new(a, b),
+        ]
+    };
+    let outer = wire_from_closed_polyline(&sq(0.0, 1000.0));
+    let mut hole = sq(300.0, 700.0);
+    hole.reverse();
+    let face = planar_face_from_wires(&outer, &[wire_from_closed_polyline(&hole)]);
+    let el = element(
+        1,
+        ElementGeom::Brep(extrude_face(&face, 2000.0)),
+        [0.0; 3],
+        0,
+    );
+    let s = section(Pt3::new(0.0, 0.0, 1000.0), Pt3::new(0.0, 0.0, 1.0), 0.0);
+    let sv = project_section(&[&el], &s).unwrap();
+    let loops = &sv.cuts[0].loops;
+    assert_eq!(loops.len(), 2);
+    let total: f32 = loops.iter().map(|l| area(l).abs()).sum();
+    assert!((total - (1e6 + 0.16e6)).abs() < 10.0, "{total}");
+}
@@ -0,0 +1,213 @@
+mod common;
+use cad_core::*;
+use common::{close, p};
+
+fn at(entity: u64, snap: DimSnap) -> DimLink {
+    DimLink { entity, snap }
+}
+
+fn vertices(doc: &Document, id: u64) -> Vec<Pt2> {
+    match &doc.entity(id).unwrap().kind {
+        EntityKind::Polyline { pts, .. } => pts.clone(),
+        k => panic!("{k:?}"),
+    }
+}
+
+/// Прямоугольный профиль: угол закреплён, стороны горизонтальны/вертикальны,
+/// ширина и высота заданы размерами. Возвращает индекс размера ширины.
+fn rectangle(doc: &mut Document, sketch: &mut Sketch) -> (u64, usize) {
+    // нарисован «на глаз»
+    let r = make_polyline(
+        doc,
+        vec![p(0.2, -0.1), p(9.0, 0.3), p(9.5, 5.5), p(-0.3, 4.8)],
+        true,
+        "0",
+    )
+    .unwrap();
+    let v = |i| at(r, DimSnap::Vertex(i));
+    sketch.add(Constraint::Fixed(at(r, DimSnap::Start)));
+    for seg in [0, 2] {
+        sketch.add(Constraint::Horizontal(LineRef::seg(r, seg)));
+    }
+    for seg in [1, 3] {
+        sketch.add(Constraint::Vertical(LineRef::seg(r, seg)));
+    }
+    let w = sketch.add(Constraint::Distance(v(0), v(1), 10.0));
+    sketch.add(Constraint::Distance(v(1), v(2), 5.0));
+    (r, w)
+}
+
+#[test]
+fn profile_follows_width() {
+    let mut doc = Document::new();
+    let mut sketch = Sketch::new("column");
+    let (r, w) = rectangle(&mut doc, &mut sketch);
+    let rep = sketch.solve(&mut doc).unwrap();
+    assert!(rep.converged, "{rep:?}");
+    assert_eq!(rep.status(), SketchStatus::WellConstrained);
+    assert_eq!(rep.dof, 0);
+    assert!(rep.free.is_empty());
+    let pts = vertices(&doc, r);
+    let want = [p(0.2, -0.1), p(10.2, -0.1), p(10.2, 4.9), p(0.2, 4.9)];
+    for (a, b) in pts.iter().zip(want) {
+        assert!(close(*a, b), "{pts:?}");
+    }
+
+    // новая ширина — профиль перестраивается
+    let Constraint::Distance(a, b, _) = sketch.constraints[w] else {
+        unreachable!()
+    };
+    sketch.constraints[w] = Constraint::Distance(a, b, 30.0);
+    assert!(sketch.solve(&mut doc).unwrap().converged);
+    let pts = vertices(&doc, r);
+    assert!(
+        close(pts[1], p(30.2, -0.1)) && close(pts[2], p(30.2, 4.9)),
+        "{pts:?}"
+    );
+}
+
+#[test]
+fn tangent_and_coincident() {
+    let mut doc = Document::new();
+    let l = make_line(&mut doc, p(0.0, 0.0), p(10.0, 0.0), "0");
+    let c = make_circle(&mut doc, p(5.0, 3.0), 1.0, "0");
+    let a = make_arc(&mut doc, p(12.0, 1.0), 1.5, 0.0, std::f32::consts::PI, "0");
+    let mut s = Sketch::new("s");
+    s.add(Constraint::Fixed(at(l, DimSnap::Start)));
+    s.add(Constraint::Fixed(at(l, DimSnap::End)));
+    s.add(Constraint::Fixed(at(c, DimSnap::Center)));
+    s.add(Constraint::Tangent(LineRef::line(l), c));
+    s.add(Constraint::Coincident(
+        at(l, DimSnap::End),
+        at(a, DimSnap::End),
+    ));
+    s.add(Constraint::EqualRadius(a, c));
+    let rep = s.solve(&mut doc).unwrap();
+    assert!(rep.converged, "{rep:?}");
+
+    let EntityKind::Circle { radius, .. } = doc.entity(c).unwrap().kind else {
+        panic!()
+    };
+    assert!((radius - 3.0).abs() < 1e-4, "{radius}");
+    let k = &doc.entity(a).unwrap().kind;
+    let EntityKind::Arc { radius, .. } = *k else {
+        panic!()
+    };
+    assert!((radius - 3.0).abs() < 1e-4);
+    assert!(close(snap_point(k, DimSnap::End).unwrap(), p(10.0, 0.0)));
+    // дуга не закреплена полностью
+    assert_eq!(rep.status(), SketchStatus::UnderConstrained);
+    assert_eq!(rep.free, vec![a]);
+}
+
+#[test]
+fn reports_degrees_of_freedom() {
+    let mut doc = Document::new();
+    let l = make_line(&mut doc, p(0.0, 0.0), p(4.0, 3.0), "0");
+    let m = make_line(&mut doc, p(0.0, 1.0), p(1.0, 5.0), "0");
+    let mut s = Sketch::new("s");
+    s.add(Constraint::Horizontal(LineRef::line(l)));
+    s.add(Constraint::Perpendicular(
+        LineRef::line(l),
+        LineRef::line(m),
+    ));
+    let rep = s.solve(&mut doc).unwrap();
+    assert!(rep.converged);
+    assert_eq!(rep.dof, 6);
+    assert_eq!(rep.status(), SketchStatus::UnderConstrained);
+    assert_eq!(rep.free, vec![l, m]);
+    let EntityKind::LineSeg { a, b } = doc.entity(m).unwrap().kind else {
+        panic!()
+    };
+    assert!((a.x - b.x).abs() < 1e-4);
+
+    // угол между отрезками
+    s.constraints[1] = Constraint::Angle(LineRef::line(l), LineRef::line(m), 0.5);
+    s.add(Constraint::Distance(
+        at(m, DimSnap::Start),
+        at(m, DimSnap::End),
+        2.0,
+    ));
+    assert!(s.solve(&mut doc).unwrap().converged);
+    let EntityKind::LineSeg { a, b } = doc.entity(m).unwrap().kind else {
+        panic!()
+    };
+    assert!(close(
+        b,
+        p(a.x + 2.0 * 0.5f32.cos(), a.y + 2.0 * 0.5f32.sin())
+    ));
+}
+
+#[test]
+fn conflicting_constraints() {
+    let mut doc = Document::new();
+    let l = make_line(&mut doc, p(0.0, 0.0), p(10.0, 0.0), "0");
+    let mut s = Sketch::new("s");
+    s.add(Constraint::Fixed(at(l, DimSnap::Start)));
+    s.add(Constraint::Fixed(at(l, DimSnap::End)));
+    let d = s.add(Constraint::Distance(
+        at(l, DimSnap::Start),
+        at(l, DimSnap::End),
+        5.0,
+    ));
+    let rep = s.solve(&mut doc).unwrap();
+    assert!(!rep.converged);
+    assert!(rep.unsatisfied.contains(&d), "{rep:?}");
+    assert_eq!(rep.status(), SketchStatus::OverConstrained);
+    // несошедшееся решение не пишется в документ
+    assert_eq!(
+        doc.entity(l).unwrap().kind,
+        EntityKind::LineSeg {
+            a: p(0.0, 0.0),
+            b: p(10.0, 0.0)
+        }
+    );
+
+    // ссылки на несуществующее — ошибка, а не отчёт
+    s.add(Constraint::Radius(l, 1.0));
+    assert!(s.solve(&mut doc).is_err());
+    let s = Sketch {
+        name: "bad".into(),
+        constraints: vec![Constraint::Horizontal(LineRef::line(999))],
+    };
+    assert!(s.solve(&mut doc).is_err());
+}
+
+#[test]
+fn collapsed_line_is_conflicting() {
+    let mut doc = Document::new();
+    let l = make_line(&mut doc, p(0.0, 0.0), p(10.0, 1.0), "0");
+    let mut s = Sketch::new("s");
+    let h = s.add(Constraint::Horizontal(LineRef::line(l)));
+    let v = s.add(Constraint::Vertical(LineRef::line(l)));
+    let rep = s.solve(&mut doc).unwrap();
+    assert!(!rep.converged, "{rep:?}");
+    assert_eq!(rep.unsatisfied, vec![h, v]);
+    assert_eq!(rep.status(), SketchStatus::OverConstrained);
+    assert_eq!(
+        doc.entity(l).unwrap().kind,
+        EntityKind::LineSeg {
+            a: p(0.0, 0.0),
+            b: p(10.0, 1.0)
+        }
+    );
+}
+
+#[test]
+fn redundant_constraints() {
+    let mut doc = Document::new();
+    let mut sketch = Sketch::new("column");
+    let (r, _) = rectangle(&mut doc, &mut sketch);
+    let extra = sketch.add(Constraint::Parallel(LineRef::seg(r, 0), LineRef::seg(r, 2)));
+    let rep = sketch.solve(&mut doc).unwrap();
+    assert!(rep.converged);
+    assert_eq!(rep.redundant, vec![extra]);
+    assert!(rep.unsatisfied.is_empty());
+    assert_eq!(rep.dof, 0);
+    assert_eq!(rep.status(), SketchStatus::OverConstrained);
+
+    // зависимости хранятся вместе с документом как обычные данные
+    let json = serde_json::to_string(&sketch).unwrap();
+    let back: Sketch = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, sketch);
+}
@@ -0,0 +1,263 @@
+mod common;
+use cad_core::*;
+use common::p;
+use std::collections::HashSet;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+fn ids(v: Vec<&Entity>) -> HashSet<u64> {
+    v.into_iter().map(|e| e.id).collect()
+}
+
+fn near(a: Bbox, b: Bbox) -> bool {
+    [
+        (a.min.x, b.min.x),
+        (a.min.y, b.min.y),
+        (a.max.x, b.max.x),
+        (a.max.y, b.max.y),
+    ]
+    .iter()
+    .all(|(u, v)| (u - v).abs() < 1e-4)
+}
+
+fn block(kind: EntityKind) -> BlockDef {
+    BlockDef {
+        name: "B".into(),
+        base: p(0.0, 0.0),
+        entities: vec![Entity::new("0", kind)],
+        attdefs: vec![],
+    }
+}
+
+/// Детерминированный генератор для случайных чертежей.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[test]
+fn bounds_of_curves() {
+    // четверть дуги во II четверти — рамка с центром
+    let arc = EntityKind::Arc {
+        center: p(0.0, 0.0),
+        radius: 2.0,
+        start_angle: FRAC_PI_2,
+        end_angle: PI,
+    };
+    assert!(near(
+        kind_bounds(&arc).unwrap(),
+        Bbox::new(p(-2.0, 0.0), p(0.0, 2.0))
+    ));
+    // дуга через ноль: концы в IV и I четвертях, крайняя точка справа
+    let arc = EntityKind::Arc {
+        center: p(0.0, 0.0),
+        radius: 1.0,
+        start_angle: -0.5,
+        end_angle: 0.5,
+    };
+    let b = kind_bounds(&arc).unwrap();
+    assert!((b.max.x - 1.0).abs() < 1e-6 && b.min.x == 0.0);
+
+    // полукруглый сегмент полилинии выходит за точки
+    let pl = EntityKind::Polyline {
+        pts: vec![p(0.0, 0.0), p(2.0, 0.0)],
+        closed: false,
+        bulges: vec![1.0],
+    };
+    let b = kind_bounds(&pl).unwrap();
+    assert!(
+        (b.min.y + 1.0).abs() < 1e-5 || (b.max.y - 1.0).abs() < 1e-5,
+        "{b:?}"
+    );
+
+    let mut doc = Document::new();
+    doc.add_block(block(EntityKind::LineSeg {
+        a: p(0.0, 0.0),
+        b: p(1.0, 1.0),
+    }));
+    let id = make_insert(&mut doc, "B", p(10.0, 10.0), p(2.0, 2.0), 0.0, "0").unwrap();
+    let e = doc.entities().iter().find(|e| e.id == id).unwrap();
+    assert!(near(
+        entity_bounds(&doc, e).unwrap(),
+        Bbox::new(p(10.0, 10.0), p(12.0, 12.0))
+    ));
+}
+
+#[test]
+fn index_follows_edits() {
+    let mut doc = Document::new();
+    let a = make_line(&mut doc, p(0.0, 0.0), p(10.0, 0.0), "0");
+    let c = make_circle(&mut doc, p(50.0, 50.0), 5.0, "0");
+    // до обновления новые сущности всегда в кандидатах
+    assert_eq!(doc.entities_near(p(-100.0, -100.0), 1.0).len(), 2);
+    doc.refresh_index();
+    assert!(doc.entities_near(p(-100.0, -100.0), 1.0).is_empty());
+    assert_eq!(ids(doc.entities_near(p(5.0, 0.5), 1.0)), HashSet::from([a]));
+    assert_eq!(
+        ids(doc.entities_near(p(56.0, 50.0), 1.5)),
+        HashSet::from([c])
+    );
+
+    move_entities(&mut doc, &[c], 100.0, 0.0);
+    doc.refresh_index();
+    assert!(doc.entities_near(p(56.0, 50.0), 1.5).is_empty());
+    assert_eq!(
+        ids(doc.entities_near(p(156.0, 50.0), 1.5)),
+        HashSet::from([c])
+    );
+
+    // обрезка меняет геометрию на месте
+    let b = make_line(&mut doc, p(5.0, -5.0), p(5.0, 5.0), "0");
+    doc.refresh_index();
+    trim(&mut doc, a, &[b], p(9.0, 0.0)).unwrap();
+    doc.refresh_index();
+    assert_eq!(ids(doc.entities_near(p(9.0, 0.0), 0.5)), HashSet::new());
+    assert_eq!(ids(doc.entities_near(p(2.0, 0.0), 0.5)), HashSet::from([a]));
+
+    doc.remove_entity(a);
+    doc.refresh_index();
+    assert!(doc.entities_near(p(2.0, 0.0), 0.5).is_empty());
+    if let Some(e) = doc.entity_mut(b) {
+        e.kind = EntityKind::LineSeg {
+            a: p(-50.0, 0.0),
+            b: p(-40.0, 0.0),
+        };
+    }
+    doc.refresh_index();
+    assert_eq!(
+        ids(doc.entities_near(p(-45.0, 0.0), 0.5)),
+        HashSet::from([b])
+    );
+}
+
+#[test]
+fn loaded_document_is_reindexed() {
+    let mut doc = Document::new();
+    make_line(&mut doc, p(0.0, 0.0), p(1.0, 0.0), "0");
+    make_line(&mut doc, p(100.0, 0.0), p(101.0, 0.0), "0");
+    let mut back = Document::from_json(&doc.to_json()).unwrap();
+
+    // индекс прочитанного не знает его сущностей: правка не делает его годным
+    let c = make_circle(&mut back, p(200.0, 0.0), 1.0, "0");
+    assert_eq!(back.entities_near(p(0.5, 0.0), 0.1).len(), 3);
+    back.refresh_index();
+    assert_eq!(back.entities_near(p(0.5, 0.0), 0.1).len(), 1);
+    assert_eq!(
+        ids(back.entities_near(p(201.0, 0.0), 0.1)),
+        HashSet::from([c])
+    );
+
+    // копия документа — с согласованным индексом
+    let copy = back.clone();
+    assert_eq!(copy.entities_near(p(100.5, 0.0), 0.1).len(), 1);
+}
+
+#[test]
+fn redefined_block_moves_inserts() {
+    let mut doc = Document::new();
+    doc.add_block(block(EntityKind::Circle {
+        center: p(0.0, 0.0),
+        radius: 1.0,
+    }));
+    let id = make_insert(&mut doc, "B", p(0.0, 0.0), p(1.0, 1.0), 0.0, "0").unwrap();
+    doc.refresh_index();
+    assert!(doc.entities_near(p(20.0, 0.0), 0.5).is_empty());
+
+    doc.add_block(block(EntityKind::Circle {
+        center: p(20.0, 0.0),
+        radius: 1.0,
+    }));
+    doc.refresh_index();
+    assert_eq!(
+        ids(doc.entities_near(p(21.0, 0.0), 0.5)),
+        HashSet::from([id])
+    );
+}
+
+#[test]
+fn redefined_nested_block_moves_outer_inserts() {
+    let mut doc = Document::new();
+    doc.add_block(block(EntityKind::LineSeg {
+        a: p(0.0, 0.0),
+        b: p(1.0, 1.0),
+    }));
+    doc.add_block(BlockDef {
+        name: "A".into(),
+        ..block(EntityKind::Insert {
+            block: "B".into(),
+            pos: p(0.0, 0.0),
+            scale: p(1.0, 1.0),
+            rotation: 0.0,
+            attribs: vec![],
+        })
+    });
+    let id = make_insert(&mut doc, "A", p(0.0, 0.0), p(1.0, 1.0), 0.0, "0").unwrap();
+    doc.refresh_index();
+    assert!(doc.entities_near(p(100.0, 100.0), 0.1).is_empty());
+
+    // B меняется, вставка A ссылается на него только через определение A
+    doc.add_block(block(EntityKind::LineSeg {
+        a: p(100.0, 100.0),
+        b: p(101.0, 101.0),
+    }));
+    doc.refresh_index();
+    assert_eq!(
+        ids(doc.entities_near(p(100.0, 100.0), 0.1)),
+        HashSet::from([id])
+    );
+}
+
+#[test]
+fn queries_match_brute_force() {
+    let mut rng = Lcg(7);
+    let mut doc = Document::new();
+    for i in 0..3000 {
+        let (x, y) = (rng.next() * 1000.0, rng.next() * 1000.0);
+        if i % 3 == 0 {
+            make_circle(&mut doc, p(x, y), rng.next() * 5.0 + 0.1, "0");
+        } else {
+            let (dx, dy) = (rng.next() * 20.0 - 10.0, rng.next() * 20.0 - 10.0);
+            make_line(&mut doc, p(x, y), p(x + dx, y + dy), "0");
+        }
+    }
+    let check = |doc: &Document, rng: &mut Lcg| {
+        for _ in 0..200 {
+            let (x, y, w) = (rng.next() * 1000.0, rng.next() * 1000.0, rng.next() * 40.0);
+            let q = Bbox::new(p(x, y), p(x + w, y + w));
+            let want: HashSet<u64> = doc
+                .entities()
+                .iter()
+                .filter(|e| entity_bounds(doc, e).is_some_and(|b| b.intersects(&q)))
+                .map(|e| e.id)
+                .collect();
+            assert_eq!(ids(doc.entities_in_rect(&q)), want);
+        }
+    };
+    // упаковка целиком
+    doc.refresh_index();
+    check(&doc, &mut rng);
+
+    // поштучные вставки, удаления и сдвиги поверх упакованного дерева
+    let all: Vec<u64> = doc.entities().iter().map(|e| e.id).collect();
+    for &id in all.iter().step_by(7) {
+        doc.remove_entity(id);
+    }
+    for &id in all.iter().skip(1).step_by(11) {
+        move_entities(&mut doc, &[id], rng.next() * 50.0, -rng.next() * 50.0);
+    }
+    for _ in 0..500 {
+        let (x, y) = (rng.next() * 1000.0, rng.next() * 1000.0);
+        make_line(&mut doc, p(x, y), p(x + 3.0, y - 2.0), "0");
+        if rng.next() < 0.02 {
+            doc.refresh_index();
+        }
+    }
+    doc.refresh_index();
+    check(&doc, &mut rng);
+}
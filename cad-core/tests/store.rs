@@ -0,0 +1,86 @@
+mod common;
+use cad_core::*;
+use common::p;
+
+fn line(i: usize) -> Entity {
+    Entity::new(
+        "0",
+        EntityKind::LineSeg {
+            a: p(i as f32, 0.0),
+            b: p(i as f32, 1.0),
+        },
+    )
+}
+
+#[test]
+fn ids_survive_json_roundtrip() {
+    let mut doc = Document::new();
+    let a = make_line(&mut doc, p(0.0, 0.0), p(1.0, 0.0), "0");
+    let b = make_circle(&mut doc, p(5.0, 5.0), 1.0, "0");
+    let c = make_line(&mut doc, p(0.0, 1.0), p(1.0, 1.0), "0");
+    doc.remove_entity(b);
+
+    let mut back = Document::from_json(&doc.to_json()).unwrap();
+    assert_eq!(back, doc);
+    // новый id не совпадает ни с одним прочитанным
+    let d = make_line(&mut back, p(2.0, 2.0), p(3.0, 3.0), "0");
+    assert!(d > c && d != a);
+    assert_eq!(back.entities().len(), 3);
+    assert_eq!(back.entity(c).unwrap().id, c);
+}
+
+#[test]
+fn legacy_duplicate_ids_are_renumbered() {
+    // старые файлы: счётчик начинался заново, id повторялись
+    let mut doc = Document::new();
+    make_line(&mut doc, p(0.0, 0.0), p(1.0, 0.0), "0");
+    make_line(&mut doc, p(0.0, 1.0), p(1.0, 1.0), "0");
+    let json = doc.to_json().replace("\"id\": 2", "\"id\": 1");
+    assert!(json.matches("\"id\": 1").count() == 2);
+
+    let back = Document::from_json(&json).unwrap();
+    let ids: Vec<u64> = back.entities().ids().collect();
+    assert_eq!(ids, vec![1, 2]);
+    assert_eq!(
+        back.entities().iter().nth(1).unwrap().kind,
+        doc.entities().iter().nth(1).unwrap().kind
+    );
+    assert_eq!(back.entities().next_id(), 3);
+}
+
+#[test]
+fn removal_keeps_draw_order() {
+    let mut store = EntityStore::new();
+    let ids: Vec<u64> = (0..100).map(|i| store.push(line(i))).collect();
+    // удаляем больше половины — срабатывает уплотнение
+    for &id in ids.iter().filter(|&&id| id % 3 != 0) {
+        assert!(store.remove(id).is_some());
+    }
+    assert!(store.remove(ids[1]).is_none());
+    let left: Vec<u64> = store.ids().collect();
+    let want: Vec<u64> = ids.iter().copied().filter(|id| id % 3 == 0).collect();
+    assert_eq!(left, want);
+    assert_eq!(store.len(), want.len());
+    assert_eq!(store.iter().nth(1).unwrap().id, want[1]);
+    assert_eq!(store.last().unwrap().id, *want.last().unwrap());
+
+    assert_eq!(store.get(want[0]).unwrap().id, want[0]);
+    assert!(store.get(ids[1]).is_none());
+
+    // новые идут в конец, удалённые id не переиспользуются
+    let n = store.push(line(0));
+    assert!(n > ids[99]);
+    assert_eq!(store.last().unwrap().id, n);
+}
+
+#[test]
+fn insert_keeps_free_ids() {
+    let mut store = EntityStore::new();
+    let mut e = line(0);
+    e.id = 40;
+    assert_eq!(store.insert(e.clone()), 40);
+    // занятый id заменяется новым
+    assert_eq!(store.insert(e), 41);
+    assert_eq!(store.push(line(1)), 42);
+    assert_eq!(store.ids().collect::<Vec<_>>(), vec![40, 41, 42]);
+}
@@ -0,0 +1,192 @@
+mod common;
+use cad_core::*;
+use common::roundtrip;
+
+fn dashed() -> LinetypeDef {
+    LinetypeDef {
+        name: "DASHDOT".into(),
+        description: "__ . __ .".into(),
+        pattern: vec![10.0, -2.5, 0.0, -2.5],
+    }
+}
+
+/// Слои «Оси» (красный, штрихпунктир, 0.18) и выключенный «Скрытый»; сущности с разными
+/// способами задания свойств.
+fn styled_doc() -> Document {
+    let mut doc = Document::new();
+    doc.linetypes.push(dashed());
+    doc.layers.push(Layer {
+        color: Color::Aci(1),
+        linetype: "DASHDOT".into(),
+        lineweight: LineWeight::Width(18),
+        ..Layer::new("AXES")
+    });
+    doc.layers.push(Layer {
+        visible: false,
+        ..Layer::new("HIDDEN")
+    });
+
+    let seg = |y: f32| EntityKind::LineSeg {
+        a: Pt2::new(0.0, y),
+        b: Pt2::new(100.0, y),
+    };
+    doc.add_entity(Entity::new("AXES", seg(0.0)));
+    doc.add_entity(Entity {
+        color: Color::Rgb([12, 200, 34]),
+        linetype: LINETYPE_CONTINUOUS.into(),
+        lineweight: LineWeight::Width(50),
+        ..Entity::new("AXES", seg(10.0))
+    });
+    doc.add_entity(Entity {
+        color: Color::Aci(30),
+        lineweight: LineWeight::Default,
+        ..Entity::new("0", seg(20.0))
+    });
+    doc.add_entity(Entity {
+        color: Color::ByBlock,
+        linetype: LINETYPE_BYBLOCK.into(),
+        lineweight: LineWeight::ByBlock,
+        ..Entity::new("0", seg(30.0))
+    });
+    doc.add_entity(Entity::new("HIDDEN", seg(40.0)));
+    doc
+}
+
+#[test]
+fn layer_table_and_entity_styles_survive_dxf() {
+    let doc = styled_doc();
+    let back = roundtrip(&doc, "styles");
+
+    let lt = back.linetype("dashdot").expect("linetype");
+    assert_eq!(lt.pattern, dashed().pattern);
+    assert_eq!(lt.description, dashed().description);
+
+    for name in ["0", "AXES", "HIDDEN"] {
+        let (a, b) = (doc.layer(name).unwrap(), back.layer(name).unwrap());
+        assert_eq!(a.color, b.color, "{name}");
+        assert!(a.linetype.eq_ignore_ascii_case(&b.linetype), "{name}");
+        assert_eq!(a.lineweight, b.lineweight, "{name}");
+        assert_eq!(a.visible, b.visible, "{name}");
+    }
+
+    assert_eq!(back.entities().len(), doc.entities().len());
+    for (a, b) in doc.entities().iter().zip(back.entities()) {
+        assert_eq!(a.layer, b.layer);
+        assert_eq!(a.color, b.color);
+        assert!(
+            a.linetype.eq_ignore_ascii_case(&b.linetype),
+            "{a:?} -> {b:?}"
+        );
+        assert_eq!(a.lineweight, b.lineweight);
+    }
+}
+
+#[test]
+fn by_layer_and_by_block_resolve() {
+    let mut doc = styled_doc();
+
+    let st = doc.entity_style(doc.entities().first().unwrap());
+    assert_eq!(st.color, Color::Aci(1));
+    assert_eq!(st.pattern, &dashed().pattern[..]);
+    assert!((st.weight_mm - 0.18).abs() < 1e-6);
+
+    let st = doc.entity_style(doc.entities().iter().nth(1).unwrap());
+    assert_eq!(st.color, Color::Rgb([12, 200, 34]));
+    assert!(st.pattern.is_empty());
+    assert!((st.weight_mm - 0.5).abs() < 1e-6);
+
+    // ПоБлоку вне вставки — белый, сплошной, вес по умолчанию
+    let st = doc.entity_style(doc.entities().iter().nth(3).unwrap());
+    assert_eq!(st.color, Color::WHITE);
+    assert!(st.pattern.is_empty());
+    assert_eq!(st.weight_mm, LineWeight::DEFAULT_MM);
+
+    // ... а во вставке — свойства вставки; слой "0" берётся у вставки
+    let mut part = doc.entities().iter().nth(3).unwrap().clone();
+    part.id = 0;
+    doc.add_block(BlockDef {
+        name: "B".into(),
+        base: Pt2::new(0.0, 0.0),
+        entities: vec![part],
+        attdefs: vec![],
+    });
+    let id = make_insert(
+        &mut doc,
+        "B",
+        Pt2::new(0.0, 0.0),
+        Pt2::new(1.0, 1.0),
+        0.0,
+        "AXES",
+    )
+    .unwrap();
+    let ins = doc.entity_mut(id).unwrap();
+    ins.color = Color::Aci(5);
+    ins.lineweight = LineWeight::Width(35);
+    let ins = ins.clone();
+
+    let sub = &doc.insert_geometry(&ins)[0];
+    assert_eq!(sub.layer, "AXES");
+    let st = doc.entity_style(sub);
+    assert_eq!(st.color, Color::Aci(5));
+    // тип линии вставки — ПоСлою, слой вставки штрихпунктирный
+    assert_eq!(st.pattern, &dashed().pattern[..]);
+    assert!((st.weight_mm - 0.35).abs() < 1e-6);
+}
+
+#[test]
+fn aci_palette() {
+    assert_eq!(aci_rgb(1), [255, 0, 0]);
+    assert_eq!(aci_rgb(5), [0, 0, 255]);
+    assert_eq!(aci_rgb(10), [255, 0, 0]);
+    assert_eq!(aci_rgb(11), [255, 127, 127]);
+    assert_eq!(aci_rgb(30), [255, 127, 0]);
+    assert_eq!(aci_rgb(90), [0, 255, 0]);
+    assert_eq!(aci_rgb(170), [0, 0, 255]);
+    assert_eq!(aci_rgb(250), [51, 51, 51]);
+    for i in [1u8, 30, 42, 133, 251] {
+        assert_eq!(aci_rgb(nearest_aci(aci_rgb(i))), aci_rgb(i), "{i}");
+    }
+}
+
+#[test]
+fn dashes_follow_pattern_across_vertices() {
+    // L-образная ломаная длиной 20, шаблон 3/−1
+    let pts = [
+        Pt2::new(0.0, 0.0),
+        Pt2::new(10.0, 0.0),
+        Pt2::new(10.0, 10.0),
+    ];
+    let dashes = dash_polyline(&pts, &[3.0, -1.0]);
+    assert_eq!(dashes.len(), 5);
+    let len = |d: &Vec<Pt2>| -> f32 {
+        d.windows(2)
+            .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+            .sum()
+    };
+    for d in &dashes {
+        assert!((len(d) - 3.0).abs() < 1e-4, "{d:?}");
+    }
+    // третий штрих огибает угол (8..11)
+    assert_eq!(dashes[2].len(), 3);
+    assert_eq!(dashes[2][1], Pt2::new(10.0, 0.0));
+
+    // точки шаблона — вырожденные штрихи
+    let dots = dash_polyline(&pts[..2], &[0.0, -2.0]);
+    assert_eq!(dots.len(), 5);
+    assert!(dots.iter().all(|d| d.len() == 2 && d[0] == d[1]));
+
+    assert_eq!(dash_polyline(&pts, &[]), vec![pts.to_vec()]);
+}
+
+#[test]
+fn svg_uses_resolved_styles_and_skips_hidden_layers() {
+    let doc = styled_doc();
+    let svg = doc.export_svg(100.0, 100.0);
+    assert!(svg.contains("stroke='#ff0000'"), "{svg}");
+    assert!(svg.contains("stroke-dasharray='10 2.5 0 2.5'"));
+    assert!(svg.contains("stroke='#0cc822'"));
+    assert!(!svg.contains("y1='40.000'"), "hidden layer exported");
+    // ACI 7 и вес по умолчанию — без вложенной группы
+    assert!(svg.contains("<line x1='0.000' y1='30.000'"));
+    assert!(!svg.contains("<g stroke='black'>"));
+}
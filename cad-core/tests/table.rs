@@ -0,0 +1,283 @@
+use cad_core::dxf_io::{export_dxf, export_dxf_with, import_dxf, DxfOptions};
+use cad_core::*;
+
+fn row(cells: &[&str]) -> Vec<String> {
+    cells.iter().map(|s| s.to_string()).collect()
+}
+
+/// Спецификация: шапка и `n` позиций.
+fn spec(n: usize) -> Table {
+    let mut rows = vec![row(&["Поз.", "Наименование", "Кол."])];
+    for i in 1..=n {
+        rows.push(vec![i.to_string(), format!("Стержень {i}"), "4".into()]);
+    }
+    Table {
+        rows,
+        align: vec![CellAlign::Center, CellAlign::Left, CellAlign::Right],
+        header_rows: 1,
+        ..Default::default()
+    }
+}
+
+fn texts(kinds: &[EntityKind]) -> Vec<(Pt2, &str)> {
+    kinds
+        .iter()
+        .filter_map(|k| match k {
+            EntityKind::Text { pos, content, .. } => Some((*pos, content.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn lines(kinds: &[EntityKind]) -> Vec<(Pt2, Pt2)> {
+    kinds
+        .iter()
+        .filter_map(|k| match k {
+            EntityKind::LineSeg { a, b } => Some((*a, *b)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn auto_widths_and_alignment() {
+    let t = spec(2);
+    let lay = t.layout();
+    // по самой длинной строке: 12 знаков × 2,5 × 0,8 + поля 2 × 2,5
+    assert_eq!(lay.widths[1], 12.0 * 2.5 * 0.8 + 5.0);
+    assert_eq!(lay.heights, [8.0, 8.0, 8.0]);
+    assert_eq!(lay.cells.len(), 9);
+    // шапка по центру, тело — по столбцам
+    assert!(lay.cells[..3].iter().all(|c| c.align == CellAlign::Center));
+    assert_eq!(lay.cells[5].align, CellAlign::Right);
+
+    let geom = table_geometry(&t, Pt2::new(10.0, 100.0));
+    // 4 горизонтали и 4 вертикали
+    assert_eq!(lines(&geom).len(), 8);
+    let tx = texts(&geom);
+    let find = |s: &str| tx.iter().find(|(_, c)| *c == s).unwrap().0;
+    let (x0, x1) = (10.0 + lay.widths[0], 10.0 + lay.widths[0] + lay.widths[1]);
+    assert_eq!(find("Стержень 1").x, x0 + 2.5);
+    let head = find("Наименование");
+    assert!((head.x - (x0 + x1 - 12.0 * 2.0) / 2.0).abs() < 1e-3);
+    // правый край текста — за полем от границы
+    let four = tx.iter().rev().find(|(_, c)| *c == "4").unwrap().0;
+    assert!((four.x + 2.0 - (x1 + lay.widths[2] - 2.5)).abs() < 1e-3);
+    // текст по вертикали посередине строки
+    assert!((find("Поз.").y - (100.0 - 4.0 - 1.25)).abs() < 1e-3);
+}
+
+#[test]
+fn fixed_width_wraps_text() {
+    let t = Table {
+        rows: vec![row(&["Арматура класса А500С по ГОСТ 34028"])],
+        // 20 знаков в строке
+        columns: vec![20.0 * 2.5 * 0.8 + 5.0],
+        ..Default::default()
+    };
+    let lay = t.layout();
+    assert_eq!(
+        lay.cells[0].lines,
+        ["Арматура класса", "А500С по ГОСТ 34028"]
+    );
+    // две строки: поля по высоте текста и шаг 1,6 h
+    assert!((lay.heights[0] - (2.5 + 1.6 * 2.5 + 2.5)).abs() < 1e-4);
+    assert_eq!(lay.widths[0], 45.0);
+
+    // принудительный перенос и слишком длинное слово
+    let t = Table {
+        rows: vec![row(&["а\nб", "ААААААААААААААААААААААААА"])],
+        columns: vec![0.0, 20.0 * 2.5 * 0.8 + 5.0],
+        ..Default::default()
+    };
+    let lay = t.layout();
+    assert_eq!(lay.cells[0].lines, ["а", "б"]);
+    assert_eq!(
+        lay.cells[1]
+            .lines
+            .iter()
+            .map(|l| l.chars().count())
+            .collect::<Vec<_>>(),
+        [20, 5]
+    );
+}
+
+#[test]
+fn merged_cells() {
+    let mut t = spec(3);
+    // «Наименование» над двумя столбцами, позиция 1 на две строки
+    t.rows[0][2].clear();
+    t.merges = vec![
+        CellMerge {
+            row: 0,
+            col: 1,
+            rows: 1,
+            cols: 2,
+        },
+        CellMerge {
+            row: 1,
+            col: 0,
+            rows: 2,
+            cols: 1,
+        },
+        // пересекает первое — отброшено
+        CellMerge {
+            row: 0,
+            col: 2,
+            rows: 2,
+            cols: 1,
+        },
+    ];
+    let lay = t.layout();
+    assert_eq!(lay.cells.len(), 12 - 2);
+    let head = &lay.cells[1];
+    assert_eq!((head.row, head.col, head.rows, head.cols), (0, 1, 1, 2));
+
+    let geom = table_geometry(&t, Pt2::new(0.0, 0.0));
+    let segs = lines(&geom);
+    let x2 = lay.widths[0] + lay.widths[1];
+    // граница между столбцами 1 и 2 не идёт через шапку
+    assert!(segs
+        .iter()
+        .any(|(a, b)| a.x == x2 && b.x == x2 && a.y == -8.0 && b.y == -32.0));
+    // граница между строками 1 и 2 не идёт через объединённую позицию
+    assert!(segs
+        .iter()
+        .any(|(a, b)| a.y == -16.0 && b.y == -16.0 && a.x == lay.widths[0]));
+    // текст объединения — по центру охвата
+    let name = texts(&geom)
+        .into_iter()
+        .find(|(_, c)| *c == "Наименование")
+        .unwrap()
+        .0;
+    assert!((name.x + 12.0 - (lay.widths[0] + lay.width()) / 2.0).abs() < 1e-3);
+}
+
+#[test]
+fn split_repeats_header() {
+    let t = spec(10);
+    // шапка и 4 строки по 8 мм
+    let parts = t.split(40.0);
+    assert_eq!(parts.len(), 3);
+    assert_eq!(
+        parts.iter().map(|p| p.rows.len()).collect::<Vec<_>>(),
+        [5, 5, 3]
+    );
+    for p in &parts {
+        assert_eq!(p.rows[0], t.rows[0]);
+        assert_eq!(p.layout().widths, t.layout().widths);
+    }
+    assert_eq!(parts[1].rows[1][0], "5");
+    // без ограничения и при запасе высоты — одна часть
+    assert_eq!(t.split(0.0), std::slice::from_ref(&t));
+    assert_eq!(t.split(1000.0).len(), 1);
+
+    // объединение не разрывается
+    let mut t = spec(4);
+    t.merges = vec![CellMerge {
+        row: 3,
+        col: 0,
+        rows: 2,
+        cols: 1,
+    }];
+    let parts = t.split(32.0);
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[1].rows.len(), 3);
+    assert_eq!(parts[1].merges[0].row, 1);
+}
+
+#[test]
+fn sheet_table_continues_to_the_right() {
+    let sheet = Sheet {
+        id: 1,
+        name: "Спецификация".into(),
+        size_mm: (420.0, 297.0),
+        viewports: vec![],
+        annots: vec![Annot::Table {
+            origin_mm: Pt2::new(30.0, 280.0),
+            table: spec(10),
+            max_height_mm: 40.0,
+        }],
+    };
+    let doc = sheet.to_document(&Document::new(), &Default::default());
+    let tables: Vec<Pt2> = doc
+        .entities()
+        .iter()
+        .filter_map(|e| match &e.kind {
+            EntityKind::Table { pos, .. } => Some(*pos),
+            _ => None,
+        })
+        .collect();
+    let w = spec(10).layout().width();
+    assert_eq!(tables.len(), 3);
+    assert_eq!(tables[1], Pt2::new(30.0 + w + 10.0, 280.0));
+    let labels = doc.entities().iter().filter(|e| match &e.kind {
+        EntityKind::Text { content, .. } => content == "Продолжение таблицы",
+        _ => false,
+    });
+    assert_eq!(labels.count(), 2);
+
+    // старая запись без параметров таблицы
+    let json = r#"{"Table":{"origin_mm":{"x":1.0,"y":2.0},"rows":[["a","b"]]}}"#;
+    let old: Annot = serde_json::from_str(json).unwrap();
+    let Annot::Table { table, .. } = old else {
+        panic!()
+    };
+    assert_eq!(table.rows, [row(&["a", "b"])]);
+    assert_eq!(table.text_height, 2.5);
+}
+
+#[test]
+fn dxf_table_roundtrip_and_exploded() {
+    let mut t = spec(2);
+    t.rows[2][1] = "Хомут\nгнутый".into();
+    t.merges = vec![CellMerge {
+        row: 1,
+        col: 1,
+        rows: 1,
+        cols: 2,
+    }];
+    let mut doc = Document::new();
+    doc.add_entity(Entity::new(
+        "0",
+        EntityKind::Table {
+            pos: Pt2::new(100.0, 50.0),
+            table: t.clone(),
+        },
+    ));
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("cad_core_{}_table.dxf", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    export_dxf(&doc, path).unwrap();
+    let text = std::fs::read_to_string(path).unwrap();
+    assert!(text.contains("ACAD_TABLE") && text.contains("AcDbTable"));
+    let back = import_dxf(path).unwrap();
+    // блок *T с сеткой — служебный
+    assert!(back.blocks().is_empty());
+    assert_eq!(back.entities().len(), 1);
+    let EntityKind::Table { pos, table } = &back.entities().first().unwrap().kind else {
+        panic!("{:?}", back.entities().first().unwrap().kind)
+    };
+    assert_eq!(*pos, Pt2::new(100.0, 50.0));
+    assert_eq!(table.rows[0], t.rows[0]);
+    assert_eq!(table.rows[2][1], "Хомут\nгнутый");
+    // закрытая объединением ячейка теряет текст
+    assert_eq!(table.rows[1][2], "");
+    assert_eq!(table.merges, t.merges);
+    assert_eq!((table.header_rows, &table.align), (1, &t.align));
+    assert_eq!(table.layout(), t.layout());
+
+    // развёрнутая — только отрезки и тексты
+    let opts = DxfOptions {
+        explode_tables: true,
+        ..Default::default()
+    };
+    export_dxf_with(&doc, path, &opts).unwrap();
+    let back = import_dxf(path).unwrap();
+    let kinds: Vec<EntityKind> = back.entities().iter().map(|e| e.kind.clone()).collect();
+    let geom = table_geometry(&t, Pt2::new(100.0, 50.0));
+    assert_eq!(lines(&kinds).len(), lines(&geom).len());
+    assert_eq!(texts(&kinds).len(), texts(&geom).len());
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,188 @@
+mod common;
+use cad_core::*;
+use common::{close, ends, kind, p};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+#[test]
+fn rotate_scale_mirror_curves() {
+    let mut doc = Document::new();
+    let arc = make_arc(&mut doc, p(0.0, 0.0), 2.0, 0.0, FRAC_PI_2, "0");
+    let nurbs =
+        nurbs_from_polyline(&mut doc, &[p(0.0, 0.0), p(1.0, 2.0), p(3.0, 0.0)], "0").unwrap();
+    let ctrl = |doc: &Document| match kind(doc, nurbs) {
+        EntityKind::NurbsCurve2D { ctrl_pts, .. } => ctrl_pts.clone(),
+        k => panic!("{k:?}"),
+    };
+    let before = ctrl(&doc);
+
+    rotate_entities(&mut doc, &[arc, nurbs], p(0.0, 0.0), FRAC_PI_2);
+    let (a, b) = ends(kind(&doc, arc));
+    assert!(
+        close(a, p(0.0, 2.0)) && close(b, p(-2.0, 0.0)),
+        "{a:?} {b:?}"
+    );
+    for (q, r) in ctrl(&doc).iter().zip(&before) {
+        assert!(close(*q, p(-r.y, r.x)));
+    }
+
+    // зеркало по оси Y: дуга остаётся дугой, концы меняются местами
+    mirror_entities(&mut doc, &[arc], p(0.0, 0.0), p(0.0, 1.0), false);
+    assert!(matches!(kind(&doc, arc), EntityKind::Arc { .. }));
+    let (a, b) = ends(kind(&doc, arc));
+    assert!(
+        close(a, p(2.0, 0.0)) && close(b, p(0.0, 2.0)),
+        "{a:?} {b:?}"
+    );
+
+    // растяжение по X: эллиптическая дуга через образы концов
+    scale_entities(&mut doc, &[arc], p(0.0, 0.0), 3.0, 1.0);
+    let EntityKind::Ellipse { major, ratio, .. } = kind(&doc, arc) else {
+        panic!("{:?}", kind(&doc, arc));
+    };
+    assert!((major.x.hypot(major.y) - 6.0).abs() < 1e-3 && (ratio - 1.0 / 3.0).abs() < 1e-4);
+    let (a, b) = ends(kind(&doc, arc));
+    assert!(
+        close(a, p(6.0, 0.0)) && close(b, p(0.0, 2.0)),
+        "{a:?} {b:?}"
+    );
+
+    // равномерный масштаб окружности и отрезка
+    let c = make_circle(&mut doc, p(1.0, 1.0), 1.0, "0");
+    scale_entities(&mut doc, &[c], p(0.0, 0.0), 2.0, 2.0);
+    assert_eq!(
+        *kind(&doc, c),
+        EntityKind::Circle {
+            center: p(2.0, 2.0),
+            radius: 2.0
+        }
+    );
+}
+
+#[test]
+fn mirror_matrix() {
+    let m = Affine2::mirror(p(0.0, 1.0), p(1.0, 2.0));
+    assert!(m.det() < 0.0);
+    // точки прямой неподвижны, перпендикуляр переворачивается
+    assert!(close(m.apply(p(3.0, 4.0)), p(3.0, 4.0)));
+    assert!(close(m.apply(p(0.0, 0.0)), p(-1.0, 1.0)));
+    assert_eq!(Affine2::mirror(p(1.0, 1.0), p(1.0, 1.0)), Affine2::IDENTITY);
+}
+
+#[test]
+fn transforms_keep_dimensions_linked() {
+    let mut doc = Document::new();
+    let a = make_line(&mut doc, p(0.0, 0.0), p(10.0, 0.0), "0");
+    let pts = vec![
+        DimPoint::on(&doc, a, DimSnap::Start).unwrap(),
+        DimPoint::on(&doc, a, DimSnap::End).unwrap(),
+    ];
+    let d = make_dimension(&mut doc, DimKind::Aligned, pts, p(5.0, 3.0), "0").unwrap();
+
+    // повёрнута только линия — точки размера едут за ней
+    rotate_entities(&mut doc, &[a], p(0.0, 0.0), FRAC_PI_2);
+    let EntityKind::Dimension { pts, .. } = kind(&doc, d) else {
+        panic!()
+    };
+    assert!(close(pts[1].pos, p(0.0, 10.0)));
+
+    // зеркальная копия линии с размером: размер копии привязан к копии линии
+    let copies = mirror_entities(&mut doc, &[a, d], p(0.0, 0.0), p(1.0, 0.0), true);
+    assert_eq!(copies.len(), 2);
+    let EntityKind::Dimension { pts, .. } = kind(&doc, copies[1]) else {
+        panic!()
+    };
+    assert!(pts.iter().all(|q| q.link.unwrap().entity == copies[0]));
+    assert!(close(pts[1].pos, p(0.0, -10.0)));
+    move_entities(&mut doc, &[copies[0]], 5.0, 0.0);
+    let EntityKind::Dimension { pts, .. } = kind(&doc, copies[1]) else {
+        panic!()
+    };
+    assert!(close(pts[1].pos, p(5.0, -10.0)));
+
+    // копия без своих объектов теряет привязки
+    let lone = copy_entities(&mut doc, &[d], &Affine2::translation(100.0, 0.0));
+    let EntityKind::Dimension { pts, .. } = kind(&doc, lone[0]) else {
+        panic!()
+    };
+    assert!(pts.iter().all(|q| q.link.is_none()));
+}
+
+#[test]
+fn rectangular_and_polar_arrays() {
+    let mut doc = Document::new();
+    let c = make_circle(&mut doc, p(0.0, 0.0), 0.5, "0");
+    let ids = array_rect(&mut doc, &[c], 3, 4, p(10.0, 5.0), 0.0);
+    assert_eq!(ids.len(), 11);
+    let centers: Vec<Pt2> = ids
+        .iter()
+        .map(|&id| match kind(&doc, id) {
+            EntityKind::Circle { center, .. } => *center,
+            k => panic!("{k:?}"),
+        })
+        .collect();
+    assert!(close(centers[0], p(10.0, 0.0)));
+    assert!(close(*centers.last().unwrap(), p(30.0, 10.0)));
+
+    // повёрнутая сетка
+    let ids = array_rect(&mut doc, &[c], 1, 2, p(10.0, 0.0), FRAC_PI_2);
+    match kind(&doc, ids[0]) {
+        EntityKind::Circle { center, .. } => assert!(close(*center, p(0.0, 10.0))),
+        k => panic!("{k:?}"),
+    }
+
+    // 6 отрезков-«спиц» по полному кругу
+    let spoke = make_line(&mut doc, p(1.0, 0.0), p(2.0, 0.0), "0");
+    let ids = array_polar(&mut doc, &[spoke], p(0.0, 0.0), p(1.0, 0.0), 6, TAU, true);
+    assert_eq!(ids.len(), 5);
+    let (a, b) = ends(kind(&doc, ids[2]));
+    assert!(close(a, p(-1.0, 0.0)) && close(b, p(-2.0, 0.0)));
+
+    // полукруг из 3 без поворота: сдвиг по базовой точке
+    let ids = array_polar(&mut doc, &[spoke], p(0.0, 0.0), p(1.0, 0.0), 3, PI, false);
+    let (a, b) = ends(kind(&doc, ids[0]));
+    assert!(
+        close(a, p(0.0, 1.0)) && close(b, p(1.0, 1.0)),
+        "{a:?} {b:?}"
+    );
+    let (a, _) = ends(kind(&doc, ids[1]));
+    assert!(close(a, p(-1.0, 0.0)));
+}
+
+#[test]
+fn path_array_follows_curve() {
+    let mut doc = Document::new();
+    // стержень поперёк начала полилинии-«уголка» 20 + 20
+    let path = make_polyline(
+        &mut doc,
+        vec![p(0.0, 0.0), p(20.0, 0.0), p(20.0, 20.0)],
+        false,
+        "0",
+    )
+    .unwrap();
+    let bar = make_line(&mut doc, p(0.0, -1.0), p(0.0, 1.0), "0");
+
+    let ids = array_path(&mut doc, &[bar], path, p(0.0, 0.0), 5, false).unwrap();
+    assert_eq!(ids.len(), 4);
+    let (a, b) = ends(kind(&doc, ids[3]));
+    assert!(
+        close(a, p(20.0, 19.0)) && close(b, p(20.0, 21.0)),
+        "{a:?} {b:?}"
+    );
+
+    // с выравниванием стержни на втором участке поворачиваются на 90°
+    let ids = array_path(&mut doc, &[bar], path, p(0.0, 0.0), 5, true).unwrap();
+    let (a, b) = ends(kind(&doc, ids[0]));
+    assert!(close(a, p(10.0, -1.0)) && close(b, p(10.0, 1.0)));
+    let (a, b) = ends(kind(&doc, ids[2]));
+    assert!(
+        close(a, p(21.0, 10.0)) && close(b, p(19.0, 10.0)),
+        "{a:?} {b:?}"
+    );
+
+    // по окружности без повтора на стыке
+    let ring = make_circle(&mut doc, p(0.0, 0.0), 10.0, "0");
+    let dot = make_circle(&mut doc, p(10.0, 0.0), 0.1, "0");
+    let ids = array_path(&mut doc, &[dot], ring, p(10.0, 0.0), 4, false).unwrap();
+    assert_eq!(ids.len(), 3);
+    assert!(array_path(&mut doc, &[dot], 9999, p(0.0, 0.0), 4, false).is_err());
+}
@@ -1,5 +1,8 @@
 use super::AppState;
-use cad_core::{Entity, EntityKind, Pt2};
+use cad_core::{
+    dash_polyline, fill_triangles, flatten_polyline, pattern_segments, Color, Entity, EntityKind,
+    EntityStyle, HatchFill, LineWeight, Pt2,
+};
 use egui::{Align2, Color32, FontId, Ui};
 
 impl AppState {
@@ -54,63 +57,12 @@ impl AppState {
     }
 
     pub fn draw_entities(&self, ui: &mut Ui, rect: egui::Rect) {
-        for e in &self.doc.entities {
-            let selected = self.selection.ids.contains(&e.id);
-            let (stroke, text_color) = self.stroke_and_text_color(ui, e, selected);
-
-            match &e.kind {
-                EntityKind::LineSeg { a, b } => {
-                    ui.painter()
-                        .line_segment([self.to_screen(*a, rect), self.to_screen(*b, rect)], stroke);
-                }
-                EntityKind::Arc {
-                    center,
-                    radius,
-                    start_angle,
-                    end_angle,
-                } => {
-                    let n = 96usize;
-                    let mut pts = Vec::with_capacity(n + 1);
-                    let (sa, ea) = (*start_angle, *end_angle);
-                    for i in 0..=n {
-                        let t = sa + (ea - sa) * (i as f32) / (n as f32);
-                        let x = center.x + radius * t.cos();
-                        let y = center.y + radius * t.sin();
-                        pts.push(self.to_screen(Pt2::new(x, y), rect));
-                    }
-                    ui.painter().add(egui::Shape::line(pts, stroke));
-                }
-                EntityKind::Polyline { pts, .. } => {
-                    if pts.len() >= 2 {
-                        let pts2: Vec<_> = pts.iter().map(|p| self.to_screen(*p, rect)).collect();
-                        ui.painter().add(egui::Shape::line(pts2, stroke));
-                    }
-                }
-                EntityKind::NurbsCurve2D { .. } => {
-                    let poly = cad_core::sample_entity_nurbs(e, 256).unwrap_or_default();
-                    if poly.len() >= 2 {
-                        let pts2: Vec<_> = poly.iter().map(|p| self.to_screen(*p, rect)).collect();
-                        ui.painter().add(egui::Shape::line(pts2, stroke));
-                    }
-                }
-                EntityKind::Text {
-                    pos,
-                    content,
-                    height,
-                } => {
-                    let sp = self.to_screen(*pos, rect);
-                    // экранный текст (масштабируется с зумом для читаемости)
-                    let zoom = self.doc.camera.zoom.max(0.01);
-                    let font_size = (*height * zoom).max(8.0);
-                    ui.painter().text(
-                        sp,
-                        Align2::CENTER_CENTER,
-                        content,
-                        FontId::proportional(font_size),
-                        text_color,
-                    );
-                }
+        for e in self.doc.entities() {
+            if !self.doc.is_layer_visible(&e.layer) {
+                continue;
             }
+            let selected = self.selection.ids.contains(&e.id);
+            self.draw_entity(ui, rect, e, selected);
         }
 
         // osnap marker
@@ -137,6 +89,145 @@ impl AppState {
         }
     }
 
+    fn draw_entity(&self, ui: &mut Ui, rect: egui::Rect, e: &Entity, selected: bool) {
+        if e.kind.is_block_ref() {
+            for sub in self.doc.insert_geometry(e) {
+                if self.doc.is_layer_visible(&sub.layer) {
+                    self.draw_entity(ui, rect, &sub, selected);
+                }
+            }
+            return;
+        }
+
+        let style = self.doc.entity_style(e);
+        let (stroke, text_color) = self.stroke_and_text_color(ui, &style, selected);
+        let pattern = style.pattern;
+
+        match &e.kind {
+            EntityKind::LineSeg { a, b } => {
+                self.stroke_path(ui, rect, &[*a, *b], stroke, pattern);
+            }
+            EntityKind::Arc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                let n = 96usize;
+                let mut pts = Vec::with_capacity(n + 1);
+                let (sa, ea) = (*start_angle, *end_angle);
+                for i in 0..=n {
+                    let t = sa + (ea - sa) * (i as f32) / (n as f32);
+                    let x = center.x + radius * t.cos();
+                    let y = center.y + radius * t.sin();
+                    pts.push(Pt2::new(x, y));
+                }
+                self.stroke_path(ui, rect, &pts, stroke, pattern);
+            }
+            EntityKind::Circle { center, radius } if pattern.is_empty() => {
+                let z = self.doc.camera.zoom.max(0.01);
+                ui.painter()
+                    .circle_stroke(self.to_screen(*center, rect), radius * z, stroke);
+            }
+            EntityKind::Circle { .. } | EntityKind::Ellipse { .. } => {
+                self.stroke_path(ui, rect, &e.kind.sample(96), stroke, pattern);
+            }
+            EntityKind::Polyline {
+                pts,
+                closed,
+                bulges,
+            } => {
+                if pts.len() >= 2 {
+                    let flat = flatten_polyline(pts, bulges, *closed, 0.05);
+                    self.stroke_path(ui, rect, &flat, stroke, pattern);
+                }
+            }
+            EntityKind::NurbsCurve2D { .. } => {
+                let poly = cad_core::sample_entity_nurbs(e, 256).unwrap_or_default();
+                if poly.len() >= 2 {
+                    self.stroke_path(ui, rect, &poly, stroke, pattern);
+                }
+            }
+            EntityKind::Text {
+                pos,
+                content,
+                height,
+            } => {
+                let sp = self.to_screen(*pos, rect);
+                // экранный текст (масштабируется с зумом для читаемости)
+                let zoom = self.doc.camera.zoom.max(0.01);
+                let font_size = (*height * zoom).max(8.0);
+                ui.painter().text(
+                    sp,
+                    Align2::CENTER_CENTER,
+                    content,
+                    FontId::proportional(font_size),
+                    text_color,
+                );
+            }
+            EntityKind::Hatch { loops, fill } => {
+                let polygons: Vec<Vec<Pt2>> = loops.iter().map(|l| l.polygon()).collect();
+                match fill {
+                    HatchFill::Solid => {
+                        let mut mesh = egui::Mesh::default();
+                        for tri in fill_triangles(&polygons) {
+                            let i = mesh.vertices.len() as u32;
+                            for p in tri {
+                                mesh.colored_vertex(self.to_screen(p, rect), stroke.color);
+                            }
+                            mesh.add_triangle(i, i + 1, i + 2);
+                        }
+                        ui.painter().add(egui::Shape::mesh(mesh));
+                    }
+                    HatchFill::Pattern { lines, .. } => {
+                        // линии образца — тонкие, вес линии на них не влияет
+                        let thin = egui::Stroke {
+                            width: 1.0,
+                            color: stroke.color,
+                        };
+                        for [a, b] in pattern_segments(&polygons, lines) {
+                            let (a, b) = (self.to_screen(a, rect), self.to_screen(b, rect));
+                            if a == b {
+                                ui.painter().circle_filled(a, 0.75, thin.color);
+                            } else {
+                                ui.painter().line_segment([a, b], thin);
+                            }
+                        }
+                    }
+                }
+            }
+            EntityKind::Insert { .. } | EntityKind::Dimension { .. } | EntityKind::Table { .. } => {
+            }
+        }
+    }
+
+    /// Ломаная в мировых координатах с учётом шаблона типа линии.
+    fn stroke_path(
+        &self,
+        ui: &Ui,
+        rect: egui::Rect,
+        pts: &[Pt2],
+        stroke: egui::Stroke,
+        pattern: &[f32],
+    ) {
+        for piece in dash_polyline(pts, pattern) {
+            let sp: Vec<_> = piece.iter().map(|p| self.to_screen(*p, rect)).collect();
+            match sp.as_slice() {
+                // точка шаблона
+                [a, b] if a == b => {
+                    ui.painter()
+                        .circle_filled(*a, stroke.width * 0.5, stroke.color);
+                }
+                [a, b] => {
+                    ui.painter().line_segment([*a, *b], stroke);
+                }
+                _ => {
+                    ui.painter().add(egui::Shape::line(sp, stroke));
+                }
+            }
+        }
+    }
+
     pub fn draw_previews(&self, ui: &mut Ui, rect: egui::Rect, p: Pt2) {
         match self.tool {
             super::Tool::Line => {
@@ -170,18 +261,27 @@ impl AppState {
 
     // ---------- helpers ----------
 
-    /// Подбор цвета штриха и текста с учётом слоя и выделения
+    /// Подбор цвета и толщины штриха с учётом свойств сущности и выделения.
+    /// Вес линии по умолчанию (0.25 мм) соответствует `style.stroke_px`.
     fn stroke_and_text_color(
         &self,
         ui: &Ui,
-        e: &Entity,
+        style: &EntityStyle,
         selected: bool,
     ) -> (egui::Stroke, Color32) {
-        let base = self.layer_color(&e.layer, ui);
+        let base = match style.color {
+            // ACI 7 — белый на тёмном фоне, чёрный на светлом
+            Color::Aci(7) => ui.visuals().strong_text_color(),
+            c => {
+                let [r, g, b] = c.rgb().unwrap_or([255, 255, 255]);
+                Color32::from_rgb(r, g, b)
+            }
+        };
+        let width = (self.doc.style.stroke_px * style.weight_mm / LineWeight::DEFAULT_MM).max(1.0);
         let (line_w, col) = if selected {
-            (self.doc.style.stroke_px + 0.8, Self::tint(base, 0.35, ui)) // подсветим
+            (width + 0.8, Self::tint(base, 0.35, ui)) // подсветим
         } else {
-            (self.doc.style.stroke_px, base)
+            (width, base)
         };
         (
             egui::Stroke {
@@ -192,30 +292,6 @@ impl AppState {
         )
     }
 
-    /// Детерминированный цвет по имени слоя (без зависимостей)
-    fn layer_color(&self, layer: &str, ui: &Ui) -> Color32 {
-        if layer == "0" || layer.is_empty() {
-            return ui.visuals().strong_text_color();
-        }
-        let mut h: u32 = 2166136261; // FNV-1a
-        for b in layer.as_bytes() {
-            h ^= *b as u32;
-            h = h.wrapping_mul(16777619);
-        }
-        // сделаем приятные пастельные цвета
-        let r = ((h >> 16) & 0xFF) as u8;
-        let g = ((h >> 8) & 0xFF) as u8;
-        let b = (h & 0xFF) as u8;
-        let (r, g, b) = (Self::pastel(r), Self::pastel(g), Self::pastel(b));
-        Color32::from_rgb(r, g, b)
-    }
-
-    #[inline]
-    fn pastel(x: u8) -> u8 {
-        // компрессия в пастельный диапазон: 96..224
-        96 + (x as u16 * 128 / 255) as u8
-    }
-
     /// Лёгкая подсветка цвета (в сторону цвета текста UI)
     fn tint(c: Color32, k: f32, ui: &Ui) -> Color32 {
         let t = ui.visuals().hyperlink_color; // яркий читаемый
@@ -0,0 +1,126 @@
+use super::AppState;
+use cad_core::{curve_length, entity_area, entity_distance, entity_extents, Bbox, Pt2};
+use egui::{Align2, FontId, Ui};
+
+/// Результат инструмента Measure: строка для панели и что подсветить на канвасе.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Measurement {
+    pub text: String,
+    /// Отрезок кратчайшего расстояния
+    pub segment: Option<(Pt2, Pt2)>,
+    /// Габаритная рамка измеренного объекта
+    pub bounds: Option<Bbox>,
+}
+
+impl AppState {
+    /// Щелчок инструментом Measure: объект — его длина, площадь и габариты,
+    /// второй объект — расстояние до первого, две точки на пустом месте — расстояние между ними.
+    pub(crate) fn measure_click(&mut self, p: Pt2, rect: egui::Rect) {
+        let Some(id) = self.pick_entity(p, rect, self.osnap.pixel_radius * 1.2) else {
+            self.measure_first = None;
+            self.selection.clear();
+            match self.tmp_pts.pop() {
+                Some(a) => self.measurement = Some(point_distance(a, p)),
+                None => {
+                    self.tmp_pts.push(p);
+                    self.measurement = None;
+                }
+            }
+            return;
+        };
+        self.tmp_pts.clear();
+        match self.measure_first.take() {
+            Some(first) if first != id => {
+                self.selection.add(id);
+                self.measurement = self.distance_between(first, id);
+            }
+            _ => {
+                self.selection.clear();
+                self.selection.add(id);
+                self.measure_first = Some(id);
+                self.measurement = self.describe(id);
+            }
+        }
+    }
+
+    fn describe(&self, id: u64) -> Option<Measurement> {
+        let e = self.doc.entity(id)?;
+        let mut parts = Vec::new();
+        if let Some(props) = entity_area(&e.kind) {
+            let c = props.centroid;
+            parts.push(format!("A = {:.3}", props.area.abs()));
+            parts.push(format!("P = {:.3}", props.perimeter));
+            parts.push(format!("C = ({:.3}, {:.3})", c.x, c.y));
+        } else if let Some(len) = curve_length(&e.kind) {
+            parts.push(format!("L = {len:.3}"));
+        }
+        let bounds = entity_extents(&self.doc, e);
+        if let Some(b) = bounds {
+            parts.push(format!(
+                "{:.3} × {:.3}",
+                b.max.x - b.min.x,
+                b.max.y - b.min.y
+            ));
+        }
+        Some(Measurement {
+            text: parts.join("   "),
+            segment: None,
+            bounds,
+        })
+    }
+
+    fn distance_between(&self, a: u64, b: u64) -> Option<Measurement> {
+        let (ea, eb) = (self.doc.entity(a)?, self.doc.entity(b)?);
+        let c = entity_distance(&self.doc, ea, eb)?;
+        Some(Measurement {
+            text: format!("D = {:.3}", c.distance),
+            segment: Some((c.a, c.b)),
+            bounds: None,
+        })
+    }
+
+    /// Результат измерения поверх чертежа и резиновая линия от первой точки.
+    pub(crate) fn draw_measurement(&self, ui: &mut Ui, rect: egui::Rect, hover: Option<Pt2>) {
+        let color = ui.visuals().warn_fg_color;
+        let stroke = egui::Stroke { width: 1.0, color };
+        let painter = ui.painter();
+        if let (Some(a), Some(p)) = (self.tmp_pts.first().copied(), hover) {
+            painter.line_segment([self.to_screen(a, rect), self.to_screen(p, rect)], stroke);
+        }
+        let Some(m) = &self.measurement else {
+            return;
+        };
+        if let Some(b) = m.bounds {
+            let r =
+                egui::Rect::from_two_pos(self.to_screen(b.min, rect), self.to_screen(b.max, rect));
+            painter.rect_stroke(r, 0.0, stroke, egui::StrokeKind::Middle);
+        }
+        if let Some((a, b)) = m.segment {
+            let (sa, sb) = (self.to_screen(a, rect), self.to_screen(b, rect));
+            painter.line_segment([sa, sb], stroke);
+            for s in [sa, sb] {
+                painter.circle_filled(s, 2.5, color);
+            }
+            painter.text(
+                sa.lerp(sb, 0.5),
+                Align2::LEFT_BOTTOM,
+                &m.text,
+                FontId::proportional(12.0),
+                color,
+            );
+        }
+    }
+}
+
+fn point_distance(a: Pt2, b: Pt2) -> Measurement {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    Measurement {
+        text: format!(
+            "D = {:.3}   ΔX = {dx:.3}   ΔY = {dy:.3}   ∠ {:.2}°",
+            dx.hypot(dy),
+            dy.atan2(dx).to_degrees()
+        ),
+        segment: Some((a, b)),
+        bounds: None,
+    }
+}
@@ -1,20 +1,19 @@
 // cad-render/src/app/mod.rs
 use anyhow::Result;
-#[cfg(feature = "ifc-ffi")]
-use cad_core::ifc::import_ifc;
+#[cfg(feature = "ifc")]
+use cad_core::ifc::{export_ifc, import_ifc};
 use cad_core::*;
-use egui::{Button, Context, Key, PointerButton, Sense, Ui};
+use egui::{Context, Key, PointerButton, Sense, Ui};
 
 mod camera;
 mod draw;
-mod history;
 mod input;
+mod measure;
 mod osnap;
 mod picking;
 mod selection;
 
 use crate::view3d::View3D;
-pub use history::History;
 pub use input::is_pan_drag;
 pub use osnap::{apply_osnap_or_grid, compute_osnap, Osnap, SnapKind};
 pub use selection::{Selection, SelectionRect};
@@ -25,6 +24,11 @@ pub enum Tool {
     Line,
     Arc,
     Nurbs,
+    Hatch,
+    Dim,
+    Fillet,
+    Chamfer,
+    Measure,
     Pan,
 }
 
@@ -33,6 +37,20 @@ pub struct AppState {
     pub tool: Tool,
 
     pub(crate) tmp_pts: Vec<Pt2>,
+    /// Образец для инструмента Hatch (см. `pattern_names`)
+    pub(crate) hatch_pattern: String,
+    /// Точки инструмента Dim, привязанные к объектам, если щёлкнули рядом с ними
+    pub(crate) dim_pts: Vec<DimPoint>,
+    /// Первая кривая инструментов Fillet/Chamfer
+    pub(crate) corner_first: Option<u64>,
+    /// Радиус скругления и расстояния фаски (вдоль первой и второй кривой)
+    pub(crate) fillet_radius: f32,
+    pub(crate) chamfer_dists: (f32, f32),
+    /// Первый объект инструмента Measure и последний результат
+    pub(crate) measure_first: Option<u64>,
+    pub(crate) measurement: Option<measure::Measurement>,
+    /// Масштаб печати в PDF, 1:N
+    pub(crate) pdf_scale: f32,
 
     pub(crate) selection: Selection,
     pub(crate) drag_prev_world: Option<Pt2>,
@@ -55,6 +73,14 @@ impl Default for AppState {
             doc: Document::new(),
             tool: Tool::Select,
             tmp_pts: Vec::new(),
+            hatch_pattern: "ANSI31".into(),
+            dim_pts: Vec::new(),
+            corner_first: None,
+            fillet_radius: 1.0,
+            chamfer_dists: (1.0, 1.0),
+            measure_first: None,
+            measurement: None,
+            pdf_scale: 100.0,
             selection: Selection::default(),
             drag_prev_world: None,
             select_rect: None,
@@ -70,6 +96,7 @@ impl Default for AppState {
 
 impl AppState {
     pub fn ui(&mut self, ctx: &Context) {
+        self.doc.refresh_index();
         egui::TopBottomPanel::top("top").show(ctx, |ui| self.toolbar(ui));
         egui::CentralPanel::default().show(ctx, |ui| {
             if self.show_3d {
@@ -83,24 +110,130 @@ impl AppState {
         });
     }
 
+    fn undo(&mut self) {
+        if self.history.undo(&mut self.doc).is_some() {
+            self.selection.clear();
+            self.select_rect = None;
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.history.redo(&mut self.doc).is_some() {
+            self.selection.clear();
+            self.select_rect = None;
+        }
+    }
+
+    /// Кнопки отмены/повтора и список шагов: щелчок по шагу откатывает или
+    /// повторяет до него.
+    fn history_ui(&mut self, ui: &mut Ui) {
+        if ui
+            .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+            .clicked()
+        {
+            self.undo();
+        }
+        if ui
+            .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+            .clicked()
+        {
+            self.redo();
+        }
+        ui.menu_button("History", |ui| {
+            let done: Vec<String> = self
+                .history
+                .undo_list()
+                .map(|t| t.label().to_string())
+                .collect();
+            let undone: Vec<String> = self
+                .history
+                .redo_list()
+                .map(|t| t.label().to_string())
+                .collect();
+            if done.is_empty() && undone.is_empty() {
+                ui.label("Nothing to undo");
+            }
+            for (i, label) in done.iter().enumerate() {
+                // последний сделанный шаг — текущее состояние
+                let current = i + 1 == done.len();
+                if ui.selectable_label(current, label).clicked() {
+                    for _ in i + 1..done.len() {
+                        self.undo();
+                    }
+                    ui.close();
+                }
+            }
+            for (i, label) in undone.iter().enumerate() {
+                if ui
+                    .add(egui::Button::new(egui::RichText::new(label).weak()))
+                    .clicked()
+                {
+                    for _ in 0..=i {
+                        self.redo();
+                    }
+                    ui.close();
+                }
+            }
+        });
+    }
+
     fn toolbar(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
             ui.heading("rust-cad");
             ui.separator();
+            self.history_ui(ui);
+            ui.separator();
 
             for (label, t) in [
                 ("Select", Tool::Select),
                 ("Line", Tool::Line),
                 ("Arc", Tool::Arc),
                 ("NURBS", Tool::Nurbs),
+                ("Hatch", Tool::Hatch),
+                ("Dim", Tool::Dim),
+                ("Fillet", Tool::Fillet),
+                ("Chamfer", Tool::Chamfer),
+                ("Measure", Tool::Measure),
                 ("Pan", Tool::Pan),
             ] {
                 if ui.selectable_label(self.tool == t, label).clicked() {
                     self.tool = t;
                     self.tmp_pts.clear();
+                    self.dim_pts.clear();
+                    self.corner_first = None;
+                    self.measure_first = None;
+                    self.measurement = None;
                     self.select_rect = None;
                 }
             }
+            if self.tool == Tool::Fillet {
+                ui.add(
+                    egui::DragValue::new(&mut self.fillet_radius)
+                        .speed(0.1)
+                        .range(0.0..=f32::MAX)
+                        .prefix("R "),
+                );
+            }
+            if self.tool == Tool::Chamfer {
+                for d in [&mut self.chamfer_dists.0, &mut self.chamfer_dists.1] {
+                    ui.add(egui::DragValue::new(d).speed(0.1).range(0.0..=f32::MAX));
+                }
+            }
+            if self.tool == Tool::Measure {
+                match &self.measurement {
+                    Some(m) => ui.label(&m.text),
+                    None => ui.weak("Pick an entity, two entities or two points"),
+                };
+            }
+            if self.tool == Tool::Hatch {
+                egui::ComboBox::from_id_salt("hatch_pattern")
+                    .selected_text(self.hatch_pattern.as_str())
+                    .show_ui(ui, |ui| {
+                        for name in pattern_names() {
+                            ui.selectable_value(&mut self.hatch_pattern, name.to_string(), *name);
+                        }
+                    });
+            }
             ui.separator();
 
             // --- 2D Zoom controls (показываем только в 2D) ---
@@ -142,7 +275,7 @@ impl AppState {
             ui.separator();
 
             // === IFC ===
-            #[cfg(feature = "ifc-ffi")]
+            #[cfg(feature = "ifc")]
             if ui.button("Import IFC").clicked() {
                 if let Some(path) = rfd::FileDialog::new()
                     .add_filter("IFC", &["ifc"])
@@ -159,12 +292,23 @@ impl AppState {
                     }
                 }
             }
-            #[cfg(not(feature = "ifc-ffi"))]
+            #[cfg(feature = "ifc")]
+            if ui.button("Export IFC").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("IFC", &["ifc"])
+                    .save_file()
+                {
+                    if let Err(e) = export_ifc(&self.project3d, &path) {
+                        eprintln!("IFC export error: {e}");
+                    }
+                }
+            }
+            #[cfg(not(feature = "ifc"))]
             {
-                let resp = ui.add_enabled(false, Button::new("Import IFC"));
-                resp.on_hover_text(
-                    "Rebuild with feature `cad-render/ifc-ffi` to enable IFC import.",
-                );
+                let resp = ui.add_enabled(false, egui::Button::new("Import IFC"));
+                resp.on_hover_text("Rebuild with feature `cad-render/ifc` to enable IFC import.");
+                let resp = ui.add_enabled(false, egui::Button::new("Export IFC"));
+                resp.on_hover_text("Rebuild with feature `cad-render/ifc` to enable IFC export.");
             }
 
             ui.separator();
@@ -177,11 +321,11 @@ impl AppState {
                 {
                     match cad_core::dxf_io::import_dxf(path.to_string_lossy().as_ref()) {
                         Ok(doc) => {
-                            self.history.record(&self.doc);
-                            self.doc = doc;
+                            self.history.replace(&mut self.doc, doc, "Import DXF");
                             self.selection.clear();
                             self.select_rect = None;
                             self.tmp_pts.clear();
+                            self.dim_pts.clear();
                             self.zoom_to_fit_all(ui.available_rect_before_wrap());
                         }
                         Err(e) => eprintln!("DXF import error: {e}"),
@@ -225,6 +369,26 @@ impl AppState {
                     }
                 }
             }
+            ui.add(
+                egui::DragValue::new(&mut self.pdf_scale)
+                    .speed(1.0)
+                    .range(1.0..=f32::MAX)
+                    .prefix("1:"),
+            );
+            if ui.button("Export PDF").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("PDF", &["pdf"])
+                    .save_file()
+                {
+                    if let Err(e) = cad_core::export_pdf(
+                        &self.doc,
+                        path.to_string_lossy().as_ref(),
+                        self.pdf_scale,
+                    ) {
+                        eprintln!("PDF export error: {e}");
+                    }
+                }
+            }
         });
     }
 
@@ -280,6 +444,13 @@ impl AppState {
         self.draw_grid(ui, rect);
         self.draw_entities(ui, rect);
         self.selection.draw_overlay(ui, rect, self);
+        if self.tool == Tool::Measure {
+            let hover = response.hover_pos().map(|mp| {
+                let p = self.from_screen(mp, rect);
+                apply_osnap_or_grid(&self.osnap, &self.doc, &self.doc.camera, rect, p)
+            });
+            self.draw_measurement(ui, rect, hover);
+        }
 
         // click
         if response.clicked_by(PointerButton::Primary) {
@@ -318,13 +489,9 @@ impl AppState {
                         let dx = world.x - prev.x;
                         let dy = world.y - prev.y;
                         if dx != 0.0 || dy != 0.0 {
-                            self.history.ensure_drag_backup(&self.doc);
-                            for id in self.selection.ids.iter().copied().collect::<Vec<_>>() {
-                                if let Some(ent) = self.doc.entities.iter_mut().find(|e| e.id == id)
-                                {
-                                    translate_entity(ent, dx, dy);
-                                }
-                            }
+                            self.history.begin(&mut self.doc, "Move");
+                            let ids: Vec<u64> = self.selection.ids.iter().copied().collect();
+                            move_entities(&mut self.doc, &ids, dx, dy);
                         }
                         self.drag_prev_world = Some(world);
                     }
@@ -348,15 +515,17 @@ impl AppState {
             if let Some(sr) = self.select_rect.take() {
                 self.apply_selection_rect(sr, rect);
             }
-            if self.history.has_drag_backup() {
-                self.history.commit_drag(&mut self.doc);
-            }
+            self.history.commit(&mut self.doc);
             self.drag_prev_world = None;
         }
 
         // Esc
         if ui.input(|i| i.key_pressed(Key::Escape)) {
             self.tmp_pts.clear();
+            self.dim_pts.clear();
+            self.corner_first = None;
+            self.measure_first = None;
+            self.measurement = None;
             self.selection.clear();
             self.select_rect = None;
             self.drag_prev_world = None;
@@ -417,18 +586,19 @@ impl AppState {
 
             // Delete
             if i.key_pressed(Key::Delete) && !self.selection.is_empty() {
-                self.history.record(&self.doc);
-                self.doc
-                    .entities
-                    .retain(|e| !self.selection.ids.contains(&e.id));
+                self.history.run(&mut self.doc, "Delete", |doc| {
+                    for &id in &self.selection.ids {
+                        doc.remove_entity(id);
+                    }
+                });
                 self.selection.clear();
             }
             // Duplicate
             if i.modifiers.ctrl && i.key_pressed(Key::D) && !self.selection.is_empty() {
-                self.history.record(&self.doc);
+                self.history.begin(&mut self.doc, "Duplicate");
                 let ids: Vec<u64> = self.selection.ids.iter().copied().collect();
                 for id in ids {
-                    if let Some(ent) = self.doc.entities.iter().find(|e| e.id == id).cloned() {
+                    if let Some(ent) = self.doc.entity(id).cloned() {
                         let mut copy = ent.clone();
                         copy.id = 0;
                         translate_entity(&mut copy, 10.0, 10.0);
@@ -436,21 +606,32 @@ impl AppState {
                         self.selection.add(new_id);
                     }
                 }
+                self.history.commit(&mut self.doc);
             }
-            // Undo / Redo
-            if i.modifiers.ctrl && i.key_pressed(Key::Z) {
-                if let Some(prev) = self.history.undo() {
-                    self.doc = prev;
+            // Explode: вставки блоков и размеры → отдельные сущности
+            if i.modifiers.ctrl && i.key_pressed(Key::E) && !self.selection.is_empty() {
+                let ids: Vec<u64> = self.selection.ids.iter().copied().collect();
+                let inserts: Vec<u64> = ids
+                    .into_iter()
+                    .filter(|&id| self.doc.entity(id).is_some_and(|e| e.kind.is_block_ref()))
+                    .collect();
+                if !inserts.is_empty() {
+                    self.history.begin(&mut self.doc, "Explode");
                     self.selection.clear();
-                    self.select_rect = None;
+                    for id in inserts {
+                        for new_id in self.doc.explode(id) {
+                            self.selection.add(new_id);
+                        }
+                    }
+                    self.history.commit(&mut self.doc);
                 }
             }
+            // Undo / Redo
+            if i.modifiers.ctrl && i.key_pressed(Key::Z) {
+                self.undo();
+            }
             if i.modifiers.ctrl && i.key_pressed(Key::Y) {
-                if let Some(next) = self.history.redo() {
-                    self.doc = next;
-                    self.selection.clear();
-                    self.select_rect = None;
-                }
+                self.redo();
             }
         });
     }
@@ -483,31 +664,25 @@ impl AppState {
             }
             Tool::Line => {
                 if self.tmp_pts.is_empty() {
-                    self.history.record(&self.doc);
                     self.tmp_pts.push(p);
                 } else {
                     let a = self.tmp_pts[0];
-                    make_line(&mut self.doc, a, p, "0");
+                    self.history
+                        .run(&mut self.doc, "Line", |doc| make_line(doc, a, p, "0"));
                     self.tmp_pts.clear();
                 }
             }
             Tool::Arc => {
                 if self.tmp_pts.is_empty() {
-                    self.history.record(&self.doc);
                     self.tmp_pts.push(p);
                 } else {
                     let center = self.tmp_pts[0];
                     let dx = p.x - center.x;
                     let dy = p.y - center.y;
                     let r = (dx * dx + dy * dy).sqrt().max(1.0);
-                    make_arc(
-                        &mut self.doc,
-                        center,
-                        r,
-                        0.0,
-                        std::f32::consts::FRAC_PI_2,
-                        "0",
-                    );
+                    self.history.run(&mut self.doc, "Arc", |doc| {
+                        make_arc(doc, center, r, 0.0, std::f32::consts::FRAC_PI_2, "0")
+                    });
                     self.tmp_pts.clear();
                 }
             }
@@ -516,16 +691,64 @@ impl AppState {
                     let same =
                         (last.x - p.x).abs() < f32::EPSILON && (last.y - p.y).abs() < f32::EPSILON;
                     if same && self.tmp_pts.len() >= 3 {
-                        nurbs_from_polyline(&mut self.doc, &self.tmp_pts, "0")?;
+                        let pts = &self.tmp_pts;
+                        self.history.run(&mut self.doc, "NURBS", |doc| {
+                            nurbs_from_polyline(doc, pts, "0")
+                        })?;
                         self.tmp_pts.clear();
                         return Ok(());
                     }
                 }
-                if self.tmp_pts.is_empty() {
-                    self.history.record(&self.doc);
-                }
                 self.tmp_pts.push(p);
             }
+            Tool::Hatch => {
+                let loops = detect_boundary(&self.doc, p)
+                    .ok_or_else(|| anyhow::anyhow!("No closed boundary around the point"))?;
+                let fill =
+                    HatchFill::pattern(&self.hatch_pattern, 0.0, 1.0).unwrap_or(HatchFill::Solid);
+                self.history.run(&mut self.doc, "Hatch", |doc| {
+                    make_hatch(doc, loops, fill, "0")
+                })?;
+            }
+            // две точки (к объекту — ассоциативно), третий щелчок — положение размерной линии
+            Tool::Dim => {
+                if self.dim_pts.len() < 2 {
+                    let tol = self.osnap.pixel_radius * 1.2;
+                    let linked = self.pick_entity(p, rect, tol).and_then(|id| {
+                        DimPoint::nearest(&self.doc, id, p, tol / self.doc.camera.zoom)
+                    });
+                    self.dim_pts.push(linked.unwrap_or(DimPoint::free(p)));
+                } else {
+                    let pts = std::mem::take(&mut self.dim_pts);
+                    self.history.run(&mut self.doc, "Dimension", |doc| {
+                        make_dimension(doc, DimKind::Aligned, pts, p, "0")
+                    })?;
+                }
+            }
+            // первая кривая, затем вторая (та же полилиния — все её вершины)
+            Tool::Fillet | Tool::Chamfer => {
+                let id = self
+                    .pick_entity(p, rect, self.osnap.pixel_radius * 1.2)
+                    .ok_or_else(|| anyhow::anyhow!("No entity under the cursor"))?;
+                let Some(first) = self.corner_first.take() else {
+                    self.corner_first = Some(id);
+                    self.selection.clear();
+                    self.selection.add(id);
+                    return Ok(());
+                };
+                self.selection.clear();
+                let (radius, (d1, d2)) = (self.fillet_radius, self.chamfer_dists);
+                let fillet_tool = self.tool == Tool::Fillet;
+                let label = if fillet_tool { "Fillet" } else { "Chamfer" };
+                self.history.run(&mut self.doc, label, |doc| {
+                    if fillet_tool {
+                        fillet(doc, first, id, radius)
+                    } else {
+                        chamfer(doc, first, id, d1, d2)
+                    }
+                })?;
+            }
+            Tool::Measure => self.measure_click(p, rect),
             Tool::Pan => {}
         }
         Ok(())
@@ -562,5 +785,8 @@ fn demo_project3d() -> cad_core::model3d::Project3D {
         meta: Default::default(),
     });
 
-    Project3D { models: vec![m] }
+    Project3D {
+        models: vec![m],
+        ..Default::default()
+    }
 }
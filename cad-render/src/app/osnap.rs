@@ -1,4 +1,7 @@
-use cad_core::{sample_entity_nurbs, snap_to_grid, Camera2D, Document, EntityKind, Pt2};
+use cad_core::{
+    ellipse_point, intersect, polyline_segments, sample_entity_nurbs, snap_to_grid, Camera2D,
+    Document, Entity, EntityKind, Pt2,
+};
 use egui;
 
 /// Тип привязки
@@ -7,6 +10,11 @@ pub enum SnapKind {
     End,
     Mid,
     Perp,
+    Center,
+    Quad,
+    Insertion,
+    /// Пересечение двух объектов
+    Int,
 }
 
 /// Состояние OSNAP
@@ -106,17 +114,58 @@ pub fn compute_osnap(
         ]
     };
 
-    for e in &doc.entities {
+    let reach = tol_px / camera.zoom.max(0.01);
+    let near = doc.entities_near(world, reach);
+    for &e in &near {
+        if !doc.is_layer_visible(&e.layer) {
+            continue;
+        }
         match &e.kind {
             EntityKind::LineSeg { a, b } => {
                 for cand in consider_seg(e.id, *a, *b) {
                     update_best(&mut best, cand);
                 }
             }
-            EntityKind::Polyline { pts, .. } => {
-                for w in pts.windows(2) {
-                    for cand in consider_seg(e.id, w[0], w[1]) {
-                        update_best(&mut best, cand);
+            EntityKind::Polyline {
+                pts,
+                closed,
+                bulges,
+            } => {
+                for seg in polyline_segments(pts, bulges, *closed) {
+                    match seg {
+                        EntityKind::Arc {
+                            center,
+                            radius,
+                            start_angle: sa,
+                            end_angle: ea,
+                        } => {
+                            let at = |t: f32| Pt2 {
+                                x: center.x + radius * t.cos(),
+                                y: center.y + radius * t.sin(),
+                            };
+                            let pts = [
+                                (at(sa), SnapKind::End),
+                                (at(ea), SnapKind::End),
+                                (at((sa + ea) * 0.5), SnapKind::Mid),
+                                (center, SnapKind::Center),
+                            ];
+                            for (p, kind) in pts {
+                                update_best(
+                                    &mut best,
+                                    candidate_for_point(camera, rect, e.id, p, kind, world, tol_px),
+                                );
+                            }
+                            let poly = sample_arc_as_polyline(center, radius, sa, ea, 32);
+                            for w in poly.windows(2) {
+                                update_best(&mut best, consider_seg(e.id, w[0], w[1])[3]);
+                            }
+                        }
+                        EntityKind::LineSeg { a, b } => {
+                            for cand in consider_seg(e.id, a, b) {
+                                update_best(&mut best, cand);
+                            }
+                        }
+                        _ => {}
                     }
                 }
             }
@@ -139,6 +188,18 @@ pub fn compute_osnap(
                 for cand in consider_seg(e.id, a, b) {
                     update_best(&mut best, cand);
                 }
+                update_best(
+                    &mut best,
+                    candidate_for_point(
+                        camera,
+                        rect,
+                        e.id,
+                        *center,
+                        SnapKind::Center,
+                        world,
+                        tol_px,
+                    ),
+                );
                 let poly = sample_arc_as_polyline(*center, *radius, sa, ea, 64);
                 for w in poly.windows(2) {
                     for cand in consider_seg(e.id, w[0], w[1]) {
@@ -146,6 +207,96 @@ pub fn compute_osnap(
                     }
                 }
             }
+            EntityKind::Circle { center, radius } => {
+                update_best(
+                    &mut best,
+                    candidate_for_point(
+                        camera,
+                        rect,
+                        e.id,
+                        *center,
+                        SnapKind::Center,
+                        world,
+                        tol_px,
+                    ),
+                );
+                for k in 0..4 {
+                    let a = k as f32 * std::f32::consts::FRAC_PI_2;
+                    let q = Pt2 {
+                        x: center.x + radius * a.cos(),
+                        y: center.y + radius * a.sin(),
+                    };
+                    update_best(
+                        &mut best,
+                        candidate_for_point(camera, rect, e.id, q, SnapKind::Quad, world, tol_px),
+                    );
+                }
+                let poly = e.kind.sample(96);
+                for w in poly.windows(2) {
+                    update_best(&mut best, consider_seg(e.id, w[0], w[1])[3]);
+                }
+            }
+            EntityKind::Ellipse {
+                center,
+                major,
+                ratio,
+                start_param,
+                end_param,
+            } => {
+                update_best(
+                    &mut best,
+                    candidate_for_point(
+                        camera,
+                        rect,
+                        e.id,
+                        *center,
+                        SnapKind::Center,
+                        world,
+                        tol_px,
+                    ),
+                );
+                let (t0, t1) = (start_param.min(*end_param), start_param.max(*end_param));
+                // концы осей — только те, что лежат на дуге
+                for k in -4..8 {
+                    let t = k as f32 * std::f32::consts::FRAC_PI_2;
+                    if t >= t0 && t <= t1 {
+                        let q = ellipse_point(*center, *major, *ratio, t);
+                        update_best(
+                            &mut best,
+                            candidate_for_point(
+                                camera,
+                                rect,
+                                e.id,
+                                q,
+                                SnapKind::Quad,
+                                world,
+                                tol_px,
+                            ),
+                        );
+                    }
+                }
+                if t1 - t0 < std::f32::consts::TAU - 1e-4 {
+                    for t in [t0, t1] {
+                        let q = ellipse_point(*center, *major, *ratio, t);
+                        update_best(
+                            &mut best,
+                            candidate_for_point(
+                                camera,
+                                rect,
+                                e.id,
+                                q,
+                                SnapKind::End,
+                                world,
+                                tol_px,
+                            ),
+                        );
+                    }
+                }
+                let poly = e.kind.sample(96);
+                for w in poly.windows(2) {
+                    update_best(&mut best, consider_seg(e.id, w[0], w[1])[3]);
+                }
+            }
             EntityKind::NurbsCurve2D { .. } => {
                 let poly = sample_entity_nurbs(e, 128).unwrap_or_default();
                 for w in poly.windows(2) {
@@ -154,6 +305,42 @@ pub fn compute_osnap(
                     }
                 }
             }
+            EntityKind::Insert { pos, .. } | EntityKind::Table { pos, .. } => {
+                update_best(
+                    &mut best,
+                    candidate_for_point(
+                        camera,
+                        rect,
+                        e.id,
+                        *pos,
+                        SnapKind::Insertion,
+                        world,
+                        tol_px,
+                    ),
+                );
+                for sub in doc.insert_geometry(e) {
+                    let poly = sub.kind.sample(64);
+                    if let (Some(a), Some(b)) = (poly.first(), poly.last()) {
+                        for p in [*a, *b] {
+                            update_best(
+                                &mut best,
+                                candidate_for_point(
+                                    camera,
+                                    rect,
+                                    e.id,
+                                    p,
+                                    SnapKind::End,
+                                    world,
+                                    tol_px,
+                                ),
+                            );
+                        }
+                    }
+                    for w in poly.windows(2) {
+                        update_best(&mut best, consider_seg(e.id, w[0], w[1])[3]);
+                    }
+                }
+            }
             EntityKind::Text { pos, .. } => {
                 // Снэп к точке вставки текста как к конечной (End)
                 update_best(
@@ -161,12 +348,47 @@ pub fn compute_osnap(
                     candidate_for_point(camera, rect, e.id, *pos, SnapKind::End, world, tol_px),
                 );
             }
+            // к штриховке не привязываемся — как OSNAPHATCH = 0 в AutoCAD
+            EntityKind::Hatch { .. } => {}
+            // точки размера — производные от объектов, привязка к ним только мешает
+            EntityKind::Dimension { .. } => {}
+        }
+    }
+
+    // пересечения — только между кривыми рядом с курсором; точка пересечения
+    // важнее перпендикуляра, который почти всегда ближе к курсору
+    let near: Vec<&Entity> = near
+        .into_iter()
+        .filter(|e| doc.is_layer_visible(&e.layer) && passes_near(&e.kind, world, reach))
+        .collect();
+    let mut int: Option<(u64, Pt2, SnapKind, f32)> = None;
+    for (i, a) in near.iter().enumerate() {
+        for b in &near[i + 1..] {
+            for x in intersect(&a.kind, &b.kind) {
+                update_best(
+                    &mut int,
+                    candidate_for_point(camera, rect, a.id, x.point, SnapKind::Int, world, tol_px),
+                );
+            }
+        }
+    }
+    if let Some(c) = int {
+        if best.is_none_or(|b| b.2 == SnapKind::Perp || c.3 < b.3) {
+            best = Some(c);
         }
     }
 
     best.map(|(id, p, k, _)| (id, p, k))
 }
 
+/// Проходит ли кривая ближе `reach` к точке (по грубой ломаной).
+fn passes_near(kind: &EntityKind, p: Pt2, reach: f32) -> bool {
+    kind.sample(64).windows(2).any(|w| {
+        project_point_to_segment(p, w[0], w[1])
+            .is_some_and(|q| (q.x - p.x).hypot(q.y - p.y) <= reach)
+    })
+}
+
 #[inline]
 fn to_screen(cam: &Camera2D, rect: egui::Rect, w: Pt2) -> egui::Pos2 {
     let z = cam.zoom.max(0.01);
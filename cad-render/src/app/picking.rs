@@ -1,5 +1,5 @@
 use super::AppState;
-use cad_core::{EntityKind, Pt2};
+use cad_core::{flatten_polyline, point_in_polygon, Bbox, EntityKind, Pt2};
 
 impl AppState {
     /// Поиск ближайшей сущности к точке `world` с допуском `tol_px` (в пикселях).
@@ -26,15 +26,24 @@ impl AppState {
             (d <= tol_px).then_some((id, d))
         };
 
-        for e in &self.doc.entities {
+        let reach = tol_px / self.doc.camera.zoom.max(0.01);
+        for e in self.doc.entities_near(world, reach) {
+            if !self.doc.is_layer_visible(&e.layer) {
+                continue;
+            }
             match &e.kind {
                 EntityKind::LineSeg { a, b } => {
                     if let Some(c) = consider_seg(e.id, *a, *b) {
                         update_best(&mut best, c);
                     }
                 }
-                EntityKind::Polyline { pts, .. } => {
-                    for w in pts.windows(2) {
+                EntityKind::Polyline {
+                    pts,
+                    closed,
+                    bulges,
+                } => {
+                    let poly = flatten_polyline(pts, bulges, *closed, 0.05);
+                    for w in poly.windows(2) {
                         if let Some(c) = consider_seg(e.id, w[0], w[1]) {
                             update_best(&mut best, c);
                         }
@@ -54,6 +63,14 @@ impl AppState {
                         }
                     }
                 }
+                EntityKind::Circle { .. } | EntityKind::Ellipse { .. } => {
+                    let poly = e.kind.sample(96);
+                    for w in poly.windows(2) {
+                        if let Some(c) = consider_seg(e.id, w[0], w[1]) {
+                            update_best(&mut best, c);
+                        }
+                    }
+                }
                 EntityKind::NurbsCurve2D { .. } => {
                     let poly = cad_core::sample_entity_nurbs(e, 128).unwrap_or_default();
                     for w in poly.windows(2) {
@@ -62,6 +79,39 @@ impl AppState {
                         }
                     }
                 }
+                EntityKind::Insert { .. }
+                | EntityKind::Dimension { .. }
+                | EntityKind::Table { .. } => {
+                    for poly in self.insert_outlines(e) {
+                        for w in poly.windows(2) {
+                            if let Some(c) = consider_seg(e.id, w[0], w[1]) {
+                                update_best(&mut best, c);
+                            }
+                        }
+                        if let [p] = poly[..] {
+                            if let Some(c) = consider_seg(e.id, p, p) {
+                                update_best(&mut best, c);
+                            }
+                        }
+                    }
+                }
+                // штриховка — по контурам или изнутри (последней в очереди)
+                EntityKind::Hatch { loops, .. } => {
+                    let polys: Vec<Vec<Pt2>> = loops.iter().map(|l| l.polygon()).collect();
+                    for poly in &polys {
+                        for i in 0..poly.len() {
+                            let (a, b) = (poly[i], poly[(i + 1) % poly.len()]);
+                            if let Some(c) = consider_seg(e.id, a, b) {
+                                update_best(&mut best, c);
+                            }
+                        }
+                    }
+                    let inside =
+                        polys.iter().filter(|p| point_in_polygon(world, p)).count() % 2 == 1;
+                    if inside {
+                        update_best(&mut best, (e.id, tol_px));
+                    }
+                }
                 // Пик текста — по точке вставки
                 EntityKind::Text { pos, .. } => {
                     let sp = self.to_screen(*pos, rect);
@@ -81,7 +131,10 @@ impl AppState {
         let (min, max, crossing) = sr.world_bounds();
         self.selection.clear();
 
-        for e in &self.doc.entities {
+        for e in self.doc.entities_in_rect(&Bbox::new(min, max)) {
+            if !self.doc.is_layer_visible(&e.layer) {
+                continue;
+            }
             let hit = match &e.kind {
                 EntityKind::LineSeg { a, b } => {
                     if crossing {
@@ -92,13 +145,18 @@ impl AppState {
                         segment_inside_rect(*a, *b, min, max)
                     }
                 }
-                EntityKind::Polyline { pts, .. } => {
+                EntityKind::Polyline {
+                    pts,
+                    closed,
+                    bulges,
+                } => {
+                    let poly = flatten_polyline(pts, bulges, *closed, 0.05);
                     if crossing {
-                        pts.windows(2)
+                        poly.windows(2)
                             .any(|w| segment_intersects_rect(w[0], w[1], min, max))
-                            || pts.iter().any(|p| rect_contains_point(min, max, *p))
+                            || poly.iter().any(|p| rect_contains_point(min, max, *p))
                     } else {
-                        pts.iter().all(|p| rect_contains_point(min, max, *p))
+                        poly.iter().all(|p| rect_contains_point(min, max, *p))
                     }
                 }
                 EntityKind::Arc {
@@ -117,6 +175,16 @@ impl AppState {
                         poly.iter().all(|p| rect_contains_point(min, max, *p))
                     }
                 }
+                EntityKind::Circle { .. } | EntityKind::Ellipse { .. } => {
+                    let poly = e.kind.sample(96);
+                    if crossing {
+                        poly.windows(2)
+                            .any(|w| segment_intersects_rect(w[0], w[1], min, max))
+                            || poly.iter().any(|p| rect_contains_point(min, max, *p))
+                    } else {
+                        poly.iter().all(|p| rect_contains_point(min, max, *p))
+                    }
+                }
                 EntityKind::NurbsCurve2D { .. } => {
                     let poly = cad_core::sample_entity_nurbs(e, 128).unwrap_or_default();
                     if crossing {
@@ -127,8 +195,41 @@ impl AppState {
                         poly.iter().all(|p| rect_contains_point(min, max, *p))
                     }
                 }
+                EntityKind::Hatch { loops, .. } => {
+                    let polys: Vec<Vec<Pt2>> = loops.iter().map(|l| l.polygon()).collect();
+                    if crossing {
+                        polys.iter().any(|poly| {
+                            poly.windows(2)
+                                .any(|w| segment_intersects_rect(w[0], w[1], min, max))
+                                || poly.iter().any(|p| rect_contains_point(min, max, *p))
+                        })
+                    } else {
+                        polys
+                            .iter()
+                            .flatten()
+                            .all(|p| rect_contains_point(min, max, *p))
+                    }
+                }
                 // Текст попадает, если его точка вставки внутри прямоугольника
                 EntityKind::Text { pos, .. } => rect_contains_point(min, max, *pos),
+                EntityKind::Insert { .. }
+                | EntityKind::Dimension { .. }
+                | EntityKind::Table { .. } => {
+                    let polys = self.insert_outlines(e);
+                    if crossing {
+                        polys.iter().any(|poly| {
+                            poly.windows(2)
+                                .any(|w| segment_intersects_rect(w[0], w[1], min, max))
+                                || poly.iter().any(|p| rect_contains_point(min, max, *p))
+                        })
+                    } else {
+                        !polys.is_empty()
+                            && polys
+                                .iter()
+                                .flatten()
+                                .all(|p| rect_contains_point(min, max, *p))
+                    }
+                }
             };
             if hit {
                 self.selection.add(e.id);
@@ -138,66 +239,22 @@ impl AppState {
 
     /// Экстенты документа (для Fit)
     pub(crate) fn doc_bounds(&self) -> Option<(Pt2, Pt2)> {
-        let mut min = Pt2::new(f32::INFINITY, f32::INFINITY);
-        let mut max = Pt2::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
-        let mut any = false;
-
-        let acc = |p: Pt2, min: &mut Pt2, max: &mut Pt2, any: &mut bool| {
-            if p.x.is_finite() && p.y.is_finite() {
-                if p.x < min.x {
-                    min.x = p.x;
-                }
-                if p.y < min.y {
-                    min.y = p.y;
-                }
-                if p.x > max.x {
-                    max.x = p.x;
-                }
-                if p.y > max.y {
-                    max.y = p.y;
-                }
-                *any = true;
-            }
-        };
-
-        for e in &self.doc.entities {
-            match &e.kind {
-                EntityKind::LineSeg { a, b } => {
-                    acc(*a, &mut min, &mut max, &mut any);
-                    acc(*b, &mut min, &mut max, &mut any);
-                }
-                EntityKind::Polyline { pts, .. } => {
-                    for &p in pts {
-                        acc(p, &mut min, &mut max, &mut any);
-                    }
-                }
-                EntityKind::Arc {
-                    center,
-                    radius,
-                    start_angle,
-                    end_angle,
-                } => {
-                    for p in sample_arc_as_polyline(*center, *radius, *start_angle, *end_angle, 64)
-                    {
-                        acc(p, &mut min, &mut max, &mut any);
-                    }
-                }
-                EntityKind::NurbsCurve2D { .. } => {
-                    for p in cad_core::sample_entity_nurbs(e, 256).unwrap_or_default() {
-                        acc(p, &mut min, &mut max, &mut any);
-                    }
-                }
-                EntityKind::Text { pos, .. } => {
-                    acc(*pos, &mut min, &mut max, &mut any);
-                }
-            }
-        }
+        cad_core::extents(&self.doc, self.doc.entities()).map(|b| (b.min, b.max))
+    }
+}
 
-        if any {
-            Some((min, max))
-        } else {
-            None
-        }
+impl AppState {
+    /// Контуры развёрнутой вставки: ломаная на каждую сущность, текст — одной точкой.
+    fn insert_outlines(&self, e: &cad_core::Entity) -> Vec<Vec<Pt2>> {
+        self.doc
+            .insert_geometry(e)
+            .iter()
+            .map(|sub| match &sub.kind {
+                EntityKind::Text { pos, .. } => vec![*pos],
+                EntityKind::Hatch { loops, .. } => loops.iter().flat_map(|l| l.polygon()).collect(),
+                kind => kind.sample(64),
+            })
+            .collect()
     }
 }
 
@@ -1,6 +1,8 @@
 mod deserialize;
 mod serialize;
 
+use std::ops::Deref;
+
 use bevy_math::{DVec2, DVec3};
 use ifc_rs_verify_derive::IfcVerify;
 
@@ -25,6 +27,14 @@ impl From<DVec2> for Direction2D {
     }
 }
 
+impl Deref for Direction2D {
+    type Target = IfcDVec2;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl IfcType for Direction2D {}
 
 /// The IfcDirection provides a direction in two or three dimensional space depending on the number
@@ -44,4 +54,12 @@ impl From<DVec3> for Direction3D {
     }
 }
 
+impl Deref for Direction3D {
+    type Target = IfcDVec3;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl IfcType for Direction3D {}
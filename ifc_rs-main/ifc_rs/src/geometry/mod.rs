@@ -24,7 +24,9 @@ pub(crate) mod rectangle_profile_def;
 pub(crate) mod representation_context;
 pub(crate) mod representation_subcontext;
 pub(crate) mod shape_representation;
+pub(crate) mod swept_disk_solid;
 pub(crate) mod transform_base;
+pub(crate) mod triangulated_face_set;
 pub(crate) mod uniform_transformations;
 
 pub struct Geometry;
@@ -52,6 +54,10 @@ impl Geometry {
             local_placement::LocalPlacement::parse_any(),
             non_uniform_transformations::CartesianTransformationOperator3DnonUniform::parse_any(),
             uniform_transformations::CartesianTransformationOperator3D::parse_any(),
+            alt((
+                triangulated_face_set::TriangulatedFaceSet::parse_any(),
+                swept_disk_solid::SweptDiskSolid::parse_any(),
+            )),
         ))
     }
 }
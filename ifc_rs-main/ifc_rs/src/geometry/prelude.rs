@@ -24,4 +24,7 @@ pub use super::representation_subcontext::GeometricRepresentationSubContext;
 pub use super::shape_representation::{
     RepresentationIdentifier, RepresentationType, ShapeItemEnum, ShapeRepresentation,
 };
+pub use super::swept_disk_solid::{MappedSweptDiskSolid, SweptDiskSolid};
+pub use super::transform_base::TransformBaseMapping;
+pub use super::triangulated_face_set::TriangulatedFaceSet;
 pub use super::uniform_transformations::{CartesianTransformationOperator3D, TransformMapping};
@@ -185,6 +185,25 @@ impl ProductDefinitionShape {
         ProductDefinitionShape::new().add_representation(shape_repr, ifc)
     }
 
+    pub fn new_triangulated_shape(
+        coords: impl Iterator<Item = impl Into<IfcDVec3>>,
+        triangles: impl Iterator<Item = [usize; 3]>,
+        sub_context: TypedId<GeometricRepresentationSubContext>,
+        ifc: &mut IFC,
+    ) -> Self {
+        let face_set = TriangulatedFaceSet::new(coords, triangles, ifc);
+
+        let shape_repr = ShapeRepresentation::new(
+            sub_context,
+            RepresentationIdentifier::Body,
+            RepresentationType::Tessellation,
+            ifc,
+        )
+        .add_item(face_set, ifc);
+
+        ProductDefinitionShape::new().add_representation(shape_repr, ifc)
+    }
+
     pub fn name(mut self, name: impl Into<StringPrimitive>) -> Self {
         self.name = name.into().into();
         self
@@ -19,6 +19,8 @@ use crate::{
 pub enum ShapeItemEnum<'a> {
     MappedItem(&'a MappedItem),
     ExtrudedAreaSolid(&'a ExtrudedAreaSolid),
+    TriangulatedFaceSet(&'a TriangulatedFaceSet),
+    SweptDiskSolid(&'a SweptDiskSolid),
     Dummy(&'a Dummy),
     Other(&'a dyn IfcType),
 }
@@ -28,6 +30,8 @@ impl<'a> Display for ShapeItemEnum<'a> {
         match self {
             ShapeItemEnum::MappedItem(mapped_item) => write!(f, "{mapped_item}"),
             ShapeItemEnum::ExtrudedAreaSolid(solid) => write!(f, "{solid}"),
+            ShapeItemEnum::TriangulatedFaceSet(face_set) => write!(f, "{face_set}"),
+            ShapeItemEnum::SweptDiskSolid(solid) => write!(f, "{solid}"),
             ShapeItemEnum::Dummy(dummy) => write!(f, "{dummy}"),
             ShapeItemEnum::Other(ifc_type) => write!(f, "{ifc_type}"),
         }
@@ -61,7 +65,7 @@ pub struct ShapeRepresentation {
     /// The supported values for context type are to be specified by implementers agreements.
     pub representation_type: OptionalParameter<RepresentationType>,
     /// Set of geometric representation items that are defined for this representation.
-    #[ifc_types(ExtrudedAreaSolid, PolyLine, MappedItem)]
+    #[ifc_types(ExtrudedAreaSolid, TriangulatedFaceSet, SweptDiskSolid, PolyLine, MappedItem)]
     pub items: IfcList<Id>,
 }
 
@@ -95,6 +99,10 @@ impl ShapeRepresentation {
                 ShapeItemEnum::MappedItem(mapped_item)
             } else if let Some(extruded_area_solid) = item.downcast_ref::<ExtrudedAreaSolid>() {
                 ShapeItemEnum::ExtrudedAreaSolid(extruded_area_solid)
+            } else if let Some(face_set) = item.downcast_ref::<TriangulatedFaceSet>() {
+                ShapeItemEnum::TriangulatedFaceSet(face_set)
+            } else if let Some(solid) = item.downcast_ref::<SweptDiskSolid>() {
+                ShapeItemEnum::SweptDiskSolid(solid)
             } else if let Some(dummy) = item.downcast_ref::<Dummy>() {
                 ShapeItemEnum::Dummy(dummy)
             } else {
@@ -0,0 +1,144 @@
+use crate::id::{Id, IdOr};
+use crate::parser::*;
+use crate::prelude::*;
+use comma::Comma;
+use ifc_rs_verify_derive::IfcVerify;
+use optional::OptionalParameter;
+use real::RealPrimitive;
+
+use std::fmt::Display;
+
+pub struct MappedSweptDiskSolid {
+    pub directrix: Option<Points>,
+    pub radius: f64,
+}
+
+/// The IfcSweptDiskSolid represents the 3D shape by a sweeping representation
+/// scheme allowing a two dimensional circularly bounded plane to move through
+/// space. A typical use is the geometry of reinforcing bars.
+///
+/// https://standards.buildingsmart.org/IFC/DEV/IFC4_2/FINAL/HTML/link/ifcsweptdisksolid.htm
+#[derive(IfcVerify)]
+pub struct SweptDiskSolid {
+    /// The curve used to define the sweeping operation. The solid is generated
+    /// by sweeping a circular disk along the Directrix.
+    #[ifc_types(PolyLine, IndexedPolyCurve)]
+    pub directrix: Id,
+
+    /// The Radius of the circular disk to be swept along the directrix.
+    pub radius: RealPrimitive,
+
+    /// This attribute is optional, if present it defines the radius of a
+    /// circular hole in the centre of the disk.
+    pub inner_radius: OptionalParameter<RealPrimitive>,
+
+    /// The parameter value on the directrix at which the sweeping operation commences.
+    pub start_param: OptionalParameter<RealPrimitive>,
+
+    /// The parameter value on the directrix at which the sweeping operation ends.
+    pub end_param: OptionalParameter<RealPrimitive>,
+}
+
+impl SweptDiskSolid {
+    pub fn new(directrix: impl Into<IdOr<PolyLine>>, radius: f64, ifc: &mut IFC) -> Self {
+        Self {
+            directrix: directrix.into().or_insert(ifc).id(),
+            radius: radius.into(),
+            inner_radius: OptionalParameter::omitted(),
+            start_param: OptionalParameter::omitted(),
+            end_param: OptionalParameter::omitted(),
+        }
+    }
+}
+
+impl<'a> IfcMappedType<'a> for SweptDiskSolid {
+    type Target = MappedSweptDiskSolid;
+
+    fn mappings(&'a self, ifc: &'a IFC) -> Self::Target {
+        let untyped = ifc.data.get_untyped(self.directrix);
+
+        let directrix = if let Some(poly_line) = untyped.downcast_ref::<PolyLine>() {
+            Some(poly_line.points(ifc))
+        } else if let Some(indexed_poly_curve) = untyped.downcast_ref::<IndexedPolyCurve>() {
+            let untyped_points = ifc.data.get_untyped(indexed_poly_curve.points);
+
+            if let Some(list_2d) = untyped_points.downcast_ref::<PointList2D>() {
+                Some(Points::D2(
+                    list_2d.coord_list.0.iter().map(|point| point.0).collect(),
+                ))
+            } else {
+                untyped_points.downcast_ref::<PointList3D>().map(|list_3d| {
+                    Points::D3(list_3d.coord_list.0.iter().map(|point| point.0).collect())
+                })
+            }
+        } else {
+            // e.g. IfcCompositeCurve, which is not supported yet
+            None
+        };
+
+        MappedSweptDiskSolid {
+            directrix,
+            radius: self.radius.0,
+        }
+    }
+}
+
+impl IFCParse for SweptDiskSolid {
+    fn parse<'a>() -> impl IFCParser<'a, Self> {
+        winnow::seq! {
+            SweptDiskSolid {
+                _: p_space_or_comment_surrounded("IFCSWEPTDISKSOLID("),
+
+                directrix: Id::parse(),
+                _: Comma::parse(),
+                radius: RealPrimitive::parse(),
+                _: Comma::parse(),
+                inner_radius: OptionalParameter::parse(),
+                _: Comma::parse(),
+                start_param: OptionalParameter::parse(),
+                _: Comma::parse(),
+                end_param: OptionalParameter::parse(),
+
+                _: p_space_or_comment_surrounded(");"),
+            }
+        }
+    }
+}
+
+impl Display for SweptDiskSolid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IFCSWEPTDISKSOLID({},{},{},{},{});",
+            self.directrix, self.radius, self.inner_radius, self.start_param, self.end_param
+        )
+    }
+}
+
+impl IfcType for SweptDiskSolid {}
+impl ShapeItem for SweptDiskSolid {}
+
+#[cfg(test)]
+mod test {
+    use winnow::Parser;
+
+    use crate::parser::IFCParse;
+
+    use super::SweptDiskSolid;
+
+    #[test]
+    fn swept_disk_solid_round_trip() {
+        let examples = [
+            "IFCSWEPTDISKSOLID(#712,0.006,$,$,$);",
+            "IFCSWEPTDISKSOLID(#45,0.02,0.015,$,$);",
+            "IFCSWEPTDISKSOLID(#45,0.008,$,0.,1.5);",
+        ];
+
+        for (index, example) in examples.into_iter().enumerate() {
+            let solid: SweptDiskSolid = SweptDiskSolid::parse().parse(example).unwrap();
+            let str_solid = solid.to_string();
+
+            assert_eq!(example, str_solid, "example {} failed", index);
+        }
+    }
+}
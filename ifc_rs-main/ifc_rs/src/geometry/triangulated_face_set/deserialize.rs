@@ -0,0 +1,47 @@
+use winnow::{combinator::delimited, Parser};
+
+use crate::{
+    geometry::triangulated_face_set::TriangulatedFaceSet,
+    id::TypedId,
+    parser::{comma::Comma, list::IfcList, optional::OptionalParameter, IFCParse, IFCParser},
+};
+
+impl IFCParse for TriangulatedFaceSet {
+    fn parse<'a>() -> impl IFCParser<'a, Self>
+    where
+        Self: Sized,
+    {
+        delimited(
+            "IFCTRIANGULATEDFACESET(",
+            (
+                TypedId::parse(),
+                Comma::parse(),
+                OptionalParameter::parse(),
+                Comma::parse(),
+                OptionalParameter::parse(),
+                Comma::parse(),
+                IfcList::parse(),
+                Comma::parse(),
+                OptionalParameter::parse(),
+            ),
+            ");",
+        )
+        .map(
+            |(coordinates, _, normals, _, closed, _, coord_index, _, pn_index)| Self {
+                coordinates,
+                normals,
+                closed,
+                coord_index,
+                pn_index,
+            },
+        )
+    }
+}
+
+#[test]
+fn parse_triangulated_face_set_works() {
+    let data = "IFCTRIANGULATEDFACESET(#12,$,.TRUE.,((1,2,3),(1,3,4)),$);";
+    let parsed = TriangulatedFaceSet::parse().parse(data).unwrap();
+    assert_eq!(parsed.triangles(), vec![[0, 1, 2], [0, 2, 3]]);
+    assert_eq!(data, parsed.to_string());
+}
@@ -0,0 +1,107 @@
+mod deserialize;
+mod serialize;
+
+use bevy_math::DVec3;
+use ifc_rs_verify_derive::IfcVerify;
+
+use crate::{
+    id::TypedId,
+    parser::{
+        bool::BoolPrimitive, integer::IntegerPrimitive, list::IfcList, optional::OptionalParameter,
+        real::IfcDVec3,
+    },
+    prelude::*,
+};
+
+/// The IfcTriangulatedFaceSet is a tessellated face set with all faces being bound by triangles.
+/// The faces are constructed by implicit polylines defined by three Cartesian points. Depending on
+/// the value of the attribute Closed the instance of IfcTriangulatedFaceSet represents:
+///
+/// * if TRUE, a boundary representation (or B-rep);
+/// * if FALSE, a face based surface representation;
+/// * if UNKNOWN, it is not known, whether it is a closed B-rep, or an open face based surface.
+///
+/// https://standards.buildingsmart.org/IFC/DEV/IFC4_2/FINAL/HTML/link/ifctriangulatedfaceset.htm
+#[derive(Debug, Clone, IfcVerify)]
+pub struct TriangulatedFaceSet {
+    /// An ordered list of Cartesian points used by the coordinate index defined at the subtypes of
+    /// IfcTessellatedFaceSet.
+    pub coordinates: TypedId<PointList3D>,
+    /// An ordered list of three directions for normals. It is a two-dimensional list of directions
+    /// provided by three parameter values.
+    pub normals: OptionalParameter<IfcList<IfcDVec3>>,
+    /// Indication whether the IfcTriangulatedFaceSet is a closed shell or not.
+    pub closed: OptionalParameter<BoolPrimitive>,
+    /// Two-dimensional list for the indexed-based triangles, where the first dimension represents
+    /// the triangles, and the second dimension holds the three (1-based) indices into the
+    /// coordinate list.
+    pub coord_index: IfcList<IfcList<IntegerPrimitive>>,
+    /// List of indices into the point list which are used instead of the coordinate list when the
+    /// coordinate index refers to a subset of points.
+    pub pn_index: OptionalParameter<IfcList<IntegerPrimitive>>,
+}
+
+impl TriangulatedFaceSet {
+    /// Creates a face set from points and 0-based triangle indices.
+    pub fn new(
+        points: impl Iterator<Item = impl Into<IfcDVec3>>,
+        triangles: impl Iterator<Item = [usize; 3]>,
+        ifc: &mut IFC,
+    ) -> Self {
+        let coordinates = ifc.data.insert_new(PointList3D::new(points));
+
+        Self {
+            coordinates,
+            normals: OptionalParameter::omitted(),
+            closed: OptionalParameter::omitted(),
+            coord_index: IfcList(
+                triangles
+                    .map(|tri| IfcList(tri.map(|i| IntegerPrimitive(i as i64 + 1)).to_vec()))
+                    .collect(),
+            ),
+            pn_index: OptionalParameter::omitted(),
+        }
+    }
+
+    pub fn closed(mut self, closed: bool) -> Self {
+        self.closed = BoolPrimitive::from(closed).into();
+        self
+    }
+
+    /// The points of this face set.
+    pub fn points(&self, ifc: &IFC) -> Vec<DVec3> {
+        ifc.data
+            .get(self.coordinates)
+            .coord_list
+            .iter()
+            .map(|p| p.0)
+            .collect()
+    }
+
+    /// The triangles of this face set as 0-based indices into [`Self::points`]. Triangles with
+    /// invalid indices are skipped.
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        let pn_index: Option<Vec<usize>> = self
+            .pn_index
+            .custom()
+            .map(|list| list.iter().map(|i| i.0 as usize).collect());
+        let resolve = |i: &IntegerPrimitive| -> Option<usize> {
+            let i = usize::try_from(i.0).ok()?.checked_sub(1)?;
+            match &pn_index {
+                Some(pn) => pn.get(i).and_then(|p| p.checked_sub(1)),
+                None => Some(i),
+            }
+        };
+
+        self.coord_index
+            .iter()
+            .filter_map(|tri| match tri.0.as_slice() {
+                [a, b, c] => Some([resolve(a)?, resolve(b)?, resolve(c)?]),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl IfcType for TriangulatedFaceSet {}
+impl ShapeItem for TriangulatedFaceSet {}
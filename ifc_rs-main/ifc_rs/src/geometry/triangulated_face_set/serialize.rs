@@ -0,0 +1,17 @@
+use std::fmt::Display;
+
+use crate::geometry::triangulated_face_set::TriangulatedFaceSet;
+
+impl Display for TriangulatedFaceSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IFCTRIANGULATEDFACESET({coordinates},{normals},{closed},{coord_index},{pn_index});",
+            coordinates = self.coordinates,
+            normals = self.normals,
+            closed = self.closed,
+            coord_index = self.coord_index,
+            pn_index = self.pn_index,
+        )
+    }
+}
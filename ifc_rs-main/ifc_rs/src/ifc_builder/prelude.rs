@@ -4,11 +4,13 @@ pub use super::openings::{
     HorizontalArbitraryOpeningParameter, OpeningParameter,
 };
 pub use super::project::IfcProjectBuilder;
+pub use super::reinforcing_bars::ReinforcingBarParameter;
 pub use super::roofs::HorizontalArbitraryRoofParameter;
 pub use super::shading_devices::VerticalShadingDeviceParameter;
 pub use super::site::IfcSiteBuilder;
 pub use super::slabs::{
-    ArbitrarySlabParameter, HorizontalArbitrarySlabParameter, IfcSlabBuilder, VerticalSlabParameter,
+    ArbitrarySlabParameter, HorizontalArbitrarySlabParameter, IfcSlabBuilder,
+    TriangulatedSlabParameter, VerticalSlabParameter,
 };
 pub use super::spaces::SpaceParameter;
 pub use super::storey::IfcStoreyBuilder;
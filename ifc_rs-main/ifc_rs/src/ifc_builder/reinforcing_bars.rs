@@ -0,0 +1,70 @@
+use bevy_math::DVec3;
+
+use crate::prelude::*;
+
+pub struct ReinforcingBarParameter {
+    /// points of the bar axis relative to `placement`
+    pub coords: Vec<DVec3>,
+    pub diameter: f64,
+    pub placement: DVec3,
+}
+
+impl<'a> IfcStoreyBuilder<'a> {
+    /// Adds a reinforcing bar swept along a polyline and aggregates it into `host`
+    #[must_use]
+    pub fn reinforcing_bar<HOST: IfcType>(
+        &mut self,
+        host: TypedId<HOST>,
+        name: &str,
+        bar_information: ReinforcingBarParameter,
+    ) -> TypedId<ReinforcingBar> {
+        let bar_length = bar_information
+            .coords
+            .windows(2)
+            .map(|w| w[0].distance(w[1]))
+            .sum();
+
+        let directrix = PolyLine::from(
+            bar_information.coords.into_iter().map(Point3D::from),
+            &mut self.project.ifc,
+        );
+        let solid = SweptDiskSolid::new(
+            directrix,
+            bar_information.diameter * 0.5,
+            &mut self.project.ifc,
+        );
+        let shape_repr = ShapeRepresentation::new(
+            self.sub_context,
+            RepresentationIdentifier::Body,
+            RepresentationType::AdvancedSweptSolid,
+            &mut self.project.ifc,
+        )
+        .add_item(solid, &mut self.project.ifc);
+        let product_shape =
+            ProductDefinitionShape::new().add_representation(shape_repr, &mut self.project.ifc);
+
+        let position = Axis3D::new(
+            Point3D::from(bar_information.placement),
+            &mut self.project.ifc,
+        );
+        let local_placement =
+            LocalPlacement::new_relative(position, self.storey, &mut self.project.ifc);
+
+        let bar = ReinforcingBar::new(name)
+            .nominal_diameter(bar_information.diameter)
+            .bar_length(bar_length)
+            .predefined_type(ReinforcingBarTypeEnum::NotDefined)
+            .owner_history(self.owner_history, &mut self.project.ifc)
+            .object_placement(local_placement, &mut self.project.ifc)
+            .representation(product_shape, &mut self.project.ifc);
+
+        let bar_id = self.project.ifc.data.insert_new(bar);
+
+        self.reinforcing_bars_to_host
+            .entry(host.id())
+            .or_default()
+            .push(bar_id);
+
+        bar_id
+    }
+}
@@ -105,6 +105,12 @@ pub struct VerticalSlabParameter {
     pub placement: DVec3,
 }
 
+pub struct TriangulatedSlabParameter {
+    pub coords: Vec<DVec3>,
+    pub triangles: Vec<[usize; 3]>,
+    pub placement: DVec3,
+}
+
 pub struct ArbitrarySlabParameter {
     pub coords: Vec<DVec3>,
     pub direction: DVec3,
@@ -198,6 +204,31 @@ impl<'a> IfcStoreyBuilder<'a> {
         )
     }
 
+    /// Slab with a tessellated body. The thickness of the material layers is not used for the
+    /// geometry.
+    pub fn triangulated_slab<'b>(
+        &'b mut self,
+        material: TypedId<MaterialLayerSetUsage>,
+        slab_type: TypedId<SlabType>,
+        name: &str,
+        slab_information: TriangulatedSlabParameter,
+    ) -> IfcSlabBuilder<'a, 'b> {
+        let product_shape = ProductDefinitionShape::new_triangulated_shape(
+            slab_information.coords.into_iter(),
+            slab_information.triangles.into_iter(),
+            self.sub_context,
+            &mut self.project.ifc,
+        );
+
+        self.slab(
+            slab_information.placement,
+            name,
+            product_shape,
+            material,
+            slab_type,
+        )
+    }
+
     pub fn slab_type(
         &mut self,
         material: TypedId<MaterialLayerSet>,
@@ -41,6 +41,9 @@ pub struct IfcStoreyBuilder<'a> {
     // Shading device relations
     pub(crate) shading_device_type_to_shading_device:
         HashMap<TypedId<ShadingDeviceType>, HashSet<TypedId<ShadingDevice>>>,
+
+    // Reinforcing bar relations (host element -> bars, in insertion order)
+    pub(crate) reinforcing_bars_to_host: HashMap<Id, Vec<TypedId<ReinforcingBar>>>,
 }
 
 impl<'a> IfcStoreyBuilder<'a> {
@@ -86,6 +89,8 @@ impl<'a> IfcStoreyBuilder<'a> {
             space_type_to_space: HashMap::new(),
 
             shading_device_type_to_shading_device: HashMap::new(),
+
+            reinforcing_bars_to_host: HashMap::new(),
         }
     }
 }
@@ -283,6 +288,18 @@ impl<'a> Drop for IfcStoreyBuilder<'a> {
                 spatial_relation.relate_structure(*shading_device, &mut self.project.ifc);
         }
 
+        // reinforcing bars ----------------------
+
+        // bars are parts of their host element, not of the storey
+        for (host, bars) in self.reinforcing_bars_to_host.iter() {
+            let rel_agg = RelAggregates::new(
+                "ElementReinforcingBarsLink",
+                *host,
+                bars.iter().map(|id| id.id()),
+            );
+            self.project.ifc.data.insert_new(rel_agg);
+        }
+
         // insert all spatial relations of this story
         self.project.ifc.data.insert_new(spatial_relation);
 
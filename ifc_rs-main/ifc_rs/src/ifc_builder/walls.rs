@@ -222,7 +222,12 @@ impl<'a> IfcStoreyBuilder<'a> {
     ) -> IfcWallBuilder<'a, 'b> {
         let wall_thickness = self.calculate_material_layer_set_thickness(material);
 
-        let curve_axis_dir = wall_information.direction.cross(DVec3::Z).normalize();
+        // walls extruded along Z have no horizontal axis direction, fall back to X
+        let curve_axis_dir = wall_information
+            .direction
+            .cross(DVec3::Z)
+            .try_normalize()
+            .unwrap_or(DVec3::X);
         let curve_axis_representation = ShapeRepresentation::new(
             self.sub_context,
             RepresentationIdentifier::Axis,
@@ -26,6 +26,9 @@ pub(crate) mod person;
 pub(crate) mod person_and_org;
 pub(crate) mod prelude;
 pub(crate) mod project;
+pub(crate) mod reinforcing_bar;
+pub(crate) mod reinforcing_bar_surface_enum;
+pub(crate) mod reinforcing_bar_type_enum;
 pub(crate) mod roof;
 pub(crate) mod rooftype;
 pub(crate) mod shading_device;
@@ -85,6 +88,7 @@ impl Objects {
                 doortype::DoorType::parse_any(),
                 shading_device::ShadingDevice::parse_any(),
                 shading_device_type::ShadingDeviceType::parse_any(),
+                reinforcing_bar::ReinforcingBar::parse_any(),
             )),
         ))
     }
@@ -16,6 +16,9 @@ pub use super::owner_history::OwnerHistory;
 pub use super::person::Person;
 pub use super::person_and_org::PersonAndOrganization;
 pub use super::project::Project;
+pub use super::reinforcing_bar::ReinforcingBar;
+pub use super::reinforcing_bar_surface_enum::ReinforcingBarSurfaceEnum;
+pub use super::reinforcing_bar_type_enum::ReinforcingBarTypeEnum;
 pub use super::roof::Roof;
 pub use super::rooftype::{type_enum::RoofTypeEnum, RoofType};
 pub use super::shading_device::ShadingDevice;
@@ -0,0 +1,198 @@
+use std::{
+    fmt::Display,
+    ops::{Deref, DerefMut},
+};
+
+use ifc_rs_verify_derive::IfcVerify;
+
+use crate::{
+    parser::{
+        comma::Comma, optional::OptionalParameter, p_space_or_comment_surrounded,
+        real::RealPrimitive, string::StringPrimitive, IFCParse, IFCParser,
+    },
+    prelude::*,
+};
+
+/// A reinforcing bar is usually made of steel with manufactured deformations
+/// in the surface, and used in concrete and masonry construction to provide
+/// additional strength. A single instance of this class may represent one or
+/// many of actual rebars, for example a row of rebars.
+///
+/// Reinforcing bars are usually decomposed from their host element with
+/// IfcRelAggregates.
+///
+/// https://standards.buildingsmart.org/IFC/DEV/IFC4_2/FINAL/HTML/link/ifcreinforcingbar.htm
+#[derive(IfcVerify)]
+pub struct ReinforcingBar {
+    #[inherited]
+    element: Element,
+
+    /// The steel grade of the reinforcing element (deprecated in IFC4).
+    pub steel_grade: OptionalParameter<StringPrimitive>,
+
+    /// The nominal diameter defining the cross-section size of the reinforcing bar.
+    pub nominal_diameter: OptionalParameter<RealPrimitive>,
+
+    /// The effective cross-section area of the reinforcing bar or group of bars.
+    pub cross_section_area: OptionalParameter<RealPrimitive>,
+
+    /// The total length of the reinforcing bar.
+    pub bar_length: OptionalParameter<RealPrimitive>,
+
+    /// Labels the reinforcing bar according to its function.
+    pub predefined_type: OptionalParameter<ReinforcingBarTypeEnum>,
+
+    /// Indicator for whether the bar surface is plain or textured.
+    pub bar_surface: OptionalParameter<ReinforcingBarSurfaceEnum>,
+}
+
+impl ReinforcingBar {
+    pub fn new(name: impl Into<StringPrimitive>) -> Self {
+        Self {
+            element: Element::new(Product::new(Object::new(Root::new(name.into())))),
+
+            steel_grade: OptionalParameter::omitted(),
+            nominal_diameter: OptionalParameter::omitted(),
+            cross_section_area: OptionalParameter::omitted(),
+            bar_length: OptionalParameter::omitted(),
+            predefined_type: OptionalParameter::omitted(),
+            bar_surface: OptionalParameter::omitted(),
+        }
+    }
+
+    pub fn nominal_diameter(mut self, nominal_diameter: f64) -> Self {
+        self.nominal_diameter = RealPrimitive::from(nominal_diameter).into();
+        self
+    }
+
+    pub fn bar_length(mut self, bar_length: f64) -> Self {
+        self.bar_length = RealPrimitive::from(bar_length).into();
+        self
+    }
+
+    pub fn predefined_type(mut self, predefined_type: ReinforcingBarTypeEnum) -> Self {
+        self.predefined_type = predefined_type.into();
+        self
+    }
+}
+
+impl IFCParse for ReinforcingBar {
+    fn parse<'a>() -> impl IFCParser<'a, Self> {
+        winnow::seq! {
+            ReinforcingBar {
+                _: p_space_or_comment_surrounded("IFCREINFORCINGBAR("),
+
+                element: Element::parse(),
+                _: Comma::parse(),
+                steel_grade: OptionalParameter::parse(),
+                _: Comma::parse(),
+                nominal_diameter: OptionalParameter::parse(),
+                _: Comma::parse(),
+                cross_section_area: OptionalParameter::parse(),
+                _: Comma::parse(),
+                bar_length: OptionalParameter::parse(),
+                _: Comma::parse(),
+                predefined_type: OptionalParameter::parse(),
+                _: Comma::parse(),
+                bar_surface: OptionalParameter::parse(),
+
+                _: p_space_or_comment_surrounded(");"),
+            }
+        }
+    }
+}
+
+impl Display for ReinforcingBar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IFCREINFORCINGBAR({},{},{},{},{},{},{});",
+            self.element,
+            self.steel_grade,
+            self.nominal_diameter,
+            self.cross_section_area,
+            self.bar_length,
+            self.predefined_type,
+            self.bar_surface,
+        )
+    }
+}
+
+impl RootBuilder for ReinforcingBar {
+    fn root_mut(&mut self) -> &mut Root {
+        &mut self.element
+    }
+}
+
+impl ObjectBuilder for ReinforcingBar {
+    fn object_mut(&mut self) -> &mut Object {
+        &mut self.element
+    }
+}
+
+impl ProductBuilder for ReinforcingBar {
+    fn product_mut(&mut self) -> &mut Product {
+        &mut self.element
+    }
+}
+
+impl ElementBuilder for ReinforcingBar {
+    fn element_mut(&mut self) -> &mut Element {
+        &mut self.element
+    }
+}
+
+impl Deref for ReinforcingBar {
+    type Target = Element;
+
+    fn deref(&self) -> &Self::Target {
+        &self.element
+    }
+}
+
+impl DerefMut for ReinforcingBar {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.element
+    }
+}
+
+impl IfcType for ReinforcingBar {}
+impl MaterialRelatable for ReinforcingBar {}
+
+#[cfg(test)]
+mod test {
+    use winnow::Parser;
+
+    use super::ReinforcingBar;
+    use crate::{parser::IFCParse, prelude::*};
+
+    #[test]
+    fn reinforcing_bar_round_trip() {
+        let examples = [
+            "IFCREINFORCINGBAR('0DWgwt6o1FOx7466fPk$jl',#2,'Bar',$,$,#33,#25,$,$,0.012,$,2.5,.MAIN.,$);",
+            "IFCREINFORCINGBAR('3mAx1fVd57XP0$bWSRSRqa',#12,'Stirrup',$,$,#40,#41,'12',$,0.008,0.0625,1.25,.LIGATURE.,.TEXTURED.);",
+            "IFCREINFORCINGBAR('1oR2Ar2yX2MwZ_jXlOTz9R',#12,$,$,$,$,$,$,'B500B',$,$,$,$,.PLAIN.);",
+        ];
+
+        for (index, example) in examples.into_iter().enumerate() {
+            let bar: ReinforcingBar = ReinforcingBar::parse().parse(example).unwrap();
+            let bar_str = bar.to_string();
+
+            assert_eq!(example, bar_str, "example {} failed", index);
+        }
+    }
+
+    #[test]
+    fn built_reinforcing_bar_round_trip() {
+        let bar = ReinforcingBar::new("Bar")
+            .nominal_diameter(0.012)
+            .bar_length(2.5)
+            .predefined_type(ReinforcingBarTypeEnum::Main);
+        let bar_str = bar.to_string();
+
+        let parsed: ReinforcingBar = ReinforcingBar::parse().parse(bar_str.as_str()).unwrap();
+
+        assert_eq!(bar_str, parsed.to_string());
+        assert!(bar_str.ends_with(",$,0.012,$,2.5,.MAIN.,$);"));
+    }
+}
@@ -0,0 +1,41 @@
+use std::str::FromStr;
+
+use strum::{Display, EnumString, VariantNames};
+use winnow::combinator::{alt, delimited};
+use winnow::Parser;
+
+use crate::parser::*;
+
+/// This enumeration describes the surface of a reinforcing bar.
+///
+/// https://standards.buildingsmart.org/IFC/DEV/IFC4_2/FINAL/HTML/link/ifcreinforcingbarsurfaceenum.htm
+#[derive(EnumString, VariantNames, Display, Clone, Copy)]
+pub enum ReinforcingBarSurfaceEnum {
+    /// The bar surface is plain.
+    #[strum(to_string = ".PLAIN.")]
+    Plain,
+
+    /// The bar surface is ribbed or otherwise textured.
+    #[strum(to_string = ".TEXTURED.")]
+    Textured,
+}
+
+impl IFCParse for ReinforcingBarSurfaceEnum {
+    fn parse<'a>() -> impl IFCParser<'a, Self> {
+        let variants: [&str; Self::VARIANTS.len()] =
+            Self::VARIANTS.try_into().expect("statically known");
+
+        delimited(
+            p_space_or_comment(),
+            alt(variants
+                .map(|v| {
+                    (
+                        v,
+                        Self::from_str(v).expect("valid ReinforcingBarSurfaceEnum"),
+                    )
+                })
+                .map(|(k, v)| k.map(move |_| v))),
+            p_space_or_comment(),
+        )
+    }
+}
@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use strum::{Display, EnumString, VariantNames};
+use winnow::combinator::{alt, delimited};
+use winnow::Parser;
+
+use crate::parser::*;
+
+/// This enumeration defines the various types of reinforcing bars according
+/// to their function.
+///
+/// https://standards.buildingsmart.org/IFC/DEV/IFC4_2/FINAL/HTML/link/ifcreinforcingbartypeenum.htm
+#[derive(EnumString, VariantNames, Display, Clone, Copy)]
+pub enum ReinforcingBarTypeEnum {
+    /// The bar is used for anchoring, e.g. in a slab edge.
+    #[strum(to_string = ".ANCHORING.")]
+    Anchoring,
+
+    /// The bar is used as edge reinforcement.
+    #[strum(to_string = ".EDGE.")]
+    Edge,
+
+    /// The bar is used as a stirrup or link.
+    #[strum(to_string = ".LIGATURE.")]
+    Ligature,
+
+    /// The bar is the main (longitudinal) reinforcement.
+    #[strum(to_string = ".MAIN.")]
+    Main,
+
+    /// The bar is used as punching shear reinforcement.
+    #[strum(to_string = ".PUNCHING.")]
+    Punching,
+
+    /// The bar is used as ring reinforcement.
+    #[strum(to_string = ".RING.")]
+    Ring,
+
+    /// The bar is used as shear reinforcement.
+    #[strum(to_string = ".SHEAR.")]
+    Shear,
+
+    /// The bar is used as a spacer.
+    #[strum(to_string = ".SPACEBAR.")]
+    SpaceBar,
+
+    /// The bar is used as a stud.
+    #[strum(to_string = ".STUD.")]
+    Stud,
+
+    /// User-defined
+    #[strum(to_string = ".USERDEFINED.")]
+    UserDefined,
+
+    /// Undefined
+    #[strum(to_string = ".NOTDEFINED.")]
+    NotDefined,
+}
+
+impl IFCParse for ReinforcingBarTypeEnum {
+    fn parse<'a>() -> impl IFCParser<'a, Self> {
+        let variants: [&str; Self::VARIANTS.len()] =
+            Self::VARIANTS.try_into().expect("statically known");
+
+        delimited(
+            p_space_or_comment(),
+            alt(variants
+                .map(|v| (v, Self::from_str(v).expect("valid ReinforcingBarTypeEnum")))
+                .map(|(k, v)| k.map(move |_| v))),
+            p_space_or_comment(),
+        )
+    }
+}
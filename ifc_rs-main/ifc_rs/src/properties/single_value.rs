@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Deref};
 
 use comma::Comma;
 use ifc_rs_verify_derive::IfcVerify;
@@ -50,6 +50,14 @@ impl PropertyBuilder for PropertySingleValue {
     }
 }
 
+impl Deref for PropertySingleValue {
+    type Target = PropertyBase;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
 impl IFCParse for PropertySingleValue {
     fn parse<'a>() -> impl IFCParser<'a, Self>
     where
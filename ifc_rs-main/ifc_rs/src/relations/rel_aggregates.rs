@@ -24,14 +24,14 @@ pub struct RelAggregates {
     /// The object definition, either an object type or an object
     /// occurrence, that represents the aggregation. It is the whole
     /// within the whole/part relationship.
-    #[ifc_types(Project, Site, Building, Storey)]
+    #[ifc_types(Project, Site, Building, Storey, Slab, Roof, Wall, Window, Door)]
     pub relating_object: Id,
 
     /// The object definitions, either object occurrences or object
     /// types, that are being aggregated. They are defined as the
     /// parts in the whole/part relationship. No order is implied
     /// between the parts.
-    #[ifc_types(Site, Building, Storey, Space)]
+    #[ifc_types(Site, Building, Storey, Space, ReinforcingBar)]
     pub related_objects: IfcList<Id>,
 }
 
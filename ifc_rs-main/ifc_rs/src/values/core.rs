@@ -18,6 +18,7 @@ pub enum IfcValue {
     Bool(BoolValue),
     Label(LabelValue),
     Real(RealValue),
+    Integer(IntegerValue),
     Identifier(IdentifierValue),
     ThermalTransmittance(ThermalTransmittanceValue),
 }
@@ -31,6 +32,7 @@ impl IFCParse for IfcValue {
             BoolValue::parse().map(Self::Bool),
             LabelValue::parse().map(Self::Label),
             RealValue::parse().map(Self::Real),
+            IntegerValue::parse().map(Self::Integer),
             IdentifierValue::parse().map(Self::Identifier),
             ThermalTransmittanceValue::parse().map(Self::ThermalTransmittance),
         ))
@@ -43,6 +45,7 @@ impl Display for IfcValue {
             IfcValue::Bool(v) => write!(f, "{v}"),
             IfcValue::Label(v) => write!(f, "{v}"),
             IfcValue::Real(v) => write!(f, "{v}"),
+            IfcValue::Integer(v) => write!(f, "{v}"),
             IfcValue::Identifier(v) => write!(f, "{v}"),
             IfcValue::ThermalTransmittance(v) => write!(f, "{v}"),
         }
@@ -95,6 +98,16 @@ mod test {
         assert_eq!(example, str_value);
     }
 
+    #[test]
+    fn ifc_value_integer_round_trip() {
+        let example = "IFCINTEGER(7)";
+
+        let value = IfcValue::parse().parse(example).unwrap();
+        let str_value = value.to_string();
+
+        assert_eq!(example, str_value);
+    }
+
     #[test]
     fn ifc_value_thermal_transmittance_round_trip() {
         let example = "IFCTHERMALTRANSMITTANCEMEASURE(0.24)";
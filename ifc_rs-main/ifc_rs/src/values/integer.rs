@@ -0,0 +1,64 @@
+use std::fmt::Display;
+
+use winnow::{
+    combinator::{alt, delimited},
+    Parser,
+};
+
+use crate::parser::{integer::IntegerPrimitive, *};
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct IntegerValue(pub IntegerPrimitive);
+
+impl IFCParse for IntegerValue {
+    fn parse<'a>() -> impl crate::parser::IFCParser<'a, Self>
+    where
+        Self: Sized,
+    {
+        delimited(
+            (alt(("IFCINTEGER", "IfcInteger")), "("),
+            IntegerPrimitive::parse(),
+            ")",
+        )
+        .map(Self)
+    }
+}
+
+impl Display for IntegerValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IFCINTEGER({})", self.0)
+    }
+}
+
+impl<T: Into<IntegerPrimitive>> From<T> for IntegerValue {
+    fn from(value: T) -> Self {
+        Self(value.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use winnow::Parser;
+
+    use super::*;
+
+    #[test]
+    fn ifc_value_integer_round_trip() {
+        let examples = ["IFCINTEGER(-42)", "IFCINTEGER(0)", "IFCINTEGER(7)"];
+
+        for (index, example) in examples.into_iter().enumerate() {
+            let value = IntegerValue::parse().parse(example).unwrap();
+            let str_value = value.to_string();
+
+            assert_eq!(example, str_value, "example {} failed", index);
+        }
+    }
+
+    #[test]
+    fn ifc_value_integer_mixed_case() {
+        let value = IntegerValue::parse().parse("IfcInteger(12)").unwrap();
+
+        assert_eq!(value, IntegerValue::from(12));
+        assert_eq!("IFCINTEGER(12)", value.to_string());
+    }
+}
@@ -1,6 +1,7 @@
 pub(crate) mod bool;
 pub(crate) mod core;
 pub(crate) mod identifier;
+pub(crate) mod integer;
 pub(crate) mod label;
 pub(crate) mod prelude;
 pub(crate) mod real;
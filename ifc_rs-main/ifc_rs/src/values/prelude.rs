@@ -1,6 +1,7 @@
 pub use super::bool::BoolValue;
 pub use super::core::IfcValue;
 pub use super::identifier::IdentifierValue;
+pub use super::integer::IntegerValue;
 pub use super::label::LabelValue;
 pub use super::real::RealValue;
 pub use super::thermal_transmittance::ThermalTransmittanceValue;